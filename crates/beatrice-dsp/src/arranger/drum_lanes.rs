@@ -7,17 +7,30 @@ use uuid::Uuid;
 use crate::events::EventClass;
 use crate::groove::quantize::QuantizedEvent;
 use crate::groove::grid::{Grid, GridPosition};
+use super::harmony::{Harmony, HarmonyAnalysis, HarmonyMode};
+use super::melody::transcribe_hum;
 use super::templates::{ArrangementTemplate, HihatDensity};
 
 /// General Music MIDI note numbers for drums
 pub const MIDI_KICK: u8 = 36;       // C1
+pub const MIDI_SIDE_STICK: u8 = 37; // C#1
 pub const MIDI_SNARE: u8 = 38;      // D1
 pub const MIDI_CLAP: u8 = 39;       // D#1
 pub const MIDI_CLOSED_HIHAT: u8 = 42; // F#1
 pub const MIDI_OPEN_HIHAT: u8 = 46;  // A#1
+pub const MIDI_CRASH: u8 = 49;      // C#2
+
+/// Bass stabs follow the kick's measured duration (a clipped "b" gives a
+/// short stab, a boomy "bmm" a long one) within this range.
+const MIN_BASS_MS: f64 = 100.0;
+const MAX_BASS_MS: f64 = 600.0;
+
+/// Shortest pad chord: even a clipped hum swells in rather than blipping.
+const MIN_PAD_MS: f64 = 400.0;
 
 /// A drum/instrument lane containing arranged notes
-#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct DrumLane {
     /// Lane name (e.g., "KICK", "SNARE", "HIHAT")
     pub name: String,
@@ -55,7 +68,8 @@ impl DrumLane {
 }
 
 /// An arranged note with timing and MIDI parameters
-#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct ArrangedNote {
     /// Timestamp in milliseconds
     pub timestamp_ms: f64,
@@ -104,7 +118,8 @@ impl ArrangedNote {
 }
 
 /// Complete arrangement with all lanes
-#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct Arrangement {
     /// Drum lanes (kick, snare, hihat, etc.)
     pub drum_lanes: Vec<DrumLane>,
@@ -118,6 +133,18 @@ pub struct Arrangement {
     /// Arpeggiator lane
     pub arp_lane: Option<DrumLane>,
 
+    /// Melody lane (the hummed pitch of HumVoiced events, snapped to the
+    /// theme's scale). `serde(default)` so arrangements saved before pitch
+    /// tracking still load.
+    #[serde(default)]
+    pub melody_lane: Option<DrumLane>,
+
+    /// Key and per-bar chords the bass, pads, arp and melody were voiced from,
+    /// with the reason for each. Only set in `HarmonyMode::FollowHum`; `None`
+    /// means the theme's fixed progression.
+    #[serde(default)]
+    pub harmony: Option<HarmonyAnalysis>,
+
     /// Arrangement metadata
     pub template: ArrangementTemplate,
     pub total_duration_ms: f64,
@@ -146,6 +173,8 @@ impl Arrangement {
             bass_lane: None,
             pad_lane: None,
             arp_lane: None,
+            melody_lane: None,
+            harmony: None,
             template,
             total_duration_ms,
             bar_count,
@@ -173,12 +202,15 @@ impl Arrangement {
         if let Some(ref arp) = self.arp_lane {
             lanes.push(arp);
         }
+        if let Some(ref melody) = self.melody_lane {
+            lanes.push(melody);
+        }
         lanes
     }
 
     /// Expand a base pattern into a full song with 4 sections:
     /// - Intro:  Kick + Hihat only
-    /// - Build:  Kick + Hihat + Snare + Bass
+    /// - Build:  Kick + Hihat + Snare + Bass + Melody
     /// - Drop:   Everything (all lanes)
     /// - Outro:  Bass only, velocity fading out
     ///
@@ -199,6 +231,8 @@ impl Arrangement {
             self.bpm,
             self.sound,
         );
+        // The song repeats the base pattern, so its bars reuse the base analysis.
+        song.harmony = self.harmony.clone();
 
         // Helper: clone a lane's events into a section, offset by section start time
         let clone_lane_to_section = |lane: &DrumLane, section: usize, fade: bool| -> Vec<ArrangedNote> {
@@ -224,7 +258,7 @@ impl Arrangement {
         };
         let is_snare = |name: &str| {
             let n = name.to_uppercase();
-            n.contains("SNARE") || n.contains("CLAP")
+            n.contains("SNARE") || n.contains("CLAP") || n.contains("RIM")
         };
 
         // Expand drum lanes
@@ -295,11 +329,24 @@ impl Arrangement {
             song.arp_lane = Some(expanded);
         }
 
+        // Expand melody lane
+        if let Some(ref base_melody) = self.melody_lane {
+            let mut expanded = DrumLane::new(&base_melody.name, base_melody.midi_note);
+            for section in 0..4 {
+                // The performer's tune enters with the Build and carries the Drop
+                if section == 1 || section == 2 {
+                    expanded.events.extend(clone_lane_to_section(base_melody, section, false));
+                }
+            }
+            expanded.sort_by_time();
+            song.melody_lane = Some(expanded);
+        }
+
         song
     }
 }
 
-use crate::themes::{Theme, scale_notes, chord_notes, bass_notes, arp_notes};
+use crate::themes::{Theme, bass_notes, arp_notes};
 
 /// Arrange events according to template rules and harmonic context
 ///
@@ -331,6 +378,24 @@ pub fn arrange_events(
     theme: &Theme,
     b_emphasis: f32,
     fidelity: f32,
+) -> Arrangement {
+    arrange_events_with_harmony(events, template, grid, theme, b_emphasis, fidelity, HarmonyMode::Theme)
+}
+
+/// [`arrange_events`] with a choice of where the harmony comes from.
+///
+/// `HarmonyMode::FollowHum` infers the key and per-bar chords from the hummed
+/// pitch (see [`Harmony::resolve`]) and voices bass, pads, arp and melody from
+/// them instead of the theme's fixed cycle; the choice is explained in
+/// `Arrangement::harmony`.
+pub fn arrange_events_with_harmony(
+    events: &[QuantizedEvent],
+    template: &ArrangementTemplate,
+    grid: &Grid,
+    theme: &Theme,
+    b_emphasis: f32,
+    fidelity: f32,
+    harmony_mode: HarmonyMode,
 ) -> Arrangement {
     let rules = template.rules();
     let fidelity = fidelity.clamp(0.0, 1.0);
@@ -349,14 +414,19 @@ pub fn arrange_events(
     let mut kick_lane = DrumLane::new("DRUMS_KICK", MIDI_KICK);
     let mut snare_lane = DrumLane::new("DRUMS_SNARE", MIDI_SNARE);
     let mut hihat_lane = DrumLane::new("DRUMS_HIHAT", MIDI_CLOSED_HIHAT);
+    let mut open_hihat_lane = DrumLane::new("DRUMS_OPEN_HIHAT", MIDI_OPEN_HIHAT);
+    let mut rimshot_lane = DrumLane::new("DRUMS_RIMSHOT", MIDI_SIDE_STICK);
+    let mut crash_lane = DrumLane::new("DRUMS_CRASH", MIDI_CRASH);
 
     // Instrument lanes - notes will be resolved per-event from theme
     let mut bass_lane = DrumLane::new("BASS", 36); // Default C2
     let mut pad_lane = DrumLane::new("PADS", 48);  // Default C3
     let mut arp_lane = DrumLane::new("ARP", 60);   // Default C4
+    let mut melody_lane = DrumLane::new("MELODY", 60); // Default C4
 
-    // Pre-calculate scale notes for the theme
-    let scale = scale_notes(theme.root_note, &theme.scale_family);
+    // Resolve the key and chords up front (the theme's, or the hum's)
+    let harmony = Harmony::resolve(events, theme, grid, harmony_mode);
+    let scale = harmony.scale();
 
     // Track arpeggio position for "Rhythmic Puppeteering"
     let mut arp_counter = 0;
@@ -400,8 +470,7 @@ pub fn arrange_events(
                         .clamp(1.0, 127.0) as u8;
 
                     // Harmonic context resolved at the note's actual sounding time.
-                    let chord_type = theme.get_chord_at_time(placed_time, grid);
-                    let current_chord = chord_notes(theme.root_note, &chord_type, &scale);
+                    let current_chord = harmony.chord_notes_at(theme, placed_time, grid);
                     let chord_root = current_chord[0];
                     let placed_pos = grid.get_grid_position(placed_time);
 
@@ -413,7 +482,7 @@ pub fn arrange_events(
 
                     bass_lane.add_note(ArrangedNote::new(
                         placed_time,
-                        200.0, // Standard bass duration
+                        event.original_event.duration_ms.clamp(MIN_BASS_MS, MAX_BASS_MS),
                         bass_velocity,
                         Some(bass_note),
                         Some(event.original_event.id),
@@ -421,8 +490,9 @@ pub fn arrange_events(
                 }
             }
 
-            EventClass::Click => {
-                // T/K sounds -> Snare
+            class @ (EventClass::Click | EventClass::Rimshot) => {
+                // T/K sounds -> Snare; sharp tongue ticks -> Rimshot. Both sit on
+                // the snare template slots.
                 let velocity = calculate_velocity(
                     event.original_event.confidence,
                     event.original_event.features.peak_amplitude,
@@ -431,7 +501,12 @@ pub fn arrange_events(
                 let (placed_time, vscale) =
                     place_event(timestamp, &rules.snare_positions, grid, fidelity);
 
-                snare_lane.add_note(ArrangedNote::new(
+                let lane = if class == EventClass::Rimshot {
+                    &mut rimshot_lane
+                } else {
+                    &mut snare_lane
+                };
+                lane.add_note(ArrangedNote::new(
                     placed_time,
                     event.original_event.duration_ms.min(100.0),
                     (velocity as f32 * vscale) as u8,
@@ -440,8 +515,9 @@ pub fn arrange_events(
                 ));
             }
 
-            EventClass::HihatNoise => {
-                // S/TS sounds -> Hi-hats or Arpeggio triggers
+            class @ (EventClass::HihatNoise | EventClass::OpenHihat) => {
+                // S/TS sounds -> Hi-hats or Arpeggio triggers; a held TSSS is
+                // an open hat and rings for as long as it was voiced.
                 let velocity = calculate_velocity(
                     event.original_event.confidence,
                     event.original_event.features.peak_amplitude,
@@ -459,9 +535,14 @@ pub fn arrange_events(
                     (velocity as f32 * (0.35 + 0.65 * fidelity)) as u8
                 };
 
-                hihat_lane.add_note(ArrangedNote::new(
+                let (lane, max_duration) = if class == EventClass::OpenHihat {
+                    (&mut open_hihat_lane, 400.0)
+                } else {
+                    (&mut hihat_lane, 100.0)
+                };
+                lane.add_note(ArrangedNote::new(
                     placed_time,
-                    event.original_event.duration_ms.min(100.0),
+                    event.original_event.duration_ms.min(max_duration),
                     hat_velocity,
                     None,
                     Some(event.original_event.id),
@@ -470,8 +551,7 @@ pub fn arrange_events(
                 // Rhythmic Puppeteering (Arpeggios): if template is ArpDrive, every
                 // hi-hat advances the arpeggiator and spawns a note at placed_time.
                 if *template == ArrangementTemplate::ArpDrive {
-                    let chord_type = theme.get_chord_at_time(placed_time, grid);
-                    let current_chord = chord_notes(theme.root_note, &chord_type, &scale);
+                    let current_chord = harmony.chord_notes_at(theme, placed_time, grid);
                     let arp_sequence =
                         arp_notes(&current_chord, &theme.arp_pattern, theme.arp_octave_range);
                     if !arp_sequence.is_empty() {
@@ -491,20 +571,45 @@ pub fn arrange_events(
                 }
             }
 
+            EventClass::Crash => {
+                // KSSH sounds -> Crash cymbal. No template slots: a crash lands
+                // where it was performed (usually a section downbeat).
+                let velocity = calculate_velocity(
+                    event.original_event.confidence,
+                    event.original_event.features.peak_amplitude,
+                );
+
+                let (placed_time, _) = place_event(timestamp, &[], grid, fidelity);
+
+                crash_lane.add_note(ArrangedNote::new(
+                    placed_time,
+                    event.original_event.duration_ms.clamp(100.0, 1000.0),
+                    velocity,
+                    None,
+                    Some(event.original_event.id),
+                ));
+            }
+
+            // Breaths, bumps and mic clicks play nothing. The quantizer drops
+            // them unless asked to keep them, and they never reach a lane.
+            EventClass::Noise => {}
+
             EventClass::HumVoiced => {
-                // Voiced sounds -> Pads (Layered triad)
+                // Voiced sounds -> Pads (Layered triad) + the hummed tune on
+                // the melody lane
                 let velocity = calculate_velocity(
                     event.original_event.confidence,
                     event.original_event.features.peak_amplitude,
                 );
 
                 // Pads have no template slots -> identity placement (they move only
-                // via quantization). Single placement drives the whole triad.
+                // via quantization). Single placement drives the whole triad,
+                // held for as long as the hum sounded (its measured duration,
+                // not the gap to the next event).
                 let (placed_time, _) = place_event(timestamp, &[], grid, fidelity);
-                let duration = event.original_event.duration_ms.max(400.0);
+                let duration = event.original_event.duration_ms.max(MIN_PAD_MS);
 
-                let chord_type = theme.get_chord_at_time(placed_time, grid);
-                let current_chord = chord_notes(theme.root_note, &chord_type, &scale);
+                let current_chord = harmony.chord_notes_at(theme, placed_time, grid);
 
                 // Add each note of the chord to the pad lane
                 for &note in &current_chord {
@@ -516,6 +621,18 @@ pub fn arrange_events(
                         Some(event.original_event.id),
                     ));
                 }
+
+                // The tune follows the same placement; notes keep their offsets
+                // from the hum's onset.
+                for note in transcribe_hum(&event.original_event.pitch_contour, scale) {
+                    melody_lane.add_note(ArrangedNote::new(
+                        placed_time + note.offset_ms,
+                        note.duration_ms,
+                        (velocity as f32 * 0.9) as u8,
+                        Some(note.midi_note),
+                        Some(event.original_event.id),
+                    ));
+                }
             }
         }
     }
@@ -526,17 +643,26 @@ pub fn arrange_events(
     merge_same_slot(&mut kick_lane);
     merge_same_slot(&mut snare_lane);
     merge_same_slot(&mut hihat_lane);
+    merge_same_slot(&mut open_hihat_lane);
+    merge_same_slot(&mut rimshot_lane);
+    merge_same_slot(&mut crash_lane);
     merge_same_slot(&mut bass_lane);
     merge_same_slot(&mut pad_lane);
     merge_same_slot(&mut arp_lane);
+    merge_same_slot(&mut melody_lane);
 
     // Add lanes to arrangement
     arrangement.add_drum_lane(kick_lane);
     arrangement.add_drum_lane(snare_lane);
     arrangement.add_drum_lane(hihat_lane);
+    arrangement.add_drum_lane(open_hihat_lane);
+    arrangement.add_drum_lane(rimshot_lane);
+    arrangement.add_drum_lane(crash_lane);
     arrangement.bass_lane = Some(bass_lane);
     arrangement.pad_lane = Some(pad_lane);
     arrangement.arp_lane = Some(arp_lane);
+    arrangement.melody_lane = Some(melody_lane);
+    arrangement.harmony = harmony.analysis().cloned();
 
     arrangement
 }
@@ -1044,6 +1170,120 @@ mod tests {
         assert_eq!(snare_lane.events.len(), 1);
     }
 
+    #[test]
+    fn refined_classes_get_their_own_lanes() {
+        let grid = Grid::new(120.0, TimeSignature::FourFour, GridDivision::Quarter, 1);
+        let template = ArrangementTemplate::SynthwaveStraight;
+        let theme = crate::themes::get_theme("BLADE RUNNER").unwrap();
+
+        let events: Vec<QuantizedEvent> = [
+            (0.0, EventClass::Crash, 0),
+            (500.0, EventClass::Rimshot, 1),
+            (1000.0, EventClass::OpenHihat, 2),
+        ]
+        .into_iter()
+        .map(|(t, class, beat)| {
+            create_quantized_event(
+                create_test_event(t, class),
+                GridPosition { bar: 0, beat, subdivision: 0 },
+            )
+        })
+        .collect();
+
+        let arrangement = arrange_events(&events, &template, &grid, &theme, 0.5, 1.0);
+        for (name, note) in [
+            ("DRUMS_CRASH", MIDI_CRASH),
+            ("DRUMS_RIMSHOT", MIDI_SIDE_STICK),
+            ("DRUMS_OPEN_HIHAT", MIDI_OPEN_HIHAT),
+        ] {
+            let lane = arrangement.drum_lanes.iter().find(|l| l.name == name).unwrap();
+            assert_eq!(lane.midi_note, note);
+            assert_eq!(lane.events.len(), 1, "{name}");
+        }
+        // The core lanes stay empty: a refinement never doubles up.
+        for name in ["DRUMS_SNARE", "DRUMS_HIHAT"] {
+            let lane = arrangement.drum_lanes.iter().find(|l| l.name == name).unwrap();
+            assert!(lane.events.is_empty(), "{name}");
+        }
+    }
+
+    #[test]
+    fn hums_are_transcribed_onto_the_melody_lane() {
+        let grid = Grid::new(120.0, TimeSignature::FourFour, GridDivision::Quarter, 1);
+        let template = ArrangementTemplate::SynthwaveStraight;
+        let theme = crate::themes::get_theme("BLADE RUNNER").unwrap();
+
+        // A hum that wanders from a flat A3 up to C4, and one with no contour.
+        let mut sung = create_test_event(500.0, EventClass::HumVoiced);
+        sung.pitch_contour = (0..60)
+            .map(|i| crate::events::PitchPoint {
+                time_ms: 11.6 + i as f64 * 5.8,
+                f0_hz: if i < 30 { 216.0 } else { 262.0 },
+                confidence: 0.9,
+            })
+            .collect();
+        let silent = create_test_event(1000.0, EventClass::HumVoiced);
+        let events = vec![
+            create_quantized_event(sung, GridPosition { bar: 0, beat: 1, subdivision: 0 }),
+            create_quantized_event(silent, GridPosition { bar: 0, beat: 2, subdivision: 0 }),
+        ];
+
+        let arrangement = arrange_events(&events, &template, &grid, &theme, 0.5, 1.0);
+        let melody = arrangement.melody_lane.as_ref().unwrap();
+        let notes: Vec<(f64, u8)> =
+            melody.events.iter().map(|n| (n.timestamp_ms, n.midi_note.unwrap())).collect();
+        assert_eq!(notes.len(), 2, "{notes:?}");
+        assert_eq!(notes[0], (500.0, 57)); // on the hum's placed time, snapped to A
+        assert_eq!(notes[1].1, 60); // C
+        assert!(arrangement.all_lanes().iter().any(|l| l.name == "MELODY"));
+
+        // Both hums still drive the pads.
+        assert_eq!(arrangement.pad_lane.as_ref().unwrap().events.len(), 6);
+    }
+
+    #[test]
+    fn follow_hum_voices_pads_and_bass_from_the_hummed_chords() {
+        let grid = Grid::new(120.0, TimeSignature::FourFour, GridDivision::Quarter, 2);
+        let template = ArrangementTemplate::SynthwaveStraight;
+        let mut theme = crate::themes::get_theme("BLADE RUNNER").unwrap();
+        theme.root_note = 62; // D natural minor
+        theme.scale_family = crate::themes::ScaleFamily::NaturalMinor;
+
+        // Bar 1 hums Bb-D-F; the kick in that bar carries a bass note.
+        let mut sung = create_test_event(2000.0, EventClass::HumVoiced);
+        sung.pitch_contour = [233.1f32, 293.7, 349.2]
+            .iter()
+            .flat_map(|&f0| std::iter::repeat_n(f0, 30))
+            .enumerate()
+            .map(|(i, f0_hz)| crate::events::PitchPoint { time_ms: i as f64 * 10.0, f0_hz, confidence: 0.9 })
+            .collect();
+        let events = vec![
+            create_quantized_event(sung, GridPosition { bar: 1, beat: 0, subdivision: 0 }),
+            create_quantized_event(
+                create_test_event(3000.0, EventClass::BilabialPlosive),
+                GridPosition { bar: 1, beat: 2, subdivision: 0 },
+            ),
+        ];
+
+        let fixed = arrange_events(&events, &template, &grid, &theme, 0.8, 1.0);
+        assert!(fixed.harmony.is_none());
+
+        let followed = arrange_events_with_harmony(
+            &events, &template, &grid, &theme, 0.8, 1.0, HarmonyMode::FollowHum,
+        );
+        let pads: Vec<u8> =
+            followed.pad_lane.as_ref().unwrap().events.iter().map(|n| n.midi_note.unwrap()).collect();
+        assert_eq!(pads, [70, 74, 77]); // Bb major, not the theme's cycle
+        let bass = &followed.bass_lane.as_ref().unwrap().events;
+        assert_eq!(bass[0].midi_note.unwrap() % 12, 10); // Bb root
+
+        let harmony = followed.harmony.as_ref().unwrap();
+        assert_eq!(harmony.bars.len(), 2);
+        assert_eq!(harmony.bars[1].chord, crate::themes::ChordType::VI);
+        assert!(harmony.bars[1].reason.contains("A#-D-F"), "{}", harmony.bars[1].reason);
+        assert!(followed.expand_to_song().harmony.is_some());
+    }
+
     #[test]
     fn test_b_emphasis_triggers_bass() {
         let grid = Grid::new(120.0, TimeSignature::FourFour, GridDivision::Quarter, 1);
@@ -1068,6 +1308,54 @@ mod tests {
         assert_eq!(arrangement_low.bass_lane.as_ref().unwrap().events.len(), 0);
     }
 
+    #[test]
+    fn bass_and_pads_follow_the_measured_duration_not_the_gap() {
+        let grid = Grid::new(120.0, TimeSignature::FourFour, GridDivision::Quarter, 2);
+        let theme = crate::themes::get_theme("BLADE RUNNER").unwrap();
+
+        // A boomy kick and a clipped one, then a long hum and a short one, each
+        // followed by ~1s of silence before the next event.
+        let sounds = [
+            (0.0, EventClass::BilabialPlosive, 350.0),
+            (1000.0, EventClass::BilabialPlosive, 40.0),
+            (2000.0, EventClass::HumVoiced, 900.0),
+            (3000.0, EventClass::HumVoiced, 150.0),
+        ];
+        let events: Vec<QuantizedEvent> = sounds
+            .iter()
+            .map(|&(t, class, duration)| {
+                let mut event = create_test_event(t, class).with_gap(1000.0);
+                event.duration_ms = duration;
+                create_quantized_event(event, grid.get_grid_position(t))
+            })
+            .collect();
+
+        let arr = arrange_events(&events, &ArrangementTemplate::SynthwaveStraight, &grid, &theme, 0.8, 1.0);
+        let bass: Vec<f64> = arr.bass_lane.as_ref().unwrap().events.iter().map(|n| n.duration_ms).collect();
+        assert_eq!(bass, [350.0, MIN_BASS_MS]);
+        let pads = &arr.pad_lane.as_ref().unwrap().events;
+        assert!(pads.iter().filter(|n| n.timestamp_ms == 2000.0).all(|n| n.duration_ms == 900.0));
+        assert!(pads.iter().filter(|n| n.timestamp_ms == 3000.0).all(|n| n.duration_ms == MIN_PAD_MS));
+    }
+
+    #[test]
+    fn layered_events_land_on_the_same_slot_in_their_own_lanes() {
+        // Multi-label detection: a kick and a hat sharing one onset.
+        let grid = Grid::new(120.0, TimeSignature::FourFour, GridDivision::Sixteenth, 1);
+        let theme = crate::themes::get_theme("BLADE RUNNER").unwrap();
+        let events: Vec<QuantizedEvent> = [EventClass::BilabialPlosive, EventClass::HihatNoise]
+            .into_iter()
+            .map(|class| create_quantized_event(create_test_event(500.0, class), grid.get_grid_position(500.0)))
+            .collect();
+
+        let arr = arrange_events(&events, &ArrangementTemplate::SynthwaveStraight, &grid, &theme, 0.6, 1.0);
+        for name in ["DRUMS_KICK", "DRUMS_HIHAT"] {
+            let lane = arr.drum_lanes.iter().find(|l| l.name == name).unwrap();
+            assert_eq!(lane.events.len(), 1, "{name}");
+            assert_eq!(lane.events[0].timestamp_ms, 500.0, "{name}");
+        }
+    }
+
     #[test]
     fn test_arp_drive_rhythmic_puppeteering() {
         let grid = Grid::new(120.0, TimeSignature::FourFour, GridDivision::Sixteenth, 1);
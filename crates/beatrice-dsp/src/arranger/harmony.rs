@@ -0,0 +1,408 @@
+// Harmony - Chooses the key and per-bar chords the arranger voices
+// Either the theme's fixed progression or one inferred from hummed pitch
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::EventClass;
+use crate::groove::grid::Grid;
+use crate::groove::quantize::QuantizedEvent;
+use crate::themes::{chord_notes, scale_notes, ChordType, ScaleFamily, Theme};
+
+/// Weight of the tonic's own share when scoring candidate keys. Breaks ties
+/// between keys whose scales cover the hum equally well (relative modes) in
+/// favour of the one the performer keeps landing on.
+const TONIC_WEIGHT: f32 = 0.5;
+
+const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+/// Chord types by scale degree, major and minor quality.
+const MAJOR_CHORDS: [ChordType; 7] = [
+    ChordType::I, ChordType::II, ChordType::III, ChordType::IV,
+    ChordType::V, ChordType::VI, ChordType::VII,
+];
+const MINOR_CHORDS: [ChordType; 7] = [
+    ChordType::Im, ChordType::IIm, ChordType::IIIm, ChordType::IVm,
+    ChordType::Vm, ChordType::VIm, ChordType::VIIm,
+];
+
+/// Where the arrangement's harmony comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(rename_all = "snake_case")]
+pub enum HarmonyMode {
+    /// The theme's root and fixed chord progression, cycled bar by bar
+    #[default]
+    Theme,
+
+    /// Key and per-bar chords inferred from the pitch of hummed events.
+    /// Bars without a hum keep the theme progression's chord (in the new key).
+    FollowHum,
+}
+
+/// The chord chosen for one bar, and why
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct BarHarmony {
+    /// Bar index (0-indexed, anchored to the grid's phase offset)
+    pub bar: u32,
+
+    /// Chord by scale degree of the detected key
+    pub chord: ChordType,
+
+    /// MIDI notes of the triad the pads voice
+    pub chord_notes: Vec<u8>,
+
+    /// Share of the bar's hummed pitch that falls on chord tones, 0.0-1.0
+    /// (0.0 when the bar has no hum)
+    pub fit: f32,
+
+    /// Human-readable reason for the choice
+    pub reason: String,
+}
+
+/// Explains the harmony an arrangement was built on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct HarmonyAnalysis {
+    pub mode: HarmonyMode,
+
+    /// Root of the detected key (MIDI note, near the theme's root)
+    pub key_root: u8,
+
+    /// Key name, e.g. "D NaturalMinor"
+    pub key_name: String,
+
+    /// The theme's scale family, transposed to the detected root
+    pub scale_family: ScaleFamily,
+
+    /// Share of all hummed pitch that falls inside the detected scale, 0.0-1.0
+    pub key_fit: f32,
+
+    /// Human-readable reason for the key
+    pub reason: String,
+
+    /// One entry per arrangement bar
+    pub bars: Vec<BarHarmony>,
+}
+
+/// The resolved harmony the arranger voices bass, pads, arps and melody from.
+#[derive(Debug, Clone)]
+pub struct Harmony {
+    root_note: u8,
+    scale: Vec<u8>,
+    /// Chord per bar; empty in `Theme` mode (the theme progression cycles).
+    bar_chords: Vec<ChordType>,
+    analysis: Option<HarmonyAnalysis>,
+}
+
+impl Harmony {
+    /// Resolve the harmony for `events` under `mode`.
+    ///
+    /// `FollowHum` builds a confidence-weighted pitch-class histogram from the
+    /// `pitch_contour` of every `HumVoiced` event, picks the root whose
+    /// `theme.scale_family` scale covers it best (plus a tonic bonus; ties keep
+    /// the theme's root), then for each bar picks the diatonic triad whose tones
+    /// cover that bar's hum best. Degrees without a diatonic fifth are never
+    /// offered. Ties prefer the theme's own chord for the bar.
+    pub fn resolve(events: &[QuantizedEvent], theme: &Theme, grid: &Grid, mode: HarmonyMode) -> Self {
+        if mode == HarmonyMode::Theme {
+            return Harmony {
+                root_note: theme.root_note,
+                scale: scale_notes(theme.root_note, &theme.scale_family),
+                bar_chords: Vec::new(),
+                analysis: None,
+            };
+        }
+
+        let bar_count = grid.bar_count as usize;
+        let mut overall = [0.0f32; 12];
+        let mut per_bar = vec![[0.0f32; 12]; bar_count];
+        for event in events {
+            if event.original_event.class != EventClass::HumVoiced {
+                continue;
+            }
+            for point in &event.original_event.pitch_contour {
+                let pitch_class = (point.midi_note().round() as i32).rem_euclid(12) as usize;
+                overall[pitch_class] += point.confidence;
+                let bar = bar_at(event.quantized_timestamp_ms + point.time_ms, grid) as usize;
+                if let Some(histogram) = per_bar.get_mut(bar) {
+                    histogram[pitch_class] += point.confidence;
+                }
+            }
+        }
+
+        let (root_note, key_fit) = detect_key(&overall, theme);
+        let scale = scale_notes(root_note, &theme.scale_family);
+        let key_name = format!("{} {:?}", NOTE_NAMES[root_note as usize % 12], theme.scale_family);
+        let reason = if key_fit > 0.0 {
+            format!("{:.0}% of the hummed pitch sits in {key_name}", key_fit * 100.0)
+        } else {
+            format!("no hummed pitch; kept the theme's key ({key_name})")
+        };
+
+        let candidates = diatonic_triads(&scale);
+        let ms_per_bar = ms_per_bar(grid);
+        let mut bar_chords = Vec::with_capacity(bar_count);
+        let mut bars = Vec::with_capacity(bar_count);
+        for (bar, histogram) in per_bar.iter().enumerate() {
+            let bar_mid_ms = grid.phase_offset_ms + (bar as f64 + 0.5) * ms_per_bar;
+            let theme_chord = theme.get_chord_at_time(bar_mid_ms, grid);
+            let total: f32 = histogram.iter().sum();
+
+            let (chord, fit, reason) = match candidates
+                .iter()
+                .map(|&(degree, chord)| {
+                    let tones = chord_notes(root_note, &chord, &scale);
+                    let on_tones: f32 = pitch_classes(&tones).map(|pc| histogram[pc]).sum();
+                    let on_root = histogram[tones[0] as usize % 12];
+                    (degree, chord, on_tones / total.max(f32::EPSILON), on_root)
+                })
+                .max_by(|a, b| {
+                    a.2.total_cmp(&b.2)
+                        .then((a.1 == theme_chord).cmp(&(b.1 == theme_chord)))
+                        .then(a.3.total_cmp(&b.3))
+                        .then(b.0.cmp(&a.0))
+                }) {
+                Some((_, chord, fit, _)) if total > 0.0 => {
+                    let names = note_names(&chord_notes(root_note, &chord, &scale));
+                    (chord, fit, format!("{:.0}% of the hummed pitch is on {names}", fit * 100.0))
+                }
+                _ => (theme_chord, 0.0, "no hum in this bar; theme progression".to_string()),
+            };
+
+            bar_chords.push(chord);
+            bars.push(BarHarmony {
+                bar: bar as u32,
+                chord,
+                chord_notes: chord_notes(root_note, &chord, &scale),
+                fit,
+                reason,
+            });
+        }
+
+        Harmony {
+            root_note,
+            scale,
+            bar_chords,
+            analysis: Some(HarmonyAnalysis {
+                mode,
+                key_root: root_note,
+                key_name,
+                scale_family: theme.scale_family,
+                key_fit,
+                reason,
+                bars,
+            }),
+        }
+    }
+
+    /// Root note of the key in use
+    pub fn root_note(&self) -> u8 {
+        self.root_note
+    }
+
+    /// Scale notes of the key in use (as `scale_notes`)
+    pub fn scale(&self) -> &[u8] {
+        &self.scale
+    }
+
+    /// The active chord at a timestamp. Bars past the planned ones (and every
+    /// bar in `Theme` mode) follow the theme's progression.
+    pub fn chord_at_time(&self, theme: &Theme, timestamp_ms: f64, grid: &Grid) -> ChordType {
+        match self.bar_chords.get(bar_at(timestamp_ms, grid) as usize) {
+            Some(&chord) => chord,
+            None => theme.get_chord_at_time(timestamp_ms, grid),
+        }
+    }
+
+    /// Triad notes of the active chord at a timestamp
+    pub fn chord_notes_at(&self, theme: &Theme, timestamp_ms: f64, grid: &Grid) -> Vec<u8> {
+        let chord = self.chord_at_time(theme, timestamp_ms, grid);
+        chord_notes(self.root_note, &chord, &self.scale)
+    }
+
+    /// Explanation of the chosen key and chords (`None` in `Theme` mode)
+    pub fn analysis(&self) -> Option<&HarmonyAnalysis> {
+        self.analysis.as_ref()
+    }
+}
+
+fn ms_per_bar(grid: &Grid) -> f64 {
+    60_000.0 / grid.bpm * grid.time_signature.beats_per_bar() as f64
+}
+
+/// Bar index of a timestamp, anchored to the grid's phase offset exactly like
+/// `Theme::get_chord_at_time`.
+fn bar_at(timestamp_ms: f64, grid: &Grid) -> u32 {
+    ((timestamp_ms - grid.phase_offset_ms).max(0.0) / ms_per_bar(grid)).floor() as u32
+}
+
+fn pitch_classes(notes: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    notes.iter().map(|&n| n as usize % 12)
+}
+
+fn note_names(notes: &[u8]) -> String {
+    pitch_classes(notes).map(|pc| NOTE_NAMES[pc]).collect::<Vec<_>>().join("-")
+}
+
+/// Pick the key root for a pitch-class histogram. Returns the root as the
+/// MIDI note nearest `theme.root_note` with that pitch class (ties go lower)
+/// and the share of the histogram inside its scale. An empty histogram keeps
+/// the theme's root.
+fn detect_key(histogram: &[f32; 12], theme: &Theme) -> (u8, f32) {
+    let total: f32 = histogram.iter().sum();
+    let theme_pc = theme.root_note as usize % 12;
+    if total <= 0.0 {
+        return (theme.root_note, 0.0);
+    }
+
+    let in_scale = |pc: usize| -> f32 {
+        pitch_classes(&scale_notes(pc as u8, &theme.scale_family)).map(|p| histogram[p]).sum::<f32>() / total
+    };
+    let best_pc = (0..12)
+        .max_by(|&a, &b| {
+            let score = |pc: usize| in_scale(pc) + TONIC_WEIGHT * histogram[pc] / total;
+            score(a).total_cmp(&score(b)).then((a == theme_pc).cmp(&(b == theme_pc)))
+        })
+        .unwrap_or(theme_pc);
+
+    let mut shift = (best_pc as i32 - theme_pc as i32).rem_euclid(12);
+    if shift >= 6 {
+        shift -= 12;
+    }
+    let root = (theme.root_note as i32 + shift).clamp(0, 115) as u8;
+    (root, in_scale(best_pc))
+}
+
+/// Diatonic triads of `scale` as `(degree, chord)`: minor when the minor third
+/// is in the scale, otherwise major. Degrees whose fifth leaves the scale
+/// (diminished, or the gaps of a pentatonic) are skipped.
+fn diatonic_triads(scale: &[u8]) -> Vec<(usize, ChordType)> {
+    let in_scale = |note: u8| scale.iter().any(|&s| (s as i32 - note as i32).rem_euclid(12) == 0);
+    scale
+        .iter()
+        .take(7)
+        .enumerate()
+        .filter(|&(_, &root)| in_scale(root + 7))
+        .map(|(degree, &root)| {
+            let chord = if in_scale(root + 3) { MINOR_CHORDS[degree] } else { MAJOR_CHORDS[degree] };
+            (degree, chord)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{Event, EventFeatures, PitchPoint};
+    use crate::groove::grid::{GridDivision, GridPosition, TimeSignature};
+
+    fn hz(midi: f32) -> f32 {
+        440.0 * 2f32.powf((midi - 69.0) / 12.0)
+    }
+
+    /// A hum at `timestamp_ms` singing each `(duration_ms, midi pitch)` in turn.
+    fn hum(timestamp_ms: f64, segments: &[(f64, f32)]) -> QuantizedEvent {
+        let mut event =
+            Event::new(timestamp_ms, 50.0, EventClass::HumVoiced, 0.9, EventFeatures::zero());
+        let mut t = 0.0;
+        for &(duration, pitch) in segments {
+            let end = t + duration;
+            while t < end {
+                event.pitch_contour.push(PitchPoint { time_ms: t, f0_hz: hz(pitch), confidence: 0.9 });
+                t += 10.0;
+            }
+        }
+        QuantizedEvent {
+            original_timestamp_ms: timestamp_ms,
+            quantized_timestamp_ms: timestamp_ms,
+            snap_delta_ms: 0.0,
+            grid_position: GridPosition { bar: 0, beat: 0, subdivision: 0 },
+            original_event: event,
+        }
+    }
+
+    #[test]
+    fn theme_mode_keeps_the_fixed_progression() {
+        let grid = Grid::new(120.0, TimeSignature::FourFour, GridDivision::Quarter, 4);
+        let theme = crate::themes::get_theme("BLADE RUNNER").unwrap();
+        let events = vec![hum(0.0, &[(1000.0, 64.0)])];
+        let harmony = Harmony::resolve(&events, &theme, &grid, HarmonyMode::Theme);
+        assert_eq!(harmony.root_note(), theme.root_note);
+        assert!(harmony.analysis().is_none());
+        for t in [0.0, 2500.0, 4500.0, 7000.0] {
+            assert_eq!(harmony.chord_at_time(&theme, t, &grid), theme.get_chord_at_time(t, &grid));
+        }
+    }
+
+    #[test]
+    fn detects_the_key_from_the_hum() {
+        let grid = Grid::new(120.0, TimeSignature::FourFour, GridDivision::Quarter, 2);
+        let mut theme = crate::themes::get_theme("BLADE RUNNER").unwrap();
+        theme.root_note = 62; // D
+        theme.scale_family = ScaleFamily::NaturalMinor;
+        // An E minor tune: E G B A G F# E, resting on E.
+        let events = vec![hum(
+            0.0,
+            &[(400.0, 64.0), (200.0, 67.0), (200.0, 71.0), (200.0, 69.0), (200.0, 67.0), (200.0, 66.0), (600.0, 64.0)],
+        )];
+        let harmony = Harmony::resolve(&events, &theme, &grid, HarmonyMode::FollowHum);
+        let analysis = harmony.analysis().unwrap();
+        assert_eq!(harmony.root_note(), 64, "{analysis:?}");
+        assert_eq!(analysis.key_name, "E NaturalMinor");
+        assert!((analysis.key_fit - 1.0).abs() < 1e-6);
+        assert_eq!(harmony.scale(), scale_notes(64, &ScaleFamily::NaturalMinor).as_slice());
+    }
+
+    #[test]
+    fn picks_the_triad_each_bar_sings() {
+        let grid = Grid::new(120.0, TimeSignature::FourFour, GridDivision::Quarter, 3);
+        let mut theme = crate::themes::get_theme("BLADE RUNNER").unwrap();
+        theme.root_note = 62;
+        theme.scale_family = ScaleFamily::NaturalMinor;
+        // Bar 0 outlines D minor, bar 1 Bb major; bar 2 is left empty.
+        let events = vec![
+            hum(0.0, &[(500.0, 62.0), (500.0, 65.0), (500.0, 69.0), (400.0, 62.0)]),
+            hum(2000.0, &[(500.0, 58.0), (500.0, 62.0), (500.0, 65.0), (400.0, 58.0)]),
+        ];
+        let harmony = Harmony::resolve(&events, &theme, &grid, HarmonyMode::FollowHum);
+        let analysis = harmony.analysis().unwrap();
+        assert_eq!(harmony.root_note(), 62, "{analysis:?}");
+        assert_eq!(analysis.bars.len(), 3);
+
+        assert_eq!(analysis.bars[0].chord, ChordType::Im);
+        assert_eq!(analysis.bars[0].chord_notes, vec![62, 65, 69]);
+        assert!((analysis.bars[0].fit - 1.0).abs() < 1e-6);
+        assert_eq!(analysis.bars[1].chord, ChordType::VI); // Bb major
+        assert_eq!(harmony.chord_notes_at(&theme, 3000.0, &grid), vec![70, 74, 77]);
+
+        // The empty bar falls back to the theme's chord for that bar.
+        assert_eq!(analysis.bars[2].chord, theme.get_chord_at_time(5000.0, &grid));
+        assert_eq!(analysis.bars[2].fit, 0.0);
+        assert!(analysis.bars[2].reason.contains("theme progression"));
+    }
+
+    #[test]
+    fn no_hum_keeps_the_theme_key() {
+        let grid = Grid::new(120.0, TimeSignature::FourFour, GridDivision::Quarter, 2);
+        let theme = crate::themes::get_theme("STRANGER THINGS").unwrap();
+        let harmony = Harmony::resolve(&[], &theme, &grid, HarmonyMode::FollowHum);
+        assert_eq!(harmony.root_note(), theme.root_note);
+        let analysis = harmony.analysis().unwrap();
+        assert_eq!(analysis.key_fit, 0.0);
+        for bar in &analysis.bars {
+            assert_eq!(bar.chord, theme.get_chord_at_time(2000.0 * bar.bar as f64 + 1000.0, &grid));
+        }
+    }
+
+    #[test]
+    fn diatonic_triads_skip_degrees_without_a_fifth() {
+        // D natural minor: Dm, (E dim skipped), F, Gm, Am, Bb, C
+        let chords: Vec<ChordType> =
+            diatonic_triads(&scale_notes(62, &ScaleFamily::NaturalMinor)).into_iter().map(|(_, c)| c).collect();
+        assert_eq!(
+            chords,
+            [ChordType::Im, ChordType::III, ChordType::IVm, ChordType::Vm, ChordType::VI, ChordType::VII]
+        );
+    }
+}
@@ -0,0 +1,176 @@
+// Melody - Transcribes hummed pitch contours into scale-snapped notes
+// Feeds the arrangement's MELODY lane from `HumVoiced` events
+
+use crate::events::PitchPoint;
+
+/// Runs of one snapped pitch shorter than this are treated as scoops or
+/// tracking flicker and folded into the neighbouring note.
+const MIN_MELODY_NOTE_MS: f64 = 60.0;
+
+/// An unvoiced stretch longer than this between contour frames ends a note.
+const MAX_MELODY_GAP_MS: f64 = 50.0;
+
+/// One transcribed note, timed from the hum's onset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HumNote {
+    /// Start in milliseconds after the hum's onset
+    pub offset_ms: f64,
+
+    /// Duration in milliseconds
+    pub duration_ms: f64,
+
+    /// MIDI note number, on the scale
+    pub midi_note: u8,
+}
+
+/// Snap a fractional MIDI pitch to the nearest note whose pitch class is in
+/// `scale` (any octave). Ties go to the lower note; an empty scale snaps to
+/// the nearest semitone.
+pub fn snap_to_scale(pitch: f32, scale: &[u8]) -> u8 {
+    let pitch = pitch.clamp(0.0, 127.0);
+    let nearest = pitch.round() as i32;
+    (nearest - 6..=nearest + 6)
+        .filter(|n| (0..=127).contains(n))
+        .filter(|&n| scale.is_empty() || scale.iter().any(|&s| (s as i32 - n).rem_euclid(12) == 0))
+        .min_by(|&a, &b| (a as f32 - pitch).abs().total_cmp(&(b as f32 - pitch).abs()).then(a.cmp(&b)))
+        .unwrap_or(nearest) as u8
+}
+
+/// Transcribe a hum's pitch contour into notes of `scale` (the theme's
+/// `scale_notes`).
+///
+/// Every frame is snapped to the scale; consecutive frames on the same note
+/// form one note, runs shorter than [`MIN_MELODY_NOTE_MS`] are absorbed by the
+/// note before them (or after, at the start), and unvoiced gaps longer than
+/// [`MAX_MELODY_GAP_MS`] split notes. The first note starts at the onset when
+/// the contour does, so it lands where the hum was placed.
+pub fn transcribe_hum(contour: &[PitchPoint], scale: &[u8]) -> Vec<HumNote> {
+    if contour.is_empty() {
+        return Vec::new();
+    }
+    // Frame spacing: the smallest step between frames.
+    let step = contour
+        .windows(2)
+        .map(|w| w[1].time_ms - w[0].time_ms)
+        .filter(|&d| d > 0.0)
+        .fold(f64::INFINITY, f64::min);
+    let step = if step.is_finite() { step } else { 0.0 };
+
+    // Runs of consecutive frames on one snapped note: (start, end, note).
+    let mut runs: Vec<(f64, f64, u8)> = Vec::new();
+    let mut previous_time: Option<f64> = None;
+    for point in contour {
+        let note = snap_to_scale(point.midi_note(), scale);
+        let end = point.time_ms + step;
+        match runs.last_mut() {
+            Some(run)
+                if run.2 == note
+                    && previous_time.is_some_and(|t| point.time_ms - t <= MAX_MELODY_GAP_MS) =>
+            {
+                run.1 = end;
+            }
+            _ => runs.push((point.time_ms, end, note)),
+        }
+        previous_time = Some(point.time_ms);
+    }
+
+    // Fold short runs into the run they touch (the one before when there is one).
+    let mut notes: Vec<(f64, f64, u8)> = Vec::new();
+    let mut pending: Option<(f64, f64)> = None;
+    for (start, end, note) in runs {
+        let joins_previous = notes.last().is_some_and(|last| start - last.1 <= MAX_MELODY_GAP_MS);
+        if end - start < MIN_MELODY_NOTE_MS {
+            match notes.last_mut() {
+                Some(last) if joins_previous => last.1 = end,
+                _ => pending = Some(pending.map_or((start, end), |(s, _)| (s, end))),
+            }
+            continue;
+        }
+        let start = match pending.take() {
+            Some((s, e)) if start - e <= MAX_MELODY_GAP_MS => s,
+            _ => start,
+        };
+        match notes.last_mut() {
+            Some(last) if last.2 == note && joins_previous => last.1 = end,
+            _ => notes.push((start, end, note)),
+        }
+    }
+
+    if let Some(first) = notes.first_mut() {
+        if first.0 <= MAX_MELODY_GAP_MS {
+            first.0 = 0.0;
+        }
+    }
+    notes
+        .into_iter()
+        .map(|(start, end, midi_note)| HumNote { offset_ms: start, duration_ms: end - start, midi_note })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::themes::{scale_notes, ScaleFamily};
+
+    fn hz(midi: f32) -> f32 {
+        440.0 * 2f32.powf((midi - 69.0) / 12.0)
+    }
+
+    /// A contour at ~5.8 ms frames from `(duration_ms, midi pitch)` segments.
+    fn contour(segments: &[(f64, f32)]) -> Vec<PitchPoint> {
+        let step = 256.0 * 1000.0 / 44_100.0;
+        let mut points = Vec::new();
+        let mut t = 11.6;
+        for &(duration, pitch) in segments {
+            let end = t + duration;
+            while t < end {
+                if pitch > 0.0 {
+                    points.push(PitchPoint { time_ms: t, f0_hz: hz(pitch), confidence: 0.9 });
+                }
+                t += step;
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn snaps_to_the_nearest_scale_note() {
+        // D natural minor: D E F G A Bb C
+        let scale = scale_notes(62, &ScaleFamily::NaturalMinor);
+        assert_eq!(snap_to_scale(62.2, &scale), 62); // D
+        assert_eq!(snap_to_scale(53.8, &scale), 53); // a flat F# -> F, an octave down
+        assert_eq!(snap_to_scale(66.0, &scale), 65); // F# ties F/G -> F
+        assert_eq!(snap_to_scale(70.9, &scale), 70); // Bb
+        assert_eq!(snap_to_scale(61.4, &[]), 61);
+    }
+
+    #[test]
+    fn steady_hum_is_one_note_from_the_onset() {
+        let scale = scale_notes(62, &ScaleFamily::NaturalMinor);
+        let notes = transcribe_hum(&contour(&[(500.0, 57.1)]), &scale);
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].midi_note, 57); // A
+        assert_eq!(notes[0].offset_ms, 0.0);
+        assert!((notes[0].duration_ms - 511.6).abs() < 10.0);
+    }
+
+    #[test]
+    fn steps_split_and_flicker_is_absorbed() {
+        let scale = scale_notes(62, &ScaleFamily::NaturalMinor);
+        // A, a 20 ms scoop through Bb, then C; then a rest and D.
+        let notes = transcribe_hum(
+            &contour(&[(200.0, 57.0), (20.0, 58.0), (200.0, 60.0), (100.0, 0.0), (150.0, 62.0)]),
+            &scale,
+        );
+        let pitches: Vec<u8> = notes.iter().map(|n| n.midi_note).collect();
+        assert_eq!(pitches, [57, 60, 62]);
+        assert!(notes.windows(2).all(|w| w[1].offset_ms >= w[0].offset_ms + w[0].duration_ms - 1e-9));
+        // The rest survives as a gap before the last note.
+        assert!(notes[2].offset_ms - (notes[1].offset_ms + notes[1].duration_ms) > 90.0);
+    }
+
+    #[test]
+    fn empty_contour_transcribes_to_nothing() {
+        assert!(transcribe_hum(&[], &[62]).is_empty());
+    }
+}
@@ -3,8 +3,12 @@
 
 use serde::{Deserialize, Serialize};
 use midly::{Smf, Header, Track, TrackEvent, TrackEventKind, MetaMessage, MidiMessage, Timing};
+use crate::events::EventClass;
 use crate::groove::grid::Grid;
-use super::drum_lanes::{Arrangement, ArrangedNote, DrumLane};
+use super::drum_lanes::{
+    Arrangement, ArrangedNote, DrumLane, MIDI_CLOSED_HIHAT, MIDI_CRASH, MIDI_KICK,
+    MIDI_OPEN_HIHAT, MIDI_SIDE_STICK, MIDI_SNARE,
+};
 
 /// MIDI export options
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -115,7 +119,7 @@ fn create_lane_track<'a>(
     let mut events: Vec<(u32, TrackEventKind)> = Vec::new();
 
     // Route this lane to the correct GM channel (spec §3.2): drums stay on 9,
-    // melodic lanes (bass/pads/arp/other) get their own channels so a DAW plays
+    // melodic lanes (bass/pads/arp/melody/other) get their own channels so a DAW plays
     // them as pitched instruments rather than percussion.
     let channel = channel_for_lane(&lane.name);
 
@@ -187,14 +191,32 @@ fn create_lane_track<'a>(
 /// Drums stay on channel 9 (GM percussion, 0-indexed). Melodic lanes get
 /// dedicated channels so a DAW instantiates a pitched instrument for them
 /// instead of treating them as percussion:
-/// BASS=0, PADS=1, ARP=2, any other melodic lane=3.
+/// BASS=0, PADS=1, ARP=2, MELODY=3, any other melodic lane=4.
 fn channel_for_lane(name: &str) -> u8 {
     match name {
         n if n.to_uppercase().starts_with("DRUMS") => 9,
         "BASS" => 0,
         "PADS" => 1,
         "ARP" => 2,
-        _ => 3,
+        "MELODY" => 3,
+        _ => 4,
+    }
+}
+
+/// General MIDI percussion key (channel 10) for an event class's drum lane.
+///
+/// HumVoiced drives the pitched pad lane and Noise is never arranged, so
+/// neither has a drum key.
+pub fn gm_drum_note(class: EventClass) -> Option<u8> {
+    match class {
+        EventClass::BilabialPlosive => Some(MIDI_KICK),
+        EventClass::HihatNoise => Some(MIDI_CLOSED_HIHAT),
+        EventClass::Click => Some(MIDI_SNARE),
+        EventClass::HumVoiced => None,
+        EventClass::OpenHihat => Some(MIDI_OPEN_HIHAT),
+        EventClass::Rimshot => Some(MIDI_SIDE_STICK),
+        EventClass::Crash => Some(MIDI_CRASH),
+        EventClass::Noise => None,
     }
 }
 
@@ -315,7 +337,7 @@ fn calculate_end_tick(lane: &DrumLane, ticks_per_ms: f64) -> u32 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::arranger::drum_lanes::{DrumLane, ArrangedNote, MIDI_KICK, MIDI_SNARE};
+    use crate::arranger::drum_lanes::{DrumLane, ArrangedNote};
     use crate::groove::grid::{Grid, TimeSignature, GridDivision};
     use crate::arranger::templates::ArrangementTemplate;
 
@@ -520,6 +542,60 @@ mod tests {
         assert!(channels.iter().any(|(n, c)| n == "BASS" && *c == 0));
     }
 
+    #[test]
+    fn hummed_melody_gets_its_own_channel_at_the_sung_pitch() {
+        let grid = test_grid();
+        let theme = crate::themes::get_theme("BLADE RUNNER").unwrap();
+        let mut hum = crate::events::Event::new(
+            0.0,
+            500.0,
+            EventClass::HumVoiced,
+            0.9,
+            crate::events::EventFeatures::zero(),
+        );
+        // A steady A3 (MIDI 57, in D minor).
+        hum.pitch_contour = (0..80)
+            .map(|i| crate::events::PitchPoint {
+                time_ms: 11.6 + i as f64 * 5.8,
+                f0_hz: 220.0,
+                confidence: 0.9,
+            })
+            .collect();
+        let quantized = crate::groove::quantize::QuantizedEvent {
+            original_timestamp_ms: 0.0,
+            quantized_timestamp_ms: 0.0,
+            snap_delta_ms: 0.0,
+            grid_position: crate::groove::grid::GridPosition { bar: 0, beat: 0, subdivision: 0 },
+            original_event: hum,
+        };
+        let arr = crate::arranger::drum_lanes::arrange_events(
+            &[quantized],
+            &ArrangementTemplate::SynthwaveStraight,
+            &grid,
+            &theme,
+            0.5,
+            1.0,
+        );
+        let bytes = export_midi(&arr, &grid, &MidiExportOptions::default()).unwrap();
+        let smf = Smf::parse(&bytes).unwrap();
+        let channels = collect_note_on_channels(&smf);
+        assert!(channels.contains(&("MELODY".to_string(), 3)));
+        let melody_keys: Vec<u8> = smf
+            .tracks
+            .iter()
+            .flat_map(|track| track.iter())
+            .filter_map(|ev| match ev.kind {
+                TrackEventKind::Midi { channel, message: MidiMessage::NoteOn { key, vel } }
+                    if channel.as_int() == 3 && vel.as_int() > 0 =>
+                {
+                    Some(key.as_int())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(melody_keys, [57]);
+    }
+
     #[test]
     fn overlapping_same_pitch_notes_are_trimmed() {
         let mut lane = DrumLane::new("PADS", 48);
@@ -536,8 +612,40 @@ mod tests {
         assert_eq!(channel_for_lane("BASS"), 0);
         assert_eq!(channel_for_lane("PADS"), 1);
         assert_eq!(channel_for_lane("ARP"), 2);
+        assert_eq!(channel_for_lane("MELODY"), 3);
         // Anything else is generic melodic
-        assert_eq!(channel_for_lane("LEAD"), 3);
+        assert_eq!(channel_for_lane("LEAD"), 4);
+    }
+
+    #[test]
+    fn gm_drum_notes_follow_the_gm_percussion_map() {
+        assert_eq!(gm_drum_note(EventClass::BilabialPlosive), Some(36)); // Bass Drum 1
+        assert_eq!(gm_drum_note(EventClass::Rimshot), Some(37)); // Side Stick
+        assert_eq!(gm_drum_note(EventClass::Click), Some(38)); // Acoustic Snare
+        assert_eq!(gm_drum_note(EventClass::HihatNoise), Some(42)); // Closed Hi-Hat
+        assert_eq!(gm_drum_note(EventClass::OpenHihat), Some(46)); // Open Hi-Hat
+        assert_eq!(gm_drum_note(EventClass::Crash), Some(49)); // Crash Cymbal 1
+        assert_eq!(gm_drum_note(EventClass::HumVoiced), None);
+        assert_eq!(gm_drum_note(EventClass::Noise), None);
+    }
+
+    #[test]
+    fn arranged_drum_lanes_use_the_gm_key_of_their_class() {
+        let grid = test_grid();
+        let theme = crate::themes::get_theme("BLADE RUNNER").unwrap();
+        let arr = crate::arranger::drum_lanes::arrange_events(
+            &[],
+            &ArrangementTemplate::SynthwaveStraight,
+            &grid,
+            &theme,
+            0.5,
+            1.0,
+        );
+        let mut drum_notes: Vec<u8> = arr.drum_lanes.iter().map(|l| l.midi_note).collect();
+        let mut gm_notes: Vec<u8> = EventClass::ALL.iter().filter_map(|&c| gm_drum_note(c)).collect();
+        drum_notes.sort_unstable();
+        gm_notes.sort_unstable();
+        assert_eq!(drum_notes, gm_notes);
     }
 
     #[test]
@@ -4,10 +4,14 @@
 pub mod templates;
 pub mod drum_lanes;
 pub mod phrase;
+pub mod melody;
+pub mod harmony;
 pub mod midi;
 
 // Re-export main types
 pub use templates::{ArrangementTemplate, TemplateRules, HihatDensity, BassRhythm};
-pub use drum_lanes::{DrumLane, ArrangedNote, Arrangement, arrange_events};
+pub use drum_lanes::{DrumLane, ArrangedNote, Arrangement, arrange_events, arrange_events_with_harmony};
 pub use phrase::{Phrase, PhraseType, PhraseStructure};
-pub use midi::{MidiExportOptions, export_midi};
+pub use melody::{HumNote, snap_to_scale, transcribe_hum};
+pub use harmony::{BarHarmony, Harmony, HarmonyAnalysis, HarmonyMode};
+pub use midi::{MidiExportOptions, export_midi, gm_drum_note};
@@ -5,7 +5,8 @@ use serde::{Deserialize, Serialize};
 use crate::groove::grid::GridPosition;
 
 /// Arrangement template defines the overall musical style
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 #[serde(rename_all = "snake_case")]
 pub enum ArrangementTemplate {
     /// Synthwave Straight - kick on 1/3, snare on 2/4, 8th hats
@@ -84,7 +85,8 @@ impl ArrangementTemplate {
 }
 
 /// Template rules define specific arrangement parameters
-#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct TemplateRules {
     /// Where kick drum hits should occur (positions within a bar pattern)
     pub kick_positions: Vec<GridPosition>,
@@ -103,7 +105,8 @@ pub struct TemplateRules {
 }
 
 /// Hi-hat density levels
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 #[serde(rename_all = "snake_case")]
 pub enum HihatDensity {
     /// Sparse - occasional hi-hats
@@ -128,7 +131,8 @@ impl HihatDensity {
 }
 
 /// Bass rhythm patterns
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 #[serde(rename_all = "snake_case")]
 pub enum BassRhythm {
     /// Whole notes - one note per bar
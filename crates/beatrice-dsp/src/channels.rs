@@ -0,0 +1,245 @@
+// Channel strategy
+//
+// How multi-channel audio becomes the signal(s) onset detection and feature
+// extraction run on. Averaging (the historical `AudioData::to_mono`) cancels
+// out-of-phase stereo mics and blurs a dry mic into a room mic, so the
+// pipeline can instead pick one channel, pick the loudest, split mid/side, or
+// analyse every channel as its own lane and merge the events.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::features::Onset;
+
+/// How the offline pipeline reduces multi-channel audio before analysis
+/// (see [`crate::OnsetConfig::channels`]). Mono input is analysed as-is
+/// whatever the strategy.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelStrategy {
+    /// Average all channels (the historical mixdown).
+    #[default]
+    Average,
+    /// Analyse one channel (0-based; clamped to the last channel).
+    Channel(u16),
+    /// Analyse the channel with the most energy over the whole recording.
+    MaxEnergy,
+    /// Analyse mid `(L+R)/2` and side `(L-R)/2` as two lanes and merge the
+    /// events — side keeps what out-of-phase mics cancel in the mid.
+    MidSide,
+    /// Analyse every channel as its own lane and merge the events.
+    PerChannel,
+}
+
+impl fmt::Display for ChannelStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChannelStrategy::Average => write!(f, "average"),
+            ChannelStrategy::Channel(n) => write!(f, "channel:{n}"),
+            ChannelStrategy::MaxEnergy => write!(f, "max-energy"),
+            ChannelStrategy::MidSide => write!(f, "mid-side"),
+            ChannelStrategy::PerChannel => write!(f, "per-channel"),
+        }
+    }
+}
+
+impl FromStr for ChannelStrategy {
+    type Err = String;
+
+    /// Parse the [`fmt::Display`] form (`average`, `channel:N`, `max-energy`,
+    /// `mid-side`, `per-channel`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "average" => Ok(ChannelStrategy::Average),
+            "max-energy" => Ok(ChannelStrategy::MaxEnergy),
+            "mid-side" => Ok(ChannelStrategy::MidSide),
+            "per-channel" => Ok(ChannelStrategy::PerChannel),
+            _ => s
+                .strip_prefix("channel:")
+                .and_then(|n| n.parse().ok())
+                .map(ChannelStrategy::Channel)
+                .ok_or_else(|| {
+                    format!(
+                        "unknown channel strategy '{s}' (expected average, channel:N, max-energy, mid-side or per-channel)"
+                    )
+                }),
+        }
+    }
+}
+
+impl ChannelStrategy {
+    /// Whether [`lanes`](Self::lanes) needs per-channel energies (an extra
+    /// pass over the audio).
+    pub(crate) fn needs_energies(self) -> bool {
+        self == ChannelStrategy::MaxEnergy
+    }
+
+    /// The mono signals to analyse for `channels`-channel audio.
+    /// `energies` (sum of squares per channel) is required for
+    /// [`ChannelStrategy::MaxEnergy`].
+    pub(crate) fn lanes(self, channels: u16, energies: Option<&[f64]>) -> Vec<Lane> {
+        let channels = channels.max(1) as usize;
+        if channels == 1 {
+            return vec![Lane::Channel(0)];
+        }
+        match self {
+            ChannelStrategy::Average => vec![Lane::Average],
+            ChannelStrategy::Channel(n) => vec![Lane::Channel((n as usize).min(channels - 1))],
+            ChannelStrategy::MaxEnergy => {
+                let loudest = energies
+                    .unwrap_or(&[])
+                    .iter()
+                    .enumerate()
+                    .max_by(|a, b| a.1.total_cmp(b.1))
+                    .map_or(0, |(ch, _)| ch);
+                vec![Lane::Channel(loudest)]
+            }
+            ChannelStrategy::MidSide => vec![Lane::Mid, Lane::Side],
+            ChannelStrategy::PerChannel => (0..channels).map(Lane::Channel).collect(),
+        }
+    }
+}
+
+/// One mono signal derived from interleaved frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Lane {
+    Average,
+    Channel(usize),
+    /// `(ch0 + ch1) / 2`
+    Mid,
+    /// `(ch0 - ch1) / 2`
+    Side,
+}
+
+impl Lane {
+    /// Derive this lane from interleaved `frames` into `mono` (cleared first).
+    pub(crate) fn mix_into(self, frames: &[f32], channels: u16, mono: &mut Vec<f32>) {
+        mono.clear();
+        let channels = channels.max(1) as usize;
+        if channels == 1 {
+            mono.extend_from_slice(frames);
+            return;
+        }
+        let frames = frames.chunks_exact(channels);
+        match self {
+            // Same summation order as `AudioData::to_mono`, so the default
+            // strategy is bit-identical to the historical mixdown.
+            Lane::Average => mono.extend(frames.map(|frame| {
+                let mut sum = 0.0;
+                for &s in frame {
+                    sum += s;
+                }
+                sum / channels as f32
+            })),
+            Lane::Channel(ch) => mono.extend(frames.map(|frame| frame[ch])),
+            Lane::Mid => mono.extend(frames.map(|frame| (frame[0] + frame[1]) / 2.0)),
+            Lane::Side => mono.extend(frames.map(|frame| (frame[0] - frame[1]) / 2.0)),
+        }
+    }
+}
+
+/// Add each channel's sum of squares over interleaved `frames` to `energies`.
+pub(crate) fn accumulate_energies(frames: &[f32], channels: u16, energies: &mut [f64]) {
+    for frame in frames.chunks_exact(channels.max(1) as usize) {
+        for (energy, &s) in energies.iter_mut().zip(frame) {
+            *energy += (s as f64) * (s as f64);
+        }
+    }
+}
+
+/// Union per-lane onsets: where lanes report the same hit (closer than
+/// `min_gap_ms`), keep the strongest. Returns each kept onset with the index
+/// of the lane it came from, in time order.
+pub(crate) fn merge_onsets(lanes: Vec<Vec<Onset>>, min_gap_ms: f64) -> Vec<(usize, Onset)> {
+    let mut all: Vec<(usize, Onset)> = lanes
+        .into_iter()
+        .enumerate()
+        .flat_map(|(lane, onsets)| onsets.into_iter().map(move |o| (lane, o)))
+        .collect();
+    all.sort_by(|a, b| a.1.timestamp_ms.total_cmp(&b.1.timestamp_ms));
+    let mut merged: Vec<(usize, Onset)> = Vec::with_capacity(all.len());
+    for (lane, onset) in all {
+        match merged.last_mut() {
+            Some(last) if onset.timestamp_ms - last.1.timestamp_ms < min_gap_ms => {
+                if onset.strength > last.1.strength {
+                    *last = (lane, onset);
+                }
+            }
+            _ => merged.push((lane, onset)),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_round_trips() {
+        for strategy in [
+            ChannelStrategy::Average,
+            ChannelStrategy::Channel(3),
+            ChannelStrategy::MaxEnergy,
+            ChannelStrategy::MidSide,
+            ChannelStrategy::PerChannel,
+        ] {
+            assert_eq!(strategy.to_string().parse::<ChannelStrategy>(), Ok(strategy));
+        }
+        assert!("left".parse::<ChannelStrategy>().is_err());
+        assert!("channel:x".parse::<ChannelStrategy>().is_err());
+    }
+
+    #[test]
+    fn lanes_collapse_for_mono_and_clamp_channel() {
+        for strategy in [ChannelStrategy::PerChannel, ChannelStrategy::MidSide, ChannelStrategy::Channel(5)] {
+            assert_eq!(strategy.lanes(1, None), vec![Lane::Channel(0)]);
+        }
+        assert_eq!(ChannelStrategy::Channel(5).lanes(2, None), vec![Lane::Channel(1)]);
+        assert_eq!(
+            ChannelStrategy::MaxEnergy.lanes(3, Some(&[1.0, 9.0, 4.0])),
+            vec![Lane::Channel(1)]
+        );
+    }
+
+    #[test]
+    fn average_lane_matches_to_mono() {
+        let samples: Vec<f32> = (0..600).map(|i| (i as f32 * 0.37).sin()).collect();
+        let audio = crate::AudioData {
+            samples,
+            sample_rate: 44_100,
+            channels: 3,
+            bit_depth: 32,
+            duration_ms: 4,
+            frame_count: 200,
+        };
+        let mut mono = Vec::new();
+        Lane::Average.mix_into(&audio.samples, audio.channels, &mut mono);
+        assert_eq!(mono, audio.to_mono());
+    }
+
+    #[test]
+    fn side_recovers_out_of_phase_signal() {
+        let frames = [0.5, -0.5, 0.25, -0.25];
+        let mut mono = Vec::new();
+        Lane::Average.mix_into(&frames, 2, &mut mono);
+        assert_eq!(mono, vec![0.0, 0.0]);
+        Lane::Side.mix_into(&frames, 2, &mut mono);
+        assert_eq!(mono, vec![0.5, 0.25]);
+    }
+
+    #[test]
+    fn merge_keeps_strongest_duplicate() {
+        let onset = |t: f64, strength: f32| Onset { timestamp_ms: t, strength };
+        let merged = merge_onsets(
+            vec![vec![onset(0.0, 0.2), onset(500.0, 0.9)], vec![onset(10.0, 0.8), onset(1000.0, 0.5)]],
+            120.0,
+        );
+        let got: Vec<(usize, f64, f32)> =
+            merged.iter().map(|(lane, o)| (*lane, o.timestamp_ms, o.strength)).collect();
+        assert_eq!(got, vec![(1, 10.0, 0.8), (0, 500.0, 0.9), (1, 1000.0, 0.5)]);
+    }
+}
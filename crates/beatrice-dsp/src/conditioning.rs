@@ -0,0 +1,1194 @@
+// Signal conditioning
+//
+// Laptop mics hand the detector DC offset, mains hum, fan noise and levels
+// that swing 20 dB between takes, and `OnsetConfig::min_flux_threshold` was
+// the only defense. This is an optional chain run on each analysed mono lane
+// before onset detection and feature extraction:
+//
+//   DC blocker → high-pass → spectral-subtraction denoiser → gain
+//
+// Every stage is causal, so one `Conditioner` serves both the streaming
+// detector and the offline passes. The denoiser's STFT holds back
+// `Conditioner::latency` samples, but (like `Resampler`) output sample `k` is
+// always input sample `k`, so onset timestamps are unaffected.
+//
+// Normalization needs the recording's level. Offline, it is measured up front
+// (`condition` for a whole buffer; `analyze_source_hybrid` spends one extra
+// read for a chunked source) and applied as a fixed gain. Live, there is no
+// future to measure, so `Conditioner::new` tracks the level as it arrives and
+// glides the gain toward the target.
+
+use std::sync::Arc;
+
+use realfft::num_complex::Complex;
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+use serde::{Deserialize, Serialize};
+
+use crate::noise::NoiseProfile;
+
+/// Options for the conditioning chain (see [`crate::OnsetConfig::conditioning`]
+/// and [`crate::StreamingConfig::conditioning`]). The default is the chain
+/// tuned for laptop mics; each stage can be switched off on its own.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConditioningConfig {
+    /// Remove DC offset (one-pole high-pass at 10 Hz).
+    pub dc_block: bool,
+    /// Cutoff (Hz) of a second-order Butterworth high-pass for rumble and
+    /// handling noise; `None` disables it.
+    pub highpass_hz: Option<f32>,
+    /// Spectral-subtraction noise reduction against a noise floor learned
+    /// from the quiet stretches of the signal; `None` disables it.
+    pub noise_reduction: Option<NoiseReduction>,
+    /// Level normalization; `None` leaves the level alone.
+    pub normalize: Option<Normalization>,
+}
+
+impl Default for ConditioningConfig {
+    fn default() -> Self {
+        ConditioningConfig {
+            dc_block: true,
+            // Below the lowest bilabial-kick fundamental (~50 Hz).
+            highpass_hz: Some(40.0),
+            noise_reduction: Some(NoiseReduction::default()),
+            normalize: Some(Normalization::Loudness { target_lufs: DEFAULT_TARGET_LUFS }),
+        }
+    }
+}
+
+/// Loudness the default chain normalizes to — about where a close-miked
+/// beatbox take lands, so the default leaves well-recorded takes nearly alone.
+pub const DEFAULT_TARGET_LUFS: f32 = -20.0;
+
+/// Spectral-subtraction settings.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NoiseReduction {
+    /// Multiple of the noise floor's power subtracted from each bin. Values
+    /// above 1 also clear the noise's random peaks.
+    pub over_subtraction: f32,
+    /// Smallest gain a bin is left with (0.1 = -20 dB): a floor instead of
+    /// zero keeps the residual noise smooth rather than chirpy.
+    pub spectral_floor: f32,
+    /// Noise gate: mute the output while its peak envelope stays below the
+    /// noise floor's RMS plus this many dB (10 dB clears the noise's own
+    /// peaks); `None` disables the gate. Subtraction alone leaves a residual
+    /// that decides the zero-crossing rate of every decaying tail — enough
+    /// to turn a kick into a hi-hat.
+    pub gate_db: Option<f32>,
+}
+
+impl Default for NoiseReduction {
+    fn default() -> Self {
+        NoiseReduction { over_subtraction: 2.0, spectral_floor: 0.1, gate_db: Some(10.0) }
+    }
+}
+
+/// What level normalization aims for.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Normalization {
+    /// Scale the sample peak to `target_dbfs`.
+    Peak { target_dbfs: f32 },
+    /// Scale the gated integrated loudness (ITU-R BS.1770) to `target_lufs`.
+    Loudness { target_lufs: f32 },
+}
+
+/// Most a quiet recording is amplified by normalization: beyond this it is
+/// mostly noise, and boosting it further only feeds the detector hiss.
+const MAX_GAIN_DB: f32 = 24.0;
+
+/// Cutoff of the DC blocker.
+const DC_CUTOFF_HZ: f64 = 10.0;
+
+/// Time constant of the live normalization gain's glide toward its target.
+const GAIN_GLIDE_SECONDS: f64 = 0.2;
+
+/// Release of the gate's peak envelope: how long a tail may dip below the
+/// threshold before the gate starts to close.
+const GATE_RELEASE_SECONDS: f64 = 0.02;
+/// Time constant of the gate closing (it opens instantly).
+const GATE_CLOSE_SECONDS: f64 = 0.005;
+/// Gate gain (-80 dB) below which the gate snaps fully shut.
+const GATE_SILENT: f32 = 1e-4;
+
+/// Causal conditioning chain for one mono signal.
+///
+/// Feed chunks of any size to [`process`](Self::process) and call
+/// [`flush`](Self::flush) once at end of stream to drain the denoiser's
+/// lookahead. With a fixed gain ([`with_gain`](Self::with_gain)) chunking does
+/// not change the output.
+pub struct Conditioner {
+    sample_rate: u32,
+    dc: Option<DcBlocker>,
+    highpass: Option<Biquad>,
+    denoiser: Option<Denoiser>,
+    gain: Gain,
+}
+
+enum Gain {
+    Fixed(f32),
+    /// Live normalization: measure what has arrived, glide toward its gain.
+    Adaptive { target: Normalization, meter: Box<LevelMeter>, current: f32, glide: f32 },
+}
+
+impl Conditioner {
+    /// A chain for live input: normalization (if enabled) adapts to the level
+    /// measured so far.
+    pub fn new(sample_rate: u32, cfg: &ConditioningConfig) -> Self {
+        let gain = match cfg.normalize {
+            Some(target) => Gain::Adaptive {
+                target,
+                meter: Box::new(LevelMeter::new(sample_rate)),
+                current: 1.0,
+                glide: (1.0 - (-1.0 / (GAIN_GLIDE_SECONDS * sample_rate.max(1) as f64)).exp()) as f32,
+            },
+            None => Gain::Fixed(1.0),
+        };
+        Self::build(sample_rate, cfg, gain)
+    }
+
+    /// A chain with a fixed output gain in place of `cfg.normalize` — for
+    /// offline passes, where the gain was measured over the whole recording.
+    pub fn with_gain(sample_rate: u32, cfg: &ConditioningConfig, gain: f32) -> Self {
+        Self::build(sample_rate, cfg, Gain::Fixed(gain))
+    }
+
+    fn build(sample_rate: u32, cfg: &ConditioningConfig, gain: Gain) -> Self {
+        let sr = sample_rate.max(1) as f64;
+        Conditioner {
+            sample_rate,
+            dc: cfg.dc_block.then(|| DcBlocker::new(sr)),
+            highpass: cfg.highpass_hz.map(|hz| Biquad::highpass(sr, hz as f64)),
+            denoiser: cfg.noise_reduction.map(|settings| Denoiser::new(settings, sr)),
+            gain,
+        }
+    }
+
+    /// Start the denoiser from a measured room-tone floor: it cleans from the
+    /// first frame instead of passing audio through while it learns one, then
+    /// follows the signal's own floor as usual. No-op without noise reduction.
+    pub fn seed_noise(&mut self, profile: &NoiseProfile) {
+        if let Some(denoiser) = self.denoiser.as_mut() {
+            denoiser.seed = Some(profile.spectrum_at(self.sample_rate));
+        }
+    }
+
+    /// Input samples an output sample waits for before it is produced.
+    pub fn latency(&self) -> usize {
+        if self.denoiser.is_some() {
+            DENOISE_WINDOW - DENOISE_HOP
+        } else {
+            0
+        }
+    }
+
+    /// Condition a chunk, appending the output samples now determined to
+    /// `out`.
+    pub fn process(&mut self, input: &[f32], out: &mut Vec<f32>) {
+        let start = out.len();
+        // The filter stages' output goes through a block on the stack, so a
+        // large chunk never grows a buffer on the audio thread.
+        let mut block = [0.0f32; DENOISE_HOP];
+        for chunk in input.chunks(DENOISE_HOP) {
+            let filtered = &mut block[..chunk.len()];
+            for (dst, &s) in filtered.iter_mut().zip(chunk) {
+                let mut s = s as f64;
+                if let Some(dc) = self.dc.as_mut() {
+                    s = dc.process(s);
+                }
+                if let Some(hp) = self.highpass.as_mut() {
+                    s = hp.process(s);
+                }
+                *dst = s as f32;
+            }
+            match self.denoiser.as_mut() {
+                Some(denoiser) => denoiser.process(filtered, out),
+                None => out.extend_from_slice(filtered),
+            }
+        }
+        self.apply_gain(&mut out[start..]);
+    }
+
+    /// End of stream: emit the held-back samples. Subsequent calls emit
+    /// nothing.
+    pub fn flush(&mut self, out: &mut Vec<f32>) {
+        let start = out.len();
+        if let Some(denoiser) = self.denoiser.as_mut() {
+            denoiser.flush(out);
+        }
+        self.apply_gain(&mut out[start..]);
+    }
+
+    fn apply_gain(&mut self, samples: &mut [f32]) {
+        match &mut self.gain {
+            Gain::Fixed(gain) => {
+                if *gain != 1.0 {
+                    samples.iter_mut().for_each(|s| *s *= *gain);
+                }
+            }
+            Gain::Adaptive { target, meter, current, glide } => {
+                meter.push(samples);
+                let goal = meter.gain(*target);
+                for s in samples {
+                    *current += (goal - *current) * *glide;
+                    *s *= *current;
+                }
+            }
+        }
+    }
+}
+
+/// Condition a whole mono buffer. Normalization is measured over the entire
+/// buffer, so the gain is the same from the first sample (unlike a live
+/// [`Conditioner::new`]).
+pub fn condition(samples: &[f32], sample_rate: u32, cfg: &ConditioningConfig) -> Vec<f32> {
+    condition_with_noise(samples, sample_rate, cfg, None)
+}
+
+/// [`condition`] with the denoiser seeded from a room-tone profile.
+pub(crate) fn condition_with_noise(
+    samples: &[f32],
+    sample_rate: u32,
+    cfg: &ConditioningConfig,
+    noise: Option<&NoiseProfile>,
+) -> Vec<f32> {
+    let mut conditioner = Conditioner::with_gain(sample_rate, cfg, 1.0);
+    if let Some(profile) = noise {
+        conditioner.seed_noise(profile);
+    }
+    let mut out = Vec::with_capacity(samples.len());
+    conditioner.process(samples, &mut out);
+    conditioner.flush(&mut out);
+    if let Some(target) = cfg.normalize {
+        let mut meter = LevelMeter::new(sample_rate);
+        meter.push(&out);
+        let gain = meter.gain(target);
+        out.iter_mut().for_each(|s| *s *= gain);
+    }
+    out
+}
+
+/// Measures the normalization gain for one lane of a chunked recording: the
+/// extra read [`crate::analyze_source_hybrid`] makes before its passes.
+pub(crate) struct GainProbe {
+    conditioner: Conditioner,
+    meter: LevelMeter,
+    out: Vec<f32>,
+}
+
+impl GainProbe {
+    pub(crate) fn new(sample_rate: u32, cfg: &ConditioningConfig, noise: Option<&NoiseProfile>) -> Self {
+        let mut conditioner = Conditioner::with_gain(sample_rate, cfg, 1.0);
+        if let Some(profile) = noise {
+            conditioner.seed_noise(profile);
+        }
+        GainProbe {
+            conditioner,
+            meter: LevelMeter::new(sample_rate),
+            out: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, mono: &[f32]) {
+        self.out.clear();
+        self.conditioner.process(mono, &mut self.out);
+        self.meter.push(&self.out);
+    }
+
+    pub(crate) fn gain(mut self, target: Normalization) -> f32 {
+        self.out.clear();
+        self.conditioner.flush(&mut self.out);
+        self.meter.push(&self.out);
+        self.meter.gain(target)
+    }
+}
+
+/// One lane's optional conditioning in the chunked offline pipeline; a
+/// passthrough when conditioning is off.
+pub(crate) struct LaneConditioner {
+    conditioner: Option<Conditioner>,
+    out: Vec<f32>,
+}
+
+impl LaneConditioner {
+    pub(crate) fn new(
+        sample_rate: u32,
+        cfg: Option<&ConditioningConfig>,
+        gain: f32,
+        noise: Option<&NoiseProfile>,
+    ) -> Self {
+        LaneConditioner {
+            conditioner: cfg.map(|cfg| {
+                let mut conditioner = Conditioner::with_gain(sample_rate, cfg, gain);
+                if let Some(profile) = noise {
+                    conditioner.seed_noise(profile);
+                }
+                conditioner
+            }),
+            out: Vec::new(),
+        }
+    }
+
+    /// The conditioned samples now ready (`mono` itself when off).
+    pub(crate) fn apply<'a>(&'a mut self, mono: &'a [f32]) -> &'a [f32] {
+        match self.conditioner.as_mut() {
+            Some(conditioner) => {
+                self.out.clear();
+                conditioner.process(mono, &mut self.out);
+                &self.out
+            }
+            None => mono,
+        }
+    }
+
+    /// End of stream: the held-back tail.
+    pub(crate) fn finish(&mut self) -> &[f32] {
+        self.out.clear();
+        if let Some(conditioner) = self.conditioner.as_mut() {
+            conditioner.flush(&mut self.out);
+        }
+        &self.out
+    }
+}
+
+/// `y[n] = x[n] - x[n-1] + R·y[n-1]`, primed with the first sample so an
+/// offset present from the start doesn't read as a step (and an onset).
+struct DcBlocker {
+    r: f64,
+    x1: Option<f64>,
+    y1: f64,
+}
+
+impl DcBlocker {
+    fn new(sample_rate: f64) -> Self {
+        DcBlocker { r: (-2.0 * std::f64::consts::PI * DC_CUTOFF_HZ / sample_rate).exp(), x1: None, y1: 0.0 }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let x1 = *self.x1.get_or_insert(x);
+        let y = x - x1 + self.r * self.y1;
+        self.x1 = Some(x);
+        self.y1 = y;
+        y
+    }
+}
+
+/// Direct-form-I biquad, coefficients normalized so `a0 = 1`.
+#[derive(Debug, Clone)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 3]) -> Self {
+        Biquad {
+            b: [b[0] / a[0], b[1] / a[0], b[2] / a[0]],
+            a: [a[1] / a[0], a[2] / a[0]],
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    /// Butterworth (Q = 1/√2) high-pass, RBJ cookbook form. The cutoff is
+    /// kept below Nyquist.
+    fn highpass(sample_rate: f64, cutoff_hz: f64) -> Self {
+        let w0 = 2.0 * std::f64::consts::PI * cutoff_hz.clamp(1.0, 0.45 * sample_rate) / sample_rate;
+        let alpha = w0.sin() / (2.0 * std::f64::consts::FRAC_1_SQRT_2);
+        let cos = w0.cos();
+        Biquad::new(
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y
+    }
+}
+
+/// Periodic Hann window (sums to a constant at 75% overlap).
+fn periodic_hann(len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / len as f32).cos())
+        .collect()
+}
+
+/// Mean power per bin of `samples` over the denoiser's STFT frames — the
+/// units its noise estimate is kept in. Zeros when `samples` is shorter than
+/// one frame.
+pub(crate) fn mean_power_spectrum(samples: &[f32]) -> Vec<f32> {
+    let fft = RealFftPlanner::<f32>::new().plan_fft_forward(DENOISE_WINDOW);
+    let window = periodic_hann(DENOISE_WINDOW);
+    let mut scratch = vec![0.0; DENOISE_WINDOW];
+    let mut spectrum = fft.make_output_vec();
+    let mut power = vec![0.0f64; spectrum.len()];
+    let mut frames = 0;
+    for frame in samples.windows(DENOISE_WINDOW).step_by(DENOISE_HOP) {
+        for ((dst, &s), &w) in scratch.iter_mut().zip(frame).zip(&window) {
+            *dst = s * w;
+        }
+        fft.process(&mut scratch, &mut spectrum).expect("buffers sized by the planner");
+        for (p, c) in power.iter_mut().zip(&spectrum) {
+            *p += c.norm_sqr() as f64;
+        }
+        frames += 1;
+    }
+    power.iter().map(|&p| (p / frames.max(1) as f64) as f32).collect()
+}
+
+/// Denoiser STFT size and hop (75% overlap).
+pub(crate) const DENOISE_WINDOW: usize = 1024;
+pub(crate) const DENOISE_HOP: usize = 256;
+/// Noise tracking by minimum statistics: the per-bin minimum of the smoothed
+/// power over the last `SUBWINDOWS × SUBWINDOW_FRAMES` frames (~750 ms at
+/// 44.1 kHz) — long enough to span the gaps between hits, short enough to
+/// follow a fan spinning up.
+const SUBWINDOW_FRAMES: usize = 16;
+const SUBWINDOWS: usize = 8;
+/// Per-frame smoothing of the power the minimum is taken over.
+const POWER_SMOOTHING: f32 = 0.85;
+/// The minimum of a noisy power estimate sits below its mean; this scales it
+/// back up.
+const MIN_STATS_BIAS: f32 = 1.5;
+/// `Σ w²` over the overlapping periodic-Hann frames at 75% overlap.
+const OLA_GAIN: f32 = 1.5;
+
+/// Spectral subtraction over a Hann-windowed STFT with overlap-add
+/// resynthesis, then the noise gate. Until the noise tracker has one
+/// sub-window of history, frames are cleaned against the room-tone seed if
+/// there is one and otherwise pass through unchanged (the STFT itself
+/// reconstructs exactly).
+struct Denoiser {
+    settings: NoiseReduction,
+    gate: Option<Gate>,
+    fft: Arc<dyn RealToComplex<f32>>,
+    ifft: Arc<dyn ComplexToReal<f32>>,
+    window: Vec<f32>,
+    /// The last `DENOISE_WINDOW` input samples; starts as the zeros before
+    /// the stream so the first frames cover its opening samples.
+    frame: Vec<f32>,
+    /// New samples in `frame` since the last STFT frame.
+    fill: usize,
+    scratch: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+    ola: Vec<f32>,
+    /// Outputs still to discard: those of the frames before the stream.
+    to_drop: usize,
+    received: usize,
+    emitted: usize,
+    /// Set while [`flush`](Self::flush) pads the stream with silence.
+    flushing: bool,
+    flushed: bool,
+
+    smoothed: Vec<f32>,
+    sub_min: Vec<f32>,
+    sub_frames: usize,
+    /// Ring of completed sub-window minima; `mins_filled` of them are valid.
+    mins: Vec<Vec<f32>>,
+    mins_next: usize,
+    mins_filled: usize,
+    /// Per-bin noise power estimate.
+    noise: Vec<f32>,
+    /// Room-tone floor used until the tracker has history of its own.
+    seed: Option<Vec<f32>>,
+}
+
+impl Denoiser {
+    fn new(settings: NoiseReduction, sample_rate: f64) -> Self {
+        let mut planner = RealFftPlanner::<f32>::new();
+        let fft = planner.plan_fft_forward(DENOISE_WINDOW);
+        let ifft = planner.plan_fft_inverse(DENOISE_WINDOW);
+        let bins = DENOISE_WINDOW / 2 + 1;
+        Denoiser {
+            settings,
+            gate: settings.gate_db.map(|db| Gate::new(db, sample_rate)),
+            spectrum: fft.make_output_vec(),
+            fft,
+            ifft,
+            window: periodic_hann(DENOISE_WINDOW),
+            frame: vec![0.0; DENOISE_WINDOW],
+            fill: 0,
+            scratch: vec![0.0; DENOISE_WINDOW],
+            ola: vec![0.0; DENOISE_WINDOW],
+            to_drop: DENOISE_WINDOW - DENOISE_HOP,
+            received: 0,
+            emitted: 0,
+            flushing: false,
+            flushed: false,
+            smoothed: Vec::with_capacity(bins),
+            sub_min: vec![f32::INFINITY; bins],
+            sub_frames: 0,
+            mins: vec![vec![0.0; bins]; SUBWINDOWS],
+            mins_next: 0,
+            mins_filled: 0,
+            noise: vec![0.0; bins],
+            seed: None,
+        }
+    }
+
+    fn process(&mut self, input: &[f32], out: &mut Vec<f32>) {
+        if self.flushed {
+            return;
+        }
+        self.received += input.len();
+        self.feed(input, out);
+    }
+
+    fn flush(&mut self, out: &mut Vec<f32>) {
+        if self.flushed {
+            return;
+        }
+        let silence = [0.0; DENOISE_HOP];
+        self.flushing = true;
+        while self.emitted < self.received {
+            self.feed(&silence, out);
+        }
+        self.flushed = true;
+    }
+
+    fn feed(&mut self, mut input: &[f32], out: &mut Vec<f32>) {
+        while !input.is_empty() {
+            let take = (DENOISE_HOP - self.fill).min(input.len());
+            let tail = DENOISE_WINDOW - DENOISE_HOP + self.fill;
+            self.frame[tail..tail + take].copy_from_slice(&input[..take]);
+            self.fill += take;
+            input = &input[take..];
+            if self.fill == DENOISE_HOP {
+                self.process_frame(out);
+                self.frame.copy_within(DENOISE_HOP.., 0);
+                self.fill = 0;
+            }
+        }
+    }
+
+    fn process_frame(&mut self, out: &mut Vec<f32>) {
+        for ((dst, &s), &w) in self.scratch.iter_mut().zip(&self.frame).zip(&self.window) {
+            *dst = s * w;
+        }
+        self.fft
+            .process(&mut self.scratch, &mut self.spectrum)
+            .expect("buffers sized by the planner");
+
+        // Frames that overlap the zeros around the stream would drag the
+        // minima down, so only whole frames of input update the estimate.
+        let warmed = self.track_noise(self.to_drop == 0 && !self.flushing);
+        // Noise RMS from its power spectrum (Parseval over the one-sided
+        // spectrum; the Hann window's mean square is 3/8).
+        let noise_rms = warmed.then(|| {
+            let power: f32 = 2.0 * self.noise.iter().sum::<f32>();
+            (power / (0.375 * (DENOISE_WINDOW * DENOISE_WINDOW) as f32)).sqrt()
+        });
+        if warmed {
+            let alpha = self.settings.over_subtraction;
+            let floor = self.settings.spectral_floor * self.settings.spectral_floor;
+            for (k, bin) in self.spectrum.iter_mut().enumerate() {
+                let power = bin.norm_sqr();
+                if power <= 0.0 {
+                    continue;
+                }
+                let noise = self.noise[k];
+                let gain = (1.0 - alpha * noise / power).max(floor).sqrt();
+                *bin *= gain;
+            }
+        }
+        // The DC and Nyquist bins of a real signal's spectrum are real.
+        self.spectrum[0].im = 0.0;
+        if let Some(last) = self.spectrum.last_mut() {
+            last.im = 0.0;
+        }
+        self.ifft
+            .process(&mut self.spectrum, &mut self.scratch)
+            .expect("buffers sized by the planner");
+
+        let scale = 1.0 / (DENOISE_WINDOW as f32 * OLA_GAIN);
+        for ((acc, &s), &w) in self.ola.iter_mut().zip(&self.scratch).zip(&self.window) {
+            *acc += s * w * scale;
+        }
+
+        // The first hop of the overlap-add buffer is now final.
+        let drop = self.to_drop.min(DENOISE_HOP);
+        self.to_drop -= drop;
+        let ready = (DENOISE_HOP - drop).min(self.received - self.emitted);
+        let start = out.len();
+        out.extend_from_slice(&self.ola[drop..drop + ready]);
+        if let Some(gate) = self.gate.as_mut() {
+            gate.process(&mut out[start..], noise_rms);
+        }
+        self.emitted += ready;
+        self.ola.copy_within(DENOISE_HOP.., 0);
+        self.ola[DENOISE_WINDOW - DENOISE_HOP..].fill(0.0);
+    }
+
+    /// Update the minimum-statistics tracker with the current spectrum and,
+    /// once it has enough history, the noise estimate. Returns whether the
+    /// estimate is ready.
+    fn track_noise(&mut self, update: bool) -> bool {
+        if update {
+            if self.smoothed.is_empty() {
+                self.smoothed.extend(self.spectrum.iter().map(|c| c.norm_sqr()));
+            } else {
+                for (p, c) in self.smoothed.iter_mut().zip(&self.spectrum) {
+                    *p = POWER_SMOOTHING * *p + (1.0 - POWER_SMOOTHING) * c.norm_sqr();
+                }
+            }
+            for (m, &p) in self.sub_min.iter_mut().zip(&self.smoothed) {
+                *m = m.min(p);
+            }
+            self.sub_frames += 1;
+            if self.sub_frames == SUBWINDOW_FRAMES {
+                self.mins[self.mins_next].copy_from_slice(&self.sub_min);
+                self.mins_next = (self.mins_next + 1) % SUBWINDOWS;
+                self.mins_filled = (self.mins_filled + 1).min(SUBWINDOWS);
+                self.sub_min.fill(f32::INFINITY);
+                self.sub_frames = 0;
+            }
+        }
+        if self.mins_filled == 0 {
+            let Some(seed) = &self.seed else {
+                return false;
+            };
+            self.noise.copy_from_slice(seed);
+            return true;
+        }
+        if update {
+            for (k, noise) in self.noise.iter_mut().enumerate() {
+                let floor = self.mins[..self.mins_filled]
+                    .iter()
+                    .map(|m| m[k])
+                    .fold(self.sub_min[k], f32::min);
+                *noise = MIN_STATS_BIAS * floor;
+            }
+        }
+        true
+    }
+}
+
+/// Downward gate keyed on a peak envelope: opens instantly when the envelope
+/// crosses the threshold, closes smoothly once it has decayed below it.
+struct Gate {
+    /// Threshold as a multiple of the noise RMS.
+    ratio: f32,
+    release: f32,
+    close: f32,
+    envelope: f32,
+    gain: f32,
+}
+
+impl Gate {
+    fn new(gate_db: f32, sample_rate: f64) -> Self {
+        Gate {
+            ratio: 10f32.powf(gate_db / 20.0),
+            release: (-1.0 / (GATE_RELEASE_SECONDS * sample_rate)).exp() as f32,
+            close: (-1.0 / (GATE_CLOSE_SECONDS * sample_rate)).exp() as f32,
+            envelope: 0.0,
+            gain: 1.0,
+        }
+    }
+
+    /// Gate `samples` against `noise_rms`; open while there is no estimate.
+    fn process(&mut self, samples: &mut [f32], noise_rms: Option<f32>) {
+        let Some(noise_rms) = noise_rms else {
+            return;
+        };
+        let threshold = noise_rms * self.ratio;
+        for s in samples {
+            self.envelope = s.abs().max(self.envelope * self.release);
+            if self.envelope >= threshold {
+                self.gain = 1.0;
+            } else if self.gain > GATE_SILENT {
+                self.gain *= self.close;
+            } else {
+                // Closed means silent: a denormal residue would still cross
+                // zero.
+                self.gain = 0.0;
+            }
+            *s *= self.gain;
+        }
+    }
+}
+
+/// Loudness histogram: 0.1 LU bins from the -70 LUFS absolute gate up.
+const HISTOGRAM_BINS: usize = 1000;
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+
+/// Peak and gated integrated loudness (ITU-R BS.1770: K-weighting, 400 ms
+/// blocks every 100 ms, absolute and relative gates) of a mono signal pushed
+/// in chunks. Memory is fixed: gated blocks go into a histogram, not a list.
+pub struct LevelMeter {
+    pre_filter: Biquad,
+    rlb_filter: Biquad,
+    step_len: usize,
+    step_fill: usize,
+    step_energy: f64,
+    /// Energies of the last four 100 ms steps (one 400 ms block).
+    steps: [f64; 4],
+    steps_seen: usize,
+    hist_count: Vec<u32>,
+    hist_energy: Vec<f64>,
+    peak: f32,
+}
+
+impl LevelMeter {
+    pub fn new(sample_rate: u32) -> Self {
+        let sr = sample_rate.max(1) as f64;
+        let pi = std::f64::consts::PI;
+
+        // K-weighting stage 1: high shelf (+4 dB above ~1.7 kHz). Stage 2: the
+        // RLB high-pass. Parameterized by rate so any input rate measures the
+        // same as the standard's 48 kHz coefficients.
+        let (f0, gain_db, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+        let k = (pi * f0 / sr).tan();
+        let vh = 10f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let pre_filter = Biquad::new(
+            [vh + vb * k / q + k * k, 2.0 * (k * k - vh), vh - vb * k / q + k * k],
+            [1.0 + k / q + k * k, 2.0 * (k * k - 1.0), 1.0 - k / q + k * k],
+        );
+        let (f0, q) = (38.13547087602444, 0.5003270373238773);
+        let k = (pi * f0 / sr).tan();
+        let rlb_filter = Biquad::new(
+            [1.0, -2.0, 1.0],
+            [1.0, 2.0 * (k * k - 1.0) / (1.0 + k / q + k * k), (1.0 - k / q + k * k) / (1.0 + k / q + k * k)],
+        );
+
+        LevelMeter {
+            pre_filter,
+            rlb_filter,
+            step_len: ((sr * 0.1) as usize).max(1),
+            step_fill: 0,
+            step_energy: 0.0,
+            steps: [0.0; 4],
+            steps_seen: 0,
+            hist_count: vec![0; HISTOGRAM_BINS],
+            hist_energy: vec![0.0; HISTOGRAM_BINS],
+            peak: 0.0,
+        }
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        for &s in samples {
+            self.peak = self.peak.max(s.abs());
+            let k = self.rlb_filter.process(self.pre_filter.process(s as f64));
+            self.step_energy += k * k;
+            self.step_fill += 1;
+            if self.step_fill == self.step_len {
+                self.end_step();
+            }
+        }
+    }
+
+    fn end_step(&mut self) {
+        self.steps[self.steps_seen % 4] = self.step_energy;
+        self.steps_seen += 1;
+        self.step_energy = 0.0;
+        self.step_fill = 0;
+        if self.steps_seen < 4 {
+            return;
+        }
+        let z = self.steps.iter().sum::<f64>() / (4 * self.step_len) as f64;
+        let loudness = lufs(z);
+        if loudness >= ABSOLUTE_GATE_LUFS {
+            let bin = (((loudness - ABSOLUTE_GATE_LUFS) * 10.0) as usize).min(HISTOGRAM_BINS - 1);
+            self.hist_count[bin] += 1;
+            self.hist_energy[bin] += z;
+        }
+    }
+
+    /// Largest absolute sample so far.
+    pub fn peak(&self) -> f32 {
+        self.peak
+    }
+
+    /// Gated integrated loudness so far, or `None` before one 400 ms block
+    /// has cleared the absolute gate.
+    pub fn integrated_lufs(&self) -> Option<f64> {
+        let mean_above = |from: usize| {
+            let count: u32 = self.hist_count[from..].iter().sum();
+            (count > 0).then(|| self.hist_energy[from..].iter().sum::<f64>() / count as f64)
+        };
+        let relative_gate = lufs(mean_above(0)?) - 10.0;
+        let from = ((relative_gate - ABSOLUTE_GATE_LUFS) * 10.0).ceil().max(0.0) as usize;
+        mean_above(from.min(HISTOGRAM_BINS - 1)).map(lufs)
+    }
+
+    /// Gain that brings the signal measured so far to `target`, capped at
+    /// +24 dB. Unity while there is nothing to measure.
+    pub fn gain(&self, target: Normalization) -> f32 {
+        let gain_db = match target {
+            Normalization::Peak { target_dbfs } if self.peak > 0.0 => {
+                target_dbfs - 20.0 * self.peak.log10()
+            }
+            Normalization::Loudness { target_lufs } => match self.integrated_lufs() {
+                Some(measured) => target_lufs - measured as f32,
+                None => return 1.0,
+            },
+            Normalization::Peak { .. } => return 1.0,
+        };
+        10f32.powf(gain_db.min(MAX_GAIN_DB) / 20.0)
+    }
+}
+
+/// BS.1770 loudness of a mean-square K-weighted energy.
+fn lufs(mean_square: f64) -> f64 {
+    -0.691 + 10.0 * mean_square.max(1e-20).log10()
+}
+
+/// A [`Conditioner`]'s running state — filter memories, the denoiser's frame,
+/// overlap-add and noise tracker, the live level and gain — for a streaming
+/// snapshot. Coefficients, windows and the room-tone seed come from the
+/// config and are rebuilt, not stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ConditionerState {
+    dc: Option<(Option<f64>, f64)>,
+    highpass: Option<BiquadState>,
+    denoiser: Option<DenoiserState>,
+    /// Adaptive normalization: the level measured so far and the current gain.
+    gain: Option<(LevelMeterState, f32)>,
+}
+
+type BiquadState = ([f64; 2], [f64; 2]);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DenoiserState {
+    #[serde(with = "crate::snapshot::f32s")]
+    frame: Vec<f32>,
+    fill: usize,
+    #[serde(with = "crate::snapshot::f32s")]
+    ola: Vec<f32>,
+    to_drop: usize,
+    received: usize,
+    emitted: usize,
+    flushing: bool,
+    flushed: bool,
+    #[serde(with = "crate::snapshot::f32s")]
+    smoothed: Vec<f32>,
+    #[serde(with = "crate::snapshot::f32s")]
+    sub_min: Vec<f32>,
+    sub_frames: usize,
+    /// The sub-window minima ring, concatenated.
+    #[serde(with = "crate::snapshot::f32s")]
+    mins: Vec<f32>,
+    mins_next: usize,
+    mins_filled: usize,
+    #[serde(with = "crate::snapshot::f32s")]
+    noise: Vec<f32>,
+    /// Gate envelope and gain.
+    gate: Option<(f32, f32)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LevelMeterState {
+    pre_filter: BiquadState,
+    rlb_filter: BiquadState,
+    step_fill: usize,
+    step_energy: f64,
+    steps: [f64; 4],
+    steps_seen: usize,
+    hist_count: Vec<u32>,
+    #[serde(with = "crate::snapshot::f64s")]
+    hist_energy: Vec<f64>,
+    peak: f32,
+}
+
+const STATE_MISMATCH: &str = "conditioner state does not match its config";
+
+/// Copy a stored buffer over a live one of the same length.
+fn restore_buffer<T: Copy>(dst: &mut [T], src: &[T]) -> Result<(), String> {
+    if dst.len() != src.len() {
+        return Err(STATE_MISMATCH.to_string());
+    }
+    dst.copy_from_slice(src);
+    Ok(())
+}
+
+impl Conditioner {
+    pub(crate) fn state(&self) -> ConditionerState {
+        ConditionerState {
+            dc: self.dc.as_ref().map(|dc| (dc.x1, dc.y1)),
+            highpass: self.highpass.as_ref().map(|hp| (hp.x, hp.y)),
+            denoiser: self.denoiser.as_ref().map(Denoiser::state),
+            gain: match &self.gain {
+                Gain::Fixed(_) => None,
+                Gain::Adaptive { meter, current, .. } => Some((meter.state(), *current)),
+            },
+        }
+    }
+
+    /// Resume from [`state`](Self::state) taken on a conditioner built from
+    /// the same config and rate.
+    pub(crate) fn restore_state(&mut self, state: ConditionerState) -> Result<(), String> {
+        match (self.dc.as_mut(), state.dc) {
+            (Some(dc), Some((x1, y1))) => (dc.x1, dc.y1) = (x1, y1),
+            (None, None) => {}
+            _ => return Err(STATE_MISMATCH.to_string()),
+        }
+        match (self.highpass.as_mut(), state.highpass) {
+            (Some(hp), Some((x, y))) => (hp.x, hp.y) = (x, y),
+            (None, None) => {}
+            _ => return Err(STATE_MISMATCH.to_string()),
+        }
+        match (self.denoiser.as_mut(), state.denoiser) {
+            (Some(denoiser), Some(state)) => denoiser.restore_state(state)?,
+            (None, None) => {}
+            _ => return Err(STATE_MISMATCH.to_string()),
+        }
+        match (&mut self.gain, state.gain) {
+            (Gain::Adaptive { meter, current, .. }, Some((state, gain))) => {
+                meter.restore_state(state)?;
+                *current = gain;
+            }
+            (Gain::Fixed(_), None) => {}
+            _ => return Err(STATE_MISMATCH.to_string()),
+        }
+        Ok(())
+    }
+}
+
+impl Denoiser {
+    fn state(&self) -> DenoiserState {
+        DenoiserState {
+            frame: self.frame.clone(),
+            fill: self.fill,
+            ola: self.ola.clone(),
+            to_drop: self.to_drop,
+            received: self.received,
+            emitted: self.emitted,
+            flushing: self.flushing,
+            flushed: self.flushed,
+            smoothed: self.smoothed.clone(),
+            sub_min: self.sub_min.clone(),
+            sub_frames: self.sub_frames,
+            mins: self.mins.concat(),
+            mins_next: self.mins_next,
+            mins_filled: self.mins_filled,
+            noise: self.noise.clone(),
+            gate: self.gate.as_ref().map(|g| (g.envelope, g.gain)),
+        }
+    }
+
+    fn restore_state(&mut self, state: DenoiserState) -> Result<(), String> {
+        let bins = self.noise.len();
+        if !state.smoothed.is_empty() && state.smoothed.len() != bins
+            || state.mins.len() != bins * self.mins.len()
+            || state.mins_next >= self.mins.len()
+        {
+            return Err(STATE_MISMATCH.to_string());
+        }
+        restore_buffer(&mut self.frame, &state.frame)?;
+        restore_buffer(&mut self.ola, &state.ola)?;
+        restore_buffer(&mut self.sub_min, &state.sub_min)?;
+        restore_buffer(&mut self.noise, &state.noise)?;
+        for (min, stored) in self.mins.iter_mut().zip(state.mins.chunks_exact(bins)) {
+            min.copy_from_slice(stored);
+        }
+        self.smoothed.clear();
+        self.smoothed.extend_from_slice(&state.smoothed);
+        match (self.gate.as_mut(), state.gate) {
+            (Some(gate), Some((envelope, gain))) => (gate.envelope, gate.gain) = (envelope, gain),
+            (None, None) => {}
+            _ => return Err(STATE_MISMATCH.to_string()),
+        }
+        self.fill = state.fill;
+        self.to_drop = state.to_drop;
+        self.received = state.received;
+        self.emitted = state.emitted;
+        self.flushing = state.flushing;
+        self.flushed = state.flushed;
+        self.sub_frames = state.sub_frames;
+        self.mins_next = state.mins_next;
+        self.mins_filled = state.mins_filled;
+        Ok(())
+    }
+}
+
+impl LevelMeter {
+    fn state(&self) -> LevelMeterState {
+        LevelMeterState {
+            pre_filter: (self.pre_filter.x, self.pre_filter.y),
+            rlb_filter: (self.rlb_filter.x, self.rlb_filter.y),
+            step_fill: self.step_fill,
+            step_energy: self.step_energy,
+            steps: self.steps,
+            steps_seen: self.steps_seen,
+            hist_count: self.hist_count.clone(),
+            hist_energy: self.hist_energy.clone(),
+            peak: self.peak,
+        }
+    }
+
+    fn restore_state(&mut self, state: LevelMeterState) -> Result<(), String> {
+        restore_buffer(&mut self.hist_count, &state.hist_count)?;
+        restore_buffer(&mut self.hist_energy, &state.hist_energy)?;
+        (self.pre_filter.x, self.pre_filter.y) = state.pre_filter;
+        (self.rlb_filter.x, self.rlb_filter.y) = state.rlb_filter;
+        self.step_fill = state.step_fill;
+        self.step_energy = state.step_energy;
+        self.steps = state.steps;
+        self.steps_seen = state.steps_seen;
+        self.peak = state.peak;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SR: u32 = 44_100;
+
+    fn sine(freq: f32, amplitude: f32, seconds: f32) -> Vec<f32> {
+        (0..(SR as f32 * seconds) as usize)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * freq * i as f32 / SR as f32).sin())
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32).sqrt()
+    }
+
+    fn denoise_only() -> ConditioningConfig {
+        ConditioningConfig { dc_block: false, highpass_hz: None, normalize: None, ..Default::default() }
+    }
+
+    /// Short clicks every 250 ms over a steady 50 Hz hum.
+    fn clicks_over_hum() -> (Vec<f32>, Vec<usize>) {
+        let mut signal = sine(50.0, 0.05, 3.0);
+        let starts: Vec<usize> = (1..12).map(|i| i * SR as usize / 4).collect();
+        for &start in &starts {
+            for i in 0..200 {
+                signal[start + i] += 0.8 * (1.0 - i as f32 / 200.0) * if i % 2 == 0 { 1.0 } else { -1.0 };
+            }
+        }
+        (signal, starts)
+    }
+
+    #[test]
+    fn stft_passes_signal_through_aligned_until_noise_is_learned() {
+        // No stage but the denoiser, and too short for the tracker to warm up:
+        // the STFT round trip must reproduce the input sample for sample.
+        let input = sine(440.0, 0.5, 0.05);
+        let out = condition(&input, SR, &denoise_only());
+        assert_eq!(out.len(), input.len());
+        for (a, b) in out.iter().zip(&input) {
+            assert!((a - b).abs() < 1e-4, "{a} vs {b}");
+        }
+    }
+
+    #[test]
+    fn chunking_does_not_change_the_output() {
+        let (input, _) = clicks_over_hum();
+        let cfg = ConditioningConfig { normalize: None, ..Default::default() };
+        let whole = condition(&input, SR, &cfg);
+        for chunk in [128, 1000, 65_536] {
+            let mut conditioner = Conditioner::with_gain(SR, &cfg, 1.0);
+            let mut out = Vec::new();
+            for piece in input.chunks(chunk) {
+                conditioner.process(piece, &mut out);
+            }
+            conditioner.flush(&mut out);
+            conditioner.flush(&mut out);
+            assert_eq!(out, whole, "chunk {chunk}");
+        }
+    }
+
+    #[test]
+    fn dc_blocker_and_highpass_remove_offset_and_rumble() {
+        let input: Vec<f32> = sine(15.0, 0.3, 2.0).iter().map(|s| s + 0.2).collect();
+        let cfg = ConditioningConfig { highpass_hz: Some(60.0), noise_reduction: None, normalize: None, dc_block: true };
+        let out = condition(&input, SR, &cfg);
+        let settled = &out[SR as usize..];
+        let mean = settled.iter().sum::<f32>() / settled.len() as f32;
+        assert!(mean.abs() < 1e-3, "DC left: {mean}");
+        assert!(rms(settled) < 0.3 * 0.707 * 0.1, "15 Hz rumble not attenuated 20 dB: {}", rms(settled));
+    }
+
+    #[test]
+    fn denoiser_suppresses_hum_between_hits_and_keeps_the_hits() {
+        let (input, starts) = clicks_over_hum();
+        let out = condition(&input, SR, &denoise_only());
+
+        // A gap between two late hits: hum only.
+        let gap = starts[9] + 4000..starts[10] - 500;
+        let before = rms(&input[gap.clone()]);
+        let after = rms(&out[gap]);
+        assert!(after < before * 0.3, "hum {before} -> {after}: less than 10 dB of reduction");
+
+        let hit = starts[10]..starts[10] + 200;
+        assert!(rms(&out[hit.clone()]) > 0.7 * rms(&input[hit]), "the hit itself was eaten");
+    }
+
+    #[test]
+    fn meter_reads_a_sine_per_bs1770() {
+        // A 997 Hz sine at full scale reads -3.01 LUFS; -20 dB of amplitude
+        // reads 20 LU lower.
+        let mut meter = LevelMeter::new(SR);
+        meter.push(&sine(997.0, 0.1, 3.0));
+        let lufs = meter.integrated_lufs().unwrap();
+        assert!((lufs + 23.01).abs() < 0.1, "{lufs}");
+        assert!((meter.peak() - 0.1).abs() < 1e-3);
+
+        let mut at_48k = LevelMeter::new(48_000);
+        at_48k.push(
+            &(0..144_000)
+                .map(|i| 0.1 * (2.0 * std::f32::consts::PI * 997.0 * i as f32 / 48_000.0).sin())
+                .collect::<Vec<_>>(),
+        );
+        assert!((at_48k.integrated_lufs().unwrap() - lufs).abs() < 0.05);
+    }
+
+    #[test]
+    fn normalization_reaches_its_target() {
+        let quiet = sine(997.0, 0.1, 2.0);
+        let plain = ConditioningConfig { dc_block: false, highpass_hz: None, noise_reduction: None, normalize: None };
+
+        let loud = condition(
+            &quiet,
+            SR,
+            &ConditioningConfig { normalize: Some(Normalization::Loudness { target_lufs: -16.0 }), ..plain },
+        );
+        let mut meter = LevelMeter::new(SR);
+        meter.push(&loud);
+        assert!((meter.integrated_lufs().unwrap() + 16.0).abs() < 0.1);
+
+        let peaked = condition(
+            &quiet,
+            SR,
+            &ConditioningConfig { normalize: Some(Normalization::Peak { target_dbfs: -1.0 }), ..plain },
+        );
+        let peak = peaked.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!((20.0 * peak.log10() + 1.0).abs() < 0.01, "{peak}");
+
+        // Silence is left alone rather than boosted without bound.
+        let silence = condition(&vec![0.0; SR as usize], SR, &ConditioningConfig::default());
+        assert!(silence.iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn live_normalization_glides_to_the_target() {
+        let quiet = sine(997.0, 0.02, 4.0);
+        let cfg = ConditioningConfig {
+            dc_block: false,
+            highpass_hz: None,
+            noise_reduction: None,
+            normalize: Some(Normalization::Loudness { target_lufs: -16.0 }),
+        };
+        let mut conditioner = Conditioner::new(SR, &cfg);
+        let mut out = Vec::new();
+        for piece in quiet.chunks(128) {
+            conditioner.process(piece, &mut out);
+        }
+        let mut meter = LevelMeter::new(SR);
+        meter.push(&out[3 * SR as usize..]);
+        assert!((meter.integrated_lufs().unwrap() + 16.0).abs() < 0.5);
+    }
+}
@@ -0,0 +1,197 @@
+//! One decoded recording, prepared once for per-onset analysis.
+//!
+//! The `*_for_window` helpers in [`crate::features`] take an [`AudioData`]
+//! and mix it to mono on every call, and [`crate::detect_onsets`] conditions
+//! its own copy first; over a long file's onsets that is one mixdown per
+//! event. [`AnalysisContext`] detects the onsets and keeps the signal they
+//! and the features are read from, so [`crate::analyze_offline`] mixes and
+//! conditions once and classifies the onsets independently — across threads
+//! with the `parallel` feature (native builds; the worklet has one thread).
+
+use crate::conditioning;
+use crate::events::types::{EventFeatures, PitchPoint};
+use crate::features::{self, extract_features, extract_mfcc, track_pitch, Onset, OnsetConfig, MFCC_COEFFS};
+use crate::ingest::AudioData;
+
+/// Onsets and the mono analysis signal of one recording.
+#[derive(Debug, Clone)]
+pub struct AnalysisContext {
+    sample_rate: u32,
+    duration_ms: i64,
+    onsets: Vec<Onset>,
+    /// Channel average, conditioned when `cfg.conditioning` is set.
+    mono: Vec<f32>,
+}
+
+impl AnalysisContext {
+    /// Detect `audio`'s onsets per `cfg` and keep the channel average the
+    /// features are taken from (the same signal [`crate::analyze_offline`]
+    /// always used). When onsets were detected on that average, its mixdown
+    /// and conditioning are reused rather than redone.
+    pub fn new(audio: &AudioData, cfg: &OnsetConfig) -> Self {
+        let (onsets, average) = features::detect_onsets_keeping_mix(audio, cfg);
+        let mono = average.unwrap_or_else(|| {
+            let mono = audio.to_mono();
+            match &cfg.conditioning {
+                Some(conditioning) => conditioning::condition_with_noise(
+                    &mono,
+                    audio.sample_rate,
+                    conditioning,
+                    cfg.noise_profile.as_ref(),
+                ),
+                None => mono,
+            }
+        });
+        AnalysisContext { sample_rate: audio.sample_rate, duration_ms: audio.duration_ms, onsets, mono }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn duration_ms(&self) -> i64 {
+        self.duration_ms
+    }
+
+    pub fn onsets(&self) -> &[Onset] {
+        &self.onsets
+    }
+
+    /// The mono analysis signal.
+    pub fn mono(&self) -> &[f32] {
+        &self.mono
+    }
+
+    /// Gap from onset `i` to the next one (or to the end of the audio, for
+    /// the last).
+    pub fn gap_ms(&self, i: usize) -> f64 {
+        let onset = self.onsets[i].timestamp_ms;
+        match self.onsets.get(i + 1) {
+            Some(next) => next.timestamp_ms - onset,
+            None => self.duration_ms as f64 - onset,
+        }
+    }
+
+    /// `duration_ms` of the signal from `start_ms`, cut at its end; empty
+    /// when that leaves nothing.
+    pub fn window(&self, start_ms: f64, duration_ms: f64) -> &[f32] {
+        let start = ((start_ms / 1000.0) * self.sample_rate as f64) as usize;
+        let len = ((duration_ms / 1000.0) * self.sample_rate as f64) as usize;
+        let end = (start + len).min(self.mono.len());
+        if start >= end {
+            return &[];
+        }
+        &self.mono[start..end]
+    }
+
+    /// [`crate::extract_features_for_window`] on the shared signal.
+    pub fn features(&self, start_ms: f64, duration_ms: f64) -> EventFeatures {
+        match self.window(start_ms, duration_ms) {
+            [] => EventFeatures::zero(),
+            window => extract_features(window, self.sample_rate),
+        }
+    }
+
+    /// [`crate::extract_mfcc_for_window`] on the shared signal.
+    pub fn mfcc(&self, start_ms: f64, duration_ms: f64) -> Vec<f32> {
+        match self.window(start_ms, duration_ms) {
+            [] => vec![0.0; MFCC_COEFFS],
+            window => extract_mfcc(window, self.sample_rate),
+        }
+    }
+
+    /// [`crate::track_pitch_for_window`] on the shared signal.
+    pub fn pitch(&self, start_ms: f64, duration_ms: f64) -> Vec<PitchPoint> {
+        match self.window(start_ms, duration_ms) {
+            [] => Vec::new(),
+            window => track_pitch(window, self.sample_rate),
+        }
+    }
+
+    /// `f(i, onset)` for every onset, results in onset order. The calls run
+    /// across threads with the `parallel` feature, so `f` must not depend on
+    /// the order they happen in.
+    pub fn map_onsets<T, F>(&self, f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize, &Onset) -> T + Sync + Send,
+    {
+        map_indices(self.onsets.len(), |i| f(i, &self.onsets[i]))
+    }
+}
+
+/// `f` over `0..n`, in order; spread over the rayon pool with the `parallel`
+/// feature.
+pub(crate) fn map_indices<T, F>(n: usize, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        (0..n).into_par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        (0..n).map(f).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extract_features_for_window, track_pitch_for_window, ChannelStrategy, ConditioningConfig};
+
+    /// Stereo clicks over a quiet 150 Hz tone.
+    fn stereo() -> AudioData {
+        let sr = 44_100;
+        let frames = sr as usize;
+        let samples = (0..frames)
+            .flat_map(|i| {
+                let tone = 0.05 * (2.0 * std::f32::consts::PI * 150.0 * i as f32 / sr as f32).sin();
+                let click = if i % 11_025 < 200 { 0.8 * (1.0 - (i % 11_025) as f32 / 200.0) } else { 0.0 };
+                [tone + click, tone - 0.5 * click]
+            })
+            .collect();
+        AudioData { samples, sample_rate: sr, channels: 2, bit_depth: 32, duration_ms: 1000, frame_count: frames }
+    }
+
+    #[test]
+    fn matches_the_per_call_helpers() {
+        let audio = stereo();
+        for channels in [ChannelStrategy::Average, ChannelStrategy::MidSide] {
+            let cfg = OnsetConfig { channels, ..OnsetConfig::default() };
+            let ctx = AnalysisContext::new(&audio, &cfg);
+            assert_eq!(ctx.onsets().len(), features::detect_onsets(&audio, &cfg).len());
+            assert!(!ctx.onsets().is_empty());
+            assert_eq!(ctx.mono(), audio.to_mono().as_slice());
+            for onset in ctx.onsets() {
+                let (start, len) = (onset.timestamp_ms, 120.0);
+                let features = extract_features_for_window(&audio, start, len);
+                assert_eq!(format!("{:?}", ctx.features(start, len)), format!("{features:?}"));
+                assert_eq!(ctx.pitch(start, len), track_pitch_for_window(&audio, start, len));
+            }
+        }
+        let past_the_end = AnalysisContext::new(&audio, &OnsetConfig::default()).features(5000.0, 50.0);
+        assert_eq!(format!("{past_the_end:?}"), format!("{:?}", EventFeatures::zero()));
+    }
+
+    #[test]
+    fn conditions_the_signal_once() {
+        let audio = stereo();
+        let cfg = OnsetConfig { conditioning: Some(ConditioningConfig::default()), ..OnsetConfig::default() };
+        let ctx = AnalysisContext::new(&audio, &cfg);
+        let expected =
+            conditioning::condition_with_noise(&audio.to_mono(), audio.sample_rate, &ConditioningConfig::default(), None);
+        assert_eq!(ctx.mono(), expected.as_slice());
+    }
+
+    #[test]
+    fn maps_onsets_in_order() {
+        let ctx = AnalysisContext::new(&stereo(), &OnsetConfig::default());
+        let times = ctx.map_onsets(|i, onset| (i, onset.timestamp_ms));
+        assert!(times.iter().enumerate().all(|(k, &(i, _))| k == i));
+        assert!(times.windows(2).all(|w| w[0].1 < w[1].1));
+    }
+}
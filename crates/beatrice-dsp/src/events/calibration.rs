@@ -4,6 +4,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::events::gaussian::{GaussianModel, DEFAULT_MAP_TAU};
+use crate::events::heuristic::ClassificationResult;
+use crate::events::hybrid::HybridClassifier;
 use crate::events::types::{EventClass, EventFeatures};
 
 /// Current calibration profile schema version. v1 = pre-MFCC (7 scalar
@@ -174,10 +177,14 @@ impl CalibrationProfile {
 
     /// Check if the profile has sufficient samples for calibration
     /// Recommended: at least 5 samples per class
+    ///
+    /// Only the four core classes are required; open hat, rimshot and crash
+    /// samples are optional refinements a profile may carry on top, as are
+    /// `Noise` samples of sounds to ignore (breaths, coughs, laughs).
     pub fn is_sufficient(&self) -> bool {
         let min_samples_per_class = 5;
 
-        // Check all four classes
+        // Check the four core classes
         let required_classes = [
             EventClass::BilabialPlosive,
             EventClass::HihatNoise,
@@ -195,6 +202,24 @@ impl CalibrationProfile {
         true
     }
 
+    /// The `(class, gaussian_vec)` pairs that MAP adaptation of the factory
+    /// Gaussian trains on. Legacy MFCC-less samples are skipped — their
+    /// zero-padded vectors would poison the adapted means (see
+    /// [`CalibrationSample::has_mfcc`]).
+    pub fn adaptation_samples(&self) -> Vec<(EventClass, Vec<f32>)> {
+        self.samples
+            .values()
+            .flatten()
+            .filter(|s| s.has_mfcc())
+            .map(|s| (s.class, s.gaussian_vec()))
+            .collect()
+    }
+
+    /// How well the profile teaches the classifier: see [`CalibrationReport`].
+    pub fn report(&self) -> CalibrationReport {
+        CalibrationReport::new(self)
+    }
+
     /// Serialize profile to JSON bytes
     pub fn to_json_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
         serde_json::to_vec_pretty(self)
@@ -206,6 +231,272 @@ impl CalibrationProfile {
     }
 }
 
+/// Leave-one-out accuracy below which a class is flagged for re-teaching.
+/// Four of five samples right passes; three of five does not.
+pub const RETEACH_ACCURACY: f32 = 0.8;
+
+/// Separability below which two classes are flagged as overlapping. 0.5 is
+/// chance: the classifier cannot tell the two apart at all.
+pub const OVERLAP_SEPARABILITY: f32 = 0.75;
+
+/// Outlier score above which a sample is suggested for dropping: that many
+/// times its class's typical distance from the rest of the class.
+pub const OUTLIER_SCORE: f32 = 2.5;
+
+/// Samples a class needs before its outlier scores mean anything; smaller
+/// classes score 0.
+const OUTLIER_MIN_SAMPLES: usize = 3;
+
+/// Floor on a class's typical distance, so a class of near-identical samples
+/// does not turn a small deviation into a huge score.
+const OUTLIER_MIN_SPREAD: f32 = 1.0;
+
+/// How well a [`CalibrationProfile`] teaches the classifier, so the
+/// calibration panel can say which class to re-teach and which samples to
+/// drop rather than only whether there are five of each
+/// ([`CalibrationProfile::is_sufficient`]).
+///
+/// Every figure comes from leave-one-out over the samples with MFCCs (legacy
+/// samples cannot adapt the model and are left out): each sample is
+/// classified by the live hybrid over the factory Gaussian MAP-adapted from
+/// all the *other* samples, as a new take of that sound would be.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationReport {
+    /// [`CalibrationProfile::is_sufficient`].
+    pub sufficient: bool,
+
+    /// Share of samples classified as their label; `None` without samples.
+    pub accuracy: Option<f32>,
+
+    /// One entry per taught class, in [`EventClass::ALL`] order.
+    pub classes: Vec<ClassQuality>,
+
+    /// One entry per pair of taught classes with samples to compare.
+    pub pairs: Vec<ClassSeparability>,
+
+    /// One entry per sample with MFCCs, grouped by class in
+    /// [`EventClass::ALL`] order.
+    pub samples: Vec<SampleQuality>,
+}
+
+/// Leave-one-out quality of one taught class.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassQuality {
+    pub class: EventClass,
+
+    /// Samples taught, legacy ones included.
+    pub samples: usize,
+
+    /// Samples with MFCCs, the ones evaluated.
+    pub evaluated: usize,
+
+    /// Share of the evaluated samples classified as this class.
+    pub accuracy: Option<f32>,
+
+    /// The class its samples are most often mistaken for.
+    pub confused_with: Option<EventClass>,
+
+    /// Accuracy is below [`RETEACH_ACCURACY`].
+    pub reteach: bool,
+}
+
+/// How well the classifier tells two taught classes apart.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassSeparability {
+    pub a: EventClass,
+    pub b: EventClass,
+
+    /// Mean over both classes' samples of the sample's own-class score as a
+    /// share of the two classes' scores: 1.0 always tells them apart, 0.5
+    /// is chance (the same sound taught under two labels).
+    pub separability: f32,
+
+    /// Samples of either class classified as the other.
+    pub confusions: usize,
+
+    /// Separability is below [`OVERLAP_SEPARABILITY`].
+    pub overlapping: bool,
+}
+
+/// Leave-one-out verdict on one sample.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SampleQuality {
+    pub class: EventClass,
+
+    /// Index into the profile's samples of `class`.
+    pub index: usize,
+
+    /// What the classifier calls it.
+    pub predicted: EventClass,
+
+    /// Distance from the rest of its class, over the class's median such
+    /// distance (0 for classes under three samples).
+    pub outlier_score: f32,
+
+    /// The outlier score is above [`OUTLIER_SCORE`].
+    pub drop: bool,
+}
+
+impl CalibrationReport {
+    /// Evaluate `profile` (see the type docs).
+    pub fn new(profile: &CalibrationProfile) -> Self {
+        let factory = GaussianModel::factory();
+        let taught: Vec<(usize, &CalibrationSample)> = EventClass::ALL
+            .iter()
+            .filter_map(|class| profile.samples.get(class))
+            .flat_map(|samples| samples.iter().enumerate().filter(|(_, s)| s.has_mfcc()))
+            .collect();
+        let vectors: Vec<Vec<f32>> = taught.iter().map(|(_, s)| s.gaussian_vec()).collect();
+
+        // Leave-one-out: each sample against the model adapted from the rest.
+        let verdicts: Vec<ClassificationResult> = taught
+            .iter()
+            .enumerate()
+            .map(|(j, (_, sample))| {
+                let others: Vec<(EventClass, Vec<f32>)> = taught
+                    .iter()
+                    .zip(&vectors)
+                    .enumerate()
+                    .filter(|&(i, _)| i != j)
+                    .map(|(_, ((_, s), v))| (s.class, v.clone()))
+                    .collect();
+                HybridClassifier::with_model(factory.map_adapt(&others, DEFAULT_MAP_TAU))
+                    .classify(&sample.features, &sample.mfcc)
+            })
+            .collect();
+
+        let taught_classes: Vec<EventClass> =
+            EventClass::ALL.into_iter().filter(|c| profile.samples.get(c).is_some_and(|s| !s.is_empty())).collect();
+        let labels: &[EventClass] = &taught.iter().map(|(_, s)| s.class).collect::<Vec<_>>();
+        let members = |class: EventClass| (0..labels.len()).filter(move |&j| labels[j] == class);
+        let slot = |class: EventClass| EventClass::ALL.iter().position(|&c| c == class).unwrap_or(0);
+
+        let classes = taught_classes
+            .iter()
+            .map(|&class| {
+                let evaluated = members(class).count();
+                let correct = members(class).filter(|&j| verdicts[j].class == class).count();
+                let accuracy = (evaluated > 0).then(|| correct as f32 / evaluated as f32);
+                let mut mistakes = [0usize; EventClass::COUNT];
+                for j in members(class).filter(|&j| verdicts[j].class != class) {
+                    mistakes[slot(verdicts[j].class)] += 1;
+                }
+                let confused_with = (0..EventClass::COUNT)
+                    .filter(|&k| mistakes[k] > 0)
+                    .max_by_key(|&k| (mistakes[k], std::cmp::Reverse(k)))
+                    .map(|k| EventClass::ALL[k]);
+                ClassQuality {
+                    class,
+                    samples: profile.samples[&class].len(),
+                    evaluated,
+                    accuracy,
+                    confused_with,
+                    reteach: accuracy.is_some_and(|a| a < RETEACH_ACCURACY),
+                }
+            })
+            .collect();
+
+        let mut pairs = Vec::new();
+        for (i, &a) in taught_classes.iter().enumerate() {
+            for &b in &taught_classes[i + 1..] {
+                if members(a).next().is_none() || members(b).next().is_none() {
+                    continue;
+                }
+                let both: Vec<usize> = members(a).chain(members(b)).collect();
+                let share = |j: usize| {
+                    let (own, other) = if labels[j] == a { (a, b) } else { (b, a) };
+                    let score = |c: EventClass| verdicts[j].all_scores[slot(c)].1;
+                    let total = score(own) + score(other);
+                    if total > 0.0 { score(own) / total } else { 0.5 }
+                };
+                let separability = both.iter().map(|&j| share(j)).sum::<f32>() / both.len() as f32;
+                let confusions = both
+                    .iter()
+                    .filter(|&&j| verdicts[j].class == if labels[j] == a { b } else { a })
+                    .count();
+                pairs.push(ClassSeparability {
+                    a,
+                    b,
+                    separability,
+                    confusions,
+                    overlapping: separability < OVERLAP_SEPARABILITY,
+                });
+            }
+        }
+
+        // Outliers: distance from the centroid of the rest of the class, per
+        // dimension over the factory classes' average variance so the noisy
+        // high MFCCs do not drown out the ones that carry the timbre.
+        let standardized: Vec<Vec<f32>> = vectors.iter().map(|v| factory.standardize(v)).collect();
+        let dims = factory.z_mean.len();
+        let var: Vec<f32> = (0..dims)
+            .map(|d| factory.vars.iter().map(|v| v[d]).sum::<f32>() / factory.vars.len().max(1) as f32)
+            .collect();
+        let mut outlier_scores = vec![0.0f32; taught.len()];
+        for &class in &taught_classes {
+            let idx: Vec<usize> = members(class).collect();
+            if idx.len() < OUTLIER_MIN_SAMPLES {
+                continue;
+            }
+            let n = (idx.len() - 1) as f32;
+            let distances: Vec<f32> = idx
+                .iter()
+                .map(|&j| {
+                    (0..dims)
+                        .map(|d| {
+                            let rest = idx.iter().filter(|&&i| i != j).map(|&i| standardized[i][d]).sum::<f32>() / n;
+                            (standardized[j][d] - rest).powi(2) / var[d]
+                        })
+                        .sum::<f32>()
+                        .sqrt()
+                })
+                .collect();
+            let mut sorted = distances.clone();
+            sorted.sort_by(f32::total_cmp);
+            let median = sorted[sorted.len() / 2].max(OUTLIER_MIN_SPREAD);
+            for (&j, d) in idx.iter().zip(distances) {
+                outlier_scores[j] = d / median;
+            }
+        }
+
+        let samples = taught
+            .iter()
+            .zip(&verdicts)
+            .zip(outlier_scores)
+            .map(|(((index, sample), verdict), outlier_score)| SampleQuality {
+                class: sample.class,
+                index: *index,
+                predicted: verdict.class,
+                outlier_score,
+                drop: outlier_score > OUTLIER_SCORE,
+            })
+            .collect();
+
+        let correct = taught.iter().zip(&verdicts).filter(|((_, s), v)| v.class == s.class).count();
+        CalibrationReport {
+            sufficient: profile.is_sufficient(),
+            accuracy: (!taught.is_empty()).then(|| correct as f32 / taught.len() as f32),
+            classes,
+            pairs,
+            samples,
+        }
+    }
+
+    /// Classes to re-teach, in [`EventClass::ALL`] order.
+    pub fn reteach(&self) -> Vec<EventClass> {
+        self.classes.iter().filter(|c| c.reteach).map(|c| c.class).collect()
+    }
+
+    /// `(class, index)` of the samples suggested for dropping.
+    pub fn drops(&self) -> Vec<(EventClass, usize)> {
+        self.samples.iter().filter(|s| s.drop).map(|s| (s.class, s.index)).collect()
+    }
+}
+
 /// K-Nearest Neighbors classifier using calibration samples
 pub struct KnnClassifier {
     profile: CalibrationProfile,
@@ -222,6 +513,34 @@ impl KnnClassifier {
     /// Classify features using KNN against calibration samples
     /// Returns the most common class among k nearest neighbors
     pub fn classify(&self, features: &EventFeatures) -> Option<(EventClass, f32)> {
+        let (votes, k_used) = self.neighbour_votes(features)?;
+
+        // Find class with most votes
+        let (best_class, vote_count) = votes
+            .into_iter()
+            .max_by_key(|(_, count)| *count)?;
+
+        // Calculate confidence as vote ratio
+        let confidence = vote_count as f32 / k_used as f32;
+
+        Some((best_class, confidence))
+    }
+
+    /// Each voting class's share of the k nearest neighbours (the confidence
+    /// [`classify`](Self::classify) reports for it). `None` without samples.
+    pub fn vote_shares(&self, features: &EventFeatures) -> Option<Vec<(EventClass, f32)>> {
+        let (votes, k_used) = self.neighbour_votes(features)?;
+        Some(
+            votes
+                .into_iter()
+                .map(|(class, count)| (class, count as f32 / k_used as f32))
+                .collect(),
+        )
+    }
+
+    /// Votes per class among the k nearest neighbours, and how many
+    /// neighbours voted.
+    fn neighbour_votes(&self, features: &EventFeatures) -> Option<(HashMap<EventClass, usize>, usize)> {
         // Collect all samples with their distances
         let mut distances: Vec<(EventClass, f32)> = Vec::new();
 
@@ -248,15 +567,7 @@ impl KnnClassifier {
             *votes.entry(*class).or_insert(0) += 1;
         }
 
-        // Find class with most votes
-        let (best_class, vote_count) = votes
-            .into_iter()
-            .max_by_key(|(_, count)| *count)?;
-
-        // Calculate confidence as vote ratio
-        let confidence = vote_count as f32 / self.k.min(distances.len()) as f32;
-
-        Some((best_class, confidence))
+        Some((votes, self.k.min(distances.len())))
     }
 
     /// Get the calibration profile
@@ -400,6 +711,101 @@ mod tests {
         assert!(full.has_mfcc());
     }
 
+    /// A `label`ed sample sounding like the factory model's `sound` mean,
+    /// nudged by `jitter` standard deviations on a few MFCCs.
+    fn sample_like(sound: EventClass, label: EventClass, jitter: f32) -> CalibrationSample {
+        let model = GaussianModel::factory();
+        let k = model.classes.iter().position(|&c| c == sound).unwrap();
+        let raw: Vec<f32> = (0..model.z_mean.len())
+            .map(|d| {
+                let nudge = if d % 4 == 1 { jitter } else { 0.0 };
+                (model.means[k][d] + nudge) * model.z_std[d] + model.z_mean[d]
+            })
+            .collect();
+        let n = crate::features::MFCC_COEFFS;
+        let features = EventFeatures {
+            spectral_centroid: 2000.0,
+            zcr: raw[n],
+            low_band_energy: 0.3,
+            mid_band_energy: 0.3,
+            high_band_energy: 0.4,
+            peak_amplitude: 0.5,
+            crest_factor: raw[n + 1],
+        };
+        CalibrationSample::with_mfcc(label, features, raw[..n].to_vec(), vec![], 44100)
+    }
+
+    fn distinct_profile() -> CalibrationProfile {
+        let mut profile = CalibrationProfile::new("Test".to_string());
+        for class in [EventClass::BilabialPlosive, EventClass::HihatNoise, EventClass::Click] {
+            for i in 0..5 {
+                profile.add_sample(sample_like(class, class, 0.1 * i as f32 - 0.2));
+            }
+        }
+        profile
+    }
+
+    #[test]
+    fn report_passes_distinct_classes() {
+        let report = distinct_profile().report();
+        assert_eq!(report.accuracy, Some(1.0), "{report:?}");
+        assert!(report.reteach().is_empty());
+        assert!(report.drops().is_empty());
+        assert_eq!(report.pairs.len(), 3);
+        for pair in &report.pairs {
+            assert!(!pair.overlapping, "{pair:?}");
+            assert_eq!(pair.confusions, 0);
+        }
+        assert!(!report.sufficient, "no hum samples");
+    }
+
+    #[test]
+    fn report_flags_one_sound_taught_as_two_classes() {
+        // "ts" for both the hi-hat and the snare.
+        let mut profile = CalibrationProfile::new("Test".to_string());
+        for i in 0..5 {
+            let jitter = 0.1 * i as f32 - 0.2;
+            profile.add_sample(sample_like(EventClass::HihatNoise, EventClass::HihatNoise, jitter));
+            profile.add_sample(sample_like(EventClass::HihatNoise, EventClass::Click, jitter));
+        }
+        let report = profile.report();
+        let pair = &report.pairs[0];
+        assert_eq!((pair.a, pair.b), (EventClass::HihatNoise, EventClass::Click));
+        assert!(pair.overlapping, "{pair:?}");
+        assert!(pair.separability < 0.6, "{pair:?}");
+        let click = report.classes.iter().find(|c| c.class == EventClass::Click).unwrap();
+        assert!(click.reteach, "{click:?}");
+        assert_eq!(click.confused_with, Some(EventClass::HihatNoise));
+        assert_eq!(report.reteach(), vec![EventClass::Click]);
+    }
+
+    #[test]
+    fn report_drops_a_mislabeled_sample() {
+        let mut profile = distinct_profile();
+        // A kick taught as a hi-hat, after a legacy sample without MFCCs.
+        profile.add_sample(CalibrationSample::new(
+            EventClass::HihatNoise,
+            create_test_features(4000.0, 0.4),
+            vec![],
+            44100,
+        ));
+        profile.add_sample(sample_like(EventClass::BilabialPlosive, EventClass::HihatNoise, 0.0));
+        let report = profile.report();
+        assert_eq!(report.drops(), vec![(EventClass::HihatNoise, 6)], "{report:?}");
+        let stray = report.samples.iter().find(|s| s.drop).unwrap();
+        assert_eq!(stray.predicted, EventClass::BilabialPlosive);
+        let hihat = report.classes.iter().find(|c| c.class == EventClass::HihatNoise).unwrap();
+        assert_eq!((hihat.samples, hihat.evaluated), (7, 6));
+        assert!(report.samples.iter().filter(|s| !s.drop).all(|s| s.outlier_score < OUTLIER_SCORE));
+    }
+
+    #[test]
+    fn report_of_an_empty_profile() {
+        let report = CalibrationProfile::new("Test".to_string()).report();
+        assert_eq!(report.accuracy, None);
+        assert!(report.classes.is_empty() && report.pairs.is_empty() && report.samples.is_empty());
+    }
+
     #[test]
     fn test_profile_serialization() {
         let mut profile = CalibrationProfile::new("Test".to_string());
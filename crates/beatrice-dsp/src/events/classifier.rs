@@ -0,0 +1,234 @@
+// Pluggable event classifiers
+//
+// Every classifier the pipeline can run — the rule heuristic, the Gaussian
+// MFCC model, the shipping hybrid of the two, and per-user kNN — implements
+// `EventClassifier`, so the offline analysis, the streaming detector, the CLI
+// and the benchmark take any of them. The registry below names them, so a
+// caller picks one with a string (`DetectEventsInput::classifier`,
+// `beatrice --classifier`, `benchmark --classifier`) and A/B runs need no
+// code edits.
+
+use crate::events::calibration::{CalibrationProfile, KnnClassifier};
+use crate::events::gaussian::{gaussian_vector, GaussianModel, DEFAULT_MAP_TAU};
+use crate::events::heuristic::{ClassificationResult, HeuristicClassifier};
+use crate::events::hybrid::HybridClassifier;
+use crate::events::types::{EventClass, EventFeatures};
+
+/// What a classifier sees of one event.
+#[derive(Debug, Clone, Copy)]
+pub struct ClassifierInput<'a> {
+    /// Scalar features of the classification window.
+    pub features: &'a EventFeatures,
+    /// Mean MFCCs (c1..c20) of the same window.
+    pub mfcc: &'a [f32],
+    /// The window's mono samples; empty when only features are at hand
+    /// (e.g. re-classifying a stored event).
+    pub samples: &'a [f32],
+    /// Rate (Hz) of `samples`; 0 when there are none.
+    pub sample_rate: u32,
+}
+
+impl<'a> ClassifierInput<'a> {
+    /// Input carrying features and MFCCs only.
+    pub fn new(features: &'a EventFeatures, mfcc: &'a [f32]) -> Self {
+        ClassifierInput { features, mfcc, samples: &[], sample_rate: 0 }
+    }
+
+    /// Attach the window the features were extracted from.
+    pub fn with_window(mut self, samples: &'a [f32], sample_rate: u32) -> Self {
+        self.samples = samples;
+        self.sample_rate = sample_rate;
+        self
+    }
+}
+
+/// An event classifier: one event's features in, a [`ClassificationResult`]
+/// (winning class, confidence and the full score array) out.
+pub trait EventClassifier: Send + Sync {
+    /// Classify one event.
+    fn classify(&self, input: &ClassifierInput) -> ClassificationResult;
+}
+
+impl EventClassifier for HeuristicClassifier {
+    fn classify(&self, input: &ClassifierInput) -> ClassificationResult {
+        HeuristicClassifier::classify(self, input.features)
+    }
+}
+
+impl EventClassifier for HybridClassifier {
+    fn classify(&self, input: &ClassifierInput) -> ClassificationResult {
+        HybridClassifier::classify(self, input.features, input.mfcc)
+    }
+}
+
+/// The bare Gaussian model, without the hybrid's hum gate or family split:
+/// classes the model wasn't fitted on score 0.
+impl EventClassifier for GaussianModel {
+    fn classify(&self, input: &ClassifierInput) -> ClassificationResult {
+        let (class, confidence, all_scores) =
+            GaussianModel::classify_all(self, &gaussian_vector(input.features, input.mfcc));
+        ClassificationResult { class, confidence, all_scores }
+    }
+}
+
+/// kNN over the profile's samples; each class scores its share of the
+/// nearest-neighbour votes. An empty profile falls back to the heuristic.
+impl EventClassifier for KnnClassifier {
+    fn classify(&self, input: &ClassifierInput) -> ClassificationResult {
+        let Some(shares) = self.vote_shares(input.features) else {
+            return HeuristicClassifier::new().classify(input.features);
+        };
+        let (class, confidence) = KnnClassifier::classify(self, input.features)
+            .unwrap_or((EventClass::BilabialPlosive, 0.0));
+        let all_scores = EventClass::ALL.map(|c| {
+            (c, shares.iter().find(|(s, _)| *s == c).map_or(0.0, |s| s.1))
+        });
+        ClassificationResult { class, confidence, all_scores }
+    }
+}
+
+/// Builds a registered classifier, personalised from `profile` when one is
+/// given (MAP adaptation for the Gaussian models, the neighbour set for kNN).
+pub type ClassifierFactory = fn(Option<&CalibrationProfile>) -> Box<dyn EventClassifier>;
+
+/// A named entry in [`CLASSIFIERS`].
+pub struct ClassifierEntry {
+    /// Name callers select the classifier by.
+    pub name: &'static str,
+    /// One-line description (CLI help, reports).
+    pub description: &'static str,
+    /// Constructor.
+    pub build: ClassifierFactory,
+}
+
+/// Neighbour count for the registry's kNN classifier.
+pub const KNN_K: usize = 5;
+
+/// Name of the classifier the app ships with.
+pub const DEFAULT_CLASSIFIER: &str = "hybrid";
+
+/// Every selectable classifier, [`DEFAULT_CLASSIFIER`] first.
+pub const CLASSIFIERS: &[ClassifierEntry] = &[
+    ClassifierEntry {
+        name: "hybrid",
+        description: "Gaussian MFCC model with the heuristic hum gate (default)",
+        build: |profile| match profile {
+            Some(profile) => {
+                Box::new(HybridClassifier::with_adaptation(&profile.adaptation_samples()))
+            }
+            None => Box::new(HybridClassifier::factory()),
+        },
+    },
+    ClassifierEntry {
+        name: "gaussian",
+        description: "Gaussian MFCC model alone (never predicts classes it wasn't fitted on)",
+        build: |profile| {
+            let model = GaussianModel::factory();
+            match profile {
+                Some(profile) => {
+                    Box::new(model.map_adapt(&profile.adaptation_samples(), DEFAULT_MAP_TAU))
+                }
+                None => Box::new(model),
+            }
+        },
+    },
+    ClassifierEntry {
+        name: "heuristic",
+        description: "Rule-based spectral heuristic (ignores calibration)",
+        build: |_| Box::new(HeuristicClassifier::new()),
+    },
+    ClassifierEntry {
+        name: "knn",
+        description: "k-nearest neighbours over calibration samples (heuristic without them)",
+        build: |profile| {
+            let profile =
+                profile.cloned().unwrap_or_else(|| CalibrationProfile::new("empty".to_string()));
+            Box::new(KnnClassifier::new(profile, KNN_K))
+        },
+    },
+];
+
+/// Look a classifier up by name.
+pub fn classifier_entry(name: &str) -> Option<&'static ClassifierEntry> {
+    CLASSIFIERS.iter().find(|entry| entry.name == name)
+}
+
+/// Build the classifier registered as `name`. Errors list the valid names.
+pub fn build_classifier(
+    name: &str,
+    profile: Option<&CalibrationProfile>,
+) -> Result<Box<dyn EventClassifier>, String> {
+    let entry = classifier_entry(name).ok_or_else(|| {
+        let names: Vec<&str> = CLASSIFIERS.iter().map(|entry| entry.name).collect();
+        format!("unknown classifier '{name}' (expected one of: {})", names.join(", "))
+    })?;
+    Ok((entry.build)(profile))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::calibration::CalibrationSample;
+
+    fn kick() -> EventFeatures {
+        EventFeatures {
+            spectral_centroid: 300.0,
+            zcr: 0.08,
+            low_band_energy: 0.7,
+            mid_band_energy: 0.2,
+            high_band_energy: 0.1,
+            peak_amplitude: 0.8,
+            crest_factor: 6.0,
+        }
+    }
+
+    #[test]
+    fn every_registered_classifier_builds_and_fills_the_score_array() {
+        let features = kick();
+        let mfcc = vec![0.0; crate::features::MFCC_COEFFS];
+        let input = ClassifierInput::new(&features, &mfcc);
+        assert_eq!(CLASSIFIERS[0].name, DEFAULT_CLASSIFIER);
+        for entry in CLASSIFIERS {
+            let result = build_classifier(entry.name, None).unwrap().classify(&input);
+            assert_eq!(result.all_scores.map(|(c, _)| c), EventClass::ALL, "{}", entry.name);
+            assert!((0.0..=1.0).contains(&result.confidence), "{}", entry.name);
+        }
+    }
+
+    #[test]
+    fn unknown_names_are_rejected_with_the_valid_ones() {
+        let err = build_classifier("onnx", None).err().unwrap();
+        assert!(err.contains("onnx") && err.contains("hybrid") && err.contains("knn"));
+    }
+
+    #[test]
+    fn heuristic_entry_matches_the_heuristic() {
+        let features = kick();
+        let input = ClassifierInput::new(&features, &[]);
+        let via_registry = build_classifier("heuristic", None).unwrap().classify(&input);
+        let direct = HeuristicClassifier::new().classify(&features);
+        assert_eq!(via_registry.class, direct.class);
+        assert_eq!(via_registry.all_scores, direct.all_scores);
+    }
+
+    #[test]
+    fn knn_votes_from_the_profile_and_falls_back_without_one() {
+        let features = kick();
+        let input = ClassifierInput::new(&features, &[]);
+
+        // No samples: the heuristic answers.
+        let fallback = build_classifier("knn", None).unwrap().classify(&input);
+        assert_eq!(fallback.class, HeuristicClassifier::new().classify(&features).class);
+
+        // A profile that (perversely) calls this sound a hum wins the vote.
+        let mut profile = CalibrationProfile::new("test".to_string());
+        for _ in 0..KNN_K {
+            profile.add_sample(CalibrationSample::new(EventClass::HumVoiced, kick(), Vec::new(), 44_100));
+        }
+        let taught = build_classifier("knn", Some(&profile)).unwrap().classify(&input);
+        assert_eq!(taught.class, EventClass::HumVoiced);
+        assert_eq!(taught.confidence, 1.0);
+        let hum = taught.all_scores.iter().find(|(c, _)| *c == EventClass::HumVoiced).unwrap();
+        assert_eq!(hum.1, 1.0);
+    }
+}
@@ -22,6 +22,11 @@ use crate::events::types::{ClassScore, EventClass, EventFeatures};
 /// the native binary and the WASM worklet.
 const FACTORY_MODEL_JSON: &str = include_str!("avp_factory_model.json");
 
+/// Sample rate (Hz) of the AVP audio the factory model's features were
+/// extracted at. Audio analysed at another rate sees different STFT/MFCC
+/// statistics; [`crate::normalize_sample_rate`] converts to this rate first.
+pub const FACTORY_SAMPLE_RATE: u32 = 44_100;
+
 /// Feature-vector dimensionality used by [`GaussianModel`]:
 /// 20 MFCCs (c1..c20) + zero-crossing rate + crest factor.
 pub const GAUSSIAN_DIMS: usize = crate::features::MFCC_COEFFS + 2;
@@ -34,12 +39,17 @@ pub const GAUSSIAN_DIMS: usize = crate::features::MFCC_COEFFS + 2;
 /// recording chain as much as the sound), while MFCCs + zcr + crest carry the
 /// discriminative timbre.
 pub fn gaussian_features(f: &EventFeatures, mfcc: &[f32]) -> Vec<f32> {
-    let mut v = Vec::with_capacity(GAUSSIAN_DIMS);
-    for i in 0..crate::features::MFCC_COEFFS {
-        v.push(mfcc.get(i).copied().unwrap_or(0.0));
+    gaussian_vector(f, mfcc).to_vec()
+}
+
+/// [`gaussian_features`] on the stack, for the allocation-free live path.
+pub(crate) fn gaussian_vector(f: &EventFeatures, mfcc: &[f32]) -> [f32; GAUSSIAN_DIMS] {
+    let mut v = [0.0; GAUSSIAN_DIMS];
+    for (slot, &c) in v.iter_mut().zip(mfcc.iter().take(crate::features::MFCC_COEFFS)) {
+        *slot = c;
     }
-    v.push(f.zcr);
-    v.push(f.crest_factor);
+    v[GAUSSIAN_DIMS - 2] = f.zcr;
+    v[GAUSSIAN_DIMS - 1] = f.crest_factor;
     v
 }
 
@@ -75,6 +85,25 @@ pub struct GaussianModel {
 
     /// Per-class log-prior, indexed like `classes`.
     pub log_priors: Vec<f32>,
+
+    /// Likelihood floor, as a squared standardized (Mahalanobis) distance:
+    /// an input farther than this from every class mean is out of
+    /// distribution and classified [`EventClass::Noise`]. `None` disables
+    /// the floor; a model saved without the field gets
+    /// [`DEFAULT_REJECT_DISTANCE`].
+    #[serde(default = "default_reject_distance")]
+    pub reject_distance: Option<f32>,
+}
+
+/// Default [`GaussianModel::reject_distance`]. Over the model's 22 dims a
+/// sample drawn from a class lands past χ² = 48 once in a thousand; the floor
+/// sits far beyond that because the training voices are a narrower world
+/// than a real room — synthetic sine kicks land near 100 and must pass —
+/// while a one-sample mic click lands above 200.
+pub const DEFAULT_REJECT_DISTANCE: f32 = 150.0;
+
+fn default_reject_distance() -> Option<f32> {
+    Some(DEFAULT_REJECT_DISTANCE)
 }
 
 impl GaussianModel {
@@ -83,7 +112,11 @@ impl GaussianModel {
     /// Covers the three percussive classes (BilabialPlosive, HihatNoise,
     /// Click); AVP has no hum class, so HumVoiced stays with the heuristic's
     /// sustained-signal gate (see the classifier composition at the call
-    /// sites). Panics only if the embedded JSON is corrupt, which the
+    /// sites). This embedded fit predates the open-hat class and folds AVP
+    /// `hho` into HihatNoise; `benchmark --fit-model` now keeps `hho` as
+    /// OpenHihat, and until the JSON is regenerated from it the hybrid splits
+    /// open hats (and rimshots/crashes, which AVP doesn't label) out of their
+    /// family heuristically. Panics only if the embedded JSON is corrupt, which the
     /// `factory_model_loads` test guards.
     pub fn factory() -> GaussianModel {
         GaussianModel::from_json(FACTORY_MODEL_JSON)
@@ -171,56 +204,113 @@ impl GaussianModel {
             means,
             vars,
             log_priors,
+            reject_distance: default_reject_distance(),
         })
     }
 
     /// Standardize a raw feature vector into model space.
-    fn standardize(&self, x: &[f32]) -> Vec<f32> {
+    pub(crate) fn standardize(&self, x: &[f32]) -> Vec<f32> {
         x.iter()
             .zip(self.z_mean.iter().zip(self.z_std.iter()))
             .map(|(v, (m, s))| (v - m) / s)
             .collect()
     }
 
-    /// Classify a raw feature vector (as produced by [`gaussian_features`]).
-    ///
-    /// Returns the winning class, its posterior probability, and the full
-    /// per-class posterior list (softmax over log-likelihood + log-prior),
-    /// suitable for the UI's per-class score display.
-    pub fn classify(&self, x: &[f32]) -> (EventClass, f32, Vec<ClassScore>) {
-        let z = self.standardize(x);
-        let mut lls: Vec<f32> = Vec::with_capacity(self.classes.len());
-        for k in 0..self.classes.len() {
+    /// Posteriors of `x` in `classes` order (the first `classes.len()`
+    /// slots), the index of the most probable class and the standardized
+    /// Mahalanobis distance to the nearest class mean. Fixed-size, so the
+    /// live path classifies without allocating.
+    fn evaluate(&self, x: &[f32]) -> ([f32; EventClass::COUNT], usize, f32) {
+        let n = self.classes.len().min(EventClass::COUNT);
+        let mut posteriors = [0.0f32; EventClass::COUNT];
+        let mut nearest = f32::INFINITY;
+        for (k, ll_slot) in posteriors.iter_mut().enumerate().take(n) {
             let mut ll = self.log_priors[k];
-            for ((zi, mi), vi) in z.iter().zip(&self.means[k]).zip(&self.vars[k]) {
+            let mut distance = 0.0;
+            // Standardize on the fly: (x − z_mean) / z_std.
+            let z = x.iter().zip(self.z_mean.iter().zip(self.z_std.iter())).map(|(v, (m, s))| (v - m) / s);
+            for ((zi, mi), vi) in z.zip(&self.means[k]).zip(&self.vars[k]) {
                 let d = zi - mi;
+                distance += d * d / vi;
                 ll -= 0.5 * (d * d / vi + vi.ln());
             }
-            lls.push(ll);
+            nearest = nearest.min(distance);
+            *ll_slot = ll;
         }
         // Softmax → posteriors.
+        let lls = &mut posteriors[..n];
         let mx = lls.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
-        let exps: Vec<f32> = lls.iter().map(|&l| (l - mx).exp()).collect();
-        let sum: f32 = exps.iter().sum();
-        let posteriors: Vec<f32> = exps.iter().map(|e| e / sum.max(1e-12)).collect();
+        for l in lls.iter_mut() {
+            *l = (*l - mx).exp();
+        }
+        let sum: f32 = lls.iter().sum();
+        for p in lls.iter_mut() {
+            *p /= sum.max(1e-12);
+        }
 
-        let best = posteriors
+        let best = lls
             .iter()
             .enumerate()
             .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
             .map(|(i, _)| i)
             .unwrap_or(0);
+        (posteriors, best, nearest)
+    }
 
-        let scores = self
+    /// The [`EventClass::Noise`] score when `nearest` is past
+    /// [`reject_distance`](Self::reject_distance): `1 - floor / distance`.
+    fn rejection(&self, nearest: f32) -> Option<f32> {
+        self.reject_distance.filter(|&floor| nearest > floor).map(|floor| 1.0 - floor / nearest)
+    }
+
+    /// Classify a raw feature vector (as produced by [`gaussian_features`]).
+    ///
+    /// Returns the winning class, its posterior probability, and the full
+    /// per-class posterior list (softmax over log-likelihood + log-prior),
+    /// suitable for the UI's per-class score display.
+    ///
+    /// An input past [`reject_distance`](Self::reject_distance) from every
+    /// class is [`EventClass::Noise`], scored `1 - floor / distance` (0.5 at
+    /// twice the floor); the class posteriors stay in the list beside it.
+    pub fn classify(&self, x: &[f32]) -> (EventClass, f32, Vec<ClassScore>) {
+        let (posteriors, best, nearest) = self.evaluate(x);
+        let mut scores: Vec<ClassScore> = self
             .classes
             .iter()
             .zip(posteriors.iter())
             .map(|(&class, &score)| ClassScore { class, score })
             .collect();
 
+        if let Some(score) = self.rejection(nearest) {
+            match scores.iter_mut().find(|s| s.class == EventClass::Noise) {
+                Some(noise) => noise.score = score,
+                None => scores.push(ClassScore { class: EventClass::Noise, score }),
+            }
+            return (EventClass::Noise, score, scores);
+        }
         (self.classes[best], posteriors[best], scores)
     }
 
+    /// [`classify`](Self::classify) with the scores laid out like
+    /// [`EventClass::ALL`] (0.0 for classes the model lacks), without
+    /// allocating.
+    pub fn classify_all(&self, x: &[f32]) -> (EventClass, f32, [(EventClass, f32); EventClass::COUNT]) {
+        let (posteriors, best, nearest) = self.evaluate(x);
+        let mut all_scores = EventClass::ALL.map(|c| {
+            let k = self.classes.iter().take(EventClass::COUNT).position(|&m| m == c);
+            (c, k.map_or(0.0, |k| posteriors[k]))
+        });
+        if let Some(score) = self.rejection(nearest) {
+            for (c, s) in all_scores.iter_mut() {
+                if *c == EventClass::Noise {
+                    *s = score;
+                }
+            }
+            return (EventClass::Noise, score, all_scores);
+        }
+        (self.classes[best], posteriors[best], all_scores)
+    }
+
     /// MAP-adapt the class means toward a user's labeled calibration samples.
     ///
     /// Each class mean becomes `(n·user_mean + tau·factory_mean) / (n + tau)`
@@ -228,8 +318,30 @@ impl GaussianModel {
     /// priors stay at factory values (5-sample variance estimates are noise).
     /// Classes with no samples are unchanged. `tau` defaults to
     /// [`DEFAULT_MAP_TAU`].
+    ///
+    /// [`EventClass::Noise`] samples (the user's "ignore" sounds: breaths,
+    /// coughs, laughs) give a model without a Noise class one, centred on
+    /// their mean with the factory classes' average variance and prior, so
+    /// sounds like them lose to it on likelihood rather than needing the
+    /// floor.
     pub fn map_adapt(&self, samples: &[(EventClass, Vec<f32>)], tau: f32) -> GaussianModel {
         let mut adapted = self.clone();
+        let ignore: Vec<Vec<f32>> = samples
+            .iter()
+            .filter(|(c, _)| c.is_noise())
+            .map(|(_, v)| self.standardize(v))
+            .collect();
+        if !ignore.is_empty() && !self.classes.contains(&EventClass::Noise) && !self.classes.is_empty() {
+            let n = ignore.len() as f32;
+            let k = self.classes.len() as f32;
+            let dims = self.z_mean.len();
+            let mean = (0..dims).map(|i| ignore.iter().map(|u| u[i]).sum::<f32>() / n).collect();
+            let var = (0..dims).map(|i| self.vars.iter().map(|v| v[i]).sum::<f32>() / k).collect();
+            adapted.classes.push(EventClass::Noise);
+            adapted.means.push(mean);
+            adapted.vars.push(var);
+            adapted.log_priors.push(self.log_priors.iter().sum::<f32>() / k);
+        }
         for (k, &class) in self.classes.iter().enumerate() {
             let user: Vec<Vec<f32>> = samples
                 .iter()
@@ -309,8 +421,10 @@ mod tests {
     #[test]
     fn posteriors_sum_to_one() {
         let model = GaussianModel::fit(&two_cluster_examples()).unwrap();
+        // Midway between two tight clusters is past the floor; the class
+        // posteriors still sum to one beside the Noise score.
         let (_, _, scores) = model.classify(&[2.0, -1.0]);
-        let total: f32 = scores.iter().map(|s| s.score).sum();
+        let total: f32 = scores.iter().filter(|s| !s.class.is_noise()).map(|s| s.score).sum();
         assert!((total - 1.0).abs() < 1e-4, "posteriors sum to {total}");
     }
 
@@ -350,6 +464,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn far_inputs_are_rejected_as_noise() {
+        let mut model = GaussianModel::fit(&two_cluster_examples()).unwrap();
+        assert_eq!(model.reject_distance, Some(DEFAULT_REJECT_DISTANCE));
+        let (c, p, scores) = model.classify(&[60.0, 60.0]);
+        assert_eq!(c, EventClass::Noise);
+        assert!(p > 0.0 && p < 1.0);
+        assert_eq!(scores.last().map(|s| s.class), Some(EventClass::Noise));
+        // In-distribution inputs are untouched.
+        assert_eq!(model.classify(&[0.1, 0.9]).0, EventClass::BilabialPlosive);
+
+        model.reject_distance = None;
+        assert_ne!(model.classify(&[60.0, 60.0]).0, EventClass::Noise);
+    }
+
+    #[test]
+    fn ignore_samples_add_a_noise_class() {
+        let mut model = GaussianModel::fit(&two_cluster_examples()).unwrap();
+        model.reject_distance = None;
+        let ignore = vec![
+            (EventClass::Noise, vec![2.5, 3.0]),
+            (EventClass::Noise, vec![2.6, 3.1]),
+            (EventClass::Noise, vec![2.4, 2.9]),
+        ];
+        assert_ne!(model.classify(&[2.5, 3.0]).0, EventClass::Noise);
+        let adapted = model.map_adapt(&ignore, DEFAULT_MAP_TAU);
+        assert_eq!(adapted.classes.len(), 3);
+        assert_eq!(adapted.classify(&[2.5, 3.0]).0, EventClass::Noise);
+        assert_eq!(adapted.classify(&[0.1, 0.9]).0, EventClass::BilabialPlosive);
+        // Adapting again moves the Noise mean rather than adding a class.
+        assert_eq!(adapted.map_adapt(&ignore, DEFAULT_MAP_TAU).classes.len(), 3);
+    }
+
     #[test]
     fn factory_model_loads() {
         let model = GaussianModel::factory();
@@ -372,6 +519,12 @@ mod tests {
         let back = GaussianModel::from_json(&json).unwrap();
         assert_eq!(back.classes, model.classes);
         assert_eq!(back.means, model.means);
+        assert_eq!(back.reject_distance, model.reject_distance);
+        // Models saved before the floor existed get the default one.
+        let mut legacy: serde_json::Value = serde_json::from_str(&json).unwrap();
+        legacy.as_object_mut().unwrap().remove("reject_distance");
+        let legacy = GaussianModel::from_json(&legacy.to_string()).unwrap();
+        assert_eq!(legacy.reject_distance, Some(DEFAULT_REJECT_DISTANCE));
         let (c, _, _) = back.classify(&[0.1, 0.9]);
         assert_eq!(c, EventClass::BilabialPlosive);
     }
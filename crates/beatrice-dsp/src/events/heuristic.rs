@@ -2,6 +2,7 @@
 // Classifies beatbox events using hand-crafted feature rules
 // MVP implementation before ML-based classification
 
+use crate::events::hybrid::HUM_GATE_MAX_ZCR;
 use crate::events::types::{ClassScore, EventClass, EventFeatures};
 
 /// Classification result with confidence scores for each class
@@ -13,8 +14,9 @@ pub struct ClassificationResult {
     /// Confidence score for the selected class [0.0, 1.0]
     pub confidence: f32,
 
-    /// Confidence scores for all classes (for debugging/visualization)
-    pub all_scores: [(EventClass, f32); 4],
+    /// Confidence scores for all classes (for debugging/visualization), in
+    /// [`EventClass::ALL`] order
+    pub all_scores: [(EventClass, f32); EventClass::COUNT],
 }
 
 impl ClassificationResult {
@@ -84,9 +86,16 @@ impl HeuristicClassifier {
             (EventClass::HihatNoise, hihat_score),
             (EventClass::Click, click_score),
             (EventClass::HumVoiced, hum_score),
+            (EventClass::OpenHihat, self.score_open_hihat(features, hihat_score)),
+            (EventClass::Rimshot, self.score_rimshot(features)),
+            (EventClass::Crash, self.score_crash(features)),
+            (EventClass::Noise, if Self::is_noise(features) { 1.0 } else { 0.0 }),
         ];
 
-        // Find the class with highest score
+        // Find the class with highest score. `max_by` keeps the last of equal
+        // maxima, so a refinement that matches its family exactly (an open
+        // hat scoring a clamped 1.0 like the closed hat) wins the tie, and so
+        // does a rejection (Noise is last).
         let (class, confidence) = all_scores
             .iter()
             .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
@@ -100,6 +109,30 @@ impl HeuristicClassifier {
         }
     }
 
+    /// Rejection rules: whether the features describe a sound that is not
+    /// part of the performance.
+    ///
+    /// - Mic bump: a subsonic thump, centroid below [`BUMP_MAX_CENTROID_HZ`]
+    ///   (a bilabial kick's fundamental sits at 50-150 Hz).
+    /// - Mic click / digital glitch: a near-impulse, crest factor at or above
+    ///   [`GLITCH_MIN_CREST`] (voiced transients peak around 4-10).
+    /// - Breath: sustained (crest below [`SUSTAINED_CREST`]), unvoiced (zcr at
+    ///   or above [`HUM_GATE_MAX_ZCR`]) and dark (under
+    ///   [`BREATH_MAX_HIGH_BAND`] of its energy above 4 kHz), where an open hat
+    ///   or crash rings bright.
+    ///
+    /// A zero centroid or crest factor (silence, legacy features) never trips
+    /// a rule.
+    pub fn is_noise(f: &EventFeatures) -> bool {
+        let bump = f.spectral_centroid > 0.0 && f.spectral_centroid < BUMP_MAX_CENTROID_HZ;
+        let glitch = f.crest_factor >= GLITCH_MIN_CREST;
+        let breath = f.crest_factor > 0.0
+            && f.crest_factor < SUSTAINED_CREST
+            && f.zcr >= HUM_GATE_MAX_ZCR
+            && f.high_band_energy < BREATH_MAX_HIGH_BAND;
+        bump || glitch || breath
+    }
+
     /// Score for BilabialPlosive (B/P sounds → kick + synth bass)
     /// Characteristics:
     /// - Low spectral centroid (< 800 Hz)
@@ -324,6 +357,128 @@ impl HeuristicClassifier {
 
         final_score.clamp(0.0, 1.0)
     }
+
+    /// Score for OpenHihat (a TSSS left to ring → open hi-hat)
+    /// Characteristics:
+    /// - Everything a closed hi-hat has
+    /// - A slow decay: crest factor below [`SUSTAINED_CREST`]
+    fn score_open_hihat(&self, f: &EventFeatures, hihat_score: f32) -> f32 {
+        // Just above the closed hat when sustained, well below it otherwise,
+        // so the spectrum decides hat-vs-not and the decay open-vs-closed.
+        (hihat_score * sustain_factor(f, 1.05, 0.5)).clamp(0.0, 1.0)
+    }
+
+    /// Score for Rimshot (tongue ticks → rimshot / side stick, ghost snare)
+    /// Characteristics:
+    /// - Click-like but brighter spectral centroid (2500-4500 Hz)
+    /// - Almost no low-band body (< 0.1) and real high-band energy (> 0.3)
+    /// - Moderate ZCR
+    /// - Very sharp transient (crest factor >= 5)
+    fn score_rimshot(&self, f: &EventFeatures) -> f32 {
+        let mut score = 0.0;
+
+        let centroid_score = if f.spectral_centroid > 2500.0 && f.spectral_centroid < 4500.0 {
+            1.0
+        } else if f.spectral_centroid > 1800.0 && f.spectral_centroid < 5000.0 {
+            0.5
+        } else {
+            0.1
+        };
+        score += centroid_score * self.config.centroid_weight;
+
+        // Band energy - thin (no chest behind it) and bright (wood/metal ring)
+        let thin_score = if f.low_band_energy < 0.1 {
+            1.0
+        } else if f.low_band_energy < 0.2 {
+            0.5
+        } else {
+            0.1
+        };
+        let bright_score = if f.high_band_energy > 0.3 {
+            1.0
+        } else if f.high_band_energy > 0.2 {
+            0.6
+        } else {
+            0.2
+        };
+        score += thin_score * bright_score * self.config.energy_weight;
+
+        let zcr_score = if f.zcr > 0.2 && f.zcr < 0.5 { 1.0 } else { 0.4 };
+        score += zcr_score * self.config.zcr_weight;
+
+        let total_weight = self.config.centroid_weight
+            + self.config.energy_weight
+            + self.config.zcr_weight;
+
+        let mut final_score = (score / total_weight).clamp(0.0, 1.0);
+
+        // Only the sharpest ticks; an ordinary K stays a Click
+        if f.crest_factor < 5.0 {
+            final_score *= 0.6;
+        }
+
+        final_score.clamp(0.0, 1.0)
+    }
+
+    /// Score for Crash (KSSH / PSSH → crash cymbal)
+    /// Characteristics:
+    /// - High ZCR (noise)
+    /// - Broadband: real low+mid energy alongside the highs, unlike a hi-hat
+    /// - Centroid 1500-4500 Hz
+    /// - Sustained (crest factor below [`SUSTAINED_CREST`])
+    fn score_crash(&self, f: &EventFeatures) -> f32 {
+        let mut score = 0.0;
+
+        let zcr_score = if f.zcr > 0.3 {
+            1.0
+        } else if f.zcr > 0.2 {
+            0.6
+        } else {
+            0.1
+        };
+        score += zcr_score * self.config.zcr_weight;
+
+        // 1.0 when the highs and the low+mid body are balanced
+        let body = f.low_band_energy + f.mid_band_energy;
+        let breadth_score = (2.0 * body.min(f.high_band_energy)).clamp(0.0, 1.0);
+        score += breadth_score * self.config.energy_weight;
+
+        let centroid_score = if f.spectral_centroid > 1500.0 && f.spectral_centroid < 4500.0 {
+            1.0
+        } else {
+            0.3
+        };
+        score += centroid_score * self.config.centroid_weight;
+
+        let total_weight = self.config.centroid_weight
+            + self.config.energy_weight
+            + self.config.zcr_weight;
+
+        ((score / total_weight) * sustain_factor(f, 1.0, 0.4)).clamp(0.0, 1.0)
+    }
+}
+
+/// Crest factor below which a noisy hit counts as ringing rather than
+/// choked (open hat, crash). Closed hats and clicks sit around 3-8.
+pub const SUSTAINED_CREST: f32 = 2.8;
+
+/// Centroid (Hz) below which an onset is a mic bump, not a kick.
+pub const BUMP_MAX_CENTROID_HZ: f32 = 40.0;
+
+/// Crest factor at or above which an onset is a mic click or digital glitch.
+pub const GLITCH_MIN_CREST: f32 = 20.0;
+
+/// High-band energy share under which sustained noise is breath, not a hat.
+pub const BREATH_MAX_HIGH_BAND: f32 = 0.25;
+
+/// `sustained` for a ringing hit, `choked` otherwise (including an unknown,
+/// zero crest factor from legacy features).
+fn sustain_factor(f: &EventFeatures, sustained: f32, choked: f32) -> f32 {
+    if f.crest_factor > 0.0 && f.crest_factor < SUSTAINED_CREST {
+        sustained
+    } else {
+        choked
+    }
 }
 
 impl Default for HeuristicClassifier {
@@ -435,6 +590,94 @@ mod tests {
         assert!(result.confidence > 0.6);
     }
 
+    #[test]
+    fn test_open_hihat_classification() {
+        let classifier = HeuristicClassifier::new();
+
+        // The hi-hat above, left to ring
+        let features = EventFeatures {
+            spectral_centroid: 4500.0,
+            zcr: 0.45,
+            low_band_energy: 0.05,
+            mid_band_energy: 0.25,
+            high_band_energy: 0.7,
+            peak_amplitude: 0.6, crest_factor: 2.0,
+        };
+
+        let result = classifier.classify(&features);
+        assert_eq!(result.class, EventClass::OpenHihat);
+    }
+
+    #[test]
+    fn test_rimshot_classification() {
+        let classifier = HeuristicClassifier::new();
+
+        // A thin, bright tick with no low end and a very sharp attack
+        let features = EventFeatures {
+            spectral_centroid: 2800.0,
+            zcr: 0.3,
+            low_band_energy: 0.05,
+            mid_band_energy: 0.55,
+            high_band_energy: 0.4,
+            peak_amplitude: 0.5, crest_factor: 6.0,
+        };
+
+        let result = classifier.classify(&features);
+        assert_eq!(result.class, EventClass::Rimshot);
+    }
+
+    #[test]
+    fn test_crash_classification() {
+        let classifier = HeuristicClassifier::new();
+
+        // Broadband, sustained noise
+        let features = EventFeatures {
+            spectral_centroid: 2800.0,
+            zcr: 0.4,
+            low_band_energy: 0.15,
+            mid_band_energy: 0.4,
+            high_band_energy: 0.45,
+            peak_amplitude: 0.7, crest_factor: 2.2,
+        };
+
+        let result = classifier.classify(&features);
+        assert_eq!(result.class, EventClass::Crash);
+    }
+
+    #[test]
+    fn test_noise_rejection_rules() {
+        let classifier = HeuristicClassifier::new();
+        let kick = EventFeatures {
+            spectral_centroid: 80.0,
+            zcr: 0.01,
+            low_band_energy: 1.0,
+            mid_band_energy: 0.0,
+            high_band_energy: 0.0,
+            peak_amplitude: 0.8, crest_factor: 5.0,
+        };
+        assert_eq!(classifier.classify(&kick).class, EventClass::BilabialPlosive);
+
+        // A subsonic mic bump, a one-sample click, and a dark sustained breath
+        let bump = EventFeatures { spectral_centroid: 10.0, ..kick.clone() };
+        let click = EventFeatures { crest_factor: 45.0, ..kick.clone() };
+        let breath = EventFeatures {
+            spectral_centroid: 1800.0,
+            zcr: 0.3,
+            low_band_energy: 0.1,
+            mid_band_energy: 0.75,
+            high_band_energy: 0.15,
+            peak_amplitude: 0.2, crest_factor: 2.0,
+        };
+        for noise in [bump, click, breath] {
+            let result = classifier.classify(&noise);
+            assert_eq!(result.class, EventClass::Noise, "{noise:?}");
+            assert_eq!(result.confidence, 1.0);
+        }
+
+        // Silence and legacy features never trip a rule
+        assert!(!HeuristicClassifier::is_noise(&EventFeatures::zero()));
+    }
+
     #[test]
     fn test_all_scores_sum() {
         let classifier = HeuristicClassifier::new();
@@ -11,8 +11,17 @@
 // Gate calibration: on all 9,777 AVP percussive utterances, `crest < 2.2 &&
 // zcr < 0.15` fires on 4 (0.04%) — so the gate costs the percussive classes
 // essentially nothing while keeping HumVoiced reachable.
+//
+// The finer classes (open hat, rimshot, crash) refine a core family. A model
+// fitted with one of them (AVP's `hho` kept apart as OpenHihat) scores it
+// directly; for any the model lacks, the model picks the family and the
+// heuristic splits that family's posterior among its members.
+//
+// Onsets the heuristic's rejection rules call noise (mic bumps and clicks,
+// breaths) go to the heuristic too, which answers `Noise`; the Gaussian's
+// likelihood floor rejects whatever else lies far from every class.
 
-use crate::events::gaussian::{gaussian_features, GaussianModel, DEFAULT_MAP_TAU};
+use crate::events::gaussian::{gaussian_vector, GaussianModel, DEFAULT_MAP_TAU};
 use crate::events::heuristic::{ClassificationResult, HeuristicClassifier};
 use crate::events::types::{EventClass, EventFeatures};
 
@@ -52,7 +61,7 @@ impl HybridClassifier {
     }
 
     /// Hybrid over the factory model MAP-adapted from labeled user samples
-    /// (`(class, gaussian_feature_vector)` pairs, see [`gaussian_features`]).
+    /// (`(class, gaussian_feature_vector)` pairs, see [`gaussian_features`](crate::gaussian_features)).
     pub fn with_adaptation(samples: &[(EventClass, Vec<f32>)]) -> Self {
         Self::with_model(GaussianModel::factory().map_adapt(samples, DEFAULT_MAP_TAU))
     }
@@ -70,31 +79,44 @@ impl HybridClassifier {
     /// call sites and the explainability UI keep working unchanged. In the
     /// Gaussian branch, HumVoiced's score is 0 (the model cannot produce it).
     pub fn classify(&self, features: &EventFeatures, mfcc: &[f32]) -> ClassificationResult {
-        if Self::is_sustained(features) {
+        if HeuristicClassifier::is_noise(features) || Self::is_sustained(features) {
             return self.heuristic.classify(features);
         }
 
-        let x = gaussian_features(features, mfcc);
-        let (class, confidence, scores) = self.gaussian.classify(&x);
+        let x = gaussian_vector(features, mfcc);
+        // Posterior for the model's classes, 0.0 for HumVoiced (gated away
+        // above) and for refinements the model lacks, until the split below.
+        let (class, _, mut all_scores) = self.gaussian.classify_all(&x);
 
-        // Rebuild the fixed 4-slot score array (UI contract): posterior for
-        // the model's classes, 0.0 for HumVoiced (gated away above).
-        let score_of = |c: EventClass| {
-            scores
+        // Split the winning family's posterior among the members the model
+        // can't tell apart, in proportion to their heuristic scores.
+        let family = class.family();
+        let is_member =
+            |c: &EventClass| c.family() == family && (*c == class || !self.gaussian.classes.contains(c));
+        if EventClass::ALL.iter().filter(|c| is_member(c)).count() > 1 {
+            let heuristic = self.heuristic.classify(features).all_scores;
+            let weight = |c: EventClass| heuristic.iter().find(|(h, _)| *h == c).map_or(0.0, |h| h.1);
+            let total: f32 = EventClass::ALL.iter().filter(|c| is_member(c)).map(|&c| weight(c)).sum();
+            let posterior: f32 = EventClass::ALL
                 .iter()
-                .find(|s| s.class == c)
-                .map(|s| s.score)
-                .unwrap_or(0.0)
-        };
-        let all_scores = [
-            (
-                EventClass::BilabialPlosive,
-                score_of(EventClass::BilabialPlosive),
-            ),
-            (EventClass::HihatNoise, score_of(EventClass::HihatNoise)),
-            (EventClass::Click, score_of(EventClass::Click)),
-            (EventClass::HumVoiced, 0.0),
-        ];
+                .filter(|c| is_member(c))
+                .map(|&c| all_scores.iter().find(|(s, _)| *s == c).map_or(0.0, |s| s.1))
+                .sum();
+            if total > 0.0 {
+                for (c, score) in all_scores.iter_mut() {
+                    if is_member(c) {
+                        *score = posterior * weight(*c) / total;
+                    }
+                }
+            }
+        }
+
+        let (class, confidence) = all_scores
+            .iter()
+            .filter(|(c, _)| c.family() == family)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .copied()
+            .unwrap_or((class, 0.0));
 
         ClassificationResult {
             class,
@@ -160,6 +182,19 @@ mod tests {
         assert_eq!(hum_score, 0.0);
     }
 
+    #[test]
+    fn breaths_and_bumps_are_rejected() {
+        let clf = HybridClassifier::factory();
+        // Hissy but dark and flat: a breath, not a hat.
+        let breath = EventFeatures { zcr: 0.3, high_band_energy: 0.1, ..sustained_hum_features() };
+        // A mic stand knock: all sub-bass.
+        let bump = EventFeatures { spectral_centroid: 15.0, ..transient_features() };
+        for features in [breath, bump] {
+            let result = clf.classify(&features, &[0.0; 20]);
+            assert_eq!(result.class, EventClass::Noise, "{features:?}");
+        }
+    }
+
     #[test]
     fn gaussian_branch_scores_sum_to_one() {
         let clf = HybridClassifier::factory();
@@ -169,6 +204,50 @@ mod tests {
         assert!(result.confidence > 0.0 && result.confidence <= 1.0);
     }
 
+    #[test]
+    fn refinements_split_their_family_without_changing_it() {
+        let clf = HybridClassifier::factory();
+        // A hi-hat left to ring: whatever the MFCCs say, only the hat family
+        // shares out the model's hat posterior.
+        let ringing_hat = EventFeatures {
+            spectral_centroid: 4500.0,
+            zcr: 0.45,
+            low_band_energy: 0.05,
+            mid_band_energy: 0.25,
+            high_band_energy: 0.7,
+            peak_amplitude: 0.6,
+            crest_factor: 2.5,
+        };
+        let choked = EventFeatures { crest_factor: 6.0, ..ringing_hat.clone() };
+        let family_of = |r: &ClassificationResult| r.class.family();
+        let ringing = clf.classify(&ringing_hat, &[0.0; 20]);
+        let closed = clf.classify(&choked, &[0.0; 20]);
+        assert_eq!(family_of(&ringing), family_of(&closed), "the model picks the family");
+        let total: f32 = ringing.all_scores.iter().map(|(_, s)| s).sum();
+        assert!((total - 1.0).abs() < 1e-3, "scores sum to {total}");
+        if family_of(&ringing) == EventClass::HihatNoise {
+            assert_eq!(ringing.class, EventClass::OpenHihat);
+            assert_eq!(closed.class, EventClass::HihatNoise);
+        }
+    }
+
+    #[test]
+    fn a_model_with_the_refinement_scores_it_directly() {
+        let hats: Vec<(EventClass, Vec<f32>)> = (0..20)
+            .flat_map(|i| {
+                let j = i as f32 * 0.01;
+                [
+                    (EventClass::HihatNoise, vec![j; 22]),
+                    (EventClass::OpenHihat, vec![3.0 + j; 22]),
+                ]
+            })
+            .collect();
+        let clf = HybridClassifier::with_model(GaussianModel::fit(&hats).unwrap());
+        // Choked features, but the model says open: the model wins.
+        let f = EventFeatures { zcr: 3.0, crest_factor: 3.0, ..transient_features() };
+        assert_eq!(clf.classify(&f, &[3.0; 20]).class, EventClass::OpenHihat);
+    }
+
     #[test]
     fn adaptation_changes_the_model() {
         // Adapt the plosive mean toward a synthetic user's cluster and verify
@@ -1,19 +1,33 @@
 // Event detection types + classification
 //
-// The offline "brain": event types, the heuristic (rule-based) classifier, and
-// user calibration (kNN). Moved out of `src-tauri/src/events` so the same code
-// backs both the native pipeline and the WASM worklet. The Tauri-adjacent pieces
-// (`backend`, `explainability`) stay in the native crate; they depend on the
+// The offline "brain": event types, the classifiers (rule heuristic, Gaussian
+// MFCC model, their hybrid, user-calibrated kNN) behind one `EventClassifier`
+// trait and a by-name registry, multi-label decomposition of layered sounds,
+// and user calibration. Moved out of `src-tauri/src/events` so the same code
+// backs both the native pipeline and the WASM worklet. The Tauri-adjacent
+// piece (`explainability`) stays in the native crate; it depends on the
 // arranger/groove layers that are not part of the DSP core.
 
 pub mod calibration;
+pub mod classifier;
 pub mod gaussian;
 pub mod heuristic;
 pub mod hybrid;
+pub mod multilabel;
 pub mod types;
 
-pub use calibration::{CalibrationProfile, CalibrationSample, KnnClassifier};
-pub use gaussian::{gaussian_features, GaussianModel, DEFAULT_MAP_TAU, GAUSSIAN_DIMS};
+pub use calibration::{
+    CalibrationProfile, CalibrationReport, CalibrationSample, ClassQuality, ClassSeparability,
+    KnnClassifier, SampleQuality,
+};
+pub use classifier::{
+    build_classifier, classifier_entry, ClassifierEntry, ClassifierFactory, ClassifierInput,
+    EventClassifier, CLASSIFIERS, DEFAULT_CLASSIFIER,
+};
+pub use gaussian::{
+    gaussian_features, GaussianModel, DEFAULT_MAP_TAU, FACTORY_SAMPLE_RATE, GAUSSIAN_DIMS,
+};
 pub use heuristic::{ClassificationResult, ClassifierConfig, HeuristicClassifier};
 pub use hybrid::{HybridClassifier, HUM_GATE_MAX_CREST, HUM_GATE_MAX_ZCR};
-pub use types::{ClassScore, Event, EventClass, EventFeatures};
+pub use multilabel::MultiLabelConfig;
+pub use types::{ClassScore, Event, EventClass, EventData, EventFeatures, PitchPoint};
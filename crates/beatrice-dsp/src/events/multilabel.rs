@@ -0,0 +1,170 @@
+// Multi-label classification: several sounds on one onset
+//
+// Beatboxers layer sounds — a "B" with a hiss on top, a hat over a held hum —
+// but every classifier names exactly one class per onset. In multi-label mode
+// the offline pipeline reads the extra sounds off the same classification two
+// ways and emits one event per component:
+//
+// - Scores: the best class of every other family whose score clears
+//   `min_score`. The Gaussian posteriors split between the classes actually
+//   sounding, so a kick+hat mix scores well for both.
+// - Bands: a kick (or hum) whose window still carries a real share of energy
+//   above 4 kHz has a hat on top; a hat or snare carrying a real share below
+//   500 Hz has a kick under it. A kick's low end dominates the energy, so the
+//   score route alone rarely sees a soft hiss over it.
+//
+// At most one class per family is emitted (an open hat and a closed hat on
+// the same onset is one hat). A rejected onset stays a single `Noise` event,
+// and `Noise` is never a layer.
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::heuristic::ClassificationResult;
+use crate::events::types::{EventClass, EventFeatures};
+
+/// Multi-label settings (see [`crate::OnsetConfig::multi_label`]).
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MultiLabelConfig {
+    /// Smallest score a class from another family needs to be emitted beside
+    /// the winner. Tuned for posteriors that sum to one (the Gaussian, hybrid
+    /// and kNN classifiers); a hum's scores come from the heuristic and are
+    /// not used.
+    pub min_score: f32,
+    /// Share of the window's energy above 4 kHz that puts a hi-hat on top of
+    /// a kick or hum.
+    pub min_high_band: f32,
+    /// Share of the window's energy below 500 Hz that puts a kick under a
+    /// hi-hat or snare.
+    pub min_low_band: f32,
+}
+
+impl Default for MultiLabelConfig {
+    fn default() -> Self {
+        MultiLabelConfig {
+            // A lone kick still gives the hat family ~0.2 of the posterior.
+            min_score: 0.35,
+            // Clean kicks and hums sit at or under 0.02.
+            min_high_band: 0.1,
+            // Hats and snares sit at or under 0.03.
+            min_low_band: 0.3,
+        }
+    }
+}
+
+impl MultiLabelConfig {
+    /// The classes sounding in one event, each with its confidence: the
+    /// classifier's winner first, then any layered classes (scored by their
+    /// posterior, or by their band's energy share). At most one per family.
+    pub fn labels(
+        &self,
+        result: &ClassificationResult,
+        features: &EventFeatures,
+    ) -> Vec<(EventClass, f32)> {
+        let mut labels = vec![(result.class, result.confidence)];
+        if result.class.is_noise() {
+            return labels;
+        }
+        let mut add = |class: EventClass, score: f32| {
+            if !class.is_noise() && !labels.iter().any(|(c, _)| c.family() == class.family()) {
+                labels.push((class, score));
+            }
+        };
+
+        if result.class != EventClass::HumVoiced {
+            let mut ranked = result.all_scores;
+            ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+            for (class, score) in ranked {
+                if score >= self.min_score {
+                    add(class, score);
+                }
+            }
+        }
+
+        match result.class.family() {
+            EventClass::BilabialPlosive | EventClass::HumVoiced
+                if features.high_band_energy >= self.min_high_band =>
+            {
+                add(EventClass::HihatNoise, features.high_band_energy)
+            }
+            EventClass::HihatNoise | EventClass::Click
+                if features.low_band_energy >= self.min_low_band =>
+            {
+                add(EventClass::BilabialPlosive, features.low_band_energy)
+            }
+            _ => {}
+        }
+        labels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(class: EventClass, scores: &[(EventClass, f32)]) -> ClassificationResult {
+        let all_scores = EventClass::ALL
+            .map(|c| (c, scores.iter().find(|(s, _)| *s == c).map_or(0.0, |s| s.1)));
+        let confidence = all_scores.iter().find(|(c, _)| *c == class).unwrap().1;
+        ClassificationResult { class, confidence, all_scores }
+    }
+
+    fn bands(low: f32, mid: f32, high: f32) -> EventFeatures {
+        EventFeatures {
+            low_band_energy: low,
+            mid_band_energy: mid,
+            high_band_energy: high,
+            ..EventFeatures::zero()
+        }
+    }
+
+    #[test]
+    fn a_clean_hit_keeps_one_label() {
+        let kick = result(
+            EventClass::BilabialPlosive,
+            &[(EventClass::BilabialPlosive, 0.8), (EventClass::HihatNoise, 0.2)],
+        );
+        let labels = MultiLabelConfig::default().labels(&kick, &bands(1.0, 0.0, 0.0));
+        assert_eq!(labels, [(EventClass::BilabialPlosive, 0.8)]);
+    }
+
+    #[test]
+    fn split_posteriors_give_both_classes() {
+        let mix = result(
+            EventClass::HihatNoise,
+            &[(EventClass::HihatNoise, 0.5), (EventClass::BilabialPlosive, 0.4), (EventClass::Click, 0.1)],
+        );
+        let labels = MultiLabelConfig::default().labels(&mix, &bands(0.2, 0.2, 0.6));
+        assert_eq!(labels, [(EventClass::HihatNoise, 0.5), (EventClass::BilabialPlosive, 0.4)]);
+    }
+
+    #[test]
+    fn high_band_energy_puts_a_hat_on_a_kick() {
+        let kick = result(EventClass::BilabialPlosive, &[(EventClass::BilabialPlosive, 0.95)]);
+        let labels = MultiLabelConfig::default().labels(&kick, &bands(0.5, 0.3, 0.2));
+        assert_eq!(labels, [(EventClass::BilabialPlosive, 0.95), (EventClass::HihatNoise, 0.2)]);
+    }
+
+    #[test]
+    fn one_label_per_family() {
+        // The open hat's posterior already covers the hat family; the band
+        // rule does not add a closed hat beside it.
+        let mix = result(
+            EventClass::BilabialPlosive,
+            &[(EventClass::BilabialPlosive, 0.55), (EventClass::OpenHihat, 0.45)],
+        );
+        let labels = MultiLabelConfig::default().labels(&mix, &bands(0.4, 0.1, 0.5));
+        assert_eq!(labels, [(EventClass::BilabialPlosive, 0.55), (EventClass::OpenHihat, 0.45)]);
+    }
+
+    #[test]
+    fn hum_scores_are_not_posteriors() {
+        // The heuristic answers for a hum; its raw scores never add layers.
+        let hum = result(
+            EventClass::HumVoiced,
+            &[(EventClass::HumVoiced, 0.9), (EventClass::Click, 0.5), (EventClass::BilabialPlosive, 0.44)],
+        );
+        let labels = MultiLabelConfig::default().labels(&hum, &bands(0.65, 0.35, 0.01));
+        assert_eq!(labels, [(EventClass::HumVoiced, 0.9)]);
+    }
+}
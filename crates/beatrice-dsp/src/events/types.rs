@@ -28,9 +28,60 @@ pub enum EventClass {
     /// Triggers: pads, bass lines, melodic elements
     /// Characteristics: Sustained energy, lower ZCR, periodic/harmonic content
     HumVoiced,
+
+    /// Drawn-out TSSS sounds - an S/TS left to ring
+    /// Triggers: open hi-hat
+    /// Characteristics: Hi-hat spectrum with a slow decay (low crest factor)
+    OpenHihat,
+
+    /// Short, thin T/K sounds - a tongue tick or an unvoiced, breathless K
+    /// Triggers: rimshot / side stick, ghost snare
+    /// Characteristics: Click spectrum shifted up, with little low-band body
+    Rimshot,
+
+    /// KSSH / PSSH sounds - a plosive opening into a long hiss
+    /// Triggers: crash cymbal
+    /// Characteristics: Broadband noise (high ZCR with real low/mid energy),
+    /// sustained
+    Crash,
+
+    /// Breaths, coughs, laughs, mic bumps and clicks - onsets that are not
+    /// part of the performance
+    /// Triggers: nothing; kept in results (flagged) but left out of
+    /// quantization and arrangement by default
+    /// Characteristics: Far from every trained class, or caught by the
+    /// heuristic's rejection rules (subsonic thumps, digital clicks, dark
+    /// sustained breath noise)
+    Noise,
 }
 
 impl EventClass {
+    /// Number of classes.
+    pub const COUNT: usize = 8;
+
+    /// Every class, in declaration order (the fixed score-array order).
+    pub const ALL: [EventClass; EventClass::COUNT] = [
+        EventClass::BilabialPlosive,
+        EventClass::HihatNoise,
+        EventClass::Click,
+        EventClass::HumVoiced,
+        EventClass::OpenHihat,
+        EventClass::Rimshot,
+        EventClass::Crash,
+        EventClass::Noise,
+    ];
+
+    /// The coarse class a finer one refines (an open hat is still a hi-hat,
+    /// a rimshot a snare-family click, a crash a cymbal hiss); the four core
+    /// classes and `Noise` are their own family.
+    pub fn family(&self) -> EventClass {
+        match self {
+            EventClass::OpenHihat | EventClass::Crash => EventClass::HihatNoise,
+            EventClass::Rimshot => EventClass::Click,
+            other => *other,
+        }
+    }
+
     /// Convert from string representation (for serialization)
     /// Accepts both PascalCase and snake_case for backwards compatibility
     pub fn from_string(s: &str) -> Self {
@@ -39,6 +90,10 @@ impl EventClass {
             "HihatNoise" | "hihat_noise" => EventClass::HihatNoise,
             "Click" | "click" => EventClass::Click,
             "HumVoiced" | "hum_voiced" => EventClass::HumVoiced,
+            "OpenHihat" | "open_hihat" => EventClass::OpenHihat,
+            "Rimshot" | "rimshot" => EventClass::Rimshot,
+            "Crash" | "crash" => EventClass::Crash,
+            "Noise" | "noise" => EventClass::Noise,
             _ => EventClass::Click, // Default fallback
         }
     }
@@ -50,6 +105,10 @@ impl EventClass {
             EventClass::HihatNoise => "HihatNoise",
             EventClass::Click => "Click",
             EventClass::HumVoiced => "HumVoiced",
+            EventClass::OpenHihat => "OpenHihat",
+            EventClass::Rimshot => "Rimshot",
+            EventClass::Crash => "Crash",
+            EventClass::Noise => "Noise",
         }
     }
 
@@ -60,8 +119,17 @@ impl EventClass {
             EventClass::HihatNoise => "S/TS (Hi-hat)",
             EventClass::Click => "T/K (Snare)",
             EventClass::HumVoiced => "Hum (Pad)",
+            EventClass::OpenHihat => "TSSS (Open hat)",
+            EventClass::Rimshot => "Tk (Rimshot)",
+            EventClass::Crash => "KSSH (Crash)",
+            EventClass::Noise => "Noise (ignored)",
         }
     }
+
+    /// Whether this is the reject outcome rather than a musical class.
+    pub fn is_noise(&self) -> bool {
+        *self == EventClass::Noise
+    }
 }
 
 /// Per-class classification score for a single event.
@@ -149,6 +217,28 @@ impl EventFeatures {
     }
 }
 
+/// One voiced frame of an event's pitch contour
+/// (see [`crate::features::track_pitch`]).
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PitchPoint {
+    /// Frame centre in milliseconds from the event's onset
+    pub time_ms: f64,
+
+    /// Fundamental frequency (Hz)
+    pub f0_hz: f32,
+
+    /// Probability the frame is voiced at this pitch [0.0, 1.0]
+    pub confidence: f32,
+}
+
+impl PitchPoint {
+    /// The pitch as a fractional MIDI note number (A4 = 440 Hz = 69).
+    pub fn midi_note(&self) -> f32 {
+        69.0 + 12.0 * (self.f0_hz / 440.0).log2()
+    }
+}
+
 /// A detected beatbox event with timing, classification, and features
 #[cfg_attr(feature = "specta", derive(specta::Type))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -159,10 +249,17 @@ pub struct Event {
     /// Timestamp in milliseconds from start of audio
     pub timestamp_ms: f64,
 
-    /// Duration of event in milliseconds
-    /// Calculated from onset to next onset or energy decay
+    /// Duration of event in milliseconds: how long the sound lasted,
+    /// measured from its energy decay (and, for a hum, its voicing). Never
+    /// longer than `gap_ms`.
     pub duration_ms: f64,
 
+    /// Milliseconds from this onset to the next one (or to the end of the
+    /// audio for the last event). `0.0` for older persisted rows
+    /// (`serde(default)`), which stored the gap as `duration_ms`.
+    #[serde(default)]
+    pub gap_ms: f64,
+
     /// Classified event type
     pub class: EventClass,
 
@@ -180,10 +277,17 @@ pub struct Event {
     /// empty for backward compatibility with older persisted rows (`serde(default)`).
     #[serde(default)]
     pub all_scores: Vec<ClassScore>,
+
+    /// Hummed pitch over the event, voiced frames only. Tracked for
+    /// `HumVoiced` events by the offline pipelines; empty for every other
+    /// class, for live events, and for older persisted rows (`serde(default)`).
+    #[serde(default)]
+    pub pitch_contour: Vec<PitchPoint>,
 }
 
 impl Event {
-    /// Create a new event with generated UUID
+    /// Create a new event with generated UUID. The gap to the next onset
+    /// starts out equal to `duration_ms`; see [`with_gap`](Self::with_gap).
     pub fn new(
         timestamp_ms: f64,
         duration_ms: f64,
@@ -195,10 +299,12 @@ impl Event {
             id: Uuid::new_v4(),
             timestamp_ms,
             duration_ms,
+            gap_ms: duration_ms,
             class,
             confidence,
             features,
             all_scores: Vec::new(),
+            pitch_contour: Vec::new(),
         }
     }
 
@@ -210,6 +316,86 @@ impl Event {
         self.all_scores = scores;
         self
     }
+
+    /// Set the gap to the next onset (builder style).
+    pub fn with_gap(mut self, gap_ms: f64) -> Self {
+        self.gap_ms = gap_ms;
+        self
+    }
+
+    /// Attach a pitch contour (builder style).
+    pub fn with_pitch_contour(mut self, contour: Vec<PitchPoint>) -> Self {
+        self.pitch_contour = contour;
+        self
+    }
+
+    /// Whether the onset was rejected as [`EventClass::Noise`]: kept in the
+    /// results for review, but not quantized or arranged by default.
+    pub fn is_rejected(&self) -> bool {
+        self.class.is_noise()
+    }
+}
+
+/// An [`Event`] as it crosses to the frontend and back: the id and class as
+/// strings. Returned by event detection and sent back to quantize and to
+/// save decisions.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventData {
+    pub id: String,
+    pub timestamp_ms: f64,
+    /// Measured sound length (energy decay / voicing), at most `gap_ms`.
+    pub duration_ms: f64,
+    /// Time to the next onset. `serde(default)` for callers that predate
+    /// measured durations and only sent the gap (as `duration_ms`).
+    #[serde(default)]
+    pub gap_ms: f64,
+    pub class: String,
+    pub confidence: f32,
+    pub features: EventFeatures,
+    /// Per-class classifier scores. `serde(default)` so older callers/rows that
+    /// predate score threading still deserialize (empty vec).
+    #[serde(default)]
+    pub all_scores: Vec<ClassScore>,
+    /// Hummed pitch contour (`HumVoiced` events only; empty otherwise and for
+    /// callers that predate pitch tracking).
+    #[serde(default)]
+    pub pitch_contour: Vec<PitchPoint>,
+}
+
+impl EventData {
+    /// The event this describes. An id that is not a UUID (a mock or
+    /// hand-written event) gets a fresh one; an unknown class reads as
+    /// [`EventClass::from_string`] does.
+    pub fn to_event(&self) -> Event {
+        Event {
+            id: Uuid::parse_str(&self.id).unwrap_or_else(|_| Uuid::new_v4()),
+            timestamp_ms: self.timestamp_ms,
+            duration_ms: self.duration_ms,
+            gap_ms: self.gap_ms,
+            class: EventClass::from_string(&self.class),
+            confidence: self.confidence,
+            features: self.features.clone(),
+            all_scores: self.all_scores.clone(),
+            pitch_contour: self.pitch_contour.clone(),
+        }
+    }
+}
+
+impl From<&Event> for EventData {
+    fn from(e: &Event) -> Self {
+        EventData {
+            id: e.id.to_string(),
+            timestamp_ms: e.timestamp_ms,
+            duration_ms: e.duration_ms,
+            gap_ms: e.gap_ms,
+            class: e.class.to_string().to_string(),
+            confidence: e.confidence,
+            features: e.features.clone(),
+            all_scores: e.all_scores.clone(),
+            pitch_contour: e.pitch_contour.clone(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -218,10 +404,23 @@ mod tests {
 
     #[test]
     fn test_event_class_round_trip() {
-        let class = EventClass::BilabialPlosive;
-        let s = class.to_string();
-        let parsed = EventClass::from_string(s);
-        assert_eq!(class, parsed);
+        for class in EventClass::ALL {
+            let s = class.to_string();
+            let parsed = EventClass::from_string(s);
+            assert_eq!(class, parsed);
+        }
+        assert_eq!(EventClass::from_string("open_hihat"), EventClass::OpenHihat);
+    }
+
+    #[test]
+    fn refined_classes_belong_to_a_core_family() {
+        for class in EventClass::ALL.into_iter().filter(|c| !c.is_noise()) {
+            assert!(EventClass::ALL[..4].contains(&class.family()), "{class:?}");
+        }
+        assert_eq!(EventClass::Crash.family(), EventClass::HihatNoise);
+        assert_eq!(EventClass::Rimshot.family(), EventClass::Click);
+        assert_eq!(EventClass::Noise.family(), EventClass::Noise);
+        assert!(Event::new(0.0, 10.0, EventClass::Noise, 0.9, EventFeatures::zero()).is_rejected());
     }
 
     #[test]
@@ -258,4 +457,14 @@ mod tests {
         assert_eq!(event.class, EventClass::Click);
         assert_eq!(event.confidence, 0.9);
     }
+
+    #[test]
+    fn event_data_round_trips() {
+        let event = Event::new(100.0, 50.0, EventClass::OpenHihat, 0.9, EventFeatures::zero()).with_gap(80.0);
+        let back = EventData::from(&event).to_event();
+        assert_eq!((back.id, back.class, back.gap_ms), (event.id, event.class, 80.0));
+
+        let mock = EventData { id: "mock-event-0".to_string(), class: "nope".to_string(), ..EventData::from(&event) };
+        assert_eq!(mock.to_event().class, EventClass::Click);
+    }
 }
@@ -1,11 +1,17 @@
 // Spectral feature extraction and onset detection
-// Implements Spectral Flux (Superflux algorithm) for onset detection
+// Picks onsets on a selectable detection function (see `onset_function`)
 // and extracts features for event classification
 
-use realfft::RealFftPlanner;
-
+use crate::channels::{self, ChannelStrategy};
+use crate::conditioning::{self, ConditioningConfig};
+use crate::noise::NoiseProfile;
+use crate::localize::{self, envelope_block, HOLD_BLOCKS};
+use crate::onset_function::{OnsetDetectionFunction, OnsetFunction};
 use crate::ingest::AudioData;
-use crate::events::types::EventFeatures;
+use crate::events::multilabel::MultiLabelConfig;
+use crate::events::types::{EventFeatures, PitchPoint};
+use crate::spectrogram::{self, Spectrogram};
+use realfft::RealFftPlanner;
 
 /// Onset detection result
 #[derive(Debug, Clone)]
@@ -40,8 +46,32 @@ pub struct OnsetConfig {
     /// Minimum spectral flux value required for an onset candidate.
     /// Frames with flux below this absolute threshold are ignored
     /// regardless of the adaptive threshold. This gates out low-energy
-    /// noise between beats.
+    /// noise between beats. In the units of `onset_function`.
     pub min_flux_threshold: f32,
+
+    /// Detection function onsets are picked on. The default is the
+    /// historical rectified flux with its parallel energy detector; the
+    /// benchmark scores every function so one can be chosen per room.
+    pub onset_function: OnsetFunction,
+
+    /// How multi-channel audio is reduced to the signal(s) onsets are
+    /// detected and (in the hybrid pipeline) features are extracted on.
+    pub channels: ChannelStrategy,
+
+    /// Optional DC/high-pass/denoise/normalize chain run on each analysed
+    /// signal before onsets and features are taken from it. `None` analyses
+    /// the audio as recorded.
+    pub conditioning: Option<ConditioningConfig>,
+
+    /// Room-tone floor the conditioning denoiser starts from (see
+    /// [`NoiseProfile::adapt_onset_config`]). Ignored without noise
+    /// reduction.
+    pub noise_profile: Option<NoiseProfile>,
+
+    /// Multi-label mode: the hybrid pipeline emits one event per sound layered
+    /// on an onset (a kick with a hat on top is two events at the same time).
+    /// `None` keeps one event per onset.
+    pub multi_label: Option<MultiLabelConfig>,
 }
 
 impl Default for OnsetConfig {
@@ -52,10 +82,18 @@ impl Default for OnsetConfig {
             threshold_factor: 2.0, // Require flux to be 2.0 std devs above mean
             min_onset_gap_ms: 120.0, // Increased to 120ms to prevent double-triggers on human plosives
             min_flux_threshold: 0.0, // Computed dynamically if left at 0.0
+            onset_function: OnsetFunction::SpectralFlux,
+            channels: ChannelStrategy::Average,
+            conditioning: None,
+            noise_profile: None,
+            multi_label: None,
         }
     }
 }
 
+/// Longest frame [`extract_features`] takes its spectrum over.
+pub(crate) const FEATURE_FRAME: usize = 2048;
+
 /// Extract spectral features from an audio segment
 /// Used for event classification
 pub fn extract_features(
@@ -66,6 +104,47 @@ pub fn extract_features(
         return EventFeatures::zero();
     }
 
+    // Spectral features from one Hann-windowed frame at the window's start
+    let window_size = samples.len().min(FEATURE_FRAME);
+    let (spectral_centroid, band_energies) = spectrogram::with_plans(|plans| {
+        let (_, magnitudes) = plans.windowed(&samples[..window_size], window_size);
+        (
+            calculate_spectral_centroid(magnitudes, sample_rate, window_size),
+            calculate_band_energies(magnitudes, sample_rate, window_size),
+        )
+    });
+    with_spectral(samples, spectral_centroid, band_energies)
+}
+
+/// [`extract_features`] and [`extract_mfcc`] of one window, with the
+/// centroid, band energies and mel frames read off [`Spectrogram`]s of it.
+/// The offline analysis path.
+///
+/// The window gets two framings, because the heuristic was tuned on one
+/// frame of up to [`FEATURE_FRAME`] samples and the factory model on
+/// [`MFCC_FRAME`]-sample frames; what they share is this thread's plans,
+/// Hann tables and the filterbank.
+pub fn extract_window(samples: &[f32], sample_rate: u32) -> (EventFeatures, Vec<f32>) {
+    if samples.is_empty() || sample_rate == 0 {
+        return (extract_features(samples, sample_rate), vec![0.0; MFCC_COEFFS]);
+    }
+    let window_size = samples.len().min(FEATURE_FRAME);
+    let frame = Spectrogram::new(&samples[..window_size], sample_rate, window_size, window_size);
+    let features = with_spectral(samples, frame.centroid(0), frame.band_energies(0));
+
+    let bank = MelFilterbank::for_mfcc(sample_rate);
+    let frames = Spectrogram::covering(samples, sample_rate, MFCC_FRAME, MFCC_HOP);
+    let mut sums = [0.0f64; MFCC_COEFFS];
+    let mut sumsqs = [0.0f64; MFCC_COEFFS];
+    for i in 0..frames.len() {
+        accumulate_frame(&frames.mel_energies(i, &bank), &mut sums, &mut sumsqs);
+    }
+    let mfcc = sums.iter().map(|&s| (s / frames.len() as f64) as f32).collect();
+    (features, mfcc)
+}
+
+/// The time-domain features of `samples` beside the given spectral ones.
+fn with_spectral(samples: &[f32], spectral_centroid: f32, band_energies: [f32; 3]) -> EventFeatures {
     // Calculate peak amplitude (loudness indicator for velocity/dynamics)
     let peak_amplitude = samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
 
@@ -77,12 +156,8 @@ pub fn extract_features(
     // Calculate Zero-Crossing Rate
     let zcr = calculate_zcr(samples);
 
-    // Calculate spectral features using FFT
-    let window_size = samples.len().min(2048);
-    let (centroid, band_energies) = calculate_spectral_features(samples, sample_rate, window_size);
-
     EventFeatures {
-        spectral_centroid: centroid,
+        spectral_centroid,
         zcr,
         low_band_energy: band_energies[0],
         mid_band_energy: band_energies[1],
@@ -116,33 +191,6 @@ fn calculate_zcr(samples: &[f32]) -> f32 {
     crossings as f32 / denominator as f32
 }
 
-/// Calculate spectral centroid and band energies
-/// Returns (centroid in Hz, [low, mid, high] energy ratios)
-fn calculate_spectral_features(
-    samples: &[f32],
-    sample_rate: u32,
-    window_size: usize,
-) -> (f32, [f32; 3]) {
-    // Pad or truncate to window size
-    let mut windowed = vec![0.0; window_size];
-    let copy_len = samples.len().min(window_size);
-    windowed[..copy_len].copy_from_slice(&samples[..copy_len]);
-
-    // Apply Hann window to reduce spectral leakage
-    apply_hann_window(&mut windowed);
-
-    // Compute FFT
-    let spectrum = compute_fft(&windowed);
-
-    // Calculate spectral centroid
-    let centroid = calculate_spectral_centroid(&spectrum, sample_rate, window_size);
-
-    // Calculate band energies
-    let band_energies = calculate_band_energies(&spectrum, sample_rate, window_size);
-
-    (centroid, band_energies)
-}
-
 /// Apply Hann window function to reduce spectral leakage.
 ///
 /// Exposed to the crate so the causal [`crate::streaming::StreamingDetector`]
@@ -166,18 +214,10 @@ pub(crate) fn apply_hann_window(samples: &mut [f32]) {
 /// Compute real FFT and return magnitude spectrum.
 ///
 /// Exposed to the crate so the streaming detector's per-frame flux uses the
-/// identical `realfft` magnitude spectrum as the offline path.
+/// identical `realfft` magnitude spectrum as the offline path. Plans come from
+/// the thread's [`spectrogram::FftPlans`].
 pub(crate) fn compute_fft(samples: &[f32]) -> Vec<f32> {
-    let mut planner = RealFftPlanner::<f32>::new();
-    let fft = planner.plan_fft_forward(samples.len());
-
-    let mut input = samples.to_vec();
-    let mut spectrum = fft.make_output_vec();
-
-    fft.process(&mut input, &mut spectrum).unwrap();
-
-    // Convert complex spectrum to magnitudes
-    spectrum.iter().map(|c| c.norm()).collect()
+    spectrogram::with_plans(|plans| plans.spectrum(samples).iter().map(|c| c.norm()).collect())
 }
 
 /// Calculate spectral centroid (center of mass of spectrum)
@@ -185,7 +225,7 @@ pub(crate) fn compute_fft(samples: &[f32]) -> Vec<f32> {
 /// Without energy weighting, broadband noise across ~1000 FFT bins overwhelms
 /// the few concentrated signal peaks, pulling the centroid to ~Nyquist/2.
 /// Returns frequency in Hz
-fn calculate_spectral_centroid(spectrum: &[f32], sample_rate: u32, window_size: usize) -> f32 {
+pub(crate) fn calculate_spectral_centroid(spectrum: &[f32], sample_rate: u32, window_size: usize) -> f32 {
     // Guard against zero window size
     if window_size == 0 {
         return 0.0;
@@ -212,7 +252,7 @@ fn calculate_spectral_centroid(spectrum: &[f32], sample_rate: u32, window_size:
 
 /// Calculate energy in frequency bands: low (0-200 Hz), mid (200-2000 Hz), high (2000+ Hz)
 /// Returns normalized energy ratios [low, mid, high]
-fn calculate_band_energies(spectrum: &[f32], sample_rate: u32, window_size: usize) -> [f32; 3] {
+pub(crate) fn calculate_band_energies(spectrum: &[f32], sample_rate: u32, window_size: usize) -> [f32; 3] {
     // Guard against zero window size
     if window_size == 0 {
         return [0.0, 0.0, 0.0];
@@ -263,54 +303,257 @@ fn calculate_band_energies(spectrum: &[f32], sample_rate: u32, window_size: usiz
     }
 }
 
-/// Detect onsets using Spectral Flux (Superflux algorithm)
+/// Detect onsets by peak picking `config.onset_function` (rectified spectral
+/// flux plus an RMS energy detector by default; see [`OnsetFunction`])
 /// Returns list of onset timestamps and strengths
+///
+/// Each picked frame is refined to the transient inside it (see
+/// [`crate::localize`]), so timestamps are where the sound starts rather than
+/// the analysis window's left edge.
+///
+/// Multi-channel audio is reduced per `config.channels`; a multi-lane
+/// strategy detects on each lane and merges the hits.
 pub fn detect_onsets(audio: &AudioData, config: &OnsetConfig) -> Vec<Onset> {
-    // Convert to mono for onset detection
-    let mono = audio.to_mono();
+    detect_onsets_keeping_mix(audio, config).0
+}
 
-    if mono.is_empty() {
-        return Vec::new();
+/// [`detect_onsets`], also handing back the signal it ran on when that is
+/// the (conditioned) channel average — the signal the offline features read —
+/// so [`crate::AnalysisContext`] does not mix and condition it twice.
+pub(crate) fn detect_onsets_keeping_mix(audio: &AudioData, config: &OnsetConfig) -> (Vec<Onset>, Option<Vec<f32>>) {
+    let energies = config.channels.needs_energies().then(|| {
+        let mut energies = vec![0.0; audio.channels.max(1) as usize];
+        channels::accumulate_energies(&audio.samples, audio.channels, &mut energies);
+        energies
+    });
+    let lanes = config.channels.lanes(audio.channels, energies.as_deref());
+
+    let mut mono = Vec::new();
+    let per_lane: Vec<Vec<Onset>> = lanes
+        .iter()
+        .map(|lane| {
+            lane.mix_into(&audio.samples, audio.channels, &mut mono);
+            if let Some(conditioning) = &config.conditioning {
+                mono = conditioning::condition_with_noise(
+                    &mono,
+                    audio.sample_rate,
+                    conditioning,
+                    config.noise_profile.as_ref(),
+                );
+            }
+
+            // Fed in chunks so the scanner's tail buffer stays one window long.
+            let mut scanner = OnsetScanner::new(audio.sample_rate, config.clone());
+            for chunk in mono.chunks(1 << 16) {
+                scanner.push(chunk);
+            }
+            scanner.finish()
+        })
+        .collect();
+
+    if per_lane.len() == 1 {
+        let average = audio.channels == 1 || lanes == [channels::Lane::Average];
+        return (per_lane.into_iter().next().unwrap_or_default(), average.then_some(mono));
     }
+    let onsets = channels::merge_onsets(per_lane, config.min_onset_gap_ms)
+        .into_iter()
+        .map(|(_, onset)| onset)
+        .collect();
+    (onsets, None)
+}
 
-    // Check for energy at the very start of the audio.
-    let leading_onset = detect_leading_onset(&mono, audio.sample_rate);
+/// Incremental front half of [`detect_onsets`], for audio too long to hold in
+/// memory.
+///
+/// Mono samples are pushed in chunks of any size; the scanner keeps only the
+/// per-hop detection-function and RMS energy envelopes (one `f32` each per hop),
+/// a ~1ms peak envelope for sub-frame localization (~1/44th of the audio at
+/// 44.1kHz) plus one analysis window of tail. Peak picking needs
+/// whole-recording statistics, so it runs in [`OnsetScanner::finish`] over the
+/// complete envelopes: the onsets are identical to [`detect_onsets`] however
+/// the stream was chunked.
+pub struct OnsetScanner {
+    config: OnsetConfig,
+    sample_rate: u32,
+    /// Unconsumed tail of the stream; `buf[0]` is absolute sample `buf_start`.
+    buf: Vec<f32>,
+    buf_start: usize,
+    total: usize,
+    /// Absolute start of the next flux frame / energy frame / envelope block.
+    next_flux: usize,
+    next_energy: usize,
+    next_block: usize,
+    odf: OnsetDetectionFunction,
+    flux: Vec<f32>,
+    energies: Vec<f32>,
+    /// Fine peak envelope the picked frames are localized on.
+    envelope: Vec<f32>,
+    /// `None` until the first 10ms have been seen.
+    leading: Option<Option<Onset>>,
+}
 
-    // Compute spectral flux across all frames
-    let flux = compute_spectral_flux(&mono, audio.sample_rate, config);
+impl OnsetScanner {
+    pub fn new(sample_rate: u32, config: OnsetConfig) -> Self {
+        let odf = OnsetDetectionFunction::new(
+            config.onset_function,
+            sample_rate,
+            config.window_size,
+            config.hop_size,
+        );
+        OnsetScanner {
+            config,
+            sample_rate,
+            buf: Vec::new(),
+            buf_start: 0,
+            total: 0,
+            next_flux: 0,
+            next_energy: 0,
+            next_block: 0,
+            odf,
+            flux: Vec::new(),
+            energies: Vec::new(),
+            envelope: Vec::new(),
+            leading: None,
+        }
+    }
 
-    if flux.is_empty() {
-        return leading_onset.into_iter().collect();
+    /// Mono samples consumed so far.
+    pub fn samples_seen(&self) -> usize {
+        self.total
     }
 
-    // Spectral flux onset detection (good for mid/high-frequency transients)
-    let mut onsets = pick_onset_peaks(&flux, audio.sample_rate, config);
+    /// Feed the next chunk of mono samples.
+    pub fn push(&mut self, mono: &[f32]) {
+        self.buf.extend_from_slice(mono);
+        self.total += mono.len();
+
+        // Check for energy at the very start of the audio.
+        if self.leading.is_none() {
+            let window_samples = (self.sample_rate as f64 * 0.01) as usize; // 10ms
+            if window_samples == 0 {
+                self.leading = Some(None);
+            } else if self.total >= window_samples {
+                self.leading = Some(detect_leading_onset(&self.buf, self.sample_rate));
+            }
+        }
+
+        let hop_size = self.config.hop_size;
+        // Guard against zero hop size
+        if hop_size == 0 {
+            return;
+        }
 
-    // Broadband energy onset detection (catches low-frequency transients like kicks)
-    // Spectral flux is biased toward high-frequency changes because high bins outnumber
-    // low bins. This parallel detector uses RMS energy in short windows to catch
-    // amplitude transients at any frequency.
-    let energy_onsets = detect_energy_onsets(&mono, audio.sample_rate, config);
-    for eo in energy_onsets {
-        // Only add if not too close to an existing onset
-        let too_close = onsets.iter().any(|o| (o.timestamp_ms - eo.timestamp_ms).abs() < config.min_onset_gap_ms);
-        if !too_close {
-            onsets.push(eo);
+        // One detection value per frame (spectral flux by default: the sum of
+        // positive differences between consecutive magnitude spectra)
+        let window_size = self.config.window_size;
+        if self.next_flux + window_size <= self.total {
+            let start = self.next_flux - self.buf_start;
+            let frames = Spectrogram::new(&self.buf[start..], self.sample_rate, window_size, hop_size);
+            for i in 0..frames.len() {
+                self.flux.push(self.odf.process_spectrum(frames.spectrum(i), frames.magnitudes(i)));
+            }
+            self.next_flux += frames.len() * hop_size;
+        }
+
+        // RMS energy per frame for the broadband energy detector
+        let energy_window = hop_size * 2; // ~23ms at 44.1kHz with hop=512
+        while self.next_energy + energy_window <= self.total {
+            let start = self.next_energy - self.buf_start;
+            let frame = &self.buf[start..start + energy_window];
+            let rms = (frame.iter().map(|s| s * s).sum::<f32>() / energy_window as f32).sqrt();
+            self.energies.push(rms);
+            self.next_energy += hop_size;
+        }
+
+        // Fine envelope for localizing the picked frames
+        let block = envelope_block(self.sample_rate);
+        while self.next_block + block <= self.total {
+            let start = self.next_block - self.buf_start;
+            self.envelope.extend(localize::block_peaks(&self.buf[start..start + block], block));
+            self.next_block += block;
+        }
+
+        // Drop samples no pending frame will read (the leading-onset check
+        // reads from sample 0, so keep everything until it has run).
+        if self.leading.is_some() {
+            let keep_from = self.next_flux.min(self.next_energy).min(self.next_block).min(self.total);
+            self.buf.drain(..keep_from - self.buf_start);
+            self.buf_start = keep_from;
         }
     }
 
-    // Sort by timestamp
-    onsets.sort_by(|a, b| a.timestamp_ms.partial_cmp(&b.timestamp_ms).unwrap());
+    /// Pick onsets over the complete envelopes.
+    pub fn finish(mut self) -> Vec<Onset> {
+        let config = &self.config;
+        if self.total == 0 {
+            return Vec::new();
+        }
+
+        let leading_onset = self.leading.take().flatten();
+
+        if self.flux.is_empty() {
+            return leading_onset.into_iter().collect();
+        }
+
+        // Detection-function peaks (flux is good for mid/high-frequency transients)
+        let mut onsets = pick_onset_peaks(&self.flux, self.sample_rate, config);
+        for onset in &mut onsets {
+            self.localize(onset, config.window_size);
+        }
+
+        // Broadband energy onset detection (catches low-frequency transients like kicks)
+        // Spectral flux is biased toward high-frequency changes because high bins outnumber
+        // low bins. This parallel detector uses RMS energy in short windows to catch
+        // amplitude transients at any frequency. Functions that already weigh the low
+        // end (SuperFlux, multi-band) run without it.
+        let energy_onsets = if config.onset_function.uses_energy_fallback() {
+            pick_energy_onsets(&self.energies, self.sample_rate, config)
+        } else {
+            Vec::new()
+        };
+        for mut eo in energy_onsets {
+            self.localize(&mut eo, config.hop_size * 2);
+            // Only add if not too close to an existing onset
+            let too_close = onsets.iter().any(|o| (o.timestamp_ms - eo.timestamp_ms).abs() < config.min_onset_gap_ms);
+            if !too_close {
+                onsets.push(eo);
+            }
+        }
+
+        // Sort by timestamp
+        onsets.sort_by(|a, b| a.timestamp_ms.partial_cmp(&b.timestamp_ms).unwrap());
 
-    // Prepend the leading onset if it doesn't overlap with the first detected onset
-    if let Some(leading) = leading_onset {
-        let too_close = onsets.first().is_some_and(|first| first.timestamp_ms < config.min_onset_gap_ms);
-        if !too_close {
-            onsets.insert(0, leading);
+        // Prepend the leading onset if it doesn't overlap with the first detected onset
+        if let Some(leading) = leading_onset {
+            let too_close = onsets.first().is_some_and(|first| first.timestamp_ms < config.min_onset_gap_ms);
+            if !too_close {
+                onsets.insert(0, leading);
+            }
         }
+
+        onsets
     }
 
-    onsets
+    /// Move an onset reported at its frame's left edge to the transient the
+    /// `frame_len`-sample frame reacted to. The search also covers the hop
+    /// before the frame, which the detection function compared against.
+    fn localize(&self, onset: &mut Onset, frame_len: usize) {
+        if self.sample_rate == 0 {
+            return;
+        }
+        let sr = self.sample_rate as f64;
+        let frame_start = (onset.timestamp_ms * sr / 1000.0).round() as usize;
+        let block = envelope_block(self.sample_rate);
+        let first = frame_start.saturating_sub(self.config.hop_size) / block;
+        let history = first.saturating_sub(HOLD_BLOCKS - 1);
+        let last = (frame_start + frame_len).div_ceil(block).min(self.envelope.len());
+        if first >= last {
+            return;
+        }
+        if let Some(b) = localize::locate_rise(&self.envelope[history..last], first - history) {
+            onset.timestamp_ms = ((history + b) * block) as f64 * 1000.0 / sr;
+        }
+    }
 }
 
 /// Check if audio starts with significant energy (onset at t=0).
@@ -338,30 +581,15 @@ fn detect_leading_onset(mono: &[f32], sample_rate: u32) -> Option<Onset> {
 /// Complements spectral flux by catching low-frequency transients (kicks/bass)
 /// that spectral flux misses due to high-frequency bin count bias.
 ///
-/// Algorithm: compute RMS in short windows, find frames where energy jumps
-/// significantly above the local average (using a ratio threshold).
-fn detect_energy_onsets(samples: &[f32], sample_rate: u32, config: &OnsetConfig) -> Vec<Onset> {
+/// Algorithm: given RMS in short windows (computed by [`OnsetScanner`]), find
+/// frames where energy jumps significantly above the local average (using a
+/// ratio threshold).
+fn pick_energy_onsets(energies: &[f32], sample_rate: u32, config: &OnsetConfig) -> Vec<Onset> {
     let hop_size = config.hop_size;
-    let window_size = config.hop_size * 2; // ~23ms at 44.1kHz with hop=512
-
-    if samples.len() < window_size || hop_size == 0 {
-        return Vec::new();
-    }
-
-    let num_frames = (samples.len() - window_size) / hop_size + 1;
-    if num_frames < 3 {
+    if energies.len() < 3 || hop_size == 0 {
         return Vec::new();
     }
 
-    // Compute RMS energy per frame
-    let mut energies: Vec<f32> = Vec::with_capacity(num_frames);
-    for i in 0..num_frames {
-        let start = i * hop_size;
-        let end = (start + window_size).min(samples.len());
-        let rms = (samples[start..end].iter().map(|s| s * s).sum::<f32>() / (end - start) as f32).sqrt();
-        energies.push(rms);
-    }
-
     // Compute local average energy using a sliding window of ~200ms
     let avg_window = (sample_rate as usize / hop_size / 5).max(3); // ~200ms
     let mut onsets = Vec::new();
@@ -394,66 +622,6 @@ fn detect_energy_onsets(samples: &[f32], sample_rate: u32, config: &OnsetConfig)
     onsets
 }
 
-/// Compute spectral flux for all frames
-/// Spectral flux = sum of positive differences between consecutive magnitude spectra
-fn compute_spectral_flux(
-    samples: &[f32],
-    _sample_rate: u32,
-    config: &OnsetConfig,
-) -> Vec<f32> {
-    let window_size = config.window_size;
-    let hop_size = config.hop_size;
-
-    // Guard against zero hop size
-    if hop_size == 0 {
-        return Vec::new();
-    }
-
-    let num_frames = (samples.len().saturating_sub(window_size)) / hop_size + 1;
-
-    if num_frames == 0 {
-        return Vec::new();
-    }
-
-    let mut flux = Vec::with_capacity(num_frames);
-    let mut prev_spectrum: Option<Vec<f32>> = None;
-
-    for frame_idx in 0..num_frames {
-        let start = frame_idx * hop_size;
-        let end = (start + window_size).min(samples.len());
-
-        if end - start < window_size {
-            break;
-        }
-
-        let frame = &samples[start..end];
-
-        // Window and compute FFT
-        let mut windowed = frame.to_vec();
-        apply_hann_window(&mut windowed);
-        let spectrum = compute_fft(&windowed);
-
-        // Calculate flux as sum of positive differences
-        let frame_flux = if let Some(ref prev) = prev_spectrum {
-            let mut sum = 0.0;
-            for (curr, prev) in spectrum.iter().zip(prev.iter()) {
-                let diff = curr - prev;
-                if diff > 0.0 {
-                    sum += diff;
-                }
-            }
-            sum
-        } else {
-            0.0 // First frame has no flux
-        };
-
-        flux.push(frame_flux);
-        prev_spectrum = Some(spectrum);
-    }
-
-    flux
-}
-
 /// Pick onset peaks from spectral flux using adaptive threshold
 fn pick_onset_peaks(
     flux: &[f32],
@@ -545,10 +713,10 @@ fn pick_onset_peaks(
 pub const MFCC_COEFFS: usize = 20;
 
 /// Number of triangular mel filters in the MFCC filterbank.
-const MFCC_MEL_FILTERS: usize = 40;
+pub const MFCC_MEL_FILTERS: usize = 40;
 
 /// MFCC frame length in samples (~23 ms at 44.1 kHz) and hop (50% overlap).
-const MFCC_FRAME: usize = 1024;
+pub(crate) const MFCC_FRAME: usize = 1024;
 const MFCC_HOP: usize = 512;
 
 /// Hz → mel (HTK formula).
@@ -561,6 +729,71 @@ fn mel_to_hz(mel: f32) -> f32 {
     700.0 * (10.0f32.powf(mel / 2595.0) - 1.0)
 }
 
+/// Triangular mel filters over the bins of one FFT frame size, for pooling a
+/// magnitude spectrum into [`MFCC_MEL_FILTERS`] band powers.
+///
+/// Filter edges are `MFCC_MEL_FILTERS + 2` points evenly spaced in mel from 0
+/// to min(Nyquist, 22.05 kHz). The fixed cap makes the filterbank — and
+/// therefore the coefficients — identical across sample rates ≥ 44.1 kHz: the
+/// factory model is fitted on 44.1 kHz AVP audio, while live capture
+/// (AudioContext) commonly runs at 48 kHz. Without the cap, 48 kHz audio gets
+/// systematically shifted filters and a silently degraded model.
+#[derive(Debug, Clone)]
+pub struct MelFilterbank {
+    /// Each filter's `(bin, weight)` pairs with a positive weight.
+    filters: Vec<Vec<(usize, f32)>>,
+}
+
+impl MelFilterbank {
+    /// The filterbank [`extract_mfcc`] pools its frames through.
+    pub fn for_mfcc(sample_rate: u32) -> Self {
+        Self::new(sample_rate, MFCC_FRAME)
+    }
+
+    pub fn new(sample_rate: u32, frame_size: usize) -> Self {
+        let n_bins = frame_size / 2 + 1;
+        let bin_width = sample_rate as f32 / frame_size as f32;
+        let mel_max = hz_to_mel((sample_rate as f32 / 2.0).min(22_050.0));
+        let edges: Vec<f32> = (0..MFCC_MEL_FILTERS + 2)
+            .map(|i| mel_to_hz(mel_max * i as f32 / (MFCC_MEL_FILTERS + 1) as f32))
+            .collect();
+        let filters = (0..MFCC_MEL_FILTERS)
+            .map(|m| {
+                let (f_lo, f_c, f_hi) = (edges[m], edges[m + 1], edges[m + 2]);
+                let bin_lo = (f_lo / bin_width).floor() as usize;
+                let bin_hi = ((f_hi / bin_width).ceil() as usize).min(n_bins - 1);
+                (bin_lo..=bin_hi)
+                    .filter_map(|bin| {
+                        let f = bin as f32 * bin_width;
+                        let weight = if f <= f_c {
+                            if f_c > f_lo { (f - f_lo) / (f_c - f_lo) } else { 0.0 }
+                        } else if f_hi > f_c {
+                            (f_hi - f) / (f_hi - f_c)
+                        } else {
+                            0.0
+                        };
+                        (weight > 0.0).then_some((bin, weight))
+                    })
+                    .collect()
+            })
+            .collect();
+        MelFilterbank { filters }
+    }
+
+    /// Power of `magnitudes` (one frame's spectrum) in each filter.
+    pub fn apply(&self, magnitudes: &[f32]) -> [f32; MFCC_MEL_FILTERS] {
+        let mut energies = [0.0f32; MFCC_MEL_FILTERS];
+        for (energy, filter) in energies.iter_mut().zip(&self.filters) {
+            for &(bin, weight) in filter {
+                if let Some(&mag) = magnitudes.get(bin) {
+                    *energy += weight * mag * mag;
+                }
+            }
+        }
+        energies
+    }
+}
+
 /// Extract mean MFCCs (c1..c13) over an audio segment.
 ///
 /// Classic "bag of frames" timbre descriptor: the segment is cut into ~23 ms
@@ -587,89 +820,10 @@ pub fn extract_mfcc_stats(
         return (vec![0.0; n_coeffs], vec![0.0; n_coeffs]);
     }
 
-    // Precompute the mel filterbank for this frame size / sample rate.
-    // Filter edges are MFCC_MEL_FILTERS + 2 points evenly spaced in mel from
-    // 0 to min(Nyquist, 22.05 kHz). The fixed cap makes the filterbank — and
-    // therefore the coefficients — identical across sample rates ≥ 44.1 kHz:
-    // the factory model is fitted on 44.1 kHz AVP audio, while live capture
-    // (AudioContext) commonly runs at 48 kHz. Without the cap, 48 kHz audio
-    // gets systematically shifted filters and a silently degraded model.
-    let n_bins = MFCC_FRAME / 2 + 1;
-    let bin_width = sample_rate as f32 / MFCC_FRAME as f32;
-    let mel_max = hz_to_mel((sample_rate as f32 / 2.0).min(22_050.0));
-    let edges: Vec<f32> = (0..MFCC_MEL_FILTERS + 2)
-        .map(|i| mel_to_hz(mel_max * i as f32 / (MFCC_MEL_FILTERS + 1) as f32))
-        .collect();
-
+    let bank = MelFilterbank::for_mfcc(sample_rate);
     let mut sum_coeffs = vec![0.0f64; n_coeffs];
     let mut sumsq_coeffs = vec![0.0f64; n_coeffs];
-    let mut n_frames = 0usize;
-
-    let mut start = 0;
-    loop {
-        // Zero-pad the final partial frame so short segments still yield MFCCs.
-        let mut frame = vec![0.0f32; MFCC_FRAME];
-        let end = (start + MFCC_FRAME).min(samples.len());
-        if start >= samples.len() {
-            break;
-        }
-        frame[..end - start].copy_from_slice(&samples[start..end]);
-
-        apply_hann_window(&mut frame);
-        let spectrum = compute_fft(&frame);
-
-        // Pool the power spectrum through the triangular mel filters.
-        let mut filter_energies = [0.0f32; MFCC_MEL_FILTERS];
-        for (m, energy) in filter_energies.iter_mut().enumerate() {
-            let (f_lo, f_c, f_hi) = (edges[m], edges[m + 1], edges[m + 2]);
-            let bin_lo = (f_lo / bin_width).floor() as usize;
-            let bin_hi = ((f_hi / bin_width).ceil() as usize).min(n_bins - 1);
-            for (bin, &mag) in spectrum
-                .iter()
-                .enumerate()
-                .take(bin_hi + 1)
-                .skip(bin_lo)
-            {
-                let f = bin as f32 * bin_width;
-                let weight = if f <= f_c {
-                    if f_c > f_lo { (f - f_lo) / (f_c - f_lo) } else { 0.0 }
-                } else if f_hi > f_c {
-                    (f_hi - f) / (f_hi - f_c)
-                } else {
-                    0.0
-                };
-                if weight > 0.0 {
-                    *energy += weight * mag * mag;
-                }
-            }
-        }
-
-        // Log-compress and DCT-II; keep c1..c13 (drop level-dependent c0).
-        // The log floor is RELATIVE to the frame's peak filter energy (80 dB
-        // below it): an absolute floor would pin near-silent filters while a
-        // gain change shifts the rest, leaking level into c1+ and breaking the
-        // level invariance that dropping c0 is supposed to buy.
-        let peak_energy = filter_energies.iter().cloned().fold(0.0f32, f32::max);
-        let floor = (peak_energy * 1e-8).max(f32::MIN_POSITIVE);
-        let log_energies: Vec<f32> =
-            filter_energies.iter().map(|&e| e.max(floor).ln()).collect();
-        let n = MFCC_MEL_FILTERS as f32;
-        for k in 0..n_coeffs {
-            let mut c = 0.0f32;
-            for (i, &le) in log_energies.iter().enumerate() {
-                c += le
-                    * (std::f32::consts::PI * (k + 1) as f32 * (i as f32 + 0.5) / n).cos();
-            }
-            sum_coeffs[k] += c as f64;
-            sumsq_coeffs[k] += (c as f64) * (c as f64);
-        }
-        n_frames += 1;
-
-        if end >= samples.len() {
-            break;
-        }
-        start += MFCC_HOP;
-    }
+    let n_frames = accumulate_mfcc(samples, &bank, &mut sum_coeffs, &mut sumsq_coeffs);
 
     if n_frames == 0 {
         return (vec![0.0; n_coeffs], vec![0.0; n_coeffs]);
@@ -687,6 +841,64 @@ pub fn extract_mfcc_stats(
     (means, stds)
 }
 
+/// [`extract_mfcc`] through a prebuilt `bank` (see
+/// [`MelFilterbank::for_mfcc`]) into a fixed-size array: no allocation once
+/// this thread's [`spectrogram::FftPlans`] hold the MFCC frame size. The
+/// streaming detector's per-event path.
+pub fn extract_mfcc_with(samples: &[f32], sample_rate: u32, bank: &MelFilterbank) -> [f32; MFCC_COEFFS] {
+    let mut means = [0.0f32; MFCC_COEFFS];
+    if samples.is_empty() || sample_rate == 0 {
+        return means;
+    }
+    let mut sum_coeffs = [0.0f64; MFCC_COEFFS];
+    let mut sumsq_coeffs = [0.0f64; MFCC_COEFFS];
+    let n_frames = accumulate_mfcc(samples, bank, &mut sum_coeffs, &mut sumsq_coeffs);
+    if n_frames > 0 {
+        for (mean, &s) in means.iter_mut().zip(&sum_coeffs) {
+            *mean = (s / n_frames as f64) as f32;
+        }
+    }
+    means
+}
+
+/// Add each frame's c1..cN (N = `sums.len()`) to `sums` and their squares to
+/// `sumsqs`; returns the number of frames. Frames are [`MFCC_FRAME`] long
+/// every [`MFCC_HOP`], the final partial one zero-padded so short segments
+/// still yield MFCCs.
+fn accumulate_mfcc(samples: &[f32], bank: &MelFilterbank, sums: &mut [f64], sumsqs: &mut [f64]) -> usize {
+    let n_frames = samples.len().saturating_sub(MFCC_FRAME).div_ceil(MFCC_HOP) + 1;
+    spectrogram::with_plans(|plans| {
+        for frame in 0..n_frames {
+            let start = (frame * MFCC_HOP).min(samples.len());
+            let end = (start + MFCC_FRAME).min(samples.len());
+            accumulate_frame(&bank.apply(plans.windowed(&samples[start..end], MFCC_FRAME).1), sums, sumsqs);
+        }
+    });
+    n_frames
+}
+
+/// Add one frame's c1..cN, from its mel `filter_energies`, to `sums` and
+/// their squares to `sumsqs`.
+fn accumulate_frame(filter_energies: &[f32; MFCC_MEL_FILTERS], sums: &mut [f64], sumsqs: &mut [f64]) {
+    // Log-compress and DCT-II; keep c1..cN (drop level-dependent c0).
+    // The log floor is RELATIVE to the frame's peak filter energy (80 dB
+    // below it): an absolute floor would pin near-silent filters while a
+    // gain change shifts the rest, leaking level into c1+ and breaking the
+    // level invariance that dropping c0 is supposed to buy.
+    let peak_energy = filter_energies.iter().cloned().fold(0.0f32, f32::max);
+    let floor = (peak_energy * 1e-8).max(f32::MIN_POSITIVE);
+    let log_energies = filter_energies.map(|e| e.max(floor).ln());
+    let n = MFCC_MEL_FILTERS as f32;
+    for (k, (sum, sumsq)) in sums.iter_mut().zip(sumsqs.iter_mut()).enumerate() {
+        let mut c = 0.0f32;
+        for (i, &le) in log_energies.iter().enumerate() {
+            c += le * (std::f32::consts::PI * (k + 1) as f32 * (i as f32 + 0.5) / n).cos();
+        }
+        *sum += c as f64;
+        *sumsq += (c as f64) * (c as f64);
+    }
+}
+
 /// Extract mean MFCCs for a specific time window of decoded audio
 /// (the MFCC counterpart of [`extract_features_for_window`]).
 pub fn extract_mfcc_for_window(audio: &AudioData, start_ms: f64, duration_ms: f64) -> Vec<f32> {
@@ -705,6 +917,9 @@ pub fn extract_mfcc_for_window(audio: &AudioData, start_ms: f64, duration_ms: f6
 
 /// Extract features for a specific time window
 /// Used to analyze audio around a detected onset
+///
+/// Mixes `audio` to mono on every call; a loop over many onsets should read
+/// its windows from one [`crate::AnalysisContext`] instead.
 pub fn extract_features_for_window(
     audio: &AudioData,
     start_ms: f64,
@@ -724,6 +939,251 @@ pub fn extract_features_for_window(
     extract_features(window, audio.sample_rate)
 }
 
+/// Lowest f0 [`track_pitch`] looks for (a low chest hum).
+pub const PITCH_MIN_HZ: f32 = 60.0;
+
+/// Highest f0 [`track_pitch`] looks for (a high, near-whistled hum).
+pub const PITCH_MAX_HZ: f32 = 1000.0;
+
+/// Longest stretch of an event the offline pipelines track pitch over; a
+/// held note longer than this keeps the pitch of its first seconds.
+pub const PITCH_CONTOUR_MAX_MS: f64 = 4000.0;
+
+/// pYIN threshold prior: Beta(2, 18) (mean 0.1) over 100 thresholds
+/// 0.01..=1.00.
+const PYIN_THRESHOLDS: usize = 100;
+const PYIN_BETA: (f32, f32) = (2.0, 18.0);
+
+/// Share of a threshold's weight given to the deepest trough when no trough
+/// dips below it (pYIN's "absolute minimum" rule).
+const PYIN_ABSOLUTE_MIN_WEIGHT: f32 = 0.01;
+
+/// Viterbi cost of an f0 jump between frames, per octave. A semitone step
+/// costs about as much as a 60%-likely candidate, so vibrato and glides are
+/// followed but a one-frame octave error is not.
+const PITCH_JUMP_COST: f32 = 6.0;
+
+/// Viterbi cost of switching between voiced and unvoiced.
+const VOICING_SWITCH_COST: f32 = 2.0;
+
+/// Frames quieter than this RMS are unvoiced without looking for a period.
+const PITCH_SILENCE_RMS: f32 = 1e-4;
+
+/// Track the fundamental frequency of a monophonic, voiced signal (a hum).
+///
+/// pYIN-style: each frame's YIN cumulative-mean-normalized difference
+/// function is searched under a whole distribution of thresholds (a Beta
+/// prior) instead of one fixed threshold, which turns its troughs into pitch
+/// candidates with probabilities; a Viterbi pass then picks the smoothest
+/// likely path through the candidates and an "unvoiced" state. Frames are
+/// about 23 ms of integration (the period search reaches [`PITCH_MIN_HZ`]) at
+/// a quarter-frame hop.
+///
+/// Returns the voiced frames only, timed from the start of `samples`.
+/// Signals shorter than two frames (~46 ms at 44.1 kHz) yield nothing.
+pub fn track_pitch(samples: &[f32], sample_rate: u32) -> Vec<PitchPoint> {
+    if sample_rate == 0 {
+        return Vec::new();
+    }
+    let window = ((sample_rate as f32 / PITCH_MIN_HZ).ceil() as usize).next_power_of_two();
+    let frame_len = 2 * window;
+    if samples.len() < frame_len {
+        return Vec::new();
+    }
+    let tau_min = ((sample_rate as f32 / PITCH_MAX_HZ).floor() as usize).max(2);
+    let tau_max = ((sample_rate as f32 / PITCH_MIN_HZ).ceil() as usize).min(window - 2);
+    let hop = window / 4;
+    let thresholds = pyin_threshold_weights();
+
+    let mut planner = RealFftPlanner::<f32>::new();
+    let forward = planner.plan_fft_forward(frame_len);
+    let inverse = planner.plan_fft_inverse(frame_len);
+    let mut head = forward.make_input_vec();
+    let mut whole = forward.make_input_vec();
+    let mut head_spectrum = forward.make_output_vec();
+    let mut whole_spectrum = forward.make_output_vec();
+    let mut correlation = inverse.make_output_vec();
+    let mut prefix = vec![0.0f64; frame_len + 1];
+    let mut diff = vec![0.0f32; tau_max + 2];
+    let mut cmnd = vec![1.0f32; tau_max + 2];
+
+    // Per frame: (f0, probability) candidates.
+    let mut frames: Vec<Vec<(f32, f32)>> = Vec::new();
+    for start in (0..=samples.len() - frame_len).step_by(hop) {
+        let frame = &samples[start..start + frame_len];
+        for (i, &x) in frame.iter().enumerate() {
+            prefix[i + 1] = prefix[i] + (x as f64) * (x as f64);
+        }
+        let energy = prefix[window];
+        if ((energy / window as f64).sqrt() as f32) < PITCH_SILENCE_RMS {
+            frames.push(Vec::new());
+            continue;
+        }
+
+        // d(τ) = Σ (x[j] − x[j+τ])² = e(0) + e(τ) − 2·r(τ), with the
+        // cross-correlation r taken through the FFT.
+        head[..window].copy_from_slice(&frame[..window]);
+        head[window..].fill(0.0);
+        whole.copy_from_slice(frame);
+        forward.process(&mut head, &mut head_spectrum).expect("buffers sized by the plan");
+        forward.process(&mut whole, &mut whole_spectrum).expect("buffers sized by the plan");
+        for (h, w) in head_spectrum.iter_mut().zip(&whole_spectrum) {
+            *h = h.conj() * w;
+        }
+        let last = head_spectrum.len() - 1;
+        head_spectrum[0].im = 0.0;
+        head_spectrum[last].im = 0.0;
+        inverse.process(&mut head_spectrum, &mut correlation).expect("buffers sized by the plan");
+        for (tau, d) in diff.iter_mut().enumerate() {
+            let shifted = prefix[tau + window] - prefix[tau];
+            let r = correlation[tau] as f64 / frame_len as f64;
+            *d = (energy + shifted - 2.0 * r).max(0.0) as f32;
+        }
+
+        // Cumulative mean normalization: d'(τ) = d(τ)·τ / Σ_{k≤τ} d(k).
+        let mut running = 0.0f32;
+        for tau in 1..diff.len() {
+            running += diff[tau];
+            cmnd[tau] = if running > 0.0 { diff[tau] * tau as f32 / running } else { 1.0 };
+        }
+
+        let troughs: Vec<usize> = (tau_min..=tau_max)
+            .filter(|&tau| cmnd[tau] < cmnd[tau - 1] && cmnd[tau] <= cmnd[tau + 1])
+            .collect();
+        let Some(&deepest) = troughs.iter().min_by(|&&a, &&b| cmnd[a].total_cmp(&cmnd[b])) else {
+            frames.push(Vec::new());
+            continue;
+        };
+        let mut probability = vec![0.0f32; troughs.len()];
+        for &(threshold, weight) in &thresholds {
+            match troughs.iter().position(|&tau| cmnd[tau] < threshold) {
+                Some(k) => probability[k] += weight,
+                None => {
+                    let k = troughs.iter().position(|&tau| tau == deepest).expect("deepest is a trough");
+                    probability[k] += weight * PYIN_ABSOLUTE_MIN_WEIGHT;
+                }
+            }
+        }
+        frames.push(
+            troughs
+                .iter()
+                .zip(&probability)
+                .filter(|(_, &p)| p > 0.0)
+                .map(|(&tau, &p)| {
+                    let (a, b, c) = (cmnd[tau - 1], cmnd[tau], cmnd[tau + 1]);
+                    let curvature = a - 2.0 * b + c;
+                    let shift = if curvature > 1e-9 { (0.5 * (a - c) / curvature).clamp(-0.5, 0.5) } else { 0.0 };
+                    (sample_rate as f32 / (tau as f32 + shift), p)
+                })
+                .collect(),
+        );
+    }
+
+    viterbi_pitch_path(&frames)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, state)| {
+            let (f0_hz, confidence) = *frames[i].get(state?)?;
+            Some(PitchPoint {
+                time_ms: (i * hop + window / 2) as f64 * 1000.0 / sample_rate as f64,
+                f0_hz,
+                confidence,
+            })
+        })
+        .collect()
+}
+
+/// [`track_pitch`] over a time window of decoded audio (channels averaged).
+pub fn track_pitch_for_window(audio: &AudioData, start_ms: f64, duration_ms: f64) -> Vec<PitchPoint> {
+    let start_sample = ((start_ms / 1000.0) * audio.sample_rate as f64) as usize;
+    let duration_samples = ((duration_ms / 1000.0) * audio.sample_rate as f64) as usize;
+
+    let mono = audio.to_mono();
+    let end_sample = (start_sample + duration_samples).min(mono.len());
+
+    if start_sample >= end_sample {
+        return Vec::new();
+    }
+
+    track_pitch(&mono[start_sample..end_sample], audio.sample_rate)
+}
+
+/// `(threshold, weight)` pairs of the pYIN threshold prior; weights sum to 1.
+fn pyin_threshold_weights() -> Vec<(f32, f32)> {
+    let (alpha, beta) = PYIN_BETA;
+    let pdf: Vec<(f32, f32)> = (1..=PYIN_THRESHOLDS)
+        .map(|i| {
+            let t = i as f32 / PYIN_THRESHOLDS as f32;
+            (t, t.powf(alpha - 1.0) * (1.0 - t).max(0.0).powf(beta - 1.0))
+        })
+        .collect();
+    let total: f32 = pdf.iter().map(|(_, w)| w).sum();
+    pdf.into_iter().map(|(t, w)| (t, w / total)).collect()
+}
+
+/// Most likely state per frame: `Some(candidate index)` when voiced, `None`
+/// when unvoiced. Emission cost is the negative log probability of the
+/// candidate (or of the frame being unvoiced); transitions cost
+/// [`PITCH_JUMP_COST`] per octave and [`VOICING_SWITCH_COST`].
+fn viterbi_pitch_path(frames: &[Vec<(f32, f32)>]) -> Vec<Option<usize>> {
+    // State k < candidates.len() is voiced at that candidate; the last state
+    // of each frame is unvoiced.
+    let emission = |frame: &[(f32, f32)]| -> Vec<f32> {
+        let voiced: f32 = frame.iter().map(|(_, p)| p).sum();
+        frame
+            .iter()
+            .map(|&(_, p)| -p.max(1e-6).ln())
+            .chain(std::iter::once(-(1.0 - voiced).clamp(1e-3, 1.0).ln()))
+            .collect()
+    };
+    let transition = |from: Option<f32>, to: Option<f32>| -> f32 {
+        match (from, to) {
+            (Some(a), Some(b)) => PITCH_JUMP_COST * (b / a).log2().abs(),
+            (None, None) => 0.0,
+            _ => VOICING_SWITCH_COST,
+        }
+    };
+    let f0 = |frame: &[(f32, f32)], state: usize| frame.get(state).map(|&(f, _)| f);
+
+    let Some(first) = frames.first() else {
+        return Vec::new();
+    };
+    let mut cost = emission(first);
+    let mut back: Vec<Vec<usize>> = vec![Vec::new()];
+    for window in frames.windows(2) {
+        let (prev, frame) = (&window[0], &window[1]);
+        let mut next = emission(frame);
+        let mut pointers = vec![0; next.len()];
+        for (state, total) in next.iter_mut().enumerate() {
+            let (best, best_cost) = cost
+                .iter()
+                .enumerate()
+                .map(|(from, &c)| (from, c + transition(f0(prev, from), f0(frame, state))))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .expect("every frame has an unvoiced state");
+            *total += best_cost;
+            pointers[state] = best;
+        }
+        cost = next;
+        back.push(pointers);
+    }
+
+    let mut state = cost
+        .iter()
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(i, _)| i)
+        .expect("every frame has an unvoiced state");
+    let mut path = vec![None; frames.len()];
+    for i in (0..frames.len()).rev() {
+        path[i] = (state < frames[i].len()).then_some(state);
+        if i > 0 {
+            state = back[i][state];
+        }
+    }
+    path
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -759,6 +1219,76 @@ mod tests {
         assert_eq!(features.spectral_centroid, 0.0);
     }
 
+    fn harmonic_tone(f0: impl Fn(f32) -> f32, seconds: f32, sample_rate: u32) -> Vec<f32> {
+        let mut phase = 0.0f32;
+        (0..(seconds * sample_rate as f32) as usize)
+            .map(|i| {
+                phase += 2.0 * std::f32::consts::PI * f0(i as f32 / sample_rate as f32) / sample_rate as f32;
+                0.4 * phase.sin() + 0.25 * (2.0 * phase).sin() + 0.15 * (3.0 * phase).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pitch_tracks_a_steady_hum() {
+        let samples = harmonic_tone(|_| 220.0, 0.5, 44_100);
+        let contour = track_pitch(&samples, 44_100);
+        let frames = (samples.len() - 2048) / 256 + 1;
+        assert!(contour.len() >= frames * 9 / 10, "{} of {frames} voiced", contour.len());
+        for point in &contour {
+            assert!((point.f0_hz - 220.0).abs() < 2.0, "{point:?}");
+            assert!((point.midi_note() - 57.0).abs() < 0.2);
+        }
+        assert!(contour.windows(2).all(|w| w[1].time_ms > w[0].time_ms));
+    }
+
+    #[test]
+    fn pitch_follows_a_step_without_octave_errors() {
+        // A3 then E4 (a fifth up), at 48 kHz.
+        let samples = harmonic_tone(|t| if t < 0.4 { 220.0 } else { 330.0 }, 0.8, 48_000);
+        let contour = track_pitch(&samples, 48_000);
+        let early: Vec<_> = contour.iter().filter(|p| p.time_ms < 350.0).collect();
+        let late: Vec<_> = contour.iter().filter(|p| p.time_ms > 450.0).collect();
+        assert!(!early.is_empty() && !late.is_empty());
+        assert!(early.iter().all(|p| (p.f0_hz - 220.0).abs() < 3.0));
+        assert!(late.iter().all(|p| (p.f0_hz - 330.0).abs() < 4.0));
+    }
+
+    #[test]
+    fn noise_and_silence_are_unvoiced() {
+        assert!(track_pitch(&vec![0.0; 22_050], 44_100).is_empty());
+        assert!(track_pitch(&[0.1; 100], 44_100).is_empty());
+
+        let mut seed = 11u32;
+        let noise: Vec<f32> = (0..22_050)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed >> 8) as f32 / (1 << 24) as f32 - 0.5
+            })
+            .collect();
+        let frames = (noise.len() - 2048) / 256 + 1;
+        assert!(track_pitch(&noise, 44_100).len() < frames / 10);
+    }
+
+    #[test]
+    fn extract_window_matches_the_separate_extractors() {
+        // A decaying chirp, at lengths under, at and past each framing.
+        let sr = 48_000u32;
+        let signal: Vec<f32> = (0..9000)
+            .map(|i| {
+                let t = i as f32 / sr as f32;
+                (2.0 * std::f32::consts::PI * (200.0 + 3000.0 * t) * t).sin() * (-t * 20.0).exp()
+            })
+            .collect();
+        for len in [0, 1, 300, MFCC_FRAME, 1500, FEATURE_FRAME, 7200, 9000] {
+            let window = &signal[..len];
+            let (features, mfcc) = extract_window(window, sr);
+            // Bit-identical, so the hybrid's frozen outputs hold.
+            assert_eq!(format!("{features:?}"), format!("{:?}", extract_features(window, sr)), "len {len}");
+            assert_eq!(mfcc, extract_mfcc(window, sr), "len {len}");
+        }
+    }
+
     #[test]
     fn test_mfcc_empty_input() {
         let mfcc = extract_mfcc(&[], 44100);
@@ -873,4 +1403,67 @@ mod tests {
         let onsets = detect_onsets(&audio, &config);
         assert!(onsets.is_empty());
     }
+
+    /// Decaying noise bursts every 300ms over a quiet floor, starting at t=0
+    /// so the leading-onset check is exercised too.
+    fn noise_bursts(sr: u32) -> AudioData {
+        let mut seed = 7u32;
+        let samples: Vec<f32> = (0..sr as usize * 2)
+            .map(|i| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let noise = (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5;
+                let t = (i % (sr as usize * 3 / 10)) as f32 / sr as f32;
+                noise * (0.01 + (-40.0 * t).exp())
+            })
+            .collect();
+        AudioData {
+            frame_count: samples.len(),
+            samples,
+            sample_rate: sr,
+            channels: 1,
+            bit_depth: 16,
+            duration_ms: 2000,
+        }
+    }
+
+    #[test]
+    fn test_onset_scanner_is_chunking_invariant() {
+        let sr = 44100u32;
+        let audio = noise_bursts(sr);
+        let config = OnsetConfig::default();
+        let whole: Vec<f64> = detect_onsets(&audio, &config).iter().map(|o| o.timestamp_ms).collect();
+        assert!(whole.len() >= 5, "expected the bursts to be detected: {whole:?}");
+
+        for chunk_len in [1, 100, 441, 4097] {
+            let mut scanner = OnsetScanner::new(sr, config.clone());
+            for chunk in audio.samples.chunks(chunk_len) {
+                scanner.push(chunk);
+            }
+            let chunked: Vec<f64> = scanner.finish().iter().map(|o| o.timestamp_ms).collect();
+            assert_eq!(chunked, whole, "chunk length {chunk_len}");
+        }
+    }
+
+    #[test]
+    fn every_onset_function_finds_the_bursts() {
+        let sr = 44100u32;
+        let audio = noise_bursts(sr);
+        for onset_function in OnsetFunction::ALL {
+            let config = OnsetConfig { onset_function, ..OnsetConfig::default() };
+            let onsets: Vec<f64> = detect_onsets(&audio, &config).iter().map(|o| o.timestamp_ms).collect();
+            // Bursts at 0, 300, ... 1800ms, localized to within a few ms.
+            let hits = (0..7)
+                .filter(|k| onsets.iter().any(|t| (t - *k as f64 * 300.0).abs() < 5.0))
+                .count();
+            assert!(hits >= 6, "{onset_function}: {onsets:?}");
+            assert!(onsets.len() <= 8, "{onset_function}: {onsets:?}");
+
+            let mut scanner = OnsetScanner::new(sr, config.clone());
+            for chunk in audio.samples.chunks(441) {
+                scanner.push(chunk);
+            }
+            let chunked: Vec<f64> = scanner.finish().iter().map(|o| o.timestamp_ms).collect();
+            assert_eq!(chunked, onsets, "{onset_function}");
+        }
+    }
 }
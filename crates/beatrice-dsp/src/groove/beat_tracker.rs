@@ -0,0 +1,399 @@
+// Beat Tracker - Causal tempo and beat-phase tracking for live input
+// A running IOI histogram for the tempo, phase-locked to the accented onsets
+
+use std::collections::VecDeque;
+use std::f64::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+use super::tempo::TempoConfig;
+use crate::events::EventClass;
+
+/// Histogram span in ms: the IOIs of four beats at 60 BPM.
+const HIST_SPAN_MS: usize = 4000;
+
+/// Beats of a candidate period whose IOIs vote for it (1×, 2×, … the period).
+const BEATS_SCORED: usize = 4;
+
+/// Spread (ms) of each IOI's Gaussian vote: the timing jitter of a performer.
+const IOI_SIGMA_MS: f64 = 10.0;
+
+/// Time constant (ms) the histogram forgets over, so a tempo change takes over
+/// within a few bars.
+const HIST_DECAY_MS: f64 = 8000.0;
+
+/// Time constant (ms) of the onsets' weight in the phase estimate: the beat
+/// locks to the last couple of seconds.
+const PHASE_DECAY_MS: f64 = 2000.0;
+
+/// Onsets kept for pairing and phase: enough for `HIST_SPAN_MS` at the
+/// detector's densest (one per `min_onset_gap_ms`).
+const MAX_ONSETS: usize = 64;
+
+/// Period (ms) the tempo prior centres on (120 BPM); the prior falls off by
+/// octaves and only settles ties between metrical levels.
+const PRIOR_PERIOD_MS: f64 = 500.0;
+
+/// The tempo and beat grid at a point in the stream.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct LiveTempo {
+    /// Estimated beats per minute (120 until the tracker locks)
+    pub bpm: f64,
+
+    /// Fraction of the current beat elapsed, in [0.0, 1.0)
+    pub beat_phase: f64,
+
+    /// Confidence in the tempo and phase [0.0, 1.0]; 0.0 until locked
+    pub confidence: f32,
+
+    /// Time of the most recent beat in ms from the start of the stream
+    pub last_beat_ms: f64,
+}
+
+impl LiveTempo {
+    /// Length of one beat in milliseconds.
+    pub fn beat_ms(&self) -> f64 {
+        60000.0 / self.bpm
+    }
+
+    /// Time of the next beat in ms from the start of the stream.
+    pub fn next_beat_ms(&self) -> f64 {
+        self.last_beat_ms + self.beat_ms()
+    }
+
+    /// Snap `t_ms` to the nearest of `subdivisions` slots per beat on this
+    /// grid (4 = sixteenths), for quantizing live events as they arrive.
+    pub fn snap_ms(&self, t_ms: f64, subdivisions: u32) -> f64 {
+        let step = self.beat_ms() / subdivisions.max(1) as f64;
+        self.last_beat_ms + ((t_ms - self.last_beat_ms) / step).round() * step
+    }
+}
+
+/// How strongly an onset of `class` marks the beat: kicks and snares carry
+/// it, hats and hums fill in between, rejected noise says nothing.
+pub fn beat_accent(class: EventClass) -> f32 {
+    match class {
+        EventClass::BilabialPlosive | EventClass::Click | EventClass::Rimshot => 1.0,
+        EventClass::HihatNoise | EventClass::OpenHihat | EventClass::Crash => 0.5,
+        EventClass::HumVoiced => 0.25,
+        EventClass::Noise => 0.0,
+    }
+}
+
+/// Causal tempo and beat-phase tracker.
+///
+/// [`estimate_tempo`](super::estimate_tempo) needs the whole onset list; this
+/// follows a performance onset by onset. Every new onset votes for its
+/// intervals to the onsets of the last four seconds in a decaying IOI
+/// histogram (1ms bins, each vote Gaussian-spread and weighted by both
+/// onsets' accents). The tempo is the beat period whose first
+/// [`BEATS_SCORED`] multiples collect the most votes, under a gentle prior
+/// toward 120 BPM that settles half/double-time ties; the beat phase is the
+/// accent-weighted circular mean of the recent onsets on that period, which
+/// keeps the grid locked to where the performer actually lands.
+///
+/// Buffers are sized at construction, so [`observe`](Self::observe) and
+/// [`tempo_at`](Self::tempo_at) never allocate and can run on the audio
+/// thread.
+#[derive(Debug, Clone)]
+pub struct BeatTracker {
+    min_period_ms: usize,
+    max_period_ms: usize,
+    min_onsets: usize,
+    /// Recent onsets: (time ms, accent).
+    onsets: VecDeque<(f64, f32)>,
+    /// IOI votes, 1ms bins from 0 to `HIST_SPAN_MS`.
+    hist: Vec<f32>,
+    /// Stream time (ms) the histogram has been decayed to.
+    hist_at_ms: f64,
+    /// Current estimate: beat period (ms), a beat time (ms) and confidence.
+    period_ms: f64,
+    anchor_ms: f64,
+    confidence: f32,
+}
+
+/// [`BeatTracker`] state that is not derived from its [`TempoConfig`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct BeatTrackerState {
+    onsets: Vec<(f64, f32)>,
+    #[serde(with = "crate::snapshot::f32s")]
+    hist: Vec<f32>,
+    hist_at_ms: f64,
+    period_ms: f64,
+    anchor_ms: f64,
+    confidence: f32,
+}
+
+impl Default for BeatTracker {
+    fn default() -> Self {
+        Self::new(&TempoConfig::default())
+    }
+}
+
+impl BeatTracker {
+    /// A tracker over `config`'s BPM range that locks once `min_onsets`
+    /// accented onsets have arrived.
+    pub fn new(config: &TempoConfig) -> Self {
+        let max_period_ms = ((60000.0 / config.min_bpm.max(1.0)) as usize).min(HIST_SPAN_MS / BEATS_SCORED);
+        let min_period_ms = ((60000.0 / config.max_bpm.max(1.0)).ceil() as usize).clamp(1, max_period_ms);
+        BeatTracker {
+            min_period_ms,
+            max_period_ms,
+            min_onsets: config.min_onsets.max(2),
+            onsets: VecDeque::with_capacity(MAX_ONSETS),
+            hist: vec![0.0; HIST_SPAN_MS + 1],
+            hist_at_ms: 0.0,
+            period_ms: PRIOR_PERIOD_MS,
+            anchor_ms: 0.0,
+            confidence: 0.0,
+        }
+    }
+
+    /// The onsets, histogram and estimate heard so far, for a streaming
+    /// snapshot.
+    pub(crate) fn state(&self) -> BeatTrackerState {
+        BeatTrackerState {
+            onsets: self.onsets.iter().copied().collect(),
+            hist: self.hist.clone(),
+            hist_at_ms: self.hist_at_ms,
+            period_ms: self.period_ms,
+            anchor_ms: self.anchor_ms,
+            confidence: self.confidence,
+        }
+    }
+
+    /// Resume from [`state`](Self::state).
+    pub(crate) fn restore_state(&mut self, state: BeatTrackerState) -> Result<(), String> {
+        if state.hist.len() != self.hist.len() || state.onsets.len() > MAX_ONSETS {
+            return Err("beat tracker state does not match its histogram".to_string());
+        }
+        self.onsets.clear();
+        self.onsets.extend(state.onsets);
+        self.hist.copy_from_slice(&state.hist);
+        self.hist_at_ms = state.hist_at_ms;
+        self.period_ms = state.period_ms;
+        self.anchor_ms = state.anchor_ms;
+        self.confidence = state.confidence;
+        Ok(())
+    }
+
+    /// Forget everything heard so far.
+    pub fn reset(&mut self) {
+        self.onsets.clear();
+        self.hist.fill(0.0);
+        self.hist_at_ms = 0.0;
+        self.period_ms = PRIOR_PERIOD_MS;
+        self.anchor_ms = 0.0;
+        self.confidence = 0.0;
+    }
+
+    /// Feed an onset at `t_ms` with `accent` (see [`beat_accent`]). Onsets
+    /// must arrive in time order; an earlier one, or one without accent, is
+    /// ignored.
+    pub fn observe(&mut self, t_ms: f64, accent: f32) {
+        let last = self.onsets.back().map_or(f64::NEG_INFINITY, |&(t, _)| t);
+        if accent <= 0.0 || t_ms <= last {
+            return;
+        }
+
+        let decay = (-(t_ms - self.hist_at_ms).max(0.0) / HIST_DECAY_MS).exp() as f32;
+        for v in &mut self.hist {
+            *v *= decay;
+        }
+        self.hist_at_ms = t_ms;
+
+        while self.onsets.front().is_some_and(|&(t, _)| t_ms - t > HIST_SPAN_MS as f64) {
+            self.onsets.pop_front();
+        }
+        for &(t, a) in &self.onsets {
+            vote(&mut self.hist, t_ms - t, accent * a);
+        }
+        if self.onsets.len() == MAX_ONSETS {
+            self.onsets.pop_front();
+        }
+        self.onsets.push_back((t_ms, accent));
+        self.estimate();
+    }
+
+    /// The tempo and beat grid at stream time `now_ms`.
+    pub fn tempo_at(&self, now_ms: f64) -> LiveTempo {
+        if self.confidence <= 0.0 {
+            return LiveTempo { bpm: 60000.0 / PRIOR_PERIOD_MS, beat_phase: 0.0, confidence: 0.0, last_beat_ms: 0.0 };
+        }
+        let beats = ((now_ms - self.anchor_ms) / self.period_ms).floor();
+        let last_beat_ms = self.anchor_ms + beats * self.period_ms;
+        LiveTempo {
+            bpm: 60000.0 / self.period_ms,
+            beat_phase: ((now_ms - last_beat_ms) / self.period_ms).clamp(0.0, 1.0 - f64::EPSILON),
+            confidence: self.confidence,
+            last_beat_ms,
+        }
+    }
+
+    /// Re-pick the period and phase from the histogram and recent onsets.
+    fn estimate(&mut self) {
+        if self.onsets.len() < self.min_onsets {
+            self.confidence = 0.0;
+            return;
+        }
+
+        // Best period on the 1ms grid, then a parabolic refinement.
+        let (mut best, mut best_score, mut total) = (self.min_period_ms, 0.0f64, 0.0f64);
+        for p in self.min_period_ms..=self.max_period_ms {
+            let s = self.score(p as f64);
+            total += s;
+            if s > best_score {
+                best = p;
+                best_score = s;
+            }
+        }
+        if best_score <= 0.0 {
+            self.confidence = 0.0;
+            return;
+        }
+        let mut period = best as f64;
+        if best > self.min_period_ms && best < self.max_period_ms {
+            let (l, r) = (self.score(period - 1.0), self.score(period + 1.0));
+            let curvature = l - 2.0 * best_score + r;
+            if curvature < 0.0 {
+                period += (0.5 * (l - r) / curvature).clamp(-0.5, 0.5);
+            }
+        }
+        let mean = total / (self.max_period_ms - self.min_period_ms + 1) as f64;
+        let salience = 1.0 - mean / best_score;
+
+        // Phase: accent- and recency-weighted circular mean of the onsets.
+        // How tightly they sit is judged on the eighth-note grid, so offbeat
+        // hats count for the lock rather than against it.
+        let newest = self.onsets.back().map_or(0.0, |&(t, _)| t);
+        let (mut re, mut im, mut re2, mut im2, mut weight) = (0.0f64, 0.0f64, 0.0f64, 0.0f64, 0.0f64);
+        for &(t, a) in &self.onsets {
+            let w = a as f64 * (-(newest - t) / PHASE_DECAY_MS).exp();
+            let theta = TAU * t / period;
+            re += w * theta.cos();
+            im += w * theta.sin();
+            re2 += w * (2.0 * theta).cos();
+            im2 += w * (2.0 * theta).sin();
+            weight += w;
+        }
+        let coherence = re2.hypot(im2) / weight.max(f64::EPSILON);
+
+        self.period_ms = period;
+        self.anchor_ms = im.atan2(re).rem_euclid(TAU) / TAU * period;
+        self.confidence = (coherence * salience).clamp(0.0, 1.0) as f32;
+    }
+
+    /// Votes for beat period `p` ms: its first [`BEATS_SCORED`] multiples,
+    /// under the tempo prior.
+    fn score(&self, p: f64) -> f64 {
+        let votes: f64 = (1..=BEATS_SCORED).map(|k| self.hist_at(k as f64 * p) as f64).sum();
+        let octaves = (p / PRIOR_PERIOD_MS).log2();
+        votes * (-0.5 * octaves * octaves).exp()
+    }
+
+    /// Histogram value at `ms`, linearly interpolated between bins.
+    fn hist_at(&self, ms: f64) -> f32 {
+        let i = ms.floor() as usize;
+        if i + 1 >= self.hist.len() {
+            return 0.0;
+        }
+        let frac = (ms - i as f64) as f32;
+        self.hist[i] * (1.0 - frac) + self.hist[i + 1] * frac
+    }
+}
+
+/// Add a Gaussian vote of `weight` for an inter-onset interval of `ioi_ms`.
+fn vote(hist: &mut [f32], ioi_ms: f64, weight: f32) {
+    let lo = (ioi_ms - 3.0 * IOI_SIGMA_MS).ceil().max(0.0) as usize;
+    let hi = ((ioi_ms + 3.0 * IOI_SIGMA_MS).floor() as usize).min(hist.len().saturating_sub(1));
+    for (bin, v) in hist.iter_mut().enumerate().take(hi + 1).skip(lo) {
+        let z = (bin as f64 - ioi_ms) / IOI_SIGMA_MS;
+        *v += weight * (-0.5 * z * z).exp() as f32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A groove at `bpm` from `start_ms` for `beats` beats: kick on the beat,
+    /// snare on the backbeat, hats on the eighths between, each a few ms off.
+    fn groove(tracker: &mut BeatTracker, bpm: f64, start_ms: f64, beats: usize) -> f64 {
+        let beat = 60000.0 / bpm;
+        let mut state = 0x2545_f491_u32;
+        let mut t = start_ms;
+        for i in 0..beats * 2 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let jitter = (state as f64 / u32::MAX as f64 - 0.5) * 10.0;
+            let class = match i % 4 {
+                0 => EventClass::BilabialPlosive,
+                2 => EventClass::Click,
+                _ => EventClass::HihatNoise,
+            };
+            tracker.observe(t + jitter, beat_accent(class));
+            t += beat / 2.0;
+        }
+        t
+    }
+
+    #[test]
+    fn locks_onto_a_steady_groove() {
+        let mut tracker = BeatTracker::default();
+        let end = groove(&mut tracker, 96.0, 300.0, 16);
+        let tempo = tracker.tempo_at(end);
+        assert!((tempo.bpm - 96.0).abs() < 1.5, "bpm {}", tempo.bpm);
+        assert!(tempo.confidence > 0.5, "confidence {}", tempo.confidence);
+        // The beats land on the kicks: 300ms + whole beats.
+        let beat = 60000.0 / 96.0;
+        let off = (tempo.last_beat_ms - 300.0).rem_euclid(beat);
+        assert!(off.min(beat - off) < 15.0, "beat {:.1}ms off the kicks", off.min(beat - off));
+        assert!((0.0..1.0).contains(&tempo.beat_phase));
+        assert!(tempo.next_beat_ms() > end - 1.0);
+    }
+
+    #[test]
+    fn follows_a_tempo_change() {
+        let mut tracker = BeatTracker::default();
+        let t = groove(&mut tracker, 90.0, 0.0, 16);
+        assert!((tracker.tempo_at(t).bpm - 90.0).abs() < 1.5);
+        let t = groove(&mut tracker, 128.0, t, 24);
+        let bpm = tracker.tempo_at(t).bpm;
+        assert!((bpm - 128.0).abs() < 2.0, "bpm {bpm}");
+    }
+
+    #[test]
+    fn stays_unsure_until_there_is_a_pulse() {
+        let mut tracker = BeatTracker::default();
+        tracker.observe(100.0, 1.0);
+        tracker.observe(600.0, 1.0);
+        assert_eq!(tracker.tempo_at(700.0).confidence, 0.0);
+
+        // Onsets at irrational spacings: no period explains them.
+        let mut scattered = BeatTracker::default();
+        let mut t = 0.0;
+        for i in 0..40 {
+            t += 180.0 + 377.0 * ((i as f64 * 0.618_034).fract());
+            scattered.observe(t, 1.0);
+        }
+        let confidence = scattered.tempo_at(t).confidence;
+        assert!(confidence < 0.35, "confidence {confidence}");
+
+        // Rejected noise never counts.
+        let mut noise = BeatTracker::default();
+        for i in 0..16 {
+            noise.observe(i as f64 * 500.0, beat_accent(EventClass::Noise));
+        }
+        assert_eq!(noise.tempo_at(8000.0).confidence, 0.0);
+    }
+
+    #[test]
+    fn snaps_to_the_live_grid() {
+        let tempo = LiveTempo { bpm: 120.0, beat_phase: 0.0, confidence: 1.0, last_beat_ms: 1000.0 };
+        assert_eq!(tempo.snap_ms(1130.0, 4), 1125.0);
+        assert_eq!(tempo.snap_ms(990.0, 2), 1000.0);
+        assert_eq!(tempo.next_beat_ms(), 1500.0);
+    }
+}
@@ -27,6 +27,14 @@ impl TimeSignature {
     pub fn beat_unit(&self) -> u32 {
         4 // Both use quarter notes as the beat unit
     }
+
+    /// Parse the snake_case name the frontend sends; unknown names are 4/4.
+    pub fn from_string(s: &str) -> Self {
+        match s {
+            "three_four" => TimeSignature::ThreeFour,
+            _ => TimeSignature::FourFour,
+        }
+    }
 }
 
 /// Grid division - defines the resolution of the musical grid
@@ -56,6 +64,16 @@ impl GridDivision {
             GridDivision::Triplet => 3,
         }
     }
+
+    /// Parse the snake_case name the frontend sends; unknown names are sixteenths.
+    pub fn from_string(s: &str) -> Self {
+        match s {
+            "quarter" => GridDivision::Quarter,
+            "eighth" => GridDivision::Eighth,
+            "triplet" => GridDivision::Triplet,
+            _ => GridDivision::Sixteenth,
+        }
+    }
 }
 
 /// Groove feel - affects timing and emphasis
@@ -72,8 +90,20 @@ pub enum GrooveFeel {
     Halftime,
 }
 
+impl GrooveFeel {
+    /// Parse the snake_case name the frontend sends; unknown names are straight.
+    pub fn from_string(s: &str) -> Self {
+        match s {
+            "swing" => GrooveFeel::Swing,
+            "halftime" => GrooveFeel::Halftime,
+            _ => GrooveFeel::Straight,
+        }
+    }
+}
+
 /// Grid position - describes location in musical time
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct GridPosition {
     /// Bar number (0-indexed)
     pub bar: u32,
@@ -2,9 +2,11 @@
 // Phase 5: Musical timing and quantization system
 
 pub mod tempo;
+pub mod beat_tracker;
 pub mod grid;
 pub mod quantize;
 
-pub use tempo::{TempoEstimate, estimate_tempo};
+pub use tempo::{TempoEstimate, estimate_tempo, estimate_tempo_at_bpm};
 pub use grid::{TimeSignature, GridDivision, GrooveFeel, Grid, GridPosition};
 pub use quantize::{QuantizeSettings, QuantizedEvent, quantize_events};
+pub use beat_tracker::{BeatTracker, LiveTempo, beat_accent};
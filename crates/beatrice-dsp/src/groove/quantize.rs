@@ -23,6 +23,11 @@ pub struct QuantizeSettings {
     /// Lookahead window in milliseconds
     /// How far ahead to search for matching grid position
     pub lookahead_ms: f64,
+
+    /// Quantize rejected (`Noise`) events too. Off by default: breaths, bumps
+    /// and mic clicks stay in the analysis but never reach the grid.
+    #[serde(default)]
+    pub include_rejected: bool,
 }
 
 impl Default for QuantizeSettings {
@@ -31,12 +36,14 @@ impl Default for QuantizeSettings {
             strength: 0.8,
             swing_amount: 0.0,
             lookahead_ms: 100.0,
+            include_rejected: false,
         }
     }
 }
 
 /// A quantized event with both original and quantized timing
-#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct QuantizedEvent {
     /// The original event data
     pub original_event: Event,
@@ -61,11 +68,18 @@ pub struct QuantizedEvent {
 /// 2. Apply strength factor to blend original and grid timing
 /// 3. Preserve relative timing within small groups (grace notes)
 /// 4. Handle swing timing for off-beats
+///
+/// Rejected events are dropped first unless `settings.include_rejected`.
 pub fn quantize_events(
     events: &[Event],
     grid: &Grid,
     settings: &QuantizeSettings,
 ) -> Vec<QuantizedEvent> {
+    let events: Vec<Event> = events
+        .iter()
+        .filter(|e| settings.include_rejected || !e.is_rejected())
+        .cloned()
+        .collect();
     if events.is_empty() {
         return Vec::new();
     }
@@ -73,7 +87,7 @@ pub fn quantize_events(
     let mut quantized = Vec::new();
 
     // Identify groups of closely-spaced events (grace notes, flams, etc.)
-    let groups = identify_event_groups(events, 30.0); // 30ms threshold for grouping
+    let groups = identify_event_groups(&events, 30.0); // 30ms threshold for grouping
 
     for group in groups {
         let group_events: Vec<&Event> = group.iter().map(|&idx| &events[idx]).collect();
@@ -264,6 +278,7 @@ mod tests {
             strength: 1.0, // Full quantization
             swing_amount: 0.0,
             lookahead_ms: 100.0,
+            include_rejected: false,
         };
 
         let event = create_test_event(520.0); // Slightly after second beat (500ms)
@@ -281,6 +296,7 @@ mod tests {
             strength: 0.5, // 50% quantization
             swing_amount: 0.0,
             lookahead_ms: 100.0,
+            include_rejected: false,
         };
 
         let event = create_test_event(520.0); // 20ms after grid position (500ms)
@@ -313,6 +329,7 @@ mod tests {
             strength: 1.0,
             swing_amount: 0.0,
             lookahead_ms: 100.0,
+            include_rejected: false,
         };
 
         let events = vec![
@@ -370,7 +387,7 @@ mod tests {
         let q = quantize_events(
             &events,
             &grid,
-            &QuantizeSettings { strength: 1.0, swing_amount: 0.0, lookahead_ms: 100.0 },
+            &QuantizeSettings { strength: 1.0, swing_amount: 0.0, lookahead_ms: 100.0, include_rejected: false },
         );
         assert_eq!(
             (q[0].grid_position.bar, q[0].grid_position.beat, q[0].grid_position.subdivision),
@@ -379,6 +396,23 @@ mod tests {
         assert!((q[0].snap_delta_ms).abs() < 1e-6); // was ~-70ms against a t=0 grid
     }
 
+    #[test]
+    fn rejected_events_are_left_off_the_grid_by_default() {
+        let grid = Grid::new(120.0, TimeSignature::FourFour, GridDivision::Sixteenth, 1);
+        let mut breath = create_test_event(260.0);
+        breath.class = EventClass::Noise;
+        let events = vec![create_test_event(0.0), breath, create_test_event(500.0)];
+
+        let q = quantize_events(&events, &grid, &QuantizeSettings::default());
+        assert_eq!(q.len(), 2);
+        assert!(q.iter().all(|e| !e.original_event.is_rejected()));
+
+        let settings = QuantizeSettings { include_rejected: true, ..QuantizeSettings::default() };
+        let q = quantize_events(&events, &grid, &settings);
+        assert_eq!(q.len(), 3);
+        assert_eq!(q[1].original_event.class, EventClass::Noise);
+    }
+
     #[test]
     fn test_zero_strength_preserves_timing() {
         let grid = Grid::new(120.0, TimeSignature::FourFour, GridDivision::Quarter, 1);
@@ -386,6 +420,7 @@ mod tests {
             strength: 0.0, // No quantization
             swing_amount: 0.0,
             lookahead_ms: 100.0,
+            include_rejected: false,
         };
 
         let event = create_test_event(520.0);
@@ -2,10 +2,11 @@
 // Uses IOI histogram and autocorrelation to find periodic structure
 
 use serde::{Deserialize, Serialize};
-use crate::audio::features::Onset;
+use crate::features::Onset;
 
 /// Tempo estimation result with BPM and beat grid positions
-#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct TempoEstimate {
     /// Estimated beats per minute
     pub bpm: f64,
@@ -142,6 +143,29 @@ pub fn estimate_tempo_with_config(
     }
 }
 
+/// Fit a beat grid to `onsets` at a tempo the caller already knows (e.g. a
+/// user override). Only the phase is searched; confidence is how well that
+/// grid lands on the onsets.
+pub fn estimate_tempo_at_bpm(onsets: &[Onset], bpm: f64) -> TempoEstimate {
+    let interval_ms = if bpm > 0.0 { 60000.0 / bpm } else { 0.0 };
+    let beat_positions_ms = generate_beat_grid(onsets, bpm, interval_ms);
+    let confidence = if onsets.is_empty() {
+        0.0
+    } else {
+        let last_onset = onsets[onsets.len() - 1].timestamp_ms;
+        let raw_alignment = best_phase_score(onsets, interval_ms, last_onset);
+        (raw_alignment / onsets.len() as f64).clamp(0.0, 1.0) as f32
+    };
+    let phase_offset_ms = beat_positions_ms.first().copied().unwrap_or(0.0);
+
+    TempoEstimate {
+        bpm,
+        confidence,
+        beat_positions_ms,
+        phase_offset_ms,
+    }
+}
+
 /// Compute inter-onset intervals (time between all pairs of onsets)
 /// Using all-to-all pairs helps find periodic structure in short samples
 fn compute_iois(onsets: &[Onset]) -> Vec<f64> {
@@ -655,6 +679,22 @@ mod tests {
         assert!((est.bpm - 80.0).abs() < 4.0, "got {}", est.bpm);
     }
 
+    #[test]
+    fn fixed_bpm_grid_keeps_the_given_spacing() {
+        // Onsets at 120 BPM, but the caller insists on 96: the grid must use
+        // the 625ms spacing and still find a phase on the onsets' timeline.
+        let onsets = onsets_every_ms(500.0, 12);
+        let est = estimate_tempo_at_bpm(&onsets, 96.0);
+        assert_eq!(est.bpm, 96.0);
+        assert!(est.beat_positions_ms.len() > 2);
+        assert_eq!(est.phase_offset_ms, est.beat_positions_ms[0]);
+        for pair in est.beat_positions_ms.windows(2) {
+            assert!((pair[1] - pair[0] - 625.0).abs() < 1e-6, "{pair:?}");
+        }
+        let on_tempo = estimate_tempo_at_bpm(&onsets, 120.0);
+        assert!(on_tempo.confidence > est.confidence);
+    }
+
     #[test]
     fn sparse_quarter_notes_do_not_fold_to_half_time() {
         // Mirrors test-pattern.wav's ACTUAL detected onsets (0/476/975/1474ms —
@@ -6,7 +6,7 @@
 // NOTE: WAV *decoding* (`ingest_wav`, via the `hound` crate) intentionally lives
 // in the native `beatrice` crate, not here — the AudioWorklet path feeds raw f32
 // render quanta directly and never touches `hound`. Only the data container and
-// its channel/duration helpers moved into the DSP crate.
+// its channel/duration/rate helpers moved into the DSP crate.
 
 /// Decoded audio with normalized f32 samples and metadata.
 #[derive(Debug, Clone)]
@@ -55,6 +55,41 @@ impl AudioData {
 
         mono
     }
+
+    /// Band-limited conversion to `sample_rate`, channel by channel (see
+    /// [`crate::resample`]). Event times are preserved: the filter adds no
+    /// delay. Equal rates return a clone.
+    pub fn resampled(&self, sample_rate: u32) -> AudioData {
+        if sample_rate == self.sample_rate || self.frame_count == 0 {
+            return AudioData { sample_rate, ..self.clone() };
+        }
+
+        let channels = self.channels.max(1) as usize;
+        let converted: Vec<Vec<f32>> = (0..channels)
+            .map(|ch| {
+                let channel: Vec<f32> =
+                    self.samples.iter().skip(ch).step_by(channels).copied().collect();
+                crate::resample::resample(&channel, self.sample_rate, sample_rate)
+            })
+            .collect();
+
+        let frame_count = converted[0].len();
+        let mut samples = Vec::with_capacity(frame_count * channels);
+        for frame_idx in 0..frame_count {
+            for channel in &converted {
+                samples.push(channel[frame_idx]);
+            }
+        }
+
+        AudioData {
+            samples,
+            sample_rate,
+            channels: self.channels,
+            bit_depth: self.bit_depth,
+            duration_ms: ((frame_count as f64 / sample_rate as f64) * 1000.0) as i64,
+            frame_count,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -96,4 +131,31 @@ mod tests {
 
         assert_eq!(audio_data.duration_secs(), 5.0);
     }
+
+    #[test]
+    fn test_resampled_keeps_channels_and_duration() {
+        // Stereo 48k: left = DC 0.5, right = DC -0.25.
+        let frames = 4800;
+        let samples: Vec<f32> = (0..frames).flat_map(|_| [0.5, -0.25]).collect();
+        let audio_data = AudioData {
+            samples,
+            sample_rate: 48000,
+            channels: 2,
+            bit_depth: 16,
+            duration_ms: 100,
+            frame_count: frames,
+        };
+
+        let out = audio_data.resampled(44100);
+
+        assert_eq!(out.sample_rate, 44100);
+        assert_eq!(out.channels, 2);
+        assert_eq!(out.frame_count, 4410);
+        assert_eq!(out.samples.len(), 4410 * 2);
+        assert_eq!(out.duration_ms, 100);
+        // Away from the edges each channel keeps its own level.
+        let mid = 2205;
+        assert!((out.samples[mid * 2] - 0.5).abs() < 1e-4);
+        assert!((out.samples[mid * 2 + 1] + 0.25).abs() < 1e-4);
+    }
 }
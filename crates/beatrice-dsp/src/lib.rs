@@ -1,34 +1,95 @@
 //! beatrice-dsp — the shared offline DSP core.
 //!
 //! This crate owns the deterministic "what" of Beatrice's analysis pipeline:
-//! audio ingest container ([`ingest::AudioData`]), spectral onset detection +
-//! feature extraction ([`features`]), event types and classification
-//! ([`events`]), and the causal [`streaming::StreamingDetector`] driven by the
-//! WASM worklet.
+//! audio ingest container ([`ingest::AudioData`]), spectral onset detection
+//! on a selectable [`onset_function`] with onsets refined to the transient by
+//! [`localize`], feature extraction ([`features`]), event types and classification
+//! ([`events`]), band-limited sample-rate conversion ([`resample`]), chunked
+//! [`source::AudioSource`] input for recordings too long to decode whole, an
+//! optional [`conditioning`] chain for noisy mics, room-tone
+//! [`noise::NoiseProfile`]s that adapt the onset gates to the room, decay-measured
+//! event durations ([`offsets`]), a shared STFT ([`spectrogram`]) and
+//! per-recording [`context::AnalysisContext`] for the offline loop, analysis of
+//! in-memory PCM ([`pcm`], the browser demo's offline path), and the causal
+//! [`streaming::StreamingDetector`] driven by the WASM worklet.
+//! It also owns the musical layer on top of the events: tempo, grid and
+//! quantization ([`groove`]), template-driven arrangement and MIDI export
+//! ([`arranger`]), the harmonic [`themes`] the arranger draws on, and the
+//! frontend's quantize/arrange/export requests ([`song`]).
 //! It is pure Rust with no Tauri dependency, so the identical code compiles for
-//! the native desktop app (`beatrice`, via `features = ["specta"]`) and for the
-//! browser AudioWorklet (`wasm-pack build --features wasm`).
+//! the native desktop app (`beatrice`, via `features = ["specta", "parallel"]`)
+//! and for the browser AudioWorklet (`wasm-pack build --features wasm`).
 //!
 //! The native `beatrice` crate consumes this crate and re-exports its modules
-//! through thin shims (`beatrice_lib::audio::features`, `beatrice_lib::events`)
-//! so every existing call site keeps compiling unchanged.
+//! through thin shims (`beatrice_lib::audio::features`, `beatrice_lib::events`,
+//! `beatrice_lib::groove`, …) so every existing call site keeps compiling
+//! unchanged.
 
+pub mod arranger;
+pub mod channels;
+pub mod conditioning;
+pub mod context;
 pub mod events;
 pub mod features;
+pub mod groove;
 pub mod ingest;
+pub mod localize;
+pub mod noise;
+pub mod offsets;
+pub mod onset_function;
+pub mod pcm;
+pub mod resample;
+mod snapshot;
+pub mod song;
+pub mod source;
+pub mod spectrogram;
 pub mod streaming;
+pub mod themes;
 
+use std::borrow::Cow;
+
+pub use channels::ChannelStrategy;
+pub use conditioning::{
+    condition, Conditioner, ConditioningConfig, LevelMeter, NoiseReduction, Normalization,
+};
+pub use context::AnalysisContext;
 pub use events::{
-    gaussian_features, CalibrationProfile, CalibrationSample, ClassScore, ClassificationResult,
-    ClassifierConfig, Event, EventClass, EventFeatures, GaussianModel, HeuristicClassifier,
-    HybridClassifier, KnnClassifier, DEFAULT_MAP_TAU, GAUSSIAN_DIMS,
+    build_classifier, gaussian_features, CalibrationProfile, CalibrationReport, CalibrationSample, ClassScore,
+    ClassificationResult, ClassifierConfig, ClassifierInput, Event, EventClass, EventClassifier,
+    EventData, EventFeatures, GaussianModel, HeuristicClassifier, HybridClassifier, KnnClassifier,
+    MultiLabelConfig, PitchPoint, CLASSIFIERS, DEFAULT_CLASSIFIER, DEFAULT_MAP_TAU, FACTORY_SAMPLE_RATE, GAUSSIAN_DIMS,
 };
 pub use features::{
     detect_onsets, extract_features, extract_features_for_window, extract_mfcc,
-    extract_mfcc_for_window, extract_mfcc_stats, Onset, OnsetConfig, MFCC_COEFFS,
+    extract_mfcc_for_window, extract_mfcc_stats, extract_window, track_pitch, track_pitch_for_window, Onset,
+    OnsetConfig, OnsetScanner, MFCC_COEFFS, PITCH_CONTOUR_MAX_MS,
 };
 pub use ingest::AudioData;
-pub use streaming::{LiveEvent, StreamingConfig, StreamingDetector};
+pub use noise::NoiseProfile;
+pub use onset_function::{OnsetDetectionFunction, OnsetFunction};
+pub use pcm::{analyze_pcm, PcmAnalysisOptions};
+pub use resample::{resample, Resampler};
+pub use song::{ArrangeEventsInput, ExportMidiInput, QuantizeEventsInput};
+pub use source::{AudioSource, MemorySource, ResampledSource};
+pub use spectrogram::Spectrogram;
+pub use streaming::{
+    LiveEvent, LiveStage, StreamingConfig, StreamingDetector, StreamingSnapshot, PROVISIONAL_WINDOW_MS,
+};
+
+/// Convert `audio` to the factory model's training rate
+/// ([`FACTORY_SAMPLE_RATE`]) — the pipeline's opt-in normalization stage.
+///
+/// Run it before [`analyze_offline_hybrid`] so onset detection, the scalar
+/// features and the MFCCs all see 44.1 kHz statistics whatever the source
+/// rate; event timestamps are unchanged (the resampler adds no delay). Audio
+/// already at the training rate is borrowed, not copied.
+pub fn normalize_sample_rate(audio: &AudioData) -> Cow<'_, AudioData> {
+    if audio.sample_rate == FACTORY_SAMPLE_RATE {
+        Cow::Borrowed(audio)
+    } else {
+        Cow::Owned(audio.resampled(FACTORY_SAMPLE_RATE))
+    }
+}
 
 /// Run the offline heuristic analysis pipeline over decoded audio.
 ///
@@ -39,35 +100,45 @@ pub use streaming::{LiveEvent, StreamingConfig, StreamingDetector};
 /// not quantize or arrange — those stay in the native crate's groove/arranger
 /// layers.
 ///
-/// Per-event duration is the gap to the next onset (or to end-of-audio for the
-/// last onset); the feature window is that duration clamped to `[50, 500]` ms.
+/// Per-event duration is measured from the sound's decay ([`offsets`]) and
+/// `gap_ms` is the gap to the next onset (or to end-of-audio for the last
+/// onset); the feature window is that gap clamped to `[50, 500]` ms.
+/// Onsets honour `cfg.channels`, but features are always taken from the
+/// channel average (conditioned, if `cfg.conditioning` is set);
+/// [`analyze_offline_hybrid`] follows the strategy for both. `HumVoiced`
+/// events carry the pitch contour of their first [`PITCH_CONTOUR_MAX_MS`].
+///
+/// The signal is mixed and conditioned once ([`AnalysisContext`]) and the
+/// onsets are classified independently, across threads with the `parallel`
+/// feature; events come back in onset order either way.
 pub fn analyze_offline(audio: &AudioData, cfg: &OnsetConfig) -> Vec<Event> {
-    let onsets = detect_onsets(audio, cfg);
+    let ctx = AnalysisContext::new(audio, cfg);
     let classifier = HeuristicClassifier::new();
-    let mut events = Vec::with_capacity(onsets.len());
 
-    for (i, onset) in onsets.iter().enumerate() {
-        let duration_ms = if i + 1 < onsets.len() {
-            onsets[i + 1].timestamp_ms - onset.timestamp_ms
+    ctx.map_onsets(|i, onset| {
+        let gap_ms = ctx.gap_ms(i);
+        let window_duration_ms = gap_ms.clamp(50.0, 500.0);
+        let features = ctx.features(onset.timestamp_ms, window_duration_ms);
+        let result = classifier.classify(&features);
+        let contour = if result.class == EventClass::HumVoiced {
+            ctx.pitch(onset.timestamp_ms, gap_ms.min(PITCH_CONTOUR_MAX_MS))
         } else {
-            audio.duration_ms as f64 - onset.timestamp_ms
+            Vec::new()
         };
-        let window_duration_ms = duration_ms.clamp(50.0, 500.0);
-        let features = extract_features_for_window(audio, onset.timestamp_ms, window_duration_ms);
-        let result = classifier.classify(&features);
-        events.push(
-            Event::new(onset.timestamp_ms, duration_ms, result.class, result.confidence, features)
-                .with_scores(result.class_scores()),
-        );
-    }
-
-    events
+        let tail = ctx.window(onset.timestamp_ms, gap_ms.clamp(0.0, PITCH_CONTOUR_MAX_MS));
+        let duration_ms = offsets::measure_duration(tail, ctx.sample_rate(), gap_ms, &contour);
+        Event::new(onset.timestamp_ms, duration_ms, result.class, result.confidence, features)
+            .with_gap(gap_ms)
+            .with_scores(result.class_scores())
+            .with_pitch_contour(contour)
+    })
 }
 
-/// Run the offline analysis pipeline with the [`HybridClassifier`] (the
-/// AVP-fitted Gaussian MFCC model + heuristic hum gate) — the app's shipping
-/// classification path since the AVP benchmark showed it at 81.6%
-/// participant-wise vs 65.8% for the pure heuristic.
+/// Run the offline analysis pipeline with any [`EventClassifier`]. The app
+/// passes the [`HybridClassifier`] (the AVP-fitted Gaussian MFCC model +
+/// heuristic hum gate) — its shipping classification path since the AVP
+/// benchmark showed it at 81.6% participant-wise vs 65.8% for the pure
+/// heuristic — unless another is picked by name ([`build_classifier`]).
 ///
 /// Same onset/duration logic as [`analyze_offline`], but the classifier's
 /// scalar features AND MFCCs are both extracted over a fixed
@@ -75,64 +146,306 @@ pub fn analyze_offline(audio: &AudioData, cfg: &OnsetConfig) -> Vec<Event> {
 /// model was fitted with and the streaming path uses. (The heuristic
 /// [`analyze_offline`] keeps the variable gap-to-next-onset feature window; its
 /// golden freeze tests pin the extraction refactor, so it must not change.)
-/// `Event.duration_ms` still reports the musical gap to the next onset.
+/// `Event.duration_ms` is measured from the sound's decay, `Event.gap_ms` is
+/// the gap to the next onset, and `HumVoiced` events carry the pitch contour
+/// of that gap (up to [`PITCH_CONTOUR_MAX_MS`]).
 pub fn analyze_offline_hybrid(
     audio: &AudioData,
     cfg: &OnsetConfig,
-    classifier: &HybridClassifier,
+    classifier: &dyn EventClassifier,
 ) -> Vec<Event> {
-    let onsets = detect_onsets(audio, cfg);
-    let mono = audio.to_mono();
-    let mut events = Vec::with_capacity(onsets.len());
+    match analyze_source_hybrid(&mut audio.source(), cfg, classifier, &mut |_| {}) {
+        Ok(events) => events,
+        Err(never) => match never {},
+    }
+}
+
+/// Frames read per chunk by [`analyze_source_hybrid`] (~1.5s at 44.1 kHz).
+const SOURCE_CHUNK_FRAMES: usize = 1 << 16;
+
+/// Progress of [`analyze_source_hybrid`], reported once per chunk read.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnalysisProgress {
+    /// Pass 1, onset detection: `frames` read so far.
+    Scanning { frames: usize, total_frames: Option<usize> },
+    /// Pass 1 finished.
+    OnsetsDetected { count: usize },
+    /// Pass 2, feature extraction and classification.
+    Classifying { done: usize, total: usize },
+}
+
+/// [`analyze_offline_hybrid`] over a chunked [`AudioSource`], in memory
+/// bounded by the chunk size rather than the recording length.
+///
+/// Onset peak picking needs whole-recording flux statistics, so the source is
+/// read twice: pass 1 feeds an [`OnsetScanner`], which keeps only the per-hop
+/// envelopes; pass 2 rewinds and, as the audio streams past, collects the
+/// window after each onset: the fixed feature window, or the gap to the next
+/// onset when that is longer, which the pitch contour and the measured
+/// duration need. The events are the same as decoding the whole source into
+/// an [`AudioData`] and calling [`analyze_offline_hybrid`].
+///
+/// Pass 1's spectra serve the flux only. Each window is framed afresh from its
+/// localized onset by [`extract_window`]: the detector's hop grid sits up to
+/// half a hop away, and reading the centroid, bands and mel frames off it
+/// would move the features the factory model was fitted on.
+///
+/// `cfg.channels` picks the signal(s) analysed. [`ChannelStrategy::MaxEnergy`]
+/// costs one more read to measure the channels; multi-lane strategies scan
+/// every lane, merge the onsets (the strongest lane wins a shared hit) and
+/// take each event's features from the lane that won it.
+///
+/// With `cfg.multi_label` set, an onset carrying layered sounds (a kick with
+/// a hat on top) yields one event per sound, in label order at the same
+/// timestamp.
+///
+/// With `cfg.conditioning` set, each lane runs through a [`Conditioner`]
+/// before both passes; normalization costs one more read to measure each
+/// lane's level, so the gain is fixed for the whole recording.
+///
+/// The windows completed by each chunk are classified together, across
+/// threads with the `parallel` feature; `Classifying` progress still counts
+/// up one onset at a time.
+pub fn analyze_source_hybrid<S: AudioSource + ?Sized>(
+    source: &mut S,
+    cfg: &OnsetConfig,
+    classifier: &dyn EventClassifier,
+    progress: &mut dyn FnMut(AnalysisProgress),
+) -> Result<Vec<Event>, S::Error> {
+    let sample_rate = source.sample_rate();
+    let channels = source.channels();
+    let total_frames = source.total_frames();
+    let mut chunk = Vec::new();
+    let mut mono = Vec::new();
 
-    for (i, onset) in onsets.iter().enumerate() {
-        let duration_ms = if i + 1 < onsets.len() {
-            onsets[i + 1].timestamp_ms - onset.timestamp_ms
+    let energies = if cfg.channels.needs_energies() && channels > 1 {
+        let mut energies = vec![0.0; channels as usize];
+        loop {
+            chunk.clear();
+            if source.read(&mut chunk, SOURCE_CHUNK_FRAMES)? == 0 {
+                break;
+            }
+            channels::accumulate_energies(&chunk, channels, &mut energies);
+        }
+        source.rewind()?;
+        Some(energies)
+    } else {
+        None
+    };
+    let lanes = cfg.channels.lanes(channels, energies.as_deref());
+
+    let gains = match cfg.conditioning.and_then(|c| c.normalize.map(|target| (c, target))) {
+        Some((conditioning, target)) => {
+            let mut probes: Vec<conditioning::GainProbe> = lanes
+                .iter()
+                .map(|_| conditioning::GainProbe::new(sample_rate, &conditioning, cfg.noise_profile.as_ref()))
+                .collect();
+            loop {
+                chunk.clear();
+                if source.read(&mut chunk, SOURCE_CHUNK_FRAMES)? == 0 {
+                    break;
+                }
+                for (lane, probe) in lanes.iter().zip(&mut probes) {
+                    lane.mix_into(&chunk, channels, &mut mono);
+                    probe.push(&mono);
+                }
+            }
+            source.rewind()?;
+            probes.into_iter().map(|probe| probe.gain(target)).collect()
+        }
+        None => vec![1.0; lanes.len()],
+    };
+    let lane_conditioners = || -> Vec<conditioning::LaneConditioner> {
+        gains
+            .iter()
+            .map(|&gain| {
+                conditioning::LaneConditioner::new(
+                    sample_rate,
+                    cfg.conditioning.as_ref(),
+                    gain,
+                    cfg.noise_profile.as_ref(),
+                )
+            })
+            .collect()
+    };
+
+    // Pass 1: onsets, per lane.
+    let mut scanners: Vec<OnsetScanner> =
+        lanes.iter().map(|_| OnsetScanner::new(sample_rate, cfg.clone())).collect();
+    let mut conditioners = lane_conditioners();
+    let mut frames = 0;
+    loop {
+        chunk.clear();
+        let read = source.read(&mut chunk, SOURCE_CHUNK_FRAMES)?;
+        if read == 0 {
+            break;
+        }
+        frames += read;
+        for ((lane, scanner), conditioner) in lanes.iter().zip(&mut scanners).zip(&mut conditioners) {
+            lane.mix_into(&chunk, channels, &mut mono);
+            scanner.push(conditioner.apply(&mono));
+        }
+        progress(AnalysisProgress::Scanning { frames, total_frames });
+    }
+    for (scanner, conditioner) in scanners.iter_mut().zip(&mut conditioners) {
+        scanner.push(conditioner.finish());
+    }
+    let duration_ms = ((frames as f64 / sample_rate as f64) * 1000.0) as i64;
+    let per_lane: Vec<Vec<Onset>> = scanners.into_iter().map(OnsetScanner::finish).collect();
+    let onsets: Vec<(usize, Onset)> = if per_lane.len() == 1 {
+        per_lane.into_iter().flatten().map(|o| (0, o)).collect()
+    } else {
+        channels::merge_onsets(per_lane, cfg.min_onset_gap_ms)
+    };
+    progress(AnalysisProgress::OnsetsDetected { count: onsets.len() });
+    if onsets.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Pass 2: per-onset features. Scalar features AND MFCCs over the SAME
+    // fixed window the factory model was fitted with (and the streaming path
+    // uses). The Gaussian's zcr/crest dims and the hum gate are calibrated to
+    // 150ms statistics; a variable gap-to-next-onset window (up to 500ms of
+    // decay/silence) skews both.
+    let window_ms = events::hybrid::HYBRID_MFCC_WINDOW_MS;
+    let window_len = ((window_ms / 1000.0) * sample_rate as f64) as usize;
+    let gap_ms = |i: usize| {
+        let onset = &onsets[i].1;
+        if i + 1 < onsets.len() {
+            onsets[i + 1].1.timestamp_ms - onset.timestamp_ms
         } else {
-            audio.duration_ms as f64 - onset.timestamp_ms
-        };
-        // Scalar features over the SAME fixed window the factory model was
-        // fitted with (and the streaming path uses). The Gaussian's zcr/crest
-        // dims and the hum gate are calibrated to 150ms statistics; a variable
-        // gap-to-next-onset window (up to 500ms of decay/silence) skews both.
-        let features = extract_features_for_window(
-            audio,
-            onset.timestamp_ms,
-            events::hybrid::HYBRID_MFCC_WINDOW_MS,
-        );
+            duration_ms as f64 - onset.timestamp_ms
+        }
+    };
+    // A hum's contour and every event's decay span its whole gap, so each
+    // onset collects the longer of the feature window and that gap;
+    // classification reads the prefix.
+    let contour_len = |i: usize| {
+        ((gap_ms(i).clamp(0.0, PITCH_CONTOUR_MAX_MS) / 1000.0) * sample_rate as f64) as usize
+    };
+    // Each lane collects the windows of the onsets it won; `owners` maps a
+    // lane's window index back to the merged onset index.
+    let mut owners: Vec<Vec<usize>> = vec![Vec::new(); lanes.len()];
+    for (i, (lane, _)) in onsets.iter().enumerate() {
+        owners[*lane].push(i);
+    }
+    let mut collectors: Vec<source::WindowCollector> = owners
+        .iter()
+        .map(|owned| {
+            let starts = owned
+                .iter()
+                .map(|&i| ((onsets[i].1.timestamp_ms / 1000.0) * sample_rate as f64) as usize)
+                .collect();
+            let lens = owned.iter().map(|&i| window_len.max(contour_len(i))).collect();
+            source::WindowCollector::new(starts, lens)
+        })
+        .collect();
 
-        let start = ((onset.timestamp_ms / 1000.0) * audio.sample_rate as f64) as usize;
-        let mfcc_len = ((events::hybrid::HYBRID_MFCC_WINDOW_MS / 1000.0)
-            * audio.sample_rate as f64) as usize;
-        let end = (start + mfcc_len).min(mono.len());
-        let mfcc = if start < end {
-            extract_mfcc(&mono[start..end], audio.sample_rate)
+    let classify = |i: usize, collected: &[f32]| -> Vec<Event> {
+        let onset = &onsets[i].1;
+        let window = &collected[..collected.len().min(window_len)];
+        let (features, mfcc) = extract_window(window, sample_rate);
+        let result = classifier
+            .classify(&ClassifierInput::new(&features, &mfcc).with_window(window, sample_rate));
+        let labels = match &cfg.multi_label {
+            Some(multi_label) => multi_label.labels(&result, &features),
+            None => vec![(result.class, result.confidence)],
+        };
+        // The sound's own span: up to the next onset, at most the contour cap.
+        let tail = &collected[..collected.len().min(contour_len(i))];
+        let contour = if labels.iter().any(|(class, _)| *class == EventClass::HumVoiced) {
+            track_pitch(tail, sample_rate)
         } else {
-            vec![0.0; MFCC_COEFFS]
+            Vec::new()
         };
+        let duration_ms = offsets::measure_duration(tail, sample_rate, gap_ms(i), &contour);
+        // One event per sound on the onset; layers share its window, scores
+        // and timing, and only the hum carries the contour.
+        labels
+            .into_iter()
+            .map(|(class, confidence)| {
+                let contour =
+                    if class == EventClass::HumVoiced { contour.clone() } else { Vec::new() };
+                Event::new(onset.timestamp_ms, duration_ms, class, confidence, features.clone())
+                    .with_gap(gap_ms(i))
+                    .with_scores(result.class_scores())
+                    .with_pitch_contour(contour)
+            })
+            .collect()
+    };
 
-        let result = classifier.classify(&features, &mfcc);
-        events.push(
-            Event::new(onset.timestamp_ms, duration_ms, result.class, result.confidence, features)
-                .with_scores(result.class_scores()),
-        );
+    // The collectors hand over each onset's window as the stream passes its
+    // end; a chunk's windows are classified together (across threads with
+    // the `parallel` feature) before the next read.
+    let mut events: Vec<Vec<Event>> = vec![Vec::new(); onsets.len()];
+    let mut done = 0;
+    let mut pending: Vec<(usize, Vec<f32>)> = Vec::new();
+    let mut flush = |pending: &mut Vec<(usize, Vec<f32>)>| {
+        let classified = context::map_indices(pending.len(), |k| classify(pending[k].0, &pending[k].1));
+        for ((i, _), labelled) in pending.drain(..).zip(classified) {
+            events[i] = labelled;
+            done += 1;
+            progress(AnalysisProgress::Classifying { done, total: onsets.len() });
+        }
+    };
+
+    source.rewind()?;
+    let mut conditioners = lane_conditioners();
+    loop {
+        chunk.clear();
+        if source.read(&mut chunk, SOURCE_CHUNK_FRAMES)? == 0 {
+            break;
+        }
+        for (((lane, collector), owned), conditioner) in
+            lanes.iter().zip(&mut collectors).zip(&owners).zip(&mut conditioners)
+        {
+            if owned.is_empty() {
+                continue;
+            }
+            lane.mix_into(&chunk, channels, &mut mono);
+            collector.push(conditioner.apply(&mono), &mut |k, window| pending.push((owned[k], window.to_vec())));
+        }
+        flush(&mut pending);
     }
+    for ((mut collector, owned), conditioner) in collectors.into_iter().zip(&owners).zip(&mut conditioners) {
+        if !owned.is_empty() {
+            collector.push(conditioner.finish(), &mut |k, window| pending.push((owned[k], window.to_vec())));
+        }
+        collector.finish(&mut |k, window| pending.push((owned[k], window.to_vec())));
+    }
+    flush(&mut pending);
 
-    events
+    Ok(events.into_iter().flatten().collect())
 }
 
 /// Stable numeric id for an [`EventClass`], for the JSON-free WASM ABI.
 ///
 /// Matches the enum declaration order and the frontend `tauri-mock` convention
-/// (`0` = plosive/kick, `1` = hi-hat, `2` = click/snare, `3` = hum). The worklet
-/// maps these back to class names, so this ordering is part of the ABI contract
-/// — do not reorder without updating `detector.worklet.ts`.
+/// (`0` = plosive/kick, `1` = hi-hat, `2` = click/snare, `3` = hum, `4` = open
+/// hat, `5` = rimshot, `6` = crash, `7` = rejected noise). The worklet maps these back to class
+/// names, so this ordering is part of the ABI contract — append new classes,
+/// never reorder, and update `detector.worklet.ts` and `jamBuffer.ts` in step.
 pub fn class_id(class: EventClass) -> f32 {
     match class {
         EventClass::BilabialPlosive => 0.0,
         EventClass::HihatNoise => 1.0,
         EventClass::Click => 2.0,
         EventClass::HumVoiced => 3.0,
+        EventClass::OpenHihat => 4.0,
+        EventClass::Rimshot => 5.0,
+        EventClass::Crash => 6.0,
+        EventClass::Noise => 7.0,
+    }
+}
+
+/// Stable numeric id for a [`LiveStage`], the last float of each WASM event
+/// record: `0` = final, `1` = provisional, `2` = revised. Part of the ABI
+/// contract like [`class_id`].
+pub fn stage_id(stage: LiveStage) -> f32 {
+    match stage {
+        LiveStage::Final => 0.0,
+        LiveStage::Provisional => 1.0,
+        LiveStage::Revised => 2.0,
     }
 }
 
@@ -141,14 +454,16 @@ use wasm_bindgen::prelude::*;
 
 /// Number of `f32`s per event record in the [`WasmDetector::push`] ABI:
 /// `[t_ms, class_id, confidence, centroid, zcr, low, mid, high, peak, crest,
-/// mfcc1..mfcc20]`. Floats 3..10 are the [`EventFeatures`] and 10..30 the mean
-/// MFCCs of the classification window, forwarded so the main thread can send a
-/// detected event back as a labeled calibration sample without re-deriving
-/// features. The worklet decodes in strides of this size, so it is part of the
-/// ABI contract — bump it in lockstep on both sides
-/// (`src/worklet/detector.worklet.ts` EVENT_STRIDE).
+/// mfcc1..mfcc20, stage]`. Floats 3..10 are the [`EventFeatures`] and 10..30
+/// the mean MFCCs of the classification window, forwarded so the main thread
+/// can send a detected event back as a labeled calibration sample without
+/// re-deriving features. Float 30 is the [`LiveStage`] ([`stage_id`]). The
+/// worklet decodes in strides of this size, so it is part of the ABI
+/// contract — bump it in lockstep on both sides
+/// (`src/worklet/detector.worklet.ts` EVENT_STRIDE). New classes only add
+/// [`class_id`] values (0-7 today); the stride is unchanged.
 #[cfg(feature = "wasm")]
-pub const WASM_EVENT_STRIDE: usize = 10 + crate::features::MFCC_COEFFS;
+pub const WASM_EVENT_STRIDE: usize = 11 + crate::features::MFCC_COEFFS;
 
 /// WASM surface over the causal [`StreamingDetector`], driven by the
 /// AudioWorklet one render quantum at a time.
@@ -156,38 +471,116 @@ pub const WASM_EVENT_STRIDE: usize = 10 + crate::features::MFCC_COEFFS;
 /// # ABI (JSON-free, no serde in the hot path)
 ///
 /// [`push`](Self::push) returns a flat `Float32Array` of [`WASM_EVENT_STRIDE`]
-/// (30) floats per event: `[t_ms, class_id, confidence, centroid, zcr,
-/// low_band, mid_band, high_band, peak, crest, mfcc1..mfcc20]`. An empty array
-/// means "no event this quantum" (the common case). The length is always a
-/// multiple of the stride. `class_id` is [`class_id`]'s mapping; floats 3..10
-/// are the event's [`EventFeatures`] in struct-declaration order and floats
-/// 10..30 the classification window's mean MFCCs. The worklet reads the
-/// records and posts one `{ type: "event", tMs, classId, conf, features }`
-/// message per event (features = all 27 trailing floats). The features let the
+/// (31) floats per event: `[t_ms, class_id, confidence, centroid, zcr,
+/// low_band, mid_band, high_band, peak, crest, mfcc1..mfcc20, stage]`. An
+/// empty array means "no event this quantum" (the common case). The length is
+/// always a multiple of the stride. `class_id` is [`class_id`]'s mapping;
+/// floats 3..10 are the event's [`EventFeatures`] in struct-declaration order,
+/// floats 10..30 the classification window's mean MFCCs and float 30 the
+/// [`stage_id`]. The worklet reads the records and posts one
+/// `{ type: "event", tMs, classId, conf, features, stage }` message per event
+/// (features = the 27 floats after the header). The features let the
 /// calibration panel echo a detected event back via
 /// [`add_calibration_sample`](Self::add_calibration_sample) as a labeled sample.
 ///
+/// # Tempo
+///
+/// [`tempo_bpm`](Self::tempo_bpm), [`beat_phase`](Self::beat_phase),
+/// [`tempo_confidence`](Self::tempo_confidence) and
+/// [`last_beat_ms`](Self::last_beat_ms) read the detector's live beat tracker
+/// ([`StreamingDetector::tempo`]) as plain numbers, so the worklet can post a
+/// `{ type: "tempo", ... }` message after any quantum that emitted events
+/// without allocating.
+///
+/// # Provisional events
+///
+/// [`set_provisional`](Self::set_provisional) turns on two-stage emission: a
+/// provisional record (stage 1) a few ms after each onset is confirmed, and a
+/// revised record (stage 2, same `t_ms`) only when the full window's class
+/// differs. Off, every record is final (stage 0).
+///
+/// # Snapshots
+///
+/// [`snapshot`](Self::snapshot) serializes the whole detector state
+/// ([`StreamingDetector::snapshot`]) and [`restore`](Self::restore) rebuilds
+/// it, so a paused jam, a new worklet instance or a restarted AudioContext
+/// picks the stream up exactly where it stopped.
+///
 /// # Calibration (Task 5, few-shot personalization)
 ///
 /// [`add_calibration_sample`](Self::add_calibration_sample) feeds a labeled
 /// example into the live profile; [`set_calibration_enabled`](Self::set_calibration_enabled)
 /// flips the FACTORY/YOURS A/B toggle. Both are cheap main→worklet messages.
+/// [`calibration_report_json`](Self::calibration_report_json) grades what was
+/// taught ([`CalibrationReport`]) once a teach completes.
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub struct WasmDetector(StreamingDetector);
+pub struct WasmDetector {
+    detector: StreamingDetector,
+    /// Reused event buffer for [`push`](Self::push).
+    events: Vec<LiveEvent>,
+}
+
+#[cfg(feature = "wasm")]
+impl From<StreamingDetector> for WasmDetector {
+    fn from(detector: StreamingDetector) -> Self {
+        WasmDetector { detector, events: Vec::with_capacity(16) }
+    }
+}
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
 impl WasmDetector {
     #[wasm_bindgen(constructor)]
     pub fn new(sample_rate: u32) -> Self {
-        Self(StreamingDetector::new(sample_rate))
+        StreamingDetector::new(sample_rate).into()
+    }
+
+    /// A detector that resamples the worklet's input to the factory model's
+    /// training rate before analysis
+    /// ([`StreamingDetector::at_model_rate`]); same ABI as [`new`](Self::new).
+    pub fn at_model_rate(sample_rate: u32) -> Self {
+        StreamingDetector::at_model_rate(sample_rate).into()
+    }
+
+    /// [`at_model_rate`](Self::at_model_rate) with the default
+    /// [`ConditioningConfig`] chain in front of detection, for noisy rooms
+    /// and quiet mics; same ABI as [`new`](Self::new).
+    pub fn conditioned_at_model_rate(sample_rate: u32) -> Self {
+        StreamingDetector::with_config(
+            sample_rate,
+            StreamingConfig {
+                analysis_sample_rate: Some(FACTORY_SAMPLE_RATE),
+                conditioning: Some(ConditioningConfig::default()),
+                ..StreamingConfig::default()
+            },
+        )
+        .into()
+    }
+
+    /// [`conditioned_at_model_rate`](Self::conditioned_at_model_rate) adapted
+    /// to a room: `profile_json` is a [`NoiseProfile`] as produced by
+    /// [`noise_profile_json`] (or persisted by the native app). A profile that
+    /// doesn't parse is ignored rather than failing the jam session.
+    pub fn conditioned_for_room(sample_rate: u32, profile_json: &[u8]) -> Self {
+        let mut cfg = StreamingConfig {
+            analysis_sample_rate: Some(FACTORY_SAMPLE_RATE),
+            conditioning: Some(ConditioningConfig::default()),
+            ..StreamingConfig::default()
+        };
+        if let Ok(profile) = NoiseProfile::from_json_bytes(profile_json) {
+            profile.adapt_streaming_config(&mut cfg);
+        }
+        StreamingDetector::with_config(sample_rate, cfg).into()
     }
 
     /// Push one render quantum. Returns [`WASM_EVENT_STRIDE`]-float records
     /// (flat) for every event confirmed during this quantum; empty if none.
+    /// Only a quantum that completes an event allocates (its records).
     pub fn push(&mut self, samples: &[f32]) -> Vec<f32> {
-        encode_live_events(self.0.push(samples))
+        self.events.clear();
+        self.detector.push_into(samples, &mut self.events);
+        encode_live_events(&self.events)
     }
 
     /// End-of-stream drain: classify every still-pending onset over whatever
@@ -195,15 +588,65 @@ impl WasmDetector {
     /// layout as [`push`](Self::push). Call once when the jam session stops so
     /// the final hit isn't dropped; subsequent calls return empty.
     pub fn flush(&mut self) -> Vec<f32> {
-        encode_live_events(self.0.flush())
+        encode_live_events(&self.detector.flush())
+    }
+
+    /// Emit a provisional verdict from the first [`PROVISIONAL_WINDOW_MS`] of
+    /// each onset, followed by a revision if the full window disagrees
+    /// ([`StreamingDetector::set_provisional_window_ms`]). Off by default.
+    pub fn set_provisional(&mut self, enabled: bool) {
+        self.detector.set_provisional_window_ms(enabled.then_some(PROVISIONAL_WINDOW_MS));
+    }
+
+    /// The detector's state as compact JSON bytes, between quanta (it
+    /// allocates; see [`StreamingDetector::snapshot`]).
+    pub fn snapshot(&self) -> Vec<u8> {
+        self.detector.snapshot().to_json_bytes().unwrap_or_default()
+    }
+
+    /// Rebuild a detector from [`snapshot`](Self::snapshot) bytes, to push the
+    /// rest of the stream at `sample_rate`. Throws if the bytes don't parse or
+    /// the snapshot was taken at another input rate (the stream's clock would
+    /// no longer match).
+    pub fn restore(bytes: &[u8], sample_rate: u32) -> Result<WasmDetector, String> {
+        let snapshot = StreamingSnapshot::from_json_bytes(bytes).map_err(|e| e.to_string())?;
+        let detector = StreamingDetector::restore(snapshot)?;
+        if detector.input_sample_rate() != sample_rate {
+            return Err(format!(
+                "snapshot was taken at {} Hz, not {sample_rate} Hz",
+                detector.input_sample_rate()
+            ));
+        }
+        Ok(detector.into())
+    }
+
+    /// Live tempo in beats per minute (120 until the tracker locks).
+    pub fn tempo_bpm(&self) -> f64 {
+        self.detector.tempo().bpm
+    }
+
+    /// Fraction of the current beat elapsed at the stream head, in `[0, 1)`.
+    pub fn beat_phase(&self) -> f64 {
+        self.detector.tempo().beat_phase
+    }
+
+    /// Confidence in the live tempo and phase, `[0, 1]`; 0 until locked.
+    pub fn tempo_confidence(&self) -> f32 {
+        self.detector.tempo().confidence
+    }
+
+    /// Stream time (ms, the events' `t_ms` clock) of the most recent beat.
+    pub fn last_beat_ms(&self) -> f64 {
+        self.detector.tempo().last_beat_ms
     }
 
     /// Add a labeled calibration sample from the main thread. `class_id` is the
-    /// [`class_id`] mapping (0=kick, 1=hihat, 2=snare/click, 3=hum); `features`
-    /// is the 27-float `[EventFeatures 7, mfcc 20]` vector (same order as the
-    /// trailing floats in [`push`](Self::push)). A legacy 7-float slice still
-    /// works (MFCCs default to zero); shorter/garbled slices are ignored so a
-    /// malformed message can never poison the profile.
+    /// [`class_id`] mapping (0=kick, 1=hihat, 2=snare/click, 3=hum, 4=open hat,
+    /// 5=rimshot, 6=crash, 7=noise); `features` is the 27-float
+    /// `[EventFeatures 7, mfcc 20]` vector (same order as the trailing floats
+    /// in [`push`](Self::push)). A legacy 7-float slice still works (MFCCs
+    /// default to zero); shorter/garbled slices are ignored so a malformed
+    /// message can never poison the profile.
     pub fn add_calibration_sample(&mut self, class_id: u32, features: &[f32]) {
         if features.len() < 7 {
             return;
@@ -224,19 +667,19 @@ impl WasmDetector {
             .unwrap_or_default();
         // raw_window empty: the live path stores features only (the offline
         // pipeline re-derives features from audio; live samples never train ML).
-        self.0.add_calibration_sample(CalibrationSample::with_mfcc(
+        self.detector.add_calibration_sample(CalibrationSample::with_mfcc(
             class,
             feats,
             mfcc,
             Vec::new(),
-            self.0.sample_rate(),
+            self.detector.sample_rate(),
         ));
     }
 
     /// Flip the FACTORY/YOURS A/B toggle. `true` = personal (MAP-adapted once
     /// the profile is sufficient); `false` = factory model.
     pub fn set_calibration_enabled(&mut self, enabled: bool) {
-        self.0.set_calibration_enabled(enabled);
+        self.detector.set_calibration_enabled(enabled);
     }
 
     /// Drop the live calibration profile (kNN reverts to `None`, so
@@ -245,28 +688,122 @@ impl WasmDetector {
     /// on jam start — otherwise the live profile would drift from what the panel
     /// accumulates and persists to localStorage.
     pub fn clear_calibration(&mut self) {
-        self.0.clear_calibration();
+        self.detector.clear_calibration();
     }
 
-    /// Whether the accumulated profile has ≥5 samples for all 4 classes.
+    /// Whether the accumulated profile has ≥5 samples for each required class
+    /// (kick, hihat, snare/click, hum). Open hat, rimshot, crash and noise
+    /// samples refine the profile but are optional.
     pub fn is_calibration_sufficient(&self) -> bool {
-        self.0.is_calibration_sufficient()
+        self.detector.is_calibration_sufficient()
     }
 
     /// The live calibration profile serialized to JSON bytes, for persistence
     /// (localStorage in the browser, `create_calibration_profile` on native).
     pub fn calibration_profile_json(&self) -> Vec<u8> {
-        self.0
+        self.detector
             .calibration_profile()
             .to_json_bytes()
             .unwrap_or_default()
     }
+
+    /// The live profile's [`CalibrationReport`] as JSON bytes (the native
+    /// `get_calibration_report` shape): which classes to re-teach and which
+    /// samples to drop. A sample's `index` counts the samples of its class in
+    /// the order they were added.
+    pub fn calibration_report_json(&self) -> Vec<u8> {
+        serde_json::to_vec(&self.detector.calibration_profile().report()).unwrap_or_default()
+    }
+}
+
+/// Run the real offline pipeline over a PCM buffer decoded in the browser (an
+/// upload or a jam capture): [`analyze_pcm`] over interleaved `samples` of
+/// `channels` channels at `sample_rate`, with `options_json` a
+/// [`PcmAnalysisOptions`] (empty for the defaults). Returns JSON bytes in the
+/// native `detect_events` result shape, `{ "events": [...], "total_count": n }`,
+/// each event carrying its per-class scores and features. Throws the error
+/// message for a bad buffer or options.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn analyze_offline_hybrid_json(
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+    options_json: &[u8],
+) -> Result<Vec<u8>, String> {
+    let options: PcmAnalysisOptions = if options_json.is_empty() {
+        PcmAnalysisOptions::default()
+    } else {
+        serde_json::from_slice(options_json).map_err(|e| format!("Invalid analysis options: {e}"))?
+    };
+    let events = analyze_pcm(samples.to_vec(), sample_rate, channels, &options)?;
+    serde_json::to_vec(&serde_json::json!({ "total_count": events.len(), "events": events }))
+        .map_err(|e| e.to_string())
+}
+
+/// Parse a JSON request body.
+#[cfg(feature = "wasm")]
+fn from_json<'a, T: serde::Deserialize<'a>>(json: &'a [u8], what: &str) -> Result<T, String> {
+    serde_json::from_slice(json).map_err(|e| format!("Invalid {what} input: {e}"))
+}
+
+/// Quantize events to a grid in the browser: `input_json` is a
+/// [`QuantizeEventsInput`] (the native `quantize_events_command` input, events
+/// as `analyze_offline_hybrid_json` returns them). Returns the
+/// `QuantizedEvent` array as JSON bytes.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn quantize_events_json(input_json: &[u8]) -> Result<Vec<u8>, String> {
+    let input: QuantizeEventsInput = from_json(input_json, "quantize")?;
+    serde_json::to_vec(&input.quantize()).map_err(|e| e.to_string())
+}
+
+/// Arrange quantized events in the browser: `input_json` is an
+/// [`ArrangeEventsInput`]. Returns the base-pattern `Arrangement` as JSON
+/// bytes; [`expand_to_song_json`] turns it into the full song, as the native
+/// `arrange_events_command` does before returning.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn arrange_events_json(input_json: &[u8]) -> Result<Vec<u8>, String> {
+    let input: ArrangeEventsInput = from_json(input_json, "arrange")?;
+    serde_json::to_vec(&input.arrange()).map_err(|e| e.to_string())
+}
+
+/// Expand an `Arrangement` (JSON) into the full Intro/Build/Drop/Outro song
+/// ([`arranger::Arrangement::expand_to_song`]), returned as JSON bytes.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn expand_to_song_json(arrangement_json: &[u8]) -> Result<Vec<u8>, String> {
+    let arrangement: arranger::Arrangement = from_json(arrangement_json, "arrangement")?;
+    serde_json::to_vec(&arrangement.expand_to_song()).map_err(|e| e.to_string())
+}
+
+/// Export an arrangement as a Standard MIDI File in the browser:
+/// `input_json` is an [`ExportMidiInput`]. Returns the file's bytes.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn export_midi_json(input_json: &[u8]) -> Result<Vec<u8>, String> {
+    let input: ExportMidiInput = from_json(input_json, "MIDI export")?;
+    input.export().map_err(|e| format!("Failed to export MIDI: {e}"))
+}
+
+/// Measure a mono room-tone capture recorded at `sample_rate` (at the
+/// model rate the conditioned detectors analyse at) and return the
+/// [`NoiseProfile`] as JSON bytes — empty when the capture is shorter than
+/// [`noise::MIN_ROOM_TONE_MS`].
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn noise_profile_json(sample_rate: u32, samples: &[f32]) -> Vec<u8> {
+    let samples = resample::resample(samples, sample_rate, FACTORY_SAMPLE_RATE);
+    NoiseProfile::from_mono(&samples, FACTORY_SAMPLE_RATE)
+        .and_then(|profile| profile.to_json_bytes().ok())
+        .unwrap_or_default()
 }
 
 /// Encode [`LiveEvent`]s as flat [`WASM_EVENT_STRIDE`]-float records (the
 /// push()/flush() ABI — see the WasmDetector docs for the layout).
 #[cfg(feature = "wasm")]
-fn encode_live_events(events: Vec<LiveEvent>) -> Vec<f32> {
+fn encode_live_events(events: &[LiveEvent]) -> Vec<f32> {
     let mut out = Vec::with_capacity(events.len() * WASM_EVENT_STRIDE);
     for e in events {
         out.push(e.t_ms as f32);
@@ -280,9 +817,8 @@ fn encode_live_events(events: Vec<LiveEvent>) -> Vec<f32> {
         out.push(f.high_band_energy);
         out.push(f.peak_amplitude);
         out.push(f.crest_factor);
-        for i in 0..crate::features::MFCC_COEFFS {
-            out.push(e.mfcc.get(i).copied().unwrap_or(0.0));
-        }
+        out.extend_from_slice(&e.mfcc);
+        out.push(stage_id(e.stage));
     }
     out
 }
@@ -297,6 +833,10 @@ fn class_from_id(id: u32) -> EventClass {
         1 => EventClass::HihatNoise,
         2 => EventClass::Click,
         3 => EventClass::HumVoiced,
+        4 => EventClass::OpenHihat,
+        5 => EventClass::Rimshot,
+        6 => EventClass::Crash,
+        7 => EventClass::Noise,
         _ => EventClass::Click,
     }
 }
@@ -312,6 +852,16 @@ mod tests {
         assert_eq!(class_id(EventClass::HihatNoise), 1.0);
         assert_eq!(class_id(EventClass::Click), 2.0);
         assert_eq!(class_id(EventClass::HumVoiced), 3.0);
+        assert_eq!(class_id(EventClass::OpenHihat), 4.0);
+        assert_eq!(class_id(EventClass::Rimshot), 5.0);
+        assert_eq!(class_id(EventClass::Crash), 6.0);
+        assert_eq!(class_id(EventClass::Noise), 7.0);
+        for (i, class) in EventClass::ALL.into_iter().enumerate() {
+            assert_eq!(class_id(class), i as f32, "ids follow EventClass::ALL");
+        }
+        assert_eq!(stage_id(LiveStage::Final), 0.0);
+        assert_eq!(stage_id(LiveStage::Provisional), 1.0);
+        assert_eq!(stage_id(LiveStage::Revised), 2.0);
     }
 }
 
@@ -363,7 +913,294 @@ mod offline_hybrid_tests {
             "crest must be computed over the fixed 150ms window"
         );
         assert_eq!(events[0].features.zcr, expected.zcr);
-        // duration_ms still reports the musical gap, not the feature window
-        assert!(events[0].duration_ms > 500.0);
+        // gap_ms still reports the musical gap, not the feature window, and
+        // the measured duration ends with the kick's decay
+        assert!(events[0].gap_ms > 500.0);
+        assert!(events[0].duration_ms < 500.0);
+    }
+
+    /// A kick with a hiss on top is one onset but two sounds: multi-label
+    /// mode emits both, at the same time, and only then.
+    #[test]
+    fn multi_label_splits_a_kick_with_a_hiss_on_top() {
+        let sr = 44_100u32;
+        let mut state = 0x2545_f491_u32;
+        let layered: Vec<f32> = synth_kick(sr, 0.15)
+            .iter()
+            .enumerate()
+            .map(|(i, k)| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                let noise = (state as f32 / u32::MAX as f32) * 2.0 - 1.0;
+                k + 0.8 * noise * (-40.0 * i as f32 / sr as f32).exp()
+            })
+            .collect();
+        let mut samples = vec![0.0; sr as usize];
+        for at in [0, sr as usize / 2] {
+            samples[at..at + layered.len()].copy_from_slice(&layered);
+        }
+        let audio = AudioData {
+            samples,
+            sample_rate: sr,
+            channels: 1,
+            bit_depth: 16,
+            duration_ms: 1000,
+            frame_count: sr as usize,
+        };
+        let classifier = HybridClassifier::factory();
+
+        let single = analyze_offline_hybrid(&audio, &OnsetConfig::default(), &classifier);
+        assert_eq!(single.len(), 2);
+        assert!(single.iter().all(|e| e.class == EventClass::BilabialPlosive));
+
+        let cfg = OnsetConfig { multi_label: Some(MultiLabelConfig::default()), ..OnsetConfig::default() };
+        let layered = analyze_offline_hybrid(&audio, &cfg, &classifier);
+        assert_eq!(layered.len(), 4);
+        for (pair, onset) in layered.chunks(2).zip(&single) {
+            assert_eq!(pair[0].class, EventClass::BilabialPlosive);
+            assert_eq!(pair[1].class, EventClass::HihatNoise);
+            assert!(pair.iter().all(|e| e.timestamp_ms == onset.timestamp_ms));
+            assert_ne!(pair[0].id, pair[1].id);
+        }
+    }
+
+    fn stereo_kicks(sample_rate: u32) -> AudioData {
+        // Three kicks, 400ms apart, hard left; the right channel stays silent.
+        let kick = synth_kick(sample_rate, 0.15);
+        let frame_count = (sample_rate as f32 * 1.3) as usize;
+        let mut samples = vec![0.0; frame_count * 2];
+        for k in 0..3 {
+            let at = k * (sample_rate as usize * 2 / 5);
+            for (i, s) in kick.iter().enumerate() {
+                samples[(at + i) * 2] = *s;
+            }
+        }
+        AudioData {
+            samples,
+            sample_rate,
+            channels: 2,
+            bit_depth: 16,
+            duration_ms: (frame_count as f64 / sample_rate as f64 * 1000.0) as i64,
+            frame_count,
+        }
+    }
+
+    /// The chunked two-pass path must reproduce the whole-buffer per-onset
+    /// extraction it replaced.
+    #[test]
+    fn source_analysis_matches_whole_buffer_extraction() {
+        let audio = stereo_kicks(44_100);
+        let classifier = HybridClassifier::factory();
+        let mut progress = Vec::new();
+        let events = analyze_source_hybrid(
+            &mut audio.source(),
+            &OnsetConfig::default(),
+            &classifier,
+            &mut |p| progress.push(p),
+        )
+        .unwrap();
+        assert_eq!(events.len(), 3, "expected one event per kick");
+
+        let window_ms = events::hybrid::HYBRID_MFCC_WINDOW_MS;
+        for event in &events {
+            let features = extract_features_for_window(&audio, event.timestamp_ms, window_ms);
+            let mfcc = extract_mfcc_for_window(&audio, event.timestamp_ms, window_ms);
+            let expected = classifier.classify(&features, &mfcc);
+            assert_eq!(event.features.crest_factor, features.crest_factor);
+            assert_eq!(event.features.spectral_centroid, features.spectral_centroid);
+            assert_eq!(event.class, expected.class);
+            assert_eq!(event.confidence, expected.confidence);
+        }
+
+        assert!(matches!(progress[0], AnalysisProgress::Scanning { total_frames: Some(n), .. } if n == audio.frame_count));
+        assert!(progress.contains(&AnalysisProgress::OnsetsDetected { count: 3 }));
+        assert_eq!(progress.last(), Some(&AnalysisProgress::Classifying { done: 3, total: 3 }));
+    }
+
+    /// Hums carry the pitch contour of their whole gap, the same one the
+    /// whole-buffer tracker finds; other classes carry none.
+    #[test]
+    fn hum_events_carry_their_pitch_contour() {
+        let sr = 44_100u32;
+        let mut samples = synth_kick(sr, 0.15);
+        samples.resize(sr as usize / 2, 0.0);
+        let hum_len = sr as usize * 3 / 5;
+        samples.extend((0..hum_len).map(|i| {
+            let t = i as f32 / sr as f32;
+            let phase = 2.0 * std::f32::consts::PI * 220.0 * t;
+            let envelope = (t / 0.02).min(1.0) * ((0.6 - t) / 0.05).clamp(0.0, 1.0);
+            envelope * (0.4 * phase.sin() + 0.25 * (2.0 * phase).sin() + 0.15 * (3.0 * phase).sin())
+        }));
+        samples.resize(sr as usize * 3 / 2, 0.0);
+        let frame_count = samples.len();
+        let audio = AudioData {
+            samples,
+            sample_rate: sr,
+            channels: 1,
+            bit_depth: 16,
+            duration_ms: 1500,
+            frame_count,
+        };
+
+        let events =
+            analyze_offline_hybrid(&audio, &OnsetConfig::default(), &HybridClassifier::factory());
+        let hum = events.iter().find(|e| e.class == EventClass::HumVoiced).expect("a hum event");
+        assert!(events.iter().filter(|e| e.class != EventClass::HumVoiced).all(|e| e.pitch_contour.is_empty()));
+        assert!(hum.pitch_contour.len() >= 100, "{} voiced frames", hum.pitch_contour.len());
+        assert!(hum.pitch_contour.iter().all(|p| (p.f0_hz - 220.0).abs() < 3.0));
+        assert_eq!(
+            hum.pitch_contour,
+            track_pitch_for_window(&audio, hum.timestamp_ms, hum.gap_ms)
+        );
+    }
+
+    /// Out-of-phase stereo mics: the average cancels to silence, every other
+    /// strategy hears the kicks.
+    #[test]
+    fn channel_strategies_recover_out_of_phase_stereo() {
+        let mut audio = stereo_kicks(44_100);
+        for frame in audio.samples.chunks_exact_mut(2) {
+            frame[1] = -frame[0];
+        }
+        let classifier = HybridClassifier::factory();
+        let analyze = |channels: ChannelStrategy| {
+            let cfg = OnsetConfig { channels, ..OnsetConfig::default() };
+            analyze_offline_hybrid(&audio, &cfg, &classifier)
+        };
+
+        assert!(analyze(ChannelStrategy::Average).is_empty());
+        for strategy in [
+            ChannelStrategy::Channel(1),
+            ChannelStrategy::MaxEnergy,
+            ChannelStrategy::MidSide,
+            ChannelStrategy::PerChannel,
+        ] {
+            let events = analyze(strategy);
+            assert_eq!(events.len(), 3, "{strategy}: one event per kick");
+            // Merged lanes must not double-count a hit both channels heard.
+            assert!(events.windows(2).all(|w| w[1].timestamp_ms - w[0].timestamp_ms > 300.0));
+            let onsets = detect_onsets(&audio, &OnsetConfig { channels: strategy, ..OnsetConfig::default() });
+            let onset_times: Vec<f64> = onsets.iter().map(|o| o.timestamp_ms).collect();
+            let event_times: Vec<f64> = events.iter().map(|e| e.timestamp_ms).collect();
+            assert_eq!(onset_times, event_times, "{strategy}");
+        }
+    }
+
+    /// `stereo_kicks` after `lead_in_ms` of silence, and the same take quiet,
+    /// hissy and with a DC offset.
+    fn clean_and_noisy_kicks(lead_in_ms: usize) -> (AudioData, AudioData) {
+        let kicks = stereo_kicks(44_100);
+        let lead_in = 44_100 * lead_in_ms / 1000;
+        let mut samples = vec![0.0; lead_in * 2];
+        samples.extend_from_slice(&kicks.samples);
+        let frame_count = kicks.frame_count + lead_in;
+        let duration_ms = kicks.duration_ms + lead_in_ms as i64;
+        let clean = AudioData { samples, frame_count, duration_ms, ..kicks };
+        let mut noisy = clean.clone();
+        let mut seed = 1u32;
+        for s in noisy.samples.iter_mut() {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let hiss = (seed >> 8) as f32 / (1 << 24) as f32 - 0.5;
+            *s = 0.05 * *s + 0.1 + 0.004 * hiss;
+        }
+        (clean, noisy)
+    }
+
+    fn assert_same_kicks(events: &[Event], reference: &[Event]) {
+        assert_eq!(events.len(), 3, "one event per kick");
+        for (event, clean) in events.iter().zip(reference) {
+            assert!((event.timestamp_ms - clean.timestamp_ms).abs() < 15.0);
+            assert_eq!(event.class, clean.class, "@ {}ms", clean.timestamp_ms);
+        }
+    }
+
+    /// A quiet, hissy take with a DC offset: after a moment of room tone to
+    /// learn the noise from, conditioning finds the same hits, at the same
+    /// times and of the same class, as the clean recording.
+    #[test]
+    fn conditioning_recovers_kicks_from_a_noisy_quiet_take() {
+        let (clean, noisy) = clean_and_noisy_kicks(300);
+        let classifier = HybridClassifier::factory();
+        let reference = analyze_offline_hybrid(&clean, &OnsetConfig::default(), &classifier);
+        assert_eq!(reference.len(), 3);
+
+        let cfg = OnsetConfig { conditioning: Some(ConditioningConfig::default()), ..OnsetConfig::default() };
+        let events = analyze_offline_hybrid(&noisy, &cfg, &classifier);
+        assert_same_kicks(&events, &reference);
+
+        // The chunked passes condition exactly like the whole-buffer path.
+        let onsets: Vec<f64> = detect_onsets(&noisy, &cfg).iter().map(|o| o.timestamp_ms).collect();
+        assert_eq!(onsets, events.iter().map(|e| e.timestamp_ms).collect::<Vec<_>>());
+    }
+
+    /// With no room tone in the take itself, a separately captured noise
+    /// profile lets the denoiser clean the very first hit.
+    #[test]
+    fn noise_profile_cleans_a_take_that_starts_on_a_hit() {
+        let (clean, noisy) = clean_and_noisy_kicks(0);
+        let classifier = HybridClassifier::factory();
+        let reference = analyze_offline_hybrid(&clean, &OnsetConfig::default(), &classifier);
+
+        // The room, recorded before the take: the same offset and hiss.
+        let (_, room) = clean_and_noisy_kicks(1000);
+        let room = AudioData { frame_count: 44_100, duration_ms: 1000, ..room };
+        let room = AudioData { samples: room.samples[..44_100 * 2].to_vec(), ..room };
+        let profile = NoiseProfile::from_audio(&room).unwrap();
+
+        let mut cfg = OnsetConfig { conditioning: Some(ConditioningConfig::default()), ..OnsetConfig::default() };
+        profile.adapt_onset_config(&mut cfg);
+        let events = analyze_offline_hybrid(&noisy, &cfg, &classifier);
+        assert_same_kicks(&events, &reference);
+        let onsets: Vec<f64> = detect_onsets(&noisy, &cfg).iter().map(|o| o.timestamp_ms).collect();
+        assert_eq!(onsets, events.iter().map(|e| e.timestamp_ms).collect::<Vec<_>>());
+    }
+
+    /// Per-channel lanes pick up hits that live on one channel only, and each
+    /// event takes its features from the channel that heard it.
+    #[test]
+    fn per_channel_merges_hits_from_each_channel() {
+        let mut audio = stereo_kicks(44_100);
+        // Move the middle kick to the right channel.
+        let at = 44_100 * 2 / 5;
+        for frame in audio.samples[at * 2..(at + 44_100 * 3 / 20) * 2].chunks_exact_mut(2) {
+            frame.swap(0, 1);
+        }
+        let cfg = OnsetConfig { channels: ChannelStrategy::PerChannel, ..OnsetConfig::default() };
+        let classifier = HybridClassifier::factory();
+        let events = analyze_offline_hybrid(&audio, &cfg, &classifier);
+        assert_eq!(events.len(), 3);
+
+        let right = AudioData {
+            samples: audio.samples.iter().skip(1).step_by(2).copied().collect(),
+            channels: 1,
+            ..audio.clone()
+        };
+        let window_ms = events::hybrid::HYBRID_MFCC_WINDOW_MS;
+        let expected = extract_features_for_window(&right, events[1].timestamp_ms, window_ms);
+        assert_eq!(events[1].features.peak_amplitude, expected.peak_amplitude);
+        assert_eq!(events[1].features.crest_factor, expected.crest_factor);
+    }
+
+    #[test]
+    fn resampled_source_analysis_matches_normalized_audio() {
+        let audio = stereo_kicks(48_000);
+        let classifier = HybridClassifier::factory();
+        let whole = analyze_offline_hybrid(
+            &normalize_sample_rate(&audio),
+            &OnsetConfig::default(),
+            &classifier,
+        );
+        let mut source = ResampledSource::new(audio.source(), FACTORY_SAMPLE_RATE);
+        let chunked =
+            analyze_source_hybrid(&mut source, &OnsetConfig::default(), &classifier, &mut |_| {})
+                .unwrap();
+
+        assert_eq!(chunked.len(), whole.len());
+        for (a, b) in chunked.iter().zip(&whole) {
+            assert_eq!(a.timestamp_ms, b.timestamp_ms);
+            assert_eq!(a.class, b.class);
+            assert!((a.confidence - b.confidence).abs() < 1e-3);
+        }
     }
 }
@@ -0,0 +1,189 @@
+//! Sub-frame onset localization.
+//!
+//! A detection-function peak only says *which frame* a transient landed in:
+//! with a 2048-sample window that is a ~46ms span, and reporting the frame's
+//! left edge puts the onset ~24ms before the sound. Both
+//! [`crate::features::detect_onsets`] and the
+//! [`StreamingDetector`](crate::StreamingDetector) therefore refine each peak
+//! in the time domain: over the region the peak frame could have reacted to,
+//! they take a ~1ms peak envelope held over [`HOLD_BLOCKS`], find its steepest
+//! overall rise, and report the first block that climbs [`RISE_FRACTION`] of
+//! the way up it. Both detectors run the same math on the same audio, so they
+//! agree on the real onset time without a compensating offset.
+
+/// Fraction of the rise (from the quiet floor before the transient to its
+/// peak) the envelope must climb for the onset to be placed there. Low enough
+/// that a slow hum attack is caught near its start, high enough that the
+/// noise floor never trips it.
+pub const RISE_FRACTION: f32 = 0.25;
+
+/// Blocks (~ms) the envelope holds each peak for: half a cycle of a 50Hz kick
+/// or bass note, so a steady low tone reads as a flat level rather than a
+/// ripple of false rises. The hold trails, so rises still land on time.
+pub const HOLD_BLOCKS: usize = 10;
+
+/// Envelope block length in samples: ~1ms at any rate.
+pub fn envelope_block(sample_rate: u32) -> usize {
+    (sample_rate as usize / 1000).max(1)
+}
+
+/// Peak absolute amplitude of consecutive `block`-sample blocks of `samples`
+/// (a trailing partial block is dropped).
+pub fn block_peaks(samples: &[f32], block: usize) -> Vec<f32> {
+    let mut peaks = Vec::new();
+    block_peaks_into(samples, block, &mut peaks);
+    peaks
+}
+
+/// [`block_peaks`] into `out` (cleared first), reusing its buffer.
+pub fn block_peaks_into(samples: &[f32], block: usize, out: &mut Vec<f32>) {
+    out.clear();
+    out.extend(samples.chunks_exact(block.max(1)).map(|b| b.iter().fold(0.0f32, |m, s| m.max(s.abs()))));
+}
+
+/// `peaks` from block `from` on, each held at the largest of itself and the
+/// `HOLD_BLOCKS - 1` blocks before it (earlier blocks only feed the hold).
+pub fn held_envelope(peaks: &[f32], from: usize) -> Vec<f32> {
+    let mut held = Vec::new();
+    held_envelope_into(peaks, from, &mut held);
+    held
+}
+
+/// [`held_envelope`] into `out` (cleared first), reusing its buffer.
+pub fn held_envelope_into(peaks: &[f32], from: usize, out: &mut Vec<f32>) {
+    out.clear();
+    out.extend(
+        (from..peaks.len()).map(|i| peaks[(i + 1).saturating_sub(HOLD_BLOCKS)..=i].iter().fold(0.0f32, |m, &p| m.max(p))),
+    );
+}
+
+/// Index of the block where the transient in `peaks` begins, searching from
+/// block `from` (earlier blocks only feed the hold).
+///
+/// The peaks are held over [`HOLD_BLOCKS`]. The transient is the largest rise
+/// of any held block over the quietest block before it (so the decaying tail
+/// of an earlier hit is not mistaken for the new one); the onset is the first
+/// block between that quiet point and the peak that reaches [`RISE_FRACTION`]
+/// of the rise. `None` when the envelope never rises, leaving the caller's
+/// frame-level estimate in place.
+pub fn locate_rise(peaks: &[f32], from: usize) -> Option<usize> {
+    rise_in_held(&held_envelope(peaks, from)).map(|i| from + i)
+}
+
+/// The onset block of [`locate_rise`] within an already held envelope.
+fn rise_in_held(held: &[f32]) -> Option<usize> {
+    // Largest rise over a running minimum, with its floor and peak blocks.
+    let mut best_rise = 0.0f32;
+    let mut best: Option<(usize, usize)> = None;
+    let mut floor_idx = 0;
+    for (i, &e) in held.iter().enumerate() {
+        if e < held[floor_idx] {
+            floor_idx = i;
+        }
+        let rise = e - held[floor_idx];
+        if rise > best_rise {
+            best_rise = rise;
+            best = Some((floor_idx, i));
+        }
+    }
+    let (floor, peak) = best?;
+    let level = held[floor] + RISE_FRACTION * best_rise;
+    (floor..=peak).find(|&i| held[i] >= level)
+}
+
+/// Refine an onset to the transient inside `region`, whose first
+/// `history_blocks` envelope blocks only feed the hold. `region` should start
+/// on an [`envelope_block`] boundary of the stream so the blocks line up with
+/// the offline envelope. Returns the onset's offset into `region`, or `None`
+/// when the searched part holds no rise.
+pub fn localize_onset(region: &[f32], history_blocks: usize, sample_rate: u32) -> Option<usize> {
+    Localizer::default().localize_onset(region, history_blocks, sample_rate)
+}
+
+/// [`localize_onset`] with its envelope buffers kept between calls, for the
+/// allocation-free streaming path.
+#[derive(Debug, Clone, Default)]
+pub struct Localizer {
+    peaks: Vec<f32>,
+    held: Vec<f32>,
+}
+
+impl Localizer {
+    /// A localizer whose buffers fit regions of up to `max_region` samples.
+    pub fn with_capacity(max_region: usize, sample_rate: u32) -> Self {
+        let blocks = max_region / envelope_block(sample_rate) + 1;
+        Localizer { peaks: Vec::with_capacity(blocks), held: Vec::with_capacity(blocks) }
+    }
+
+    /// See [`localize_onset`].
+    pub fn localize_onset(&mut self, region: &[f32], history_blocks: usize, sample_rate: u32) -> Option<usize> {
+        let block = envelope_block(sample_rate);
+        block_peaks_into(region, block, &mut self.peaks);
+        held_envelope_into(&self.peaks, history_blocks, &mut self.held);
+        rise_in_held(&self.held).map(|i| (history_blocks + i) * block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Silence, then a decaying noise burst from `onset`.
+    fn burst_at(onset: usize, len: usize) -> Vec<f32> {
+        let mut state = 0x2545_f491_u32;
+        (0..len)
+            .map(|i| {
+                if i < onset {
+                    return 0.0;
+                }
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                let noise = (state as f32 / u32::MAX as f32) * 2.0 - 1.0;
+                noise * (-((i - onset) as f32) / 2000.0).exp()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_a_burst_to_within_a_block() {
+        let sr = 44_100;
+        let onset = 1234;
+        let found = localize_onset(&burst_at(onset, 4096), 0, sr).unwrap();
+        assert!(found.abs_diff(onset) <= envelope_block(sr), "found {found}, true {onset}");
+    }
+
+    #[test]
+    fn a_decaying_tail_does_not_hide_the_next_hit() {
+        // A loud tail fading out, then a quieter hit.
+        let mut peaks: Vec<f32> = (0..40).map(|i| 0.8 * 0.85f32.powi(i)).collect();
+        peaks.extend([0.3, 0.25, 0.2, 0.15]);
+        assert_eq!(locate_rise(&peaks, 0), Some(40));
+    }
+
+    #[test]
+    fn a_steady_bass_note_does_not_mask_a_kick() {
+        // A 60Hz tone throughout, a kick-like burst on top from 30ms.
+        let sr = 44_100;
+        let onset = sr as usize * 30 / 1000;
+        let tone = |i: usize| 0.3 * (2.0 * std::f32::consts::PI * 60.0 * i as f32 / sr as f32).sin();
+        let region: Vec<f32> =
+            burst_at(onset, 3000).iter().enumerate().map(|(i, b)| tone(i) + b).collect();
+        let found = localize_onset(&region, HOLD_BLOCKS, sr).unwrap();
+        assert!(found.abs_diff(onset) <= 2 * envelope_block(sr), "found {found}, true {onset}");
+    }
+
+    #[test]
+    fn slow_attacks_land_near_their_start() {
+        // A 10-block linear swell from silence.
+        let peaks: Vec<f32> = (0..30).map(|i| ((i as f32 - 10.0) / 10.0).clamp(0.0, 1.0)).collect();
+        assert_eq!(locate_rise(&peaks, 0), Some(13));
+    }
+
+    #[test]
+    fn flat_regions_are_left_alone() {
+        assert_eq!(locate_rise(&[0.1; 16], 0), None);
+        assert_eq!(locate_rise(&[], 0), None);
+        assert_eq!(localize_onset(&[0.0; 30], 0, 44_100), None);
+    }
+}
@@ -0,0 +1,258 @@
+// Room-tone noise profiles
+//
+// Calibration teaches the classifier the performer's sounds; a noise profile
+// teaches the pipeline the room. A few seconds of "silence" recorded in the
+// same place give the noise floor's spectrum and how much spectral flux and
+// short-window energy that floor produces on its own. From those the onset
+// gates (`OnsetConfig::min_flux_threshold`, `StreamingConfig::min_flux`,
+// `StreamingConfig::energy_min_rms`) are raised just above what the room can
+// trigger, and the conditioning denoiser starts from the measured floor
+// instead of passing audio through while it learns one.
+
+use serde::{Deserialize, Serialize};
+
+use crate::conditioning::{self, DENOISE_WINDOW};
+use crate::features::{apply_hann_window, compute_fft, OnsetConfig};
+use crate::ingest::AudioData;
+use crate::onset_function::OnsetFunction;
+use crate::streaming::StreamingConfig;
+
+/// How far above the room's own flux and energy the onset gates are set.
+const NOISE_MARGIN: f32 = 1.5;
+
+/// Fraction of room-tone frames whose flux/energy the gates must clear. The
+/// loudest few percent are left out so a chair creak in the capture doesn't
+/// deafen the detector.
+const NOISE_QUANTILE: f32 = 0.95;
+
+/// Shortest capture a profile is taken from.
+pub const MIN_ROOM_TONE_MS: f64 = 500.0;
+
+/// The noise floor of a recording setup, measured from a room-tone capture
+/// (see [`NoiseProfile::from_audio`]). Serialized as JSON alongside
+/// calibration profiles.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoiseProfile {
+    /// Rate (Hz) the capture was measured at.
+    pub sample_rate: u32,
+    /// Length of the capture in milliseconds.
+    pub duration_ms: f64,
+    /// RMS level of the capture.
+    pub rms: f32,
+    /// Mean power per bin (DC to Nyquist) of the denoiser's Hann-windowed
+    /// 1024-sample STFT frames.
+    pub spectrum: Vec<f32>,
+    /// Spectral flux the room produces at [`OnsetConfig::default`]'s STFT size
+    /// ([`NOISE_QUANTILE`] of frames).
+    pub onset_flux: f32,
+    /// Spectral flux the room produces at [`StreamingConfig::default`]'s STFT
+    /// size.
+    pub live_flux: f32,
+    /// Short-window RMS the room reaches at the streaming detector's window.
+    pub live_rms: f32,
+}
+
+impl NoiseProfile {
+    /// Measure a room-tone capture (all channels averaged). `None` when it is
+    /// shorter than [`MIN_ROOM_TONE_MS`].
+    pub fn from_audio(audio: &AudioData) -> Option<Self> {
+        Self::from_mono(&audio.to_mono(), audio.sample_rate)
+    }
+
+    /// Measure a mono room-tone capture. `None` when it is shorter than
+    /// [`MIN_ROOM_TONE_MS`].
+    pub fn from_mono(samples: &[f32], sample_rate: u32) -> Option<Self> {
+        let duration_ms = samples.len() as f64 * 1000.0 / sample_rate.max(1) as f64;
+        if sample_rate == 0 || duration_ms < MIN_ROOM_TONE_MS {
+            return None;
+        }
+        let mean = samples.iter().map(|&s| s as f64).sum::<f64>() / samples.len() as f64;
+        let rms = (samples.iter().map(|&s| s as f64 * s as f64).sum::<f64>() / samples.len() as f64).sqrt();
+        // The denoiser runs after the DC blocker, so its floor is measured
+        // without the offset (whose window leakage would read as low-end noise).
+        let centred: Vec<f32> = samples.iter().map(|&s| (s as f64 - mean) as f32).collect();
+        let onset = OnsetConfig::default();
+        let live = StreamingConfig::default();
+        Some(NoiseProfile {
+            sample_rate,
+            duration_ms,
+            rms: rms as f32,
+            spectrum: conditioning::mean_power_spectrum(&centred),
+            onset_flux: flux_quantile(samples, onset.window_size, onset.hop_size),
+            live_flux: flux_quantile(samples, live.window_size, live.hop_size),
+            live_rms: rms_quantile(samples, live.window_size, live.hop_size),
+        })
+    }
+
+    /// [`rms`](Self::rms) in dBFS.
+    pub fn noise_floor_dbfs(&self) -> f32 {
+        20.0 * self.rms.max(1e-10).log10()
+    }
+
+    /// Raise `config`'s flux gate above what the room triggers and hand the
+    /// denoiser this floor (used when `config.conditioning` is set). The flux
+    /// was measured as spectral flux, so other onset functions keep their gate.
+    pub fn adapt_onset_config(&self, config: &mut OnsetConfig) {
+        if config.onset_function == OnsetFunction::SpectralFlux {
+            let default = OnsetConfig::default();
+            let gate = NOISE_MARGIN * scale_flux(self.onset_flux, default.window_size, config.window_size);
+            config.min_flux_threshold = config.min_flux_threshold.max(gate);
+        }
+        config.noise_profile = Some(self.clone());
+    }
+
+    /// Raise `config`'s flux and energy gates above what the room triggers
+    /// and hand the denoiser this floor (used when `config.conditioning` is
+    /// set). As offline, the flux gate only applies to spectral flux.
+    pub fn adapt_streaming_config(&self, config: &mut StreamingConfig) {
+        if config.onset_function == OnsetFunction::SpectralFlux {
+            let default = StreamingConfig::default();
+            let gate = NOISE_MARGIN * scale_flux(self.live_flux, default.window_size, config.window_size);
+            config.min_flux = config.min_flux.max(gate);
+        }
+        config.energy_min_rms = config.energy_min_rms.max(NOISE_MARGIN * self.live_rms);
+        config.noise_profile = Some(self.clone());
+    }
+
+    /// [`spectrum`](Self::spectrum) on the bin grid of a denoiser running at
+    /// `sample_rate`, matching bins by frequency.
+    pub(crate) fn spectrum_at(&self, sample_rate: u32) -> Vec<f32> {
+        let bins = DENOISE_WINDOW / 2 + 1;
+        if sample_rate == self.sample_rate || self.spectrum.len() < 2 {
+            let mut spectrum = self.spectrum.clone();
+            spectrum.resize(bins, 0.0);
+            return spectrum;
+        }
+        let last = self.spectrum.len() - 1;
+        let ratio = sample_rate as f64 / self.sample_rate as f64;
+        (0..bins)
+            .map(|k| {
+                let pos = k as f64 * ratio;
+                if pos >= last as f64 {
+                    // Above the capture's Nyquist: assume the floor stays flat.
+                    return self.spectrum[last];
+                }
+                let i = pos as usize;
+                let frac = (pos - i as f64) as f32;
+                self.spectrum[i] * (1.0 - frac) + self.spectrum[i + 1] * frac
+            })
+            .collect()
+    }
+
+    /// Serialize the profile to JSON bytes.
+    pub fn to_json_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec_pretty(self)
+    }
+
+    /// Deserialize a profile from JSON bytes.
+    pub fn from_json_bytes(data: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(data)
+    }
+}
+
+/// Flux of stationary noise grows with the STFT size (`N/2` bins of
+/// magnitude `∝ √N`), so a profile measured at one size carries to another.
+fn scale_flux(flux: f32, measured_window: usize, window: usize) -> f32 {
+    flux * (window as f32 / measured_window.max(1) as f32).powf(1.5)
+}
+
+/// The [`NOISE_QUANTILE`] of rectified spectral flux over `samples`, computed
+/// the way both onset detectors compute it.
+fn flux_quantile(samples: &[f32], window: usize, hop: usize) -> f32 {
+    let mut flux = Vec::new();
+    let mut prev: Option<Vec<f32>> = None;
+    for start in (0..samples.len().saturating_sub(window) + 1).step_by(hop.max(1)) {
+        let Some(frame) = samples.get(start..start + window) else { break };
+        let mut windowed = frame.to_vec();
+        apply_hann_window(&mut windowed);
+        let spectrum = compute_fft(&windowed);
+        if let Some(prev) = &prev {
+            flux.push(spectrum.iter().zip(prev).map(|(c, p)| (c - p).max(0.0)).sum());
+        }
+        prev = Some(spectrum);
+    }
+    quantile(flux)
+}
+
+/// The [`NOISE_QUANTILE`] of short-window RMS over `samples`.
+fn rms_quantile(samples: &[f32], window: usize, hop: usize) -> f32 {
+    let rms = samples
+        .windows(window.max(1))
+        .step_by(hop.max(1))
+        .map(|w| (w.iter().map(|s| s * s).sum::<f32>() / w.len() as f32).sqrt())
+        .collect();
+    quantile(rms)
+}
+
+fn quantile(mut values: Vec<f32>) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f32::total_cmp);
+    values[((values.len() - 1) as f32 * NOISE_QUANTILE).round() as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hiss(len: usize, amplitude: f32) -> Vec<f32> {
+        let mut seed = 7u32;
+        (0..len)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                amplitude * ((seed >> 8) as f32 / (1 << 24) as f32 - 0.5)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rejects_a_capture_too_short_to_measure() {
+        assert!(NoiseProfile::from_mono(&hiss(44_100 / 4, 0.01), 44_100).is_none());
+        assert!(NoiseProfile::from_mono(&hiss(44_100, 0.01), 44_100).is_some());
+    }
+
+    #[test]
+    fn gates_clear_the_room_and_scale_with_its_level() {
+        let quiet = NoiseProfile::from_mono(&hiss(88_200, 0.01), 44_100).unwrap();
+        let loud = NoiseProfile::from_mono(&hiss(88_200, 0.1), 44_100).unwrap();
+        assert!((loud.onset_flux / quiet.onset_flux - 10.0).abs() < 0.1);
+        assert!((loud.rms / quiet.rms - 10.0).abs() < 0.1);
+
+        let mut onset = OnsetConfig::default();
+        loud.adapt_onset_config(&mut onset);
+        assert!(onset.min_flux_threshold > loud.onset_flux);
+        assert!(onset.noise_profile.is_some());
+
+        // A quiet room never lowers the streaming defaults.
+        let mut live = StreamingConfig::default();
+        quiet.adapt_streaming_config(&mut live);
+        assert_eq!(live.energy_min_rms, StreamingConfig::default().energy_min_rms);
+        loud.adapt_streaming_config(&mut live);
+        assert!(live.min_flux > loud.live_flux);
+    }
+
+    #[test]
+    fn spectrum_maps_across_rates_by_frequency() {
+        let profile = NoiseProfile {
+            sample_rate: 48_000,
+            duration_ms: 1000.0,
+            rms: 0.0,
+            spectrum: (0..=DENOISE_WINDOW / 2).map(|k| k as f32).collect(),
+            onset_flux: 0.0,
+            live_flux: 0.0,
+            live_rms: 0.0,
+        };
+        assert_eq!(profile.spectrum_at(48_000), profile.spectrum);
+        let at_24k = profile.spectrum_at(24_000);
+        assert_eq!(at_24k.len(), DENOISE_WINDOW / 2 + 1);
+        assert_eq!(at_24k[100], 50.0);
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let profile = NoiseProfile::from_mono(&hiss(44_100, 0.01), 44_100).unwrap();
+        let bytes = profile.to_json_bytes().unwrap();
+        assert_eq!(NoiseProfile::from_json_bytes(&bytes).unwrap(), profile);
+    }
+}
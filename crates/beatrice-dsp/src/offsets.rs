@@ -0,0 +1,152 @@
+//! Event offsets: where a sound actually ends.
+//!
+//! The gap to the next onset says nothing about how long a sound lasted — a
+//! short "t" before a pause would get the whole pause. The offline pipelines
+//! instead end each event where its own energy has decayed: the held peak
+//! envelope of [`crate::localize`] falling [`DECAY_LEVEL`] of the way from its
+//! peak back to the floor and staying there for [`DECAY_HOLD_MS`]. A hum's
+//! level swells and dips while it is still sung, so a voiced pitch contour
+//! extends the event to its last voiced frame. The gap is kept beside the
+//! measured duration ([`Event::gap_ms`](crate::Event::gap_ms)), and a duration
+//! never runs past it.
+
+use crate::events::types::PitchPoint;
+use crate::localize::{block_peaks, envelope_block, held_envelope, HOLD_BLOCKS};
+
+/// Fraction of the way from the floor up to the event's peak (−20 dB of the
+/// rise) under which the sound counts as ended.
+pub const DECAY_LEVEL: f32 = 0.1;
+
+/// How long the envelope must stay under [`DECAY_LEVEL`] for the event to end
+/// there, so a dip between syllables of a held sound does not cut it.
+pub const DECAY_HOLD_MS: f64 = 30.0;
+
+/// Milliseconds after the start of `samples` (an event's audio from its
+/// onset) at which its energy has decayed; the full length when it never
+/// does.
+pub fn decay_offset_ms(samples: &[f32], sample_rate: u32) -> f64 {
+    if sample_rate == 0 {
+        return 0.0;
+    }
+    let block = envelope_block(sample_rate);
+    let block_ms = block as f64 * 1000.0 / sample_rate as f64;
+    let whole_ms = samples.len() as f64 * 1000.0 / sample_rate as f64;
+    let held = held_envelope(&block_peaks(samples, block), 0);
+
+    let Some(peak_idx) = (0..held.len()).max_by(|&a, &b| held[a].total_cmp(&held[b]).then(b.cmp(&a))) else {
+        return whole_ms;
+    };
+    let peak = held[peak_idx];
+    let floor = held[peak_idx..].iter().fold(peak, |m, &e| m.min(e));
+    if peak <= floor {
+        return whole_ms;
+    }
+    let level = floor + DECAY_LEVEL * (peak - floor);
+
+    // First run of blocks under the level that lasts DECAY_HOLD_MS or reaches
+    // the end of the audio.
+    let hold_blocks = (DECAY_HOLD_MS / block_ms).ceil() as usize;
+    let mut run_start = None;
+    for i in peak_idx..held.len() {
+        if held[i] >= level {
+            run_start = None;
+            continue;
+        }
+        let start = *run_start.get_or_insert(i);
+        if i + 1 - start >= hold_blocks || i + 1 == held.len() {
+            // The hold kept the envelope up for HOLD_BLOCKS - 1 blocks after
+            // the raw peaks fell under the level.
+            let end = (start + 1).saturating_sub(HOLD_BLOCKS).max(peak_idx + 1);
+            return (end as f64 * block_ms).min(whole_ms);
+        }
+    }
+    whole_ms
+}
+
+/// End of a voiced pitch contour in milliseconds from the event's onset: its
+/// last voiced frame plus one frame step. `0.0` for an empty contour.
+pub fn voiced_end_ms(contour: &[PitchPoint]) -> f64 {
+    let Some(last) = contour.last() else {
+        return 0.0;
+    };
+    let step = contour
+        .windows(2)
+        .map(|w| w[1].time_ms - w[0].time_ms)
+        .filter(|&d| d > 0.0)
+        .fold(f64::INFINITY, f64::min);
+    last.time_ms + if step.is_finite() { step } else { 0.0 }
+}
+
+/// An event's measured duration: the energy decay of `samples` (its audio
+/// from the onset), extended to the end of its voiced `contour` for a hum and
+/// capped at `gap_ms`, the gap to the next onset.
+pub fn measure_duration(samples: &[f32], sample_rate: u32, gap_ms: f64, contour: &[PitchPoint]) -> f64 {
+    decay_offset_ms(samples, sample_rate).max(voiced_end_ms(contour)).min(gap_ms.max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SR: u32 = 44_100;
+
+    /// `ms` of a 200Hz tone, its amplitude given by `envelope(t_ms)`.
+    fn tone(ms: f64, envelope: impl Fn(f64) -> f32) -> Vec<f32> {
+        let n = (ms / 1000.0 * SR as f64) as usize;
+        (0..n)
+            .map(|i| {
+                let t = i as f64 / SR as f64;
+                envelope(t * 1000.0) * (2.0 * std::f64::consts::PI * 200.0 * t).sin() as f32
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_short_hit_ends_at_its_decay_not_the_gap() {
+        // 40ms of sound, then 2s of silence before the next onset.
+        let samples = tone(2040.0, |t| if t < 40.0 { 0.8 } else { 0.0 });
+        let duration = measure_duration(&samples, SR, 2040.0, &[]);
+        assert!((duration - 40.0).abs() < 3.0, "{duration}");
+    }
+
+    #[test]
+    fn an_exponential_tail_ends_twenty_db_down() {
+        // e^(-t/50ms) reaches 0.1 at ~115ms.
+        let samples = tone(600.0, |t| (-t / 50.0).exp() as f32);
+        let duration = decay_offset_ms(&samples, SR);
+        assert!((duration - 115.0).abs() < 8.0, "{duration}");
+    }
+
+    #[test]
+    fn a_short_dip_does_not_end_the_sound() {
+        // A 15ms gap inside a held sound (shorter than DECAY_HOLD_MS).
+        let samples = tone(700.0, |t| if (200.0..215.0).contains(&t) || t >= 500.0 { 0.0 } else { 0.5 });
+        let duration = decay_offset_ms(&samples, SR);
+        assert!((duration - 500.0).abs() < 3.0, "{duration}");
+    }
+
+    #[test]
+    fn sustained_sound_runs_to_the_gap() {
+        let samples = tone(300.0, |_| 0.5);
+        assert_eq!(measure_duration(&samples, SR, 300.0, &[]), 300.0);
+    }
+
+    #[test]
+    fn a_hum_lasts_as_long_as_it_is_voiced() {
+        // A hum fading through the decay level while still voiced to 400ms.
+        let samples = tone(1000.0, |t| if t < 420.0 { (1.0 - t / 420.0) as f32 } else { 0.0 });
+        let contour: Vec<PitchPoint> = (0..=66)
+            .map(|k| PitchPoint { time_ms: 11.6 + k as f64 * 5.8, f0_hz: 200.0, confidence: 0.9 })
+            .collect();
+        assert!(decay_offset_ms(&samples, SR) < 400.0);
+        let duration = measure_duration(&samples, SR, 1000.0, &contour);
+        assert!((duration - voiced_end_ms(&contour)).abs() < 1e-9 && duration > 395.0, "{duration}");
+    }
+
+    #[test]
+    fn silence_and_empty_input() {
+        assert_eq!(decay_offset_ms(&[], SR), 0.0);
+        assert_eq!(decay_offset_ms(&[0.0; 4410], SR), 100.0);
+        assert_eq!(voiced_end_ms(&[]), 0.0);
+    }
+}
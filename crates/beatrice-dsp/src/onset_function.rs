@@ -0,0 +1,496 @@
+// Onset detection functions
+//
+// The value per STFT frame that onset peak picking runs on. The historical
+// detector is plain rectified spectral flux on linear bins, which is biased
+// toward hi-hats (high bins outnumber low ones), so a parallel RMS energy
+// detector catches the kicks it misses. The alternatives here trade that
+// bolt-on for a function with a different bias: SuperFlux on a log-frequency,
+// log-magnitude spectrogram with a vibrato-suppressing maximum filter,
+// high-frequency content, rectified complex-domain, and a multi-band flux that
+// weighs the kick, snare and hat regions equally. Offline and streaming
+// detectors share one implementation so their values agree frame for frame.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+use realfft::num_complex::Complex;
+use serde::{Deserialize, Serialize};
+
+use crate::spectrogram;
+
+/// SuperFlux filterbank resolution and range (Böck & Widmer, DAFx 2013).
+const SUPERFLUX_BANDS_PER_OCTAVE: f32 = 24.0;
+const SUPERFLUX_FMIN_HZ: f32 = 30.0;
+const SUPERFLUX_FMAX_HZ: f32 = 17_000.0;
+
+/// Width (in filterbank bands) of the maximum filter applied to the reference
+/// frame: a partial gliding by up to one band (vibrato, a sliding hum) stays
+/// under its own maximum and adds no flux.
+const SUPERFLUX_MAX_FILTER_BANDS: usize = 3;
+
+/// Band edges of [`OnsetFunction::MultiBand`], matching the kick / snare /
+/// hat split of the feature extractor's band energies.
+const MULTI_BAND_EDGES_HZ: [f32; 2] = [500.0, 4000.0];
+
+/// Which onset detection function the detector peaks on (see
+/// [`crate::OnsetConfig::onset_function`] and
+/// [`crate::StreamingConfig::onset_function`]).
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnsetFunction {
+    /// Rectified spectral flux on linear bins plus the RMS energy detector
+    /// for low-frequency hits (the historical detector).
+    #[default]
+    SpectralFlux,
+    /// SuperFlux: rectified flux of a 24-band-per-octave log-magnitude
+    /// spectrogram against a frequency-max-filtered earlier frame, which
+    /// suppresses vibrato and pitch glides.
+    SuperFlux,
+    /// Rise in high-frequency content (bin-index-weighted magnitude), plus the
+    /// energy detector: sharp on clicks and hats.
+    Hfc,
+    /// Rectified complex-domain deviation from a constant-magnitude,
+    /// steady-phase prediction, plus the energy detector: also fires on soft
+    /// tonal onsets that barely change the magnitude.
+    ComplexDomain,
+    /// Spectral flux summed over three bands (kick, snare, hat regions), each
+    /// normalised by its width so low-frequency hits weigh as much as
+    /// high-frequency ones without a separate energy detector.
+    MultiBand,
+}
+
+impl OnsetFunction {
+    /// Every function, in a stable report order.
+    pub const ALL: [OnsetFunction; 5] = [
+        OnsetFunction::SpectralFlux,
+        OnsetFunction::SuperFlux,
+        OnsetFunction::Hfc,
+        OnsetFunction::ComplexDomain,
+        OnsetFunction::MultiBand,
+    ];
+
+    /// Whether the RMS energy detector runs alongside this function to catch
+    /// kicks it under-weights. SuperFlux's log-frequency bands and the
+    /// multi-band fusion already give the low end its share.
+    pub fn uses_energy_fallback(self) -> bool {
+        matches!(
+            self,
+            OnsetFunction::SpectralFlux | OnsetFunction::Hfc | OnsetFunction::ComplexDomain
+        )
+    }
+}
+
+impl fmt::Display for OnsetFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OnsetFunction::SpectralFlux => "spectral-flux",
+            OnsetFunction::SuperFlux => "superflux",
+            OnsetFunction::Hfc => "hfc",
+            OnsetFunction::ComplexDomain => "complex-domain",
+            OnsetFunction::MultiBand => "multi-band",
+        })
+    }
+}
+
+impl FromStr for OnsetFunction {
+    type Err = String;
+
+    /// Parse the [`fmt::Display`] form (`spectral-flux`, `superflux`, `hfc`,
+    /// `complex-domain`, `multi-band`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OnsetFunction::ALL.into_iter().find(|f| f.to_string() == s).ok_or_else(|| {
+            format!(
+                "unknown onset function '{s}' (expected spectral-flux, superflux, hfc, complex-domain or multi-band)"
+            )
+        })
+    }
+}
+
+/// Frame-by-frame evaluator of an [`OnsetFunction`].
+///
+/// Feed Hann-windowed frames of `window_size` samples, one hop apart, to
+/// [`process`](Self::process); each call returns that frame's detection value
+/// (0.0 until enough earlier frames exist to compare against).
+///
+/// The buffers each function keeps between frames are allocated up front, so
+/// [`process_spectrum`](Self::process_spectrum) does not allocate.
+pub struct OnsetDetectionFunction {
+    function: OnsetFunction,
+    /// Previous frame's magnitudes (flux, multi-band, complex-domain), valid
+    /// once `has_prev` is set.
+    prev_magnitudes: Vec<f32>,
+    has_prev: bool,
+    /// Previous frame's high-frequency content.
+    prev_hfc: Option<f32>,
+    /// Phases of the previous two frames, older first (complex-domain), and
+    /// how many of them are filled.
+    phases: [Vec<f32>; 2],
+    phase_frames: usize,
+    /// Recent log-filtered frames, newest last (SuperFlux); the reference is
+    /// `lag` frames back.
+    history: VecDeque<Vec<f32>>,
+    /// Spare frame buffers the history recycles.
+    spare: Vec<Vec<f32>>,
+    lag: usize,
+    /// SuperFlux triangular filters as `(first_bin, weights)`.
+    filterbank: Vec<(usize, Vec<f32>)>,
+    /// Multi-band split as bin indices.
+    band_bins: [usize; 2],
+}
+
+impl OnsetDetectionFunction {
+    pub fn new(function: OnsetFunction, sample_rate: u32, window_size: usize, hop_size: usize) -> Self {
+        let bin_hz = sample_rate as f32 / window_size.max(1) as f32;
+        let bins = window_size / 2 + 1;
+        let band_bins = MULTI_BAND_EDGES_HZ.map(|hz| ((hz / bin_hz).round() as usize).clamp(1, bins));
+        // The reference frame sits where the Hann windows overlap by half or
+        // less, so a slow swell is not mistaken for an onset.
+        let lag = ((window_size as f32 / (4.0 * hop_size.max(1) as f32)).round() as usize).max(1);
+        let filterbank = if function == OnsetFunction::SuperFlux {
+            log_filterbank(sample_rate, window_size)
+        } else {
+            Vec::new()
+        };
+        let phase_bins = if function == OnsetFunction::ComplexDomain { bins } else { 0 };
+        let spare = if function == OnsetFunction::SuperFlux {
+            (0..=lag).map(|_| Vec::with_capacity(filterbank.len())).collect()
+        } else {
+            Vec::new()
+        };
+        OnsetDetectionFunction {
+            function,
+            prev_magnitudes: Vec::with_capacity(bins),
+            has_prev: false,
+            prev_hfc: None,
+            phases: [Vec::with_capacity(phase_bins), Vec::with_capacity(phase_bins)],
+            phase_frames: 0,
+            history: VecDeque::with_capacity(lag + 1),
+            spare,
+            lag,
+            filterbank,
+            band_bins,
+        }
+    }
+
+    pub fn function(&self) -> OnsetFunction {
+        self.function
+    }
+
+    /// Detection value of the next Hann-windowed frame.
+    pub fn process(&mut self, windowed: &[f32]) -> f32 {
+        let mut magnitudes = Vec::new();
+        spectrogram::with_plans(|plans| {
+            let spectrum = plans.spectrum(windowed);
+            magnitudes.extend(spectrum.iter().map(|c| c.norm()));
+            self.process_spectrum(spectrum, &magnitudes)
+        })
+    }
+
+    /// Detection value of the next frame given its spectrum and magnitudes
+    /// (a [`Spectrogram`](crate::Spectrogram) frame, or one from
+    /// [`FftPlans`](crate::spectrogram::FftPlans)).
+    pub fn process_spectrum(&mut self, spectrum: &[Complex<f32>], magnitudes: &[f32]) -> f32 {
+        match self.function {
+            OnsetFunction::SpectralFlux => {
+                let flux = if self.has_prev { rectified_flux(magnitudes, &self.prev_magnitudes) } else { 0.0 };
+                self.remember(magnitudes);
+                flux
+            }
+            OnsetFunction::MultiBand => {
+                let prev = &self.prev_magnitudes;
+                let flux = if !self.has_prev {
+                    0.0
+                } else {
+                    // Each band's mean per-bin flux, rescaled so a broadband
+                    // change scores what plain flux would.
+                    let [low, mid] = self.band_bins;
+                    let bands = [0..low, low..mid.max(low), mid.max(low)..magnitudes.len()];
+                    let used = bands.iter().filter(|b| !b.is_empty()).count().max(1);
+                    bands
+                        .into_iter()
+                        .filter(|b| !b.is_empty())
+                        .map(|b| {
+                            let width = b.len() as f32;
+                            rectified_flux(&magnitudes[b.clone()], &prev[b]) / width
+                        })
+                        .sum::<f32>()
+                        * magnitudes.len() as f32
+                        / used as f32
+                };
+                self.remember(magnitudes);
+                flux
+            }
+            OnsetFunction::Hfc => {
+                let bins = magnitudes.len().max(1) as f32;
+                let hfc = magnitudes.iter().enumerate().map(|(k, m)| k as f32 / bins * m).sum::<f32>();
+                let rise = self.prev_hfc.map_or(0.0, |prev| (hfc - prev).max(0.0));
+                self.prev_hfc = Some(hfc);
+                rise
+            }
+            OnsetFunction::ComplexDomain => {
+                let value = match (self.has_prev, self.phase_frames) {
+                    (true, 2) => {
+                        let (prev_mag, [older, prev]) = (&self.prev_magnitudes, &self.phases);
+                        spectrum
+                            .iter()
+                            .enumerate()
+                            .filter(|&(k, _)| magnitudes[k] >= prev_mag[k])
+                            .map(|(k, &x)| {
+                                let target = Complex::from_polar(prev_mag[k], 2.0 * prev[k] - older[k]);
+                                (x - target).norm()
+                            })
+                            .sum()
+                    }
+                    _ => 0.0,
+                };
+                self.remember(magnitudes);
+                // The previous frame becomes the older one; its buffer takes
+                // this frame's phases.
+                self.phases.swap(0, 1);
+                self.phases[1].clear();
+                self.phases[1].extend(spectrum.iter().map(|c| c.arg()));
+                self.phase_frames = (self.phase_frames + 1).min(2);
+                value
+            }
+            OnsetFunction::SuperFlux => {
+                let mut filtered = self.spare.pop().unwrap_or_default();
+                filtered.clear();
+                filtered.extend(self.filterbank.iter().map(|(first, weights)| {
+                    let energy: f32 = weights.iter().zip(&magnitudes[*first..]).map(|(w, m)| w * m).sum();
+                    (1.0 + energy).log10()
+                }));
+                let value = if self.history.len() >= self.lag {
+                    let reference = &self.history[self.history.len() - self.lag];
+                    let half = SUPERFLUX_MAX_FILTER_BANDS / 2;
+                    filtered
+                        .iter()
+                        .enumerate()
+                        .map(|(b, &x)| {
+                            let lo = b.saturating_sub(half);
+                            let hi = (b + half + 1).min(reference.len());
+                            let max = reference[lo..hi].iter().copied().fold(f32::MIN, f32::max);
+                            (x - max).max(0.0)
+                        })
+                        .sum()
+                } else {
+                    0.0
+                };
+                self.history.push_back(filtered);
+                if self.history.len() > self.lag {
+                    self.spare.extend(self.history.pop_front());
+                }
+                value
+            }
+        }
+    }
+
+    /// Keep a copy of `magnitudes` as the previous frame.
+    fn remember(&mut self, magnitudes: &[f32]) {
+        self.prev_magnitudes.clear();
+        self.prev_magnitudes.extend_from_slice(magnitudes);
+        self.has_prev = true;
+    }
+
+    /// The frames the next value is measured against, for a streaming
+    /// snapshot.
+    pub(crate) fn state(&self) -> OnsetFunctionState {
+        OnsetFunctionState {
+            prev_magnitudes: self.prev_magnitudes.clone(),
+            has_prev: self.has_prev,
+            prev_hfc: self.prev_hfc,
+            phases: self.phases.clone(),
+            phase_frames: self.phase_frames,
+            history: self.history.iter().cloned().collect(),
+        }
+    }
+
+    /// Resume from [`state`](Self::state) taken on a function built with the
+    /// same parameters. History frames reuse the spare buffers.
+    pub(crate) fn restore_state(&mut self, state: OnsetFunctionState) -> Result<(), String> {
+        if state.history.len() > self.lag || state.history.iter().any(|f| f.len() != self.filterbank.len()) {
+            return Err("onset function state does not match its parameters".to_string());
+        }
+        self.prev_magnitudes.clear();
+        self.prev_magnitudes.extend_from_slice(&state.prev_magnitudes);
+        self.has_prev = state.has_prev;
+        self.prev_hfc = state.prev_hfc;
+        for (phases, stored) in self.phases.iter_mut().zip(&state.phases) {
+            phases.clear();
+            phases.extend_from_slice(stored);
+        }
+        self.phase_frames = state.phase_frames;
+        self.spare.extend(self.history.drain(..));
+        for stored in state.history {
+            let mut frame = self.spare.pop().unwrap_or_default();
+            frame.clear();
+            frame.extend_from_slice(&stored);
+            self.history.push_back(frame);
+        }
+        Ok(())
+    }
+}
+
+/// [`OnsetDetectionFunction`] state carried between frames.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct OnsetFunctionState {
+    #[serde(with = "crate::snapshot::f32s")]
+    prev_magnitudes: Vec<f32>,
+    has_prev: bool,
+    prev_hfc: Option<f32>,
+    phases: [Vec<f32>; 2],
+    phase_frames: usize,
+    history: Vec<Vec<f32>>,
+}
+
+/// Sum of positive magnitude differences.
+fn rectified_flux(spectrum: &[f32], prev: &[f32]) -> f32 {
+    let mut sum = 0.0;
+    for (curr, prev) in spectrum.iter().zip(prev.iter()) {
+        let diff = curr - prev;
+        if diff > 0.0 {
+            sum += diff;
+        }
+    }
+    sum
+}
+
+
+
+/// Triangular filters with log-spaced centres between
+/// [`SUPERFLUX_FMIN_HZ`] and [`SUPERFLUX_FMAX_HZ`] (capped at Nyquist).
+/// Centres that fall on the same FFT bin are merged, so short windows get
+/// fewer, wider low bands rather than duplicates.
+fn log_filterbank(sample_rate: u32, window_size: usize) -> Vec<(usize, Vec<f32>)> {
+    let bins = window_size / 2 + 1;
+    let bin_hz = sample_rate as f32 / window_size.max(1) as f32;
+    let fmax = SUPERFLUX_FMAX_HZ.min(sample_rate as f32 / 2.0);
+    if bins < 3 || fmax <= SUPERFLUX_FMIN_HZ {
+        return Vec::new();
+    }
+    let octaves = (fmax / SUPERFLUX_FMIN_HZ).log2();
+    let count = (octaves * SUPERFLUX_BANDS_PER_OCTAVE).ceil() as usize;
+    let mut centres: Vec<usize> = (0..=count)
+        .map(|i| SUPERFLUX_FMIN_HZ * 2f32.powf(i as f32 / SUPERFLUX_BANDS_PER_OCTAVE))
+        .map(|hz| ((hz / bin_hz).round() as usize).min(bins - 1))
+        .collect();
+    centres.dedup();
+
+    centres
+        .windows(3)
+        .map(|w| {
+            let (left, centre, right) = (w[0], w[1], w[2]);
+            let weights = (left..=right)
+                .map(|k| {
+                    if k <= centre {
+                        (k - left) as f32 / (centre - left) as f32
+                    } else {
+                        (right - k) as f32 / (right - centre) as f32
+                    }
+                })
+                .collect();
+            (left, weights)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::apply_hann_window;
+
+    const SR: u32 = 44_100;
+
+    /// Detection values over `signal` at 2048/512 framing.
+    fn curve(function: OnsetFunction, signal: &[f32]) -> Vec<f32> {
+        let mut odf = OnsetDetectionFunction::new(function, SR, 2048, 512);
+        signal
+            .windows(2048)
+            .step_by(512)
+            .map(|frame| {
+                let mut frame = frame.to_vec();
+                apply_hann_window(&mut frame);
+                odf.process(&frame)
+            })
+            .collect()
+    }
+
+    fn argmax(values: &[f32]) -> usize {
+        values.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)).map(|(i, _)| i).unwrap()
+    }
+
+    /// Silence then a decaying noise burst at sample 22050.
+    fn burst() -> Vec<f32> {
+        let mut seed = 7u32;
+        (0..44_100)
+            .map(|i| {
+                if i < 22_050 {
+                    return 0.0;
+                }
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let noise = (seed >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0;
+                noise * 0.5 * (-((i - 22_050) as f32) / 4000.0).exp()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for function in OnsetFunction::ALL {
+            assert_eq!(function.to_string().parse::<OnsetFunction>(), Ok(function));
+        }
+        assert!("flux".parse::<OnsetFunction>().is_err());
+        assert_eq!(OnsetFunction::default(), OnsetFunction::SpectralFlux);
+    }
+
+    #[test]
+    fn every_function_peaks_at_a_burst() {
+        // The burst enters frames starting after 22050 - 2048; the peak must
+        // sit within the frames that straddle it.
+        let expected = (22_050 - 2048) / 512..=22_050 / 512 + 1;
+        for function in OnsetFunction::ALL {
+            let values = curve(function, &burst());
+            assert_eq!(values[0], 0.0, "{function}");
+            let peak = argmax(&values);
+            assert!(expected.contains(&peak), "{function}: peak at frame {peak}");
+            assert!(values[peak] > 0.0, "{function}");
+        }
+    }
+
+    #[test]
+    fn superflux_ignores_a_vibrato_that_plain_flux_sees() {
+        // A steady tone with a ±30-cent, 6 Hz vibrato at 440 Hz.
+        let mut phase = 0.0f32;
+        let tone: Vec<f32> = (0..44_100)
+            .map(|i| {
+                let t = i as f32 / SR as f32;
+                let f = 440.0 * 2f32.powf(0.3 / 12.0 * (2.0 * std::f32::consts::PI * 6.0 * t).sin());
+                phase += 2.0 * std::f32::consts::PI * f / SR as f32;
+                0.5 * phase.sin()
+            })
+            .collect();
+        let mean = |values: &[f32]| values[4..].iter().sum::<f32>() / (values.len() - 4) as f32;
+        let flux = curve(OnsetFunction::SpectralFlux, &tone);
+        let superflux = curve(OnsetFunction::SuperFlux, &tone);
+        // Relative to each function's response to a real onset.
+        let flux_onset = curve(OnsetFunction::SpectralFlux, &burst()).into_iter().fold(0.0, f32::max);
+        let superflux_onset = curve(OnsetFunction::SuperFlux, &burst()).into_iter().fold(0.0, f32::max);
+        assert!(
+            mean(&superflux) / superflux_onset < 0.5 * mean(&flux) / flux_onset,
+            "superflux {} / {superflux_onset}, flux {} / {flux_onset}",
+            mean(&superflux),
+            mean(&flux)
+        );
+    }
+
+    #[test]
+    fn filterbank_is_log_spaced_and_in_range() {
+        let bank = log_filterbank(SR, 2048);
+        assert!(bank.len() > 100, "{}", bank.len());
+        assert!(bank.windows(2).all(|w| w[1].0 >= w[0].0));
+        assert!(bank.iter().all(|(first, weights)| first + weights.len() <= 1025));
+        // Short windows merge coincident low centres instead of duplicating them.
+        assert!(log_filterbank(SR, 512).len() < bank.len());
+    }
+}
@@ -0,0 +1,163 @@
+//! Offline analysis of a caller-supplied PCM buffer.
+//!
+//! The native `detect_events` command opens a file and looks its calibration
+//! and room-tone profiles up by database id. The browser demo has neither a
+//! file system nor a database: it decodes an upload or a jam capture itself
+//! and holds any profiles in memory. [`analyze_pcm`] runs the same pipeline
+//! ([`crate::analyze_offline_hybrid`]) over those samples, configured by a
+//! [`PcmAnalysisOptions`] that mirrors the command's options with the
+//! profiles inline. The WASM build exposes it as
+//! [`analyze_offline_hybrid_json`](crate::analyze_offline_hybrid_json).
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::channels::ChannelStrategy;
+use crate::conditioning::ConditioningConfig;
+use crate::events::{build_classifier, CalibrationProfile, Event, MultiLabelConfig, DEFAULT_CLASSIFIER};
+use crate::features::OnsetConfig;
+use crate::ingest::AudioData;
+use crate::noise::NoiseProfile;
+use crate::onset_function::OnsetFunction;
+
+/// How [`analyze_pcm`] detects and classifies. Every field defaults (a `{}`
+/// JSON object is the shipping configuration), matching the native
+/// `detect_events` command's options of the same names.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PcmAnalysisOptions {
+    /// Resample to the classifier's training rate
+    /// ([`FACTORY_SAMPLE_RATE`](crate::FACTORY_SAMPLE_RATE)) first.
+    pub normalize_sample_rate: bool,
+    /// How multi-channel audio is reduced before detection. Defaults to
+    /// averaging.
+    pub channel_strategy: Option<ChannelStrategy>,
+    /// Conditioning chain applied before detection and feature extraction.
+    pub conditioning: Option<ConditioningConfig>,
+    /// Registered classifier to run. Defaults to [`DEFAULT_CLASSIFIER`].
+    pub classifier: Option<String>,
+    /// Onset detection function. Defaults to spectral flux.
+    pub onset_function: Option<OnsetFunction>,
+    /// Emit one event per sound layered on an onset.
+    pub multi_label: Option<MultiLabelConfig>,
+    /// User calibration profile the classifier is personalised from.
+    pub calibration_profile: Option<CalibrationProfile>,
+    /// Room-tone profile that raises the onset gates and seeds the denoiser.
+    pub noise_profile: Option<NoiseProfile>,
+}
+
+impl PcmAnalysisOptions {
+    /// The onset configuration these options describe.
+    pub fn onset_config(&self) -> OnsetConfig {
+        let mut config = OnsetConfig {
+            channels: self.channel_strategy.unwrap_or_default(),
+            conditioning: self.conditioning,
+            onset_function: self.onset_function.unwrap_or_default(),
+            multi_label: self.multi_label,
+            ..OnsetConfig::default()
+        };
+        if let Some(profile) = &self.noise_profile {
+            profile.adapt_onset_config(&mut config);
+        }
+        config
+    }
+}
+
+/// Detect and classify the events in `samples`: interleaved PCM of `channels`
+/// channels at `sample_rate` Hz, normalized to `[-1.0, 1.0]`. Errors on an
+/// empty or ragged buffer and on an unknown classifier name.
+pub fn analyze_pcm(
+    samples: Vec<f32>,
+    sample_rate: u32,
+    channels: u16,
+    options: &PcmAnalysisOptions,
+) -> Result<Vec<Event>, String> {
+    if sample_rate == 0 || channels == 0 {
+        return Err(format!("Invalid audio format: {sample_rate} Hz, {channels} channels"));
+    }
+    let frame_count = samples.len() / channels as usize;
+    if frame_count == 0 || frame_count * channels as usize != samples.len() {
+        return Err(format!("Expected whole frames of {channels} channels, got {} samples", samples.len()));
+    }
+    let classifier = build_classifier(
+        options.classifier.as_deref().unwrap_or(DEFAULT_CLASSIFIER),
+        options.calibration_profile.as_ref(),
+    )?;
+
+    let audio = AudioData {
+        samples,
+        sample_rate,
+        channels,
+        bit_depth: 32,
+        duration_ms: (frame_count as u64 * 1000 / sample_rate as u64) as i64,
+        frame_count,
+    };
+    let audio = if options.normalize_sample_rate { crate::normalize_sample_rate(&audio) } else { Cow::Owned(audio) };
+    Ok(crate::analyze_offline_hybrid(&audio, &options.onset_config(), classifier.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HybridClassifier;
+
+    /// Two decaying noise bursts a half second apart, in stereo.
+    fn stereo_bursts(sample_rate: u32) -> Vec<f32> {
+        let mut state = 0x2545_f491_u32;
+        (0..sample_rate as usize)
+            .flat_map(|i| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                let noise = (state as f32 / u32::MAX as f32) * 2.0 - 1.0;
+                let t = (i % (sample_rate as usize / 2)) as f32 / sample_rate as f32;
+                let s = 0.8 * noise * (-t / 0.02).exp();
+                [s, 0.5 * s]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_the_offline_pipeline() {
+        let samples = stereo_bursts(44_100);
+        let options: PcmAnalysisOptions = serde_json::from_str("{}").unwrap();
+        let events = analyze_pcm(samples.clone(), 44_100, 2, &options).unwrap();
+        let audio = AudioData {
+            samples,
+            sample_rate: 44_100,
+            channels: 2,
+            bit_depth: 32,
+            duration_ms: 1000,
+            frame_count: 44_100,
+        };
+        let expected = crate::analyze_offline_hybrid(&audio, &OnsetConfig::default(), &HybridClassifier::factory());
+        assert_eq!(events.len(), 2);
+        assert_eq!(events.len(), expected.len());
+        for (e, x) in events.iter().zip(&expected) {
+            assert_eq!((e.timestamp_ms, e.class, e.confidence), (x.timestamp_ms, x.class, x.confidence));
+            assert_eq!(e.all_scores.len(), x.all_scores.len());
+        }
+    }
+
+    #[test]
+    fn options_read_like_the_native_command() {
+        let options: PcmAnalysisOptions = serde_json::from_str(
+            r#"{"classifier":"heuristic","onset_function":"super_flux","normalize_sample_rate":true}"#,
+        )
+        .unwrap();
+        assert_eq!(options.onset_config().onset_function, OnsetFunction::SuperFlux);
+        let events = analyze_pcm(stereo_bursts(48_000), 48_000, 2, &options).unwrap();
+        assert_eq!(events.len(), 2);
+    }
+
+    #[test]
+    fn rejects_bad_buffers_and_classifiers() {
+        let options = PcmAnalysisOptions::default();
+        assert!(analyze_pcm(vec![0.0; 3], 44_100, 2, &options).is_err());
+        assert!(analyze_pcm(Vec::new(), 44_100, 1, &options).is_err());
+        assert!(analyze_pcm(vec![0.0; 4], 0, 1, &options).is_err());
+        let unknown = PcmAnalysisOptions { classifier: Some("nope".to_string()), ..options };
+        assert!(analyze_pcm(vec![0.0; 4410], 44_100, 1, &unknown).is_err());
+    }
+}
@@ -0,0 +1,378 @@
+//! Band-limited sample-rate conversion.
+//!
+//! The factory [`GaussianModel`](crate::GaussianModel) was fitted on 44.1 kHz
+//! AVP audio ([`FACTORY_SAMPLE_RATE`](crate::events::gaussian::FACTORY_SAMPLE_RATE)),
+//! and every rate-dependent stage — the STFT hop behind onset detection, the
+//! MFCC frame length, the band-energy bins — sees different statistics at
+//! 48 kHz or 96 kHz. Converting to the training rate up front makes the
+//! classifier's inputs rate-independent instead of relying on the MFCC
+//! filterbank cap alone.
+//!
+//! # Design
+//!
+//! A polyphase windowed-sinc interpolator over the reduced rational ratio
+//! `to / from = up / down`. Each output sample sits at input position
+//! `n * down / up`; its integer part picks the input taps and its fractional
+//! part (`0..up`) picks one of the precomputed filter phases. The kernel is a
+//! Kaiser-windowed sinc with [`ZERO_CROSSINGS`] lobes per side at the lower of
+//! the two rates, so downsampling is anti-aliased and upsampling is
+//! anti-imaged with the same code path.
+//!
+//! The filter is symmetric and evaluated around the output instant, so it adds
+//! NO time shift: an onset at 500ms in the input is at 500ms in the output.
+//! (Streaming only has to wait for [`Resampler::latency`] input samples of
+//! lookahead before an output sample can be produced.)
+//!
+//! Uncommon rate pairs whose reduced `up` exceeds [`MAX_PHASES`] (e.g.
+//! 44100 → 44101) round each output to the nearest of [`MAX_PHASES`] phases —
+//! a ≤1/2048-sample timing jitter, far below anything the detectors resolve.
+
+use serde::{Deserialize, Serialize};
+
+/// Kernel lobes on each side of the centre tap, measured at the lower rate.
+const ZERO_CROSSINGS: usize = 16;
+
+/// Passband edge as a fraction of the lower rate's Nyquist. Leaves a short
+/// transition band so the Kaiser window reaches full stopband attenuation
+/// before the folding frequency.
+const ROLLOFF: f64 = 0.94;
+
+/// Kaiser window shape (β ≈ 8.6 → ~85 dB stopband).
+const KAISER_BETA: f64 = 8.6;
+
+/// Upper bound on the precomputed filter phases (table size = phases × taps).
+const MAX_PHASES: usize = 1024;
+
+/// Stateful band-limited resampler for a mono stream.
+///
+/// Feed chunks of any size to [`process`](Self::process); call
+/// [`flush`](Self::flush) once at end of stream to drain the lookahead tail.
+/// Chunking does not change the output: pushing a signal in 128-sample render
+/// quanta yields exactly what [`resample`] produces for the whole buffer.
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    from_rate: u32,
+    to_rate: u32,
+    /// Reduced ratio: `to / from = up / down`.
+    up: u64,
+    down: u64,
+    /// Precomputed filter phases and taps per phase; `table[p * taps + k]`.
+    phases: usize,
+    taps: usize,
+    table: Vec<f32>,
+    /// Input history. `buf[0]` is absolute input index `buf_start`, which is
+    /// negative during warm-up (the implicit zeros before the first sample).
+    buf: Vec<f32>,
+    buf_start: i64,
+    /// Real input samples received so far.
+    received: i64,
+    /// Next output's input position: `base + frac / up`.
+    base: i64,
+    frac: u64,
+}
+
+impl Resampler {
+    /// Create a resampler from `from_rate` to `to_rate` (Hz).
+    pub fn new(from_rate: u32, to_rate: u32) -> Self {
+        let from_rate = from_rate.max(1);
+        let to_rate = to_rate.max(1);
+        let g = gcd(from_rate as u64, to_rate as u64);
+        let up = to_rate as u64 / g;
+        let down = from_rate as u64 / g;
+
+        // Scale the kernel to the lower rate: when downsampling, the cutoff
+        // drops below the input Nyquist and the kernel widens to match.
+        let scale = (up as f64 / down as f64).min(1.0);
+        let cutoff = 0.5 * ROLLOFF * scale; // cycles per input sample
+        let half = (ZERO_CROSSINGS as f64 / scale).ceil() as usize;
+        let taps = 2 * half;
+        let phases = if up == down { 1 } else { (up as usize).min(MAX_PHASES) };
+
+        let i0_beta = bessel_i0(KAISER_BETA);
+        let mut table = Vec::with_capacity(phases * taps);
+        for p in 0..phases {
+            let offset = p as f64 / phases as f64;
+            let start = table.len();
+            for k in 0..taps {
+                // Distance (in input samples) from tap k to the output instant.
+                let d = (half as f64 - 1.0 - k as f64) + offset;
+                let x = 2.0 * cutoff * d;
+                let sinc = if x == 0.0 {
+                    1.0
+                } else {
+                    (std::f64::consts::PI * x).sin() / (std::f64::consts::PI * x)
+                };
+                let r = (d / half as f64).clamp(-1.0, 1.0);
+                let window = bessel_i0(KAISER_BETA * (1.0 - r * r).sqrt()) / i0_beta;
+                table.push((2.0 * cutoff * sinc * window) as f32);
+            }
+            // Unity DC gain per phase, so a constant input stays constant and
+            // no phase carries a ripple of its own.
+            let sum: f32 = table[start..].iter().sum();
+            if sum != 0.0 {
+                for c in &mut table[start..] {
+                    *c /= sum;
+                }
+            }
+        }
+
+        Resampler {
+            from_rate,
+            to_rate,
+            up,
+            down,
+            phases,
+            taps,
+            table,
+            buf: vec![0.0; half - 1],
+            buf_start: -(half as i64 - 1),
+            received: 0,
+            base: 0,
+            frac: 0,
+        }
+    }
+
+    /// Input sample rate (Hz).
+    pub fn from_rate(&self) -> u32 {
+        self.from_rate
+    }
+
+    /// Output sample rate (Hz).
+    pub fn to_rate(&self) -> u32 {
+        self.to_rate
+    }
+
+    /// Whether input and output rates match (the resampler copies through).
+    pub fn is_passthrough(&self) -> bool {
+        self.from_rate == self.to_rate
+    }
+
+    /// Input samples of lookahead an output sample waits for before it can be
+    /// produced (zero for a passthrough).
+    pub fn latency(&self) -> usize {
+        if self.is_passthrough() {
+            0
+        } else {
+            self.taps / 2
+        }
+    }
+
+    /// Size the input history for chunks of up to `chunk` samples, so
+    /// [`process`](Self::process) does not allocate for them (the streaming
+    /// detector's real-time path).
+    pub fn reserve(&mut self, chunk: usize) {
+        self.buf.reserve((self.taps + chunk).saturating_sub(self.buf.len()));
+    }
+
+    /// Resample a chunk, appending the output samples that are now fully
+    /// determined to `out`.
+    pub fn process(&mut self, input: &[f32], out: &mut Vec<f32>) {
+        if self.is_passthrough() {
+            out.extend_from_slice(input);
+            return;
+        }
+        self.buf.extend_from_slice(input);
+        self.received += input.len() as i64;
+        self.emit(self.received, self.received, out);
+    }
+
+    /// End of stream: treat the input as zero beyond its last sample and emit
+    /// the remaining outputs up to the end of the input's duration. Subsequent
+    /// calls emit nothing.
+    pub fn flush(&mut self, out: &mut Vec<f32>) {
+        if self.is_passthrough() {
+            return;
+        }
+        let half = self.taps / 2;
+        let padded_end = self.buf_start + self.buf.len() as i64;
+        let available = self.received + half as i64;
+        if padded_end < available {
+            self.buf.resize(self.buf.len() + (available - padded_end) as usize, 0.0);
+        }
+        self.emit(available, self.received, out);
+    }
+
+    /// Emit every output whose taps lie below input index `available` and
+    /// whose position lies before input index `end`, then drop consumed input.
+    fn emit(&mut self, available: i64, end: i64, out: &mut Vec<f32>) {
+        let half = (self.taps / 2) as i64;
+        while self.base < end {
+            // Nearest precomputed phase; rounding up to `phases` means the
+            // next input sample at phase 0.
+            let mut phase = ((self.frac * self.phases as u64 + self.up / 2) / self.up) as usize;
+            let mut centre = self.base;
+            if phase == self.phases {
+                phase = 0;
+                centre += 1;
+            }
+            if centre + half >= available {
+                break;
+            }
+            let first = (centre - (half - 1) - self.buf_start) as usize;
+            let window = &self.buf[first..first + self.taps];
+            let coeffs = &self.table[phase * self.taps..(phase + 1) * self.taps];
+            out.push(window.iter().zip(coeffs).map(|(x, h)| x * h).sum());
+
+            self.frac += self.down;
+            self.base += (self.frac / self.up) as i64;
+            self.frac %= self.up;
+        }
+
+        let keep_from = self.base - (half - 1);
+        let consumed = (keep_from - self.buf_start).clamp(0, self.buf.len() as i64);
+        if consumed > 0 {
+            self.buf.drain(..consumed as usize);
+            self.buf_start += consumed;
+        }
+    }
+
+    /// The stream position and input history, for a streaming snapshot.
+    pub(crate) fn state(&self) -> ResamplerState {
+        ResamplerState {
+            buf: self.buf.clone(),
+            buf_start: self.buf_start,
+            received: self.received,
+            base: self.base,
+            frac: self.frac,
+        }
+    }
+
+    /// Resume from [`state`](Self::state) taken on a resampler between the
+    /// same rates.
+    pub(crate) fn restore_state(&mut self, state: ResamplerState) -> Result<(), String> {
+        if state.frac >= self.up {
+            return Err("resampler state does not match its rates".to_string());
+        }
+        self.buf.clear();
+        self.buf.extend_from_slice(&state.buf);
+        self.buf_start = state.buf_start;
+        self.received = state.received;
+        self.base = state.base;
+        self.frac = state.frac;
+        Ok(())
+    }
+}
+
+/// [`Resampler`] state that is not derived from its rates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ResamplerState {
+    #[serde(with = "crate::snapshot::f32s")]
+    buf: Vec<f32>,
+    buf_start: i64,
+    received: i64,
+    base: i64,
+    frac: u64,
+}
+
+/// Resample a whole mono buffer from `from_rate` to `to_rate` (Hz).
+///
+/// Returns `ceil(len * to_rate / from_rate)` samples, time-aligned with the
+/// input (no filter delay). Equal rates return a copy.
+pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    let mut resampler = Resampler::new(from_rate, to_rate);
+    let expected = (samples.len() as u64 * resampler.up).div_ceil(resampler.down) as usize;
+    let mut out = Vec::with_capacity(expected);
+    resampler.process(samples, &mut out);
+    resampler.flush(&mut out);
+    out
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Zeroth-order modified Bessel function of the first kind (power series),
+/// for the Kaiser window.
+fn bessel_i0(x: f64) -> f64 {
+    let q = x * x / 4.0;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for k in 1..64 {
+        term *= q / (k * k) as f64;
+        sum += term;
+        if term < sum * 1e-12 {
+            break;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, sample_rate: u32, secs: f32) -> Vec<f32> {
+        let n = (sample_rate as f32 * secs) as usize;
+        (0..n)
+            .map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / sample_rate as f32).sin())
+            .collect()
+    }
+
+    fn rms(x: &[f32]) -> f32 {
+        (x.iter().map(|s| s * s).sum::<f32>() / x.len().max(1) as f32).sqrt()
+    }
+
+    #[test]
+    fn equal_rates_pass_through() {
+        let x = sine(440.0, 44_100, 0.1);
+        assert_eq!(resample(&x, 44_100, 44_100), x);
+    }
+
+    #[test]
+    fn output_length_follows_the_ratio() {
+        let x = vec![0.0f32; 44_100];
+        assert_eq!(resample(&x, 44_100, 48_000).len(), 48_000);
+        assert_eq!(resample(&x, 44_100, 22_050).len(), 22_050);
+        assert_eq!(resample(&x[..1001], 48_000, 44_100).len(), 920); // ceil(919.69)
+    }
+
+    #[test]
+    fn passband_tone_keeps_level_and_phase() {
+        // A 1 kHz tone converted 48k → 44.1k must match a tone generated
+        // natively at 44.1k (away from the edges): no gain, no delay.
+        let out = resample(&sine(1000.0, 48_000, 0.5), 48_000, 44_100);
+        let reference = sine(1000.0, 44_100, 0.5);
+        let mid = 2000..out.len().min(reference.len()) - 2000;
+        let err = out[mid.clone()]
+            .iter()
+            .zip(&reference[mid])
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0f32, f32::max);
+        assert!(err < 1e-3, "max deviation from the native tone: {err}");
+    }
+
+    #[test]
+    fn downsampling_rejects_content_above_the_new_nyquist() {
+        // 30 kHz is legal at 96k but would alias to 14.1 kHz at 44.1k.
+        let out = resample(&sine(30_000.0, 96_000, 0.5), 96_000, 44_100);
+        let level = rms(&out[2000..out.len() - 2000]);
+        assert!(level < 1e-3, "aliased energy leaked through: rms {level}");
+    }
+
+    #[test]
+    fn chunked_stream_matches_whole_buffer() {
+        let x = sine(3000.0, 48_000, 0.3);
+        let whole = resample(&x, 48_000, 44_100);
+        let mut r = Resampler::new(48_000, 44_100);
+        let mut streamed = Vec::new();
+        for chunk in x.chunks(128) {
+            r.process(chunk, &mut streamed);
+        }
+        r.flush(&mut streamed);
+        assert_eq!(streamed, whole);
+        let before = streamed.len();
+        r.flush(&mut streamed);
+        assert_eq!(streamed.len(), before, "flush must be idempotent");
+    }
+
+    #[test]
+    fn odd_rate_pairs_use_the_phase_cap() {
+        let r = Resampler::new(44_100, 44_101);
+        assert_eq!(r.phases, MAX_PHASES);
+        let out = resample(&sine(1000.0, 44_100, 0.2), 44_100, 44_101);
+        assert!((rms(&out[1000..out.len() - 1000]) - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-2);
+    }
+}
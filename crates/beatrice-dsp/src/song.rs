@@ -0,0 +1,306 @@
+//! From detected events to a song: the quantize, arrange and MIDI export
+//! requests the frontend makes.
+//!
+//! The native `quantize_events_command`, `arrange_events_command` and
+//! `export_midi_command` deserialize these inputs and run their methods; the
+//! WASM build takes the same JSON
+//! ([`quantize_events_json`](crate::quantize_events_json) and friends). Both
+//! go through the code here, so the browser demo quantizes, arranges and
+//! exports exactly as the desktop app does. Names the frontend sends as
+//! strings (time signature, division, feel, template, theme) are parsed
+//! leniently, falling back to the defaults rather than failing.
+
+use serde::Deserialize;
+
+use crate::arranger::{self, Arrangement, ArrangementTemplate, HarmonyMode, MidiExportOptions};
+use crate::events::{Event, EventData};
+use crate::groove::{self, Grid, GridDivision, GrooveFeel, QuantizeSettings, QuantizedEvent, TimeSignature};
+use crate::themes::{self, Theme};
+
+/// Theme an unknown theme name falls back to.
+pub const FALLBACK_THEME: &str = "BLADE RUNNER";
+
+/// Build the grid a request describes, anchored to its phase offset.
+#[allow(clippy::too_many_arguments)]
+fn grid(
+    bpm: f64,
+    time_signature: &str,
+    division: &str,
+    feel: &str,
+    swing_amount: f32,
+    bar_count: u32,
+    phase_offset_ms: Option<f64>,
+) -> Grid {
+    Grid::with_phase(
+        bpm,
+        TimeSignature::from_string(time_signature),
+        GridDivision::from_string(division),
+        GrooveFeel::from_string(feel),
+        swing_amount,
+        bar_count,
+        phase_offset_ms.unwrap_or(0.0),
+    )
+}
+
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuantizeEventsInput {
+    pub events: Vec<EventData>,
+    pub bpm: f64,
+    pub time_signature: String,
+    pub division: String,
+    pub feel: String,
+    pub swing_amount: f32,
+    pub bar_count: u32,
+    pub quantize_strength: f32,
+    pub lookahead_ms: f64,
+    /// Grid phase offset (ms) from tempo estimation. Anchors the quantization grid
+    /// to the performer's downbeat. Defaults to 0.0 (t=0 anchor) for back-compat.
+    #[serde(default)]
+    pub phase_offset_ms: Option<f64>,
+    /// Quantize events rejected as noise too. Defaults to false: they stay in
+    /// the detected events but are left off the grid.
+    #[serde(default)]
+    pub include_rejected: bool,
+}
+
+impl QuantizeEventsInput {
+    /// The grid, anchored to the estimated beat phase so a leading silence /
+    /// anacrusis doesn't misquantize every downbeat.
+    pub fn grid(&self) -> Grid {
+        grid(
+            self.bpm,
+            &self.time_signature,
+            &self.division,
+            &self.feel,
+            self.swing_amount,
+            self.bar_count,
+            self.phase_offset_ms,
+        )
+    }
+
+    /// Quantize the events to the grid.
+    pub fn quantize(&self) -> Vec<QuantizedEvent> {
+        let settings = QuantizeSettings {
+            strength: self.quantize_strength,
+            swing_amount: self.swing_amount,
+            lookahead_ms: self.lookahead_ms,
+            include_rejected: self.include_rejected,
+        };
+        let events: Vec<Event> = self.events.iter().map(EventData::to_event).collect();
+        groove::quantize_events(&events, &self.grid(), &settings)
+    }
+}
+
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArrangeEventsInput {
+    pub events: Vec<QuantizedEvent>,
+    pub template: String,
+    pub theme_name: String,
+    pub bpm: f64,
+    pub time_signature: String,
+    pub division: String,
+    pub feel: String,
+    pub swing_amount: f32,
+    pub bar_count: u32,
+    pub b_emphasis: f32,
+    /// Grid phase offset (ms) from tempo estimation. Anchors the arrangement grid
+    /// (chord boundaries, beat placement) to the performer's downbeat. Defaults to
+    /// 0.0 for back-compat.
+    #[serde(default)]
+    pub phase_offset_ms: Option<f64>,
+    /// Placement fidelity [0.0, 1.0] (spec §4.3). 1.0 "Follow me" plays every event
+    /// at its quantized position (templates only shape velocity); 0.0 "Produce for
+    /// me" snaps off-template hits to the nearest template slot. Never deletes
+    /// events. Defaults to 0.8 (the UI slider lands in Task 4).
+    #[serde(default = "default_fidelity")]
+    pub fidelity: f32,
+    /// Where the chords come from: the theme's fixed progression (default) or
+    /// `follow_hum`, which infers the key and per-bar chords from the hummed
+    /// pitch and explains them in `Arrangement::harmony`.
+    #[serde(default)]
+    pub harmony_mode: HarmonyMode,
+}
+
+/// Default placement fidelity when the frontend omits it (serde back-compat).
+fn default_fidelity() -> f32 {
+    0.8
+}
+
+impl ArrangeEventsInput {
+    /// The grid, anchored to the estimated beat phase so chord boundaries and
+    /// beat placement line up with the performer's downbeat.
+    pub fn grid(&self) -> Grid {
+        grid(
+            self.bpm,
+            &self.time_signature,
+            &self.division,
+            &self.feel,
+            self.swing_amount,
+            self.bar_count,
+            self.phase_offset_ms,
+        )
+    }
+
+    /// The named theme. Unknown names must NOT hard-error — a persisted Run may
+    /// reference a since-renamed/removed theme — so they fall back to
+    /// [`FALLBACK_THEME`], and the arranger snapshots the RESOLVED canonical
+    /// name into the Arrangement.
+    pub fn theme(&self) -> Theme {
+        themes::get_theme(&self.theme_name)
+            .unwrap_or_else(|| themes::get_theme(FALLBACK_THEME).expect("Theme must exist"))
+    }
+
+    /// Arrange the events into the base pattern, with harmonic context.
+    /// [`Arrangement::expand_to_song`] turns it into the full song.
+    pub fn arrange(&self) -> Arrangement {
+        arranger::arrange_events_with_harmony(
+            &self.events,
+            &ArrangementTemplate::from_string(&self.template),
+            &self.grid(),
+            &self.theme(),
+            self.b_emphasis,
+            self.fidelity,
+            self.harmony_mode,
+        )
+    }
+}
+
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportMidiInput {
+    pub arrangement: Arrangement,
+    pub bpm: f64,
+    pub time_signature: String,
+    pub division: String,
+    pub feel: String,
+    pub swing_amount: f32,
+    pub bar_count: u32,
+    pub ppq: Option<u16>,
+    pub include_tempo: Option<bool>,
+    pub include_time_signature: Option<bool>,
+    pub track_names: Option<bool>,
+    /// Grid phase offset (ms) from tempo estimation. Extends grid duration so a
+    /// phase-shifted arrangement's tail isn't truncated on export. Defaults to 0.0.
+    #[serde(default)]
+    pub phase_offset_ms: Option<f64>,
+}
+
+impl ExportMidiInput {
+    /// The grid, anchored to the estimated beat phase so the exported grid
+    /// duration covers the phase-shifted tail.
+    pub fn grid(&self) -> Grid {
+        grid(
+            self.bpm,
+            &self.time_signature,
+            &self.division,
+            &self.feel,
+            self.swing_amount,
+            self.bar_count,
+            self.phase_offset_ms,
+        )
+    }
+
+    /// Export options: the defaults, overridden by any option given.
+    pub fn options(&self) -> MidiExportOptions {
+        let defaults = MidiExportOptions::default();
+        MidiExportOptions {
+            ppq: self.ppq.unwrap_or(defaults.ppq),
+            include_tempo: self.include_tempo.unwrap_or(defaults.include_tempo),
+            include_time_signature: self.include_time_signature.unwrap_or(defaults.include_time_signature),
+            track_names: self.track_names.unwrap_or(defaults.track_names),
+        }
+    }
+
+    /// The arrangement as Standard MIDI File bytes.
+    pub fn export(&self) -> Result<Vec<u8>, String> {
+        arranger::export_midi(&self.arrangement, &self.grid(), &self.options())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{EventClass, EventFeatures};
+
+    /// Kick, hat, snare, hat on the eighths of a 120 BPM bar, pushed a
+    /// little late, as the frontend sends them.
+    fn event_data() -> Vec<EventData> {
+        [EventClass::BilabialPlosive, EventClass::HihatNoise, EventClass::Click, EventClass::HihatNoise]
+            .into_iter()
+            .cycle()
+            .take(16)
+            .enumerate()
+            .map(|(i, class)| {
+                let mut data = EventData::from(&Event::new(i as f64 * 250.0 + 12.0, 80.0, class, 0.9, EventFeatures::zero()));
+                data.id = format!("mock-event-{i}");
+                data
+            })
+            .collect()
+    }
+
+    fn grid_json() -> &'static str {
+        r#""bpm":120.0,"time_signature":"four_four","division":"sixteenth","feel":"straight","swing_amount":0.0,"bar_count":2"#
+    }
+
+    #[test]
+    fn runs_the_pipeline_from_frontend_json() {
+        let quantize: QuantizeEventsInput = serde_json::from_value(serde_json::json!({
+            "events": event_data(),
+            "bpm": 120.0, "time_signature": "four_four", "division": "sixteenth", "feel": "straight",
+            "swing_amount": 0.0, "bar_count": 2, "quantize_strength": 1.0, "lookahead_ms": 100.0,
+        }))
+        .unwrap();
+        let quantized = quantize.quantize();
+        assert_eq!(quantized.len(), 16);
+        assert!(quantized.iter().all(|q| q.quantized_timestamp_ms % 125.0 == 0.0));
+
+        let arrange: ArrangeEventsInput = serde_json::from_str(&format!(
+            r#"{{"events":{},"template":"synthwave_straight","theme_name":"stranger things","b_emphasis":0.5,{}}}"#,
+            serde_json::to_string(&quantized).unwrap(),
+            grid_json(),
+        ))
+        .unwrap();
+        assert_eq!(arrange.fidelity, 0.8);
+        let base = arrange.arrange();
+        assert_eq!(base.theme_name, "STRANGER THINGS");
+        let expected = arranger::arrange_events(
+            &quantized,
+            &ArrangementTemplate::SynthwaveStraight,
+            &arrange.grid(),
+            &themes::get_theme("STRANGER THINGS").unwrap(),
+            0.5,
+            0.8,
+        );
+        assert_eq!(serde_json::to_string(&base).unwrap(), serde_json::to_string(&expected).unwrap());
+
+        let song = base.expand_to_song();
+        let export: ExportMidiInput = serde_json::from_str(&format!(
+            r#"{{"arrangement":{},"ppq":960,"include_tempo":null,"include_time_signature":null,"track_names":false,{}}}"#,
+            serde_json::to_string(&song).unwrap(),
+            grid_json(),
+        ))
+        .unwrap();
+        let options = export.options();
+        assert_eq!((options.ppq, options.include_tempo, options.track_names), (960, true, false));
+        let smf = midly::Smf::parse(&export.export().unwrap()).unwrap().to_static();
+        assert_eq!(smf.header.timing, midly::Timing::Metrical(960.into()));
+    }
+
+    #[test]
+    fn unknown_names_fall_back_to_the_defaults() {
+        let g = grid(90.0, "seven_eight", "thirty_second", "shuffle", 0.0, 1, None);
+        assert_eq!(
+            (g.time_signature, g.division, g.feel, g.phase_offset_ms),
+            (TimeSignature::FourFour, GridDivision::Sixteenth, GrooveFeel::Straight, 0.0)
+        );
+        let arrange: ArrangeEventsInput = serde_json::from_str(&format!(
+            r#"{{"events":[],"template":"polka","theme_name":"Miami Vice","b_emphasis":0.5,{}}}"#,
+            grid_json(),
+        ))
+        .unwrap();
+        assert_eq!(arrange.theme().name, FALLBACK_THEME);
+        assert_eq!(arrange.arrange().template, ArrangementTemplate::SynthwaveStraight);
+    }
+}
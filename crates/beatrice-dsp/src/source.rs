@@ -0,0 +1,355 @@
+// Chunked audio sources
+//
+// `AudioData` holds a whole decoded recording in memory, which is fine for a
+// few takes but not for a 30-minute rehearsal (~600 MB of f32 stereo at
+// 48 kHz, before `to_mono` clones it). An `AudioSource` is the pull-based
+// alternative: it hands out interleaved frames a chunk at a time and can be
+// rewound, which is all [`crate::analyze_source_hybrid`]'s two passes need.
+//
+// Decoders (WAV via `hound`, compressed via symphonia) implement it in the
+// native crate; this module has the in-memory and resampling adapters plus
+// the helpers the chunked pipeline shares.
+
+use std::collections::VecDeque;
+use std::convert::Infallible;
+
+use crate::ingest::AudioData;
+use crate::resample::Resampler;
+
+/// A rewindable stream of interleaved f32 frames in [-1.0, 1.0].
+pub trait AudioSource {
+    type Error;
+
+    /// Sample rate in Hz.
+    fn sample_rate(&self) -> u32;
+
+    /// Interleaved channel count.
+    fn channels(&self) -> u16;
+
+    /// Total frames, when the container declares it up front (used only to
+    /// report progress).
+    fn total_frames(&self) -> Option<usize>;
+
+    /// Append up to `max_frames` interleaved frames to `buf`. Returns the
+    /// number of frames appended; `0` means end of stream.
+    fn read(&mut self, buf: &mut Vec<f32>, max_frames: usize) -> Result<usize, Self::Error>;
+
+    /// Restart from the first frame.
+    fn rewind(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<S: AudioSource + ?Sized> AudioSource for Box<S> {
+    type Error = S::Error;
+
+    fn sample_rate(&self) -> u32 {
+        (**self).sample_rate()
+    }
+
+    fn channels(&self) -> u16 {
+        (**self).channels()
+    }
+
+    fn total_frames(&self) -> Option<usize> {
+        (**self).total_frames()
+    }
+
+    fn read(&mut self, buf: &mut Vec<f32>, max_frames: usize) -> Result<usize, Self::Error> {
+        (**self).read(buf, max_frames)
+    }
+
+    fn rewind(&mut self) -> Result<(), Self::Error> {
+        (**self).rewind()
+    }
+}
+
+/// [`AudioSource`] over already-decoded audio (see [`AudioData::source`]).
+pub struct MemorySource<'a> {
+    audio: &'a AudioData,
+    /// Next frame to hand out.
+    frame: usize,
+}
+
+impl AudioData {
+    /// Read this audio through the chunked [`AudioSource`] interface.
+    pub fn source(&self) -> MemorySource<'_> {
+        MemorySource { audio: self, frame: 0 }
+    }
+}
+
+impl AudioSource for MemorySource<'_> {
+    type Error = Infallible;
+
+    fn sample_rate(&self) -> u32 {
+        self.audio.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.audio.channels
+    }
+
+    fn total_frames(&self) -> Option<usize> {
+        Some(self.audio.frame_count)
+    }
+
+    fn read(&mut self, buf: &mut Vec<f32>, max_frames: usize) -> Result<usize, Infallible> {
+        let frames = max_frames.min(self.audio.frame_count.saturating_sub(self.frame));
+        let channels = self.audio.channels as usize;
+        let start = self.frame * channels;
+        buf.extend_from_slice(&self.audio.samples[start..start + frames * channels]);
+        self.frame += frames;
+        Ok(frames)
+    }
+
+    fn rewind(&mut self) -> Result<(), Infallible> {
+        self.frame = 0;
+        Ok(())
+    }
+}
+
+/// Band-limited rate conversion of another source, channel by channel — the
+/// streaming counterpart of [`AudioData::resampled`]. Equal rates pass
+/// straight through.
+pub struct ResampledSource<S> {
+    inner: S,
+    to_rate: u32,
+    /// One per channel; empty when passing through.
+    resamplers: Vec<Resampler>,
+    chunk: Vec<f32>,
+    channel: Vec<f32>,
+    converted: Vec<Vec<f32>>,
+    /// Converted interleaved samples not yet handed out.
+    pending: Vec<f32>,
+    ended: bool,
+}
+
+impl<S: AudioSource> ResampledSource<S> {
+    pub fn new(inner: S, to_rate: u32) -> Self {
+        let mut source = ResampledSource {
+            inner,
+            to_rate,
+            resamplers: Vec::new(),
+            chunk: Vec::new(),
+            channel: Vec::new(),
+            converted: Vec::new(),
+            pending: Vec::new(),
+            ended: false,
+        };
+        source.reset();
+        source
+    }
+
+    fn reset(&mut self) {
+        let from_rate = self.inner.sample_rate();
+        let channels = self.inner.channels().max(1) as usize;
+        self.resamplers = if from_rate == self.to_rate {
+            Vec::new()
+        } else {
+            (0..channels).map(|_| Resampler::new(from_rate, self.to_rate)).collect()
+        };
+        self.converted = vec![Vec::new(); self.resamplers.len()];
+        self.pending.clear();
+        self.ended = false;
+    }
+}
+
+impl<S: AudioSource> AudioSource for ResampledSource<S> {
+    type Error = S::Error;
+
+    fn sample_rate(&self) -> u32 {
+        self.to_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn total_frames(&self) -> Option<usize> {
+        let from_rate = self.inner.sample_rate() as u64;
+        self.inner.total_frames().map(|n| {
+            if self.resamplers.is_empty() {
+                n
+            } else {
+                (n as u64 * self.to_rate as u64).div_ceil(from_rate) as usize
+            }
+        })
+    }
+
+    fn read(&mut self, buf: &mut Vec<f32>, max_frames: usize) -> Result<usize, S::Error> {
+        if self.resamplers.is_empty() {
+            return self.inner.read(buf, max_frames);
+        }
+
+        let channels = self.resamplers.len();
+        while self.pending.len() < max_frames * channels && !self.ended {
+            self.chunk.clear();
+            let frames = self.inner.read(&mut self.chunk, max_frames)?;
+            for (ch, resampler) in self.resamplers.iter_mut().enumerate() {
+                let out = &mut self.converted[ch];
+                out.clear();
+                if frames == 0 {
+                    resampler.flush(out);
+                } else {
+                    self.channel.clear();
+                    self.channel.extend(self.chunk.iter().skip(ch).step_by(channels));
+                    resampler.process(&self.channel, out);
+                }
+            }
+            self.ended = frames == 0;
+
+            let converted_frames = self.converted[0].len();
+            for frame_idx in 0..converted_frames {
+                for channel in &self.converted {
+                    self.pending.push(channel[frame_idx]);
+                }
+            }
+        }
+
+        let take = (max_frames * channels).min(self.pending.len());
+        buf.extend(self.pending.drain(..take));
+        Ok(take / channels)
+    }
+
+    fn rewind(&mut self) -> Result<(), S::Error> {
+        self.inner.rewind()?;
+        self.reset();
+        Ok(())
+    }
+}
+
+/// Collects windows `[starts[i], starts[i] + lens[i])` out of a mono stream
+/// pushed in chunks, holding only the windows that are still open.
+///
+/// `starts` must be ascending. Windows are handed to the callback with their
+/// index in `starts` order (a short window waits for a longer one before it);
+/// a window running past the end of the stream is clamped there (like slicing
+/// `mono[start..(start + len).min(mono.len())]`), and one starting past it is
+/// empty.
+pub(crate) struct WindowCollector {
+    starts: Vec<usize>,
+    lens: Vec<usize>,
+    /// Next window to open.
+    next: usize,
+    open: VecDeque<(usize, Vec<f32>)>,
+    /// Absolute index of the next pushed sample.
+    pos: usize,
+}
+
+impl WindowCollector {
+    pub(crate) fn new(starts: Vec<usize>, lens: Vec<usize>) -> Self {
+        debug_assert_eq!(starts.len(), lens.len());
+        WindowCollector { starts, lens, next: 0, open: VecDeque::new(), pos: 0 }
+    }
+
+    pub(crate) fn push(&mut self, chunk: &[f32], done: &mut impl FnMut(usize, &[f32])) {
+        let end = self.pos + chunk.len();
+        while self.next < self.starts.len() && self.starts[self.next] < end {
+            self.open.push_back((self.next, Vec::with_capacity(self.lens[self.next])));
+            self.next += 1;
+        }
+
+        for (index, window) in self.open.iter_mut() {
+            let start = self.starts[*index];
+            let from = (start + window.len()).max(self.pos);
+            let to = (start + self.lens[*index]).min(end);
+            if from < to {
+                window.extend_from_slice(&chunk[from - self.pos..to - self.pos]);
+            }
+        }
+        self.pos = end;
+
+        while self.open.front().is_some_and(|(i, w)| w.len() == self.lens[*i]) {
+            let (index, window) = self.open.pop_front().expect("front checked above");
+            done(index, &window);
+        }
+    }
+
+    /// End of stream: hand out whatever is still open, then the windows that
+    /// start past the end.
+    pub(crate) fn finish(mut self, done: &mut impl FnMut(usize, &[f32])) {
+        for (index, window) in self.open.drain(..) {
+            done(index, &window);
+        }
+        for index in self.next..self.starts.len() {
+            done(index, &[]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stereo_ramp(frames: usize, sample_rate: u32) -> AudioData {
+        let samples: Vec<f32> = (0..frames * 2)
+            .map(|i| if i % 2 == 0 { i as f32 / (frames * 2) as f32 } else { -0.5 })
+            .collect();
+        AudioData {
+            samples,
+            sample_rate,
+            channels: 2,
+            bit_depth: 32,
+            duration_ms: (frames as f64 / sample_rate as f64 * 1000.0) as i64,
+            frame_count: frames,
+        }
+    }
+
+    fn read_all<S: AudioSource<Error = Infallible>>(source: &mut S, chunk_frames: usize) -> Vec<f32> {
+        let mut out = Vec::new();
+        while source.read(&mut out, chunk_frames).unwrap() > 0 {}
+        out
+    }
+
+    #[test]
+    fn memory_source_reads_and_rewinds() {
+        let audio = stereo_ramp(1000, 44_100);
+        let mut source = audio.source();
+        assert_eq!(read_all(&mut source, 96), audio.samples);
+        source.rewind().unwrap();
+        assert_eq!(read_all(&mut source, 1000), audio.samples);
+    }
+
+    #[test]
+    fn resampled_source_matches_whole_buffer_conversion() {
+        let audio = stereo_ramp(4800, 48_000);
+        let expected = audio.resampled(44_100);
+
+        let mut source = ResampledSource::new(audio.source(), 44_100);
+        assert_eq!(source.total_frames(), Some(expected.frame_count));
+        let got = read_all(&mut source, 333);
+        assert_eq!(got.len(), expected.samples.len());
+        for (a, b) in got.iter().zip(&expected.samples) {
+            assert!((a - b).abs() < 1e-5, "{a} vs {b}");
+        }
+
+        source.rewind().unwrap();
+        assert_eq!(read_all(&mut source, 4096), got);
+    }
+
+    #[test]
+    fn window_collector_matches_slicing() {
+        let mono: Vec<f32> = (0..1000).map(|i| i as f32).collect();
+        let starts = vec![0, 10, 15, 500, 990, 1000, 1200];
+        let lens = vec![30, 600, 30, 30, 30, 30, 30];
+        let expected: Vec<Vec<f32>> = starts
+            .iter()
+            .zip(&lens)
+            .map(|(&s, &len)| mono[s.min(mono.len())..(s + len).min(mono.len())].to_vec())
+            .collect();
+
+        for chunk_len in [1, 7, 64, 1000] {
+            let mut collector = WindowCollector::new(starts.clone(), lens.clone());
+            let mut got = Vec::new();
+            let mut done = |index: usize, window: &[f32]| got.push((index, window.to_vec()));
+            for chunk in mono.chunks(chunk_len) {
+                collector.push(chunk, &mut done);
+            }
+            collector.finish(&mut done);
+
+            let indices: Vec<usize> = got.iter().map(|(i, _)| *i).collect();
+            assert_eq!(indices, (0..starts.len()).collect::<Vec<_>>(), "chunk {chunk_len}");
+            for ((_, window), want) in got.iter().zip(&expected) {
+                assert_eq!(window, want, "chunk {chunk_len}");
+            }
+        }
+    }
+}
@@ -0,0 +1,319 @@
+//! Shared STFT analysis.
+//!
+//! Onset detection, the scalar features and the MFCCs all look at Hann-windowed
+//! magnitude spectra. They used to build their own: a fresh `realfft` planner
+//! per frame, the window formula re-evaluated per sample, the mel filter
+//! weights recomputed per frame. They now share per-thread [`FftPlans`] that
+//! keep each size's plan, window and buffers, so a frame's spectrum costs no
+//! allocation. [`Spectrogram`] stores the frames of a whole signal — any size
+//! and hop, with the centroid, band-energy and mel readouts the features
+//! need — for callers that read them more than once.
+//!
+//! Onset detection and the per-onset features do not share frames: a feature
+//! window starts at the localized onset, off the detection hop grid, so
+//! reusing the detector's frames would move every feature. Within a window,
+//! the offline path ([`crate::extract_window`]) reads the centroid and band
+//! energies off one [`Spectrogram`] frame and the mel frames off another
+//! framing of the same samples — two sizes, fixed by what the heuristic and
+//! the factory model were tuned on. What is computed once is the signal (see
+//! [`crate::AnalysisContext`]), the plans, the windows and the filterbank.
+
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use realfft::num_complex::Complex;
+use realfft::{RealFftPlanner, RealToComplex};
+
+use crate::features::{
+    calculate_band_energies, calculate_spectral_centroid, MelFilterbank, FEATURE_FRAME, MFCC_FRAME, MFCC_MEL_FILTERS,
+};
+
+/// Unpinned frame sizes kept planned at once; past this the least recently
+/// used is dropped. Short windows (a feature window under
+/// [`FEATURE_FRAME`](crate::features::FEATURE_FRAME), the tail of a stream)
+/// make one-off sizes, which must not pile up on a long-lived thread.
+const SPARE_SIZES: usize = 4;
+
+/// `realfft` plans, Hann windows and buffers, cached by frame size.
+///
+/// The crate's fixed frame sizes — the feature and MFCC frames, and any size
+/// a caller [`prepare`](Self::prepare)s (the streaming detector's) — stay
+/// planned for the thread's life; at most [`SPARE_SIZES`] others are kept.
+#[derive(Default)]
+pub struct FftPlans {
+    sizes: Vec<SizedPlan>,
+    /// Bumped per lookup, for least-recently-used eviction.
+    clock: u64,
+}
+
+struct SizedPlan {
+    size: usize,
+    pinned: bool,
+    last_used: u64,
+    fft: Arc<dyn RealToComplex<f32>>,
+    hann: Vec<f32>,
+    input: Vec<f32>,
+    output: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+    magnitudes: Vec<f32>,
+}
+
+thread_local! {
+    static PLANS: RefCell<FftPlans> = RefCell::new(FftPlans::default());
+}
+
+/// Run `f` with this thread's [`FftPlans`]. Not re-entrant: `f` must not
+/// call back into a function that uses the plans.
+pub fn with_plans<R>(f: impl FnOnce(&mut FftPlans) -> R) -> R {
+    PLANS.with(|plans| f(&mut plans.borrow_mut()))
+}
+
+impl FftPlans {
+    fn plan(&mut self, size: usize) -> &mut SizedPlan {
+        self.clock += 1;
+        let k = match self.sizes.iter().position(|p| p.size == size) {
+            Some(k) => k,
+            None => {
+                let spares = self.sizes.iter().filter(|p| !p.pinned).count();
+                if spares >= SPARE_SIZES {
+                    let oldest = (0..self.sizes.len())
+                        .filter(|&k| !self.sizes[k].pinned)
+                        .min_by_key(|&k| self.sizes[k].last_used)
+                        .expect("spare sizes are planned");
+                    self.sizes.swap_remove(oldest);
+                }
+                // A planner per size: its own cache would keep every plan.
+                let fft = RealFftPlanner::<f32>::new().plan_fft_forward(size);
+                self.sizes.push(SizedPlan {
+                    size,
+                    pinned: size == FEATURE_FRAME || size == MFCC_FRAME,
+                    last_used: 0,
+                    input: fft.make_input_vec(),
+                    output: fft.make_output_vec(),
+                    scratch: fft.make_scratch_vec(),
+                    magnitudes: vec![0.0; size / 2 + 1],
+                    hann: hann_window(size),
+                    fft,
+                });
+                self.sizes.len() - 1
+            }
+        };
+        let plan = &mut self.sizes[k];
+        plan.last_used = self.clock;
+        plan
+    }
+
+    /// Spectrum of a frame that is already windowed (any length).
+    pub fn spectrum(&mut self, windowed: &[f32]) -> &[Complex<f32>] {
+        let plan = self.plan(windowed.len());
+        plan.input.copy_from_slice(windowed);
+        plan.fft
+            .process_with_scratch(&mut plan.input, &mut plan.output, &mut plan.scratch)
+            .expect("buffers sized by the planner");
+        &plan.output
+    }
+
+    /// Spectrum of `frame` zero-padded (or truncated) to `size` samples and
+    /// Hann-windowed over all `size` of them.
+    pub fn windowed_spectrum(&mut self, frame: &[f32], size: usize) -> &[Complex<f32>] {
+        &Self::transform(self.plan(size), frame).output
+    }
+
+    /// [`windowed_spectrum`](Self::windowed_spectrum) and its magnitudes.
+    pub fn windowed(&mut self, frame: &[f32], size: usize) -> (&[Complex<f32>], &[f32]) {
+        let plan = Self::transform(self.plan(size), frame);
+        for (m, c) in plan.magnitudes.iter_mut().zip(&plan.output) {
+            *m = c.norm();
+        }
+        (&plan.output, &plan.magnitudes)
+    }
+
+    /// Plan (and allocate the buffers for) `size`-sample frames now and keep
+    /// them, so no frame of that size allocates.
+    pub fn prepare(&mut self, size: usize) {
+        self.plan(size).pinned = true;
+    }
+
+    fn transform<'a>(plan: &'a mut SizedPlan, frame: &[f32]) -> &'a mut SizedPlan {
+        let size = plan.input.len();
+        let len = frame.len().min(size);
+        plan.input[..len].copy_from_slice(&frame[..len]);
+        plan.input[len..].fill(0.0);
+        for (s, w) in plan.input.iter_mut().zip(&plan.hann) {
+            *s *= w;
+        }
+        plan.fft
+            .process_with_scratch(&mut plan.input, &mut plan.output, &mut plan.scratch)
+            .expect("buffers sized by the planner");
+        plan
+    }
+}
+
+/// The Hann taper [`crate::features::apply_hann_window`] applies, as a table.
+fn hann_window(n: usize) -> Vec<f32> {
+    (0..n)
+        .map(|i| 0.5 * (1.0 - (2.0 * std::f32::consts::PI * i as f32 / n as f32).cos()))
+        .collect()
+}
+
+/// Hann-windowed STFT frames of a mono signal: complex bins and their
+/// magnitudes, `frame_size / 2 + 1` per frame.
+#[derive(Debug, Clone)]
+pub struct Spectrogram {
+    sample_rate: u32,
+    frame_size: usize,
+    hop_size: usize,
+    bins: usize,
+    spectra: Vec<Complex<f32>>,
+    magnitudes: Vec<f32>,
+}
+
+impl Spectrogram {
+    /// Frames of `frame_size` samples every `hop_size`, from the start of
+    /// `signal`, that fit wholly inside it (the onset detectors' framing).
+    pub fn new(signal: &[f32], sample_rate: u32, frame_size: usize, hop_size: usize) -> Self {
+        let count = if frame_size == 0 || signal.len() < frame_size {
+            0
+        } else {
+            (signal.len() - frame_size) / hop_size.max(1) + 1
+        };
+        Self::with_frames(signal, sample_rate, frame_size, hop_size, count)
+    }
+
+    /// Like [`new`](Self::new), but frames continue until one reaches the end
+    /// of `signal`, the last zero-padded (the MFCC framing: a short signal
+    /// still gets one frame).
+    pub fn covering(signal: &[f32], sample_rate: u32, frame_size: usize, hop_size: usize) -> Self {
+        let count = if frame_size == 0 || signal.is_empty() {
+            0
+        } else {
+            signal.len().saturating_sub(frame_size).div_ceil(hop_size.max(1)) + 1
+        };
+        Self::with_frames(signal, sample_rate, frame_size, hop_size, count)
+    }
+
+    fn with_frames(signal: &[f32], sample_rate: u32, frame_size: usize, hop_size: usize, count: usize) -> Self {
+        let bins = frame_size / 2 + 1;
+        let mut spectra = Vec::with_capacity(count * bins);
+        with_plans(|plans| {
+            for k in 0..count {
+                let start = (k * hop_size).min(signal.len());
+                let end = (start + frame_size).min(signal.len());
+                spectra.extend_from_slice(plans.windowed_spectrum(&signal[start..end], frame_size));
+            }
+        });
+        let magnitudes = spectra.iter().map(|c| c.norm()).collect();
+        Spectrogram { sample_rate, frame_size, hop_size, bins, spectra, magnitudes }
+    }
+
+    /// Number of frames.
+    pub fn len(&self) -> usize {
+        // `bins` is at least one (the DC bin).
+        self.magnitudes.len() / self.bins
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn frame_size(&self) -> usize {
+        self.frame_size
+    }
+
+    pub fn hop_size(&self) -> usize {
+        self.hop_size
+    }
+
+    /// Complex bins of frame `i`.
+    pub fn spectrum(&self, i: usize) -> &[Complex<f32>] {
+        &self.spectra[i * self.bins..(i + 1) * self.bins]
+    }
+
+    /// Magnitudes of frame `i`.
+    pub fn magnitudes(&self, i: usize) -> &[f32] {
+        &self.magnitudes[i * self.bins..(i + 1) * self.bins]
+    }
+
+    /// Energy-weighted spectral centroid of frame `i`, in Hz.
+    pub fn centroid(&self, i: usize) -> f32 {
+        calculate_spectral_centroid(self.magnitudes(i), self.sample_rate, self.frame_size)
+    }
+
+    /// Share of frame `i`'s energy below 500 Hz, to 4 kHz and above.
+    pub fn band_energies(&self, i: usize) -> [f32; 3] {
+        calculate_band_energies(self.magnitudes(i), self.sample_rate, self.frame_size)
+    }
+
+    /// Frame `i`'s power pooled through `bank`'s mel filters.
+    pub fn mel_energies(&self, i: usize, bank: &MelFilterbank) -> [f32; MFCC_MEL_FILTERS] {
+        bank.apply(self.magnitudes(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::{apply_hann_window, compute_fft};
+
+    fn tone(n: usize) -> Vec<f32> {
+        (0..n).map(|i| (i as f32 * 0.07).sin() * 0.5 + (i as f32 * 0.61).sin() * 0.2).collect()
+    }
+
+    #[test]
+    fn frames_match_a_fresh_fft() {
+        let signal = tone(5000);
+        let spec = Spectrogram::new(&signal, 44_100, 1024, 512);
+        assert_eq!(spec.len(), (5000 - 1024) / 512 + 1);
+        for i in [0, 3, spec.len() - 1] {
+            let mut frame = signal[i * 512..i * 512 + 1024].to_vec();
+            apply_hann_window(&mut frame);
+            // Bit-identical to the per-frame path, so frozen outputs hold.
+            assert_eq!(spec.magnitudes(i), compute_fft(&frame).as_slice());
+        }
+    }
+
+    #[test]
+    fn covering_pads_the_last_frame() {
+        let signal = tone(1500);
+        let spec = Spectrogram::covering(&signal, 44_100, 1024, 512);
+        assert_eq!(spec.len(), 2);
+        let mut last = vec![0.0; 1024];
+        last[..1500 - 512].copy_from_slice(&signal[512..]);
+        apply_hann_window(&mut last);
+        assert_eq!(spec.magnitudes(1), compute_fft(&last).as_slice());
+        // A signal shorter than a frame still gets one.
+        assert_eq!(Spectrogram::covering(&signal[..100], 44_100, 1024, 512).len(), 1);
+        assert!(Spectrogram::covering(&[], 44_100, 1024, 512).is_empty());
+        assert!(Spectrogram::new(&signal[..100], 44_100, 1024, 512).is_empty());
+    }
+
+    #[test]
+    fn one_off_sizes_do_not_pile_up() {
+        let mut plans = FftPlans::default();
+        plans.prepare(441);
+        plans.windowed(&tone(MFCC_FRAME), MFCC_FRAME);
+        for size in 100..200 {
+            plans.windowed(&tone(size), size);
+        }
+        let mut sizes: Vec<usize> = plans.sizes.iter().map(|p| p.size).collect();
+        sizes.sort_unstable();
+        assert_eq!(sizes, [196, 197, 198, 199, 441, MFCC_FRAME]);
+        // An evicted size plans again, bit-identically.
+        let mut frame = tone(150);
+        let again = plans.windowed(&frame, 150).1.to_vec();
+        apply_hann_window(&mut frame);
+        assert_eq!(again, compute_fft(&frame));
+    }
+
+    #[test]
+    fn readouts_follow_the_spectrum() {
+        // 100 Hz vs 8 kHz sines.
+        let sine = |hz: f32| -> Vec<f32> {
+            (0..2048).map(|i| (2.0 * std::f32::consts::PI * hz * i as f32 / 44_100.0).sin()).collect()
+        };
+        let low = Spectrogram::new(&sine(100.0), 44_100, 2048, 2048);
+        let high = Spectrogram::new(&sine(8000.0), 44_100, 2048, 2048);
+        assert!(low.centroid(0) < 200.0 && high.centroid(0) > 7000.0);
+        assert!(low.band_energies(0)[0] > 0.9 && high.band_energies(0)[2] > 0.9);
+    }
+}
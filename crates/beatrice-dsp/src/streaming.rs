@@ -18,37 +18,133 @@
 //! | Adaptive threshold    | mean + 2σ over the WHOLE file   | mean + 2σ over a rolling 2s flux window   |
 //! | Peak picking          | flux[i-1] < flux[i] > flux[i+1] over all frames | same, but confirmed 1 hop late (~6ms)     |
 //! | Classification window | hybrid: fixed [onset, onset+150ms]; heuristic: onset → next onset, clamp 50-500ms | fixed [onset, onset+`feature_window_ms`], deferred |
+//! | Detection function    | `OnsetConfig::onset_function`   | same function, same per-frame math        |
 //! | Kick fallback         | parallel RMS-envelope detector  | causal energy-rise detector               |
 //! | Leading onset (t≈0)   | first-10ms RMS gate             | same                                       |
 //!
 //! # The onset-timing convention (READ THIS before touching alignment)
 //!
-//! Both detectors' spectral flux peaks when the transient sits near the CENTER
-//! of the analysis window, but both *report* the window's LEFT EDGE
-//! (`frame_index * hop`). So the reported time is systematically early by
-//! ~`window/2`. Measured on the fixtures, offline reports a transient at true
-//! time `S` as `S − 24ms` (its `window/2` = 23.2ms plus ~1ms of flux group
-//! delay). Offline is the source of truth (spec §5.1), so to land inside the
-//! ±20ms tolerance band the streaming detector — whose own window is only
-//! 512 (`window/2` ≈ 5.8ms) — must report using OFFLINE's convention, i.e. it
-//! estimates the true onset from its own window centre and then subtracts
-//! [`StreamingConfig::onset_align_ms`] to match offline's early-reporting bias.
-//! Without this, streaming would be ~18-24ms LATE relative to offline and fail
-//! the tolerance test. This offset is calibrated against the fixtures, not
-//! guessed; see `tests/streaming_tolerance.rs`.
+//! A detection-function peak only locates a transient to within its analysis
+//! window — 46ms offline, 12ms here — and the two windows differ, so the raw
+//! frame times of the two detectors disagree (offline's left edge lands ~24ms
+//! before the sound). Both therefore refine every peak with the same
+//! time-domain step ([`crate::localize`]): over the peak frame plus the hop
+//! before it, the first ~1ms block of a held peak envelope that climbs a
+//! quarter of the way up the steepest rise is the onset. Envelope blocks are aligned to the stream
+//! (absolute multiples of the block length) in both, so on the same audio they
+//! report the same time — the real start of the sound, with no per-class
+//! compensating offset. `tests/streaming_tolerance.rs` holds them together.
 //!
 //! # Latency
 //!
 //! `push` is causal. A confirmed onset is emitted after the classification
 //! window fills: ~`feature_window_ms` after the true onset (plus one hop for
 //! peak confirmation). `t_ms` on the returned [`LiveEvent`] is the onset's
-//! *estimated* time (offline-aligned), NOT the emission time.
+//! localized time (as offline reports it), NOT the emission time.
+//!
+//! # Provisional events
+//!
+//! The classification window is the latency floor (docs/latency.md). With
+//! [`StreamingConfig::provisional_window_ms`] set, each onset is also
+//! classified from just its first milliseconds as soon as they arrive (as
+//! early as peak confirmation) and emitted as a [`LiveStage::Provisional`]
+//! event. When the full window fills, the onset is
+//! classified again as usual; only if that verdict differs is a
+//! [`LiveStage::Revised`] event emitted, with the same `t_ms` as the
+//! provisional one it corrects. A consumer that applies revisions by `t_ms`
+//! ends up with exactly the events the detector reports without provisional
+//! emission. `tests/streaming_tolerance.rs` measures how often the
+//! provisional class changes on the fixture corpus.
+//!
+//! # Sample rate
+//!
+//! By default the detector analyses at the input rate. With
+//! [`StreamingConfig::analysis_sample_rate`] set (see
+//! [`StreamingDetector::at_model_rate`]) every pushed chunk first runs through
+//! a [`Resampler`], so a 48 kHz AudioContext feeds the classifier the same
+//! 44.1 kHz statistics the factory model was fitted on. The resampler is
+//! zero-phase, so `t_ms` is unaffected; it only adds ~0.4ms of lookahead to
+//! emission.
+//!
+//! # Conditioning
+//!
+//! [`StreamingConfig::conditioning`] runs the same DC/high-pass/denoise/gain
+//! chain as the offline pipeline's `OnsetConfig::conditioning`, after
+//! resampling. The denoiser's STFT adds
+//! [`Conditioner::latency`](crate::Conditioner::latency) samples (~17ms) of
+//! emission delay but no timing shift. Normalization cannot see the future
+//! here, so it glides toward the level measured so far rather than applying
+//! one whole-take gain.
+//!
+//! # Tempo
+//!
+//! Every emitted event also feeds a causal [`BeatTracker`], weighted by how
+//! strongly its class marks the beat ([`beat_accent`]: kicks and snares over
+//! hats, rejected noise not at all). [`StreamingDetector::tempo`] reads the
+//! current BPM, beat phase and confidence at the stream head, so the jam UI
+//! can lock a metronome and snap events to the live grid
+//! ([`LiveTempo::snap_ms`]) as they arrive. The tracker sees onsets at their
+//! `t_ms`, so the beat grid is in the same clock as the events.
+//!
+//! # Snapshots
+//!
+//! Everything the detector has learned from the stream so far — the
+//! resampler and conditioner state, the unread tail of the sample ring, the
+//! rolling flux and energy statistics, pending onsets, the beat tracker and
+//! the calibration profile — can be captured with
+//! [`StreamingDetector::snapshot`] and rebuilt with
+//! [`StreamingDetector::restore`], so a jam survives a pause, a move to
+//! another worklet or an AudioContext restart. Only the ring samples a later
+//! read can still reach are stored, and floats round-trip bit-exactly: a
+//! stream split at any sample and resumed from a snapshot yields exactly the
+//! events of an uninterrupted run (`tests/streaming_snapshot.rs`).
+//!
+//! # Real-time safety
+//!
+//! [`StreamingDetector::push_into`] runs on the audio render thread and does
+//! not allocate once the detector is built: the sample ring, flux and energy
+//! histories, pending queue, frame and window scratch and localization
+//! envelopes are all sized up front, the FFT plans for every frame size it
+//! uses are prepared in the constructor, and event MFCCs are fixed-size
+//! arrays, and the beat tracker's onset ring and IOI histogram are fixed-size.
+//! Events are appended to a caller-owned buffer. The guarantee holds
+//! for chunks up to [`PREALLOCATED_CHUNK`] samples with the hybrid, Gaussian
+//! and heuristic classifiers; the kNN classifier allocates per vote, and
+//! [`flush`](StreamingDetector::flush) may allocate (it is not real-time).
+//! The FFT plans are per thread, so build the detector on the thread that
+//! pushes to it. `tests/streaming_alloc.rs` counts allocations over a long
+//! stream to hold this.
 
 use std::collections::VecDeque;
 
+use serde::{Deserialize, Serialize};
+
+use crate::conditioning::{Conditioner, ConditionerState, ConditioningConfig};
 use crate::events::types::{EventClass, EventFeatures};
-use crate::events::{CalibrationProfile, CalibrationSample, HybridClassifier};
-use crate::features::{apply_hann_window, compute_fft, extract_features, extract_mfcc};
+use crate::events::{
+    build_classifier, CalibrationProfile, CalibrationSample, ClassifierInput, EventClassifier,
+    DEFAULT_CLASSIFIER,
+};
+use crate::features::{
+    extract_features, extract_mfcc_with, MelFilterbank, FEATURE_FRAME, MFCC_COEFFS, MFCC_FRAME,
+};
+use crate::groove::beat_tracker::BeatTrackerState;
+use crate::groove::{beat_accent, BeatTracker, LiveTempo};
+use crate::localize::{envelope_block, Localizer, HOLD_BLOCKS};
+use crate::noise::NoiseProfile;
+use crate::onset_function::{OnsetDetectionFunction, OnsetFunction, OnsetFunctionState};
+use crate::resample::{Resampler, ResamplerState};
+use crate::spectrogram;
+
+/// Provisional classification window (ms) the worklet uses: a third of the
+/// full window, 100ms earlier. Shorter windows misread kicks (they need
+/// ~40ms of the pitch sweep) and overturn most provisional classes on the
+/// fixture corpus; see `tests/streaming_tolerance.rs`.
+pub const PROVISIONAL_WINDOW_MS: f64 = 50.0;
+
+/// Input chunk length (samples) the detector's buffers are sized for. Render
+/// quanta are 128; longer chunks still work but may grow a buffer once.
+pub const PREALLOCATED_CHUNK: usize = 4096;
 
 /// A classified event emitted by the streaming detector.
 ///
@@ -59,10 +155,11 @@ use crate::features::{apply_hann_window, compute_fft, extract_features, extract_
 /// with [`crate::Event::timestamp_ms`] within tolerance.
 #[derive(Debug, Clone)]
 pub struct LiveEvent {
-    /// Estimated onset time in milliseconds from the start of the stream,
-    /// using offline's reporting convention (see module docs).
+    /// Onset time in milliseconds from the start of the stream, localized to
+    /// the transient the same way offline is (see module docs).
     pub t_ms: f64,
-    /// Classified event type.
+    /// Classified event type; [`EventClass::Noise`] for a rejected onset,
+    /// which is still reported so the UI can show it greyed out.
     pub class: EventClass,
     /// Classification confidence in `[0.0, 1.0]`.
     pub confidence: f32,
@@ -70,7 +167,24 @@ pub struct LiveEvent {
     pub features: EventFeatures,
     /// Mean MFCCs (c1..c20) of the classification window — forwarded so a
     /// calibration echo-back carries the full Gaussian feature vector.
-    pub mfcc: Vec<f32>,
+    pub mfcc: [f32; MFCC_COEFFS],
+    /// Where this event sits in two-stage emission (see module docs);
+    /// [`LiveStage::Final`] unless provisional emission is on.
+    pub stage: LiveStage,
+}
+
+/// Which verdict on an onset a [`LiveEvent`] carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LiveStage {
+    /// The only event for its onset, classified from the full window.
+    #[default]
+    Final,
+    /// An early guess from the first `provisional_window_ms` of the onset.
+    /// The onset is reported again only if the full window disagrees.
+    Provisional,
+    /// The full-window verdict on an onset whose provisional class it
+    /// replaces; same `t_ms` as that provisional event.
+    Revised,
 }
 
 /// Tunable parameters for [`StreamingDetector`].
@@ -79,7 +193,7 @@ pub struct LiveEvent {
 /// (512/256) and are calibrated against the fixture corpus so streaming matches
 /// offline within ±20ms / same-class on ≥95% of onsets. Change with care and
 /// re-run `cargo test -p beatrice-dsp streaming`.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamingConfig {
     /// STFT window size in samples. Offline uses 2048; streaming uses 512 for
     /// latency (spec §5.1).
@@ -96,16 +210,17 @@ pub struct StreamingConfig {
     /// [`crate::OnsetConfig::min_onset_gap_ms`].
     pub min_onset_gap_ms: f64,
     /// Absolute minimum flux for an onset candidate — gates out low-energy noise
-    /// between beats when the rolling mean/σ are both tiny.
+    /// between beats when the rolling mean/σ are both tiny. In the units of
+    /// `onset_function`.
     pub min_flux: f32,
+    /// Detection function the peaks are picked on. Mirrors
+    /// [`crate::OnsetConfig::onset_function`]; the energy-rise fallback only
+    /// runs for functions that [use it](OnsetFunction::uses_energy_fallback).
+    pub onset_function: OnsetFunction,
     /// Length of the (forward) classification window in milliseconds, measured
-    /// from the estimated true onset. Emission is deferred until this much
+    /// from the localized onset. Emission is deferred until this much
     /// post-onset audio is in the ring, so features are comparable to offline's.
     pub feature_window_ms: f64,
-    /// Offline-convention alignment offset in milliseconds (see module docs).
-    /// The estimated true onset time is reduced by this to match offline's
-    /// early left-edge reporting.
-    pub onset_align_ms: f64,
     /// RMS threshold on the first 10ms for a leading onset at t≈0. Mirrors
     /// [`crate::features`]'s `detect_leading_onset`.
     pub leading_rms_threshold: f32,
@@ -115,6 +230,22 @@ pub struct StreamingConfig {
     pub energy_rise_factor: f32,
     /// Absolute minimum RMS for the energy fallback to consider a frame.
     pub energy_min_rms: f32,
+    /// Resample the input to this rate (Hz) before analysis. `None` analyses
+    /// at the input rate; [`StreamingDetector::at_model_rate`] sets it to the
+    /// factory model's training rate.
+    pub analysis_sample_rate: Option<u32>,
+    /// Optional conditioning chain applied (at the analysis rate) before
+    /// detection. `None` analyses the input as it arrives.
+    pub conditioning: Option<ConditioningConfig>,
+    /// Room-tone floor the conditioning denoiser starts from (see
+    /// [`NoiseProfile::adapt_streaming_config`]). Ignored without noise
+    /// reduction.
+    pub noise_profile: Option<NoiseProfile>,
+    /// Also emit a provisional verdict classified from this many ms after
+    /// the onset (see the module docs), e.g. [`PROVISIONAL_WINDOW_MS`].
+    /// `None`, or a window no shorter than `feature_window_ms`, emits one
+    /// final event per onset.
+    pub provisional_window_ms: Option<f64>,
 }
 
 impl Default for StreamingConfig {
@@ -126,6 +257,7 @@ impl Default for StreamingConfig {
             stats_window_ms: 2000.0,
             min_onset_gap_ms: 120.0,
             min_flux: 0.5,
+            onset_function: OnsetFunction::SpectralFlux,
             // Matches the Gaussian factory model's training window
             // (HYBRID_MFCC_WINDOW_MS): the model learned 150ms timbre stats, and
             // classifying over a different window measurably costs accuracy
@@ -133,29 +265,32 @@ impl Default for StreamingConfig {
             // deferral is fine for the visual jam form (latency gate was NO-GO
             // for live synthesis anyway).
             feature_window_ms: crate::events::hybrid::HYBRID_MFCC_WINDOW_MS,
-            // Calibrated against the fixture corpus (see tests/streaming_tolerance.rs
-            // and the module docs). Offline's large 2048 window reports different
-            // per-class biases: it lands near the true centre for low-frequency
-            // kicks (wants align≈0) but ~17ms early for high-frequency hats (wants
-            // align≈17). 12ms is the compromise that keeps BOTH classes inside the
-            // ±20ms band — the sweep plateau [10,14]ms all yield 99% corpus match.
-            onset_align_ms: 12.0,
             leading_rms_threshold: 0.02,
             energy_rise_factor: 3.0,
             energy_min_rms: 0.03,
+            analysis_sample_rate: None,
+            conditioning: None,
+            noise_profile: None,
+            provisional_window_ms: None,
         }
     }
 }
 
 /// An onset whose peak is confirmed but whose classification window has not yet
 /// filled — held until `feature_window_ms` of post-onset audio arrives.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct PendingOnset {
-    /// Estimated true-onset absolute sample index (window centre of peak frame).
+    /// Localized onset absolute sample index.
     onset_abs: usize,
     /// Absolute sample index at which the classification window is full.
     ready_abs: usize,
-    /// Reported time in ms (offline-aligned), computed at confirmation time.
+    /// Reported time in ms, computed at confirmation time.
     t_ms: f64,
+    /// Absolute sample index at which the provisional window is full; `None`
+    /// when no provisional event is wanted.
+    provisional_abs: Option<usize>,
+    /// The class the provisional event reported, once emitted.
+    provisional: Option<EventClass>,
 }
 
 /// Causal onset + classification detector for the live worklet.
@@ -164,9 +299,21 @@ struct PendingOnset {
 /// [`LiveEvent`]s whose classification window completed during that call. See
 /// the module docs for the design and the timing convention.
 pub struct StreamingDetector {
+    /// Rate the detector analyses at (the resampler's output rate, if any).
     sample_rate: u32,
+    /// Rate of the samples handed to `push`.
+    input_sample_rate: u32,
     cfg: StreamingConfig,
 
+    /// Converts pushed input to `sample_rate`; `None` when the rates match.
+    resampler: Option<Resampler>,
+    /// Reused output buffer for the resampler.
+    resampled: Vec<f32>,
+    /// Conditions the analysis-rate signal; `None` when conditioning is off.
+    conditioner: Option<Conditioner>,
+    /// Reused output buffer for the conditioner.
+    conditioned: Vec<f32>,
+
     /// Sample ring (last `ring_capacity` mono samples). `ring_start_abs` is the
     /// absolute index of `ring.front()`.
     ring: VecDeque<f32>,
@@ -177,8 +324,17 @@ pub struct StreamingDetector {
 
     /// Absolute sample index where the next STFT frame begins.
     next_frame_start: usize,
-    /// Previous frame's magnitude spectrum (for flux differencing).
-    prev_spectrum: Option<Vec<f32>>,
+    /// Per-frame onset detection function (holds the previous frames).
+    odf: OnsetDetectionFunction,
+    /// Envelope buffers for onset localization.
+    localizer: Localizer,
+    /// The filterbank event MFCCs are pooled through.
+    mel_bank: MelFilterbank,
+    /// The current STFT frame, read out of the ring.
+    frame: Vec<f32>,
+    /// Scratch for the leading-onset head, localization regions and
+    /// classification windows.
+    window: Vec<f32>,
 
     /// Last three flux values for local-maximum peak confirmation:
     /// (value, absolute frame-start sample). `flux_win[1]` is the candidate.
@@ -202,31 +358,51 @@ pub struct StreamingDetector {
 
     /// Confirmed-but-not-yet-classified onsets, in arrival order.
     pending: VecDeque<PendingOnset>,
+    /// Tempo and beat phase, fed every emitted event.
+    beats: BeatTracker,
 
     /// The accumulating user calibration profile (Task 5). Samples are added
     /// live in jam mode; the source of truth for `adapted`.
     profile: CalibrationProfile,
-    /// Hybrid classifier MAP-adapted to `profile`, rebuilt whenever samples
-    /// change. `Some` only once the profile is sufficient (≥5 samples for all
-    /// 4 classes), so a half-taught profile never overrides the factory model.
-    adapted: Option<HybridClassifier>,
+    /// The selected classifier personalised from `profile`, rebuilt whenever
+    /// samples change. `Some` only once the profile is sufficient (≥5 samples
+    /// for all 4 core classes), so a half-taught profile never overrides the
+    /// factory model.
+    adapted: Option<Box<dyn EventClassifier>>,
     /// The A/B toggle. When `true` AND `adapted` is `Some`, the MAP-adapted
     /// model classifies. When `false`, the factory model always wins — this is
     /// what the panel's FACTORY/YOURS switch flips.
     calibration_enabled: bool,
-    /// The user-agnostic factory hybrid (AVP Gaussian + hum gate).
-    factory: HybridClassifier,
+    /// Registry name of the selected classifier (see
+    /// [`set_classifier`](Self::set_classifier)).
+    classifier: String,
+    /// The selected classifier, user-agnostic (the factory hybrid — AVP
+    /// Gaussian + hum gate — by default).
+    factory: Box<dyn EventClassifier>,
 }
 
 impl StreamingDetector {
     /// Create a detector for the given sample rate with default (calibrated)
-    /// config and the heuristic classifier.
+    /// config and the default classifier ([`DEFAULT_CLASSIFIER`]).
     pub fn new(sample_rate: u32) -> Self {
         Self::with_config(sample_rate, StreamingConfig::default())
     }
 
     /// Create a detector with a custom [`StreamingConfig`].
-    pub fn with_config(sample_rate: u32, cfg: StreamingConfig) -> Self {
+    pub fn with_config(input_sample_rate: u32, cfg: StreamingConfig) -> Self {
+        let sample_rate = cfg.analysis_sample_rate.unwrap_or(input_sample_rate);
+        let resampler = (sample_rate != input_sample_rate).then(|| {
+            let mut resampler = Resampler::new(input_sample_rate, sample_rate);
+            resampler.reserve(PREALLOCATED_CHUNK);
+            resampler
+        });
+        let conditioner = cfg.conditioning.map(|c| {
+            let mut conditioner = Conditioner::new(sample_rate, &c);
+            if let Some(profile) = &cfg.noise_profile {
+                conditioner.seed_noise(profile);
+            }
+            conditioner
+        });
         let sr = sample_rate.max(1) as f64;
         // 4s ring (spec §5.1) — big enough to hold any classification window and
         // the STFT history even if push() is called with large chunks.
@@ -235,31 +411,69 @@ impl StreamingDetector {
             ((cfg.stats_window_ms / 1000.0 * sr) as usize / cfg.hop_size.max(1)).max(4);
         // Energy floor over ~200ms, matching offline's local-average window.
         let energy_capacity = ((sr * 0.2) as usize / cfg.hop_size.max(1)).max(3);
+        let odf =
+            OnsetDetectionFunction::new(cfg.onset_function, sample_rate, cfg.window_size, cfg.hop_size);
+
+        // Everything `push` touches is sized here, so the hot path never
+        // allocates (see the module docs). A chunk of PREALLOCATED_CHUNK input
+        // samples turns into at most `chunk` at the analysis rate, plus what
+        // the conditioner was holding back.
+        let chunk = (PREALLOCATED_CHUNK as f64 * sr / input_sample_rate.max(1) as f64).ceil() as usize + 64;
+        let held_back = conditioner.as_ref().map_or(0, Conditioner::latency);
+        let fw_samples = (cfg.feature_window_ms / 1000.0 * sr) as usize;
+        let block = envelope_block(sample_rate);
+        let max_region = cfg.hop_size + cfg.window_size + (HOLD_BLOCKS + 1) * block;
+        let head = (sr * 0.01) as usize;
+        // Onsets are at least min_onset_gap_ms apart, so only so many can wait
+        // on one classification window.
+        let max_pending = (cfg.feature_window_ms / cfg.min_onset_gap_ms.max(1.0)) as usize + 4;
+        // The FFT plans are per thread: plan every size the hot path uses on
+        // the thread that builds the detector (the worklet's only thread).
+        spectrogram::with_plans(|plans| {
+            plans.prepare(cfg.window_size);
+            plans.prepare(fw_samples.clamp(1, FEATURE_FRAME));
+            if let Some(ms) = cfg.provisional_window_ms {
+                plans.prepare(((ms / 1000.0 * sr) as usize).clamp(1, FEATURE_FRAME));
+            }
+            plans.prepare(MFCC_FRAME);
+        });
 
         StreamingDetector {
             sample_rate,
-            cfg,
-            ring: VecDeque::with_capacity(ring_capacity + 4096),
+            input_sample_rate,
+            resampler,
+            resampled: Vec::with_capacity(chunk),
+            conditioner,
+            conditioned: Vec::with_capacity(chunk + held_back),
+            ring: VecDeque::with_capacity(ring_capacity + chunk + held_back),
             ring_capacity,
             ring_start_abs: 0,
             samples_seen: 0,
             next_frame_start: 0,
-            prev_spectrum: None,
+            odf,
+            localizer: Localizer::with_capacity(max_region, sample_rate),
+            mel_bank: MelFilterbank::for_mfcc(sample_rate),
+            frame: vec![0.0; cfg.window_size],
+            window: Vec::with_capacity(max_region.max(fw_samples).max(head).max(1)),
             flux_win: VecDeque::with_capacity(3),
-            flux_hist: VecDeque::new(),
+            flux_hist: VecDeque::with_capacity(stats_capacity + 1),
             flux_sum: 0.0,
             flux_sq_sum: 0.0,
             stats_capacity,
-            energy_hist: VecDeque::new(),
+            energy_hist: VecDeque::with_capacity(energy_capacity + 1),
             energy_sum: 0.0,
             energy_capacity,
             last_onset_abs: None,
             leading_checked: false,
-            pending: VecDeque::new(),
+            pending: VecDeque::with_capacity(max_pending),
+            beats: BeatTracker::default(),
+            cfg,
             profile: CalibrationProfile::new("live".to_string()),
             adapted: None,
             calibration_enabled: false,
-            factory: HybridClassifier::factory(),
+            classifier: DEFAULT_CLASSIFIER.to_string(),
+            factory: build_classifier(DEFAULT_CLASSIFIER, None)
+                .expect("the default classifier is registered"),
         }
     }
 
@@ -277,11 +491,46 @@ impl StreamingDetector {
         det
     }
 
-    /// The detector's sample rate (Hz).
+    /// Create a detector that resamples its input to the factory model's
+    /// training rate ([`crate::FACTORY_SAMPLE_RATE`]) before analysis — what
+    /// the worklet uses, since AudioContexts commonly run at 48 kHz.
+    pub fn at_model_rate(sample_rate: u32) -> Self {
+        Self::with_config(
+            sample_rate,
+            StreamingConfig {
+                analysis_sample_rate: Some(crate::FACTORY_SAMPLE_RATE),
+                ..StreamingConfig::default()
+            },
+        )
+    }
+
+    /// The rate (Hz) the detector analyses at — the rate its features and
+    /// MFCCs are extracted at. Equals the input rate unless
+    /// [`StreamingConfig::analysis_sample_rate`] is set.
     pub fn sample_rate(&self) -> u32 {
         self.sample_rate
     }
 
+    /// The rate (Hz) of the samples handed to [`push`](Self::push).
+    pub fn input_sample_rate(&self) -> u32 {
+        self.input_sample_rate
+    }
+
+    /// Classify with the registry classifier called `name` (see
+    /// [`crate::events::CLASSIFIERS`]) instead of the default hybrid. The
+    /// calibration profile and A/B toggle carry over.
+    pub fn set_classifier(&mut self, name: &str) -> Result<(), String> {
+        self.factory = build_classifier(name, None)?;
+        self.classifier = name.to_string();
+        self.rebuild_adapted();
+        Ok(())
+    }
+
+    /// Registry name of the classifier in use.
+    pub fn classifier_name(&self) -> &str {
+        &self.classifier
+    }
+
     /// Add a labeled calibration sample to the live profile (Task 5). Rebuilds
     /// the MAP-adapted model; it takes over only once the profile becomes
     /// sufficient ([`CalibrationProfile::is_sufficient`]).
@@ -322,20 +571,13 @@ impl StreamingDetector {
         &self.profile
     }
 
-    /// Rebuild the MAP-adapted hybrid from the current profile. `adapted` is
-    /// `Some` only when the profile is sufficient, so an under-taught profile
-    /// never overrides the factory model even with calibration enabled.
+    /// Rebuild the personalised classifier from the current profile.
+    /// `adapted` is `Some` only when the profile is sufficient, so an
+    /// under-taught profile never overrides the factory model even with
+    /// calibration enabled.
     fn rebuild_adapted(&mut self) {
         self.adapted = if self.profile.is_sufficient() {
-            let samples: Vec<(EventClass, Vec<f32>)> = self
-                .profile
-                .samples
-                .values()
-                .flatten()
-                .filter(|s| s.has_mfcc()) // legacy MFCC-less samples poison MAP
-                .map(|s| (s.class, s.gaussian_vec()))
-                .collect();
-            Some(HybridClassifier::with_adaptation(&samples))
+            build_classifier(&self.classifier, Some(&self.profile)).ok()
         } else {
             None
         };
@@ -344,7 +586,41 @@ impl StreamingDetector {
     /// Feed a chunk of mono samples (any length). Returns the events whose
     /// classification window completed during this call. Causal: an event's
     /// `t_ms` is its estimated onset, emitted ~`feature_window_ms` later.
+    ///
+    /// Allocates the returned `Vec` when an event completes; the worklet uses
+    /// [`push_into`](Self::push_into) instead.
     pub fn push(&mut self, samples: &[f32]) -> Vec<LiveEvent> {
+        let mut out = Vec::new();
+        self.push_into(samples, &mut out);
+        out
+    }
+
+    /// [`push`](Self::push), appending the events to `out`. Does not allocate
+    /// (see the module docs) as long as `out` has room for them.
+    pub fn push_into(&mut self, samples: &[f32], out: &mut Vec<LiveEvent>) {
+        if self.resampler.is_none() && self.conditioner.is_none() {
+            return self.analyse(samples, out);
+        }
+        let mut resampled = std::mem::take(&mut self.resampled);
+        let mut conditioned = std::mem::take(&mut self.conditioned);
+        resampled.clear();
+        conditioned.clear();
+        let mut input = samples;
+        if let Some(resampler) = self.resampler.as_mut() {
+            resampler.process(input, &mut resampled);
+            input = &resampled;
+        }
+        if let Some(conditioner) = self.conditioner.as_mut() {
+            conditioner.process(input, &mut conditioned);
+            input = &conditioned;
+        }
+        self.analyse(input, out);
+        self.resampled = resampled;
+        self.conditioned = conditioned;
+    }
+
+    /// Run detection over a chunk already at the analysis rate.
+    fn analyse(&mut self, samples: &[f32], out: &mut Vec<LiveEvent>) {
         if !samples.is_empty() {
             self.ring.extend(samples.iter().copied());
             self.samples_seen += samples.len();
@@ -361,9 +637,7 @@ impl StreamingDetector {
 
         // Single emission point: drain every pending onset whose classification
         // window has now filled. Registration only queues, so nothing is lost.
-        let mut out = Vec::new();
-        self.drain_ready(&mut out);
-        out
+        self.drain_ready(out);
     }
 
     /// End-of-stream: classify every still-pending onset from whatever audio
@@ -373,18 +647,29 @@ impl StreamingDetector {
     /// The window is TRIMMED to the audio that actually arrived (matching the
     /// offline pipeline's end-of-file clamp) rather than zero-padded: padding
     /// silent frames dilutes the MFCC mean and measurably shifts the verdict
-    /// on short tails.
+    /// on short tails. A trimmed window is a new FFT size, so unlike `push`
+    /// this may allocate.
     pub fn flush(&mut self) -> Vec<LiveEvent> {
+        // Drain the resampler's and conditioner's lookahead first: those
+        // samples may complete (or confirm) onsets of their own.
         let mut out = Vec::new();
+        if self.resampler.is_some() || self.conditioner.is_some() {
+            let mut tail = Vec::new();
+            if let Some(resampler) = self.resampler.as_mut() {
+                resampler.flush(&mut tail);
+            }
+            if let Some(conditioner) = self.conditioner.as_mut() {
+                let mut conditioned = Vec::new();
+                conditioner.process(&tail, &mut conditioned);
+                conditioner.flush(&mut conditioned);
+                tail = conditioned;
+            }
+            self.analyse(&tail, &mut out);
+        }
         while let Some(p) = self.pending.pop_front() {
-            let fw_samples =
-                (self.cfg.feature_window_ms / 1000.0 * self.sample_rate as f64) as usize;
             let available = self.samples_seen.saturating_sub(p.onset_abs);
-            let win = self.ring_slice(p.onset_abs, fw_samples.min(available).max(1));
-            let features = extract_features(&win, self.sample_rate);
-            let mfcc = extract_mfcc(&win, self.sample_rate);
-            let (class, confidence) = self.classify(&features, &mfcc);
-            out.push(LiveEvent { t_ms: p.t_ms, class, confidence, features, mfcc });
+            let event = self.classify_pending(&p, self.fw_samples().min(available).max(1));
+            self.emit(event, p.provisional, &mut out);
         }
         out
     }
@@ -408,19 +693,11 @@ impl StreamingDetector {
         }
     }
 
-    /// Read `len` samples of the ring starting at absolute index `abs`. Missing
-    /// tail (not yet arrived) or head (already trimmed) is zero-padded.
-    fn ring_slice(&self, abs: usize, len: usize) -> Vec<f32> {
-        let mut out = vec![0.0f32; len];
-        for (k, slot) in out.iter_mut().enumerate() {
-            let a = abs + k;
-            if a >= self.ring_start_abs && a < self.samples_seen {
-                if let Some(&s) = self.ring.get(a - self.ring_start_abs) {
-                    *slot = s;
-                }
-            }
-        }
-        out
+    /// Read `len` samples of the ring starting at absolute index `abs` into
+    /// the `window` scratch buffer.
+    fn read_window(&mut self, abs: usize, len: usize) {
+        self.window.resize(len, 0.0);
+        read_ring(&self.ring, self.ring_start_abs, abs, &mut self.window);
     }
 
     /// Leading-onset (t≈0) gate: if the first 10ms carries energy, offline emits
@@ -435,39 +712,27 @@ impl StreamingDetector {
             return; // wait for enough audio
         }
         self.leading_checked = true;
-        let head = self.ring_slice(0, win);
-        let rms = (head.iter().map(|s| s * s).sum::<f32>() / win as f32).sqrt();
+        self.read_window(0, win);
+        let rms = (self.window.iter().map(|s| s * s).sum::<f32>() / win as f32).sqrt();
         if rms > self.cfg.leading_rms_threshold {
             // Report at t=0 (offline's convention for a leading onset).
             self.register_onset(0, 0.0);
         }
     }
 
-    /// Process one STFT frame at `next_frame_start`: compute causal flux, update
-    /// rolling stats, confirm the previous frame as a peak if warranted, and run
-    /// the energy-rise fallback.
+    /// Process one STFT frame at `next_frame_start`: compute the causal onset
+    /// detection value, update rolling stats, confirm the previous frame as a
+    /// peak if warranted, and run the energy-rise fallback.
     fn process_frame(&mut self) {
         let start = self.next_frame_start;
-        let frame = self.ring_slice(start, self.cfg.window_size);
-
-        // --- spectral flux (same rectified-flux math as offline) ---
-        let mut windowed = frame.clone();
-        apply_hann_window(&mut windowed);
-        let spectrum = compute_fft(&windowed);
-        let flux = match &self.prev_spectrum {
-            Some(prev) => {
-                let mut sum = 0.0f32;
-                for (c, p) in spectrum.iter().zip(prev.iter()) {
-                    let d = c - p;
-                    if d > 0.0 {
-                        sum += d;
-                    }
-                }
-                sum
-            }
-            None => 0.0,
-        };
-        self.prev_spectrum = Some(spectrum);
+        read_ring(&self.ring, self.ring_start_abs, start, &mut self.frame);
+
+        // --- onset detection function (same math as offline) ---
+        let (odf, frame, size) = (&mut self.odf, &self.frame, self.cfg.window_size);
+        let flux = spectrogram::with_plans(|plans| {
+            let (spectrum, magnitudes) = plans.windowed(frame, size);
+            odf.process_spectrum(spectrum, magnitudes)
+        });
 
         // Peak confirmation uses the stats as they stand BEFORE folding in this
         // frame's flux, so the candidate frame is judged against its own past
@@ -476,18 +741,17 @@ impl StreamingDetector {
         let threshold = (mean + self.cfg.threshold_factor as f64 * std).max(self.cfg.min_flux as f64);
 
         // Slide the 3-wide confirmation window: [i-1, i(candidate), i+1=this].
-        self.flux_win.push_back((flux, start));
-        while self.flux_win.len() > 3 {
+        if self.flux_win.len() == 3 {
             self.flux_win.pop_front();
         }
+        self.flux_win.push_back((flux, start));
         if self.flux_win.len() == 3 {
             let prev = self.flux_win[0].0;
             let (cand, cand_start) = self.flux_win[1];
             let next = self.flux_win[2].0;
             let is_peak = cand > prev && cand > next;
             if is_peak && (cand as f64) > threshold {
-                // Estimated true onset = centre of the candidate window.
-                let onset_abs = cand_start + self.cfg.window_size / 2;
+                let onset_abs = self.localize(cand_start, self.cfg.window_size);
                 self.try_register(onset_abs);
             }
         }
@@ -496,18 +760,20 @@ impl StreamingDetector {
         self.push_flux(flux);
 
         // --- energy-rise fallback (causal mirror of offline's RMS detector) ---
-        self.energy_fallback(start);
+        if self.cfg.onset_function.uses_energy_fallback() {
+            self.energy_fallback(start);
+        }
     }
 
     /// Causal broadband-energy detector for low-frequency kicks that the
     /// high-bin-biased flux under-weights. Fires when the current short-window
     /// RMS both exceeds `energy_rise_factor` × the rolling floor and is a
     /// meaningful absolute level. Refractory + `try_register` de-dup against
-    /// flux onsets, so this only adds onsets flux missed.
+    /// flux onsets, so this only adds onsets flux missed. Reads the frame
+    /// `process_frame` just analysed.
     fn energy_fallback(&mut self, start: usize) {
         let win = self.cfg.window_size;
-        let frame = self.ring_slice(start, win);
-        let rms = (frame.iter().map(|s| s * s).sum::<f32>() / win as f32).sqrt();
+        let rms = (self.frame.iter().map(|s| s * s).sum::<f32>() / win as f32).sqrt();
 
         let floor = if self.energy_hist.is_empty() {
             0.0
@@ -518,7 +784,7 @@ impl StreamingDetector {
             && rms / floor > self.cfg.energy_rise_factor
             && rms > self.cfg.energy_min_rms;
         if is_rise {
-            let onset_abs = start + win / 2;
+            let onset_abs = self.localize(start, win);
             self.try_register(onset_abs);
         }
 
@@ -531,9 +797,24 @@ impl StreamingDetector {
         }
     }
 
+    /// Absolute sample index of the transient a `frame_len`-sample frame at
+    /// `frame_start` reacted to (see [`crate::localize`]), searching the frame
+    /// and the hop before it. Falls back to the frame centre when the region
+    /// holds no rise.
+    fn localize(&mut self, frame_start: usize, frame_len: usize) -> usize {
+        let block = envelope_block(self.sample_rate);
+        let first = frame_start.saturating_sub(self.cfg.hop_size) / block;
+        let history = first.saturating_sub(HOLD_BLOCKS - 1);
+        let from = history * block;
+        self.read_window(from, frame_start + frame_len - from);
+        self.localizer
+            .localize_onset(&self.window, first - history, self.sample_rate)
+            .map_or(frame_start + frame_len / 2, |offset| from + offset)
+    }
+
     /// Register an onset if the refractory window since the last one has
-    /// elapsed. Computes the reported (offline-aligned) time and queues a
-    /// pending classification.
+    /// elapsed. Computes the reported time and queues a pending
+    /// classification.
     fn try_register(&mut self, onset_abs: usize) {
         let gap_samples = (self.cfg.min_onset_gap_ms * self.sample_rate as f64 / 1000.0) as usize;
         if let Some(last) = self.last_onset_abs {
@@ -541,8 +822,7 @@ impl StreamingDetector {
                 return;
             }
         }
-        let t_ms = (onset_abs as f64 / self.sample_rate as f64 * 1000.0 - self.cfg.onset_align_ms)
-            .max(0.0);
+        let t_ms = onset_abs as f64 / self.sample_rate as f64 * 1000.0;
         self.register_onset(onset_abs, t_ms);
     }
 
@@ -553,43 +833,220 @@ impl StreamingDetector {
     /// exactly once and never lost.
     fn register_onset(&mut self, onset_abs: usize, t_ms: f64) {
         self.last_onset_abs = Some(onset_abs);
-        let fw_samples = (self.cfg.feature_window_ms / 1000.0 * self.sample_rate as f64) as usize;
-        let ready_abs = onset_abs + fw_samples;
-        self.pending.push_back(PendingOnset { onset_abs, ready_abs, t_ms });
+        let ready_abs = onset_abs + self.fw_samples();
+        let provisional_abs = self.pw_samples().map(|n| onset_abs + n);
+        self.pending.push_back(PendingOnset { onset_abs, ready_abs, t_ms, provisional_abs, provisional: None });
     }
 
-    /// Emit any pending onsets whose classification window has filled.
+    /// Emit a provisional event for every pending onset whose provisional
+    /// window has filled, then the final verdict on any whose classification
+    /// window has.
     fn drain_ready(&mut self, out: &mut Vec<LiveEvent>) {
+        if let Some(len) = self.pw_samples() {
+            for i in 0..self.pending.len() {
+                let p = &self.pending[i];
+                if p.provisional.is_some() {
+                    continue;
+                }
+                // Provisional windows fill in arrival order too.
+                if p.provisional_abs.map_or(true, |at| at > self.samples_seen) {
+                    break;
+                }
+                let (onset_abs, t_ms) = (p.onset_abs, p.t_ms);
+                let mut event = self.classify_window(onset_abs, t_ms, len);
+                event.stage = LiveStage::Provisional;
+                self.pending[i].provisional = Some(event.class);
+                out.push(event);
+            }
+        }
         while let Some(p) = self.pending.front() {
             if p.ready_abs > self.samples_seen {
                 break;
             }
             let p = self.pending.pop_front().unwrap();
-            let fw_samples =
-                (self.cfg.feature_window_ms / 1000.0 * self.sample_rate as f64) as usize;
-            let win = self.ring_slice(p.onset_abs, fw_samples.max(1));
-            let features = extract_features(&win, self.sample_rate);
-            let mfcc = extract_mfcc(&win, self.sample_rate);
-            let (class, confidence) = self.classify(&features, &mfcc);
-            out.push(LiveEvent { t_ms: p.t_ms, class, confidence, features, mfcc });
+            let event = self.classify_pending(&p, self.fw_samples().max(1));
+            self.emit(event, p.provisional, out);
+        }
+    }
+
+    /// Hand an onset's final verdict to the beat tracker and the caller: as
+    /// is when no provisional event went out, as a revision when it corrects
+    /// the provisional class, not at all when it agrees with it.
+    fn emit(&mut self, mut event: LiveEvent, provisional: Option<EventClass>, out: &mut Vec<LiveEvent>) {
+        self.beats.observe(event.t_ms, beat_accent(event.class));
+        match provisional {
+            None => out.push(event),
+            Some(class) if class != event.class => {
+                event.stage = LiveStage::Revised;
+                out.push(event);
+            }
+            Some(_) => {}
+        }
+    }
+
+    /// Turn provisional emission on (`Some(ms)`, e.g.
+    /// [`PROVISIONAL_WINDOW_MS`]) or off. Onsets already pending keep the
+    /// setting they were registered under. Prepares the window's FFT plan, so
+    /// call it off the hot path.
+    pub fn set_provisional_window_ms(&mut self, ms: Option<f64>) {
+        self.cfg.provisional_window_ms = ms;
+        if let Some(len) = self.pw_samples() {
+            spectrogram::with_plans(|plans| plans.prepare(len.min(FEATURE_FRAME)));
         }
     }
 
-    /// Classify features + MFCCs through the hybrid model: the MAP-adapted
-    /// model when calibration is enabled AND the profile is sufficient
-    /// (`adapted` is `Some`), the factory model otherwise. This gate is what
-    /// the A/B toggle flips:
+    /// The tempo and beat phase at the stream head: the live BPM, how far
+    /// into the current beat the latest pushed sample is, and how sure the
+    /// tracker is (0.0 until it has locked). Does not allocate.
+    pub fn tempo(&self) -> LiveTempo {
+        self.beats.tempo_at(self.samples_seen as f64 * 1000.0 / self.sample_rate.max(1) as f64)
+    }
+
+    /// Capture the detector's state at the stream head (see the module
+    /// docs). Allocates; call it between pushes, off the render thread.
+    pub fn snapshot(&self) -> StreamingSnapshot {
+        let ring_start = self.oldest_needed().max(self.ring_start_abs);
+        StreamingSnapshot {
+            input_sample_rate: self.input_sample_rate,
+            config: self.cfg.clone(),
+            resampler: self.resampler.as_ref().map(Resampler::state),
+            conditioner: self.conditioner.as_ref().map(Conditioner::state),
+            ring: self.ring.iter().skip(ring_start - self.ring_start_abs).copied().collect(),
+            ring_start_abs: ring_start,
+            samples_seen: self.samples_seen,
+            next_frame_start: self.next_frame_start,
+            odf: self.odf.state(),
+            flux_win: self.flux_win.iter().copied().collect(),
+            flux_hist: self.flux_hist.iter().copied().collect(),
+            flux_sum: self.flux_sum,
+            flux_sq_sum: self.flux_sq_sum,
+            energy_hist: self.energy_hist.iter().copied().collect(),
+            energy_sum: self.energy_sum,
+            last_onset_abs: self.last_onset_abs,
+            leading_checked: self.leading_checked,
+            pending: self.pending.iter().cloned().collect(),
+            beats: self.beats.state(),
+            profile: self.profile.clone(),
+            calibration_enabled: self.calibration_enabled,
+            classifier: self.classifier.clone(),
+        }
+    }
+
+    /// Rebuild a detector from a [`snapshot`](Self::snapshot). Pushing the
+    /// rest of the stream to it emits exactly what the original would have.
+    /// Like the constructors, it prepares the FFT plans on the calling
+    /// thread, so restore on the thread that will push.
+    pub fn restore(snapshot: StreamingSnapshot) -> Result<Self, String> {
+        let s = snapshot;
+        // The bytes may come from anywhere (the worklet hands them over from
+        // JS): a zero rate or frame size would build a detector that never
+        // advances.
+        let cfg = &s.config;
+        if s.input_sample_rate == 0 || cfg.analysis_sample_rate == Some(0) {
+            return Err("snapshot sample rate is zero".to_string());
+        }
+        if cfg.window_size == 0 || cfg.hop_size == 0 {
+            return Err("snapshot window or hop size is zero".to_string());
+        }
+        let mut det = Self::with_config(s.input_sample_rate, s.config);
+        match (det.resampler.as_mut(), s.resampler) {
+            (Some(resampler), Some(state)) => resampler.restore_state(state)?,
+            (None, None) => {}
+            _ => return Err("snapshot resampler does not match its config".to_string()),
+        }
+        match (det.conditioner.as_mut(), s.conditioner) {
+            (Some(conditioner), Some(state)) => conditioner.restore_state(state)?,
+            (None, None) => {}
+            _ => return Err("snapshot conditioner does not match its config".to_string()),
+        }
+        if s.ring_start_abs + s.ring.len() != s.samples_seen || s.flux_win.len() > 3 {
+            return Err("snapshot stream position is inconsistent".to_string());
+        }
+        det.ring.extend(s.ring);
+        det.ring_start_abs = s.ring_start_abs;
+        det.samples_seen = s.samples_seen;
+        det.next_frame_start = s.next_frame_start;
+        det.odf.restore_state(s.odf)?;
+        det.flux_win.extend(s.flux_win);
+        det.flux_hist.extend(s.flux_hist);
+        det.flux_sum = s.flux_sum;
+        det.flux_sq_sum = s.flux_sq_sum;
+        det.energy_hist.extend(s.energy_hist);
+        det.energy_sum = s.energy_sum;
+        det.last_onset_abs = s.last_onset_abs;
+        det.leading_checked = s.leading_checked;
+        det.pending.extend(s.pending);
+        det.beats.restore_state(s.beats)?;
+        det.profile = s.profile;
+        det.calibration_enabled = s.calibration_enabled;
+        det.set_classifier(&s.classifier)?;
+        Ok(det)
+    }
+
+    /// Oldest absolute sample a later read can reach: the leading-onset head
+    /// until it is checked, pending classification windows, the next STFT
+    /// frame, and the localization region of the next peak candidate (the
+    /// newest frame in `flux_win`) or energy rise (the next frame).
+    fn oldest_needed(&self) -> usize {
+        if !self.leading_checked {
+            return 0;
+        }
+        let block = envelope_block(self.sample_rate);
+        let frame = self.flux_win.back().map_or(self.next_frame_start, |&(_, start)| start);
+        let first = frame.min(self.next_frame_start).saturating_sub(self.cfg.hop_size) / block;
+        let region = first.saturating_sub(HOLD_BLOCKS - 1) * block;
+        self.pending.iter().map(|p| p.onset_abs).fold(region, usize::min)
+    }
+
+    /// Classification window length in samples.
+    fn fw_samples(&self) -> usize {
+        (self.cfg.feature_window_ms / 1000.0 * self.sample_rate as f64) as usize
+    }
+
+    /// Provisional window length in samples; `None` when provisional
+    /// emission is off or its window would be no shorter than the full one.
+    fn pw_samples(&self) -> Option<usize> {
+        let ms = self.cfg.provisional_window_ms?;
+        let len = (ms / 1000.0 * self.sample_rate as f64) as usize;
+        (len > 0 && len < self.fw_samples()).then_some(len)
+    }
+
+    /// Classify the `len` samples from `p`'s onset into its event.
+    fn classify_pending(&mut self, p: &PendingOnset, len: usize) -> LiveEvent {
+        self.classify_window(p.onset_abs, p.t_ms, len)
+    }
+
+    /// Classify the `len` samples from `onset_abs` into an event at `t_ms`.
+    fn classify_window(&mut self, onset_abs: usize, t_ms: f64, len: usize) -> LiveEvent {
+        self.read_window(onset_abs, len);
+        let win = &self.window;
+        let features = extract_features(win, self.sample_rate);
+        let mfcc = extract_mfcc_with(win, self.sample_rate, &self.mel_bank);
+        let (class, confidence) = self.classify_input(
+            &ClassifierInput::new(&features, &mfcc).with_window(win, self.sample_rate),
+        );
+        LiveEvent { t_ms, class, confidence, features, mfcc, stage: LiveStage::Final }
+    }
+
+    /// Classify features + MFCCs through the selected classifier: the
+    /// personalised one when calibration is enabled AND the profile is
+    /// sufficient (`adapted` is `Some`), the factory one otherwise. This gate
+    /// is what the A/B toggle flips:
     /// [`set_calibration_enabled`](Self::set_calibration_enabled).
     ///
     /// Public so the worklet's calibration UI can re-classify a probe/event
     /// through the live gate without re-running detection.
     pub fn classify(&self, f: &EventFeatures, mfcc: &[f32]) -> (EventClass, f32) {
+        self.classify_input(&ClassifierInput::new(f, mfcc))
+    }
+
+    fn classify_input(&self, input: &ClassifierInput) -> (EventClass, f32) {
         let clf = if self.calibration_enabled {
-            self.adapted.as_ref().unwrap_or(&self.factory)
+            self.adapted.as_deref().unwrap_or(self.factory.as_ref())
         } else {
-            &self.factory
+            self.factory.as_ref()
         };
-        let r = clf.classify(f, mfcc);
+        let r = clf.classify(input);
         (r.class, r.confidence)
     }
 
@@ -618,6 +1075,60 @@ impl StreamingDetector {
     }
 }
 
+/// Serialized [`StreamingDetector`] state; see
+/// [`StreamingDetector::snapshot`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamingSnapshot {
+    input_sample_rate: u32,
+    config: StreamingConfig,
+    resampler: Option<ResamplerState>,
+    conditioner: Option<ConditionerState>,
+    /// The ring from the oldest sample a later read can reach
+    /// (`ring_start_abs`) to the stream head.
+    #[serde(with = "crate::snapshot::f32s")]
+    ring: Vec<f32>,
+    ring_start_abs: usize,
+    samples_seen: usize,
+    next_frame_start: usize,
+    odf: OnsetFunctionState,
+    flux_win: Vec<(f32, usize)>,
+    #[serde(with = "crate::snapshot::f32s")]
+    flux_hist: Vec<f32>,
+    flux_sum: f64,
+    flux_sq_sum: f64,
+    #[serde(with = "crate::snapshot::f32s")]
+    energy_hist: Vec<f32>,
+    energy_sum: f64,
+    last_onset_abs: Option<usize>,
+    leading_checked: bool,
+    pending: Vec<PendingOnset>,
+    beats: BeatTrackerState,
+    profile: CalibrationProfile,
+    calibration_enabled: bool,
+    classifier: String,
+}
+
+impl StreamingSnapshot {
+    /// Serialize the snapshot to (compact) JSON bytes.
+    pub fn to_json_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec(self)
+    }
+
+    /// Deserialize a snapshot from JSON bytes.
+    pub fn from_json_bytes(data: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(data)
+    }
+}
+
+/// Copy `ring` (whose front is absolute sample `ring_start`) from absolute
+/// index `abs` into `out`. Missing tail (not yet arrived) or head (already
+/// trimmed) is zero-filled.
+fn read_ring(ring: &VecDeque<f32>, ring_start: usize, abs: usize, out: &mut [f32]) {
+    for (k, slot) in out.iter_mut().enumerate() {
+        *slot = (abs + k).checked_sub(ring_start).and_then(|i| ring.get(i)).copied().unwrap_or(0.0);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -670,6 +1181,36 @@ mod tests {
         assert!(det.flush().is_empty(), "flush must be idempotent");
     }
 
+    #[test]
+    fn at_model_rate_analyses_48k_input_at_44k() {
+        let sr = 48_000u32;
+        let mut audio = vec![0.0f32; (sr / 4) as usize]; // 250ms lead-in
+        audio.extend(synth_kick(sr, 0.2));
+        let mut det = StreamingDetector::at_model_rate(sr);
+        assert_eq!(det.input_sample_rate(), sr);
+        assert_eq!(det.sample_rate(), crate::FACTORY_SAMPLE_RATE);
+        let mut events: Vec<_> = audio.chunks(128).flat_map(|c| det.push(c)).collect();
+        events.extend(det.flush());
+        assert_eq!(events.len(), 1, "expected exactly one onset, got {}", events.len());
+        assert!((events[0].t_ms - 250.0).abs() < 3.0, "t_ms was {}", events[0].t_ms);
+        assert_eq!(events[0].class, EventClass::BilabialPlosive);
+    }
+
+    #[test]
+    fn every_onset_function_detects_a_live_kick() {
+        let sr = 44_100u32;
+        let mut audio = vec![0.0f32; (sr / 4) as usize]; // 250ms lead-in
+        audio.extend(synth_kick(sr, 0.2));
+        for onset_function in crate::OnsetFunction::ALL {
+            let cfg = StreamingConfig { onset_function, ..StreamingConfig::default() };
+            let mut det = StreamingDetector::with_config(sr, cfg);
+            let mut events: Vec<_> = audio.chunks(128).flat_map(|c| det.push(c)).collect();
+            events.extend(det.flush());
+            assert_eq!(events.len(), 1, "{onset_function}: {} events", events.len());
+            assert!((events[0].t_ms - 250.0).abs() < 3.0, "{onset_function}: t_ms {}", events[0].t_ms);
+        }
+    }
+
     #[test]
     fn silence_emits_nothing() {
         let mut det = StreamingDetector::new(44_100);
@@ -714,6 +1255,133 @@ mod tests {
         assert_eq!(events[0].class, EventClass::BilabialPlosive);
     }
 
+    #[test]
+    fn tracks_the_tempo_of_a_live_groove() {
+        // Kicks on every beat at 100 BPM, from 300ms in.
+        let sr = 44_100u32;
+        let beat = 0.6;
+        let mut audio = vec![0.0f32; (sr as f64 * (0.3 + 12.0 * beat)) as usize];
+        for k in 0..12 {
+            let start = (sr as f64 * (0.3 + k as f64 * beat)) as usize;
+            for (i, s) in synth_kick(sr, 0.2).into_iter().enumerate() {
+                audio[start + i] += s;
+            }
+        }
+        let mut det = StreamingDetector::new(sr);
+        assert_eq!(det.tempo().confidence, 0.0);
+        let events: Vec<_> = audio.chunks(128).flat_map(|c| det.push(c)).collect();
+        assert!(events.len() >= 10, "{} events", events.len());
+        let tempo = det.tempo();
+        assert!((tempo.bpm - 100.0).abs() < 2.0, "bpm {}", tempo.bpm);
+        assert!(tempo.confidence > 0.5, "confidence {}", tempo.confidence);
+        // The grid sits on the kicks, so they snap to themselves.
+        for e in &events {
+            assert!((tempo.snap_ms(e.t_ms, 1) - e.t_ms).abs() < 15.0, "kick at {} off the grid", e.t_ms);
+        }
+    }
+
+    #[test]
+    fn snapshot_at_every_quantum_resumes_exactly() {
+        // Kicks 300ms apart, restored from a serialized snapshot before every
+        // render quantum: each split lands somewhere between a peak frame,
+        // its confirmation and its classification window. The noise floor
+        // makes every ring sample matter (trimmed samples read as zeros).
+        let sr = 44_100u32;
+        let mut seed = 1u32;
+        let mut audio: Vec<f32> = (0..(sr as f64 * 1.2) as usize)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed >> 8) as f32 / (1u32 << 24) as f32 * 0.02 - 0.01
+            })
+            .collect();
+        for k in 0..3 {
+            let start = (sr as f64 * (0.1 + 0.3 * k as f64)) as usize;
+            for (i, s) in synth_kick(sr, 0.2).into_iter().enumerate() {
+                audio[start + i] += s;
+            }
+        }
+        let mut det = StreamingDetector::new(sr);
+        let expected: Vec<String> = audio.chunks(128).flat_map(|c| det.push(c)).map(|e| format!("{e:?}")).collect();
+        assert_eq!(expected.len(), 3);
+
+        let mut det = StreamingDetector::new(sr);
+        let mut resumed = Vec::new();
+        for chunk in audio.chunks(128) {
+            let bytes = det.snapshot().to_json_bytes().unwrap();
+            det = StreamingDetector::restore(StreamingSnapshot::from_json_bytes(&bytes).unwrap()).unwrap();
+            resumed.extend(det.push(chunk).iter().map(|e| format!("{e:?}")));
+        }
+        assert_eq!(resumed, expected);
+        assert_eq!(format!("{:?}", det.tempo()), {
+            let mut det = StreamingDetector::new(sr);
+            audio.chunks(128).for_each(|c| drop(det.push(c)));
+            format!("{:?}", det.tempo())
+        });
+    }
+
+    #[test]
+    fn restore_rejects_a_degenerate_config() {
+        let det = StreamingDetector::new(44_100);
+        let corrupt: [fn(&mut StreamingSnapshot); 4] = [
+            |s| s.config.hop_size = 0,
+            |s| s.config.window_size = 0,
+            |s| s.input_sample_rate = 0,
+            |s| s.config.analysis_sample_rate = Some(0),
+        ];
+        for (k, corrupt) in corrupt.iter().enumerate() {
+            let mut snapshot = det.snapshot();
+            corrupt(&mut snapshot);
+            assert!(StreamingDetector::restore(snapshot).is_err(), "corruption {k} restored");
+        }
+        assert!(StreamingDetector::restore(det.snapshot()).is_ok());
+    }
+
+    #[test]
+    fn provisional_verdict_arrives_early_and_holds() {
+        // A kick 250ms in: the provisional event comes out ~100ms before the
+        // full window fills, and the full window agrees, so nothing follows.
+        let sr = 44_100u32;
+        let mut audio = vec![0.0f32; (sr / 4) as usize];
+        audio.extend(synth_kick(sr, 0.5));
+        let cfg = StreamingConfig { provisional_window_ms: Some(PROVISIONAL_WINDOW_MS), ..StreamingConfig::default() };
+        let mut det = StreamingDetector::with_config(sr, cfg);
+        let mut emitted = Vec::new();
+        for (i, chunk) in audio.chunks(128).enumerate() {
+            for e in det.push(chunk) {
+                emitted.push(((i + 1) * 128) as f64 * 1000.0 / sr as f64 - e.t_ms);
+                assert_eq!(e.stage, LiveStage::Provisional);
+                assert_eq!(e.class, EventClass::BilabialPlosive);
+            }
+        }
+        assert!(det.flush().is_empty());
+        assert_eq!(emitted.len(), 1, "expected one provisional event, got {}", emitted.len());
+        assert!(emitted[0] < PROVISIONAL_WINDOW_MS + 5.0, "emitted {}ms after the onset", emitted[0]);
+    }
+
+    #[test]
+    fn disagreeing_full_window_revises_the_provisional_class() {
+        // The synthetic corpus rarely disagrees on cue, so hand `emit` the
+        // final verdicts directly: revised, swallowed, and single-stage.
+        let mut det = StreamingDetector::new(44_100);
+        let event = |class| LiveEvent {
+            t_ms: 100.0,
+            class,
+            confidence: 0.9,
+            features: EventFeatures::zero(),
+            mfcc: [0.0; MFCC_COEFFS],
+            stage: LiveStage::Final,
+        };
+        let mut out = Vec::new();
+        det.emit(event(EventClass::Rimshot), Some(EventClass::HihatNoise), &mut out);
+        det.emit(event(EventClass::HihatNoise), Some(EventClass::HihatNoise), &mut out);
+        det.emit(event(EventClass::Click), None, &mut out);
+        let stages: Vec<_> = out.iter().map(|e| (e.class, e.stage)).collect();
+        assert_eq!(
+            stages,
+            [(EventClass::Rimshot, LiveStage::Revised), (EventClass::Click, LiveStage::Final)]
+        );
+    }
+
     // ---- Task 5: few-shot calibration (kNN-first + heuristic fallback) ----
 
     use crate::events::CalibrationSample;
@@ -832,6 +1500,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn selected_classifier_serves_both_sides_of_the_toggle() {
+        let (pf, pm) = probe();
+        let mut det = StreamingDetector::new(44_100);
+        assert_eq!(det.classifier_name(), DEFAULT_CLASSIFIER);
+        assert!(det.set_classifier("no-such-model").is_err());
+        assert_eq!(det.classifier_name(), DEFAULT_CLASSIFIER);
+
+        det.set_classifier("knn").unwrap();
+        det.set_calibration_enabled(true);
+        // kNN without samples answers like the heuristic.
+        let heuristic = crate::events::HeuristicClassifier::new().classify(&pf);
+        assert_eq!(det.classify(&pf, &pm), (heuristic.class, heuristic.confidence));
+
+        // kNN memorises the weird lesson outright once the profile is sufficient.
+        teach(&mut det, EventClass::Click, hihat_like());
+        teach(&mut det, EventClass::BilabialPlosive, kick_like());
+        teach(&mut det, EventClass::HihatNoise, other_hat());
+        teach(&mut det, EventClass::HumVoiced, hum_like());
+        assert_eq!(det.classify(&pf, &pm), (EventClass::Click, 1.0));
+    }
+
     #[test]
     fn clear_calibration_reverts_to_factory() {
         // A re-teach begins on a detector that was re-seeded with a sufficient
@@ -966,4 +1656,36 @@ mod tests {
             assert!((x - y).abs() < 1e-6, "chunking changed t_ms: {x} vs {y}");
         }
     }
+
+    #[test]
+    fn conditioning_finds_kicks_under_hum_and_dc() {
+        // Quiet kicks every 500ms riding a DC offset and mains hum louder
+        // than the kicks' own tails.
+        let sr = 44_100u32;
+        let mut audio = vec![0.0f32; sr as usize * 2];
+        let kick = synth_kick(sr, 0.2);
+        let starts = [sr as usize / 2, sr as usize, sr as usize * 3 / 2];
+        for &at in &starts {
+            for (i, s) in kick.iter().enumerate() {
+                audio[at + i] += 0.1 * s;
+            }
+        }
+        for (i, s) in audio.iter_mut().enumerate() {
+            *s += 0.2 + 0.02 * (2.0 * std::f32::consts::PI * 50.0 * i as f32 / sr as f32).sin();
+        }
+
+        let mut det = StreamingDetector::with_config(
+            sr,
+            StreamingConfig { conditioning: Some(ConditioningConfig::default()), ..StreamingConfig::default() },
+        );
+        let mut events: Vec<_> = audio.chunks(128).flat_map(|c| det.push(c)).collect();
+        events.extend(det.flush());
+        let times: Vec<f64> = events.iter().map(|e| e.t_ms).collect();
+        assert_eq!(events.len(), starts.len(), "one event per kick: {times:?}");
+        for (event, &at) in events.iter().zip(&starts) {
+            let expected_ms = at as f64 / sr as f64 * 1000.0;
+            assert!((event.t_ms - expected_ms).abs() < 25.0, "{} vs {expected_ms}", event.t_ms);
+            assert_eq!(event.class, EventClass::BilabialPlosive);
+        }
+    }
 }
@@ -4,7 +4,8 @@
 use serde::{Deserialize, Serialize};
 
 /// Musical scale families
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub enum ScaleFamily {
     MinorPentatonic,
     NaturalMinor,
@@ -14,7 +15,8 @@ pub enum ScaleFamily {
 }
 
 /// Chord types by scale degree
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub enum ChordType {
     I,    // Major I
     II,   // Major II
@@ -33,14 +35,16 @@ pub enum ChordType {
 }
 
 /// Chord progression structure
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct ChordProgression {
     pub chords: Vec<ChordType>,
     pub bars_per_chord: u32,
 }
 
 /// Arpeggiator patterns
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub enum ArpPattern {
     Up158,       // 1-5-8 ascending
     Down851,     // 8-5-1 descending
@@ -49,7 +53,8 @@ pub enum ArpPattern {
 }
 
 /// Bass line patterns
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub enum BassPattern {
     Root,           // Just root notes
     RootFifth,      // Root and fifth
@@ -58,14 +63,17 @@ pub enum BassPattern {
 }
 
 /// Drum kit palettes — every variant must have a distinct implemented sound
-/// in the TS synth (src/audio/timbre.ts). Do not add variants without a DSP mapping.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+/// in the TS synth (src/audio/timbre.ts) and the native renderer
+/// (src/render/timbre.rs). Do not add variants without a DSP mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub enum DrumPalette {
     SynthwaveDrums, // layered 80s electronic (the original Beatrice kit)
     TR808,          // analog-style: long boomy sine kick with pitch snap, snappy noise snare, tight metallic hat
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub enum FxProfile {
     GatedReverb, // 80s gated reverb: shaped IR truncated at the gate with a short fade
     WideChorus,  // modulated stereo short delays on pad/arp
@@ -75,7 +83,8 @@ pub enum FxProfile {
 
 /// The render-time sound identity of a theme. Snapshot into every Arrangement
 /// so playback/export need no side-channel theme lookup.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct ThemeSound {
     pub drum_palette: DrumPalette,
     pub fx_profile: FxProfile,
@@ -89,7 +98,8 @@ pub struct ThemeSound {
 /// own `match theme { "BLADE RUNNER" => ..., _ => cyan }`, so any new theme
 /// silently rendered a generic default. Every field is a `#RRGGBB` string
 /// (validated by the registry test).
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct ThemeVisuals {
     /// Primary colour of the reactive 3D geometry and event pillars.
     pub accent_hex: String,
@@ -101,7 +111,8 @@ pub struct ThemeVisuals {
 }
 
 /// Complete theme definition
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct Theme {
     pub name: String,
     pub bpm_range: (u32, u32),           // Suggested BPM range
@@ -120,7 +131,8 @@ pub struct Theme {
 }
 
 /// Theme summary for UI display
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct ThemeSummary {
     pub name: String,
     pub description: String,
@@ -1,9 +1,12 @@
 //! Golden freeze test for the extracted offline pipeline.
 //!
-//! The golden JSON files in `tests/golden/` were generated from the CURRENT
-//! master (pre-extraction) offline heuristic pipeline. This test re-runs
-//! `beatrice_dsp::analyze_offline` over the same deterministic fixtures and
-//! asserts the output is field-for-field identical (to 1e-6 on floats).
+//! The golden JSON files in `tests/golden/` were generated from the
+//! pre-extraction offline heuristic pipeline and re-blessed when onsets began
+//! to be localized to the transient (timestamps, and with them every window,
+//! moved). This test re-runs `beatrice_dsp::analyze_offline` over the same
+//! deterministic fixtures and asserts the output is field-for-field identical
+//! (to 1e-6 on floats). After an intended pipeline change, re-bless with
+//! `BEATRICE_BLESS_GOLDEN=1 cargo test --test offline_freeze`.
 //!
 //! Random UUIDs (`Event.id`) are NEVER compared — they differ every run — so the
 //! golden snapshots omit them entirely. Everything the pipeline *computes*
@@ -14,7 +17,7 @@ use std::fs;
 use std::path::PathBuf;
 
 use beatrice_dsp::{analyze_offline, AudioData, Event, EventClass, EventFeatures, OnsetConfig};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Fixtures are 16-bit mono PCM WAVs generated deterministically by
 /// `scripts/generate-test-audio.mjs`. We decode them here with a tiny inline
@@ -44,14 +47,14 @@ const FIXTURES: [&str; 7] = [
 const EPS_ABS: f64 = 1e-6;
 const EPS_REL: f64 = 1e-4;
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 struct GoldenScore {
     class: String,
     score: f32,
 }
 
 /// The id-free golden snapshot shape (mirrors `Event` minus the random UUID).
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 struct GoldenEvent {
     timestamp_ms: f64,
     duration_ms: f64,
@@ -174,9 +177,12 @@ fn assert_events_match_golden(events: &[Event], name: &str) {
         close(f.peak_amplitude as f64, gf.peak_amplitude as f64, "peak_amplitude");
         close(f.crest_factor as f64, gf.crest_factor as f64, "crest_factor");
 
+        // Goldens blessed before a class was added carry fewer scores: those
+        // they have must still lead the array unchanged, followed by one score
+        // per class added since.
         assert_eq!(
             ev.all_scores.len(),
-            g.all_scores.len(),
+            EventClass::COUNT,
             "[{name}][{i}] all_scores length changed"
         );
         for (j, (s, gs)) in ev.all_scores.iter().zip(g.all_scores.iter()).enumerate() {
@@ -200,12 +206,38 @@ fn assert_events_match_golden(events: &[Event], name: &str) {
     }
 }
 
+/// Write `events` as the new golden for `name`.
+fn bless(events: &[Event], name: &str) {
+    let golden: Vec<GoldenEvent> = events
+        .iter()
+        .map(|ev| GoldenEvent {
+            timestamp_ms: ev.timestamp_ms,
+            duration_ms: ev.duration_ms,
+            class: ev.class.to_string().to_owned(),
+            confidence: ev.confidence,
+            features: ev.features.clone(),
+            all_scores: ev
+                .all_scores
+                .iter()
+                .map(|s| GoldenScore { class: s.class.to_string().to_owned(), score: s.score })
+                .collect(),
+        })
+        .collect();
+    let path = golden_dir().join(format!("{name}.json"));
+    fs::write(&path, serde_json::to_string_pretty(&golden).unwrap()).unwrap();
+}
+
 #[test]
 fn offline_pipeline_frozen_against_golden() {
+    let bless_goldens = std::env::var_os("BEATRICE_BLESS_GOLDEN").is_some();
     let cfg = OnsetConfig::default();
     for name in FIXTURES {
         let audio = load_fixture(name);
         let events = analyze_offline(&audio, &cfg);
+        if bless_goldens {
+            bless(&events, name);
+            continue;
+        }
         assert_events_match_golden(&events, name);
     }
 }
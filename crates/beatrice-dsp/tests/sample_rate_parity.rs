@@ -0,0 +1,260 @@
+//! Classification parity across source sample rates.
+//!
+//! The factory model was fitted on 44.1 kHz audio. Each golden fixture is
+//! rendered at 22.05 / 48 / 96 kHz with the band-limited resampler, then run
+//! through the pipeline with rate normalization on
+//! ([`beatrice_dsp::normalize_sample_rate`] offline,
+//! [`StreamingDetector::at_model_rate`] live). Every rendition must reproduce
+//! the 44.1 kHz reference: same onsets (±[`TOLERANCE_MS`]) and same class.
+//!
+//! A 22.05 kHz source has nothing above 11 kHz, and no resampler can restore
+//! the band the model's upper MFCCs (and the hi-hat class) live in. For that
+//! rendition only the onsets are held to the bar; its class agreement is
+//! reported, not asserted.
+//!
+//! The un-normalized agreement is printed alongside for comparison — it is
+//! what the classifier saw before the conversion stage existed.
+
+use std::fs;
+use std::path::PathBuf;
+
+use beatrice_dsp::events::HybridClassifier;
+use beatrice_dsp::{
+    analyze_offline_hybrid, normalize_sample_rate, AudioData, EventClass, OnsetConfig,
+    StreamingDetector,
+};
+
+const FIXTURES: [&str; 7] = [
+    "test-pattern",
+    "test-offgrid",
+    "test-8bar-progression",
+    "test-kick",
+    "test-hihat",
+    "test-snare",
+    "test-hum",
+];
+
+/// Renditions of the 44.1 kHz fixtures.
+const RATES: [u32; 3] = [22_050, 48_000, 96_000];
+
+/// ±ms for a rendition's onset to count as the reference onset: two offline
+/// STFT hops (2 × 512 samples ≈ 23ms). Resampling perturbs the spectrum just
+/// enough to flip a near-tied flux peak to a neighbouring frame.
+const TOLERANCE_MS: f64 = 25.0;
+
+/// Onset agreement bar for band-limited (< 44.1 kHz) renditions, where the
+/// missing top octave also moves a few marginal flux peaks.
+const BAND_LIMITED_ONSET_BAR: f64 = 0.95;
+
+fn repo_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .to_path_buf()
+}
+
+/// Minimal 16-bit PCM WAV loader (mirrors `offline_freeze.rs`; keeps the DSP
+/// crate free of a `hound` dependency).
+fn load_fixture(name: &str) -> AudioData {
+    let path = repo_root().join("test-audio").join(format!("{name}.wav"));
+    let bytes = fs::read(&path).unwrap_or_else(|e| {
+        panic!("cannot read fixture {} ({e}). Run `node scripts/generate-test-audio.mjs`.", path.display())
+    });
+    assert_eq!(&bytes[0..4], b"RIFF", "not a RIFF file: {name}");
+    assert_eq!(&bytes[8..12], b"WAVE", "not a WAVE file: {name}");
+
+    let mut pos = 12;
+    let mut channels = 1u16;
+    let mut sample_rate = 44_100u32;
+    let mut bits = 16u16;
+    let mut data: &[u8] = &[];
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let size =
+            u32::from_le_bytes([bytes[pos + 4], bytes[pos + 5], bytes[pos + 6], bytes[pos + 7]])
+                as usize;
+        let body = &bytes[pos + 8..(pos + 8 + size).min(bytes.len())];
+        if id == b"fmt " {
+            channels = u16::from_le_bytes([body[2], body[3]]);
+            sample_rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+            bits = u16::from_le_bytes([body[14], body[15]]);
+        } else if id == b"data" {
+            data = body;
+        }
+        pos += 8 + size + (size & 1);
+    }
+    assert_eq!(bits, 16, "fixture {name} is not 16-bit PCM");
+    let samples: Vec<f32> = data
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+        .collect();
+    let frame_count = samples.len() / channels as usize;
+    let duration_ms = ((frame_count as f64 / sample_rate as f64) * 1000.0) as i64;
+    AudioData { samples, sample_rate, channels, bit_depth: bits, duration_ms, frame_count }
+}
+
+/// Count reference `(t_ms, class)` events reproduced by `candidate`: greedy
+/// 1:1 nearest match within ±[`TOLERANCE_MS`] and (if `same_class`) the same
+/// class.
+fn matched(
+    reference: &[(f64, EventClass)],
+    candidate: &[(f64, EventClass)],
+    same_class: bool,
+) -> usize {
+    let mut used = vec![false; candidate.len()];
+    let mut count = 0;
+    for (t, class) in reference {
+        let best = candidate
+            .iter()
+            .enumerate()
+            .filter(|(i, (ct, cc))| {
+                !used[*i] && (!same_class || cc == class) && (ct - t).abs() <= TOLERANCE_MS
+            })
+            .min_by(|(_, (a, _)), (_, (b, _))| (a - t).abs().total_cmp(&(b - t).abs()));
+        if let Some((i, _)) = best {
+            used[i] = true;
+            count += 1;
+        }
+    }
+    count
+}
+
+fn offline(audio: &AudioData, hybrid: &HybridClassifier) -> Vec<(f64, EventClass)> {
+    analyze_offline_hybrid(audio, &OnsetConfig::default(), hybrid)
+        .iter()
+        .map(|e| (e.timestamp_ms, e.class))
+        .collect()
+}
+
+fn streaming(mut det: StreamingDetector, audio: &AudioData) -> Vec<(f64, EventClass)> {
+    let mono = audio.to_mono();
+    let mut live = Vec::new();
+    for chunk in mono.chunks(128) {
+        live.extend(det.push(chunk));
+    }
+    live.extend(det.flush());
+    live.iter().map(|e| (e.t_ms, e.class)).collect()
+}
+
+/// Corpus-wide agreement per rendition rate: `matched / max(reference,
+/// candidate)` over all fixtures, so both missed and spurious events count.
+/// `onsets` is the normalized agreement with the class ignored.
+struct Agreement {
+    native: [(usize, usize); RATES.len()],
+    normalized: [(usize, usize); RATES.len()],
+    onsets: [(usize, usize); RATES.len()],
+}
+
+impl Agreement {
+    fn new() -> Self {
+        Agreement {
+            native: [(0, 0); RATES.len()],
+            normalized: [(0, 0); RATES.len()],
+            onsets: [(0, 0); RATES.len()],
+        }
+    }
+
+    fn rate(counts: (usize, usize)) -> f64 {
+        if counts.1 == 0 {
+            1.0
+        } else {
+            counts.0 as f64 / counts.1 as f64
+        }
+    }
+
+    /// Fold one fixture's rendition into the totals and print its row.
+    fn add(
+        &mut self,
+        name: &str,
+        k: usize,
+        reference: &[(f64, EventClass)],
+        native: &[(f64, EventClass)],
+        normalized: &[(f64, EventClass)],
+    ) {
+        let native_matched = matched(reference, native, true);
+        let normalized_matched = matched(reference, normalized, true);
+        self.native[k].0 += native_matched;
+        self.native[k].1 += reference.len().max(native.len());
+        self.normalized[k].0 += normalized_matched;
+        self.normalized[k].1 += reference.len().max(normalized.len());
+        self.onsets[k].0 += matched(reference, normalized, false);
+        self.onsets[k].1 += reference.len().max(normalized.len());
+        println!(
+            "  {name:<24} {:>6} Hz  reference={:<3} native={native_matched:<3} normalized={normalized_matched:<3}",
+            RATES[k],
+            reference.len(),
+        );
+    }
+
+    /// Full-band renditions (≥ 44.1 kHz) must reach `bar` with classes
+    /// compared; band-limited ones [`BAND_LIMITED_ONSET_BAR`] with onsets
+    /// only (see the module docs).
+    fn assert_parity(&self, bar: f64) {
+        for (k, rate) in RATES.into_iter().enumerate() {
+            let native = Self::rate(self.native[k]);
+            let normalized = Self::rate(self.normalized[k]);
+            let onsets = Self::rate(self.onsets[k]);
+            println!(
+                "  {:-<24} {rate:>6} Hz  native={:.1}% normalized={:.1}% onsets={:.1}%",
+                "",
+                native * 100.0,
+                normalized * 100.0,
+                onsets * 100.0
+            );
+            let (what, achieved, bar) = if rate >= beatrice_dsp::FACTORY_SAMPLE_RATE {
+                ("onset+class", normalized, bar)
+            } else {
+                ("onset", onsets, BAND_LIMITED_ONSET_BAR)
+            };
+            assert!(
+                achieved >= bar,
+                "{rate} Hz rendition: {:.1}% {what} agreement with 44.1 kHz (bar {:.0}%)",
+                achieved * 100.0,
+                bar * 100.0
+            );
+        }
+    }
+}
+
+#[test]
+fn offline_classification_is_rate_independent_when_normalized() {
+    let hybrid = HybridClassifier::factory();
+    let mut agreement = Agreement::new();
+    println!("\n=== offline parity vs 44.1 kHz (±{TOLERANCE_MS}ms, same class) ===");
+    for name in FIXTURES {
+        let source = load_fixture(name);
+        assert_eq!(source.sample_rate, 44_100, "{name}: fixtures are rendered at 44.1 kHz");
+        let reference = offline(&source, &hybrid);
+
+        for (k, rate) in RATES.into_iter().enumerate() {
+            let rendition = source.resampled(rate);
+            let native = offline(&rendition, &hybrid);
+            let normalized = offline(&normalize_sample_rate(&rendition), &hybrid);
+            agreement.add(name, k, &reference, &native, &normalized);
+        }
+    }
+    // Offline is deterministic over whole-file statistics: exact parity.
+    agreement.assert_parity(1.0);
+}
+
+#[test]
+fn streaming_classification_is_rate_independent_at_model_rate() {
+    let mut agreement = Agreement::new();
+    println!("\n=== streaming parity vs 44.1 kHz (±{TOLERANCE_MS}ms, same class) ===");
+    for name in FIXTURES {
+        let source = load_fixture(name);
+        let reference = streaming(StreamingDetector::new(source.sample_rate), &source);
+
+        for (k, rate) in RATES.into_iter().enumerate() {
+            let rendition = source.resampled(rate);
+            let native = streaming(StreamingDetector::new(rate), &rendition);
+            let normalized = streaming(StreamingDetector::at_model_rate(rate), &rendition);
+            agreement.add(name, k, &reference, &native, &normalized);
+        }
+    }
+    // The rolling 2s threshold can promote a borderline flux peak into an
+    // extra onset; everything the reference detected must still be there.
+    agreement.assert_parity(0.98);
+}
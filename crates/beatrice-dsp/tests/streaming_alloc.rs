@@ -0,0 +1,156 @@
+//! The streaming hot path is allocation-free.
+//!
+//! The worklet calls [`StreamingDetector::push_into`] on the audio thread
+//! every 128-sample render quantum, where an allocation can stall the
+//! callback. A counting global allocator records every allocation made on
+//! the pushing thread after the detector is built, across a long synthetic
+//! stream of kicks, hats, snares and hums over a noise floor, for each
+//! detector setup the worklet uses and for each onset function and
+//! allocation-free classifier.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use beatrice_dsp::{
+    ConditioningConfig, LiveEvent, OnsetFunction, StreamingConfig, StreamingDetector,
+    FACTORY_SAMPLE_RATE, PROVISIONAL_WINDOW_MS,
+};
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Count only this thread's allocations, and only while set: the test
+    /// harness allocates on other threads at the same time.
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+fn record() {
+    if COUNTING.with(Cell::get) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Render quantum of an AudioWorklet.
+const QUANTUM: usize = 128;
+
+/// Length of the synthetic stream.
+const SECONDS: f64 = 30.0;
+
+/// Kicks, hats, snares and hums cycling every 250ms over low noise.
+fn synthetic_stream(sample_rate: u32) -> Vec<f32> {
+    let sr = sample_rate as f64;
+    let step = (sr * 0.25) as usize;
+    let mut state = 0x9e37_79b9_u32;
+    let mut noise = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        (state as f32 / u32::MAX as f32) * 2.0 - 1.0
+    };
+    (0..(SECONDS * sr) as usize)
+        .map(|i| {
+            let hit = i / step;
+            let t = (i % step) as f64 / sr;
+            let decay = |tau: f64| (-t / tau).exp() as f32;
+            let tone = |hz: f64| (2.0 * std::f64::consts::PI * hz * t).sin() as f32;
+            let sound = match hit % 4 {
+                0 => 0.9 * decay(0.04) * tone(150.0 - 600.0 * t.min(0.1)),
+                1 => 0.5 * decay(0.015) * noise(),
+                2 => 0.6 * decay(0.05) * (0.6 * noise() + 0.4 * tone(200.0)),
+                _ => 0.4 * (t < 0.2) as u8 as f32 * tone(180.0),
+            };
+            sound + 0.002 * noise()
+        })
+        .collect()
+}
+
+/// Push `stream` through `det` a quantum at a time, counting allocations.
+/// Returns the allocation count and the events emitted.
+fn count_allocations(mut det: StreamingDetector, stream: &[f32]) -> (usize, usize) {
+    let mut events: Vec<LiveEvent> = Vec::with_capacity(64);
+    let mut emitted = 0;
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    COUNTING.with(|c| c.set(true));
+    for quantum in stream.chunks(QUANTUM) {
+        det.push_into(quantum, &mut events);
+        emitted += events.len();
+        events.clear();
+    }
+    COUNTING.with(|c| c.set(false));
+    (ALLOCATIONS.load(Ordering::Relaxed) - before, emitted)
+}
+
+fn assert_allocation_free(name: &str, det: StreamingDetector, stream: &[f32]) {
+    let (allocations, emitted) = count_allocations(det, stream);
+    // One event per 250ms hit, give or take the detector's judgement.
+    assert!(emitted as f64 > SECONDS * 2.0, "{name}: only {emitted} events");
+    assert_eq!(allocations, 0, "{name}: {allocations} allocations over {emitted} events");
+}
+
+#[test]
+fn default_detector_does_not_allocate() {
+    let stream = synthetic_stream(44_100);
+    for name in ["hybrid", "gaussian", "heuristic"] {
+        let mut det = StreamingDetector::new(44_100);
+        det.set_classifier(name).unwrap();
+        assert_allocation_free(name, det, &stream);
+    }
+}
+
+#[test]
+fn every_onset_function_is_allocation_free() {
+    let stream = synthetic_stream(44_100);
+    for onset_function in OnsetFunction::ALL {
+        let cfg = StreamingConfig { onset_function, ..StreamingConfig::default() };
+        let det = StreamingDetector::with_config(44_100, cfg);
+        assert_allocation_free(&format!("{onset_function:?}"), det, &stream);
+    }
+}
+
+#[test]
+fn resampling_and_conditioning_do_not_allocate() {
+    let stream = synthetic_stream(48_000);
+    assert_allocation_free("at_model_rate", StreamingDetector::at_model_rate(48_000), &stream);
+    let cfg = StreamingConfig {
+        analysis_sample_rate: Some(FACTORY_SAMPLE_RATE),
+        conditioning: Some(ConditioningConfig::default()),
+        ..StreamingConfig::default()
+    };
+    assert_allocation_free("conditioned", StreamingDetector::with_config(48_000, cfg), &stream);
+}
+
+#[test]
+fn provisional_emission_does_not_allocate() {
+    // The worklet's setup: model rate, then two-stage emission switched on.
+    let stream = synthetic_stream(48_000);
+    let mut det = StreamingDetector::at_model_rate(48_000);
+    det.set_provisional_window_ms(Some(PROVISIONAL_WINDOW_MS));
+    assert_allocation_free("provisional", det, &stream);
+}
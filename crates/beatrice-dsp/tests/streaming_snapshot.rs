@@ -0,0 +1,227 @@
+//! A [`StreamingDetector`] snapshot resumes the stream exactly.
+//!
+//! Each fixture is pushed in a pseudo-random chunk schedule, once
+//! uninterrupted and once snapshotted — serialized to bytes, restored into a
+//! fresh detector, and continued — at several arbitrary sample positions.
+//! Both runs must emit byte-identical events (compared through their `Debug`
+//! form, which prints every float's exact value), tempo and flush tail, for
+//! each detector setup the worklet uses and for each onset function with
+//! state of its own.
+
+use std::fs;
+use std::path::PathBuf;
+
+use beatrice_dsp::{
+    AudioData, CalibrationSample, ConditioningConfig, LiveEvent, LiveStage, OnsetFunction, StreamingConfig,
+    StreamingDetector, StreamingSnapshot, FACTORY_SAMPLE_RATE, PROVISIONAL_WINDOW_MS,
+};
+
+/// The multi-event fixtures: the onsets, tempo and pending windows a split
+/// has to land between.
+const FIXTURES: [&str; 3] = ["test-pattern", "test-offgrid", "test-8bar-progression"];
+
+/// Snapshots taken per run.
+const SPLITS: usize = 5;
+
+fn repo_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .to_path_buf()
+}
+
+/// Minimal 16-bit PCM WAV loader (mirrors `streaming_tolerance.rs`).
+fn load_fixture(name: &str) -> AudioData {
+    let path = repo_root().join("test-audio").join(format!("{name}.wav"));
+    let bytes = fs::read(&path).unwrap_or_else(|e| {
+        panic!("cannot read fixture {} ({e}). Run `node scripts/generate-test-audio.mjs`.", path.display())
+    });
+    assert_eq!(&bytes[0..4], b"RIFF", "not a RIFF file: {name}");
+    assert_eq!(&bytes[8..12], b"WAVE", "not a WAVE file: {name}");
+
+    let mut pos = 12;
+    let mut channels = 1u16;
+    let mut sample_rate = 44_100u32;
+    let mut bits = 16u16;
+    let mut data: &[u8] = &[];
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let size =
+            u32::from_le_bytes([bytes[pos + 4], bytes[pos + 5], bytes[pos + 6], bytes[pos + 7]])
+                as usize;
+        let body = &bytes[pos + 8..(pos + 8 + size).min(bytes.len())];
+        if id == b"fmt " {
+            channels = u16::from_le_bytes([body[2], body[3]]);
+            sample_rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+            bits = u16::from_le_bytes([body[14], body[15]]);
+        } else if id == b"data" {
+            data = body;
+        }
+        pos += 8 + size + (size & 1);
+    }
+    assert_eq!(bits, 16, "fixture {name} is not 16-bit PCM");
+    let samples: Vec<f32> = data
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+        .collect();
+    let frame_count = samples.len() / channels as usize;
+    let duration_ms = ((frame_count as f64 / sample_rate as f64) * 1000.0) as i64;
+    AudioData { samples, sample_rate, channels, bit_depth: bits, duration_ms, frame_count }
+}
+
+/// Deterministic xorshift, so a failing split position reproduces.
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
+
+/// Chunk lengths covering `len` samples: mostly render quanta, with odd
+/// sizes so splits land mid-quantum.
+fn chunk_schedule(len: usize, rng: &mut Rng) -> Vec<usize> {
+    let mut chunks = Vec::new();
+    let mut pushed = 0;
+    while pushed < len {
+        let size = if rng.below(4) == 0 { 1 + rng.below(700) } else { 128 };
+        chunks.push(size.min(len - pushed));
+        pushed += size;
+    }
+    chunks
+}
+
+/// A common AudioContext rate, which the worklet resamples down to the model
+/// rate.
+const DEVICE_RATE: u32 = 48_000;
+
+/// The setups the worklet builds: plain, a 48 kHz device resampled to the
+/// model rate (what `WasmDetector.at_model_rate` runs), and fully conditioned
+/// with provisional emission, plus the onset functions whose state spans
+/// several frames. Each comes with the input rate to render the fixture at,
+/// if not its own.
+fn setups() -> Vec<(&'static str, Option<u32>, StreamingConfig)> {
+    vec![
+        ("default", None, StreamingConfig::default()),
+        (
+            "48k device at model rate",
+            Some(DEVICE_RATE),
+            // `StreamingDetector::at_model_rate`'s configuration
+            StreamingConfig { analysis_sample_rate: Some(FACTORY_SAMPLE_RATE), ..StreamingConfig::default() },
+        ),
+        (
+            "conditioned + provisional",
+            None,
+            StreamingConfig {
+                conditioning: Some(ConditioningConfig::default()),
+                provisional_window_ms: Some(PROVISIONAL_WINDOW_MS),
+                ..StreamingConfig::default()
+            },
+        ),
+        (
+            "superflux",
+            None,
+            StreamingConfig { onset_function: OnsetFunction::SuperFlux, ..StreamingConfig::default() },
+        ),
+        (
+            "complex domain",
+            None,
+            StreamingConfig { onset_function: OnsetFunction::ComplexDomain, ..StreamingConfig::default() },
+        ),
+    ]
+}
+
+/// Teach the detector the first events it emits, as the calibration panel
+/// does, so the profile and the adapted model are part of the state too.
+fn teach(det: &mut StreamingDetector, events: &[LiveEvent], taught: &mut usize) {
+    for e in events.iter().filter(|e| e.stage != LiveStage::Provisional) {
+        if *taught == 24 {
+            det.set_calibration_enabled(true);
+        }
+        if *taught < 24 {
+            let sample = CalibrationSample::with_mfcc(
+                e.class,
+                e.features.clone(),
+                e.mfcc.to_vec(),
+                Vec::new(),
+                det.sample_rate(),
+            );
+            det.add_calibration_sample(sample);
+        }
+        *taught += 1;
+    }
+}
+
+/// Everything a run emits, in `Debug` form: events, the tempo after each
+/// chunk that emitted, and the flush tail.
+fn run(
+    input_rate: u32,
+    cfg: &StreamingConfig,
+    mono: &[f32],
+    chunks: &[usize],
+    splits: &[usize],
+    snapshot_bytes: &mut Vec<usize>,
+) -> Vec<String> {
+    let mut det = StreamingDetector::with_config(input_rate, cfg.clone());
+    let mut trace = Vec::new();
+    let mut taught = 0;
+    let mut pos = 0;
+    for (i, &len) in chunks.iter().enumerate() {
+        if splits.contains(&i) {
+            let bytes = det.snapshot().to_json_bytes().expect("snapshot serializes");
+            snapshot_bytes.push(bytes.len());
+            let snapshot = StreamingSnapshot::from_json_bytes(&bytes).expect("snapshot deserializes");
+            det = StreamingDetector::restore(snapshot).expect("snapshot restores");
+        }
+        let events = det.push(&mono[pos..pos + len]);
+        pos += len;
+        teach(&mut det, &events, &mut taught);
+        if !events.is_empty() {
+            trace.push(format!("{:?}", det.tempo()));
+        }
+        trace.extend(events.iter().map(|e| format!("{e:?}")));
+    }
+    trace.extend(det.flush().iter().map(|e| format!("{e:?}")));
+    trace
+}
+
+#[test]
+fn restored_snapshots_resume_byte_identically() {
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+    let mut snapshot_bytes = Vec::new();
+    for name in FIXTURES {
+        let fixture = load_fixture(name);
+        for (setup, device_rate, cfg) in setups() {
+            let audio = match device_rate {
+                Some(rate) => fixture.resampled(rate),
+                None => fixture.clone(),
+            };
+            let mono = audio.to_mono();
+            let chunks = chunk_schedule(mono.len(), &mut rng);
+            let mut splits: Vec<usize> = (0..SPLITS).map(|_| rng.below(chunks.len())).collect();
+            // Also before the first sample, while the leading-onset check
+            // still needs the stream head.
+            splits.push(0);
+            let uninterrupted = run(audio.sample_rate, &cfg, &mono, &chunks, &[], &mut Vec::new());
+            let resumed = run(audio.sample_rate, &cfg, &mono, &chunks, &splits, &mut snapshot_bytes);
+            assert!(uninterrupted.len() > 4, "{name} / {setup}: too few events to test");
+            assert_eq!(
+                resumed.len(),
+                uninterrupted.len(),
+                "{name} / {setup}: split at chunks {splits:?} changed the event count"
+            );
+            for (k, (a, b)) in uninterrupted.iter().zip(&resumed).enumerate() {
+                assert_eq!(a, b, "{name} / {setup}: split at chunks {splits:?} diverged at output {k}");
+            }
+        }
+    }
+    // Compact: the ring tail, not the 4s ring (~700KB of f32 at 44.1kHz).
+    let largest = snapshot_bytes.iter().copied().max().unwrap_or(0);
+    println!("largest snapshot: {largest} bytes");
+    assert!(largest < 256 * 1024, "snapshot of {largest} bytes is not compact");
+}
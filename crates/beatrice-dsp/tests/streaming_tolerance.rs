@@ -1,5 +1,6 @@
 //! THE SPEC BAR (§5.1): the causal [`StreamingDetector`] must reproduce the
-//! offline detector's onsets within ±20ms AND the same class on ≥95% of onsets,
+//! offline detector's onsets within ±20ms (held here to ±10ms) AND the same
+//! class on ≥95% of onsets,
 //! across the fixture corpus. Offline stays the source of truth; this test only
 //! asserts streaming does not drift away from it.
 //!
@@ -13,7 +14,10 @@ use std::fs;
 use std::path::PathBuf;
 
 use beatrice_dsp::events::HybridClassifier;
-use beatrice_dsp::{analyze_offline_hybrid, AudioData, LiveEvent, OnsetConfig, StreamingDetector};
+use beatrice_dsp::{
+    analyze_offline_hybrid, AudioData, LiveEvent, LiveStage, OnsetConfig, StreamingConfig, StreamingDetector,
+    PROVISIONAL_WINDOW_MS,
+};
 
 /// The corpus. Multi-event patterns are the meaningful tolerance surface; the
 /// single-hit fixtures pin per-class alignment at t≈0.
@@ -28,7 +32,9 @@ const FIXTURES: [&str; 7] = [
 ];
 
 /// ±ms window for a streaming event to count as matching an offline onset.
-const TOLERANCE_MS: f64 = 20.0;
+/// Tighter than the spec's ±20ms: both detectors localize onsets with the
+/// same time-domain step, so they disagree by a few ms at most.
+const TOLERANCE_MS: f64 = 10.0;
 /// Corpus-wide pass bar.
 const BAR: f64 = 0.95;
 
@@ -84,8 +90,12 @@ fn load_fixture(name: &str) -> AudioData {
 /// Run streaming over the fixture in 128-sample render quanta (the worklet's
 /// real chunk size).
 fn run_streaming(audio: &AudioData) -> Vec<LiveEvent> {
+    run_streaming_with(audio, StreamingConfig::default())
+}
+
+fn run_streaming_with(audio: &AudioData, cfg: StreamingConfig) -> Vec<LiveEvent> {
     let mono = audio.to_mono();
-    let mut det = StreamingDetector::new(audio.sample_rate);
+    let mut det = StreamingDetector::with_config(audio.sample_rate, cfg);
     let mut live = Vec::new();
     for chunk in mono.chunks(128) {
         live.extend(det.push(chunk));
@@ -154,3 +164,68 @@ fn streaming_matches_offline_within_tolerance() {
         BAR * 100.0
     );
 }
+
+/// Ceiling on the share of provisional classes the full window overturns
+/// (45% at the time of writing). Nearly all revisions are the generated hats,
+/// which read as hats over their first 50ms and as rimshots over the full
+/// 150ms (as offline reports them); kicks and snares hold. A regression past
+/// this means the provisional preview has become noise.
+const REVISION_BAR: f64 = 0.5;
+
+#[test]
+fn provisional_classes_rarely_change() {
+    let cfg = StreamingConfig { provisional_window_ms: Some(PROVISIONAL_WINDOW_MS), ..StreamingConfig::default() };
+    let mut total_provisional = 0usize;
+    let mut total_revised = 0usize;
+
+    println!("\n=== provisional ({PROVISIONAL_WINDOW_MS}ms) vs full-window classes ===");
+    for name in FIXTURES {
+        let audio = load_fixture(name);
+        let reference = run_streaming(&audio);
+        let staged = run_streaming_with(&audio, cfg.clone());
+
+        // Replay the two-stage stream the way the UI does: provisional events
+        // appear, revisions overwrite them by t_ms.
+        let mut applied: Vec<LiveEvent> = Vec::new();
+        let (mut provisional, mut revised) = (0usize, 0usize);
+        for e in staged {
+            match e.stage {
+                LiveStage::Provisional => {
+                    provisional += 1;
+                    applied.push(e);
+                }
+                LiveStage::Revised => {
+                    revised += 1;
+                    let earlier = applied.iter_mut().rfind(|a| a.t_ms == e.t_ms);
+                    let earlier = earlier.unwrap_or_else(|| panic!("{name}: revision at {}ms revises nothing", e.t_ms));
+                    assert_eq!(earlier.stage, LiveStage::Provisional, "{name}: onset at {}ms revised twice", e.t_ms);
+                    assert_ne!(earlier.class, e.class, "{name}: revision at {}ms changes nothing", e.t_ms);
+                    *earlier = e;
+                }
+                // Only an onset the stream cut short skips its provisional.
+                LiveStage::Final => applied.push(e),
+            }
+        }
+
+        // Applying the revisions lands exactly on the single-stage verdicts.
+        assert_eq!(applied.len(), reference.len(), "{name}: onset count differs");
+        for (a, r) in applied.iter().zip(&reference) {
+            assert_eq!((a.t_ms, a.class), (r.t_ms, r.class), "{name}: final verdict differs");
+        }
+
+        total_provisional += provisional;
+        total_revised += revised;
+        let rate = if provisional == 0 { 0.0 } else { revised as f64 / provisional as f64 };
+        println!("  {name:<24} provisional={provisional:<3} revised={revised:<3} rate={:.0}%", rate * 100.0);
+    }
+
+    let rate = if total_provisional == 0 { 0.0 } else { total_revised as f64 / total_provisional as f64 };
+    println!("  {:-<24} corpus revised={total_revised}/{total_provisional} rate={:.1}%\n", "", rate * 100.0);
+    assert!(total_provisional > 0, "no provisional events on the corpus");
+    assert!(
+        rate <= REVISION_BAR,
+        "{:.1}% of provisional classes were revised (bar: {:.0}%)",
+        rate * 100.0,
+        REVISION_BAR * 100.0
+    );
+}
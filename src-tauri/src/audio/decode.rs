@@ -0,0 +1,378 @@
+// Compressed audio decoding
+// Demuxes and decodes FLAC, MP3, Ogg (Vorbis/Opus), MP4/M4A (AAC/ALAC) and
+// WebM/Matroska with symphonia. symphonia has no Opus codec, so Opus packets
+// (Ogg-Opus voice memos, browser MediaRecorder WebM) are decoded separately.
+// Decoding is packet-by-packet behind `CompressedSource`, so long recordings
+// can be analysed without holding the whole PCM stream.
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use beatrice_dsp::AudioSource;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{
+    CodecParameters, Decoder, DecoderOptions, CODEC_TYPE_NULL, CODEC_TYPE_OPUS,
+};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use super::ingest::{read_to_end, AudioData, AudioError, AudioFormat};
+
+/// Decode a compressed container into interleaved f32 samples in [-1.0, 1.0],
+/// the same normalization `ingest_wav` applies to PCM.
+pub(crate) fn decode_compressed(data: &[u8], format: AudioFormat) -> Result<AudioData, AudioError> {
+    let source = CompressedSource::open(Arc::from(data), format)?;
+    let bit_depth = source.bit_depth;
+    read_to_end(source, bit_depth)
+}
+
+enum Codec {
+    Symphonia {
+        decoder: Box<dyn Decoder>,
+        buf: Option<SampleBuffer<f32>>,
+    },
+    #[cfg(feature = "opus")]
+    Opus { decoder: Box<ropus::Decoder>, pcm: Vec<f32> },
+}
+
+/// Chunked reader over a compressed container. The compressed bytes stay in
+/// memory (an order of magnitude smaller than the PCM); packets are decoded
+/// only as `read` needs them.
+pub struct CompressedSource {
+    data: Arc<[u8]>,
+    format: AudioFormat,
+    reader: Box<dyn FormatReader>,
+    track_id: u32,
+    codec: Codec,
+    sample_rate: u32,
+    channels: u16,
+    /// Set once the first packet has decoded: `sample_rate` and `channels`
+    /// are then the stream's, and `read` de-interleaves with that stride.
+    spec_locked: bool,
+    /// Lossless codecs keep their source depth; lossy ones decode straight
+    /// to float, so they report 32-bit like a float WAV.
+    bit_depth: u16,
+    total_frames: Option<usize>,
+    /// Decoded interleaved samples not handed out yet.
+    pending: Vec<f32>,
+    /// Interleaved samples still to drop from the front of the stream
+    /// (Opus pre-skip, MP4 encoder priming).
+    skip: usize,
+    ended: bool,
+}
+
+impl CompressedSource {
+    pub fn open(data: Arc<[u8]>, format: AudioFormat) -> Result<Self, AudioError> {
+        let mut hint = Hint::new();
+        hint.with_extension(format.extension());
+
+        // Gapless mode trims MP3 encoder delay/padding (LAME/Xing tag) so onset
+        // times line up with the original recording instead of shifting by ~25-50ms.
+        let format_opts = FormatOptions { enable_gapless: true, ..Default::default() };
+        let stream = MediaSourceStream::new(Box::new(Cursor::new(data.clone())), Default::default());
+        let probed = symphonia::default::get_probe()
+            .format(&hint, stream, &format_opts, &MetadataOptions::default())
+            .map_err(|e| decode_error(format, e))?;
+        let reader = probed.format;
+
+        let track = reader
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or_else(|| AudioError::UnsupportedFormat(format!("{} file has no audio track", format.name())))?;
+        let track_id = track.id;
+        let params = track.codec_params.clone();
+
+        let (codec, sample_rate, channels, pre_skip) = if params.codec == CODEC_TYPE_OPUS {
+            open_opus(&params, format)?
+        } else {
+            let decoder = symphonia::default::get_codecs()
+                .make(&params, &DecoderOptions::default())
+                .map_err(|e| decode_error(format, e))?;
+            let channels = params.channels.map(|c| c.count() as u16).unwrap_or(0);
+            (Codec::Symphonia { decoder, buf: None }, params.sample_rate.unwrap_or(0), channels, 0)
+        };
+
+        let mut source = CompressedSource {
+            data,
+            format,
+            reader,
+            track_id,
+            codec,
+            sample_rate,
+            channels,
+            spec_locked: false,
+            bit_depth: params.bits_per_sample.map(|b| b as u16).unwrap_or(32),
+            total_frames: None,
+            pending: Vec::new(),
+            skip: 0,
+            ended: false,
+        };
+
+        // Decode up to the first audio, so the stream's real rate and layout
+        // are known. A container that yields no audio at all (truncated
+        // download, header only) is broken, not an empty recording.
+        while source.pending.is_empty() && !source.ended {
+            source.decode_packet()?;
+        }
+        if source.channels == 0 || source.sample_rate == 0 || source.pending.is_empty() {
+            return Err(AudioError::InvalidData);
+        }
+
+        // symphonia parses but does not apply MP4 edit lists, so AAC priming
+        // (~2048 samples) would otherwise delay every onset by ~46ms.
+        let priming = if format == AudioFormat::Mp4 {
+            mp4_priming_frames(&source.data, source.sample_rate)
+        } else {
+            0
+        };
+        source.skip = (pre_skip + priming) * source.channels as usize;
+        source.total_frames = params.n_frames.map(|n| (n as usize).saturating_sub(pre_skip + priming));
+        source.apply_skip();
+        Ok(source)
+    }
+
+    /// Decode the next packet onto `pending`; sets `ended` at end of stream.
+    fn decode_packet(&mut self) -> Result<(), AudioError> {
+        let format = self.format;
+        let Some(packet) = next_packet(self.reader.as_mut(), self.track_id, format)? else {
+            self.ended = true;
+            return Ok(());
+        };
+
+        match &mut self.codec {
+            Codec::Symphonia { decoder, buf } => {
+                let decoded = match decoder.decode(&packet) {
+                    Ok(decoded) => decoded,
+                    // A corrupt frame is skipped, as a player would, rather than
+                    // failing the whole file.
+                    Err(SymphoniaError::DecodeError(e)) => {
+                        log::warn!("Skipping undecodable {} frame: {}", format.name(), e);
+                        return Ok(());
+                    }
+                    Err(e) => return Err(decode_error(format, e)),
+                };
+                let spec = *decoded.spec();
+                let channels = spec.channels.count() as u16;
+                if !self.spec_locked {
+                    // Container parameters can be missing or approximate; the
+                    // first decoded packet says what the stream really is.
+                    self.sample_rate = spec.rate;
+                    self.channels = channels;
+                    self.spec_locked = true;
+                } else if spec.rate != self.sample_rate || channels != self.channels {
+                    // The layout or rate changed mid-stream. `read` hands out
+                    // one fixed stride, so splicing this in would garble
+                    // everything after it.
+                    return Err(AudioError::DecodeError(format!(
+                        "{}: stream changes from {} Hz, {} ch to {} Hz, {} ch mid-file",
+                        format.name(),
+                        self.sample_rate,
+                        self.channels,
+                        spec.rate,
+                        channels
+                    )));
+                }
+
+                let needed = decoded.capacity() * self.channels as usize;
+                if !matches!(buf, Some(b) if b.capacity() >= needed) {
+                    *buf = Some(SampleBuffer::new(decoded.capacity() as u64, spec));
+                }
+                let buf = buf.as_mut().expect("sample buffer allocated above");
+                buf.copy_interleaved_ref(decoded);
+                self.pending.extend_from_slice(buf.samples());
+            }
+            #[cfg(feature = "opus")]
+            Codec::Opus { decoder, pcm } => {
+                let frames = match decoder.decode_float(&packet.data, pcm, ropus::DecodeMode::Normal) {
+                    Ok(frames) => frames,
+                    Err(e) => {
+                        log::warn!("Skipping undecodable {} Opus packet: {:?}", format.name(), e);
+                        return Ok(());
+                    }
+                };
+                self.pending.extend_from_slice(&pcm[..frames * self.channels as usize]);
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_skip(&mut self) {
+        let skip = self.skip.min(self.pending.len());
+        self.pending.drain(..skip);
+        self.skip -= skip;
+    }
+}
+
+impl AudioSource for CompressedSource {
+    type Error = AudioError;
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn total_frames(&self) -> Option<usize> {
+        self.total_frames
+    }
+
+    fn read(&mut self, buf: &mut Vec<f32>, max_frames: usize) -> Result<usize, AudioError> {
+        let channels = self.channels as usize;
+        let wanted = max_frames * channels;
+        while self.pending.len() < wanted && !self.ended {
+            self.decode_packet()?;
+            self.apply_skip();
+        }
+        let take = wanted.min(self.pending.len()) / channels * channels;
+        buf.extend(self.pending.drain(..take));
+        Ok(take / channels)
+    }
+
+    fn rewind(&mut self) -> Result<(), AudioError> {
+        *self = CompressedSource::open(self.data.clone(), self.format)?;
+        Ok(())
+    }
+}
+
+fn decode_error(format: AudioFormat, e: SymphoniaError) -> AudioError {
+    match e {
+        SymphoniaError::Unsupported(what) => {
+            AudioError::UnsupportedFormat(format!("{} ({})", format.name(), what))
+        }
+        other => AudioError::DecodeError(format!("{}: {}", format.name(), other)),
+    }
+}
+
+/// Read the next packet of `track_id`, or `None` at end of stream.
+fn next_packet(
+    reader: &mut dyn FormatReader,
+    track_id: u32,
+    format: AudioFormat,
+) -> Result<Option<symphonia::core::formats::Packet>, AudioError> {
+    loop {
+        match reader.next_packet() {
+            Ok(packet) if packet.track_id() == track_id => return Ok(Some(packet)),
+            Ok(_) => continue,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                return Ok(None)
+            }
+            // A chained Ogg stream or a new Matroska segment: keep what we have.
+            Err(SymphoniaError::ResetRequired) => return Ok(None),
+            Err(e) => return Err(decode_error(format, e)),
+        }
+    }
+}
+
+/// Iterate the ISO-BMFF boxes in `data` as (type, body) pairs.
+fn mp4_boxes(mut data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    std::iter::from_fn(move || {
+        if data.len() < 8 {
+            return None;
+        }
+        let size = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as u64;
+        let (header, size) = match size {
+            0 => (8, data.len() as u64),
+            1 if data.len() >= 16 => {
+                let mut large = [0u8; 8];
+                large.copy_from_slice(&data[8..16]);
+                (16, u64::from_be_bytes(large))
+            }
+            _ => (8, size),
+        };
+        if size < header as u64 || size > data.len() as u64 {
+            return None;
+        }
+        let (item, rest) = data.split_at(size as usize);
+        data = rest;
+        Some((&item[4..8], &item[header..]))
+    })
+}
+
+/// Leading frames the first audio track's edit list skips (the encoder
+/// priming), converted from the media timescale to `sample_rate`.
+fn mp4_priming_frames(data: &[u8], sample_rate: u32) -> usize {
+    let child = |body: &[u8], kind: &[u8]| mp4_boxes(body).find(|(t, _)| *t == kind).map(|(_, b)| b.to_vec());
+    let Some(moov) = child(data, b"moov") else { return 0 };
+    for (kind, trak) in mp4_boxes(&moov) {
+        if kind != b"trak" {
+            continue;
+        }
+        let Some(mdia) = child(trak, b"mdia") else { continue };
+        if !child(&mdia, b"hdlr").is_some_and(|h| h.len() >= 12 && &h[8..12] == b"soun") {
+            continue;
+        }
+        let timescale = child(&mdia, b"mdhd").and_then(|h| match h.first() {
+            Some(0) if h.len() >= 16 => Some(u32::from_be_bytes([h[12], h[13], h[14], h[15]]) as u64),
+            Some(1) if h.len() >= 24 => Some(u32::from_be_bytes([h[20], h[21], h[22], h[23]]) as u64),
+            _ => None,
+        });
+        let Some(timescale) = timescale else { continue };
+        let Some(elst) = child(trak, b"edts").and_then(|edts| child(&edts, b"elst")) else { return 0 };
+
+        // First non-empty edit; an empty edit (media_time -1) only inserts a delay.
+        let version = elst.first().copied().unwrap_or(0);
+        let entry_len = if version == 1 { 20 } else { 12 };
+        for entry in elst.get(8..).unwrap_or(&[]).chunks_exact(entry_len) {
+            let media_time = if version == 1 {
+                i64::from_be_bytes(entry[8..16].try_into().expect("8-byte slice"))
+            } else {
+                i32::from_be_bytes(entry[4..8].try_into().expect("4-byte slice")) as i64
+            };
+            if media_time >= 0 && timescale > 0 {
+                return (media_time as u64 * sample_rate as u64 / timescale) as usize;
+            }
+        }
+        return 0;
+    }
+    0
+}
+
+/// Opus always decodes at 48kHz. The OpusHead pre-skip (encoder lookahead) is
+/// dropped from the front, per RFC 7845 §4.2. Returns the decoder with the
+/// stream's rate, channel count and pre-skip in frames.
+#[cfg(feature = "opus")]
+fn open_opus(params: &CodecParameters, format: AudioFormat) -> Result<(Codec, u32, u16, usize), AudioError> {
+    use ropus::{Channels, Decoder};
+
+    const OPUS_RATE: u32 = 48_000;
+    /// Longest Opus packet: 120ms at 48kHz
+    const MAX_PACKET_FRAMES: usize = 5760;
+
+    let head = params.extra_data.as_deref().unwrap_or(&[]);
+    let (channels, pre_skip) = if head.len() >= 19 && head.starts_with(b"OpusHead") {
+        if head[18] != 0 {
+            return Err(AudioError::UnsupportedFormat(format!(
+                "{} multichannel Opus (mapping family {})",
+                format.name(),
+                head[18]
+            )));
+        }
+        (head[9] as u16, u16::from_le_bytes([head[10], head[11]]) as usize)
+    } else {
+        let channels = params.channels.map(|c| c.count() as u16).unwrap_or(0);
+        (channels, params.delay.unwrap_or(0) as usize)
+    };
+    if !(1..=2).contains(&channels) {
+        return Err(AudioError::UnsupportedFormat(format!("{} Opus with {} channels", format.name(), channels)));
+    }
+
+    let layout = if channels == 2 { Channels::Stereo } else { Channels::Mono };
+    let decoder = Decoder::new(OPUS_RATE, layout)
+        .map_err(|e| AudioError::DecodeError(format!("{}: {:?}", format.name(), e)))?;
+    let pcm = vec![0.0f32; MAX_PACKET_FRAMES * channels as usize];
+    Ok((Codec::Opus { decoder: Box::new(decoder), pcm }, OPUS_RATE, channels, pre_skip))
+}
+
+#[cfg(not(feature = "opus"))]
+fn open_opus(_params: &CodecParameters, format: AudioFormat) -> Result<(Codec, u32, u16, usize), AudioError> {
+    Err(AudioError::UnsupportedFormat(format!(
+        "{} Opus (built without the `opus` feature)",
+        format.name()
+    )))
+}
@@ -1,11 +1,17 @@
 // Audio ingestion module
 // Reads WAV files, extracts metadata, and normalizes audio samples.
+// `ingest_audio` sniffs the container and also accepts compressed input
+// (FLAC, MP3, Ogg, M4A, WebM), decoded in `super::decode`. `open_audio` is the
+// chunked counterpart for recordings too long to decode in one piece.
 //
 // The `AudioData` container itself now lives in the `beatrice-dsp` crate (shared
 // with the WASM worklet); only WAV *decoding* (which needs `hound`) stays here.
 
-use hound::{WavReader, SampleFormat};
-use std::io::Cursor;
+use beatrice_dsp::AudioSource;
+use hound::{SampleFormat, WavReader, WavSpec};
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
 use thiserror::Error;
 
 // Re-export the shared container so existing `crate::audio::AudioData` paths and
@@ -17,76 +23,131 @@ pub enum AudioError {
     #[error("Failed to read WAV file: {0}")]
     WavReadError(#[from] hound::Error),
 
+    #[error("Failed to read audio file: {0}")]
+    IoError(#[from] std::io::Error),
+
     #[error("Unsupported audio format: {0}")]
     UnsupportedFormat(String),
 
+    #[error("Failed to decode audio: {0}")]
+    DecodeError(String),
+
     #[error("Invalid audio data")]
     InvalidData,
 }
 
-/// Ingest a WAV file from raw bytes
-/// Returns AudioData with normalized samples and metadata
-pub fn ingest_wav(data: &[u8]) -> Result<AudioData, AudioError> {
-    let cursor = Cursor::new(data);
-    let mut reader = WavReader::new(cursor)?;
-
-    let spec = reader.spec();
-    let sample_rate = spec.sample_rate;
-    let channels = spec.channels;
-    let bit_depth = spec.bits_per_sample;
-    let sample_format = spec.sample_format;
-
-    // Read and normalize samples to f32 [-1.0, 1.0]
-    let samples: Vec<f32> = match (sample_format, bit_depth) {
-        (SampleFormat::Int, 8) => {
-            // 8-bit PCM: unsigned, range [0, 255] -> [-1.0, 1.0]
-            reader
-                .samples::<i32>()
-                .collect::<Result<Vec<_>, _>>()?
-                .into_iter()
-                .map(|s| (s as f32 - 128.0) / 128.0)
-                .collect()
-        }
-        (SampleFormat::Int, 16) => {
-            // 16-bit PCM: signed, range [-32768, 32767] -> [-1.0, 1.0]
-            reader
-                .samples::<i16>()
-                .collect::<Result<Vec<_>, _>>()?
-                .into_iter()
-                .map(|s| s as f32 / 32768.0)
-                .collect()
-        }
-        (SampleFormat::Int, 24) => {
-            // 24-bit PCM: signed, range [-8388608, 8388607] -> [-1.0, 1.0]
-            reader
-                .samples::<i32>()
-                .collect::<Result<Vec<_>, _>>()?
-                .into_iter()
-                .map(|s| s as f32 / 8388608.0)
-                .collect()
+/// Containers `ingest_audio` recognises from their leading bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    Wav,
+    Flac,
+    Mp3,
+    Ogg,
+    Mp4,
+    WebM,
+}
+
+impl AudioFormat {
+    /// Identify the container from its magic bytes (file extensions lie,
+    /// especially for recordings shared off a phone).
+    pub fn sniff(data: &[u8]) -> Option<AudioFormat> {
+        match data {
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some(AudioFormat::Wav),
+            [b'f', b'L', b'a', b'C', ..] => Some(AudioFormat::Flac),
+            [b'O', b'g', b'g', b'S', ..] => Some(AudioFormat::Ogg),
+            [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some(AudioFormat::Mp4),
+            [0x1A, 0x45, 0xDF, 0xA3, ..] => Some(AudioFormat::WebM),
+            [b'I', b'D', b'3', ..] => Some(AudioFormat::Mp3),
+            // Bare MPEG audio frame sync (11 set bits), layer III
+            [0xFF, b, ..] if b & 0xE0 == 0xE0 && b & 0x06 == 0x02 => Some(AudioFormat::Mp3),
+            _ => None,
         }
-        (SampleFormat::Int, 32) => {
-            // 32-bit PCM: signed, range [-2147483648, 2147483647] -> [-1.0, 1.0]
-            reader
-                .samples::<i32>()
-                .collect::<Result<Vec<_>, _>>()?
-                .into_iter()
-                .map(|s| s as f32 / 2147483648.0)
-                .collect()
+    }
+
+    /// Canonical file extension, used for stored inputs and decoder hints
+    pub fn extension(self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Ogg => "ogg",
+            AudioFormat::Mp4 => "m4a",
+            AudioFormat::WebM => "webm",
         }
-        (SampleFormat::Float, 32) => {
-            // 32-bit float: already in [-1.0, 1.0] (typically)
-            reader
-                .samples::<f32>()
-                .collect::<Result<Vec<_>, _>>()?
+    }
+
+    /// Display name for error messages
+    pub fn name(self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "WAV",
+            AudioFormat::Flac => "FLAC",
+            AudioFormat::Mp3 => "MP3",
+            AudioFormat::Ogg => "Ogg",
+            AudioFormat::Mp4 => "M4A",
+            AudioFormat::WebM => "WebM",
         }
-        _ => {
-            return Err(AudioError::UnsupportedFormat(format!(
-                "{:?} bit {}-bit audio",
-                sample_format, bit_depth
-            )));
+    }
+}
+
+/// Ingest an audio file of any supported container from raw bytes
+/// WAV goes through `ingest_wav`; compressed formats are decoded to the same
+/// interleaved f32 [-1.0, 1.0] samples.
+pub fn ingest_audio(data: &[u8]) -> Result<AudioData, AudioError> {
+    match AudioFormat::sniff(data) {
+        Some(AudioFormat::Wav) => ingest_wav(data),
+        Some(format) => super::decode::decode_compressed(data, format),
+        None => Err(unrecognised_format()),
+    }
+}
+
+fn unrecognised_format() -> AudioError {
+    AudioError::UnsupportedFormat(
+        "unrecognised file (expected WAV, FLAC, MP3, Ogg, M4A or WebM)".to_string(),
+    )
+}
+
+/// A chunked audio source, as opened by `open_audio`
+pub type BoxedAudioSource = Box<dyn AudioSource<Error = AudioError> + Send>;
+
+/// Open the audio file at `path` for chunked reading (same containers as
+/// `ingest_audio`). WAV streams straight off disk; a compressed file is held
+/// as compressed bytes and decoded a packet at a time. Either way memory
+/// stays bounded however long the recording is.
+pub fn open_audio(path: &Path) -> Result<BoxedAudioSource, AudioError> {
+    let mut file = File::open(path)?;
+    let mut magic = Vec::with_capacity(12);
+    (&mut file).take(12).read_to_end(&mut magic)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    match AudioFormat::sniff(&magic) {
+        Some(AudioFormat::Wav) => Ok(Box::new(WavSource::new(BufReader::new(file))?)),
+        Some(format) => {
+            let mut data = Vec::new();
+            file.read_to_end(&mut data)?;
+            Ok(Box::new(super::decode::CompressedSource::open(data.into(), format)?))
         }
-    };
+        None => Err(unrecognised_format()),
+    }
+}
+
+/// Ingest a WAV file from raw bytes
+/// Returns AudioData with normalized samples and metadata
+pub fn ingest_wav(data: &[u8]) -> Result<AudioData, AudioError> {
+    let source = WavSource::new(Cursor::new(data))?;
+    let bit_depth = source.spec.bits_per_sample;
+    read_to_end(source, bit_depth)
+}
+
+/// Drain `source` into an in-memory `AudioData`.
+pub(super) fn read_to_end<S: AudioSource<Error = AudioError>>(
+    mut source: S,
+    bit_depth: u16,
+) -> Result<AudioData, AudioError> {
+    let sample_rate = source.sample_rate();
+    let channels = source.channels();
+    let mut samples =
+        Vec::with_capacity(source.total_frames().unwrap_or(0) * channels as usize);
+    while source.read(&mut samples, 1 << 16)? > 0 {}
 
     let total_samples = samples.len();
     let frame_count = total_samples / channels as usize;
@@ -105,6 +166,100 @@ pub fn ingest_wav(data: &[u8]) -> Result<AudioData, AudioError> {
     })
 }
 
+/// Chunked WAV reader: `ingest_wav`'s normalization, one chunk at a time
+pub struct WavSource<R: Read + Seek> {
+    reader: WavReader<R>,
+    spec: WavSpec,
+}
+
+impl<R: Read + Seek> WavSource<R> {
+    pub fn new(inner: R) -> Result<Self, AudioError> {
+        let reader = WavReader::new(inner)?;
+        let spec = reader.spec();
+        match (spec.sample_format, spec.bits_per_sample) {
+            (SampleFormat::Int, 8 | 16 | 24 | 32) | (SampleFormat::Float, 32) => {}
+            (sample_format, bit_depth) => {
+                return Err(AudioError::UnsupportedFormat(format!(
+                    "{:?} bit {}-bit audio",
+                    sample_format, bit_depth
+                )));
+            }
+        }
+        Ok(WavSource { reader, spec })
+    }
+
+    /// Bit depth of the PCM data (8, 16, 24, 32)
+    pub fn bit_depth(&self) -> u16 {
+        self.spec.bits_per_sample
+    }
+}
+
+/// Append up to `count` samples of type `S`, normalized by `convert`
+fn read_samples<S: hound::Sample, R: Read>(
+    reader: &mut WavReader<R>,
+    count: usize,
+    buf: &mut Vec<f32>,
+    convert: impl Fn(S) -> f32,
+) -> Result<(), AudioError> {
+    for sample in reader.samples::<S>().take(count) {
+        buf.push(convert(sample?));
+    }
+    Ok(())
+}
+
+impl<R: Read + Seek> AudioSource for WavSource<R> {
+    type Error = AudioError;
+
+    fn sample_rate(&self) -> u32 {
+        self.spec.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.spec.channels
+    }
+
+    fn total_frames(&self) -> Option<usize> {
+        Some(self.reader.duration() as usize)
+    }
+
+    fn read(&mut self, buf: &mut Vec<f32>, max_frames: usize) -> Result<usize, AudioError> {
+        let channels = self.spec.channels as usize;
+        let count = max_frames * channels;
+        let start = buf.len();
+        // Normalize samples to f32 [-1.0, 1.0]
+        match (self.spec.sample_format, self.spec.bits_per_sample) {
+            // 8-bit PCM: unsigned, range [0, 255] -> [-1.0, 1.0]
+            (SampleFormat::Int, 8) => {
+                read_samples(&mut self.reader, count, buf, |s: i32| (s as f32 - 128.0) / 128.0)?
+            }
+            // 16-bit PCM: signed, range [-32768, 32767] -> [-1.0, 1.0]
+            (SampleFormat::Int, 16) => {
+                read_samples(&mut self.reader, count, buf, |s: i16| s as f32 / 32768.0)?
+            }
+            // 24-bit PCM: signed, range [-8388608, 8388607] -> [-1.0, 1.0]
+            (SampleFormat::Int, 24) => {
+                read_samples(&mut self.reader, count, buf, |s: i32| s as f32 / 8388608.0)?
+            }
+            // 32-bit PCM: signed, range [-2147483648, 2147483647] -> [-1.0, 1.0]
+            (SampleFormat::Int, _) => {
+                read_samples(&mut self.reader, count, buf, |s: i32| s as f32 / 2147483648.0)?
+            }
+            // 32-bit float: already in [-1.0, 1.0] (typically)
+            (SampleFormat::Float, _) => read_samples(&mut self.reader, count, buf, |s: f32| s)?,
+        }
+
+        // A truncated final frame is dropped
+        let frames = (buf.len() - start) / channels;
+        buf.truncate(start + frames * channels);
+        Ok(frames)
+    }
+
+    fn rewind(&mut self) -> Result<(), AudioError> {
+        self.reader.seek(0)?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -144,4 +299,27 @@ mod tests {
 
         assert_eq!(audio_data.duration_secs(), 5.0);
     }
+
+    #[test]
+    fn test_sniff_audio_format() {
+        assert_eq!(AudioFormat::sniff(b"RIFF\x24\0\0\0WAVEfmt "), Some(AudioFormat::Wav));
+        assert_eq!(AudioFormat::sniff(b"fLaC\0\0\0\x22"), Some(AudioFormat::Flac));
+        assert_eq!(AudioFormat::sniff(b"OggS\0\x02"), Some(AudioFormat::Ogg));
+        assert_eq!(AudioFormat::sniff(b"\0\0\0\x20ftypM4A "), Some(AudioFormat::Mp4));
+        assert_eq!(AudioFormat::sniff(&[0x1A, 0x45, 0xDF, 0xA3, 0x9F]), Some(AudioFormat::WebM));
+        assert_eq!(AudioFormat::sniff(b"ID3\x04\0"), Some(AudioFormat::Mp3));
+        assert_eq!(AudioFormat::sniff(&[0xFF, 0xFB, 0x90, 0x64]), Some(AudioFormat::Mp3));
+        // ADTS AAC shares the sync word but has layer bits 00
+        assert_eq!(AudioFormat::sniff(&[0xFF, 0xF1, 0x50, 0x80]), None);
+        assert_eq!(AudioFormat::sniff(b"RIFF\x24\0\0\0AVI "), None);
+        assert_eq!(AudioFormat::sniff(b""), None);
+    }
+
+    #[test]
+    fn test_ingest_audio_rejects_unknown_bytes() {
+        assert!(matches!(
+            ingest_audio(b"definitely not audio"),
+            Err(AudioError::UnsupportedFormat(_))
+        ));
+    }
 }
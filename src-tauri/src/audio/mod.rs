@@ -1,20 +1,33 @@
 // Audio processing module
-// Handles WAV file ingestion and audio data processing.
+// Handles WAV and compressed-audio ingestion and audio data processing.
 //
 // Onset detection + feature extraction + the `AudioData` container now live in
 // the shared `beatrice-dsp` crate. This module re-exports them so every existing
 // `crate::audio::…` / `beatrice_lib::audio::…` path keeps resolving unchanged.
 
+mod decode;
 pub mod ingest;
 pub mod recording;
 
 // Re-export the DSP `features` module so `crate::audio::features::Onset`
 // (used by the groove layer) and `beatrice_lib::audio::features` still resolve.
 pub use beatrice_dsp::features;
+// Room-tone profiles; `noise::NoiseProfile` is the measurement, not the
+// `state::NoiseProfile` row that points at it.
+pub use beatrice_dsp::noise;
 
-pub use ingest::{ingest_wav, AudioData, AudioError};
+pub use decode::CompressedSource;
+pub use ingest::{
+    ingest_audio, ingest_wav, open_audio, AudioData, AudioError, AudioFormat, BoxedAudioSource,
+    WavSource,
+};
 pub use features::{
     detect_onsets, extract_features, extract_features_for_window, extract_mfcc,
     extract_mfcc_for_window, extract_mfcc_stats, Onset, OnsetConfig, MFCC_COEFFS,
 };
 pub use recording::{AudioRecorder, RecordingData, RecordingError};
+pub use beatrice_dsp::{
+    analyze_source_hybrid, normalize_sample_rate, resample, AnalysisProgress, AudioSource,
+    ChannelStrategy, ConditioningConfig, NoiseReduction, Normalization, OnsetFunction,
+    ResampledSource, FACTORY_SAMPLE_RATE,
+};
@@ -0,0 +1,721 @@
+//! Headless Beatrice pipeline — audio in, JSON / MIDI / WAV out, no Tauri GUI.
+//!
+//! Runs the same stages the app does (hybrid event detection, tempo estimation,
+//! phase-anchored quantization, fidelity arrangement, song expansion) so the
+//! pipeline can be scripted into an asset build. The Tauri app itself builds as
+//! `beatrice-app`. Input may be WAV, FLAC, MP3, Ogg (Vorbis/Opus), M4A or WebM;
+//! the container is sniffed, not trusted from the extension. Like the app,
+//! analysis runs at the classifier's 44.1 kHz training rate unless
+//! `--native-rate` is given.
+//!
+//! ```text
+//! cargo run --release --bin beatrice -- arrange take.wav --theme "TWIN PEAKS" --out take.json
+//! cargo run --release --bin beatrice -- export-midi take.wav --bars 8 --out take.mid
+//! cargo run --release --bin beatrice -- render take.wav --fidelity 1.0 --out take-song.wav
+//! ```
+
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use beatrice_lib::arranger::{self, Arrangement, ArrangementTemplate, HarmonyMode, MidiExportOptions};
+use beatrice_lib::audio::noise::NoiseProfile;
+use beatrice_lib::audio::{
+    self, AudioData, ChannelStrategy, ConditioningConfig, Onset, OnsetConfig, OnsetFunction,
+};
+use beatrice_lib::events::{
+    build_classifier, classifier_entry, CalibrationProfile, Event, EventClassifier,
+    MultiLabelConfig, DEFAULT_CLASSIFIER,
+};
+use beatrice_lib::groove::{
+    self, Grid, GridDivision, GrooveFeel, QuantizeSettings, QuantizedEvent, TempoEstimate,
+    TimeSignature,
+};
+use beatrice_lib::render::{self, RenderOptions};
+use beatrice_lib::themes;
+
+const HELP: &str = "\
+beatrice — run the Beatrice pipeline headlessly
+
+USAGE:
+    beatrice <COMMAND> <input> [OPTIONS]
+
+    <input> is a WAV, FLAC, MP3, Ogg (Vorbis/Opus), M4A or WebM recording.
+
+COMMANDS:
+    analyze       Detect + classify events and estimate tempo (JSON)
+    arrange       analyze + quantize + arrange into a full song (JSON)
+    export-midi   arrange, then write the song as a Standard MIDI File
+    render        arrange, then bounce the song to a 16-bit stereo WAV
+
+OPTIONS:
+    --out <path>            Output file (JSON commands default to stdout;
+                            required for export-midi and render)
+    --json <path>           Also write the JSON report (export-midi/render)
+    --theme <name>          Theme name (default: BLADE RUNNER)
+    --template <name>       synthwave_straight | synthwave_halftime | arp_drive
+                            (default: the theme's default template)
+    --division <name>       quarter | eighth | sixteenth | triplet (default: sixteenth)
+    --feel <name>           straight | swing | halftime (default: straight)
+    --swing <0-1>           Swing amount (default: 0.0)
+    --bars <n>              Bar count of the base pattern (default: 4)
+    --bpm <bpm>             Override the estimated tempo
+    --strength <0-1>        Quantize strength (default: 0.8)
+    --b-emphasis <0-1>      B (bilabial plosive) emphasis (default: 0.6)
+    --fidelity <0-1>        Placement fidelity, 1.0 = follow me (default: 0.8)
+    --harmony <mode>        theme | follow-hum: the theme's chord cycle, or a
+                            key and per-bar chords inferred from the hum
+                            (default: theme)
+    --classifier <name>     hybrid | gaussian | heuristic | knn (default: hybrid)
+    --calibration <path>    Calibration profile JSON; MAP-adapts the classifier
+                            (the kNN classifier votes over its samples)
+    --native-rate           Analyze at the input's sample rate instead of
+                            resampling to the model's 44.1 kHz training rate
+    --channels <strategy>   average | channel:N | max-energy | mid-side |
+                            per-channel: how multi-channel input is analyzed
+                            (default: average)
+    --onset-function <name> spectral-flux | superflux | hfc | complex-domain |
+                            multi-band: what onsets are picked on
+                            (default: spectral-flux)
+    --multi-label           Emit one event per layered sound (a kick with a
+                            hat on top gives a kick and a hat)
+    --include-rejected      Quantize onsets rejected as noise (breaths, mic
+                            bumps and clicks) instead of leaving them out
+    --condition             Remove DC and rumble, reduce steady background
+                            noise and normalize loudness before detection
+    --room-tone <path>      A few seconds of the room with nobody playing;
+                            raises the onset gate above its noise and seeds
+                            the --condition denoiser
+    --sample-rate <hz>      Render sample rate (default: 44100)
+    -h, --help              Print this help
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Analyze,
+    Arrange,
+    ExportMidi,
+    Render,
+}
+
+impl Command {
+    fn parse(s: &str) -> Option<Command> {
+        match s {
+            "analyze" => Some(Command::Analyze),
+            "arrange" => Some(Command::Arrange),
+            "export-midi" => Some(Command::ExportMidi),
+            "render" => Some(Command::Render),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Args {
+    command: Command,
+    input: PathBuf,
+    out: Option<PathBuf>,
+    json: Option<PathBuf>,
+    theme: String,
+    template: Option<ArrangementTemplate>,
+    division: GridDivision,
+    feel: GrooveFeel,
+    swing: f32,
+    bars: u32,
+    bpm: Option<f64>,
+    strength: f32,
+    b_emphasis: f32,
+    fidelity: f32,
+    harmony: HarmonyMode,
+    classifier: String,
+    calibration: Option<PathBuf>,
+    native_rate: bool,
+    channels: ChannelStrategy,
+    onset_function: OnsetFunction,
+    multi_label: Option<MultiLabelConfig>,
+    include_rejected: bool,
+    conditioning: Option<ConditioningConfig>,
+    room_tone: Option<PathBuf>,
+    sample_rate: u32,
+}
+
+fn parse_division(v: &str) -> Result<GridDivision, String> {
+    match v {
+        "quarter" => Ok(GridDivision::Quarter),
+        "eighth" => Ok(GridDivision::Eighth),
+        "sixteenth" => Ok(GridDivision::Sixteenth),
+        "triplet" => Ok(GridDivision::Triplet),
+        _ => Err(format!("invalid --division value: {v}")),
+    }
+}
+
+fn parse_harmony(v: &str) -> Result<HarmonyMode, String> {
+    match v {
+        "theme" => Ok(HarmonyMode::Theme),
+        "follow-hum" => Ok(HarmonyMode::FollowHum),
+        _ => Err(format!("invalid --harmony value: {v}")),
+    }
+}
+
+fn parse_feel(v: &str) -> Result<GrooveFeel, String> {
+    match v {
+        "straight" => Ok(GrooveFeel::Straight),
+        "swing" => Ok(GrooveFeel::Swing),
+        "halftime" => Ok(GrooveFeel::Halftime),
+        _ => Err(format!("invalid --feel value: {v}")),
+    }
+}
+
+/// `ArrangementTemplate::from_string` silently defaults; the CLI rejects typos.
+fn parse_template(v: &str) -> Result<ArrangementTemplate, String> {
+    let template = ArrangementTemplate::from_string(v);
+    if template.to_string() == v.to_lowercase() {
+        Ok(template)
+    } else {
+        Err(format!("invalid --template value: {v}"))
+    }
+}
+
+fn parse_unit(flag: &str, v: &str) -> Result<f32, String> {
+    match v.parse::<f32>() {
+        Ok(x) if (0.0..=1.0).contains(&x) => Ok(x),
+        _ => Err(format!("invalid {flag} value: {v} (expected 0.0-1.0)")),
+    }
+}
+
+/// Parse CLI args. Returns `Ok(None)` when `--help` was requested.
+fn parse_args(argv: &[String]) -> Result<Option<Args>, String> {
+    if argv.is_empty() || argv.iter().any(|a| a == "-h" || a == "--help") {
+        return Ok(None);
+    }
+    let command = Command::parse(&argv[0])
+        .ok_or_else(|| format!("unknown command: {}\n\nRun with --help.", argv[0]))?;
+
+    let mut args = Args {
+        command,
+        input: PathBuf::new(),
+        out: None,
+        json: None,
+        theme: "BLADE RUNNER".to_string(),
+        template: None,
+        division: GridDivision::Sixteenth,
+        feel: GrooveFeel::Straight,
+        swing: 0.0,
+        bars: 4,
+        bpm: None,
+        strength: 0.8,
+        b_emphasis: 0.6,
+        fidelity: 0.8,
+        harmony: HarmonyMode::Theme,
+        classifier: DEFAULT_CLASSIFIER.to_string(),
+        calibration: None,
+        native_rate: false,
+        channels: ChannelStrategy::default(),
+        onset_function: OnsetFunction::default(),
+        multi_label: None,
+        include_rejected: false,
+        conditioning: None,
+        room_tone: None,
+        sample_rate: RenderOptions::default().sample_rate,
+    };
+    let mut input: Option<PathBuf> = None;
+
+    let mut i = 1;
+    while i < argv.len() {
+        let flag = argv[i].as_str();
+        let mut value = || {
+            i += 1;
+            argv.get(i).cloned().ok_or(format!("{flag} requires a value"))
+        };
+        match flag {
+            "--out" => args.out = Some(PathBuf::from(value()?)),
+            "--json" => args.json = Some(PathBuf::from(value()?)),
+            "--theme" => args.theme = value()?,
+            "--template" => args.template = Some(parse_template(&value()?)?),
+            "--division" => args.division = parse_division(&value()?)?,
+            "--feel" => args.feel = parse_feel(&value()?)?,
+            "--swing" => args.swing = parse_unit(flag, &value()?)?,
+            "--strength" => args.strength = parse_unit(flag, &value()?)?,
+            "--b-emphasis" => args.b_emphasis = parse_unit(flag, &value()?)?,
+            "--fidelity" => args.fidelity = parse_unit(flag, &value()?)?,
+            "--harmony" => args.harmony = parse_harmony(&value()?)?,
+            "--bars" => {
+                let v = value()?;
+                args.bars = match v.parse::<u32>() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(format!("invalid --bars value: {v}")),
+                };
+            }
+            "--bpm" => {
+                let v = value()?;
+                args.bpm = match v.parse::<f64>() {
+                    Ok(b) if b > 0.0 && b.is_finite() => Some(b),
+                    _ => return Err(format!("invalid --bpm value: {v}")),
+                };
+            }
+            "--classifier" => {
+                let v = value()?;
+                if classifier_entry(&v).is_none() {
+                    return Err(format!("invalid --classifier value: {v}"));
+                }
+                args.classifier = v;
+            }
+            "--calibration" => args.calibration = Some(PathBuf::from(value()?)),
+            "--native-rate" => args.native_rate = true,
+            "--channels" => {
+                let v = value()?;
+                args.channels = v.parse().map_err(|e| format!("invalid --channels value: {e}"))?;
+            }
+            "--onset-function" => {
+                let v = value()?;
+                args.onset_function = v.parse().map_err(|e| format!("invalid --onset-function value: {e}"))?;
+            }
+            "--multi-label" => args.multi_label = Some(MultiLabelConfig::default()),
+            "--include-rejected" => args.include_rejected = true,
+            "--condition" => args.conditioning = Some(ConditioningConfig::default()),
+            "--room-tone" => args.room_tone = Some(PathBuf::from(value()?)),
+            "--sample-rate" => {
+                let v = value()?;
+                args.sample_rate = v.parse().map_err(|_| format!("invalid --sample-rate value: {v}"))?;
+            }
+            other if other.starts_with("--") => {
+                return Err(format!("unknown argument: {other}\n\nRun with --help."))
+            }
+            path if input.is_none() => input = Some(PathBuf::from(path)),
+            extra => return Err(format!("unexpected argument: {extra}")),
+        }
+        i += 1;
+    }
+
+    args.input = input.ok_or("an input audio file is required")?;
+    if matches!(args.command, Command::ExportMidi | Command::Render) && args.out.is_none() {
+        return Err("--out is required for export-midi and render".to_string());
+    }
+    Ok(Some(args))
+}
+
+#[derive(Serialize)]
+struct AudioSummary {
+    path: String,
+    sample_rate: u32,
+    /// Rate the events were detected and classified at.
+    analysis_sample_rate: u32,
+    channels: u16,
+    /// How the channels were reduced for detection.
+    channel_strategy: ChannelStrategy,
+    /// Pre-processing applied before detection, if any.
+    conditioning: Option<ConditioningConfig>,
+    /// Level of the `--room-tone` capture in dBFS, if one was given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    noise_floor_dbfs: Option<f32>,
+    bit_depth: u16,
+    duration_ms: i64,
+}
+
+/// The machine-readable report. Stages that did not run are omitted.
+#[derive(Serialize)]
+struct Report {
+    audio: AudioSummary,
+    /// Registry name of the classifier that labelled the events.
+    classifier: String,
+    used_calibration: bool,
+    tempo: TempoEstimate,
+    events: Vec<Event>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quantized_events: Option<Vec<QuantizedEvent>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    arrangement: Option<Arrangement>,
+}
+
+fn load_classifier(name: &str, path: Option<&Path>) -> Result<Box<dyn EventClassifier>, String> {
+    let Some(path) = path else {
+        return build_classifier(name, None);
+    };
+    let bytes = std::fs::read(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    let profile = CalibrationProfile::from_json_bytes(&bytes)
+        .map_err(|e| format!("invalid calibration profile {}: {e}", path.display()))?;
+    if !profile.is_sufficient() {
+        eprintln!(
+            "warning: calibration profile '{}' has fewer than 5 samples per class",
+            profile.name
+        );
+    }
+    build_classifier(name, Some(&profile))
+}
+
+/// Measure the `--room-tone` capture at the rate the take is analysed at.
+fn load_noise_profile(path: Option<&Path>, sample_rate: u32) -> Result<Option<NoiseProfile>, String> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let bytes = std::fs::read(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    let room = audio::ingest_audio(&bytes).map_err(|e| format!("cannot decode {}: {e}", path.display()))?;
+    NoiseProfile::from_audio(&room.resampled(sample_rate)).map(Some).ok_or_else(|| {
+        format!(
+            "room tone {} is too short ({} ms; at least {} ms needed)",
+            path.display(),
+            room.duration_ms,
+            audio::noise::MIN_ROOM_TONE_MS
+        )
+    })
+}
+
+/// Detect events and tempo — the `analyze` stage every command starts with.
+fn analyze(
+    args: &Args,
+    audio: &AudioData,
+    classifier: &dyn EventClassifier,
+    noise: Option<&NoiseProfile>,
+) -> (Vec<Event>, TempoEstimate) {
+    let mut config = OnsetConfig {
+        channels: args.channels,
+        conditioning: args.conditioning,
+        onset_function: args.onset_function,
+        multi_label: args.multi_label,
+        ..OnsetConfig::default()
+    };
+    if let Some(noise) = noise {
+        noise.adapt_onset_config(&mut config);
+    }
+    let events = beatrice_dsp::analyze_offline_hybrid(audio, &config, classifier);
+    let onsets = event_onsets(&events);
+    // A --bpm override refits the phase at that tempo, so the beat grid the
+    // JSON reports (and quantize/arrange build on) agrees with its own BPM.
+    let tempo = match args.bpm {
+        Some(bpm) => groove::estimate_tempo_at_bpm(&onsets, bpm),
+        None => groove::estimate_tempo(&onsets, audio.sample_rate),
+    };
+    (events, tempo)
+}
+
+/// One onset per distinct event timestamp. The hybrid pass already ran onset
+/// detection, so tempo is read off its events instead of detecting again
+/// (it only looks at timing); multi-label layers share their onset's
+/// timestamp and count once.
+fn event_onsets(events: &[Event]) -> Vec<Onset> {
+    let mut onsets: Vec<Onset> = Vec::with_capacity(events.len());
+    for event in events {
+        if onsets.last().is_some_and(|o| o.timestamp_ms == event.timestamp_ms) {
+            continue;
+        }
+        onsets.push(Onset { timestamp_ms: event.timestamp_ms, strength: 1.0 });
+    }
+    onsets
+}
+
+fn grid_for(args: &Args, tempo: &TempoEstimate, bars: u32) -> Grid {
+    Grid::with_phase(
+        tempo.bpm,
+        TimeSignature::FourFour,
+        args.division,
+        args.feel,
+        args.swing,
+        bars,
+        tempo.phase_offset_ms,
+    )
+}
+
+/// Quantize + arrange + expand to the full Intro/Build/Drop/Outro song, exactly
+/// as `quantize_events_command` → `arrange_events_command` do in the app.
+fn arrange(args: &Args, events: &[Event], tempo: &TempoEstimate) -> Result<(Vec<QuantizedEvent>, Arrangement), String> {
+    let theme = themes::get_theme(&args.theme).ok_or_else(|| {
+        format!(
+            "unknown theme: {} (available: {})",
+            args.theme,
+            themes::list_theme_names().join(", ")
+        )
+    })?;
+    let template = args.template.unwrap_or(theme.default_template);
+    let grid = grid_for(args, tempo, args.bars);
+    let settings = QuantizeSettings {
+        strength: args.strength,
+        swing_amount: args.swing,
+        lookahead_ms: 100.0,
+        include_rejected: args.include_rejected,
+    };
+    let quantized = groove::quantize_events(events, &grid, &settings);
+    let base = arranger::arrange_events_with_harmony(
+        &quantized,
+        &template,
+        &grid,
+        &theme,
+        args.b_emphasis,
+        args.fidelity,
+        args.harmony,
+    );
+    Ok((quantized, base.expand_to_song()))
+}
+
+fn write_output(path: Option<&Path>, bytes: &[u8]) -> Result<(), String> {
+    match path {
+        Some(p) => std::fs::write(p, bytes).map_err(|e| format!("cannot write {}: {e}", p.display())),
+        None => {
+            use std::io::Write;
+            let mut stdout = std::io::stdout().lock();
+            stdout
+                .write_all(bytes)
+                .and_then(|_| stdout.write_all(b"\n"))
+                .map_err(|e| format!("cannot write to stdout: {e}"))
+        }
+    }
+}
+
+fn run() -> Result<(), String> {
+    let argv: Vec<String> = std::env::args().skip(1).collect();
+    let args = match parse_args(&argv)? {
+        Some(a) => a,
+        None => {
+            print!("{HELP}");
+            return Ok(());
+        }
+    };
+
+    let bytes = std::fs::read(&args.input)
+        .map_err(|e| format!("cannot read {}: {e}", args.input.display()))?;
+    let audio = audio::ingest_audio(&bytes).map_err(|e| format!("cannot decode {}: {e}", args.input.display()))?;
+    let analysis = if args.native_rate {
+        Cow::Borrowed(&audio)
+    } else {
+        audio::normalize_sample_rate(&audio)
+    };
+    let classifier = load_classifier(&args.classifier, args.calibration.as_deref())?;
+    let noise = load_noise_profile(args.room_tone.as_deref(), analysis.sample_rate)?;
+
+    let (events, tempo) = analyze(&args, &analysis, classifier.as_ref(), noise.as_ref());
+    eprintln!(
+        "{} events, {:.1} BPM (confidence {:.0}%)",
+        events.len(),
+        tempo.bpm,
+        tempo.confidence * 100.0
+    );
+
+    let mut report = Report {
+        audio: AudioSummary {
+            path: args.input.display().to_string(),
+            sample_rate: audio.sample_rate,
+            analysis_sample_rate: analysis.sample_rate,
+            channels: audio.channels,
+            channel_strategy: args.channels,
+            conditioning: args.conditioning,
+            noise_floor_dbfs: noise.as_ref().map(NoiseProfile::noise_floor_dbfs),
+            bit_depth: audio.bit_depth,
+            duration_ms: audio.duration_ms,
+        },
+        classifier: args.classifier.clone(),
+        used_calibration: args.calibration.is_some(),
+        tempo,
+        events,
+        quantized_events: None,
+        arrangement: None,
+    };
+
+    if args.command != Command::Analyze {
+        let (quantized, song) = arrange(&args, &report.events, &report.tempo)?;
+        let notes: usize = song.all_lanes().iter().map(|l| l.events.len()).sum();
+        eprintln!(
+            "{} arrangement: {} bars, {} notes, {:.1}s",
+            song.theme_name,
+            song.bar_count,
+            notes,
+            song.total_duration_ms / 1000.0
+        );
+
+        match args.command {
+            Command::ExportMidi => {
+                let grid = grid_for(&args, &report.tempo, song.bar_count);
+                let midi = arranger::export_midi(&song, &grid, &MidiExportOptions::default())
+                    .map_err(|e| format!("MIDI export failed: {e}"))?;
+                write_output(args.out.as_deref(), &midi)?;
+            }
+            Command::Render => {
+                let options = RenderOptions { sample_rate: args.sample_rate };
+                let wav = render::render_wav(&song, &options).map_err(|e| e.to_string())?;
+                write_output(args.out.as_deref(), &wav)?;
+            }
+            Command::Analyze | Command::Arrange => {}
+        }
+        report.quantized_events = Some(quantized);
+        report.arrangement = Some(song);
+    }
+
+    let json = serde_json::to_vec_pretty(&report).map_err(|e| format!("cannot serialize report: {e}"))?;
+    match args.command {
+        Command::Analyze | Command::Arrange => write_output(args.out.as_deref(), &json)?,
+        Command::ExportMidi | Command::Render => {
+            if let Some(path) = &args.json {
+                write_output(Some(path), &json)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_args_help_returns_none() {
+        assert!(parse_args(&argv(&["--help"])).unwrap().is_none());
+        assert!(parse_args(&[]).unwrap().is_none());
+    }
+
+    #[test]
+    fn parse_args_defaults_match_the_app() {
+        let args = parse_args(&argv(&["arrange", "take.wav"])).unwrap().unwrap();
+        assert_eq!(args.command, Command::Arrange);
+        assert_eq!(args.input, PathBuf::from("take.wav"));
+        assert_eq!(args.theme, "BLADE RUNNER");
+        assert_eq!(args.division, GridDivision::Sixteenth);
+        assert_eq!(args.feel, GrooveFeel::Straight);
+        assert_eq!(args.bars, 4);
+        assert!((args.fidelity - 0.8).abs() < 1e-6, "ArrangeEventsInput default");
+        assert!(args.template.is_none(), "falls back to the theme's template");
+        assert_eq!(args.harmony, HarmonyMode::Theme, "ArrangeEventsInput default");
+        assert!(!args.native_rate, "normalizes to the model rate like detect_events");
+        assert_eq!(args.channels, ChannelStrategy::Average);
+        assert_eq!(args.onset_function, OnsetFunction::SpectralFlux);
+        assert!(args.multi_label.is_none(), "one event per onset, like detect_events");
+        assert!(!args.include_rejected, "QuantizeEventsInput default");
+        assert!(args.conditioning.is_none(), "detect_events leaves the signal raw by default");
+        assert!(args.room_tone.is_none());
+        assert_eq!(args.classifier, "hybrid", "detect_events' default classifier");
+    }
+
+    #[test]
+    fn parse_args_reads_all_options() {
+        let args = parse_args(&argv(&[
+            "render", "take.wav", "--out", "song.wav", "--json", "r.json", "--theme", "twin peaks",
+            "--template", "arp_drive", "--division", "triplet", "--feel", "swing", "--swing", "0.3",
+            "--bars", "8", "--bpm", "96", "--strength", "1", "--b-emphasis", "0.2", "--fidelity",
+            "1.0", "--calibration", "me.json", "--native-rate", "--sample-rate", "48000",
+            "--channels", "channel:1", "--condition", "--room-tone", "room.wav", "--classifier",
+            "knn", "--harmony", "follow-hum", "--onset-function", "superflux", "--multi-label",
+            "--include-rejected",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(args.out, Some(PathBuf::from("song.wav")));
+        assert_eq!(args.json, Some(PathBuf::from("r.json")));
+        assert_eq!(args.theme, "twin peaks");
+        assert_eq!(args.template, Some(ArrangementTemplate::ArpDrive));
+        assert_eq!(args.division, GridDivision::Triplet);
+        assert_eq!(args.feel, GrooveFeel::Swing);
+        assert!((args.swing - 0.3).abs() < 1e-6);
+        assert_eq!(args.bars, 8);
+        assert_eq!(args.bpm, Some(96.0));
+        assert!((args.b_emphasis - 0.2).abs() < 1e-6);
+        assert_eq!(args.calibration, Some(PathBuf::from("me.json")));
+        assert!(args.native_rate);
+        assert_eq!(args.channels, ChannelStrategy::Channel(1));
+        assert_eq!(args.conditioning, Some(ConditioningConfig::default()));
+        assert_eq!(args.room_tone, Some(PathBuf::from("room.wav")));
+        assert_eq!(args.sample_rate, 48000);
+        assert_eq!(args.classifier, "knn");
+        assert_eq!(args.harmony, HarmonyMode::FollowHum);
+        assert_eq!(args.onset_function, OnsetFunction::SuperFlux);
+        assert_eq!(args.multi_label, Some(MultiLabelConfig::default()));
+        assert!(args.include_rejected);
+    }
+
+    #[test]
+    fn parse_args_rejects_bad_values() {
+        assert!(parse_args(&argv(&["mix", "a.wav"])).unwrap_err().contains("unknown command"));
+        assert!(parse_args(&argv(&["arrange"])).unwrap_err().contains("input"));
+        assert!(parse_args(&argv(&["arrange", "a.wav", "--division", "32nd"])).is_err());
+        assert!(parse_args(&argv(&["arrange", "a.wav", "--template", "polka"])).is_err());
+        assert!(parse_args(&argv(&["arrange", "a.wav", "--fidelity", "1.5"])).is_err());
+        assert!(parse_args(&argv(&["arrange", "a.wav", "--bars", "0"])).is_err());
+        assert!(parse_args(&argv(&["arrange", "a.wav", "--channels", "left"])).is_err());
+        assert!(parse_args(&argv(&["arrange", "a.wav", "--classifier", "onnx"])).is_err());
+        assert!(parse_args(&argv(&["arrange", "a.wav", "--harmony", "jazz"])).is_err());
+        assert!(parse_args(&argv(&["arrange", "a.wav", "--onset-function", "flux"])).is_err());
+        assert!(parse_args(&argv(&["render", "a.wav"])).unwrap_err().contains("--out"));
+    }
+
+    /// Eight layered kick+noise hits at 120 BPM.
+    fn layered_kicks() -> AudioData {
+        let sr = 44_100u32;
+        let beat = (sr / 2) as usize; // 120 BPM
+        let mut state = 0x2545_f491_u32;
+        let mut samples = vec![0.0f32; beat * 8];
+        for k in 0..8 {
+            for i in 0..sr as usize * 3 / 20 {
+                let t = i as f32 / sr as f32;
+                let freq = if t < 0.05 { 150.0 - 90.0 * t / 0.05 } else { 60.0 };
+                let kick = (2.0 * std::f32::consts::PI * freq * t).sin() * (-35.0 * t).exp() * 0.9;
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                let noise = (state as f32 / u32::MAX as f32) * 2.0 - 1.0;
+                samples[k * beat + i] = kick + 0.8 * noise * (-40.0 * t).exp();
+            }
+        }
+        let frame_count = samples.len();
+        AudioData { samples, sample_rate: sr, channels: 1, bit_depth: 16, duration_ms: 4000, frame_count }
+    }
+
+    /// Tempo comes from the hybrid events, not a second onset pass, and
+    /// matches what the onsets themselves would give even when multi-label
+    /// mode stacks several events on one onset.
+    #[test]
+    fn analyze_reads_tempo_off_the_events() {
+        let audio = layered_kicks();
+        let sr = audio.sample_rate;
+        let args = parse_args(&argv(&["analyze", "a.wav", "--multi-label"])).unwrap().unwrap();
+        let classifier = build_classifier(DEFAULT_CLASSIFIER, None).unwrap();
+
+        let (events, tempo) = analyze(&args, &audio, classifier.as_ref(), None);
+        let onsets = audio::detect_onsets(&audio, &OnsetConfig::default());
+        let from_events = event_onsets(&events);
+        assert!(events.len() > from_events.len(), "layered onsets emit several events");
+        assert_eq!(
+            from_events.iter().map(|o| o.timestamp_ms).collect::<Vec<_>>(),
+            onsets.iter().map(|o| o.timestamp_ms).collect::<Vec<_>>()
+        );
+        let expected = groove::estimate_tempo(&onsets, sr);
+        assert_eq!(tempo.bpm, expected.bpm);
+        assert_eq!(tempo.beat_positions_ms, expected.beat_positions_ms);
+    }
+
+    #[test]
+    fn analyze_bpm_override_rebuilds_the_beat_grid() {
+        let audio = layered_kicks();
+        let args = parse_args(&argv(&["analyze", "a.wav", "--bpm", "96"])).unwrap().unwrap();
+        let classifier = build_classifier(DEFAULT_CLASSIFIER, None).unwrap();
+
+        let (_, tempo) = analyze(&args, &audio, classifier.as_ref(), None);
+        assert_eq!(tempo.bpm, 96.0);
+        assert!(tempo.beat_positions_ms.len() > 2);
+        assert_eq!(tempo.phase_offset_ms, tempo.beat_positions_ms[0]);
+        for pair in tempo.beat_positions_ms.windows(2) {
+            assert!((pair[1] - pair[0] - 60000.0 / 96.0).abs() < 1e-6, "{pair:?}");
+        }
+    }
+
+    #[test]
+    fn arrange_rejects_unknown_theme() {
+        let mut args = parse_args(&argv(&["arrange", "a.wav"])).unwrap().unwrap();
+        args.theme = "NOT A THEME".to_string();
+        let tempo = TempoEstimate {
+            bpm: 120.0,
+            confidence: 1.0,
+            beat_positions_ms: vec![],
+            phase_offset_ms: 0.0,
+        };
+        let err = arrange(&args, &[], &tempo).unwrap_err();
+        assert!(err.contains("BLADE RUNNER"), "lists the available themes: {err}");
+    }
+}
@@ -1,12 +1,16 @@
 //! AVP benchmark runner — participant-wise heuristic vs calibrated accuracy.
 //!
 //! Measures Beatrice's event classifier against the published AVP
-//! ("Amateur Vocal Percussion") dataset (Delgado et al., Zenodo, CC-BY), which
-//! uses the exact same 4-class taxonomy Beatrice targets. The runner reports
+//! ("Amateur Vocal Percussion") dataset (Delgado et al., Zenodo, CC-BY), whose
+//! kick / snare / closed-hat / open-hat labels map one-to-one onto Beatrice
+//! classes. The runner reports
 //! two numbers side by side, both on the same held-out eval set:
 //!   1. the rule-based `HeuristicClassifier` (no personalization), and
 //!   2. a per-participant kNN calibration (first N utterances/class → profile).
 //!
+//! It also scores onset detection: every `OnsetFunction` is run over each
+//! recording and its onsets matched against the annotated ones.
+//!
 //! The dataset is NOT bundled. Download it from Zenodo, then point `--dataset`
 //! at the extracted folder. Run `benchmark --help` for the expected layout.
 //!
@@ -18,10 +22,11 @@ use std::collections::HashMap;
 use std::io::Read;
 use std::path::{Path, PathBuf};
 
-use beatrice_lib::audio::{self, AudioData};
+use beatrice_lib::audio::{self, AudioData, OnsetConfig, OnsetFunction};
 use beatrice_lib::events::{
-    gaussian_features, CalibrationProfile, CalibrationSample, EventClass, EventFeatures,
-    GaussianModel, HeuristicClassifier, HybridClassifier, KnnClassifier, DEFAULT_MAP_TAU,
+    build_classifier, classifier_entry, gaussian_features, CalibrationProfile, CalibrationSample,
+    ClassifierInput, EventClass, EventFeatures, GaussianModel, HeuristicClassifier,
+    HybridClassifier, KnnClassifier, DEFAULT_MAP_TAU,
 };
 
 /// Feature window (ms) extracted around each annotated onset. Fixed so every
@@ -37,13 +42,11 @@ const DEFAULT_CALIB_PER_CLASS: usize = 5;
 /// kNN neighbor count for the calibrated pass.
 const KNN_K: usize = 5;
 
-/// All four classes Beatrice discriminates, in a stable report order.
-const CLASSES: [EventClass; 4] = [
-    EventClass::BilabialPlosive,
-    EventClass::HihatNoise,
-    EventClass::Click,
-    EventClass::HumVoiced,
-];
+/// Onset-matching tolerance for the detection scores (the MIREX convention).
+const ONSET_TOLERANCE_MS: f64 = 50.0;
+
+/// Every class Beatrice discriminates, in a stable report order.
+const CLASSES: [EventClass; EventClass::COUNT] = EventClass::ALL;
 
 const HELP: &str = "\
 benchmark — AVP participant-wise accuracy runner
@@ -61,6 +64,9 @@ OPTIONS:
                              (for offline error analysis / tuning).
     --fit-model <FILE>       Fit the Gaussian factory model on ALL participants
                              and write it as JSON (the embeddable artifact).
+    --classifier <NAME>      Also score a registered classifier (hybrid |
+                             gaussian | heuristic | knn) as shipped and
+                             calibrated per participant. Repeatable.
     -h, --help               Print this help.
 
 EXPECTED DATASET LAYOUT:
@@ -81,8 +87,9 @@ EXPECTED DATASET LAYOUT:
         kd   kick drum        -> BilabialPlosive
         sd   snare drum       -> Click
         hhc  closed hi-hat    -> HihatNoise
-        hho  open hi-hat      -> HihatNoise   (folded: Beatrice has no open-hat
-                                               class; hho is reported separately)
+        hho  open hi-hat      -> OpenHihat
+    AVP has no rimshot, crash, hum or noise labels; those classes only ever
+    appear as (wrong) predictions in the report.
     A header row (non-numeric first column) is tolerated and skipped. Unknown
     labels are counted and skipped.
 
@@ -94,6 +101,11 @@ PROTOCOL:
     is apples-to-apples. Calibration examples come from the same participant
     (that is the point of personalization) but are excluded from that
     participant's eval set.
+
+    Onset detection runs each onset function (spectral-flux, superflux, hfc,
+    complex-domain, multi-band) over every recording and matches detected to
+    annotated onsets one-to-one within +/-50 ms. Unknown-label rows are
+    skipped here too, so finding one of those costs a false alarm.
 ";
 
 // ---------------------------------------------------------------------------
@@ -102,8 +114,8 @@ PROTOCOL:
 // ---------------------------------------------------------------------------
 
 /// A single annotated utterance: an onset within a WAV, its participant, and
-/// the Beatrice class it maps to. `avp_raw` preserves the original AVP label so
-/// the report can count open-hat (`hho`) folding.
+/// the Beatrice class it maps to. `avp_raw` preserves the original AVP label
+/// for the feature dump.
 #[derive(Debug, Clone)]
 struct Item {
     participant: String,
@@ -114,15 +126,12 @@ struct Item {
 }
 
 /// Map an AVP class label to Beatrice's taxonomy.
-///
-/// `hho` (open hi-hat) folds onto `HihatNoise` because Beatrice has no
-/// open-hat class; the caller counts hho separately for the report caveat.
 fn map_avp_class(label: &str) -> Option<EventClass> {
     match label.trim().to_lowercase().as_str() {
         "kd" => Some(EventClass::BilabialPlosive),
         "sd" => Some(EventClass::Click),
         "hhc" => Some(EventClass::HihatNoise),
-        "hho" => Some(EventClass::HihatNoise),
+        "hho" => Some(EventClass::OpenHihat),
         _ => None,
     }
 }
@@ -244,7 +253,7 @@ fn precision(conf: &Confusion, c: EventClass) -> Option<f64> {
 /// Recall for one class: correct predictions of `c` / all true `c`.
 fn recall(conf: &Confusion, c: EventClass) -> Option<f64> {
     let tp = *conf.get(&(c, c)).unwrap_or(&0);
-    let actual: usize = CLASSES.iter().map(|&p| *conf.get(&(c, p)).unwrap_or(&0)).sum();
+    let actual = support(conf, c);
     if actual == 0 {
         None
     } else {
@@ -252,13 +261,46 @@ fn recall(conf: &Confusion, c: EventClass) -> Option<f64> {
     }
 }
 
+/// Number of eval utterances whose truth is `c`.
+fn support(conf: &Confusion, c: EventClass) -> usize {
+    CLASSES.iter().map(|&p| *conf.get(&(c, p)).unwrap_or(&0)).sum()
+}
+
+/// Match detected onsets to annotated ones one-to-one within `tolerance_ms`
+/// (both sorted ascending, each annotation taking the earliest unmatched
+/// detection in range). Returns `(hits, false_alarms, misses)`.
+fn match_onsets(detected: &[f64], truth: &[f64], tolerance_ms: f64) -> (usize, usize, usize) {
+    let mut hits = 0;
+    let mut d = 0;
+    for &t in truth {
+        while d < detected.len() && detected[d] < t - tolerance_ms {
+            d += 1;
+        }
+        if d < detected.len() && detected[d] <= t + tolerance_ms {
+            hits += 1;
+            d += 1;
+        }
+    }
+    (hits, detected.len() - hits, truth.len() - hits)
+}
+
+/// F-measure of `(hits, false_alarms, misses)`; `None` with nothing to score.
+fn f_measure((hits, false_alarms, misses): (usize, usize, usize)) -> Option<f64> {
+    let denom = 2 * hits + false_alarms + misses;
+    (denom > 0).then(|| 2.0 * hits as f64 / denom as f64)
+}
+
 /// Short human label used in report tables.
 fn class_label(c: EventClass) -> &'static str {
     match c {
         EventClass::BilabialPlosive => "kd → BilabialPlosive",
-        EventClass::HihatNoise => "hhc/hho → HihatNoise",
+        EventClass::HihatNoise => "hhc → HihatNoise",
         EventClass::Click => "sd → Click",
         EventClass::HumVoiced => "(none) → HumVoiced",
+        EventClass::OpenHihat => "hho → OpenHihat",
+        EventClass::Rimshot => "(none) → Rimshot",
+        EventClass::Crash => "(none) → Crash",
+        EventClass::Noise => "(none) → Noise",
     }
 }
 
@@ -411,6 +453,69 @@ fn extract_all_mfccs(
     Ok(mfccs)
 }
 
+/// Onset-detection scores for one `OnsetFunction`.
+struct OnsetResult {
+    function: OnsetFunction,
+    /// `(hits, false_alarms, misses)` per participant, in participant order.
+    per_participant: Vec<(usize, usize, usize)>,
+}
+
+impl OnsetResult {
+    /// Counts pooled over every recording.
+    fn pooled(&self) -> (usize, usize, usize) {
+        self.per_participant
+            .iter()
+            .fold((0, 0, 0), |acc, c| (acc.0 + c.0, acc.1 + c.1, acc.2 + c.2))
+    }
+
+    /// Mean over participants of each participant's F-measure.
+    fn mean_participant_f(&self) -> f64 {
+        let fs: Vec<f64> = self.per_participant.iter().filter_map(|&c| f_measure(c)).collect();
+        if fs.is_empty() {
+            return 0.0;
+        }
+        fs.iter().sum::<f64>() / fs.len() as f64
+    }
+}
+
+/// Run every onset function over every annotated recording (each decoded
+/// once) and match the detections against that recording's annotations.
+fn run_onset_passes(items: &[Item]) -> Result<Vec<OnsetResult>, String> {
+    let participants = group_by_participant(items);
+    let mut results: Vec<OnsetResult> = OnsetFunction::ALL
+        .iter()
+        .map(|&function| OnsetResult { function, per_participant: vec![(0, 0, 0); participants.len()] })
+        .collect();
+
+    for (p, (_, indices)) in participants.iter().enumerate() {
+        let mut recordings: Vec<(&Path, Vec<f64>)> = Vec::new();
+        for &i in indices {
+            let it = &items[i];
+            match recordings.iter_mut().find(|(path, _)| *path == it.wav_path.as_path()) {
+                Some((_, onsets)) => onsets.push(it.onset_ms),
+                None => recordings.push((it.wav_path.as_path(), vec![it.onset_ms])),
+            }
+        }
+        for (path, mut truth) in recordings {
+            truth.sort_by(f64::total_cmp);
+            let bytes =
+                std::fs::read(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+            let audio = audio::ingest_wav(&bytes)
+                .map_err(|e| format!("cannot decode {}: {e}", path.display()))?;
+            for result in &mut results {
+                let config = OnsetConfig { onset_function: result.function, ..OnsetConfig::default() };
+                let detected: Vec<f64> =
+                    audio::detect_onsets(&audio, &config).iter().map(|o| o.timestamp_ms).collect();
+                let (hits, false_alarms, misses) =
+                    match_onsets(&detected, &truth, ONSET_TOLERANCE_MS);
+                let counts = &mut result.per_participant[p];
+                *counts = (counts.0 + hits, counts.1 + false_alarms, counts.2 + misses);
+            }
+        }
+    }
+    Ok(results)
+}
+
 // ---------------------------------------------------------------------------
 // Report assembly.
 // ---------------------------------------------------------------------------
@@ -530,12 +635,83 @@ fn run_gaussian_passes(
     (agnostic, adapted)
 }
 
+/// Score the registered classifier `name` on the shared eval set, twice: as
+/// shipped (no profile) and personalised from each participant's calibration
+/// items. Unlike the LOPO rows, the Gaussian-based entries use the embedded
+/// factory model, which was fitted on every AVP participant — read their
+/// numbers as an upper bound.
+fn run_registry_passes(
+    items: &[Item],
+    feats: &[EventFeatures],
+    mfccs: &[Vec<f32>],
+    name: &str,
+    calib_per_class: usize,
+) -> Result<(PassResult, PassResult), String> {
+    let mut shipped = PassResult {
+        per_participant: Vec::new(),
+        confusion: Confusion::new(),
+        scored: 0,
+    };
+    let mut calibrated = PassResult {
+        per_participant: Vec::new(),
+        confusion: Confusion::new(),
+        scored: 0,
+    };
+    let agnostic = build_classifier(name, None)?;
+
+    for (participant, indices) in group_by_participant(items) {
+        let participant_items: Vec<Item> = indices.iter().map(|&i| items[i].clone()).collect();
+        let (calib_idx_local, eval_idx_local) =
+            participant_split_indices(&participant_items, calib_per_class);
+        let mut profile = CalibrationProfile::new(format!("avp-{participant}"));
+        for &li in &calib_idx_local {
+            let g = indices[li];
+            profile.add_sample(CalibrationSample::with_mfcc(
+                items[g].label,
+                feats[g].clone(),
+                mfccs[g].clone(),
+                Vec::new(),
+                44100,
+            ));
+        }
+        let personal = build_classifier(name, Some(&profile))?;
+
+        let mut shipped_correct = 0usize;
+        let mut calibrated_correct = 0usize;
+        for &li in &eval_idx_local {
+            let g = indices[li];
+            let truth = items[g].label;
+            let input = ClassifierInput::new(&feats[g], &mfccs[g]);
+
+            let spred = agnostic.classify(&input).class;
+            *shipped.confusion.entry((truth, spred)).or_insert(0) += 1;
+            if spred == truth {
+                shipped_correct += 1;
+            }
+            shipped.scored += 1;
+
+            let cpred = personal.classify(&input).class;
+            *calibrated.confusion.entry((truth, cpred)).or_insert(0) += 1;
+            if cpred == truth {
+                calibrated_correct += 1;
+            }
+            calibrated.scored += 1;
+        }
+        if !eval_idx_local.is_empty() {
+            shipped.per_participant.push((shipped_correct, eval_idx_local.len()));
+            calibrated.per_participant.push((calibrated_correct, eval_idx_local.len()));
+        }
+    }
+
+    Ok((shipped, calibrated))
+}
+
 /// Run both passes. Both are scored on the same held-out eval set.
 fn run_passes(
     items: &[Item],
     feats: &[EventFeatures],
     calib_per_class: usize,
-) -> (PassResult, PassResult, usize) {
+) -> (PassResult, PassResult) {
     let heuristic = HeuristicClassifier::new();
     let mut heur = PassResult {
         per_participant: Vec::new(),
@@ -547,8 +723,6 @@ fn run_passes(
         confusion: Confusion::new(),
         scored: 0,
     };
-    let mut open_hat_eval = 0usize;
-
     for (_participant, indices) in group_by_participant(items) {
         // Split this participant's items into calib/eval by class.
         let participant_items: Vec<Item> = indices.iter().map(|&i| items[i].clone()).collect();
@@ -571,9 +745,6 @@ fn run_passes(
 
         for &g in &eval_global {
             let truth = items[g].label;
-            if items[g].avp_raw == "hho" {
-                open_hat_eval += 1;
-            }
 
             // Heuristic pass.
             let hpred = heuristic.classify(&feats[g]).class;
@@ -604,7 +775,7 @@ fn run_passes(
         }
     }
 
-    (heur, calib, open_hat_eval)
+    (heur, calib)
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -612,13 +783,14 @@ fn build_report(
     dataset: &Path,
     items: &[Item],
     skipped_unknown: usize,
-    open_hat_eval: usize,
     calib_per_class: usize,
     window_ms: f64,
     heur: &PassResult,
     calib: &PassResult,
     gauss_agn: &PassResult,
     gauss_ada: &PassResult,
+    registry: &[(String, PassResult, PassResult)],
+    onsets: &[OnsetResult],
 ) -> String {
     let participants = group_by_participant(items).len();
     let heur_overall = mean_participant_accuracy(&heur.per_participant) * 100.0;
@@ -638,8 +810,7 @@ fn build_report(
          - Utterances scored (held-out eval set): **{}**\n\
          - Calibration per class per participant: **{calib_per_class}**\n\
          - Feature window: **{window_ms:.0} ms**\n\
-         - Unknown-label rows skipped: **{skipped_unknown}**\n\
-         - Open-hat (`hho`) utterances in eval, folded into HihatNoise: **{open_hat_eval}**\n\n",
+         - Unknown-label rows skipped: **{skipped_unknown}**\n\n",
         items.len(),
         heur.scored,
     ));
@@ -654,22 +825,31 @@ fn build_report(
         "| Gaussian MFCC model, user-agnostic (LOPO) | {gauss_agn_overall:.1}% |\n"
     ));
     out.push_str(&format!(
-        "| Gaussian MFCC model + MAP calibration (LOPO, tau={DEFAULT_MAP_TAU:.0}) | **{gauss_ada_overall:.1}%** |\n\n"
+        "| Gaussian MFCC model + MAP calibration (LOPO, tau={DEFAULT_MAP_TAU:.0}) | **{gauss_ada_overall:.1}%** |\n"
     ));
+    for (name, shipped, calibrated) in registry {
+        out.push_str(&format!(
+            "| `{name}` as shipped | {:.1}% |\n| `{name}` + calibration | {:.1}% |\n",
+            mean_participant_accuracy(&shipped.per_participant) * 100.0,
+            mean_participant_accuracy(&calibrated.per_participant) * 100.0,
+        ));
+    }
+    out.push('\n');
 
     out.push_str("## Per-class precision / recall\n\n");
     out.push_str(
-        "| Class | Heuristic P | Heuristic R | kNN P | kNN R | Gaussian P | Gaussian R |\n",
+        "| Class | Eval N | Heuristic P | Heuristic R | kNN P | kNN R | Gaussian P | Gaussian R |\n",
     );
-    out.push_str("|---|---|---|---|---|---|---|\n");
+    out.push_str("|---|---|---|---|---|---|---|---|\n");
     let fmt = |v: Option<f64>| match v {
         Some(x) => format!("{:.1}%", x * 100.0),
         None => "—".to_string(),
     };
     for &c in &CLASSES {
         out.push_str(&format!(
-            "| {} | {} | {} | {} | {} | {} | {} |\n",
+            "| {} | {} | {} | {} | {} | {} | {} | {} |\n",
             class_label(c),
+            support(&heur.confusion, c),
             fmt(precision(&heur.confusion, c)),
             fmt(recall(&heur.confusion, c)),
             fmt(precision(&calib.confusion, c)),
@@ -680,6 +860,42 @@ fn build_report(
     }
     out.push('\n');
 
+    for (name, shipped, calibrated) in registry {
+        out.push_str(&format!("## `{name}` per-class precision / recall\n\n"));
+        out.push_str("| Class | Shipped P | Shipped R | Calibrated P | Calibrated R |\n");
+        out.push_str("|---|---|---|---|---|\n");
+        for &c in &CLASSES {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                class_label(c),
+                fmt(precision(&shipped.confusion, c)),
+                fmt(recall(&shipped.confusion, c)),
+                fmt(precision(&calibrated.confusion, c)),
+                fmt(recall(&calibrated.confusion, c)),
+            ));
+        }
+        out.push('\n');
+    }
+
+    if !onsets.is_empty() {
+        out.push_str(&format!("## Onset detection (±{ONSET_TOLERANCE_MS:.0} ms)\n\n"));
+        out.push_str("| Onset function | Precision | Recall | F-measure | Participant-wise F |\n");
+        out.push_str("|---|---|---|---|---|\n");
+        for result in onsets {
+            let (hits, false_alarms, misses) = result.pooled();
+            let ratio = |n: usize, d: usize| (d > 0).then(|| n as f64 / d as f64);
+            out.push_str(&format!(
+                "| `{}` | {} | {} | {} | {:.1}% |\n",
+                result.function,
+                fmt(ratio(hits, hits + false_alarms)),
+                fmt(ratio(hits, hits + misses)),
+                fmt(f_measure((hits, false_alarms, misses))),
+                result.mean_participant_f() * 100.0,
+            ));
+        }
+        out.push('\n');
+    }
+
     out.push_str(
         "## Protocol\n\n\
          Participant-wise accuracy (Delgado et al.): the mean over participants of each \
@@ -689,8 +905,14 @@ fn build_report(
          participant's eval set. The Gaussian rows are leave-one-participant-out: each \
          participant is scored by a model fitted only on the other 27 participants \
          (their own voice never trains their factory model), then MAP-adapted from their \
-         calibration samples. Open hi-hat (`hho`) has no Beatrice class and is folded \
-         into HihatNoise (counted above).\n\n\
+         calibration samples. Open hi-hat (`hho`) is scored as its own class; rimshot, \
+         crash, hum and noise have no AVP truth (Eval N 0), so their rows only carry \
+         precision, i.e. how often other sounds are mistaken for them. Rows for \
+         classifiers picked with `--classifier` use the embedded factory model, which \
+         saw every participant, so they are not leave-one-participant-out. Onset \
+         detection runs each onset function with the app's default settings over every \
+         recording; a detection within ±50 ms of an annotation (one-to-one) is a hit, and \
+         participant-wise F is the mean of each participant's F-measure.\n\n\
          Dataset: AVP \"Amateur Vocal Percussion\" (Delgado et al.), Zenodo, CC-BY.\n",
     );
 
@@ -709,6 +931,7 @@ struct Args {
     window_ms: f64,
     dump_features: Option<PathBuf>,
     fit_model: Option<PathBuf>,
+    classifiers: Vec<String>,
 }
 
 /// Parse CLI args. Returns `Ok(None)` when `--help` was requested.
@@ -719,6 +942,7 @@ fn parse_args(argv: &[String]) -> Result<Option<Args>, String> {
     let mut window_ms = FEATURE_WINDOW_MS;
     let mut dump_features: Option<PathBuf> = None;
     let mut fit_model: Option<PathBuf> = None;
+    let mut classifiers: Vec<String> = Vec::new();
 
     let mut i = 0;
     while i < argv.len() {
@@ -758,6 +982,14 @@ fn parse_args(argv: &[String]) -> Result<Option<Args>, String> {
                 let v = argv.get(i).ok_or("--fit-model requires a path argument")?;
                 fit_model = Some(PathBuf::from(v));
             }
+            "--classifier" => {
+                i += 1;
+                let v = argv.get(i).ok_or("--classifier requires a name")?;
+                if classifier_entry(v).is_none() {
+                    return Err(format!("invalid --classifier value: {v}"));
+                }
+                classifiers.push(v.clone());
+            }
             other => return Err(format!("unknown argument: {other}\n\nRun with --help.")),
         }
         i += 1;
@@ -774,6 +1006,7 @@ fn parse_args(argv: &[String]) -> Result<Option<Args>, String> {
         window_ms,
         dump_features,
         fit_model,
+        classifiers,
     }))
 }
 
@@ -878,13 +1111,24 @@ fn run() -> Result<(), String> {
     }
 
     println!("Running heuristic + calibrated passes ...");
-    let (heur, calib, open_hat_eval) = run_passes(&items, &feats, args.calib_per_class);
+    let (heur, calib) = run_passes(&items, &feats, args.calib_per_class);
 
     println!("Running Gaussian LOPO passes (28 fits) ...");
     let mfcc_means: Vec<Vec<f32>> = mfccs.iter().map(|(mean, _)| mean.clone()).collect();
     let (gauss_agn, gauss_ada) =
         run_gaussian_passes(&items, &feats, &mfcc_means, &gfeats, args.calib_per_class);
 
+    let mut registry = Vec::new();
+    for name in &args.classifiers {
+        println!("Running registry classifier `{name}` ...");
+        let (shipped, calibrated) =
+            run_registry_passes(&items, &feats, &mfcc_means, name, args.calib_per_class)?;
+        registry.push((name.clone(), shipped, calibrated));
+    }
+
+    println!("Scoring onset detection functions ...");
+    let onsets = run_onset_passes(&items)?;
+
     if let Some(model_path) = &args.fit_model {
         // The shipping artifact: fitted on ALL participants (LOPO above is the
         // honest accuracy estimate for exactly this construction).
@@ -913,13 +1157,14 @@ fn run() -> Result<(), String> {
         &args.dataset,
         &items,
         skipped_unknown,
-        open_hat_eval,
         args.calib_per_class,
         args.window_ms,
         &heur,
         &calib,
         &gauss_agn,
         &gauss_ada,
+        &registry,
+        &onsets,
     );
 
     // Print the tables to stdout (skip the leading "# AVP Benchmark Results").
@@ -969,14 +1214,14 @@ mod tests {
         assert_eq!(map_avp_class("kd"), Some(EventClass::BilabialPlosive));
         assert_eq!(map_avp_class("sd"), Some(EventClass::Click));
         assert_eq!(map_avp_class("hhc"), Some(EventClass::HihatNoise));
-        assert_eq!(map_avp_class("hho"), Some(EventClass::HihatNoise));
+        assert_eq!(map_avp_class("hho"), Some(EventClass::OpenHihat));
         assert_eq!(map_avp_class("xx"), None);
     }
 
     #[test]
     fn avp_class_mapping_is_case_and_space_insensitive() {
         assert_eq!(map_avp_class(" KD "), Some(EventClass::BilabialPlosive));
-        assert_eq!(map_avp_class("HHO"), Some(EventClass::HihatNoise));
+        assert_eq!(map_avp_class("HHO"), Some(EventClass::OpenHihat));
     }
 
     #[test]
@@ -1052,11 +1297,29 @@ mod tests {
         assert_eq!(items.len(), 2);
         assert_eq!(items[0].label, EventClass::BilabialPlosive);
         assert_eq!(items[0].onset_ms, 500.0); // seconds → ms
-        assert_eq!(items[1].label, EventClass::HihatNoise);
+        assert_eq!(items[1].label, EventClass::OpenHihat);
         assert_eq!(items[1].avp_raw, "hho");
         assert_eq!(items[0].participant, "p01");
     }
 
+    #[test]
+    fn match_onsets_pairs_one_to_one_within_tolerance() {
+        let truth = [100.0, 500.0, 900.0];
+        // 480 hits 500; 520 is a second detection of the same onset (false
+        // alarm); 960 is out of range of 900 (miss + false alarm); 100 ± 0 hits.
+        let detected = [100.0, 480.0, 520.0, 960.0];
+        assert_eq!(match_onsets(&detected, &truth, 50.0), (2, 2, 1));
+        assert_eq!(match_onsets(&[], &truth, 50.0), (0, 0, 3));
+        assert_eq!(match_onsets(&detected, &[], 50.0), (0, 4, 0));
+    }
+
+    #[test]
+    fn f_measure_from_counts() {
+        assert_eq!(f_measure((0, 0, 0)), None);
+        assert!((f_measure((2, 2, 1)).unwrap() - 4.0 / 7.0).abs() < 1e-12);
+        assert_eq!(f_measure((3, 0, 0)), Some(1.0));
+    }
+
     #[test]
     fn mean_participant_accuracy_averages_over_participants() {
         // Participant-wise mean must weight each participant equally regardless
@@ -1091,6 +1354,22 @@ mod tests {
         assert!(precision(&conf, EventClass::HumVoiced).is_none());
     }
 
+    #[test]
+    fn open_hats_are_scored_as_their_own_class() {
+        let mut conf: Confusion = HashMap::new();
+        // Truth hho: 2 right, 2 heard as a closed hat.
+        conf.insert((EventClass::OpenHihat, EventClass::OpenHihat), 2);
+        conf.insert((EventClass::OpenHihat, EventClass::HihatNoise), 2);
+
+        assert_eq!(support(&conf, EventClass::OpenHihat), 4);
+        assert!((recall(&conf, EventClass::OpenHihat).unwrap() - 0.5).abs() < 1e-9);
+        // The closed-hat row sees only false positives.
+        assert_eq!(precision(&conf, EventClass::HihatNoise), Some(0.0));
+        assert!(recall(&conf, EventClass::HihatNoise).is_none());
+        // Classes AVP never labels have no support.
+        assert_eq!(support(&conf, EventClass::Crash), 0);
+    }
+
     #[test]
     fn parse_args_requires_dataset() {
         let err = parse_args(&["--out".to_string(), "x.md".to_string()]).unwrap_err();
@@ -1114,12 +1393,20 @@ mod tests {
             "3".to_string(),
             "--window-ms".to_string(),
             "200".to_string(),
+            "--classifier".to_string(),
+            "knn".to_string(),
+            "--classifier".to_string(),
+            "heuristic".to_string(),
         ];
         let args = parse_args(&argv).unwrap().unwrap();
         assert_eq!(args.dataset, PathBuf::from("/data/AVP"));
         assert_eq!(args.out, PathBuf::from("r.md"));
         assert_eq!(args.calib_per_class, 3);
         assert!((args.window_ms - 200.0).abs() < 1e-9);
+        assert_eq!(args.classifiers, ["knn", "heuristic"]);
+
+        let bad = ["--dataset", "/data/AVP", "--classifier", "onnx"].map(String::from);
+        assert!(parse_args(&bad).unwrap_err().contains("onnx"));
     }
 
     #[test]
@@ -1,16 +1,22 @@
 // Tauri IPC Commands
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use uuid::Uuid;
 
-use crate::arranger::{self, ArrangementTemplate, Arrangement, MidiExportOptions};
-use crate::audio::{self, OnsetConfig};
-use crate::events::{self, ClassScore, Event, EventClass, EventDecision, EventFeatures};
-use crate::groove::{self, TempoEstimate, Grid, GridDivision, GrooveFeel, TimeSignature, QuantizeSettings, QuantizedEvent};
+use crate::arranger::Arrangement;
+use crate::audio::{
+    self, AudioSource, ChannelStrategy, ConditioningConfig, OnsetConfig, OnsetFunction,
+};
+use crate::events::{self, EventData, EventDecision, EventFeatures, MultiLabelConfig};
+use crate::groove::{self, TempoEstimate, QuantizedEvent};
 use crate::pipeline::{TraceBuilder, TraceWriter};
+use crate::render::{self, RenderOptions};
+use crate::song::{self, ArrangeEventsInput, ExportMidiInput, QuantizeEventsInput};
 use crate::state::{
-    self, ArtifactKind, CalibrationProfile, DbConnection, Project, ProjectSummary, Run,
-    RunStatus, RunWithArtifacts,
+    self, ArtifactKind, CalibrationProfile, DbConnection, NoiseProfile, Project, ProjectSummary,
+    Run, RunStatus, RunWithArtifacts,
 };
 
 #[derive(Debug, Serialize, specta::Type)]
@@ -49,7 +55,7 @@ pub async fn create_project(
     input: CreateProjectInput,
 ) -> CommandResult<Project> {
     // Ingest audio to extract metadata and validate format
-    let audio_data = crate::audio::ingest_wav(&input.input_data)
+    let audio_data = crate::audio::ingest_audio(&input.input_data)
         .map_err(|e| CommandError {
             message: format!("Failed to process audio file: {}", e),
         })?;
@@ -57,11 +63,17 @@ pub async fn create_project(
     // Calculate hash
     let input_sha256 = state::storage::calculate_sha256(&input.input_data);
 
-    // Store the input file
+    // Store the input file as-is, named for its sniffed container
+    let extension = crate::audio::AudioFormat::sniff(&input.input_data)
+        .map_or("wav", |f| f.extension());
     let project_id = Uuid::new_v4();
-    let (input_path, _) =
-        state::storage::store_file(&project_id, None, "input.wav", &input.input_data)
-            .map_err(CommandError::from)?;
+    let (input_path, _) = state::storage::store_file(
+        &project_id,
+        None,
+        &format!("input.{}", extension),
+        &input.input_data,
+    )
+    .map_err(CommandError::from)?;
 
     // Use audio metadata for duration
     let duration_ms = audio_data.duration_ms;
@@ -115,6 +127,14 @@ pub struct CreateRunInput {
     /// to 0 so callers that predate phase persistence keep working.
     #[serde(default)]
     pub phase_offset_ms: Option<f64>,
+    /// Channel strategy the run's events are detected with. Defaults to
+    /// averaging the channels, the behaviour before strategies existed.
+    #[serde(default)]
+    pub channel_strategy: Option<ChannelStrategy>,
+    /// Pre-processing chain the run's events are detected with. Defaults to
+    /// none (the raw signal).
+    #[serde(default)]
+    pub conditioning: Option<ConditioningConfig>,
 }
 
 #[tauri::command]
@@ -132,6 +152,8 @@ pub fn create_run(db: State<'_, DbConnection>, input: CreateRunInput) -> Command
         input.quantize_strength,
         input.b_emphasis,
         input.phase_offset_ms.unwrap_or(0.0),
+        input.channel_strategy.unwrap_or_default(),
+        input.conditioning,
     )
     .map_err(CommandError::from)?;
 
@@ -311,6 +333,103 @@ pub fn delete_calibration_profile(
     state::delete_calibration_profile(&db, &uuid).map_err(CommandError::from)
 }
 
+/// Grade a stored calibration profile: leave-one-out accuracy per class,
+/// which classes overlap, and which samples look like outliers
+#[tauri::command]
+#[specta::specta]
+pub fn get_calibration_report(
+    db: State<'_, DbConnection>,
+    id: String,
+) -> CommandResult<events::CalibrationReport> {
+    let uuid = Uuid::parse_str(&id).map_err(CommandError::from)?;
+    let db_profile = state::get_calibration_profile(&db, &uuid)
+        .map_err(CommandError::from)?
+        .ok_or_else(|| CommandError {
+            message: "Calibration profile not found".to_string(),
+        })?;
+    let profile_data = std::fs::read(&db_profile.profile_json_path).map_err(|e| CommandError {
+        message: format!("Failed to read calibration profile: {}", e),
+    })?;
+    let profile = events::CalibrationProfile::from_json_bytes(&profile_data).map_err(|e| {
+        CommandError {
+            message: format!("Failed to parse calibration profile: {}", e),
+        }
+    })?;
+    Ok(profile.report())
+}
+
+// ==================== NOISE PROFILE COMMANDS ====================
+
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct CreateNoiseProfileInput {
+    pub name: String,
+    /// A few seconds of room tone: the WAV bytes `stop_recording` returns, or
+    /// any supported audio file's bytes.
+    pub audio_data: Vec<u8>,
+    pub notes: Option<String>,
+}
+
+/// Measure a room-tone capture and store it as a noise profile
+#[tauri::command]
+#[specta::specta]
+pub async fn create_noise_profile(
+    db: State<'_, DbConnection>,
+    input: CreateNoiseProfileInput,
+) -> CommandResult<NoiseProfile> {
+    let audio = audio::ingest_audio(&input.audio_data).map_err(|e| CommandError {
+        message: format!("Failed to ingest audio: {}", e),
+    })?;
+    let measured = audio::noise::NoiseProfile::from_audio(&audio).ok_or_else(|| CommandError {
+        message: format!(
+            "Room tone too short: {} ms recorded, at least {} ms needed",
+            audio.duration_ms,
+            audio::noise::MIN_ROOM_TONE_MS
+        ),
+    })?;
+    let profile_data = measured.to_json_bytes().map_err(CommandError::from)?;
+
+    let profile_id = Uuid::new_v4();
+    let (path, _) = state::storage::store_calibration_profile(
+        &profile_id,
+        "noise_profile.json",
+        &profile_data,
+    )
+    .map_err(CommandError::from)?;
+
+    state::create_noise_profile(
+        &db,
+        profile_id,
+        input.name,
+        path.to_string_lossy().to_string(),
+        measured.noise_floor_dbfs(),
+        input.notes,
+    )
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_noise_profile(
+    db: State<'_, DbConnection>,
+    id: String,
+) -> CommandResult<Option<NoiseProfile>> {
+    let uuid = Uuid::parse_str(&id).map_err(CommandError::from)?;
+    state::get_noise_profile(&db, &uuid).map_err(CommandError::from)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_noise_profiles(db: State<'_, DbConnection>) -> CommandResult<Vec<NoiseProfile>> {
+    state::list_noise_profiles(&db).map_err(CommandError::from)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn delete_noise_profile(db: State<'_, DbConnection>, id: String) -> CommandResult<()> {
+    let uuid = Uuid::parse_str(&id).map_err(CommandError::from)?;
+    state::delete_noise_profile(&db, &uuid).map_err(CommandError::from)
+}
+
 // ==================== EVENT DETECTION COMMANDS ====================
 
 #[derive(Debug, Serialize, specta::Type)]
@@ -338,7 +457,7 @@ pub struct DetectOnsetsInput {
 #[specta::specta]
 pub fn detect_onsets(input: DetectOnsetsInput) -> CommandResult<OnsetDetectionResult> {
     // Ingest audio
-    let audio = audio::ingest_wav(&input.audio_data).map_err(|e| CommandError {
+    let audio = audio::ingest_audio(&input.audio_data).map_err(|e| CommandError {
         message: format!("Failed to ingest audio: {}", e),
     })?;
 
@@ -377,26 +496,41 @@ pub struct EventDetectionResult {
     pub total_count: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize, specta::Type)]
-pub struct EventData {
-    pub id: String,
-    pub timestamp_ms: f64,
-    pub duration_ms: f64,
-    pub class: String,
-    pub confidence: f32,
-    pub features: EventFeatures,
-    /// Per-class classifier scores. `serde(default)` so older callers/rows that
-    /// predate score threading still deserialize (empty vec).
-    #[serde(default)]
-    pub all_scores: Vec<ClassScore>,
-}
-
 #[derive(Debug, Deserialize, specta::Type)]
 pub struct DetectEventsInput {
     pub file_path: String,
     pub run_id: Option<String>,
     pub use_calibration: bool,
     pub calibration_profile_id: Option<String>,
+    /// Resample to the classifier's training rate (44.1 kHz) before detection,
+    /// so 48/96 kHz recordings see the statistics the model was fitted on.
+    pub normalize_sample_rate: bool,
+    /// How multi-channel recordings are reduced before detection (average,
+    /// one channel, loudest channel, mid/side, or every channel merged).
+    /// Defaults to averaging.
+    #[serde(default)]
+    pub channel_strategy: Option<ChannelStrategy>,
+    /// DC blocking, high-pass, noise reduction and normalization applied
+    /// before detection and feature extraction. Defaults to off.
+    #[serde(default)]
+    pub conditioning: Option<ConditioningConfig>,
+    /// Room-tone profile (from `create_noise_profile`) that raises the onset
+    /// gate above the room's noise and seeds the conditioning denoiser.
+    #[serde(default)]
+    pub noise_profile_id: Option<String>,
+    /// Registered classifier to run (`hybrid`, `gaussian`, `heuristic`, `knn`).
+    /// Defaults to the shipping hybrid.
+    #[serde(default)]
+    pub classifier: Option<String>,
+    /// Onset detection function (spectral flux, SuperFlux, HFC,
+    /// complex-domain or multi-band). Defaults to spectral flux.
+    #[serde(default)]
+    pub onset_function: Option<OnsetFunction>,
+    /// Multi-label mode: emit one event per sound layered on an onset (a
+    /// kick with a hat on top becomes a kick and a hat at the same time).
+    /// Defaults to off (one event per onset).
+    #[serde(default)]
+    pub multi_label: Option<MultiLabelConfig>,
 }
 
 /// Detect and classify events in audio data
@@ -406,26 +540,17 @@ pub async fn detect_events(
     db: State<'_, DbConnection>,
     input: DetectEventsInput,
 ) -> CommandResult<EventDetectionResult> {
-    // Read audio from disk with size limit (50MB)
-    const MAX_FILE_SIZE: u64 = 50 * 1024 * 1024;
-    let metadata = std::fs::metadata(&input.file_path).map_err(|e| CommandError {
-        message: format!("Cannot access audio file: {}", e),
-    })?;
-    if metadata.len() > MAX_FILE_SIZE {
-        return Err(CommandError {
-            message: format!(
-                "Audio file too large ({:.1}MB). Maximum is {:.0}MB.",
-                metadata.len() as f64 / 1_048_576.0,
-                MAX_FILE_SIZE as f64 / 1_048_576.0,
-            ),
-        });
-    }
-    let file_bytes = std::fs::read(&input.file_path).map_err(|e| CommandError {
-        message: format!("Failed to read audio file: {}", e),
-    })?;
-    let audio = audio::ingest_wav(&file_bytes).map_err(|e| CommandError {
+    // Stream the recording from disk rather than reading it whole: rehearsal
+    // takes run 10-30 minutes, so memory stays bounded by the chunk size.
+    let source = audio::open_audio(Path::new(&input.file_path)).map_err(|e| CommandError {
         message: format!("Failed to ingest audio: {}", e),
     })?;
+    let mut source: audio::BoxedAudioSource = if input.normalize_sample_rate {
+        Box::new(audio::ResampledSource::new(source, audio::FACTORY_SAMPLE_RATE))
+    } else {
+        source
+    };
+    let analysis_sample_rate = source.sample_rate();
 
     // Initialize trace writer if run_id is provided
     let trace_writer = if let Some(ref run_id_str) = input.run_id {
@@ -452,21 +577,13 @@ pub async fn detect_events(
         let _ = writer.write(&entry);
     }
 
-    // Detect onsets
-    let config = OnsetConfig::default();
-    let onsets = audio::detect_onsets(&audio, &config);
-
-    if let Some(ref writer) = trace_writer {
-        let data = serde_json::json!({
-            "onsets_detected": onsets.len()
-        });
-        let entry = TraceBuilder::stage("event_detection")
-            .with_data(0.3, format!("Detected {} onsets", onsets.len()), data);
-        let _ = writer.write(&entry);
-    }
-
-    // Initialize classifier
-    let classifier = if input.use_calibration {
+    // Initialize classifier: the selected registry entry, personalised from
+    // the calibration profile when one is in use.
+    let classifier_name = input
+        .classifier
+        .as_deref()
+        .unwrap_or(events::DEFAULT_CLASSIFIER);
+    let calibration_profile = if input.use_calibration {
         if let Some(ref profile_id_str) = input.calibration_profile_id {
             let profile_id =
                 Uuid::parse_str(profile_id_str).map_err(CommandError::from)?;
@@ -492,17 +609,10 @@ pub async fn detect_events(
                     }
                 })?;
 
-            // MAP-adapt the factory Gaussian model from the user's labeled
-            // calibration samples (AVP LOPO: 81.6% adapted vs 79.7% agnostic;
-            // the old per-user kNN sat at 60.2% and is retired from this path).
-            let samples: Vec<(EventClass, Vec<f32>)> = calibration_profile
-                .samples
-                .values()
-                .flatten()
-                .filter(|s| s.has_mfcc()) // legacy MFCC-less samples poison MAP
-                .map(|s| (s.class, s.gaussian_vec()))
-                .collect();
-            events::HybridClassifier::with_adaptation(&samples)
+            // The default hybrid MAP-adapts the factory Gaussian model from the
+            // user's labeled calibration samples (AVP LOPO: 81.6% adapted vs
+            // 79.7% agnostic; the per-user kNN sat at 60.2%).
+            Some(calibration_profile)
         } else {
             return Err(CommandError {
                 message: "Calibration profile ID required when use_calibration is true"
@@ -510,67 +620,94 @@ pub async fn detect_events(
             });
         }
     } else {
-        events::HybridClassifier::factory()
+        None
     };
-
-    // Classify each onset
-    let mono = audio.to_mono();
-    let mut events = Vec::new();
-
-    for (i, onset) in onsets.iter().enumerate() {
-        // Calculate duration (to next onset or end of audio)
-        let duration_ms = if i + 1 < onsets.len() {
-            onsets[i + 1].timestamp_ms - onset.timestamp_ms
-        } else {
-            audio.duration_ms as f64 - onset.timestamp_ms
-        };
-
-        // Scalar features over the fixed window the factory model was fitted
-        // with (matches analyze_offline_hybrid and the streaming detector).
-        let features = audio::extract_features_for_window(
-            &audio,
-            onset.timestamp_ms,
-            events::hybrid::HYBRID_MFCC_WINDOW_MS,
-        );
-
-        // MFCCs over the fixed window the factory model was fitted with.
-        let start = ((onset.timestamp_ms / 1000.0) * audio.sample_rate as f64) as usize;
-        let mfcc_len = ((events::hybrid::HYBRID_MFCC_WINDOW_MS / 1000.0)
-            * audio.sample_rate as f64) as usize;
-        let end = (start + mfcc_len).min(mono.len());
-        let mfcc = if start < end {
-            audio::extract_mfcc(&mono[start..end], audio.sample_rate)
-        } else {
-            vec![0.0; audio::MFCC_COEFFS]
+    let classifier = events::build_classifier(classifier_name, calibration_profile.as_ref())
+        .map_err(|message| CommandError { message })?;
+
+    // Detect onsets (pass 1, traced as 0.0-0.3) and classify them (pass 2,
+    // 0.3-0.9). Scan progress is traced every 5% of the scan so a long take
+    // doesn't flood the trace file.
+    let mut config = OnsetConfig {
+        channels: input.channel_strategy.unwrap_or_default(),
+        conditioning: input.conditioning,
+        onset_function: input.onset_function.unwrap_or_default(),
+        multi_label: input.multi_label,
+        ..OnsetConfig::default()
+    };
+    if let Some(ref profile_id_str) = input.noise_profile_id {
+        let profile_id = Uuid::parse_str(profile_id_str).map_err(CommandError::from)?;
+        let db_profile = state::get_noise_profile(&db, &profile_id)
+            .map_err(CommandError::from)?
+            .ok_or_else(|| CommandError {
+                message: "Noise profile not found".to_string(),
+            })?;
+        let profile_data = std::fs::read(&db_profile.profile_json_path).map_err(|e| CommandError {
+            message: format!("Failed to read noise profile: {}", e),
+        })?;
+        let noise_profile =
+            audio::noise::NoiseProfile::from_json_bytes(&profile_data).map_err(|e| CommandError {
+                message: format!("Failed to parse noise profile: {}", e),
+            })?;
+        noise_profile.adapt_onset_config(&mut config);
+    }
+    let mut last_scan_progress = 0.0f32;
+    let mut report = |progress: audio::AnalysisProgress| {
+        let Some(ref writer) = trace_writer else { return };
+        let entry = match progress {
+            audio::AnalysisProgress::Scanning { frames, total_frames: Some(total) } if total > 0 => {
+                let progress = 0.3 * (frames as f32 / total as f32).min(1.0);
+                if progress - last_scan_progress < 0.015 {
+                    return;
+                }
+                last_scan_progress = progress;
+                TraceBuilder::stage("event_detection").progress(
+                    progress,
+                    format!(
+                        "Scanned {:.0}s / {:.0}s of audio",
+                        frames as f64 / analysis_sample_rate as f64,
+                        total as f64 / analysis_sample_rate as f64
+                    ),
+                )
+            }
+            audio::AnalysisProgress::Scanning { .. } => return,
+            audio::AnalysisProgress::OnsetsDetected { count } => {
+                let data = serde_json::json!({
+                    "onsets_detected": count
+                });
+                TraceBuilder::stage("event_detection")
+                    .with_data(0.3, format!("Detected {} onsets", count), data)
+            }
+            audio::AnalysisProgress::Classifying { done, total } => {
+                if done % 10 != 0 {
+                    return;
+                }
+                let progress = 0.3 + (0.6 * (done as f32 / total as f32));
+                TraceBuilder::stage("event_detection")
+                    .progress(progress, format!("Classified {} / {} events", done, total))
+            }
         };
+        let _ = writer.write(&entry);
+    };
 
-        let result = classifier.classify(&features, &mfcc);
-        let event = Event::new(
-            onset.timestamp_ms,
-            duration_ms,
-            result.class,
-            result.confidence,
-            features,
-        )
-        .with_scores(result.class_scores());
-        events.push(event);
-
-        // Progress trace
-        if let Some(ref writer) = trace_writer {
-            if i % 10 == 0 {
-                let progress = 0.3 + (0.6 * (i as f32 / onsets.len() as f32));
-                let entry = TraceBuilder::stage("event_detection")
-                    .progress(progress, format!("Classified {} / {} events", i, onsets.len()));
-                let _ = writer.write(&entry);
-            }
-        }
-    }
+    let events = audio::analyze_source_hybrid(&mut source, &config, classifier.as_ref(), &mut report)
+        .map_err(|e| CommandError {
+            message: format!("Failed to read audio: {}", e),
+        })?;
 
     // Write completion trace
     if let Some(ref writer) = trace_writer {
         let data = serde_json::json!({
             "events_detected": events.len(),
-            "used_calibration": input.use_calibration
+            "used_calibration": input.use_calibration,
+            "classifier": classifier_name,
+            "analysis_sample_rate": analysis_sample_rate,
+            "channel_strategy": config.channels.to_string(),
+            "onset_function": config.onset_function.to_string(),
+            "conditioning": config.conditioning,
+            "multi_label": config.multi_label,
+            "noise_profile_id": input.noise_profile_id,
+            "min_flux_threshold": config.min_flux_threshold
         });
         let entry = TraceBuilder::stage("event_detection")
             .with_data(1.0, format!("Detected {} events", events.len()), data);
@@ -578,18 +715,7 @@ pub async fn detect_events(
     }
 
     // Convert to serializable format
-    let event_data: Vec<EventData> = events
-        .iter()
-        .map(|e| EventData {
-            id: e.id.to_string(),
-            timestamp_ms: e.timestamp_ms,
-            duration_ms: e.duration_ms,
-            class: e.class.to_string().to_string(),
-            confidence: e.confidence,
-            features: e.features.clone(),
-            all_scores: e.all_scores.clone(),
-        })
-        .collect();
+    let event_data: Vec<EventData> = events.iter().map(EventData::from).collect();
 
     Ok(EventDetectionResult {
         total_count: event_data.len(),
@@ -608,7 +734,7 @@ pub struct ExtractFeaturesInput {
 #[tauri::command]
 #[specta::specta]
 pub fn extract_features(input: ExtractFeaturesInput) -> CommandResult<EventFeatures> {
-    let audio = audio::ingest_wav(&input.audio_data).map_err(|e| CommandError {
+    let audio = audio::ingest_audio(&input.audio_data).map_err(|e| CommandError {
         message: format!("Failed to ingest audio: {}", e),
     })?;
 
@@ -632,7 +758,7 @@ pub fn estimate_tempo(input: EstimateTempoInput) -> CommandResult<TempoEstimate>
     let file_bytes = std::fs::read(&input.file_path).map_err(|e| CommandError {
         message: format!("Failed to read audio file: {}", e),
     })?;
-    let audio = audio::ingest_wav(&file_bytes).map_err(|e| CommandError {
+    let audio = audio::ingest_audio(&file_bytes).map_err(|e| CommandError {
         message: format!("Failed to ingest audio: {}", e),
     })?;
 
@@ -646,280 +772,63 @@ pub fn estimate_tempo(input: EstimateTempoInput) -> CommandResult<TempoEstimate>
     Ok(tempo_estimate)
 }
 
-#[derive(Debug, Deserialize, specta::Type)]
-pub struct QuantizeEventsInput {
-    pub events: Vec<EventData>,
-    pub bpm: f64,
-    pub time_signature: String,
-    pub division: String,
-    pub feel: String,
-    pub swing_amount: f32,
-    pub bar_count: u32,
-    pub quantize_strength: f32,
-    pub lookahead_ms: f64,
-    /// Grid phase offset (ms) from tempo estimation. Anchors the quantization grid
-    /// to the performer's downbeat. Defaults to 0.0 (t=0 anchor) for back-compat.
-    #[serde(default)]
-    pub phase_offset_ms: Option<f64>,
-}
-
 /// Quantize events to a musical grid
 #[tauri::command]
 #[specta::specta]
 pub fn quantize_events_command(input: QuantizeEventsInput) -> CommandResult<Vec<QuantizedEvent>> {
-    // Parse time signature
-    let time_signature = match input.time_signature.as_str() {
-        "four_four" => TimeSignature::FourFour,
-        "three_four" => TimeSignature::ThreeFour,
-        _ => TimeSignature::FourFour,
-    };
-
-    // Parse grid division
-    let division = match input.division.as_str() {
-        "quarter" => GridDivision::Quarter,
-        "eighth" => GridDivision::Eighth,
-        "sixteenth" => GridDivision::Sixteenth,
-        "triplet" => GridDivision::Triplet,
-        _ => GridDivision::Sixteenth,
-    };
-
-    // Parse groove feel
-    let feel = match input.feel.as_str() {
-        "straight" => GrooveFeel::Straight,
-        "swing" => GrooveFeel::Swing,
-        "halftime" => GrooveFeel::Halftime,
-        _ => GrooveFeel::Straight,
-    };
-
-    // Create grid, anchored to the estimated beat phase so a leading silence /
-    // anacrusis doesn't misquantize every downbeat.
-    let grid = Grid::with_phase(
-        input.bpm,
-        time_signature,
-        division,
-        feel,
-        input.swing_amount,
-        input.bar_count,
-        input.phase_offset_ms.unwrap_or(0.0),
-    );
-
-    // Create quantize settings
-    let settings = QuantizeSettings {
-        strength: input.quantize_strength,
-        swing_amount: input.swing_amount,
-        lookahead_ms: input.lookahead_ms,
-    };
-
-    // Convert EventData back to Event objects
-    let events: Vec<Event> = input
-        .events
-        .iter()
-        .map(|e| {
-            let id = Uuid::parse_str(&e.id).unwrap_or_else(|_| Uuid::new_v4());
-            Event {
-                id,
-                timestamp_ms: e.timestamp_ms,
-                duration_ms: e.duration_ms,
-                class: EventClass::from_string(&e.class),
-                confidence: e.confidence,
-                features: e.features.clone(),
-                all_scores: e.all_scores.clone(),
-            }
-        })
-        .collect();
-
-    // Quantize events
-    let quantized = groove::quantize_events(&events, &grid, &settings);
-
-    Ok(quantized)
+    Ok(input.quantize())
 }
 
 // ==================== ARRANGER COMMANDS ====================
 
-#[derive(Debug, Deserialize, specta::Type)]
-pub struct ArrangeEventsInput {
-    pub events: Vec<QuantizedEvent>,
-    pub template: String,
-    pub theme_name: String,
-    pub bpm: f64,
-    pub time_signature: String,
-    pub division: String,
-    pub feel: String,
-    pub swing_amount: f32,
-    pub bar_count: u32,
-    pub b_emphasis: f32,
-    /// Grid phase offset (ms) from tempo estimation. Anchors the arrangement grid
-    /// (chord boundaries, beat placement) to the performer's downbeat. Defaults to
-    /// 0.0 for back-compat.
-    #[serde(default)]
-    pub phase_offset_ms: Option<f64>,
-    /// Placement fidelity [0.0, 1.0] (spec §4.3). 1.0 "Follow me" plays every event
-    /// at its quantized position (templates only shape velocity); 0.0 "Produce for
-    /// me" snaps off-template hits to the nearest template slot. Never deletes
-    /// events. Defaults to 0.8 (the UI slider lands in Task 4).
-    #[serde(default = "default_fidelity")]
-    pub fidelity: f32,
-}
-
-/// Default placement fidelity when the frontend omits it (serde back-compat).
-fn default_fidelity() -> f32 {
-    0.8
-}
-
 /// Arrange quantized events into a musical arrangement
 #[tauri::command]
 #[specta::specta]
 pub fn arrange_events_command(input: ArrangeEventsInput) -> CommandResult<Arrangement> {
-    // Parse template
-    let template = ArrangementTemplate::from_string(&input.template);
-
-    // Get theme by name. Unknown names must NOT hard-error — a persisted Run may
-    // reference a since-renamed/removed theme. Fall back to BLADE RUNNER and let
-    // the arranger snapshot the RESOLVED canonical name into the Arrangement.
-    let theme = match crate::themes::get_theme(&input.theme_name) {
-        Some(t) => t,
-        None => {
-            log::warn!(
-                "unknown theme '{}', falling back to BLADE RUNNER",
-                input.theme_name
-            );
-            crate::themes::get_theme("BLADE RUNNER").expect("Theme must exist")
-        }
-    };
-
-    // Parse time signature
-    let time_signature = match input.time_signature.as_str() {
-        "four_four" => TimeSignature::FourFour,
-        "three_four" => TimeSignature::ThreeFour,
-        _ => TimeSignature::FourFour,
-    };
-
-    // Parse grid division
-    let division = match input.division.as_str() {
-        "quarter" => GridDivision::Quarter,
-        "eighth" => GridDivision::Eighth,
-        "sixteenth" => GridDivision::Sixteenth,
-        "triplet" => GridDivision::Triplet,
-        _ => GridDivision::Sixteenth,
-    };
-
-    // Parse groove feel
-    let feel = match input.feel.as_str() {
-        "straight" => GrooveFeel::Straight,
-        "swing" => GrooveFeel::Swing,
-        "halftime" => GrooveFeel::Halftime,
-        _ => GrooveFeel::Straight,
-    };
-
-    // Create grid, anchored to the estimated beat phase so chord boundaries and
-    // beat placement line up with the performer's downbeat.
-    let grid = Grid::with_phase(
-        input.bpm,
-        time_signature,
-        division,
-        feel,
-        input.swing_amount,
-        input.bar_count,
-        input.phase_offset_ms.unwrap_or(0.0),
-    );
-
-    // Arrange events with harmonic context
-    let base_arrangement = arranger::arrange_events(
-        &input.events,
-        &template,
-        &grid,
-        &theme,
-        input.b_emphasis,
-        input.fidelity,
-    );
+    if crate::themes::get_theme(&input.theme_name).is_none() {
+        log::warn!(
+            "unknown theme '{}', falling back to {}",
+            input.theme_name,
+            song::FALLBACK_THEME
+        );
+    }
 
     // Expand base pattern into full song (Intro/Build/Drop/Outro)
-    let arrangement = base_arrangement.expand_to_song();
+    Ok(input.arrange().expand_to_song())
+}
 
-    Ok(arrangement)
+/// Export arrangement as MIDI file bytes
+#[tauri::command]
+#[specta::specta]
+pub fn export_midi_command(input: ExportMidiInput) -> CommandResult<Vec<u8>> {
+    input.export().map_err(|e| CommandError {
+        message: format!("Failed to export MIDI: {}", e),
+    })
 }
 
 #[derive(Debug, Deserialize, specta::Type)]
-pub struct ExportMidiInput {
+pub struct RenderWavInput {
     pub arrangement: Arrangement,
-    pub bpm: f64,
-    pub time_signature: String,
-    pub division: String,
-    pub feel: String,
-    pub swing_amount: f32,
-    pub bar_count: u32,
-    pub ppq: Option<u16>,
-    pub include_tempo: Option<bool>,
-    pub include_time_signature: Option<bool>,
-    pub track_names: Option<bool>,
-    /// Grid phase offset (ms) from tempo estimation. Extends grid duration so a
-    /// phase-shifted arrangement's tail isn't truncated on export. Defaults to 0.0.
+    /// Output sample rate in Hz. Defaults to 44100.
     #[serde(default)]
-    pub phase_offset_ms: Option<f64>,
+    pub sample_rate: Option<u32>,
 }
 
-/// Export arrangement as MIDI file bytes
+/// Render an arrangement to 16-bit stereo WAV bytes with the native synth
+/// (no WebAudio), using the theme sound snapshotted on the arrangement.
 #[tauri::command]
 #[specta::specta]
-pub fn export_midi_command(input: ExportMidiInput) -> CommandResult<Vec<u8>> {
-    // Parse time signature
-    let time_signature = match input.time_signature.as_str() {
-        "four_four" => TimeSignature::FourFour,
-        "three_four" => TimeSignature::ThreeFour,
-        _ => TimeSignature::FourFour,
-    };
-
-    // Parse grid division
-    let division = match input.division.as_str() {
-        "quarter" => GridDivision::Quarter,
-        "eighth" => GridDivision::Eighth,
-        "sixteenth" => GridDivision::Sixteenth,
-        "triplet" => GridDivision::Triplet,
-        _ => GridDivision::Sixteenth,
-    };
-
-    // Parse groove feel
-    let feel = match input.feel.as_str() {
-        "straight" => GrooveFeel::Straight,
-        "swing" => GrooveFeel::Swing,
-        "halftime" => GrooveFeel::Halftime,
-        _ => GrooveFeel::Straight,
-    };
-
-    // Create grid, anchored to the estimated beat phase so the exported grid
-    // duration covers the phase-shifted tail.
-    let grid = Grid::with_phase(
-        input.bpm,
-        time_signature,
-        division,
-        feel,
-        input.swing_amount,
-        input.bar_count,
-        input.phase_offset_ms.unwrap_or(0.0),
-    );
-
-    // Create MIDI export options
-    let mut options = MidiExportOptions::default();
-    if let Some(ppq) = input.ppq {
-        options.ppq = ppq;
-    }
-    if let Some(include_tempo) = input.include_tempo {
-        options.include_tempo = include_tempo;
-    }
-    if let Some(include_time_signature) = input.include_time_signature {
-        options.include_time_signature = include_time_signature;
-    }
-    if let Some(track_names) = input.track_names {
-        options.track_names = track_names;
+pub fn render_wav_command(input: RenderWavInput) -> CommandResult<Vec<u8>> {
+    let mut options = RenderOptions::default();
+    if let Some(sample_rate) = input.sample_rate {
+        options.sample_rate = sample_rate;
     }
 
-    // Export to MIDI
-    let midi_bytes = arranger::export_midi(&input.arrangement, &grid, &options)
-        .map_err(|e| CommandError {
-            message: format!("Failed to export MIDI: {}", e),
-        })?;
+    let wav_bytes = render::render_wav(&input.arrangement, &options).map_err(|e| CommandError {
+        message: format!("Failed to render WAV: {}", e),
+    })?;
 
-    Ok(midi_bytes)
+    Ok(wav_bytes)
 }
 
 // ==================== EXPLAINABILITY COMMANDS ====================
@@ -962,19 +871,8 @@ pub fn save_event_decisions(
     let mut decisions = Vec::new();
 
     for event_data in &input.events {
-        // Convert EventData to Event
-        let id = Uuid::parse_str(&event_data.id).unwrap_or_else(|_| Uuid::new_v4());
-        let event = Event {
-            id,
-            timestamp_ms: event_data.timestamp_ms,
-            duration_ms: event_data.duration_ms,
-            class: EventClass::from_string(&event_data.class),
-            confidence: event_data.confidence,
-            features: event_data.features.clone(),
-            all_scores: event_data.all_scores.clone(),
-        };
-
-        let quantized = quantized_lookup.get(&id).copied();
+        let event = event_data.to_event();
+        let quantized = quantized_lookup.get(&event.id).copied();
         let arrangement = input.arrangement.as_ref();
 
         let decision = EventDecision::from_pipeline_data(&event, quantized, arrangement);
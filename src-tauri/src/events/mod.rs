@@ -1,23 +1,28 @@
 // Event detection module
 // Onset detection, feature extraction, and event classification.
 //
-// The event types, heuristic classifier, and calibration (kNN) now live in the
-// shared `beatrice-dsp` crate. This module re-exports them — including the
-// submodules `types`, `heuristic`, and `calibration`, so paths like
-// `crate::events::heuristic::ClassificationResult` keep resolving — and keeps
-// the Tauri-adjacent `backend`/`explainability` pieces here.
+// The event types, the classifiers (and their by-name registry), and
+// calibration (kNN) now live in the shared `beatrice-dsp` crate. This module
+// re-exports them — including the submodules `types`, `heuristic`, and
+// `calibration`, so paths like `crate::events::heuristic::ClassificationResult`
+// keep resolving — and keeps the Tauri-adjacent `explainability` piece here.
 
 // Re-export the DSP event submodules so existing `crate::events::{types,
 // heuristic, calibration}::…` paths still resolve.
-pub use beatrice_dsp::events::{calibration, gaussian, heuristic, hybrid, types};
+pub use beatrice_dsp::events::{
+    calibration, classifier, gaussian, heuristic, hybrid, multilabel, types,
+};
 
-pub mod backend;
 pub mod explainability;
 
-pub use backend::{Classifier, ClassifierBackend, ClassifierError};
-pub use calibration::{CalibrationProfile, CalibrationSample, KnnClassifier};
+pub use calibration::{CalibrationProfile, CalibrationReport, CalibrationSample, KnnClassifier};
+pub use classifier::{
+    build_classifier, classifier_entry, ClassifierInput, EventClassifier, CLASSIFIERS,
+    DEFAULT_CLASSIFIER,
+};
 pub use gaussian::{gaussian_features, GaussianModel, DEFAULT_MAP_TAU, GAUSSIAN_DIMS};
 pub use heuristic::{ClassificationResult, ClassifierConfig, HeuristicClassifier};
 pub use hybrid::{HybridClassifier, HUM_GATE_MAX_CREST, HUM_GATE_MAX_ZCR};
-pub use types::{ClassScore, Event, EventClass, EventFeatures};
+pub use multilabel::MultiLabelConfig;
+pub use types::{ClassScore, Event, EventClass, EventData, EventFeatures, PitchPoint};
 pub use explainability::{EventDecision, AssignedNote};
@@ -3,14 +3,18 @@
 
 use tauri::Manager;
 
-pub mod arranger;
 pub mod audio;
 mod commands;
 pub mod events;
-pub mod groove;
 mod pipeline;
+pub mod render;
 mod state;
-pub mod themes;
+
+// Groove, arrangement, themes and the frontend's quantize/arrange/export
+// inputs (`song`) live in the shared `beatrice-dsp` crate, so the browser
+// build arranges and exports MIDI too; re-exported so every `crate::groove::…`
+// / `beatrice_lib::arranger::…` path still resolves.
+pub use beatrice_dsp::{arranger, groove, song, themes};
 
 /// tauri-specta builder holding the full specta-annotated command set.
 ///
@@ -35,6 +39,11 @@ fn specta_builder() -> tauri_specta::Builder<tauri::Wry> {
         commands::get_calibration_profile,
         commands::update_calibration_profile,
         commands::delete_calibration_profile,
+        commands::get_calibration_report,
+        commands::list_noise_profiles,
+        commands::create_noise_profile,
+        commands::get_noise_profile,
+        commands::delete_noise_profile,
         commands::detect_onsets,
         commands::detect_events,
         commands::extract_features,
@@ -42,6 +51,7 @@ fn specta_builder() -> tauri_specta::Builder<tauri::Wry> {
         commands::quantize_events_command,
         commands::arrange_events_command,
         commands::export_midi_command,
+        commands::render_wav_command,
         commands::list_themes,
         commands::get_theme,
         commands::list_theme_names,
@@ -0,0 +1,264 @@
+// Render DSP - WebAudio-equivalent building blocks for the native renderer
+// AudioParam-style automation, band-limited oscillators, and the biquad filter
+// family the TS synth uses, so each voice can be ported node for node.
+
+use std::f64::consts::PI;
+
+/// MIDI velocity (0-127) → gain with the same curve as the TS `velocityToGain`.
+pub fn velocity_to_gain(velocity: u8) -> f32 {
+    let linear = (velocity as f32 / 127.0).clamp(0.0, 1.0);
+    linear * linear * 0.8 + linear * 0.2
+}
+
+pub fn midi_to_freq(note: u8) -> f32 {
+    440.0 * 2f32.powf((note as f32 - 69.0) / 12.0)
+}
+
+/// Master soft-clip: identity for |x| <= 0.7, then a tanh knee that tops out at
+/// 0.97, so a dense mix of voices + FX returns can never reach full scale.
+pub fn soft_clip(x: f32) -> f32 {
+    const L: f32 = 0.7;
+    const RANGE: f32 = 0.97 - L;
+    let a = x.abs().min(1.0);
+    let y = if a <= L { a } else { L + RANGE * ((a - L) / RANGE).tanh() };
+    y.copysign(x)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Ramp {
+    Set,
+    Linear,
+    Exponential,
+}
+
+/// A scheduled parameter curve with `AudioParam` semantics: `set` jumps at its
+/// time, `linear`/`exponential` ramp FROM the previous event TO their own time,
+/// and the last value holds afterwards. Times are seconds from note start.
+#[derive(Debug, Clone)]
+pub struct Param {
+    initial: f32,
+    events: Vec<(f32, f32, Ramp)>,
+}
+
+impl Param {
+    pub fn new(initial: f32) -> Self {
+        Param { initial, events: Vec::new() }
+    }
+
+    fn push(mut self, time: f32, value: f32, ramp: Ramp) -> Self {
+        // Stable insert by time, like the AudioParam event timeline.
+        let at = self.events.partition_point(|e| e.0 <= time);
+        self.events.insert(at, (time, value, ramp));
+        self
+    }
+
+    pub fn set(self, time: f32, value: f32) -> Self {
+        self.push(time, value, Ramp::Set)
+    }
+
+    pub fn linear(self, time: f32, value: f32) -> Self {
+        self.push(time, value, Ramp::Linear)
+    }
+
+    pub fn exponential(self, time: f32, value: f32) -> Self {
+        self.push(time, value, Ramp::Exponential)
+    }
+
+    pub fn at(&self, t: f32) -> f32 {
+        let (mut t0, mut v0) = (0.0f32, self.initial);
+        for &(t1, v1, ramp) in &self.events {
+            if t < t1 {
+                let frac = if t1 > t0 { (t - t0) / (t1 - t0) } else { 1.0 };
+                return match ramp {
+                    Ramp::Set => v0,
+                    Ramp::Linear => v0 + (v1 - v0) * frac,
+                    // Same sign + non-zero required, as in WebAudio; else hold.
+                    Ramp::Exponential if v0 * v1 > 0.0 => v0 * (v1 / v0).powf(frac),
+                    Ramp::Exponential => v0,
+                };
+            }
+            t0 = t1;
+            v0 = v1;
+        }
+        v0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Wave {
+    Sine,
+    Triangle,
+    Square,
+    Sawtooth,
+}
+
+/// Phase-accumulating oscillator. Square and sawtooth are PolyBLEP-corrected so
+/// the high hat partials don't alias into the audible band at 44.1 kHz.
+#[derive(Debug, Clone)]
+pub struct Osc {
+    wave: Wave,
+    phase: f64,
+}
+
+fn poly_blep(t: f64, dt: f64) -> f64 {
+    if t < dt {
+        let x = t / dt;
+        x + x - x * x - 1.0
+    } else if t > 1.0 - dt {
+        let x = (t - 1.0) / dt;
+        x * x + x + x + 1.0
+    } else {
+        0.0
+    }
+}
+
+impl Osc {
+    pub fn new(wave: Wave) -> Self {
+        Osc { wave, phase: 0.0 }
+    }
+
+    pub fn next(&mut self, freq: f32, sample_rate: f32) -> f32 {
+        let dt = (freq as f64 / sample_rate as f64).clamp(0.0, 0.5);
+        let p = self.phase;
+        let y = match self.wave {
+            Wave::Sine => (2.0 * PI * p).sin(),
+            Wave::Triangle => {
+                if p < 0.25 {
+                    4.0 * p
+                } else if p < 0.75 {
+                    2.0 - 4.0 * p
+                } else {
+                    4.0 * p - 4.0
+                }
+            }
+            Wave::Square => {
+                let naive = if p < 0.5 { 1.0 } else { -1.0 };
+                naive + poly_blep(p, dt) - poly_blep((p + 0.5) % 1.0, dt)
+            }
+            Wave::Sawtooth => {
+                // Starts at 0 rising, like the WebAudio sawtooth.
+                let q = (p + 0.5) % 1.0;
+                2.0 * q - 1.0 - poly_blep(q, dt)
+            }
+        };
+        self.phase = (p + dt) % 1.0;
+        y as f32
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterKind {
+    Lowpass,
+    Highpass,
+    Bandpass,
+}
+
+/// WebAudio `BiquadFilterNode` (Audio EQ Cookbook). As in the spec, lowpass and
+/// highpass read Q in dB while bandpass reads it linearly; the default Q is 1.
+/// Coefficients are refreshed once per 128-sample render quantum.
+#[derive(Debug, Clone)]
+pub struct Biquad {
+    kind: FilterKind,
+    q: f32,
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+    countdown: usize,
+}
+
+const RENDER_QUANTUM: usize = 128;
+
+impl Biquad {
+    pub fn new(kind: FilterKind, q: f32) -> Self {
+        Biquad {
+            kind,
+            q,
+            b: [1.0, 0.0, 0.0],
+            a: [0.0, 0.0],
+            x: [0.0; 2],
+            y: [0.0; 2],
+            countdown: 0,
+        }
+    }
+
+    fn update(&mut self, freq: f32, sample_rate: f32) {
+        let nyquist = sample_rate as f64 / 2.0;
+        let f = (freq as f64).clamp(10.0, nyquist * 0.99);
+        let w0 = 2.0 * PI * f / sample_rate as f64;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = match self.kind {
+            FilterKind::Lowpass | FilterKind::Highpass => {
+                sin / (2.0 * 10f64.powf(self.q as f64 / 20.0))
+            }
+            FilterKind::Bandpass => sin / (2.0 * (self.q as f64).max(1e-4)),
+        };
+        let (b0, b1, b2) = match self.kind {
+            FilterKind::Lowpass => ((1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0),
+            FilterKind::Highpass => ((1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0),
+            FilterKind::Bandpass => (alpha, 0.0, -alpha),
+        };
+        let a0 = 1.0 + alpha;
+        self.b = [b0 / a0, b1 / a0, b2 / a0];
+        self.a = [-2.0 * cos / a0, (1.0 - alpha) / a0];
+    }
+
+    pub fn process(&mut self, input: f32, freq: f32, sample_rate: f32) -> f32 {
+        if self.countdown == 0 {
+            self.update(freq, sample_rate);
+            self.countdown = RENDER_QUANTUM;
+        }
+        self.countdown -= 1;
+        let x0 = input as f64;
+        let y0 = self.b[0] * x0 + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [x0, self.x[0]];
+        self.y = [y0, self.y[0]];
+        y0 as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn param_follows_audio_param_timeline() {
+        let p = Param::new(0.0).set(0.0, 1.0).exponential(1.0, 0.01);
+        assert!((p.at(0.0) - 1.0).abs() < 1e-6);
+        assert!((p.at(0.5) - 0.1).abs() < 1e-4);
+        assert!((p.at(2.0) - 0.01).abs() < 1e-6, "last value holds");
+
+        let l = Param::new(0.001).linear(0.01, 0.5).set(0.7, 0.5);
+        assert!((l.at(0.005) - 0.2505).abs() < 1e-4);
+        assert!((l.at(0.3) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn soft_clip_is_identity_below_knee_and_capped() {
+        assert_eq!(soft_clip(0.5), 0.5);
+        assert_eq!(soft_clip(-0.7), -0.7);
+        assert!(soft_clip(5.0) <= 0.97);
+        assert!(soft_clip(-5.0) >= -0.97);
+    }
+
+    #[test]
+    fn lowpass_attenuates_above_cutoff() {
+        let sr = 44100.0;
+        let rms = |freq: f32| {
+            let mut osc = Osc::new(Wave::Sine);
+            let mut lp = Biquad::new(FilterKind::Lowpass, 1.0);
+            let mut acc = 0.0f32;
+            for i in 0..8820 {
+                let y = lp.process(osc.next(freq, sr), 500.0, sr);
+                if i >= 4410 {
+                    acc += y * y;
+                }
+            }
+            (acc / 4410.0).sqrt()
+        };
+        assert!(rms(100.0) > 0.6);
+        assert!(rms(5000.0) < 0.02);
+    }
+}
@@ -0,0 +1,251 @@
+// Render FX - The themed master bus: sidechain duck, sends, returns, soft clip
+// Port of `createFxBus` in src/audio/scheduleArrangement.ts. Each instrument
+// renders into its own mono input; this module fans those out to the dry groups
+// and the delay / reverb / chorus sends and sums the stereo master.
+
+use realfft::num_complex::Complex;
+use realfft::RealFftPlanner;
+
+use super::dsp::{soft_clip, Biquad, FilterKind};
+use super::timbre::{seed_from, ChorusParams, FxParams, Instrument, Mulberry32, SeedPart};
+
+/// Group gain applied to both the drum and the music dry sums (headroom before
+/// the master level).
+const GROUP_GAIN: f32 = 0.85;
+
+/// Sidechain duck of the bass/pad/arp inputs on every kick: a fast dip toward
+/// 0.3 (τ = 5 ms), then recovery to unity 80 ms later (τ = 50 ms) — the two
+/// `setTargetAtTime` calls of the TS `duck()`.
+fn duck_curve(len: usize, sr: f32, kick_times: &[f32]) -> Vec<f32> {
+    let mut targets: Vec<(usize, f32, f32)> = Vec::with_capacity(kick_times.len() * 2);
+    for &t in kick_times {
+        targets.push(((t * sr).round() as usize, 0.3, 0.005));
+        targets.push((((t + 0.08) * sr).round() as usize, 1.0, 0.05));
+    }
+    targets.sort_by_key(|&(at, _, _)| at);
+
+    let mut curve = Vec::with_capacity(len);
+    let (mut g, mut target, mut coeff) = (1.0f32, 1.0f32, 0.0f32);
+    let mut next = 0;
+    for i in 0..len {
+        while next < targets.len() && targets[next].0 <= i {
+            let (_, v, tau) = targets[next];
+            target = v;
+            coeff = 1.0 - (-1.0 / (tau * sr)).exp();
+            next += 1;
+        }
+        g += (target - g) * coeff;
+        curve.push(g);
+    }
+    curve
+}
+
+fn send_bus(inputs: &[Vec<f32>], len: usize, level: impl Fn(Instrument) -> f32) -> Vec<f32> {
+    let mut bus = vec![0.0f32; len];
+    for inst in Instrument::ALL {
+        let gain = level(inst);
+        if gain > 0.0 {
+            for (acc, x) in bus.iter_mut().zip(&inputs[inst.index()]) {
+                *acc += x * gain;
+            }
+        }
+    }
+    bus
+}
+
+/// Feedback delay; with `filter_hz` the lowpass sits INSIDE the loop so each
+/// repeat gets darker (DarkDelay).
+fn feedback_delay(input: &[f32], sr: f32, fx: &FxParams) -> Vec<f32> {
+    let d = ((fx.delay_time_sec * sr).round() as usize).max(1);
+    let mut line = vec![0.0f32; input.len()];
+    let mut out = vec![0.0f32; input.len()];
+    let mut lp = fx.delay_filter_hz.map(|_| Biquad::new(FilterKind::Lowpass, 1.0));
+    for i in 0..input.len() {
+        let delayed = if i >= d { line[i - d] } else { 0.0 };
+        out[i] = delayed;
+        let fb = match (&mut lp, fx.delay_filter_hz) {
+            (Some(f), Some(hz)) => f.process(delayed, hz, sr),
+            _ => delayed,
+        };
+        line[i] = input[i] + fb * fx.delay_feedback;
+    }
+    out
+}
+
+/// Seeded stereo impulse response (`createImpulseResponse`): decaying noise,
+/// optionally gated with a 15 ms linear fade that ends exactly at the gate.
+fn impulse_response(sr: f32, fx: &FxParams) -> [Vec<f32>; 2] {
+    let duration = fx.reverb_duration_sec;
+    let len = ((sr * duration).floor() as usize).max(1);
+    let base = seed_from(&[SeedPart::Str("ir"), SeedPart::Str(&format!("{:?}", fx.profile))]);
+    let fade = 0.015;
+    let channel = |ch: u64| {
+        let mut rand = Mulberry32::new(seed_from(&[
+            SeedPart::Str("ir-ch"),
+            SeedPart::Num(base as u64),
+            SeedPart::Num(ch),
+        ]));
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / sr;
+                let mut s = (rand.next_f32() * 2.0 - 1.0) * (1.0 - t / duration).powf(fx.reverb_decay);
+                if let Some(gate) = fx.reverb_gate_sec {
+                    if t >= gate {
+                        s = 0.0;
+                    } else if t >= gate - fade {
+                        s *= (gate - t) / fade;
+                    }
+                }
+                s
+            })
+            .collect::<Vec<f32>>()
+    };
+    [channel(0), channel(1)]
+}
+
+/// ConvolverNode's default `normalize = true` scale, so the wet level matches
+/// the browser render regardless of IR length or sample rate.
+fn ir_normalization(ir: &[Vec<f32>; 2], sr: f32) -> f32 {
+    const GAIN_CALIBRATION_DB: f32 = -58.0;
+    const CALIBRATION_SAMPLE_RATE: f32 = 44100.0;
+    const MIN_POWER: f32 = 0.000125;
+    let n = (ir[0].len() + ir[1].len()) as f32;
+    let energy: f32 = ir.iter().flat_map(|c| c.iter()).map(|s| s * s).sum();
+    let mut power = (energy / n).sqrt();
+    if !power.is_finite() || power < MIN_POWER {
+        power = MIN_POWER;
+    }
+    (1.0 / power) * 10f32.powf(GAIN_CALIBRATION_DB / 20.0) * (CALIBRATION_SAMPLE_RATE / sr)
+}
+
+/// Linear convolution via one zero-padded real FFT, truncated to `signal.len()`.
+fn convolve(signal: &[f32], ir: &[f32]) -> Vec<f32> {
+    let n = (signal.len() + ir.len()).next_power_of_two();
+    let mut planner = RealFftPlanner::<f64>::new();
+    let fwd = planner.plan_fft_forward(n);
+    let inv = planner.plan_fft_inverse(n);
+
+    let spectrum = |x: &[f32]| {
+        let mut buf = fwd.make_input_vec();
+        for (b, &s) in buf.iter_mut().zip(x) {
+            *b = s as f64;
+        }
+        let mut out = fwd.make_output_vec();
+        fwd.process(&mut buf, &mut out).expect("fft length matches plan");
+        out
+    };
+    let a = spectrum(signal);
+    let b = spectrum(ir);
+    let mut prod: Vec<Complex<f64>> = a.iter().zip(&b).map(|(x, y)| x * y).collect();
+    let mut time = inv.make_output_vec();
+    inv.process(&mut prod, &mut time).expect("ifft length matches plan");
+    let scale = 1.0 / n as f64;
+    time.iter().take(signal.len()).map(|&v| (v * scale) as f32).collect()
+}
+
+/// Two modulated short delays panned ±0.7 (equal-power), summed to stereo.
+fn chorus(input: &[f32], sr: f32, c: &ChorusParams) -> [Vec<f32>; 2] {
+    let mut out = [vec![0.0f32; input.len()], vec![0.0f32; input.len()]];
+    let [out_l, out_r] = &mut out;
+    for (delay_ms, rate_hz, pan) in [(c.delay_ms_l, c.rate_hz_l, -0.7f32), (c.delay_ms_r, c.rate_hz_r, 0.7)] {
+        let x = (pan + 1.0) / 2.0;
+        let (gl, gr) = ((x * std::f32::consts::FRAC_PI_2).cos(), (x * std::f32::consts::FRAC_PI_2).sin());
+        for (i, (l, r)) in out_l.iter_mut().zip(out_r.iter_mut()).enumerate() {
+            let t = i as f32 / sr;
+            let lfo = (2.0 * std::f32::consts::PI * rate_hz * t).sin() * c.depth_ms / 1000.0;
+            let delay = ((delay_ms / 1000.0).min(0.05) + lfo).clamp(0.0, 0.05) * sr;
+            let pos = i as f32 - delay;
+            if pos < 0.0 {
+                continue;
+            }
+            let j = pos.floor() as usize;
+            let frac = pos - j as f32;
+            let next = input.get(j + 1).copied().unwrap_or(0.0);
+            let s = input[j] * (1.0 - frac) + next * frac;
+            *l += s * gl;
+            *r += s * gr;
+        }
+    }
+    out
+}
+
+/// Sum the instrument inputs through the themed bus into a clipped stereo
+/// master. `inputs` are indexed by [`Instrument`]; `kick_times` drive the duck.
+pub fn mix(mut inputs: Vec<Vec<f32>>, kick_times: &[f32], sr: f32, fx: &FxParams) -> [Vec<f32>; 2] {
+    let len = inputs[0].len();
+
+    let duck = duck_curve(len, sr, kick_times);
+    for inst in [Instrument::Bass, Instrument::Pad, Instrument::Arp] {
+        for (x, g) in inputs[inst.index()].iter_mut().zip(&duck) {
+            *x *= g;
+        }
+    }
+
+    // Dry path: every profile keeps a dry send into the drum or music group.
+    let dry = send_bus(&inputs, len, |i| fx.sends[i.index()].dry * GROUP_GAIN);
+    let mut left = dry.clone();
+    let mut right = dry;
+
+    if fx.delay_time_sec > 0.0 {
+        let send = send_bus(&inputs, len, |i| fx.sends[i.index()].delay);
+        let wet = feedback_delay(&send, sr, fx);
+        for i in 0..len {
+            left[i] += wet[i] * fx.delay_return;
+            right[i] += wet[i] * fx.delay_return;
+        }
+    }
+
+    if fx.reverb_duration_sec > 0.0 {
+        let send = send_bus(&inputs, len, |i| fx.sends[i.index()].reverb);
+        let ir = impulse_response(sr, fx);
+        let scale = ir_normalization(&ir, sr) * fx.reverb_return;
+        let wet_l = convolve(&send, &ir[0]);
+        let wet_r = convolve(&send, &ir[1]);
+        for i in 0..len {
+            left[i] += wet_l[i] * scale;
+            right[i] += wet_r[i] * scale;
+        }
+    }
+
+    if let Some(c) = &fx.chorus {
+        let send = send_bus(&inputs, len, |i| fx.sends[i.index()].chorus);
+        let [wet_l, wet_r] = chorus(&send, sr, c);
+        for i in 0..len {
+            left[i] += wet_l[i] * fx.chorus_return;
+            right[i] += wet_r[i] * fx.chorus_return;
+        }
+    }
+
+    for s in left.iter_mut().chain(right.iter_mut()) {
+        *s = soft_clip(*s * fx.master_level);
+    }
+    [left, right]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duck_dips_then_recovers() {
+        let sr = 1000.0;
+        let curve = duck_curve(1000, sr, &[0.1]);
+        assert!((curve[50] - 1.0).abs() < 1e-6);
+        assert!(curve[170] < 0.35, "ducked to ~0.3, got {}", curve[170]);
+        assert!(curve[999] > 0.99, "recovered to unity, got {}", curve[999]);
+    }
+
+    #[test]
+    fn fft_convolution_matches_direct() {
+        let signal = [1.0, 0.5, -0.25, 0.0, 0.75];
+        let ir = [0.5, -1.0, 0.25];
+        let fast = convolve(&signal, &ir);
+        for n in 0..signal.len() {
+            let direct: f32 = (0..=n)
+                .filter(|&k| n - k < ir.len())
+                .map(|k| signal[k] * ir[n - k])
+                .sum();
+            assert!((fast[n] - direct).abs() < 1e-5, "n={n}: {} vs {direct}", fast[n]);
+        }
+    }
+}
@@ -0,0 +1,249 @@
+// Render - Native offline synthesis of an Arrangement to PCM / WAV
+// A WebAudio-free port of src/audio/{timbre,scheduleArrangement,renderWav}.ts so
+// the CLI and backend can bounce a song without a browser. Output is fully
+// deterministic: every noise burst and reverb IR is seeded exactly as in TS.
+
+pub mod dsp;
+pub mod fx;
+pub mod timbre;
+pub mod voices;
+
+use std::io::Cursor;
+
+use thiserror::Error;
+
+use crate::arranger::Arrangement;
+use timbre::{derive_timbre, seed_from, Instrument, SeedPart};
+
+pub use timbre::TimbreParams;
+
+/// Longest song we agree to render (10 minutes plus FX tail).
+pub const MAX_RENDER_SECS: f64 = 600.0;
+
+#[derive(Error, Debug)]
+pub enum RenderError {
+    #[error("Unsupported sample rate: {0}Hz (expected 8000-192000)")]
+    InvalidSampleRate(u32),
+    #[error("Arrangement too long to render: {0:.1}s")]
+    TooLong(f64),
+    #[error("Failed to write WAV: {0}")]
+    WavWriteError(#[from] hound::Error),
+}
+
+/// Offline render settings
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Output sample rate in Hz
+    pub sample_rate: u32,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions { sample_rate: 44100 }
+    }
+}
+
+/// Rendered stereo master, post soft-clip, in [-0.97, 0.97].
+#[derive(Debug, Clone)]
+pub struct RenderedAudio {
+    pub sample_rate: u32,
+    pub left: Vec<f32>,
+    pub right: Vec<f32>,
+}
+
+impl RenderedAudio {
+    pub fn frame_count(&self) -> usize {
+        self.left.len()
+    }
+
+    pub fn duration_secs(&self) -> f64 {
+        self.left.len() as f64 / self.sample_rate as f64
+    }
+}
+
+/// Synthesize every lane of `arrangement` with the theme sound snapshotted on it.
+///
+/// The render length is `total_duration_ms` plus the timbre's tail (longest dry
+/// voice + FX ring-out), so reverb and delay ring out instead of being cut.
+pub fn render_arrangement(
+    arrangement: &Arrangement,
+    options: &RenderOptions,
+) -> Result<RenderedAudio, RenderError> {
+    if !(8000..=192_000).contains(&options.sample_rate) {
+        return Err(RenderError::InvalidSampleRate(options.sample_rate));
+    }
+    let sr = options.sample_rate as f32;
+    let timbre = derive_timbre(&arrangement.sound, arrangement.bpm);
+
+    let duration_secs = arrangement.total_duration_ms.max(0.0) / 1000.0 + timbre.fx.render_tail_sec as f64;
+    if duration_secs > MAX_RENDER_SECS {
+        return Err(RenderError::TooLong(duration_secs));
+    }
+    let frames = ((duration_secs * options.sample_rate as f64).ceil() as usize).max(1);
+
+    let mut inputs = vec![vec![0.0f32; frames]; Instrument::COUNT];
+    let mut kick_times = Vec::new();
+
+    for lane in arrangement.all_lanes() {
+        let instrument = Instrument::for_lane(&lane.name);
+        let out = &mut inputs[instrument.index()];
+        for (index, note) in lane.events.iter().enumerate() {
+            let t = (note.timestamp_ms.max(0.0) / 1000.0) as f32;
+            let start = (t * sr).round() as usize;
+            let midi_note = note.midi_note.unwrap_or(lane.midi_note);
+            // Per-note noise seed, stable across renders (same key as the TS synth).
+            let seed = seed_from(&[
+                SeedPart::Str(&lane.name),
+                SeedPart::Num(index as u64),
+                SeedPart::Str("noise"),
+            ]);
+            match instrument {
+                Instrument::Kick => {
+                    voices::kick(out, start, sr, note.velocity, &timbre.kick);
+                    // Only the kick triggers the sidechain duck.
+                    kick_times.push(t);
+                }
+                Instrument::Snare => voices::snare(out, start, sr, note.velocity, &timbre.snare, seed),
+                Instrument::Hihat => voices::hihat(out, start, sr, note.velocity, &timbre.hihat, seed),
+                Instrument::OpenHihat => voices::hihat(out, start, sr, note.velocity, &timbre.open_hihat, seed),
+                Instrument::Crash => voices::hihat(out, start, sr, note.velocity, &timbre.crash, seed),
+                Instrument::Bass => voices::bass(out, start, sr, note.velocity, midi_note, note.duration_ms),
+                Instrument::Pad => {
+                    voices::pad(out, start, sr, note.velocity, midi_note, note.duration_ms, &timbre.pad)
+                }
+                Instrument::Arp => {
+                    voices::arp(out, start, sr, note.velocity, midi_note, note.duration_ms, &timbre.arp)
+                }
+            }
+        }
+    }
+
+    let [left, right] = fx::mix(inputs, &kick_times, sr, &timbre.fx);
+    Ok(RenderedAudio { sample_rate: options.sample_rate, left, right })
+}
+
+/// Encode a render as 16-bit stereo PCM WAV bytes (same format as the TS export).
+pub fn encode_wav16(audio: &RenderedAudio) -> Result<Vec<u8>, RenderError> {
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate: audio.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut cursor = Cursor::new(Vec::with_capacity(44 + audio.frame_count() * 4));
+    {
+        let mut writer = hound::WavWriter::new(&mut cursor, spec)?;
+        for (&l, &r) in audio.left.iter().zip(&audio.right) {
+            writer.write_sample((l.clamp(-1.0, 1.0) * 32767.0) as i16)?;
+            writer.write_sample((r.clamp(-1.0, 1.0) * 32767.0) as i16)?;
+        }
+        writer.finalize()?;
+    }
+    Ok(cursor.into_inner())
+}
+
+/// Render an arrangement straight to WAV file bytes.
+pub fn render_wav(arrangement: &Arrangement, options: &RenderOptions) -> Result<Vec<u8>, RenderError> {
+    encode_wav16(&render_arrangement(arrangement, options)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arranger::{ArrangedNote, ArrangementTemplate, DrumLane};
+    use crate::themes::{DrumPalette, FxProfile, ThemeSound};
+
+    fn note(t: f64, dur: f64) -> ArrangedNote {
+        ArrangedNote::new(t, dur, 100, None, None)
+    }
+
+    fn one_bar(fx_profile: FxProfile) -> Arrangement {
+        let sound = ThemeSound { drum_palette: DrumPalette::SynthwaveDrums, fx_profile, pad_sustain: true };
+        let mut arr = Arrangement::new(
+            ArrangementTemplate::SynthwaveStraight,
+            2000.0,
+            1,
+            "BLADE RUNNER".to_string(),
+            120.0,
+            sound,
+        );
+        let mut kick = DrumLane::new("DRUMS_KICK", 36);
+        kick.events = vec![note(0.0, 100.0), note(1000.0, 100.0)];
+        let mut pad = DrumLane::new("PADS", 48);
+        pad.events = vec![note(0.0, 2000.0)];
+        arr.drum_lanes = vec![kick];
+        arr.pad_lane = Some(pad);
+        arr
+    }
+
+    #[test]
+    fn render_is_deterministic_and_bounded() {
+        let arr = one_bar(FxProfile::GatedReverb);
+        let opts = RenderOptions { sample_rate: 22050 };
+        let a = render_arrangement(&arr, &opts).unwrap();
+        let b = render_arrangement(&arr, &opts).unwrap();
+        assert_eq!(a.left, b.left);
+        assert_eq!(a.right, b.right);
+        let peak = a.left.iter().chain(&a.right).fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(peak > 0.1, "render should not be silent (peak {peak})");
+        assert!(peak <= 0.97, "soft clip caps the master (peak {peak})");
+    }
+
+    #[test]
+    fn render_length_includes_fx_tail() {
+        let arr = one_bar(FxProfile::Dry);
+        let audio = render_arrangement(&arr, &RenderOptions { sample_rate: 8000 }).unwrap();
+        // 2.0s + Synthwave dry overhang (crash 1.2) + 0.1 margin.
+        assert!((audio.duration_secs() - 3.3).abs() < 1e-3, "{}", audio.duration_secs());
+    }
+
+    #[test]
+    fn crash_lane_rings_on_its_own_voice() {
+        // One hit at t=0 on each lane, Dry so nothing but the voice rings.
+        let render = |lane: &str| {
+            let mut arr = one_bar(FxProfile::Dry);
+            let mut hit = DrumLane::new(lane, 49);
+            hit.events = vec![note(0.0, 100.0)];
+            arr.drum_lanes = vec![hit];
+            arr.pad_lane = None;
+            render_arrangement(&arr, &RenderOptions { sample_rate: 22050 }).unwrap().left
+        };
+        let rms = |x: &[f32]| (x.iter().map(|s| s * s).sum::<f32>() / x.len() as f32).sqrt();
+        let (crash, snare) = (render("DRUMS_CRASH"), render("DRUMS_SNARE"));
+        // 400-600ms in: the snare (longest layer 0.18s) is gone, the crash is not.
+        let tail = 8820..13230;
+        assert!(rms(&snare[tail.clone()]) < 1e-4);
+        assert!(rms(&crash[tail.clone()]) > 1e-3, "crash rms {}", rms(&crash[tail]));
+    }
+
+    #[test]
+    fn dry_profile_is_mono_and_wet_profile_is_not() {
+        let dry = render_arrangement(&one_bar(FxProfile::Dry), &RenderOptions { sample_rate: 8000 }).unwrap();
+        assert_eq!(dry.left, dry.right);
+        let wet = render_arrangement(&one_bar(FxProfile::WideChorus), &RenderOptions { sample_rate: 8000 }).unwrap();
+        assert_ne!(wet.left, wet.right);
+    }
+
+    #[test]
+    fn wav_header_matches_render() {
+        let arr = one_bar(FxProfile::Dry);
+        let opts = RenderOptions { sample_rate: 8000 };
+        let frames = render_arrangement(&arr, &opts).unwrap().frame_count();
+        let bytes = render_wav(&arr, &opts).unwrap();
+        let reader = hound::WavReader::new(Cursor::new(&bytes)).unwrap();
+        let spec = reader.spec();
+        assert_eq!(spec.channels, 2);
+        assert_eq!(spec.sample_rate, 8000);
+        assert_eq!(spec.bits_per_sample, 16);
+        assert_eq!(reader.duration() as usize, frames);
+    }
+
+    #[test]
+    fn rejects_bad_sample_rate() {
+        let arr = one_bar(FxProfile::Dry);
+        assert!(matches!(
+            render_arrangement(&arr, &RenderOptions { sample_rate: 1000 }),
+            Err(RenderError::InvalidSampleRate(1000))
+        ));
+    }
+}
@@ -0,0 +1,617 @@
+// Render Timbre - ThemeSound → concrete DSP parameters for the native renderer
+// A verbatim port of src/audio/timbre.ts so the Rust WAV matches what the user
+// hears in the WebAudio preview. Keep the two tables in lockstep.
+
+use crate::themes::{DrumPalette, FxProfile, ThemeSound};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SendLevels {
+    pub dry: f32,
+    pub delay: f32,
+    pub reverb: f32,
+    pub chorus: f32,
+}
+
+/// Dry send for a single voice — no wet routing.
+const DRY_SEND: SendLevels = SendLevels { dry: 1.0, delay: 0.0, reverb: 0.0, chorus: 0.0 };
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KickParams {
+    pub sub_freq_start_hz: f32,
+    pub sub_freq_end_hz: f32,
+    pub sweep_sec: f32,
+    pub sub_level: f32,
+    pub sub_decay_sec: f32,
+    pub click_level: f32,
+    pub click_decay_sec: f32,
+    pub harm_level: f32,
+    pub harm_decay_sec: f32, // 0 level = layer skipped
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnareParams {
+    pub noise_bandpass_start_hz: f32,
+    pub noise_bandpass_end_hz: f32,
+    pub noise_hipass_hz: f32,
+    pub noise_level: f32,
+    pub noise_decay_sec: f32,
+    pub body_freq_start_hz: f32,
+    pub body_freq_end_hz: f32,
+    pub body_level: f32,
+    pub body_decay_sec: f32,
+    pub ring_freq_hz: f32,
+    pub ring_level: f32,
+    pub ring_decay_sec: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HihatParams {
+    pub metallic_freqs_hz: &'static [f32],
+    pub metallic_level: f32, // TOTAL layer gain — split evenly across the oscillators
+    pub metallic_decay_sec: f32,
+    pub hipass_hz: f32,
+    pub noise_hipass_hz: f32,
+    pub noise_level: f32,
+    pub noise_decay_sec: f32,
+}
+
+/// Pad articulation. `pad_sustain` changes these envelope times only — the
+/// oscillators and filter are identical for both.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PadParams {
+    pub sustain: bool,
+    pub attack_sec: f32,
+    pub decay_to_level: f32,
+    pub decay_by_portion: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArpParams {
+    pub level: f32,
+    pub attack_sec: f32,
+    pub max_decay_sec: f32,
+    pub filter_start_mul: f32,
+    pub filter_end_mul: f32,
+    pub filter_sweep_sec: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChorusParams {
+    pub delay_ms_l: f32,
+    pub delay_ms_r: f32,
+    pub depth_ms: f32,
+    pub rate_hz_l: f32,
+    pub rate_hz_r: f32,
+}
+
+/// Per-instrument send levels, indexed by [`Instrument`].
+pub type Sends = [SendLevels; Instrument::COUNT];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FxParams {
+    pub profile: FxProfile,
+    pub delay_time_sec: f32, // 0 = no delay graph
+    pub delay_feedback: f32,
+    pub delay_filter_hz: Option<f32>, // lowpass INSIDE the feedback loop
+    pub reverb_duration_sec: f32,     // 0 = no reverb graph
+    pub reverb_decay: f32,
+    pub reverb_gate_sec: Option<f32>, // IR truncated here, 15ms fade ending at the gate
+    pub chorus: Option<ChorusParams>,
+    pub sends: Sends,
+    pub delay_return: f32,
+    pub reverb_return: f32,
+    pub chorus_return: f32,
+    pub master_level: f32,
+    pub render_tail_sec: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimbreParams {
+    pub kick: KickParams,
+    pub snare: SnareParams,
+    pub hihat: HihatParams,
+    /// Open hi-hat and crash: the hi-hat's metallic + noise layers, left to ring.
+    pub open_hihat: HihatParams,
+    pub crash: HihatParams,
+    pub pad: PadParams,
+    pub arp: ArpParams,
+    pub fx: FxParams,
+}
+
+/// The eight synth voices. Each one feeds its own bus input (see `fx::mix`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instrument {
+    Kick,
+    Snare,
+    Hihat,
+    Bass,
+    Pad,
+    Arp,
+    OpenHihat,
+    Crash,
+}
+
+impl Instrument {
+    pub const COUNT: usize = 8;
+    pub const ALL: [Instrument; Instrument::COUNT] = [
+        Instrument::Kick,
+        Instrument::Snare,
+        Instrument::Hihat,
+        Instrument::Bass,
+        Instrument::Pad,
+        Instrument::Arp,
+        Instrument::OpenHihat,
+        Instrument::Crash,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Route a lane to a voice by name, mirroring `scheduleNote` in the TS synth
+    /// (unknown lanes fall back to the snare as a generic percussion hit).
+    pub fn for_lane(lane_name: &str) -> Instrument {
+        let name = lane_name.to_uppercase();
+        if name.contains("KICK") {
+            Instrument::Kick
+        } else if name.contains("SNARE") || name.contains("CLAP") {
+            Instrument::Snare
+        } else if name.contains("RIMSHOT") {
+            // The side stick has no voice of its own; it plays the snare.
+            Instrument::Snare
+        } else if name.contains("CRASH") {
+            Instrument::Crash
+        } else if name.contains("OPEN_HIHAT") {
+            Instrument::OpenHihat
+        } else if name.contains("HIHAT") || name.contains("HAT") {
+            Instrument::Hihat
+        } else if name.contains("BASS") {
+            Instrument::Bass
+        } else if name.contains("ARP") || name.contains("MELODY") {
+            // The hummed melody plays on the arp's lead voice.
+            Instrument::Arp
+        } else if name.contains("PAD") || name.contains("SYNTH") {
+            Instrument::Pad
+        } else {
+            Instrument::Snare
+        }
+    }
+}
+
+// ─── Drum palettes ──────────────────────────────────────────────────────────
+// Exhaustive over DrumPalette: a new variant fails to compile here until it is
+// given a DSP mapping (the TS table enforces the same contract via tsc).
+
+const SYNTHWAVE_HAT_FREQS: [f32; 5] = [3742.0, 4835.0, 5917.0, 7264.0, 8476.0];
+const TR808_HAT_FREQS: [f32; 6] = [3600.0, 5200.0, 6800.0, 8100.0, 9500.0, 10500.0];
+
+fn drums(palette: DrumPalette) -> (KickParams, SnareParams, HihatParams) {
+    match palette {
+        DrumPalette::SynthwaveDrums => (
+            KickParams {
+                sub_freq_start_hz: 150.0,
+                sub_freq_end_hz: 45.0,
+                sweep_sec: 0.08,
+                sub_level: 0.9,
+                sub_decay_sec: 0.4,
+                click_level: 0.35,
+                click_decay_sec: 0.025,
+                harm_level: 0.2,
+                harm_decay_sec: 0.25,
+            },
+            SnareParams {
+                noise_bandpass_start_hz: 3500.0,
+                noise_bandpass_end_hz: 2000.0,
+                noise_hipass_hz: 200.0,
+                noise_level: 0.65,
+                noise_decay_sec: 0.18,
+                body_freq_start_hz: 220.0,
+                body_freq_end_hz: 120.0,
+                body_level: 0.5,
+                body_decay_sec: 0.08,
+                ring_freq_hz: 180.0,
+                ring_level: 0.12,
+                ring_decay_sec: 0.12,
+            },
+            HihatParams {
+                metallic_freqs_hz: &SYNTHWAVE_HAT_FREQS,
+                metallic_level: 0.3,
+                metallic_decay_sec: 0.06,
+                hipass_hz: 7000.0,
+                noise_hipass_hz: 9000.0,
+                noise_level: 0.25,
+                noise_decay_sec: 0.04,
+            },
+        ),
+        DrumPalette::TR808 => (
+            KickParams {
+                sub_freq_start_hz: 140.0,
+                sub_freq_end_hz: 50.0,
+                sweep_sec: 0.06,
+                sub_level: 1.0,
+                sub_decay_sec: 0.7,
+                click_level: 0.1,
+                click_decay_sec: 0.015,
+                harm_level: 0.0,
+                harm_decay_sec: 0.0,
+            },
+            SnareParams {
+                noise_bandpass_start_hz: 5000.0,
+                noise_bandpass_end_hz: 4000.0,
+                noise_hipass_hz: 800.0,
+                noise_level: 0.8,
+                noise_decay_sec: 0.16,
+                body_freq_start_hz: 185.0,
+                body_freq_end_hz: 175.0,
+                body_level: 0.35,
+                body_decay_sec: 0.1,
+                ring_freq_hz: 330.0,
+                ring_level: 0.2,
+                ring_decay_sec: 0.12,
+            },
+            HihatParams {
+                metallic_freqs_hz: &TR808_HAT_FREQS,
+                metallic_level: 0.35,
+                metallic_decay_sec: 0.035,
+                hipass_hz: 8000.0,
+                noise_hipass_hz: 10000.0,
+                noise_level: 0.15,
+                noise_decay_sec: 0.025,
+            },
+        ),
+    }
+}
+
+/// Open hi-hat and crash for a palette: its hat's oscillator bank, given a long
+/// ring (open hat) or a lower, wider band and a long wash (crash).
+fn cymbals(palette: DrumPalette) -> (HihatParams, HihatParams) {
+    match palette {
+        DrumPalette::SynthwaveDrums => (
+            HihatParams {
+                metallic_freqs_hz: &SYNTHWAVE_HAT_FREQS,
+                metallic_level: 0.3,
+                metallic_decay_sec: 0.3,
+                hipass_hz: 7000.0,
+                noise_hipass_hz: 8000.0,
+                noise_level: 0.25,
+                noise_decay_sec: 0.25,
+            },
+            HihatParams {
+                metallic_freqs_hz: &SYNTHWAVE_HAT_FREQS,
+                metallic_level: 0.25,
+                metallic_decay_sec: 1.2,
+                hipass_hz: 4500.0,
+                noise_hipass_hz: 5000.0,
+                noise_level: 0.35,
+                noise_decay_sec: 1.0,
+            },
+        ),
+        DrumPalette::TR808 => (
+            HihatParams {
+                metallic_freqs_hz: &TR808_HAT_FREQS,
+                metallic_level: 0.35,
+                metallic_decay_sec: 0.25,
+                hipass_hz: 8000.0,
+                noise_hipass_hz: 9000.0,
+                noise_level: 0.15,
+                noise_decay_sec: 0.2,
+            },
+            HihatParams {
+                metallic_freqs_hz: &TR808_HAT_FREQS,
+                metallic_level: 0.3,
+                metallic_decay_sec: 0.9,
+                hipass_hz: 5000.0,
+                noise_hipass_hz: 6000.0,
+                noise_level: 0.3,
+                noise_decay_sec: 0.8,
+            },
+        ),
+    }
+}
+
+// ─── FX profiles ────────────────────────────────────────────────────────────
+// Chorus sends are pad/arp only (bass through chorus = low-end phase cancellation).
+// Delay time: GatedReverb/WideChorus = dotted eighth; DarkDelay = eighth; Dry = 0.
+
+const fn send(dry: f32, delay: f32, reverb: f32, chorus: f32) -> SendLevels {
+    SendLevels { dry, delay, reverb, chorus }
+}
+
+fn fx_base(profile: FxProfile, bpm: f32) -> FxParams {
+    let base = FxParams {
+        profile,
+        delay_time_sec: 0.0,
+        delay_feedback: 0.0,
+        delay_filter_hz: None,
+        reverb_duration_sec: 0.0,
+        reverb_decay: 0.0,
+        reverb_gate_sec: None,
+        chorus: None,
+        sends: [DRY_SEND; Instrument::COUNT],
+        delay_return: 0.6,
+        reverb_return: 0.7,
+        chorus_return: 1.0,
+        master_level: 0.8,
+        render_tail_sec: 0.0,
+    };
+    // Send order follows Instrument: kick, snare, hihat, bass, pad, arp, then
+    // the open hat and crash, which take the closed hat's sends.
+    match profile {
+        FxProfile::GatedReverb => FxParams {
+            delay_time_sec: 0.75 * 60.0 / bpm,
+            delay_feedback: 0.25,
+            reverb_duration_sec: 2.0,
+            reverb_decay: 1.5,
+            reverb_gate_sec: Some(0.28),
+            sends: [
+                send(1.0, 0.0, 0.05, 0.0),
+                send(1.0, 0.1, 0.45, 0.0),
+                send(1.0, 0.08, 0.12, 0.0),
+                send(1.0, 0.0, 0.03, 0.0),
+                send(1.0, 0.15, 0.3, 0.0),
+                send(1.0, 0.2, 0.2, 0.0),
+                send(1.0, 0.08, 0.12, 0.0),
+                send(1.0, 0.08, 0.12, 0.0),
+            ],
+            ..base
+        },
+        FxProfile::DarkDelay => FxParams {
+            delay_time_sec: 0.5 * 60.0 / bpm,
+            delay_feedback: 0.45,
+            delay_filter_hz: Some(1200.0),
+            reverb_duration_sec: 1.2,
+            reverb_decay: 3.0,
+            sends: [
+                send(1.0, 0.0, 0.02, 0.0),
+                send(1.0, 0.25, 0.15, 0.0),
+                send(1.0, 0.15, 0.05, 0.0),
+                send(1.0, 0.08, 0.0, 0.0),
+                send(1.0, 0.3, 0.15, 0.0),
+                send(1.0, 0.35, 0.1, 0.0),
+                send(1.0, 0.15, 0.05, 0.0),
+                send(1.0, 0.15, 0.05, 0.0),
+            ],
+            ..base
+        },
+        FxProfile::WideChorus => FxParams {
+            delay_time_sec: 0.75 * 60.0 / bpm,
+            delay_feedback: 0.2,
+            reverb_duration_sec: 1.5,
+            reverb_decay: 2.0,
+            chorus: Some(ChorusParams {
+                delay_ms_l: 8.0,
+                delay_ms_r: 12.0,
+                depth_ms: 2.0,
+                rate_hz_l: 0.6,
+                rate_hz_r: 0.8,
+            }),
+            sends: [
+                send(1.0, 0.0, 0.05, 0.0),
+                send(1.0, 0.08, 0.2, 0.0),
+                send(1.0, 0.05, 0.1, 0.0),
+                send(1.0, 0.0, 0.02, 0.0),
+                send(1.0, 0.1, 0.25, 0.3),
+                send(1.0, 0.12, 0.15, 0.3),
+                send(1.0, 0.05, 0.1, 0.0),
+                send(1.0, 0.05, 0.1, 0.0),
+            ],
+            ..base
+        },
+        FxProfile::Dry => FxParams {
+            delay_return: 0.0,
+            reverb_return: 0.0,
+            chorus_return: 0.0,
+            ..base
+        },
+    }
+}
+
+const PAD_SUSTAINED: PadParams = PadParams {
+    sustain: true,
+    attack_sec: 0.15,
+    decay_to_level: 1.0,
+    decay_by_portion: 0.8,
+};
+
+const PAD_RHYTHMIC: PadParams = PadParams {
+    sustain: false,
+    attack_sec: 0.01,
+    decay_to_level: 0.2,
+    decay_by_portion: 0.4,
+};
+
+/// Fixed pluck voice for all themes.
+const ARP: ArpParams = ArpParams {
+    level: 0.35,
+    attack_sec: 0.005,
+    max_decay_sec: 0.3,
+    filter_start_mul: 6.0,
+    filter_end_mul: 1.5,
+    filter_sweep_sec: 0.15,
+};
+
+/// Clamp bpm to [40, 300]; non-finite or <= 0 → 120.
+fn clamp_bpm(bpm: f64) -> f32 {
+    if !bpm.is_finite() || bpm <= 0.0 {
+        return 120.0;
+    }
+    bpm.clamp(40.0, 300.0) as f32
+}
+
+/// Longest post-note ring of a single dry voice, from the actual palette params.
+fn dry_overhang_sec(
+    kick: &KickParams,
+    snare: &SnareParams,
+    hats: [&HihatParams; 3],
+    arp: &ArpParams,
+) -> f32 {
+    let kick_tail = kick.sub_decay_sec + 0.05;
+    let snare_tail = snare.noise_decay_sec.max(snare.body_decay_sec).max(snare.ring_decay_sec);
+    let hat_tail = hats
+        .iter()
+        .map(|h| h.metallic_decay_sec.max(h.noise_decay_sec))
+        .fold(0.0, f32::max);
+    let bass_tail = 0.05;
+    let pad_tail = 0.1;
+    kick_tail
+        .max(snare_tail)
+        .max(hat_tail)
+        .max(bass_tail)
+        .max(pad_tail)
+        .max(arp.max_decay_sec)
+}
+
+/// FX ring-out: the longer of the reverb tail (gate if present, else the full IR)
+/// and the delay feedback decaying to -60dB.
+fn fx_ring_sec(fx: &FxParams) -> f32 {
+    let reverb_tail = fx.reverb_gate_sec.unwrap_or(fx.reverb_duration_sec);
+    let delay_tail = if fx.delay_feedback > 0.0 {
+        fx.delay_time_sec * (0.001f32.ln() / fx.delay_feedback.ln()).ceil()
+    } else {
+        0.0
+    };
+    reverb_tail.max(delay_tail)
+}
+
+/// Map a ThemeSound + bpm to the full parameter set (the Rust `deriveTimbre`).
+pub fn derive_timbre(sound: &ThemeSound, bpm: f64) -> TimbreParams {
+    let (kick, snare, hihat) = drums(sound.drum_palette);
+    let (open_hihat, crash) = cymbals(sound.drum_palette);
+    let pad = if sound.pad_sustain { PAD_SUSTAINED } else { PAD_RHYTHMIC };
+    let mut fx = fx_base(sound.fx_profile, clamp_bpm(bpm));
+    let hats = [&hihat, &open_hihat, &crash];
+    fx.render_tail_sec = dry_overhang_sec(&kick, &snare, hats, &ARP) + fx_ring_sec(&fx) + 0.1;
+    TimbreParams { kick, snare, hihat, open_hihat, crash, pad, arp: ARP, fx }
+}
+
+/// mulberry32 PRNG — bit-identical to the TS `mulberry32`, yielding [0, 1).
+pub struct Mulberry32(u32);
+
+impl Mulberry32 {
+    pub fn new(seed: u32) -> Self {
+        Mulberry32(seed)
+    }
+
+    pub fn next_f32(&mut self) -> f32 {
+        self.0 = self.0.wrapping_add(0x6d2b_79f5);
+        let a = self.0;
+        let mut t = (a ^ (a >> 15)).wrapping_mul(1 | a);
+        t = (t.wrapping_add((t ^ (t >> 7)).wrapping_mul(61 | t))) ^ t;
+        ((t ^ (t >> 14)) as f64 / 4_294_967_296.0) as f32
+    }
+}
+
+/// One part of a [`seed_from`] key.
+#[derive(Debug, Clone, Copy)]
+pub enum SeedPart<'a> {
+    Str(&'a str),
+    Num(u64),
+}
+
+/// FNV-1a 32-bit over type-tagged, unit-separated parts — the TS `seedFrom`,
+/// so per-note noise and reverb IRs match the browser render seed for seed.
+pub fn seed_from(parts: &[SeedPart<'_>]) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    let mut feed = |s: &str| {
+        // JS hashes UTF-16 code units; every key we build is ASCII.
+        for c in s.encode_utf16() {
+            hash ^= c as u32;
+            hash = hash.wrapping_mul(0x0100_0193);
+        }
+    };
+    for part in parts {
+        match part {
+            SeedPart::Str(s) => {
+                feed("s:");
+                feed(s);
+            }
+            SeedPart::Num(n) => {
+                feed("n:");
+                feed(&n.to_string());
+            }
+        }
+        feed("\u{1f}");
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mulberry32_matches_reference_sequence() {
+        // First outputs of the JS mulberry32(42).
+        let mut r = Mulberry32::new(42);
+        let a = r.next_f32();
+        let b = r.next_f32();
+        assert!((a - 0.601_103_8).abs() < 1e-6, "got {a}");
+        assert!((b - 0.448_290_6).abs() < 1e-6, "got {b}");
+    }
+
+    #[test]
+    fn seed_from_matches_reference_hashes() {
+        // seedFrom("ir", "GatedReverb") and seedFrom("DRUMS_SNARE", 3, "noise") in JS.
+        assert_eq!(seed_from(&[SeedPart::Str("ir"), SeedPart::Str("GatedReverb")]), 4_152_451_787);
+        assert_eq!(
+            seed_from(&[SeedPart::Str("DRUMS_SNARE"), SeedPart::Num(3), SeedPart::Str("noise")]),
+            124_043_728
+        );
+    }
+
+    #[test]
+    fn seed_from_is_tag_and_boundary_sensitive() {
+        assert_ne!(seed_from(&[SeedPart::Str("1")]), seed_from(&[SeedPart::Num(1)]));
+        assert_ne!(
+            seed_from(&[SeedPart::Str("ab"), SeedPart::Str("c")]),
+            seed_from(&[SeedPart::Str("a"), SeedPart::Str("bc")])
+        );
+    }
+
+    #[test]
+    fn dry_profile_has_no_fx_ring() {
+        let sound = ThemeSound {
+            drum_palette: DrumPalette::TR808,
+            fx_profile: FxProfile::Dry,
+            pad_sustain: false,
+        };
+        let t = derive_timbre(&sound, 120.0);
+        // The 808 crash (0.9) is the longest dry voice; + 0.1 margin.
+        assert!((t.fx.render_tail_sec - 1.0).abs() < 1e-5);
+        assert!(!t.pad.sustain);
+    }
+
+    #[test]
+    fn lane_routing_matches_ts_synth() {
+        assert_eq!(Instrument::for_lane("DRUMS_KICK"), Instrument::Kick);
+        assert_eq!(Instrument::for_lane("DRUMS_SNARE"), Instrument::Snare);
+        assert_eq!(Instrument::for_lane("DRUMS_HIHAT"), Instrument::Hihat);
+        assert_eq!(Instrument::for_lane("BASS"), Instrument::Bass);
+        assert_eq!(Instrument::for_lane("PADS"), Instrument::Pad);
+        assert_eq!(Instrument::for_lane("ARP"), Instrument::Arp);
+        assert_eq!(Instrument::for_lane("MELODY"), Instrument::Arp);
+        assert_eq!(Instrument::for_lane("COWBELL"), Instrument::Snare);
+    }
+
+    #[test]
+    fn extended_drum_lanes_get_their_own_voices() {
+        // The arranger's DRUMS_OPEN_HIHAT / DRUMS_RIMSHOT / DRUMS_CRASH lanes.
+        assert_eq!(Instrument::for_lane("DRUMS_OPEN_HIHAT"), Instrument::OpenHihat);
+        assert_eq!(Instrument::for_lane("DRUMS_CRASH"), Instrument::Crash);
+        assert_eq!(Instrument::for_lane("DRUMS_RIMSHOT"), Instrument::Snare);
+    }
+
+    #[test]
+    fn cymbals_ring_longer_than_the_closed_hat() {
+        for drum_palette in [DrumPalette::SynthwaveDrums, DrumPalette::TR808] {
+            let sound = ThemeSound { drum_palette, fx_profile: FxProfile::Dry, pad_sustain: true };
+            let t = derive_timbre(&sound, 120.0);
+            assert!(t.open_hihat.metallic_decay_sec > t.hihat.metallic_decay_sec);
+            assert!(t.crash.metallic_decay_sec > t.open_hihat.metallic_decay_sec);
+            // Open hat and crash share the closed hat's sends.
+            for inst in [Instrument::OpenHihat, Instrument::Crash] {
+                assert_eq!(t.fx.sends[inst.index()], t.fx.sends[Instrument::Hihat.index()]);
+            }
+        }
+    }
+}
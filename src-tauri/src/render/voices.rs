@@ -0,0 +1,247 @@
+// Render Voices - Native ports of the TS instrument schedulers
+// Each voice adds one note into its instrument's bus input buffer. Layer levels,
+// envelopes and filter sweeps are copied from src/audio/scheduleArrangement.ts.
+
+use super::dsp::{midi_to_freq, velocity_to_gain, Biquad, FilterKind, Osc, Param, Wave};
+use super::timbre::{ArpParams, HihatParams, KickParams, Mulberry32, PadParams, SnareParams};
+
+/// Add `len` samples of `voice(t)` into `out` starting at frame `start`, where
+/// `t` is seconds since the note began. Samples past the buffer are dropped.
+fn render_into(out: &mut [f32], start: usize, len: usize, sr: f32, mut voice: impl FnMut(f32) -> f32) {
+    let end = (start + len).min(out.len());
+    for (i, slot) in out.iter_mut().enumerate().take(end).skip(start) {
+        *slot += voice((i - start) as f32 / sr);
+    }
+}
+
+fn samples(sec: f32, sr: f32) -> usize {
+    (sec * sr).ceil().max(0.0) as usize
+}
+
+/// Seeded white noise, `sec` long — the TS `createNoiseBuffer`.
+fn noise_buffer(sec: f32, sr: f32, seed: u32) -> Vec<f32> {
+    let mut rand = Mulberry32::new(seed);
+    (0..samples(sec, sr).max(1)).map(|_| rand.next_f32() * 2.0 - 1.0).collect()
+}
+
+pub fn kick(out: &mut [f32], start: usize, sr: f32, velocity: u8, p: &KickParams) {
+    let gain = velocity_to_gain(velocity);
+
+    // Layer 1: sub-bass body (sine, pitch sweep, brief sustain before decay)
+    let sub_freq = Param::new(p.sub_freq_start_hz)
+        .set(0.0, p.sub_freq_start_hz)
+        .exponential(p.sweep_sec, p.sub_freq_end_hz);
+    let sub_gain = Param::new(0.0)
+        .set(0.0, gain * p.sub_level)
+        .set(0.02, gain * p.sub_level)
+        .exponential(p.sub_decay_sec, 0.001);
+    let mut sub = Osc::new(Wave::Sine);
+    render_into(out, start, samples(p.sub_decay_sec + 0.05, sr), sr, |t| {
+        sub.next(sub_freq.at(t), sr) * sub_gain.at(t)
+    });
+
+    // Layer 2: click transient (triangle burst for attack definition)
+    let click_freq = Param::new(3500.0).set(0.0, 3500.0).exponential(0.02, 200.0);
+    let click_gain = Param::new(0.0)
+        .set(0.0, gain * p.click_level)
+        .exponential(p.click_decay_sec, 0.001);
+    let mut click = Osc::new(Wave::Triangle);
+    render_into(out, start, samples(p.click_decay_sec + 0.005, sr), sr, |t| {
+        click.next(click_freq.at(t), sr) * click_gain.at(t)
+    });
+
+    // Layer 3: harmonic warmth (tanh-saturated 90 Hz sine), skipped at level 0
+    if p.harm_level > 0.0 {
+        let harm_gain = Param::new(0.0)
+            .set(0.0, gain * p.harm_level)
+            .exponential(p.harm_decay_sec, 0.001);
+        let mut harm = Osc::new(Wave::Sine);
+        render_into(out, start, samples(p.harm_decay_sec + 0.05, sr), sr, |t| {
+            (harm.next(90.0, sr) * 2.0).tanh() * harm_gain.at(t)
+        });
+    }
+}
+
+pub fn snare(out: &mut [f32], start: usize, sr: f32, velocity: u8, p: &SnareParams, seed: u32) {
+    let gain = velocity_to_gain(velocity);
+
+    // Layer 1: noise burst → bandpass sweep → highpass (the "crack")
+    let noise = noise_buffer(p.noise_decay_sec, sr, seed);
+    let bp_freq = Param::new(p.noise_bandpass_start_hz)
+        .set(0.0, p.noise_bandpass_start_hz)
+        .exponential(0.1, p.noise_bandpass_end_hz);
+    let noise_gain = Param::new(0.0)
+        .set(0.0, gain * p.noise_level)
+        .exponential(p.noise_decay_sec, 0.001);
+    let mut bandpass = Biquad::new(FilterKind::Bandpass, 0.8);
+    let mut hipass = Biquad::new(FilterKind::Highpass, 1.0);
+    let mut i = 0;
+    render_into(out, start, noise.len(), sr, |t| {
+        let x = bandpass.process(noise[i], bp_freq.at(t), sr);
+        i += 1;
+        hipass.process(x, p.noise_hipass_hz, sr) * noise_gain.at(t)
+    });
+
+    // Layer 2: tonal body (triangle sweep)
+    let body_freq = Param::new(p.body_freq_start_hz)
+        .set(0.0, p.body_freq_start_hz)
+        .exponential(0.04, p.body_freq_end_hz);
+    let body_gain = Param::new(0.0)
+        .set(0.0, gain * p.body_level)
+        .exponential(p.body_decay_sec, 0.001);
+    let mut body = Osc::new(Wave::Triangle);
+    render_into(out, start, samples(p.body_decay_sec + 0.02, sr), sr, |t| {
+        body.next(body_freq.at(t), sr) * body_gain.at(t)
+    });
+
+    // Layer 3: ring / resonance
+    let ring_gain = Param::new(0.0)
+        .set(0.0, gain * p.ring_level)
+        .exponential(p.ring_decay_sec, 0.001);
+    let mut ring = Osc::new(Wave::Sine);
+    render_into(out, start, samples(p.ring_decay_sec + 0.03, sr), sr, |t| {
+        ring.next(p.ring_freq_hz, sr) * ring_gain.at(t)
+    });
+}
+
+pub fn hihat(out: &mut [f32], start: usize, sr: f32, velocity: u8, p: &HihatParams, seed: u32) {
+    let gain = velocity_to_gain(velocity);
+
+    // Metallic shimmer: detuned squares sharing one envelope, then a highpass.
+    // metallic_level is the TOTAL layer gain, split evenly per oscillator.
+    let per_osc = p.metallic_level / p.metallic_freqs_hz.len() as f32;
+    let env = Param::new(0.0)
+        .set(0.0, gain)
+        .exponential(p.metallic_decay_sec, 0.001);
+    let mut oscs: Vec<Osc> = p.metallic_freqs_hz.iter().map(|_| Osc::new(Wave::Square)).collect();
+    let mut hipass = Biquad::new(FilterKind::Highpass, 1.0);
+    render_into(out, start, samples(p.metallic_decay_sec + 0.02, sr), sr, |t| {
+        let sum: f32 = oscs
+            .iter_mut()
+            .zip(p.metallic_freqs_hz)
+            .map(|(osc, &f)| osc.next(f, sr) * per_osc)
+            .sum();
+        hipass.process(sum * env.at(t), p.hipass_hz, sr)
+    });
+
+    // Noise layer for air
+    let noise = noise_buffer(p.noise_decay_sec, sr, seed);
+    let noise_gain = Param::new(0.0)
+        .set(0.0, gain * p.noise_level)
+        .exponential(p.noise_decay_sec, 0.001);
+    let mut noise_hp = Biquad::new(FilterKind::Highpass, 1.0);
+    let mut i = 0;
+    render_into(out, start, noise.len(), sr, |t| {
+        let x = noise_hp.process(noise[i], p.noise_hipass_hz, sr);
+        i += 1;
+        x * noise_gain.at(t)
+    });
+}
+
+pub fn bass(out: &mut [f32], start: usize, sr: f32, velocity: u8, midi_note: u8, duration_ms: f64) {
+    let gain = velocity_to_gain(velocity);
+    let freq = midi_to_freq(midi_note);
+    let dur = (duration_ms as f32 / 1000.0).max(0.05);
+
+    // Velocity to filter cutoff (the growl)
+    let vel_factor = (velocity as f32 / 127.0).powi(2);
+    let cutoff = Param::new(0.0)
+        .set(0.0, freq * (2.0 + 6.0 * vel_factor))
+        .exponential(dur * 0.6, freq * (1.0 + 1.5 * vel_factor));
+    let amp = Param::new(0.0)
+        .set(0.0, 0.001)
+        .linear(0.008, gain * 0.5)
+        .set(dur * 0.7, gain * 0.5)
+        .exponential(dur, 0.001);
+    let sub_gain = Param::new(0.0).set(0.0, gain * 0.3).exponential(dur, 0.001);
+
+    // Dual detuned saws through the swept lowpass, plus a sine sub an octave down
+    let mut osc1 = Osc::new(Wave::Sawtooth);
+    let mut osc2 = Osc::new(Wave::Sawtooth);
+    let mut sub = Osc::new(Wave::Sine);
+    let mut filter = Biquad::new(FilterKind::Lowpass, 2.0);
+    render_into(out, start, samples(dur + 0.05, sr), sr, |t| {
+        let saws = osc1.next(freq, sr) + osc2.next(freq * 1.005, sr);
+        filter.process(saws, cutoff.at(t), sr) * amp.at(t) + sub.next(freq / 2.0, sr) * sub_gain.at(t)
+    });
+}
+
+pub fn pad(
+    out: &mut [f32],
+    start: usize,
+    sr: f32,
+    velocity: u8,
+    midi_note: u8,
+    duration_ms: f64,
+    p: &PadParams,
+) {
+    const DETUNES: [f32; 4] = [-7.0, -3.0, 3.0, 7.0];
+    const WAVES: [Wave; 4] = [Wave::Sawtooth, Wave::Square, Wave::Sawtooth, Wave::Square];
+
+    let gain = velocity_to_gain(velocity);
+    let freq = midi_to_freq(midi_note);
+    let dur = (duration_ms as f32 / 1000.0).max(0.2);
+
+    // Attack → decay → HOLD → release over the last 20%. pad_sustain only moves
+    // these envelope points; the oscillators and filter are fixed.
+    let peak = gain * 0.22;
+    let decay_target = (peak * p.decay_to_level).max(0.001);
+    let amp = Param::new(0.0)
+        .set(0.0, 0.001)
+        .linear(p.attack_sec, peak.max(0.001))
+        .exponential(dur * p.decay_by_portion, decay_target)
+        .set(dur * 0.8, decay_target)
+        .exponential(dur, 0.001);
+
+    // Slow filter sweep for movement
+    let vel_factor = (velocity as f32 / 127.0).powi(2);
+    let base = 800.0 + 1000.0 * vel_factor;
+    let cutoff = Param::new(0.0)
+        .set(0.0, 1500.0 + 2000.0 * vel_factor)
+        .linear(dur * 0.5, base)
+        .linear(dur, base * 1.5);
+
+    let mut oscs: Vec<Osc> = WAVES.iter().map(|&w| Osc::new(w)).collect();
+    let mut lfos: Vec<Osc> = (0..4).map(|_| Osc::new(Wave::Sine)).collect();
+    let mut filter = Biquad::new(FilterKind::Lowpass, 1.5);
+    render_into(out, start, samples(dur + 0.1, sr), sr, |t| {
+        let mut sum = 0.0;
+        for i in 0..4 {
+            // Slow vibrato: per-voice LFO wobbles the detune by (3 + i) cents
+            let lfo = lfos[i].next(0.3 + i as f32 * 0.15, sr) * (3.0 + i as f32);
+            let cents = DETUNES[i] + lfo;
+            sum += oscs[i].next(freq * 2f32.powf(cents / 1200.0), sr);
+        }
+        filter.process(sum, cutoff.at(t), sr) * amp.at(t)
+    });
+}
+
+pub fn arp(
+    out: &mut [f32],
+    start: usize,
+    sr: f32,
+    velocity: u8,
+    midi_note: u8,
+    duration_ms: f64,
+    p: &ArpParams,
+) {
+    let gain = velocity_to_gain(velocity);
+    let freq = midi_to_freq(midi_note);
+    let dur = (duration_ms as f32 / 1000.0).max(0.05);
+
+    // Single saw through a resonant lowpass sweeping down, sharp pluck envelope
+    let cutoff = Param::new(0.0)
+        .set(0.0, p.filter_start_mul * freq)
+        .exponential(p.filter_sweep_sec, p.filter_end_mul * freq);
+    let decay_end = dur.min(p.max_decay_sec);
+    let amp = Param::new(0.0)
+        .set(0.0, 0.001)
+        .linear(p.attack_sec, (gain * p.level).max(0.001))
+        .exponential(decay_end, 0.001);
+
+    let mut osc = Osc::new(Wave::Sawtooth);
+    let mut filter = Biquad::new(FilterKind::Lowpass, 1.0);
+    render_into(out, start, samples(decay_end + 0.02, sr), sr, |t| {
+        filter.process(osc.next(freq, sr), cutoff.at(t), sr) * amp.at(t)
+    });
+}
@@ -99,6 +99,30 @@ pub(crate) fn run_migrations(conn: &Connection) -> DbResult<()> {
         )?;
     }
 
+    if current_version < 3 {
+        migration_v3(conn)?;
+        conn.execute(
+            "INSERT INTO schema_migrations (version) VALUES (?1)",
+            [3],
+        )?;
+    }
+
+    if current_version < 4 {
+        migration_v4(conn)?;
+        conn.execute(
+            "INSERT INTO schema_migrations (version) VALUES (?1)",
+            [4],
+        )?;
+    }
+
+    if current_version < 5 {
+        migration_v5(conn)?;
+        conn.execute(
+            "INSERT INTO schema_migrations (version) VALUES (?1)",
+            [5],
+        )?;
+    }
+
     Ok(())
 }
 
@@ -204,6 +228,50 @@ fn migration_v2(conn: &Connection) -> DbResult<()> {
     Ok(())
 }
 
+fn migration_v3(conn: &Connection) -> DbResult<()> {
+    // Record the channel strategy each run's events were detected with
+    // (`ChannelStrategy`'s display form). Existing rows were all analysed on
+    // the channel average.
+    conn.execute(
+        "ALTER TABLE runs ADD COLUMN channel_strategy TEXT NOT NULL DEFAULT 'average'",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn migration_v4(conn: &Connection) -> DbResult<()> {
+    // The pre-processing chain each run's events were detected with, as
+    // `ConditioningConfig` JSON. NULL means unconditioned, which is how every
+    // existing row was analysed.
+    conn.execute("ALTER TABLE runs ADD COLUMN conditioning TEXT", [])?;
+
+    Ok(())
+}
+
+fn migration_v5(conn: &Connection) -> DbResult<()> {
+    // Room-tone noise profiles, stored beside calibration profiles: the
+    // `NoiseProfile` JSON lives on disk, the row keeps what the picker shows.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS noise_profiles (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            profile_json_path TEXT NOT NULL,
+            noise_floor_dbfs REAL NOT NULL,
+            notes TEXT
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_noise_profiles_created_at ON noise_profiles(created_at DESC)",
+        [],
+    )?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -216,12 +284,12 @@ mod tests {
         // Verify tables exist
         let table_count: i32 = conn
             .query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name IN ('projects', 'runs', 'artifacts', 'calibration_profiles')",
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name IN ('projects', 'runs', 'artifacts', 'calibration_profiles', 'noise_profiles')",
                 [],
                 |row| row.get(0),
             )
             .unwrap();
 
-        assert_eq!(table_count, 4);
+        assert_eq!(table_count, 5);
     }
 }
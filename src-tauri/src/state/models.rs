@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 use uuid::Uuid;
 
+use crate::audio::{ChannelStrategy, ConditioningConfig};
+
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct Project {
     pub id: Uuid,
@@ -28,6 +30,12 @@ pub struct Run {
     /// Tempo phase offset (ms) captured at creation so replaying this run
     /// anchors quantization/chords exactly as the user first heard it.
     pub phase_offset_ms: f64,
+    /// How multi-channel input was reduced for event detection, so a replay
+    /// analyses the same signal the user first heard arranged.
+    pub channel_strategy: ChannelStrategy,
+    /// Pre-processing applied before event detection (`None` = the raw
+    /// signal), recorded for the same reason.
+    pub conditioning: Option<ConditioningConfig>,
     pub status: RunStatus,
 }
 
@@ -116,6 +124,19 @@ pub struct CalibrationProfile {
     pub notes: Option<String>,
 }
 
+/// A stored room-tone capture's noise profile (the measured
+/// `beatrice_dsp::NoiseProfile` JSON lives at `profile_json_path`).
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct NoiseProfile {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub profile_json_path: String,
+    /// RMS level of the room tone in dBFS.
+    pub noise_floor_dbfs: f32,
+    pub notes: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct ProjectSummary {
     pub id: Uuid,
@@ -3,10 +3,12 @@ use chrono::Utc;
 use rusqlite::params;
 use uuid::Uuid;
 
+use crate::audio::{ChannelStrategy, ConditioningConfig};
+
 use super::db::{DbConnection, DbResult};
 use super::models::{
-    Artifact, ArtifactKind, CalibrationProfile, Project, ProjectSummary, Run, RunStatus,
-    RunWithArtifacts,
+    Artifact, ArtifactKind, CalibrationProfile, NoiseProfile, Project, ProjectSummary, Run,
+    RunStatus, RunWithArtifacts,
 };
 
 // ==================== ROW MAPPER HELPERS ====================
@@ -119,6 +121,24 @@ pub fn list_projects(db: &DbConnection) -> DbResult<Vec<ProjectSummary>> {
     Ok(projects)
 }
 
+/// Parse a `ChannelStrategy` column (its display form), surfacing a corrupt
+/// value as a rusqlite error like `col_uuid`.
+fn col_channel_strategy(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<ChannelStrategy> {
+    let s: String = row.get(idx)?;
+    s.parse().map_err(|e: String| {
+        rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, e.into())
+    })
+}
+
+/// Parse a nullable `ConditioningConfig` JSON column, surfacing a corrupt
+/// value as a rusqlite error like `col_channel_strategy`.
+fn col_conditioning(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<Option<ConditioningConfig>> {
+    let json: Option<String> = row.get(idx)?;
+    json.map(|s| serde_json::from_str(&s))
+        .transpose()
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, e.into()))
+}
+
 // ==================== RUN QUERIES ====================
 
 /// Create a new run
@@ -133,6 +153,8 @@ pub fn create_run(
     quantize_strength: f64,
     b_emphasis: f64,
     phase_offset_ms: f64,
+    channel_strategy: ChannelStrategy,
+    conditioning: Option<ConditioningConfig>,
 ) -> DbResult<Run> {
     let run = Run {
         id: Uuid::new_v4(),
@@ -145,13 +167,21 @@ pub fn create_run(
         quantize_strength,
         b_emphasis,
         phase_offset_ms,
+        channel_strategy,
+        conditioning,
         status: RunStatus::Pending,
     };
 
+    let conditioning_json = run
+        .conditioning
+        .map(|c| serde_json::to_string(&c))
+        .transpose()
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
+
     let conn = db.lock();
     conn.execute(
-        "INSERT INTO runs (id, project_id, created_at, pipeline_version, theme, bpm, swing, quantize_strength, b_emphasis, phase_offset_ms, status)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        "INSERT INTO runs (id, project_id, created_at, pipeline_version, theme, bpm, swing, quantize_strength, b_emphasis, phase_offset_ms, channel_strategy, conditioning, status)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
         params![
             run.id.to_string(),
             run.project_id.to_string(),
@@ -163,6 +193,8 @@ pub fn create_run(
             run.quantize_strength,
             run.b_emphasis,
             run.phase_offset_ms,
+            run.channel_strategy.to_string(),
+            conditioning_json,
             run.status.to_string(),
         ],
     )?;
@@ -174,7 +206,7 @@ pub fn create_run(
 pub fn get_run(db: &DbConnection, id: &Uuid) -> DbResult<Option<Run>> {
     let conn = db.lock();
     let mut stmt = conn.prepare(
-        "SELECT id, project_id, created_at, pipeline_version, theme, bpm, swing, quantize_strength, b_emphasis, phase_offset_ms, status
+        "SELECT id, project_id, created_at, pipeline_version, theme, bpm, swing, quantize_strength, b_emphasis, phase_offset_ms, channel_strategy, conditioning, status
          FROM runs WHERE id = ?1",
     )?;
 
@@ -190,7 +222,9 @@ pub fn get_run(db: &DbConnection, id: &Uuid) -> DbResult<Option<Run>> {
             quantize_strength: row.get(7)?,
             b_emphasis: row.get(8)?,
             phase_offset_ms: row.get(9)?,
-            status: RunStatus::from_string(&row.get::<_, String>(10)?),
+            channel_strategy: col_channel_strategy(row, 10)?,
+            conditioning: col_conditioning(row, 11)?,
+            status: RunStatus::from_string(&row.get::<_, String>(12)?),
         })
     });
 
@@ -205,7 +239,7 @@ pub fn get_run(db: &DbConnection, id: &Uuid) -> DbResult<Option<Run>> {
 pub fn list_runs_for_project(db: &DbConnection, project_id: &Uuid) -> DbResult<Vec<Run>> {
     let conn = db.lock();
     let mut stmt = conn.prepare(
-        "SELECT id, project_id, created_at, pipeline_version, theme, bpm, swing, quantize_strength, b_emphasis, phase_offset_ms, status
+        "SELECT id, project_id, created_at, pipeline_version, theme, bpm, swing, quantize_strength, b_emphasis, phase_offset_ms, channel_strategy, conditioning, status
          FROM runs WHERE project_id = ?1
          ORDER BY created_at DESC",
     )?;
@@ -223,7 +257,9 @@ pub fn list_runs_for_project(db: &DbConnection, project_id: &Uuid) -> DbResult<V
                 quantize_strength: row.get(7)?,
                 b_emphasis: row.get(8)?,
                 phase_offset_ms: row.get(9)?,
-                status: RunStatus::from_string(&row.get::<_, String>(10)?),
+                channel_strategy: col_channel_strategy(row, 10)?,
+                conditioning: col_conditioning(row, 11)?,
+                status: RunStatus::from_string(&row.get::<_, String>(12)?),
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
@@ -435,6 +471,95 @@ pub fn delete_calibration_profile(db: &DbConnection, id: &Uuid) -> DbResult<()>
     Ok(())
 }
 
+// ==================== NOISE PROFILE QUERIES ====================
+
+/// Create a new noise profile
+pub fn create_noise_profile(
+    db: &DbConnection,
+    id: Uuid,
+    name: String,
+    profile_json_path: String,
+    noise_floor_dbfs: f32,
+    notes: Option<String>,
+) -> DbResult<NoiseProfile> {
+    let profile = NoiseProfile {
+        id,
+        name,
+        created_at: Utc::now(),
+        profile_json_path,
+        noise_floor_dbfs,
+        notes,
+    };
+
+    let conn = db.lock();
+    conn.execute(
+        "INSERT INTO noise_profiles (id, name, created_at, profile_json_path, noise_floor_dbfs, notes)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            profile.id.to_string(),
+            profile.name,
+            profile.created_at.to_rfc3339(),
+            profile.profile_json_path,
+            profile.noise_floor_dbfs,
+            profile.notes,
+        ],
+    )?;
+
+    Ok(profile)
+}
+
+fn noise_profile_from_row(row: &rusqlite::Row) -> rusqlite::Result<NoiseProfile> {
+    Ok(NoiseProfile {
+        id: col_uuid(row, 0)?,
+        name: row.get(1)?,
+        created_at: col_datetime(row, 2)?,
+        profile_json_path: row.get(3)?,
+        noise_floor_dbfs: row.get(4)?,
+        notes: row.get(5)?,
+    })
+}
+
+/// Get a noise profile by ID
+pub fn get_noise_profile(db: &DbConnection, id: &Uuid) -> DbResult<Option<NoiseProfile>> {
+    let conn = db.lock();
+    let mut stmt = conn.prepare(
+        "SELECT id, name, created_at, profile_json_path, noise_floor_dbfs, notes
+         FROM noise_profiles WHERE id = ?1",
+    )?;
+
+    match stmt.query_row([id.to_string()], noise_profile_from_row) {
+        Ok(profile) => Ok(Some(profile)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// List all noise profiles
+pub fn list_noise_profiles(db: &DbConnection) -> DbResult<Vec<NoiseProfile>> {
+    let conn = db.lock();
+    let mut stmt = conn.prepare(
+        "SELECT id, name, created_at, profile_json_path, noise_floor_dbfs, notes
+         FROM noise_profiles
+         ORDER BY created_at DESC",
+    )?;
+
+    let profiles = stmt
+        .query_map([], noise_profile_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(profiles)
+}
+
+/// Delete a noise profile
+pub fn delete_noise_profile(db: &DbConnection, id: &Uuid) -> DbResult<()> {
+    let conn = db.lock();
+    conn.execute(
+        "DELETE FROM noise_profiles WHERE id = ?1",
+        params![id.to_string()],
+    )?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -456,7 +581,7 @@ mod tests {
     }
 
     #[test]
-    fn create_run_round_trips_phase_offset() {
+    fn create_run_round_trips_phase_offset_channel_strategy_and_conditioning() {
         let db = test_db();
         let project_id = Uuid::new_v4();
         create_project(&db, project_id, "n".into(), "/p".into(), "sha".into(), 1000).unwrap();
@@ -471,12 +596,16 @@ mod tests {
             0.8,
             0.6,
             123.4,
+            ChannelStrategy::Channel(1),
+            Some(ConditioningConfig { highpass_hz: Some(60.0), ..ConditioningConfig::default() }),
         )
         .unwrap();
         assert_eq!(run.phase_offset_ms, 123.4);
 
         let fetched = get_run(&db, &run.id).unwrap().unwrap();
         assert_eq!(fetched.phase_offset_ms, 123.4);
+        assert_eq!(fetched.channel_strategy, ChannelStrategy::Channel(1));
+        assert_eq!(fetched.conditioning, run.conditioning);
     }
 
     #[test]
@@ -509,6 +638,25 @@ mod tests {
 
         let fetched = get_run(&db, &run_id).unwrap().unwrap();
         assert_eq!(fetched.phase_offset_ms, 0.0);
+        assert_eq!(fetched.channel_strategy, ChannelStrategy::Average);
+        assert_eq!(fetched.conditioning, None);
+    }
+
+    #[test]
+    fn noise_profiles_round_trip_and_delete() {
+        let db = test_db();
+        let id = Uuid::new_v4();
+        let created =
+            create_noise_profile(&db, id, "studio".into(), "/n.json".into(), -62.5, None).unwrap();
+        assert_eq!(created.id, id);
+
+        let fetched = get_noise_profile(&db, &id).unwrap().unwrap();
+        assert_eq!(fetched.name, "studio");
+        assert_eq!(fetched.noise_floor_dbfs, -62.5);
+        assert_eq!(list_noise_profiles(&db).unwrap().len(), 1);
+
+        delete_noise_profile(&db, &id).unwrap();
+        assert!(get_noise_profile(&db, &id).unwrap().is_none());
     }
 
     #[test]
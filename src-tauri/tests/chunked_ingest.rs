@@ -0,0 +1,121 @@
+//! Chunked ingest: `open_audio` + `analyze_source_hybrid` (the `detect_events`
+//! path) must find exactly what whole-file ingest + `analyze_offline_hybrid`
+//! finds, for WAV read off disk and for every compressed container.
+
+use std::path::{Path, PathBuf};
+
+use beatrice_lib::audio::{self, AnalysisProgress, OnsetConfig};
+use beatrice_lib::events::{Event, HybridClassifier};
+
+fn test_audio(name: &str) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join("test-audio")
+        .join(name);
+    assert!(
+        path.exists(),
+        "missing {}. Run 'node scripts/generate-test-audio.mjs' first.",
+        path.display()
+    );
+    path
+}
+
+fn compressed_fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("compressed")
+        .join(name)
+}
+
+fn in_memory(path: &Path) -> Vec<Event> {
+    let audio = audio::ingest_audio(&std::fs::read(path).unwrap()).unwrap();
+    beatrice_dsp::analyze_offline_hybrid(&audio, &OnsetConfig::default(), &HybridClassifier::factory())
+}
+
+fn chunked(path: &Path, progress: &mut dyn FnMut(AnalysisProgress)) -> Vec<Event> {
+    let mut source = audio::open_audio(path).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+    audio::analyze_source_hybrid(
+        &mut source,
+        &OnsetConfig::default(),
+        &HybridClassifier::factory(),
+        progress,
+    )
+    .unwrap()
+}
+
+fn assert_same_events(name: &str, chunked: &[Event], reference: &[Event]) {
+    assert_eq!(chunked.len(), reference.len(), "{name}: event count");
+    for (c, r) in chunked.iter().zip(reference) {
+        assert_eq!(c.timestamp_ms, r.timestamp_ms, "{name}");
+        assert_eq!(c.duration_ms, r.duration_ms, "{name}");
+        assert_eq!(c.class, r.class, "{name} @ {}ms", r.timestamp_ms);
+        assert_eq!(c.confidence, r.confidence, "{name} @ {}ms", r.timestamp_ms);
+    }
+}
+
+#[test]
+fn chunked_wav_matches_whole_file_analysis() {
+    for name in ["test-pattern.wav", "test-offgrid.wav", "test-8bar-progression.wav"] {
+        let path = test_audio(name);
+        let reference = in_memory(&path);
+        assert!(!reference.is_empty(), "{name}: no events");
+        assert_same_events(name, &chunked(&path, &mut |_| {}), &reference);
+    }
+}
+
+#[test]
+fn chunked_compressed_matches_whole_file_analysis() {
+    for name in [
+        "test-pattern.flac",
+        "test-pattern.mp3",
+        "test-pattern.opus",
+        "test-pattern.webm",
+        "test-pattern.m4a",
+    ] {
+        let path = compressed_fixture(name);
+        let reference = in_memory(&path);
+        assert_eq!(reference.len(), 4, "{name}: test-pattern has four hits");
+        assert_same_events(name, &chunked(&path, &mut |_| {}), &reference);
+    }
+}
+
+/// A recording many chunks long streams from disk, reports monotone
+/// progress through both passes, and still finds every hit.
+#[test]
+fn long_recording_streams_with_progress() {
+    const REPEATS: usize = 24; // 24 × 2.5s = 60s
+    let pattern = audio::ingest_wav(&std::fs::read(test_audio("test-pattern.wav")).unwrap()).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("rehearsal.wav");
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate: pattern.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for _ in 0..REPEATS {
+        for &s in &pattern.to_mono() {
+            let s = (s * 32767.0) as i16;
+            writer.write_sample(s).unwrap();
+            writer.write_sample(s).unwrap();
+        }
+    }
+    writer.finalize().unwrap();
+
+    let mut scanned = Vec::new();
+    let mut classified = Vec::new();
+    let events = chunked(&path, &mut |p| match p {
+        AnalysisProgress::Scanning { frames, total_frames } => scanned.push((frames, total_frames)),
+        AnalysisProgress::Classifying { done, total } => classified.push((done, total)),
+        AnalysisProgress::OnsetsDetected { .. } => {}
+    });
+
+    assert_eq!(events.len(), 4 * REPEATS, "every repeat of the pattern");
+    let total = pattern.frame_count * REPEATS;
+    assert!(scanned.len() > 10, "expected many chunks, got {}", scanned.len());
+    assert!(scanned.windows(2).all(|w| w[0].0 < w[1].0));
+    assert_eq!(scanned.last(), Some(&(total, Some(total))));
+    assert_eq!(classified.last(), Some(&(events.len(), events.len())));
+}
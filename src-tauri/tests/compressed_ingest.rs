@@ -0,0 +1,132 @@
+//! Compressed-audio ingest: every container `ingest_audio` accepts must decode
+//! to the same normalized samples and the same onsets as the WAV it came from.
+//!
+//! The fixtures in `tests/fixtures/compressed/` are `test-pattern.wav` (see
+//! scripts/generate-test-audio.mjs: kick@0, hihat@500, snare@1000, kick@1500ms)
+//! encoded once and committed — FLAC, MP3 (LAME, with gapless tag), Ogg/Opus,
+//! WebM/Opus (the browser `MediaRecorder` shape) and M4A (AAC-LC with an
+//! iTunes-style priming edit list). The encoders are not build dependencies.
+
+use std::path::PathBuf;
+
+use beatrice_lib::audio::{self, AudioData, AudioError, AudioFormat, OnsetConfig};
+use beatrice_lib::groove;
+
+/// Onset agreement with the lossless reference. Lossy codecs smear the attack
+/// a little; a real timing bug (un-trimmed encoder delay) is 25-50ms.
+const ONSET_EPS_MS: f64 = 12.0;
+
+const FIXTURES: [(&str, AudioFormat, u32); 5] = [
+    ("test-pattern.flac", AudioFormat::Flac, 44100),
+    ("test-pattern.mp3", AudioFormat::Mp3, 44100),
+    ("test-pattern.opus", AudioFormat::Ogg, 48000),
+    ("test-pattern.webm", AudioFormat::WebM, 48000),
+    ("test-pattern.m4a", AudioFormat::Mp4, 44100),
+];
+
+fn fixture_bytes(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("compressed")
+        .join(name);
+    std::fs::read(&path).unwrap_or_else(|e| panic!("missing fixture {}: {e}", path.display()))
+}
+
+fn onset_times(audio: &AudioData) -> Vec<f64> {
+    audio::detect_onsets(audio, &OnsetConfig::default())
+        .iter()
+        .map(|o| o.timestamp_ms)
+        .collect()
+}
+
+#[test]
+fn every_fixture_is_sniffed_and_decoded() {
+    for (name, format, sample_rate) in FIXTURES {
+        let bytes = fixture_bytes(name);
+        assert_eq!(AudioFormat::sniff(&bytes), Some(format), "{name}: sniffed container");
+
+        let audio = audio::ingest_audio(&bytes).unwrap_or_else(|e| panic!("{name}: {e}"));
+        assert_eq!(audio.sample_rate, sample_rate, "{name}: sample rate");
+        assert_eq!(audio.channels, 1, "{name}: channels");
+        assert_eq!(audio.frame_count * audio.channels as usize, audio.samples.len());
+        assert!(
+            (audio.duration_ms - 2500).abs() <= 60,
+            "{name}: duration {}ms, expected ~2500ms",
+            audio.duration_ms
+        );
+        let peak = audio.samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(peak > 0.5 && peak <= 1.0, "{name}: peak {peak} outside the normalized range");
+    }
+}
+
+#[test]
+fn onsets_match_the_lossless_reference() {
+    let reference = onset_times(&audio::ingest_audio(&fixture_bytes("test-pattern.flac")).unwrap());
+    assert_eq!(reference.len(), 4, "reference onsets {reference:?}");
+    for (onset, expected) in reference.iter().zip([0.0, 500.0, 1000.0, 1500.0]) {
+        assert!((onset - expected).abs() < 80.0, "reference onset {onset} vs pattern {expected}");
+    }
+
+    for (name, _, _) in &FIXTURES[1..] {
+        let audio = audio::ingest_audio(&fixture_bytes(name)).unwrap();
+        let onsets = onset_times(&audio);
+        assert_eq!(onsets.len(), reference.len(), "{name}: onsets {onsets:?} vs {reference:?}");
+        for (a, r) in onsets.iter().zip(&reference) {
+            assert!(
+                (a - r).abs() <= ONSET_EPS_MS,
+                "{name}: onset {a:.1}ms vs lossless {r:.1}ms — encoder delay not trimmed?"
+            );
+        }
+    }
+}
+
+#[test]
+fn tempo_is_container_independent() {
+    let estimate = |name: &str| {
+        let audio = audio::ingest_audio(&fixture_bytes(name)).unwrap();
+        let onsets = audio::detect_onsets(&audio, &OnsetConfig::default());
+        groove::estimate_tempo(&onsets, audio.sample_rate).bpm
+    };
+    let reference = estimate("test-pattern.flac");
+    for (name, _, _) in &FIXTURES[1..] {
+        let bpm = estimate(name);
+        assert!((bpm - reference).abs() < 1.0, "{name}: {bpm:.2} BPM vs lossless {reference:.2}");
+    }
+}
+
+#[test]
+fn flac_decodes_bit_identical_to_the_wav() {
+    let wav_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join("test-audio")
+        .join("test-pattern.wav");
+    let wav = std::fs::read(&wav_path).unwrap_or_else(|e| {
+        panic!(
+            "Failed to read {}: {e}. Run 'node scripts/generate-test-audio.mjs' first.",
+            wav_path.display()
+        )
+    });
+    let from_wav = audio::ingest_audio(&wav).unwrap();
+    let from_flac = audio::ingest_audio(&fixture_bytes("test-pattern.flac")).unwrap();
+    assert_eq!(from_flac.bit_depth, 16);
+    assert_eq!(from_flac.samples, from_wav.samples);
+}
+
+#[test]
+fn truncated_files_are_errors_not_panics() {
+    for (name, _, _) in FIXTURES {
+        let bytes = fixture_bytes(name);
+        for len in [4, 64, 512] {
+            let result = audio::ingest_audio(&bytes[..len]);
+            assert!(
+                matches!(
+                    result,
+                    Err(AudioError::DecodeError(_) | AudioError::UnsupportedFormat(_) | AudioError::InvalidData)
+                ),
+                "{name} cut to {len} bytes: {:?}",
+                result.map(|a| a.frame_count)
+            );
+        }
+    }
+}
@@ -75,6 +75,7 @@ fn run_pipeline(path: &Path) -> (Vec<QuantizedEvent>, Grid, Theme) {
         strength: 0.8,
         swing_amount: 0.0,
         lookahead_ms: 100.0,
+        include_rejected: false,
     };
     let quantized = groove::quantize_events(&detected, &grid, &settings);
 
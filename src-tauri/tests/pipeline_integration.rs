@@ -270,7 +270,7 @@ fn test_pattern_full_pipeline() {
 
     // Step 4: Quantize
     let grid = Grid::new_with_feel(tempo.bpm, TimeSignature::FourFour, GridDivision::Sixteenth, GrooveFeel::Straight, 0.0, 4);
-    let settings = QuantizeSettings { strength: 0.8, swing_amount: 0.0, lookahead_ms: 100.0 };
+    let settings = QuantizeSettings { strength: 0.8, swing_amount: 0.0, lookahead_ms: 100.0, include_rejected: false };
     let quantized = groove::quantize_events(&events, &grid, &settings);
 
     println!("Pipeline: {} events quantized", quantized.len());
@@ -0,0 +1,184 @@
+//! Golden freeze test for the native arrangement renderer.
+//!
+//! Renders one fixed, hand-built two-bar arrangement per registered theme (so
+//! every `DrumPalette`, `FxProfile` and `pad_sustain` combination in the
+//! registry is exercised) and compares the WAV against `tests/golden/render/`.
+//!
+//! Two levels of strictness, mirroring `beatrice-dsp/tests/offline_freeze.rs`:
+//!   - the 50ms-block RMS envelope of each channel is compared to a tolerance on
+//!     every architecture — a real synthesis change moves it far beyond that;
+//!   - the SHA-256 of the exact WAV bytes is compared only on the architecture
+//!     the golden was frozen on, since libm `sin`/`exp`/`powf` may round one
+//!     ULP differently elsewhere and flip the odd 16-bit LSB.
+//!
+//! Regenerate after an intentional sound change with
+//! `BEATRICE_BLESS_RENDER=1 cargo test --test render_freeze`.
+
+use std::fs;
+use std::path::PathBuf;
+
+use beatrice_lib::arranger::{ArrangedNote, Arrangement, ArrangementTemplate, DrumLane};
+use beatrice_lib::render::{render_wav, RenderOptions};
+use beatrice_lib::themes::{self, Theme};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Low rate keeps the test fast; the synthesis path is rate-independent.
+const SAMPLE_RATE: u32 = 22050;
+const BLOCK_MS: usize = 50;
+const RMS_EPS: f64 = 1e-4;
+
+#[derive(Serialize, Deserialize)]
+struct RenderGolden {
+    arch: String,
+    sha256: String,
+    frames: usize,
+    rms_left: Vec<f64>,
+    rms_right: Vec<f64>,
+}
+
+fn golden_path(theme: &Theme) -> PathBuf {
+    let slug = theme.name.to_lowercase().replace(' ', "_");
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join("render")
+        .join(format!("{slug}.json"))
+}
+
+fn note(t: f64, dur: f64, velocity: u8, midi: Option<u8>) -> ArrangedNote {
+    ArrangedNote::new(t, dur, velocity, midi, None)
+}
+
+/// Two bars of 4/4 touching every voice: kick on 1 and 3, snare on 2 and 4,
+/// eighth-note hats, root/fifth bass, one pad chord tone per bar, and a
+/// sixteenth-note arp run.
+fn fixed_arrangement(theme: &Theme) -> Arrangement {
+    let bpm = theme.bpm_range.0 as f64;
+    let beat = 60_000.0 / bpm;
+    let root = theme.root_note;
+    let mut arr = Arrangement::new(
+        ArrangementTemplate::SynthwaveStraight,
+        beat * 8.0,
+        2,
+        theme.name.clone(),
+        bpm,
+        theme.sound,
+    );
+
+    let mut kick = DrumLane::new("DRUMS_KICK", 36);
+    let mut snare = DrumLane::new("DRUMS_SNARE", 38);
+    let mut hihat = DrumLane::new("DRUMS_HIHAT", 42);
+    let mut bass = DrumLane::new("BASS", 36);
+    let mut pad = DrumLane::new("PADS", 48);
+    let mut arp = DrumLane::new("ARP", 60);
+    for b in 0..8 {
+        let t = b as f64 * beat;
+        if b % 2 == 0 {
+            kick.add_note(note(t, 100.0, 120, None));
+            bass.add_note(note(t, 200.0, 110, Some(root - 24)));
+        } else {
+            snare.add_note(note(t, 100.0, 100, None));
+            bass.add_note(note(t, 200.0, 90, Some(root - 17)));
+        }
+        hihat.add_note(note(t, 50.0, 80, None));
+        hihat.add_note(note(t + beat / 2.0, 50.0, 60, None));
+        for s in 0..4 {
+            arp.add_note(note(t + s as f64 * beat / 4.0, beat / 4.0, 90, Some(root + [0, 3, 7, 12][s])));
+        }
+    }
+    pad.add_note(note(0.0, beat * 4.0, 100, Some(root - 12)));
+    pad.add_note(note(beat * 4.0, beat * 4.0, 100, Some(root - 5)));
+
+    arr.drum_lanes = vec![kick, snare, hihat];
+    arr.bass_lane = Some(bass);
+    arr.pad_lane = Some(pad);
+    arr.arp_lane = Some(arp);
+    arr
+}
+
+/// Per-block RMS of one interleaved channel of 16-bit stereo WAV bytes.
+fn block_rms(wav: &[u8], channel: usize) -> Vec<f64> {
+    let reader = hound::WavReader::new(std::io::Cursor::new(wav)).expect("valid wav");
+    let samples: Vec<f64> = reader
+        .into_samples::<i16>()
+        .skip(channel)
+        .step_by(2)
+        .map(|s| s.expect("sample") as f64 / 32768.0)
+        .collect();
+    let block = SAMPLE_RATE as usize * BLOCK_MS / 1000;
+    samples
+        .chunks(block)
+        .map(|c| (c.iter().map(|s| s * s).sum::<f64>() / c.len() as f64).sqrt())
+        .collect()
+}
+
+fn snapshot(wav: &[u8]) -> RenderGolden {
+    RenderGolden {
+        arch: std::env::consts::ARCH.to_string(),
+        sha256: hex::encode(Sha256::digest(wav)),
+        frames: (wav.len() - 44) / 4,
+        rms_left: block_rms(wav, 0),
+        rms_right: block_rms(wav, 1),
+    }
+}
+
+#[test]
+fn render_matches_golden_for_every_theme() {
+    let bless = std::env::var_os("BEATRICE_BLESS_RENDER").is_some();
+    let options = RenderOptions { sample_rate: SAMPLE_RATE };
+
+    for theme in themes::all_themes() {
+        let wav = render_wav(&fixed_arrangement(&theme), &options).expect("render");
+        let actual = snapshot(&wav);
+        let path = golden_path(&theme);
+
+        if bless {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, serde_json::to_string_pretty(&actual).unwrap()).unwrap();
+            continue;
+        }
+
+        let golden: RenderGolden = serde_json::from_str(
+            &fs::read_to_string(&path).unwrap_or_else(|e| panic!("missing golden {}: {e}", path.display())),
+        )
+        .expect("golden json");
+
+        assert_eq!(actual.frames, golden.frames, "{}: frame count", theme.name);
+        for (ch, (a, g)) in [(&actual.rms_left, &golden.rms_left), (&actual.rms_right, &golden.rms_right)]
+            .into_iter()
+            .enumerate()
+        {
+            assert_eq!(a.len(), g.len(), "{}: block count", theme.name);
+            for (i, (x, y)) in a.iter().zip(g.iter()).enumerate() {
+                assert!(
+                    (x - y).abs() <= RMS_EPS,
+                    "{}: channel {ch} block {i} rms {x} vs golden {y}",
+                    theme.name
+                );
+            }
+        }
+        if actual.arch == golden.arch {
+            assert_eq!(actual.sha256, golden.sha256, "{}: WAV bytes drifted", theme.name);
+        }
+    }
+}
+
+#[test]
+fn every_theme_renders_audibly_and_differently() {
+    let options = RenderOptions { sample_rate: SAMPLE_RATE };
+    let hashes: Vec<String> = themes::all_themes()
+        .iter()
+        .map(|theme| {
+            let wav = render_wav(&fixed_arrangement(theme), &options).expect("render");
+            let rms = block_rms(&wav, 0);
+            assert!(rms.iter().any(|&r| r > 0.05), "{} rendered near-silence", theme.name);
+            hex::encode(Sha256::digest(&wav))
+        })
+        .collect();
+    for i in 0..hashes.len() {
+        for j in (i + 1)..hashes.len() {
+            assert_ne!(hashes[i], hashes[j], "two themes rendered identical audio");
+        }
+    }
+}
@@ -231,6 +231,8 @@ function App() {
             run_id: null,
             use_calibration: !!profileId,
             calibration_profile_id: profileId,
+            normalize_sample_rate: true,
+            channel_strategy: "average",
           })
           .then(unwrap);
 
@@ -357,6 +359,8 @@ function App() {
               // Persist the tempo phase so replaying this run anchors
               // quantization/chords exactly as the user first heard it.
               phase_offset_ms: tempoResult.phase_offset_ms,
+              // Recorded so a replay detects on the same channel mix.
+              channel_strategy: "average",
             })
           );
 
@@ -776,6 +780,7 @@ function App() {
       checkLane(arrangement.bass_lane);
       checkLane(arrangement.pad_lane);
       checkLane(arrangement.arp_lane);
+      checkLane(arrangement.melody_lane ?? null);
 
       const reasoning =
         winner && runnerUp
@@ -836,6 +841,7 @@ function App() {
     collectLane(arrangement.bass_lane);
     collectLane(arrangement.pad_lane);
     collectLane(arrangement.arp_lane);
+    collectLane(arrangement.melody_lane ?? null);
 
     return notes;
   }, [pipelineResult?.arrangement, eventDecisions]);
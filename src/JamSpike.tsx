@@ -159,11 +159,13 @@ export default function JamSpike() {
         let repResolve: (() => void) | null = null;
 
         node.port.onmessage = (e: MessageEvent) => {
-          const data = e.data as { type: string; t?: number };
+          const data = e.data as { type: string; t?: number; stage?: number };
           // The StreamingDetector posts { type: "event", ... } per classified
           // onset. The harness only cares about timing (detect delta), so it
-          // treats every event as a hit and ignores class/confidence.
-          if (data.type !== "event") return;
+          // treats every event as a hit and ignores class/confidence. With
+          // two-stage emission the provisional event is the hit; a revision
+          // (stage 2) re-reports an onset already counted.
+          if (data.type !== "event" || data.stage === 2) return;
           if (mode === "loopback") {
             // React to a real transient with a kick (the jam behaviour).
             scheduleKick(ctx!, bus, ctx!.currentTime, 120, JAM_TIMBRE.kick);
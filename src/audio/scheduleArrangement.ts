@@ -270,7 +270,17 @@ export function createFxBus(
   }
 
   // ---- Input nodes + per-instrument fan-out ----
-  const kinds: InstrumentKind[] = ["kick", "snare", "hihat", "bass", "pad", "arp"];
+  const kinds: InstrumentKind[] = [
+    "kick",
+    "snare",
+    "hihat",
+    "bass",
+    "pad",
+    "arp",
+    "openHihat",
+    "crash",
+  ];
+  const drumKinds: InstrumentKind[] = ["kick", "snare", "hihat", "openHihat", "crash"];
   const inputs = {} as Record<InstrumentKind, GainNode>;
   for (const k of kinds) {
     const input = ctx.createGain();
@@ -278,7 +288,7 @@ export function createFxBus(
     inputs[k] = input;
 
     const s = fx.sends[k];
-    const group = k === "kick" || k === "snare" || k === "hihat" ? drumGroup : musicGroup;
+    const group = drumKinds.includes(k) ? drumGroup : musicGroup;
 
     // Dry path → group (every profile has a dry send).
     const dry = ctx.createGain();
@@ -459,6 +469,10 @@ export function scheduleSnare(
   ringOsc.stop(time + p.ringDecaySec + 0.03);
 }
 
+/**
+ * Closed hat by default; the open hat and crash play the same two layers with
+ * their own (longer) params into their own bus input via `kind`.
+ */
 export function scheduleHihat(
   ctx: BaseAudioContext,
   bus: FxBus,
@@ -466,9 +480,10 @@ export function scheduleHihat(
   velocity: number,
   p: HihatParams,
   seed: number,
+  kind: "hihat" | "openHihat" | "crash" = "hihat",
 ): void {
   const gain = velocityToGain(velocity);
-  const dest = bus.inputs.hihat;
+  const dest = bus.inputs[kind];
 
   // === Metallic shimmer: multiple detuned square waves through highpass ===
   // metallicLevel is the TOTAL layer gain, so each oscillator gets an equal
@@ -728,11 +743,19 @@ function scheduleNote(
     bus.duck(noteTime);
   } else if (name.includes("SNARE") || name.includes("CLAP")) {
     scheduleSnare(ctx, bus, noteTime, note.velocity, timbre.snare, noiseSeed);
+  } else if (name.includes("RIMSHOT")) {
+    // The side stick has no voice of its own; it plays the snare.
+    scheduleSnare(ctx, bus, noteTime, note.velocity, timbre.snare, noiseSeed);
+  } else if (name.includes("CRASH")) {
+    scheduleHihat(ctx, bus, noteTime, note.velocity, timbre.crash, noiseSeed, "crash");
+  } else if (name.includes("OPEN_HIHAT")) {
+    scheduleHihat(ctx, bus, noteTime, note.velocity, timbre.openHihat, noiseSeed, "openHihat");
   } else if (name.includes("HIHAT") || name.includes("HAT")) {
     scheduleHihat(ctx, bus, noteTime, note.velocity, timbre.hihat, noiseSeed);
   } else if (name.includes("BASS")) {
     scheduleBass(ctx, bus, noteTime, note.velocity, midiNote, note.duration_ms);
-  } else if (name.includes("ARP")) {
+  } else if (name.includes("ARP") || name.includes("MELODY")) {
+    // The hummed melody plays on the arp's lead voice.
     scheduleArp(ctx, bus, noteTime, note.velocity, midiNote, note.duration_ms, timbre.arp);
   } else if (name.includes("PAD") || name.includes("SYNTH")) {
     schedulePad(ctx, bus, noteTime, note.velocity, midiNote, note.duration_ms, timbre.pad);
@@ -744,7 +767,8 @@ function scheduleNote(
 
 /**
  * Collect the playable lanes (those with events) from an arrangement. Supports
- * both the current shape (`drum_lanes` + `bass_lane`/`pad_lane`/`arp_lane`) and
+ * both the current shape (`drum_lanes` + `bass_lane`/`pad_lane`/`arp_lane`/
+ * `melody_lane`) and
  * the legacy `tracks` shape. Returns lanes in schedule order.
  */
 export function collectArrangementLanes(arrangement: unknown): Lane[] {
@@ -770,6 +794,10 @@ export function collectArrangementLanes(arrangement: unknown): Lane[] {
     if (arpLane && arpLane.events.length > 0) {
       lanes.push(arpLane);
     }
+    const melodyLane = arr.melody_lane as Lane | null | undefined;
+    if (melodyLane && melodyLane.events.length > 0) {
+      lanes.push(melodyLane);
+    }
   } else if (Array.isArray(arr.tracks)) {
     const tracks = arr.tracks as Array<{ name: string; events: unknown[] }>;
     for (const track of tracks) {
@@ -827,6 +855,7 @@ export function calculateArrangementDuration(arrangement: unknown, bpm: number):
     checkLanes(arr.bass_lane);
     checkLanes(arr.pad_lane);
     checkLanes(arr.arp_lane);
+    checkLanes(arr.melody_lane);
   }
 
   if (totalDurationMs <= 0) {
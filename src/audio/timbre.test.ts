@@ -97,9 +97,17 @@ describe("deriveTimbre(BR_SOUND, 90)", () => {
     expect(t.pad.sustain).toBe(true);
   });
 
-  it("computes renderTailSec = dryOverhang 0.45 + fxRing 2.5 + 0.1", () => {
+  it("computes renderTailSec = dryOverhang 1.2 + fxRing 2.5 + 0.1", () => {
+    // dryOverhang = crash metallic 1.2 (rings past the 0.45 kick)
     // fxRing = max(gate 0.28, delay 0.5 * ceil(ln0.001/ln0.25)=0.5*5=2.5) = 2.5
-    expect(t.fx.renderTailSec).toBeCloseTo(3.05, 5);
+    expect(t.fx.renderTailSec).toBeCloseTo(3.8, 5);
+  });
+
+  it("gives the open hat and crash longer rings and the closed hat's sends", () => {
+    expect(t.openHihat.metallicDecaySec).toBeGreaterThan(t.hihat.metallicDecaySec);
+    expect(t.crash.metallicDecaySec).toBeGreaterThan(t.openHihat.metallicDecaySec);
+    expect(t.fx.sends.openHihat).toEqual(t.fx.sends.hihat);
+    expect(t.fx.sends.crash).toEqual(t.fx.sends.hihat);
   });
 });
 
@@ -117,9 +125,9 @@ describe("deriveTimbre(ST_SOUND, 120)", () => {
     expect(t.pad.sustain).toBe(false);
   });
 
-  it("computes renderTailSec = 0.75 + 0.25*9 + 0.1", () => {
-    // dryOverhang = kick 0.7+0.05 = 0.75; fxRing = max(reverb 1.2, delay 0.25*9=2.25) = 2.25
-    expect(t.fx.renderTailSec).toBeCloseTo(3.1, 5);
+  it("computes renderTailSec = 0.9 + 0.25*9 + 0.1", () => {
+    // dryOverhang = crash 0.9 (> kick 0.7+0.05); fxRing = max(reverb 1.2, delay 0.25*9=2.25) = 2.25
+    expect(t.fx.renderTailSec).toBeCloseTo(3.25, 5);
   });
 });
 
@@ -140,14 +148,14 @@ describe("deriveTimbre bpm clamp [40, 300], non-finite/<=0 → 120", () => {
 
 // (f) Dry profile tail --------------------------------------------------------
 describe("deriveTimbre — Dry profile", () => {
-  it("renderTailSec = dryOverhang 0.45 + 0 + 0.1 for SynthwaveDrums", () => {
+  it("renderTailSec = dryOverhang 1.2 + 0 + 0.1 for SynthwaveDrums", () => {
     const drySound: ThemeSound = {
       drum_palette: "SynthwaveDrums",
       fx_profile: "Dry",
       pad_sustain: true,
     };
     const t = deriveTimbre(drySound, 120);
-    expect(t.fx.renderTailSec).toBeCloseTo(0.55, 5);
+    expect(t.fx.renderTailSec).toBeCloseTo(1.3, 5);
   });
 });
 
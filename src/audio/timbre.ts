@@ -8,7 +8,15 @@
 // renderMetaFromArrangement.
 import type { ThemeSound, DrumPalette, FxProfile } from "../bindings";
 
-export type InstrumentKind = "kick" | "snare" | "hihat" | "bass" | "pad" | "arp";
+export type InstrumentKind =
+  | "kick"
+  | "snare"
+  | "hihat"
+  | "bass"
+  | "pad"
+  | "arp"
+  | "openHihat"
+  | "crash";
 
 export interface SendLevels {
   dry: number;
@@ -97,6 +105,9 @@ export interface TimbreParams {
   kick: KickParams;
   snare: SnareParams;
   hihat: HihatParams;
+  /** Open hi-hat and crash: the hi-hat's metallic + noise layers, left to ring. */
+  openHihat: HihatParams;
+  crash: HihatParams;
   pad: PadParams;
   arp: ArpParams;
   fx: FxParams;
@@ -190,6 +201,53 @@ const DRUMS: Record<DrumPalette, Pick<TimbreParams, "kick" | "snare" | "hihat">>
   },
 };
 
+// ─── Cymbals ────────────────────────────────────────────────────────────────
+// Same EXPANDABILITY CONTRACT as DRUMS. Each palette's open hat and crash reuse
+// its hat's oscillator bank, given a long ring (open hat) or a lower, wider band
+// and a long wash (crash).
+const CYMBALS: Record<DrumPalette, Pick<TimbreParams, "openHihat" | "crash">> = {
+  SynthwaveDrums: {
+    openHihat: {
+      metallicFreqsHz: [3742, 4835, 5917, 7264, 8476],
+      metallicLevel: 0.3,
+      metallicDecaySec: 0.3,
+      hipassHz: 7000,
+      noiseHipassHz: 8000,
+      noiseLevel: 0.25,
+      noiseDecaySec: 0.25,
+    },
+    crash: {
+      metallicFreqsHz: [3742, 4835, 5917, 7264, 8476],
+      metallicLevel: 0.25,
+      metallicDecaySec: 1.2,
+      hipassHz: 4500,
+      noiseHipassHz: 5000,
+      noiseLevel: 0.35,
+      noiseDecaySec: 1.0,
+    },
+  },
+  TR808: {
+    openHihat: {
+      metallicFreqsHz: [3600, 5200, 6800, 8100, 9500, 10500],
+      metallicLevel: 0.35,
+      metallicDecaySec: 0.25,
+      hipassHz: 8000,
+      noiseHipassHz: 9000,
+      noiseLevel: 0.15,
+      noiseDecaySec: 0.2,
+    },
+    crash: {
+      metallicFreqsHz: [3600, 5200, 6800, 8100, 9500, 10500],
+      metallicLevel: 0.3,
+      metallicDecaySec: 0.9,
+      hipassHz: 5000,
+      noiseHipassHz: 6000,
+      noiseLevel: 0.3,
+      noiseDecaySec: 0.8,
+    },
+  },
+};
+
 /** An FxProfile entry minus the derived renderTailSec (computed in deriveTimbre). */
 type FxBase = Omit<FxParams, "renderTailSec">;
 
@@ -201,6 +259,7 @@ const DRY_SEND: SendLevels = { dry: 1, delay: 0, reverb: 0, chorus: 0 };
 // profile added in Rust breaks tsc here until it is given a full routing spec.
 //
 // Chorus sends are pad/arp only (bass through chorus = low-end phase cancellation).
+// The open hat and crash take the closed hat's sends.
 // delayTimeSec: GatedReverb/WideChorus = dotted eighth = 0.75*60/bpm;
 //               DarkDelay = eighth = 0.5*60/bpm; Dry = 0.
 const FX: Record<FxProfile, (bpm: number) => FxBase> = {
@@ -217,6 +276,8 @@ const FX: Record<FxProfile, (bpm: number) => FxBase> = {
       kick: { dry: 1, delay: 0, reverb: 0.05, chorus: 0 },
       snare: { dry: 1, delay: 0.1, reverb: 0.45, chorus: 0 },
       hihat: { dry: 1, delay: 0.08, reverb: 0.12, chorus: 0 },
+      openHihat: { dry: 1, delay: 0.08, reverb: 0.12, chorus: 0 },
+      crash: { dry: 1, delay: 0.08, reverb: 0.12, chorus: 0 },
       bass: { dry: 1, delay: 0, reverb: 0.03, chorus: 0 },
       pad: { dry: 1, delay: 0.15, reverb: 0.3, chorus: 0 },
       arp: { dry: 1, delay: 0.2, reverb: 0.2, chorus: 0 },
@@ -239,6 +300,8 @@ const FX: Record<FxProfile, (bpm: number) => FxBase> = {
       kick: { dry: 1, delay: 0, reverb: 0.02, chorus: 0 },
       snare: { dry: 1, delay: 0.25, reverb: 0.15, chorus: 0 },
       hihat: { dry: 1, delay: 0.15, reverb: 0.05, chorus: 0 },
+      openHihat: { dry: 1, delay: 0.15, reverb: 0.05, chorus: 0 },
+      crash: { dry: 1, delay: 0.15, reverb: 0.05, chorus: 0 },
       bass: { dry: 1, delay: 0.08, reverb: 0, chorus: 0 },
       pad: { dry: 1, delay: 0.3, reverb: 0.15, chorus: 0 },
       arp: { dry: 1, delay: 0.35, reverb: 0.1, chorus: 0 },
@@ -261,6 +324,8 @@ const FX: Record<FxProfile, (bpm: number) => FxBase> = {
       kick: { dry: 1, delay: 0, reverb: 0.05, chorus: 0 },
       snare: { dry: 1, delay: 0.08, reverb: 0.2, chorus: 0 },
       hihat: { dry: 1, delay: 0.05, reverb: 0.1, chorus: 0 },
+      openHihat: { dry: 1, delay: 0.05, reverb: 0.1, chorus: 0 },
+      crash: { dry: 1, delay: 0.05, reverb: 0.1, chorus: 0 },
       bass: { dry: 1, delay: 0, reverb: 0.02, chorus: 0 },
       pad: { dry: 1, delay: 0.1, reverb: 0.25, chorus: 0.3 },
       arp: { dry: 1, delay: 0.12, reverb: 0.15, chorus: 0.3 },
@@ -283,6 +348,8 @@ const FX: Record<FxProfile, (bpm: number) => FxBase> = {
       kick: DRY_SEND,
       snare: DRY_SEND,
       hihat: DRY_SEND,
+      openHihat: DRY_SEND,
+      crash: DRY_SEND,
       bass: DRY_SEND,
       pad: DRY_SEND,
       arp: DRY_SEND,
@@ -330,7 +397,7 @@ function clampBpm(bpm: number): number {
  * Longest post-note ring a single dry voice sustains after its note, using the
  * ACTUAL palette params so the winning voice is never hardcoded.
  *   kick: subDecaySec + 0.05 osc-stop margin
- *   snare/hihat: their longest decay layer
+ *   snare/hihat/open hat/crash: their longest decay layer
  *   bass: 0.05 (envelope ends AT durSec; margin only)
  *   pad: 0.1 (osc-stop margin)
  *   arp: maxDecaySec
@@ -338,16 +405,16 @@ function clampBpm(bpm: number): number {
 function dryOverhangSec(
   kick: KickParams,
   snare: SnareParams,
-  hihat: HihatParams,
+  hats: HihatParams[],
   arp: ArpParams,
 ): number {
   const kickTail = kick.subDecaySec + 0.05;
   const snareTail = Math.max(snare.noiseDecaySec, snare.bodyDecaySec, snare.ringDecaySec);
-  const hihatTail = Math.max(hihat.metallicDecaySec, hihat.noiseDecaySec);
+  const hatTail = Math.max(...hats.map((h) => Math.max(h.metallicDecaySec, h.noiseDecaySec)));
   const bassTail = 0.05;
   const padTail = 0.1;
   const arpTail = arp.maxDecaySec;
-  return Math.max(kickTail, snareTail, hihatTail, bassTail, padTail, arpTail);
+  return Math.max(kickTail, snareTail, hatTail, bassTail, padTail, arpTail);
 }
 
 /** FX ring-out: the longer of the reverb tail (gate if present, else full IR)
@@ -365,21 +432,25 @@ function fxRingSec(fx: FxBase): number {
  * PURE — no logging, no side effects. Maps a (validated) ThemeSound + bpm to the
  * full concrete DSP parameter set. bpm is clamped to [40, 300] (non-finite/<=0 → 120).
  *
- * The returned kick/snare/hihat/pad/arp params are SHARED module-level tables,
+ * The returned drum/cymbal/pad/arp params are SHARED module-level tables,
  * not copies — treat them as read-only. Mutating them would corrupt every
  * subsequent call's output.
  */
 export function deriveTimbre(sound: ThemeSound, bpm: number): TimbreParams {
   const clamped = clampBpm(bpm);
   const { kick, snare, hihat } = DRUMS[sound.drum_palette];
+  const { openHihat, crash } = CYMBALS[sound.drum_palette];
   const pad = sound.pad_sustain ? PAD_SUSTAINED : PAD_RHYTHMIC;
   const arp = ARP;
   const fxBase = FX[sound.fx_profile](clamped);
-  const renderTailSec = dryOverhangSec(kick, snare, hihat, arp) + fxRingSec(fxBase) + 0.1;
+  const renderTailSec =
+    dryOverhangSec(kick, snare, [hihat, openHihat, crash], arp) + fxRingSec(fxBase) + 0.1;
   return {
     kick,
     snare,
     hihat,
+    openHihat,
+    crash,
     pad,
     arp,
     fx: { ...fxBase, renderTailSec },
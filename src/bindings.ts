@@ -121,6 +121,53 @@ async deleteCalibrationProfile(id: string) : Promise<Result<null, CommandError>>
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Grade a stored calibration profile: leave-one-out accuracy per class,
+ * which classes overlap, and which samples look like outliers
+ */
+async getCalibrationReport(id: string) : Promise<Result<CalibrationReport, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_calibration_report", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listNoiseProfiles() : Promise<Result<NoiseProfile[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_noise_profiles") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Measure a room-tone capture and store it as a noise profile
+ */
+async createNoiseProfile(input: CreateNoiseProfileInput) : Promise<Result<NoiseProfile, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_noise_profile", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getNoiseProfile(id: string) : Promise<Result<NoiseProfile | null, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_noise_profile", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteNoiseProfile(id: string) : Promise<Result<null, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_noise_profile", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
  * Detect onsets in audio data
  */
@@ -198,6 +245,18 @@ async exportMidiCommand(input: ExportMidiInput) : Promise<Result<number[], Comma
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Render an arrangement to 16-bit stereo WAV bytes with the native synth
+ * (no WebAudio), using the theme sound snapshotted on the arrangement.
+ */
+async renderWavCommand(input: RenderWavInput) : Promise<Result<number[], CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("render_wav_command", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
  * List all available themes with summaries
  */
@@ -320,7 +379,13 @@ phase_offset_ms?: number | null;
  * me" snaps off-template hits to the nearest template slot. Never deletes
  * events. Defaults to 0.8 (the UI slider lands in Task 4).
  */
-fidelity?: number }
+fidelity?: number; 
+/**
+ * Where the chords come from: the theme's fixed progression (default) or
+ * `follow_hum`, which infers the key and per-bar chords from the hummed
+ * pitch and explains them in `Arrangement::harmony`.
+ */
+harmony_mode?: HarmonyMode }
 /**
  * An arranged note with timing and MIDI parameters
  */
@@ -365,6 +430,18 @@ pad_lane: DrumLane | null;
  * Arpeggiator lane
  */
 arp_lane: DrumLane | null; 
+/**
+ * Melody lane (the hummed pitch of HumVoiced events, snapped to the
+ * theme's scale). `serde(default)` so arrangements saved before pitch
+ * tracking still load.
+ */
+melody_lane?: DrumLane | null; 
+/**
+ * Key and per-bar chords the bass, pads, arp and melody were voiced from,
+ * with the reason for each. Only set in `HarmonyMode::FollowHum`; `None`
+ * means the theme's fixed progression.
+ */
+harmony?: HarmonyAnalysis | null; 
 /**
  * Arrangement metadata
  */
@@ -416,11 +493,96 @@ velocity: number;
  * Duration in milliseconds
  */
 duration_ms: number }
+/**
+ * The chord chosen for one bar, and why
+ */
+export type BarHarmony = { 
+/**
+ * Bar index (0-indexed, anchored to the grid's phase offset)
+ */
+bar: number; 
+/**
+ * Chord by scale degree of the detected key
+ */
+chord: ChordType; 
+/**
+ * MIDI notes of the triad the pads voice
+ */
+chord_notes: number[]; 
+/**
+ * Share of the bar's hummed pitch that falls on chord tones, 0.0-1.0
+ * (0.0 when the bar has no hum)
+ */
+fit: number; 
+/**
+ * Human-readable reason for the choice
+ */
+reason: string }
 /**
  * Bass line patterns
  */
 export type BassPattern = "Root" | "RootFifth" | "OffbeatEighths" | "Walking"
 export type CalibrationProfile = { id: string; name: string; created_at: string; profile_json_path: string; notes: string | null }
+/**
+ * How well a [`CalibrationProfile`] teaches the classifier, so the
+ * calibration panel can say which class to re-teach and which samples to
+ * drop rather than only whether there are five of each
+ * ([`CalibrationProfile::is_sufficient`]).
+ * 
+ * Every figure comes from leave-one-out over the samples with MFCCs (legacy
+ * samples cannot adapt the model and are left out): each sample is
+ * classified by the live hybrid over the factory Gaussian MAP-adapted from
+ * all the *other* samples, as a new take of that sound would be.
+ */
+export type CalibrationReport = { 
+/**
+ * [`CalibrationProfile::is_sufficient`].
+ */
+sufficient: boolean; 
+/**
+ * Share of samples classified as their label; `None` without samples.
+ */
+accuracy: number | null; 
+/**
+ * One entry per taught class, in [`EventClass::ALL`] order.
+ */
+classes: ClassQuality[]; 
+/**
+ * One entry per pair of taught classes with samples to compare.
+ */
+pairs: ClassSeparability[]; 
+/**
+ * One entry per sample with MFCCs, grouped by class in
+ * [`EventClass::ALL`] order.
+ */
+samples: SampleQuality[] }
+/**
+ * How the offline pipeline reduces multi-channel audio before analysis
+ * (see [`crate::OnsetConfig::channels`]). Mono input is analysed as-is
+ * whatever the strategy.
+ */
+export type ChannelStrategy = 
+/**
+ * Average all channels (the historical mixdown).
+ */
+"average" | 
+/**
+ * Analyse one channel (0-based; clamped to the last channel).
+ */
+{ channel: number } | 
+/**
+ * Analyse the channel with the most energy over the whole recording.
+ */
+"max_energy" | 
+/**
+ * Analyse mid `(L+R)/2` and side `(L-R)/2` as two lanes and merge the
+ * events — side keeps what out-of-phase mics cancel in the mid.
+ */
+"mid_side" | 
+/**
+ * Analyse every channel as its own lane and merge the events.
+ */
+"per_channel"
 /**
  * Chord progression structure
  */
@@ -429,6 +591,30 @@ export type ChordProgression = { chords: ChordType[]; bars_per_chord: number }
  * Chord types by scale degree
  */
 export type ChordType = "I" | "II" | "III" | "IV" | "V" | "VI" | "VII" | "Im" | "IIm" | "IIIm" | "IVm" | "Vm" | "VIm" | "VIIm"
+/**
+ * Leave-one-out quality of one taught class.
+ */
+export type ClassQuality = { class: EventClass; 
+/**
+ * Samples taught, legacy ones included.
+ */
+samples: number; 
+/**
+ * Samples with MFCCs, the ones evaluated.
+ */
+evaluated: number; 
+/**
+ * Share of the evaluated samples classified as this class.
+ */
+accuracy: number | null; 
+/**
+ * The class its samples are most often mistaken for.
+ */
+confused_with: EventClass | null; 
+/**
+ * Accuracy is below [`RETEACH_ACCURACY`].
+ */
+reteach: boolean }
 /**
  * Per-class classification score for a single event.
  * 
@@ -447,17 +633,112 @@ class: EventClass;
  * Confidence score for `class` in [0.0, 1.0].
  */
 score: number }
+/**
+ * How well the classifier tells two taught classes apart.
+ */
+export type ClassSeparability = { a: EventClass; b: EventClass; 
+/**
+ * Mean over both classes' samples of the sample's own-class score as a
+ * share of the two classes' scores: 1.0 always tells them apart, 0.5
+ * is chance (the same sound taught under two labels).
+ */
+separability: number; 
+/**
+ * Samples of either class classified as the other.
+ */
+confusions: number; 
+/**
+ * Separability is below [`OVERLAP_SEPARABILITY`].
+ */
+overlapping: boolean }
 export type CommandError = { message: string }
+/**
+ * Options for the conditioning chain (see [`crate::OnsetConfig::conditioning`]
+ * and [`crate::StreamingConfig::conditioning`]). The default is the chain
+ * tuned for laptop mics; each stage can be switched off on its own.
+ */
+export type ConditioningConfig = { 
+/**
+ * Remove DC offset (one-pole high-pass at 10 Hz).
+ */
+dc_block: boolean; 
+/**
+ * Cutoff (Hz) of a second-order Butterworth high-pass for rumble and
+ * handling noise; `None` disables it.
+ */
+highpass_hz: number | null; 
+/**
+ * Spectral-subtraction noise reduction against a noise floor learned
+ * from the quiet stretches of the signal; `None` disables it.
+ */
+noise_reduction: NoiseReduction | null; 
+/**
+ * Level normalization; `None` leaves the level alone.
+ */
+normalize: Normalization | null }
 export type CreateArtifactInput = { run_id: string; kind: string; filename: string; data: number[] }
 export type CreateCalibrationProfileInput = { name: string; profile_data: number[]; notes: string | null }
+export type CreateNoiseProfileInput = { name: string; 
+/**
+ * A few seconds of room tone: the WAV bytes `stop_recording` returns, or
+ * any supported audio file's bytes.
+ */
+audio_data: number[]; notes: string | null }
 export type CreateProjectInput = { name: string; input_data: number[] }
 export type CreateRunInput = { project_id: string; pipeline_version: string; theme: string; bpm: number; swing: number; quantize_strength: number; b_emphasis: number; 
 /**
  * Tempo phase offset (ms). Optional for backward compatibility; defaults
  * to 0 so callers that predate phase persistence keep working.
  */
-phase_offset_ms?: number | null }
-export type DetectEventsInput = { file_path: string; run_id: string | null; use_calibration: boolean; calibration_profile_id: string | null }
+phase_offset_ms?: number | null; 
+/**
+ * Channel strategy the run's events are detected with. Defaults to
+ * averaging the channels, the behaviour before strategies existed.
+ */
+channel_strategy?: ChannelStrategy | null; 
+/**
+ * Pre-processing chain the run's events are detected with. Defaults to
+ * none (the raw signal).
+ */
+conditioning?: ConditioningConfig | null }
+export type DetectEventsInput = { file_path: string; run_id: string | null; use_calibration: boolean; calibration_profile_id: string | null; 
+/**
+ * Resample to the classifier's training rate (44.1 kHz) before detection,
+ * so 48/96 kHz recordings see the statistics the model was fitted on.
+ */
+normalize_sample_rate: boolean; 
+/**
+ * How multi-channel recordings are reduced before detection (average,
+ * one channel, loudest channel, mid/side, or every channel merged).
+ * Defaults to averaging.
+ */
+channel_strategy?: ChannelStrategy | null; 
+/**
+ * DC blocking, high-pass, noise reduction and normalization applied
+ * before detection and feature extraction. Defaults to off.
+ */
+conditioning?: ConditioningConfig | null; 
+/**
+ * Room-tone profile (from `create_noise_profile`) that raises the onset
+ * gate above the room's noise and seeds the conditioning denoiser.
+ */
+noise_profile_id?: string | null; 
+/**
+ * Registered classifier to run (`hybrid`, `gaussian`, `heuristic`, `knn`).
+ * Defaults to the shipping hybrid.
+ */
+classifier?: string | null; 
+/**
+ * Onset detection function (spectral flux, SuperFlux, HFC,
+ * complex-domain or multi-band). Defaults to spectral flux.
+ */
+onset_function?: OnsetFunction | null; 
+/**
+ * Multi-label mode: emit one event per sound layered on an onset (a
+ * kick with a hat on top becomes a kick and a hat at the same time).
+ * Defaults to off (one event per onset).
+ */
+multi_label?: MultiLabelConfig | null }
 export type DetectOnsetsInput = { audio_data: number[]; window_size: number | null; hop_size: number | null; threshold_factor: number | null }
 /**
  * A drum/instrument lane containing arranged notes
@@ -494,10 +775,17 @@ id: string;
  */
 timestamp_ms: number; 
 /**
- * Duration of event in milliseconds
- * Calculated from onset to next onset or energy decay
+ * Duration of event in milliseconds: how long the sound lasted,
+ * measured from its energy decay (and, for a hum, its voicing). Never
+ * longer than `gap_ms`.
  */
 duration_ms: number; 
+/**
+ * Milliseconds from this onset to the next one (or to the end of the
+ * audio for the last event). `0.0` for older persisted rows
+ * (`serde(default)`), which stored the gap as `duration_ms`.
+ */
+gap_ms?: number; 
 /**
  * Classified event type
  */
@@ -518,7 +806,13 @@ features: EventFeatures;
  * UI can render real score bars and name the actual runner-up. Defaults to
  * empty for backward compatibility with older persisted rows (`serde(default)`).
  */
-all_scores?: ClassScore[] }
+all_scores?: ClassScore[]; 
+/**
+ * Hummed pitch over the event, voiced frames only. Tracked for
+ * `HumVoiced` events by the offline pipelines; empty for every other
+ * class, for live events, and for older persisted rows (`serde(default)`).
+ */
+pitch_contour?: PitchPoint[] }
 /**
  * Classification of detected beatbox events
  * Maps beatbox sounds to musical instruments/synthesis targets
@@ -547,13 +841,56 @@ export type EventClass =
  * Triggers: pads, bass lines, melodic elements
  * Characteristics: Sustained energy, lower ZCR, periodic/harmonic content
  */
-"HumVoiced"
-export type EventData = { id: string; timestamp_ms: number; duration_ms: number; class: string; confidence: number; features: EventFeatures; 
+"HumVoiced" | 
+/**
+ * Drawn-out TSSS sounds - an S/TS left to ring
+ * Triggers: open hi-hat
+ * Characteristics: Hi-hat spectrum with a slow decay (low crest factor)
+ */
+"OpenHihat" | 
+/**
+ * Short, thin T/K sounds - a tongue tick or an unvoiced, breathless K
+ * Triggers: rimshot / side stick, ghost snare
+ * Characteristics: Click spectrum shifted up, with little low-band body
+ */
+"Rimshot" | 
+/**
+ * KSSH / PSSH sounds - a plosive opening into a long hiss
+ * Triggers: crash cymbal
+ * Characteristics: Broadband noise (high ZCR with real low/mid energy),
+ * sustained
+ */
+"Crash" | 
+/**
+ * Breaths, coughs, laughs, mic bumps and clicks - onsets that are not
+ * part of the performance
+ * Triggers: nothing; kept in results (flagged) but left out of
+ * quantization and arrangement by default
+ * Characteristics: Far from every trained class, or caught by the
+ * heuristic's rejection rules (subsonic thumps, digital clicks, dark
+ * sustained breath noise)
+ */
+"Noise"
+export type EventData = { id: string; timestamp_ms: number; 
+/**
+ * Measured sound length (energy decay / voicing), at most `gap_ms`.
+ */
+duration_ms: number; 
+/**
+ * Time to the next onset. `serde(default)` for callers that predate
+ * measured durations and only sent the gap (as `duration_ms`).
+ */
+gap_ms?: number; class: string; confidence: number; features: EventFeatures; 
 /**
  * Per-class classifier scores. `serde(default)` so older callers/rows that
  * predate score threading still deserialize (empty vec).
  */
-all_scores?: ClassScore[] }
+all_scores?: ClassScore[]; 
+/**
+ * Hummed pitch contour (`HumVoiced` events only; empty otherwise and for
+ * callers that predate pitch tracking).
+ */
+pitch_contour?: PitchPoint[] }
 /**
  * Complete decision information for a single event
  * showing how it moved through the pipeline
@@ -634,8 +971,159 @@ beat: number;
  * Subdivision within beat (0-indexed)
  */
 subdivision: number }
+/**
+ * Explains the harmony an arrangement was built on
+ */
+export type HarmonyAnalysis = { mode: HarmonyMode; 
+/**
+ * Root of the detected key (MIDI note, near the theme's root)
+ */
+key_root: number; 
+/**
+ * Key name, e.g. "D NaturalMinor"
+ */
+key_name: string; 
+/**
+ * The theme's scale family, transposed to the detected root
+ */
+scale_family: ScaleFamily; 
+/**
+ * Share of all hummed pitch that falls inside the detected scale, 0.0-1.0
+ */
+key_fit: number; 
+/**
+ * Human-readable reason for the key
+ */
+reason: string; 
+/**
+ * One entry per arrangement bar
+ */
+bars: BarHarmony[] }
+/**
+ * Where the arrangement's harmony comes from
+ */
+export type HarmonyMode = 
+/**
+ * The theme's root and fixed chord progression, cycled bar by bar
+ */
+"theme" | 
+/**
+ * Key and per-bar chords inferred from the pitch of hummed events.
+ * Bars without a hum keep the theme progression's chord (in the new key).
+ */
+"follow_hum"
+/**
+ * A stored room-tone capture's noise profile (the measured
+ * `beatrice_dsp::NoiseProfile` JSON lives at `profile_json_path`).
+ */
+/**
+ * Multi-label settings (see [`crate::OnsetConfig::multi_label`]).
+ */
+export type MultiLabelConfig = { 
+/**
+ * Smallest score a class from another family needs to be emitted beside
+ * the winner. Tuned for posteriors that sum to one (the Gaussian, hybrid
+ * and kNN classifiers); a hum's scores come from the heuristic and are
+ * not used.
+ */
+min_score: number; 
+/**
+ * Share of the window's energy above 4 kHz that puts a hi-hat on top of
+ * a kick or hum.
+ */
+min_high_band: number; 
+/**
+ * Share of the window's energy below 500 Hz that puts a kick under a
+ * hi-hat or snare.
+ */
+min_low_band: number }
+export type NoiseProfile = { id: string; name: string; created_at: string; profile_json_path: string; 
+/**
+ * RMS level of the room tone in dBFS.
+ */
+noise_floor_dbfs: number; notes: string | null }
+/**
+ * Spectral-subtraction settings.
+ */
+export type NoiseReduction = { 
+/**
+ * Multiple of the noise floor's power subtracted from each bin. Values
+ * above 1 also clear the noise's random peaks.
+ */
+over_subtraction: number; 
+/**
+ * Smallest gain a bin is left with (0.1 = -20 dB): a floor instead of
+ * zero keeps the residual noise smooth rather than chirpy.
+ */
+spectral_floor: number; 
+/**
+ * Noise gate: mute the output while its peak envelope stays below the
+ * noise floor's RMS plus this many dB (10 dB clears the noise's own
+ * peaks); `None` disables the gate. Subtraction alone leaves a residual
+ * that decides the zero-crossing rate of every decaying tail — enough
+ * to turn a kick into a hi-hat.
+ */
+gate_db: number | null }
+/**
+ * What level normalization aims for.
+ */
+export type Normalization = 
+/**
+ * Scale the sample peak to `target_dbfs`.
+ */
+{ peak: { target_dbfs: number } } | 
+/**
+ * Scale the gated integrated loudness (ITU-R BS.1770) to `target_lufs`.
+ */
+{ loudness: { target_lufs: number } }
+export type OnsetFunction = 
+/**
+ * Rectified spectral flux on linear bins plus the RMS energy detector
+ * for low-frequency hits (the historical detector).
+ */
+"spectral_flux" | 
+/**
+ * SuperFlux: rectified flux of a 24-band-per-octave log-magnitude
+ * spectrogram against a frequency-max-filtered earlier frame, which
+ * suppresses vibrato and pitch glides.
+ */
+"super_flux" | 
+/**
+ * Rise in high-frequency content (bin-index-weighted magnitude), plus the
+ * energy detector: sharp on clicks and hats.
+ */
+"hfc" | 
+/**
+ * Rectified complex-domain deviation from a constant-magnitude,
+ * steady-phase prediction, plus the energy detector: also fires on soft
+ * tonal onsets that barely change the magnitude.
+ */
+"complex_domain" | 
+/**
+ * Spectral flux summed over three bands (kick, snare, hat regions), each
+ * normalised by its width so low-frequency hits weigh as much as
+ * high-frequency ones without a separate energy detector.
+ */
+"multi_band"
 export type OnsetData = { timestamp_ms: number; strength: number }
 export type OnsetDetectionResult = { onsets: OnsetData[]; total_count: number }
+/**
+ * One voiced frame of an event's pitch contour
+ * (see [`crate::features::track_pitch`]).
+ */
+export type PitchPoint = { 
+/**
+ * Frame centre in milliseconds from the event's onset
+ */
+time_ms: number; 
+/**
+ * Fundamental frequency (Hz)
+ */
+f0_hz: number; 
+/**
+ * Probability the frame is voiced at this pitch [0.0, 1.0]
+ */
+confidence: number }
 export type Project = { id: string; created_at: string; name: string; input_path: string; input_sha256: string; duration_ms: number }
 export type ProjectSummary = { id: string; name: string; created_at: string; duration_ms: number; run_count: number }
 export type QuantizeEventsInput = { events: EventData[]; bpm: number; time_signature: string; division: string; feel: string; swing_amount: number; bar_count: number; quantize_strength: number; lookahead_ms: number; 
@@ -643,7 +1131,12 @@ export type QuantizeEventsInput = { events: EventData[]; bpm: number; time_signa
  * Grid phase offset (ms) from tempo estimation. Anchors the quantization grid
  * to the performer's downbeat. Defaults to 0.0 (t=0 anchor) for back-compat.
  */
-phase_offset_ms?: number | null }
+phase_offset_ms?: number | null; 
+/**
+ * Quantize events rejected as noise too. Defaults to false: they stay in
+ * the detected events but are left off the grid.
+ */
+include_rejected?: boolean }
 /**
  * A quantized event with both original and quantized timing
  */
@@ -668,14 +1161,50 @@ snap_delta_ms: number;
  * Position on the musical grid
  */
 grid_position: GridPosition }
+export type RenderWavInput = { arrangement: Arrangement; 
+/**
+ * Output sample rate in Hz. Defaults to 44100.
+ */
+sample_rate?: number | null }
 export type Run = { id: string; project_id: string; created_at: string; pipeline_version: string; theme: string; bpm: number; swing: number; quantize_strength: number; b_emphasis: number; 
 /**
  * Tempo phase offset (ms) captured at creation so replaying this run
  * anchors quantization/chords exactly as the user first heard it.
  */
-phase_offset_ms: number; status: RunStatus }
+phase_offset_ms: number; 
+/**
+ * How multi-channel input was reduced for event detection, so a replay
+ * analyses the same signal the user first heard arranged.
+ */
+channel_strategy: ChannelStrategy; 
+/**
+ * Pre-processing applied before event detection (`None` = the raw
+ * signal), recorded for the same reason.
+ */
+conditioning: ConditioningConfig | null; status: RunStatus }
 export type RunStatus = "pending" | "processing" | "complete" | "failed"
 export type RunWithArtifacts = { run: Run; artifacts: Artifact[] }
+/**
+ * Leave-one-out verdict on one sample.
+ */
+export type SampleQuality = { class: EventClass; 
+/**
+ * Index into the profile's samples of `class`.
+ */
+index: number; 
+/**
+ * What the classifier calls it.
+ */
+predicted: EventClass; 
+/**
+ * Distance from the rest of its class, over the class's median such
+ * distance (0 for classes under three samples).
+ */
+outlier_score: number; 
+/**
+ * The outlier score is above [`OUTLIER_SCORE`].
+ */
+drop: boolean }
 export type SaveEventDecisionsInput = { run_id: string; events: EventData[]; quantized_events: QuantizedEvent[] | null; arrangement: Arrangement | null }
 /**
  * Musical scale families
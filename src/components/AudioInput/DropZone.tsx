@@ -8,6 +8,10 @@ interface DropZoneProps {
   onError: (error: string) => void;
 }
 
+// Containers the backend's ingest_audio decodes (it sniffs the bytes; the
+// extension check just keeps obviously wrong files out of the picker).
+const AUDIO_EXTENSIONS = ['.wav', '.flac', '.mp3', '.ogg', '.opus', '.m4a', '.webm'];
+
 interface FileInfo {
   name: string;
   size: number;
@@ -22,8 +26,9 @@ export function DropZone({ onProjectCreated, onError }: DropZoneProps) {
 
   const validateAndProcessFile = async (file: File) => {
     // Validate file type
-    if (!file.name.toLowerCase().endsWith('.wav')) {
-      onError('Only WAV files are supported');
+    const lowerName = file.name.toLowerCase();
+    if (!AUDIO_EXTENSIONS.some((ext) => lowerName.endsWith(ext))) {
+      onError('Supported formats: WAV, FLAC, MP3, OGG/Opus, M4A, WebM');
       return;
     }
 
@@ -49,7 +54,7 @@ export function DropZone({ onProjectCreated, onError }: DropZoneProps) {
       // Create project via Tauri command
       const project = unwrap(
         await commands.createProject({
-          name: file.name.replace('.wav', ''),
+          name: file.name.replace(/\.[^.]+$/, ''),
           input_data: Array.from(uint8Array),
         })
       );
@@ -135,7 +140,7 @@ export function DropZone({ onProjectCreated, onError }: DropZoneProps) {
       }}
       role="button"
       tabIndex={isProcessing ? -1 : 0}
-      aria-label="Upload a WAV file"
+      aria-label="Upload an audio file"
       aria-disabled={isProcessing}
       animate={{
         scale: isDragOver ? 1.02 : 1,
@@ -165,7 +170,7 @@ export function DropZone({ onProjectCreated, onError }: DropZoneProps) {
       <input
         ref={fileInputRef}
         type="file"
-        accept=".wav,audio/wav"
+        accept={[...AUDIO_EXTENSIONS, 'audio/*'].join(',')}
         onChange={handleFileInputChange}
         style={{ display: 'none' }}
       />
@@ -240,7 +245,7 @@ export function DropZone({ onProjectCreated, onError }: DropZoneProps) {
               textAlign: 'center',
             }}
           >
-            DROP WAV FILE HERE
+            DROP AUDIO FILE HERE
           </div>
           <div style={{ fontSize: '16px', color: '#666' }}>
             or click to browse
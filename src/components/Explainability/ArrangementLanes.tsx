@@ -22,6 +22,7 @@ export function ArrangementLanes({ arrangement, currentTime, isPlaying }: Arrang
       arrangement.bass_lane,
       arrangement.pad_lane,
       arrangement.arp_lane,
+      arrangement.melody_lane,
     ].filter(Boolean) as DrumLane[];
     return allLanes;
   }, [arrangement]);
@@ -12,7 +12,16 @@ export function ModelInspector({ features, className, confidence }: ModelInspect
   // Simulate class probabilities (in reality, this would come from the model)
   // For demonstration, we'll show the actual class with its confidence,
   // and distribute the remaining probability among other classes
-  const allClasses: EventClass[] = ['BilabialPlosive', 'HihatNoise', 'Click', 'HumVoiced'];
+  const allClasses: EventClass[] = [
+    'BilabialPlosive',
+    'HihatNoise',
+    'Click',
+    'HumVoiced',
+    'OpenHihat',
+    'Rimshot',
+    'Crash',
+    'Noise',
+  ];
   const otherConfidence = (1 - confidence) / (allClasses.length - 1);
 
   const classProbabilities = allClasses.map(cls => ({
@@ -51,6 +51,12 @@ interface CalibrationPanelProps {
    * so freshly taught samples don't append onto a re-seeded profile (Finding 2).
    */
   onResetCalibration: () => void;
+  /**
+   * Told when teaching starts and stops. While teaching, the live detector
+   * must emit full-window verdicts only: a provisional event's features come
+   * from the onset's first 50ms, not the window the model is trained on.
+   */
+  onTeaching: (teaching: boolean) => void;
   /** Close the panel. */
   onClose: () => void;
 }
@@ -62,6 +68,7 @@ export function CalibrationPanel({
   onSample,
   onToggle,
   onResetCalibration,
+  onTeaching,
   onClose,
 }: CalibrationPanelProps) {
   const [state, dispatch] = useReducer(calibrationReducer, INITIAL_CALIBRATION_STATE);
@@ -100,6 +107,14 @@ export function CalibrationPanel({
     onToggle(true);
   }, [state.phase, saved, onToggle]);
 
+  // Full-window events only while teaching; closing the panel mid-teach
+  // restores provisional emission.
+  const teaching = state.phase === "teaching";
+  useEffect(() => {
+    onTeaching(teaching);
+    return () => onTeaching(false);
+  }, [teaching, onTeaching]);
+
   // Returning session: when the hook reports a SUFFICIENT re-seeded profile,
   // enter `restored` so the toggle is live immediately (Finding 1). The reducer
   // ignores RESTORE unless idle, so this can't clobber a teach already underway.
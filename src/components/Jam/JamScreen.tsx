@@ -7,7 +7,7 @@
 // the mic are recorded in parallel and handed to the EXISTING offline pipeline
 // exactly like an upload — where the real arrangement is produced.
 
-import { useEffect, useRef, useState } from "react";
+import { useCallback, useEffect, useRef, useState } from "react";
 import { motion, AnimatePresence } from "framer-motion";
 import { commands, unwrap } from "../../types/ipc";
 import { EVENT_CLASS_COLORS, EVENT_CLASS_NAMES } from "../../types/explainability";
@@ -29,6 +29,7 @@ export function JamScreen({ onProjectCreated, onError, onExit }: JamScreenProps)
     error,
     liveEvents,
     eventCount,
+    tempo,
     level,
     analyser,
     calibrationRestored,
@@ -37,10 +38,13 @@ export function JamScreen({ onProjectCreated, onError, onExit }: JamScreenProps)
     capture,
     addCalibrationSample,
     setCalibrationEnabled,
+    setProvisional,
     resetCalibration,
   } = useJamSession();
   const [isCapturing, setIsCapturing] = useState(false);
   const [showCalibration, setShowCalibration] = useState(false);
+  // Teaching samples need full-window features: no provisional events then.
+  const onTeaching = useCallback((teaching: boolean) => setProvisional(!teaching), [setProvisional]);
   // The most recent live event drives the CalibrationPanel's sample capture.
   const latestEvent = liveEvents.length > 0 ? liveEvents[liveEvents.length - 1] : null;
   const canvasRef = useRef<HTMLCanvasElement>(null);
@@ -244,6 +248,12 @@ export function JamScreen({ onProjectCreated, onError, onExit }: JamScreenProps)
       {/* Event counter (drives the e2e assertion) */}
       <div style={{ fontFamily: "monospace", fontSize: 14, color: "#aaa" }}>
         events: <span data-testid="jam-event-count">{eventCount}</span>
+        {/* Live tempo from the detector's beat tracker, once it has locked. */}
+        {tempo && tempo.conf > 0 && (
+          <span style={{ marginLeft: 16, opacity: 0.4 + 0.6 * tempo.conf }}>
+            bpm: <span data-testid="jam-bpm">{Math.round(tempo.bpm)}</span>
+          </span>
+        )}
       </div>
 
       {/* Few-shot calibration panel (Task 5). Opens over the flash tiles; the
@@ -256,6 +266,7 @@ export function JamScreen({ onProjectCreated, onError, onExit }: JamScreenProps)
           onSample={addCalibrationSample}
           onToggle={setCalibrationEnabled}
           onResetCalibration={resetCalibration}
+          onTeaching={onTeaching}
           onClose={() => setShowCalibration(false)}
         />
       )}
@@ -22,7 +22,10 @@ import { isTauriAvailable } from "../utils/tauri-mock";
 
 /** A labeled calibration sample as accumulated on the main thread. */
 export interface CalibrationSampleInput {
-  /** EventClass id (0=kick, 1=hihat, 2=snare/click, 3=hum). */
+  /**
+   * EventClass id (0=kick, 1=hihat, 2=snare/click, 3=hum, 4=open hat,
+   * 5=rimshot, 6=crash, 7=noise).
+   */
   classId: number;
   /**
    * Feature vector in worklet-event order:
@@ -33,16 +36,29 @@ export interface CalibrationSampleInput {
 }
 
 /** classId -> Rust EventClass variant name (index === classId). */
-const CLASS_NAMES = ["BilabialPlosive", "HihatNoise", "Click", "HumVoiced"] as const;
+const CLASS_NAMES = [
+  "BilabialPlosive",
+  "HihatNoise",
+  "Click",
+  "HumVoiced",
+  "OpenHihat",
+  "Rimshot",
+  "Crash",
+  "Noise",
+] as const;
 
 /**
  * Minimum samples PER CLASS for a profile to classify with kNN. Mirrors the
- * Rust `CalibrationProfile::is_sufficient` (≥5 for all 4 classes) and the
+ * Rust `CalibrationProfile::is_sufficient` (≥5 for all 4 core classes) and the
  * `SAMPLES_PER_CLASS` the teach flow collects. Kept here so the frontend can
  * decide whether a re-seeded profile is immediately usable without a wasm call.
  */
 export const MIN_SAMPLES_PER_CLASS = 5;
-/** The four classIds a sufficient profile must cover (0=kick..3=hum). */
+/**
+ * The four core classIds a sufficient profile must cover (0=kick..3=hum).
+ * Open hat / rimshot / crash samples (4..6) are optional refinements, and
+ * Noise samples (7) teach sounds to ignore (breaths, coughs, laughs).
+ */
 const REQUIRED_CLASS_IDS = [0, 1, 2, 3] as const;
 
 /**
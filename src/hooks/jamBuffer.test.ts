@@ -48,6 +48,18 @@ describe("JamBuffer", () => {
     });
   });
 
+  it("revise() replaces the verdict on an onset in place", () => {
+    const b = new JamBuffer(4000);
+    b.push({ t_ms: 100, classId: 1, conf: 0.6 });
+    b.push({ t_ms: 350, classId: 0, conf: 0.9 });
+    b.revise({ t_ms: 100, classId: 5, conf: 0.8 });
+    b.revise({ t_ms: 999, classId: 2, conf: 0.8 }); // unknown onset: dropped
+    expect(b.events()).toEqual([
+      { t_ms: 100, classId: 5, conf: 0.8 },
+      { t_ms: 350, classId: 0, conf: 0.9 },
+    ]);
+  });
+
   it("eviction is relative to the newest event, not wall clock", () => {
     const b = new JamBuffer(1000);
     b.push({ t_ms: 1000, classId: 0, conf: 0.9 });
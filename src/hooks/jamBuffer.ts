@@ -24,7 +24,8 @@ export interface JamEvent {
 
 /**
  * classId -> EventClass string. Mirrors the WASM detector's class_id mapping
- * (crates/beatrice-dsp: 0=kick, 1=hihat, 2=snare/click, 3=hum). Out-of-range
+ * (crates/beatrice-dsp: 0=kick, 1=hihat, 2=snare/click, 3=hum, 4=open hat,
+ * 5=rimshot, 6=crash, 7=rejected noise). Out-of-range
  * ids fall back to BilabialPlosive so a garbled event never crashes the UI.
  */
 export const JAM_CLASS_TO_EVENT_CLASS: Record<number, EventClass> = {
@@ -32,6 +33,10 @@ export const JAM_CLASS_TO_EVENT_CLASS: Record<number, EventClass> = {
   1: "HihatNoise",
   2: "Click",
   3: "HumVoiced",
+  4: "OpenHihat",
+  5: "Rimshot",
+  6: "Crash",
+  7: "Noise",
 };
 
 // Per-class default features so the offline pipeline is never fed a NaN. These
@@ -43,6 +48,10 @@ const DEFAULT_FEATURES: Record<EventClass, EventData["features"]> = {
   HihatNoise: { spectral_centroid: 4200, zcr: 0.45, low_band_energy: 0.05, mid_band_energy: 0.25, high_band_energy: 0.7, peak_amplitude: 0.6 },
   Click: { spectral_centroid: 1800, zcr: 0.3, low_band_energy: 0.2, mid_band_energy: 0.6, high_band_energy: 0.2, peak_amplitude: 0.7 },
   HumVoiced: { spectral_centroid: 600, zcr: 0.05, low_band_energy: 0.3, mid_band_energy: 0.45, high_band_energy: 0.25, peak_amplitude: 0.6 },
+  OpenHihat: { spectral_centroid: 4000, zcr: 0.45, low_band_energy: 0.05, mid_band_energy: 0.25, high_band_energy: 0.7, peak_amplitude: 0.6 },
+  Rimshot: { spectral_centroid: 2800, zcr: 0.3, low_band_energy: 0.05, mid_band_energy: 0.55, high_band_energy: 0.4, peak_amplitude: 0.6 },
+  Crash: { spectral_centroid: 3000, zcr: 0.4, low_band_energy: 0.2, mid_band_energy: 0.3, high_band_energy: 0.5, peak_amplitude: 0.7 },
+  Noise: { spectral_centroid: 1200, zcr: 0.3, low_band_energy: 0.4, mid_band_energy: 0.5, high_band_energy: 0.1, peak_amplitude: 0.2 },
 };
 
 /**
@@ -65,6 +74,16 @@ export class JamBuffer {
     this.buf = this.buf.filter((x) => x.t_ms > cutoff);
   }
 
+  /**
+   * Replace the event at `e.t_ms` with a revised verdict on the same onset
+   * (the worklet's stage-2 "event"). A revision for an evicted or unknown
+   * onset is dropped.
+   */
+  revise(e: JamEvent): void {
+    const i = this.buf.findIndex((x) => x.t_ms === e.t_ms);
+    if (i >= 0) this.buf[i] = e;
+  }
+
   /** The raw live events currently inside the window (oldest first). */
   events(): JamEvent[] {
     return [...this.buf];
@@ -12,7 +12,7 @@
 // derives Intro/Build/Drop/Outro from duration/4, so a variable FX tail must NOT
 // leak into it.
 //
-// The themed FX tail (timbre.fx.renderTailSec — up to ~3s for DarkDelay) is kept
+// The themed FX tail (timbre.fx.renderTailSec — up to ~4s at slow tempos) is kept
 // AUDIBLE but out of the exposed duration: the delay/reverb keeps ringing after
 // the last note, so we hold teardown (AudioContext close + STOP button) until
 // `musical duration + tail` has elapsed, while clamping the displayed time to the
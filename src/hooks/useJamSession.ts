@@ -6,6 +6,9 @@
 //   1. flash it visually (append to `liveEvents`),
 //   2. push it into the rolling JamBuffer (UI/inspection history), and
 //   3. bump `eventCount`.
+// The worklet runs two-stage emission: an event first arrives with a
+// provisional class (~100ms sooner), and a "revised" event with the same tMs
+// replaces it in place if the full classification window disagrees.
 // There are NO scheduleKick/Snare/... calls — the user's mouth does not drive
 // real-time synthesis (that path missed the acoustic budget).
 //
@@ -25,8 +28,11 @@ import { JamBuffer } from "./jamBuffer";
 import { negotiateRecorderMimeType } from "./recorderMime";
 import { loadCalibrationSamples, isCalibrationSufficient } from "./calibrationStore";
 
-/** EventClass id emitted by the WASM detector (0=kick,1=hihat,2=snare/click,3=hum). */
-export type JamClassId = 0 | 1 | 2 | 3;
+/**
+ * EventClass id emitted by the WASM detector (0=kick,1=hihat,2=snare/click,3=hum,
+ * 4=open hat,5=rimshot,6=crash).
+ */
+export type JamClassId = 0 | 1 | 2 | 3 | 4 | 5 | 6;
 
 /** A live detector event surfaced to the UI for flash tiles. */
 export interface JamLiveEvent {
@@ -47,6 +53,22 @@ export interface JamLiveEvent {
   features: number[];
 }
 
+/**
+ * The live detector's beat tracker, as of its latest event. Lock a metronome
+ * to `lastBeatMs + n * 60000 / bpm`, and quantize a live event by snapping its
+ * `tMs` to that grid. Times share the events' stream-start clock.
+ */
+export interface JamTempo {
+  /** estimated beats per minute (120 until the tracker locks) */
+  bpm: number;
+  /** fraction of the current beat elapsed when this was posted [0,1) */
+  phase: number;
+  /** tracker confidence [0,1]; 0 until it has locked */
+  conf: number;
+  /** most recent beat, ms from stream start */
+  lastBeatMs: number;
+}
+
 export interface JamSession {
   /** true between a successful start() and stop()/capture() teardown */
   isRunning: boolean;
@@ -56,6 +78,8 @@ export interface JamSession {
   liveEvents: JamLiveEvent[];
   /** cumulative count of events since start() (never trimmed) */
   eventCount: number;
+  /** live tempo and beat phase; null until the first event of the session */
+  tempo: JamTempo | null;
   /** smoothed input RMS level [0,1] for the meter */
   level: number;
   /** the live AnalyserNode, for the waveform canvas (null until ready) */
@@ -81,6 +105,12 @@ export interface JamSession {
   addCalibrationSample: (classId: JamClassId, features: number[]) => void;
   /** Flip the FACTORY/YOURS A/B toggle on the live detector. */
   setCalibrationEnabled: (enabled: boolean) => void;
+  /**
+   * Switch provisional (two-stage) emission on the live detector. On by
+   * default; the calibration panel turns it off while teaching so every
+   * sample carries full-window features.
+   */
+  setProvisional: (enabled: boolean) => void;
   /**
    * Drop the live detector's calibration profile (kNN reverts to heuristic).
    * Called when a re-teach begins so freshly taught samples don't append onto a
@@ -102,6 +132,7 @@ export function useJamSession(): JamSession {
   const [error, setError] = useState<string | null>(null);
   const [liveEvents, setLiveEvents] = useState<JamLiveEvent[]>([]);
   const [eventCount, setEventCount] = useState(0);
+  const [tempo, setTempo] = useState<JamTempo | null>(null);
   const [level, setLevel] = useState(0);
   const [analyser, setAnalyser] = useState<AnalyserNode | null>(null);
   const [calibrationRestored, setCalibrationRestored] = useState(false);
@@ -185,6 +216,7 @@ export function useJamSession(): JamSession {
     setError(null);
     setLiveEvents([]);
     setEventCount(0);
+    setTempo(null);
     setCalibrationRestored(false);
     bufferRef.current = new JamBuffer(BUFFER_WINDOW_MS);
     keyRef.current = 0;
@@ -261,13 +293,37 @@ export function useJamSession(): JamSession {
           classId?: number;
           conf?: number;
           features?: number[];
+          stage?: number;
+          bpm?: number;
+          phase?: number;
+          lastBeatMs?: number;
         };
+        if (data.type === "tempo") {
+          // Posted after each quantum that emitted events (see the worklet ABI).
+          setTempo({
+            bpm: data.bpm ?? 120,
+            phase: data.phase ?? 0,
+            conf: data.conf ?? 0,
+            lastBeatMs: data.lastBeatMs ?? 0,
+          });
+          return;
+        }
         if (data.type !== "event") return;
         const tMs = data.tMs ?? 0;
         const classId = (data.classId ?? 0) as JamClassId;
         const conf = data.conf ?? 0;
         const features = data.features ?? [];
 
+        if (data.stage === 2) {
+          // Revised: the full window overturned the provisional class of an
+          // onset already on screen. Correct it in place (same key, so the
+          // tile recolors instead of flashing again); it is not a new event.
+          bufferRef.current.revise({ t_ms: tMs, classId, conf });
+          setLiveEvents((prev) =>
+            prev.map((ev) => (ev.tMs === tMs ? { ...ev, classId, conf, features } : ev))
+          );
+          return;
+        }
         bufferRef.current.push({ t_ms: tMs, classId, conf });
         const key = keyRef.current++;
         setLiveEvents((prev) => {
@@ -388,6 +444,10 @@ export function useJamSession(): JamSession {
     nodeRef.current?.port.postMessage({ type: "setCalibration", enabled });
   }, []);
 
+  const setProvisional = useCallback((enabled: boolean) => {
+    nodeRef.current?.port.postMessage({ type: "setProvisional", enabled });
+  }, []);
+
   const resetCalibration = useCallback(() => {
     // Clearing the worklet profile means a just-restored session is no longer
     // "restored" — the user is teaching a fresh profile from scratch.
@@ -400,6 +460,7 @@ export function useJamSession(): JamSession {
     error,
     liveEvents,
     eventCount,
+    tempo,
     level,
     analyser,
     calibrationRestored,
@@ -408,6 +469,7 @@ export function useJamSession(): JamSession {
     capture,
     addCalibrationSample,
     setCalibrationEnabled,
+    setProvisional,
     resetCalibration,
   };
 }
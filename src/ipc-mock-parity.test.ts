@@ -31,6 +31,7 @@ describe("mock <-> bindings parity", () => {
 
     const det = unwrap(await commands.detectEvents({
       file_path: project.input_path, run_id: null, use_calibration: false, calibration_profile_id: null,
+      normalize_sample_rate: true,
     }));
     expect(det.events.length).toBeGreaterThan(0);
 
@@ -77,12 +78,14 @@ describe("mock <-> bindings parity", () => {
     const det = unwrap(await commands.detectEvents({
       file_path: project.input_path, run_id: null,
       use_calibration: true, calibration_profile_id: "mock-cal-1",
+      normalize_sample_rate: true,
     }));
     expect(det.events.length).toBeGreaterThan(0);
     // Flag without id → rejects, same as the Rust command.
     const bad = await commands.detectEvents({
       file_path: project.input_path, run_id: null,
       use_calibration: true, calibration_profile_id: null,
+      normalize_sample_rate: true,
     });
     expect(bad.status).toBe("error");
   });
@@ -1,5 +1,6 @@
 // Zustand store for Beatrice UI state management
 import { create } from 'zustand';
+import type { ChannelStrategy, ConditioningConfig } from '../bindings';
 
 export type Screen = 'input' | 'recording' | 'processing' | 'results';
 
@@ -25,6 +26,10 @@ export interface Run {
   // Tempo phase offset (ms) persisted at creation so replaying a run anchors
   // quantization/chords exactly as the user first heard it.
   phase_offset_ms: number;
+  // How multi-channel input was reduced for event detection.
+  channel_strategy: ChannelStrategy;
+  // Pre-processing chain applied before detection (null = raw signal).
+  conditioning: ConditioningConfig | null;
   status: 'pending' | 'processing' | 'complete' | 'failed';
 }
 
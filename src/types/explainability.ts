@@ -34,6 +34,10 @@ export const EVENT_CLASS_NAMES: Record<EventClass, string> = {
   HihatNoise: 'S/TS (Hi-hat)',
   Click: 'T/K (Snare)',
   HumVoiced: 'Hum (Pad)',
+  OpenHihat: 'TSSS (Open hat)',
+  Rimshot: 'Tk (Rimshot)',
+  Crash: 'KSSH (Crash)',
+  Noise: 'Noise (ignored)',
 };
 
 // Neo-brutalist color scheme for event classes
@@ -42,4 +46,8 @@ export const EVENT_CLASS_COLORS: Record<EventClass, string> = {
   HihatNoise: '#00FFFF',      // Cyan
   Click: '#00FF00',           // Green
   HumVoiced: '#FFFF00',       // Yellow
+  OpenHihat: '#0080FF',       // Azure
+  Rimshot: '#80FF80',         // Mint
+  Crash: '#FF8000',           // Orange
+  Noise: '#808080',           // Grey
 };
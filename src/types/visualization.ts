@@ -13,7 +13,15 @@ export interface DetectedEvent {
   id: string;
   timestamp_ms: number;
   duration_ms: number;
-  class: 'BilabialPlosive' | 'HihatNoise' | 'Click' | 'HumVoiced';
+  class:
+    | 'BilabialPlosive'
+    | 'HihatNoise'
+    | 'Click'
+    | 'HumVoiced'
+    | 'OpenHihat'
+    | 'Rimshot'
+    | 'Crash'
+    | 'Noise';
   confidence: number;
   features: EventFeatures;
 }
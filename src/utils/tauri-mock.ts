@@ -27,6 +27,13 @@ import type {
 // Type-only import (erased at runtime, so no circular dependency with the
 // generated bindings that resolve `invoke` through this very module).
 
+import {
+  analyzeWithWasm,
+  arrangeWithWasm,
+  exportMidiWithWasm,
+  quantizeWithWasm,
+} from './wasmAnalysis';
+
 // Stub classes required by @tauri-apps/plugin-fs when aliased through this mock
 export class Resource {
   readonly rid: number;
@@ -437,6 +444,52 @@ function buildMockArrangement(args: Record<string, any>): unknown {
   };
 }
 
+/**
+ * Sketch of `quantize_events_command` for fabricated events: snaps each to the
+ * nearest sixteenth, returning QuantizedEvent[] matching the Rust struct.
+ */
+function mockQuantize(a: Record<string, any>): unknown[] {
+  const inputEvents = a.input?.events || [];
+  const bpm = a.input?.bpm || 120;
+  const beatMs = 60000 / bpm;
+  const divisionMs = beatMs / 4; // sixteenth note
+  // Anchor the grid to the estimated beat phase (Grid::with_phase). Mirrors the
+  // Rust caller sweep so mock quantization lines up with the real backend.
+  const phaseOffsetMs = a.input?.phase_offset_ms ?? 0;
+
+  return inputEvents.map((event: any) => {
+    const relative = event.timestamp_ms - phaseOffsetMs;
+    const nearestGrid = phaseOffsetMs + Math.max(0, Math.round(relative / divisionMs)) * divisionMs;
+    return {
+      original_event: event,
+      original_timestamp_ms: event.timestamp_ms,
+      quantized_timestamp_ms: nearestGrid,
+      snap_delta_ms: nearestGrid - event.timestamp_ms,
+      grid_position: { bar: 0, beat: 0, subdivision: 0 },
+      // Legacy convenience fields consumed by the mock arranger:
+      event_id: event.id,
+      event,
+    };
+  });
+}
+
+/**
+ * Run a groove/arranger command through the WASM build of beatrice-dsp, the
+ * same code the native command runs. Falls back to `mock` where the build is
+ * unavailable or refuses the input.
+ */
+async function withWasmFallback<T>(
+  what: string,
+  real: () => Promise<T | null>,
+  mock: () => T
+): Promise<T> {
+  const result = await real().catch((err) => {
+    console.warn(`[Tauri Mock] WASM ${what} failed, using the mock`, err);
+    return null;
+  });
+  return result ?? mock();
+}
+
 /**
  * Assert that every required (camelCase) arg key is present, throwing on drift.
  * The generated bindings camelCase multi-word arg keys (`projectId`, `runId`, …),
@@ -505,6 +558,8 @@ const HANDLERS: Record<string, Handler> = {
       quantize_strength: a.input?.quantize_strength || 0.8,
       b_emphasis: a.input?.b_emphasis || 0.6,
       phase_offset_ms: a.input?.phase_offset_ms ?? 0,
+      channel_strategy: a.input?.channel_strategy ?? 'average',
+      conditioning: a.input?.conditioning ?? null,
       status: 'pending',
     };
   },
@@ -541,10 +596,28 @@ const HANDLERS: Record<string, Handler> = {
       // than rethrowing.
       throw { message: 'Calibration profile ID required when use_calibration is true' };
     }
-    // In mock mode, generate events from stored audio data (saved by create_project)
-    const storedData = lastProjectAudioData ? Array.from(lastProjectAudioData) : [];
-    const events = generateMockEvents(storedData);
-    return { events, total_count: events.length };
+    // Run the real pipeline (the WASM build of beatrice-dsp) over the stored
+    // audio (saved by create_project). The mock has no profile store, so
+    // calibration and room-tone ids are not applied. Fall back to fabricated
+    // events where WebAudio or the WASM build is unavailable.
+    return (async () => {
+      const real = lastProjectAudioData
+        ? await analyzeWithWasm(lastProjectAudioData, {
+            channel_strategy: a.input?.channel_strategy ?? null,
+            conditioning: a.input?.conditioning ?? null,
+            classifier: a.input?.classifier ?? null,
+            onset_function: a.input?.onset_function ?? null,
+            multi_label: a.input?.multi_label ?? null,
+          }).catch((err) => {
+            console.warn('[Tauri Mock] WASM analysis failed, using mock events', err);
+            return null;
+          })
+        : null;
+      if (real) return real;
+      const storedData = lastProjectAudioData ? Array.from(lastProjectAudioData) : [];
+      const events = generateMockEvents(storedData);
+      return { events, total_count: events.length };
+    })();
   },
 
   detect_onsets: (a) => {
@@ -573,42 +646,22 @@ const HANDLERS: Record<string, Handler> = {
 
   quantize_events_command: (a) => {
     requireKeys(a, ['input']);
-    // Return QuantizedEvent[] matching the Rust struct
-    const inputEvents = a.input?.events || [];
-    const bpm = a.input?.bpm || 120;
-    const beatMs = 60000 / bpm;
-    const divisionMs = beatMs / 4; // sixteenth note
-    // Anchor the grid to the estimated beat phase (Grid::with_phase). Mirrors the
-    // Rust caller sweep so mock quantization lines up with the real backend.
-    const phaseOffsetMs = a.input?.phase_offset_ms ?? 0;
-
-    return inputEvents.map((event: any) => {
-      const relative = event.timestamp_ms - phaseOffsetMs;
-      const nearestGrid = phaseOffsetMs + Math.max(0, Math.round(relative / divisionMs)) * divisionMs;
-      return {
-        original_event: event,
-        original_timestamp_ms: event.timestamp_ms,
-        quantized_timestamp_ms: nearestGrid,
-        snap_delta_ms: nearestGrid - event.timestamp_ms,
-        grid_position: { bar: 0, beat: 0, subdivision: 0 },
-        // Legacy convenience fields consumed by the mock arranger:
-        event_id: event.id,
-        event,
-      };
-    });
+    return withWasmFallback('quantize', () => quantizeWithWasm(a.input), () => mockQuantize(a));
   },
 
   // --- Arranger (match Rust Arrangement struct) ---
   arrange_events_command: (a) => {
     requireKeys(a, ['input']);
-    return buildMockArrangement(a);
+    return withWasmFallback('arrange', () => arrangeWithWasm(a.input), () => buildMockArrangement(a));
   },
 
   // --- MIDI export ---
   export_midi_command: (a) => {
     requireKeys(a, ['input']);
-    console.log('[Tauri Mock] MIDI export (simulated)');
-    return new Array(100).fill(0);
+    return withWasmFallback('MIDI export', () => exportMidiWithWasm(a.input), () => {
+      console.log('[Tauri Mock] MIDI export (simulated)');
+      return new Array(100).fill(0);
+    });
   },
 
   // --- Themes (match Rust ThemeSummary / Theme structs) ---
@@ -649,6 +702,25 @@ const HANDLERS: Record<string, Handler> = {
 
   delete_calibration_profile: (a) => { requireKeys(a, ['id']); return null; },
 
+  // The mock stores no profiles (get_calibration_profile is always null), so
+  // grading one fails the way the Rust command does for an unknown id.
+  get_calibration_report: (a) => {
+    requireKeys(a, ['id']);
+    throw { message: 'Calibration profile not found' };
+  },
+
+  // --- Noise profiles ---
+  list_noise_profiles: () => [],
+
+  get_noise_profile: (a) => { requireKeys(a, ['id']); return null; },
+
+  create_noise_profile: (a) => {
+    requireKeys(a, ['input']);
+    return { id: 'mock-noise-1', name: a.input?.name || 'Room', created_at: new Date().toISOString(), profile_json_path: 'mock://noise_profile.json', noise_floor_dbfs: -60, notes: a.input?.notes ?? null };
+  },
+
+  delete_noise_profile: (a) => { requireKeys(a, ['id']); return null; },
+
   // --- Recording ---
   start_recording: () => null,
 
@@ -0,0 +1,133 @@
+// wasmAnalysis — the real offline pipeline in the browser demo.
+//
+// The browser mock has no Rust backend, but the beatrice-dsp WASM build
+// exports `analyze_offline_hybrid_json`: the same detect → classify pipeline
+// the native `detect_events` command runs, over a PCM buffer. This module
+// decodes the project's audio (an upload or a jam capture) with WebAudio,
+// hands the interleaved samples to that export and returns its
+// `EventDetectionResult`-shaped JSON.
+//
+// Decoding goes through an OfflineAudioContext at the model rate (44.1 kHz),
+// so the browser's resampler stands in for the native `normalize_sample_rate`
+// step. Where WebAudio or the WASM build is unavailable (tests, old browsers)
+// `analyzeWithWasm` resolves to `null` and the caller falls back to the
+// canned events.
+//
+// The same build quantizes, arranges and exports MIDI from the native
+// commands' inputs (`quantizeWithWasm`, `arrangeWithWasm`,
+// `exportMidiWithWasm`), so the rest of the demo runs the real groove and
+// arranger code too. They resolve to `null` under the same conditions.
+
+/** The rate the factory classifier was fitted at (crate FACTORY_SAMPLE_RATE). */
+const MODEL_SAMPLE_RATE = 44100;
+
+const wasmUrl = new URL(
+  "../../crates/beatrice-dsp/pkg/beatrice_dsp_bg.wasm",
+  import.meta.url
+);
+
+/** The exports of the WASM build this module calls. */
+interface DspPkg {
+  analyze_offline_hybrid_json(
+    samples: Float32Array,
+    sampleRate: number,
+    channels: number,
+    optionsJson: Uint8Array
+  ): Uint8Array;
+  quantize_events_json(inputJson: Uint8Array): Uint8Array;
+  arrange_events_json(inputJson: Uint8Array): Uint8Array;
+  expand_to_song_json(arrangementJson: Uint8Array): Uint8Array;
+  export_midi_json(inputJson: Uint8Array): Uint8Array;
+}
+
+let dsp: Promise<DspPkg | null> | null = null;
+
+/** Load and instantiate the WASM build once; `null` if it cannot load. */
+function loadDsp(): Promise<DspPkg | null> {
+  dsp ??= import("../../crates/beatrice-dsp/pkg/beatrice_dsp")
+    .then(async (pkg) => {
+      await pkg.default({ module_or_path: wasmUrl });
+      return pkg as unknown as DspPkg;
+    })
+    .catch((err) => {
+      console.warn("[wasmAnalysis] WASM pipeline unavailable, using mock results", err);
+      return null;
+    });
+  return dsp;
+}
+
+const toJson = (value: unknown) => new TextEncoder().encode(JSON.stringify(value));
+const fromJson = <T>(bytes: Uint8Array): T => JSON.parse(new TextDecoder().decode(bytes));
+
+/** Options forwarded to the pipeline (the crate's `PcmAnalysisOptions`). */
+export interface WasmAnalysisOptions {
+  channel_strategy?: unknown;
+  conditioning?: unknown;
+  classifier?: string | null;
+  onset_function?: unknown;
+  multi_label?: unknown;
+}
+
+/**
+ * Detect and classify the events in an encoded audio file (any format the
+ * browser decodes). Resolves to the `{ events, total_count }` result, or
+ * `null` when the real pipeline is unavailable here. Rejects with the
+ * pipeline's message when it refuses the audio or options.
+ */
+export async function analyzeWithWasm(
+  audioBytes: Uint8Array,
+  options: WasmAnalysisOptions = {}
+): Promise<{ events: unknown[]; total_count: number } | null> {
+  if (typeof OfflineAudioContext === "undefined" || audioBytes.length === 0) {
+    return null;
+  }
+  const pkg = await loadDsp();
+  if (!pkg) {
+    return null;
+  }
+
+  // decodeAudioData detaches its input, so hand it a copy.
+  const ctx = new OfflineAudioContext(1, 1, MODEL_SAMPLE_RATE);
+  const decoded = await ctx.decodeAudioData(audioBytes.slice().buffer);
+  const channels = decoded.numberOfChannels;
+  const interleaved = new Float32Array(decoded.length * channels);
+  for (let c = 0; c < channels; c++) {
+    const data = decoded.getChannelData(c);
+    for (let i = 0; i < data.length; i++) {
+      interleaved[i * channels + c] = data[i];
+    }
+  }
+
+  const result = pkg.analyze_offline_hybrid_json(interleaved, decoded.sampleRate, channels, toJson(options));
+  return fromJson(result);
+}
+
+/**
+ * Quantize events to a grid. `input` is the `quantize_events_command` input;
+ * resolves to the `QuantizedEvent` array, or `null` when the WASM build is
+ * unavailable. Rejects with the crate's message on an input it refuses.
+ */
+export async function quantizeWithWasm(input: unknown): Promise<unknown[] | null> {
+  const pkg = await loadDsp();
+  return pkg && fromJson(pkg.quantize_events_json(toJson(input)));
+}
+
+/**
+ * Arrange quantized events and expand the result into the full song, as the
+ * native `arrange_events_command` does. `input` is that command's input;
+ * resolves to the `Arrangement`, or `null` when the WASM build is unavailable.
+ */
+export async function arrangeWithWasm(input: unknown): Promise<unknown | null> {
+  const pkg = await loadDsp();
+  return pkg && fromJson(pkg.expand_to_song_json(pkg.arrange_events_json(toJson(input))));
+}
+
+/**
+ * Export an arrangement as Standard MIDI File bytes. `input` is the
+ * `export_midi_command` input; resolves to the bytes as a number array (the
+ * command's `Vec<u8>` over IPC), or `null` when the WASM build is unavailable.
+ */
+export async function exportMidiWithWasm(input: unknown): Promise<number[] | null> {
+  const pkg = await loadDsp();
+  return pkg && Array.from(pkg.export_midi_json(toJson(input)));
+}
@@ -5,35 +5,69 @@
 //
 // OUTBOUND (worklet -> main):
 //   { type: "ready" }                                   after WASM init
-//   { type: "event", t, tMs, classId, conf, features }  per confirmed onset
+//   { type: "event", t, tMs, classId, conf, features, stage }
+//                                                       per confirmed onset
 //     - t        : shared worklet clock (seconds) for scheduling
 //     - tMs      : onset's estimated time relative to STREAM START
-//     - classId  : EventClass id (0=kick, 1=hihat, 2=snare/click, 3=hum)
+//     - classId  : EventClass id (0=kick, 1=hihat, 2=snare/click, 3=hum,
+//                  4=open hat, 5=rimshot, 6=crash, 7=rejected noise)
 //     - conf     : classification confidence [0,1]
 //     - features : [centroid, zcr, low, mid, high, peak, crest, mfcc1..mfcc20]
 //                  — the 7-float EventFeatures vector followed by the window's
 //                  20 mean MFCCs, forwarded so the calibration panel can echo a
 //                  detected event straight back as a labeled sample
 //                  ({type:"calibrate"}) without re-deriving features.
+//     - stage    : 1 = provisional (classified from the onset's first 50ms,
+//                  ~100ms before the full window fills), 2 = revised (the
+//                  full window disagreed: REPLACE the provisional event with
+//                  the same tMs), 0 = final (an onset the stream cut short
+//                  before its provisional window filled). Provisional
+//                  emission is switched on at init, so 0 is rare.
+//   { type: "tempo", bpm, phase, conf, lastBeatMs }     after any quantum (or
+//                                        flush) that emitted events, posted
+//                                        AFTER that quantum's "event" messages
+//     - bpm        : live tempo (120 until the beat tracker locks)
+//     - phase      : fraction of the current beat elapsed at the stream head
+//     - conf       : tracker confidence [0,1]; 0 until locked
+//     - lastBeatMs : most recent beat, on the same STREAM START clock as tMs
 //   { type: "flushed" }                                 after a flush drains,
 //                                        posted AFTER the final "event" messages
 //                                        so the main thread can tear down safely.
+//   { type: "snapshot", bytes }          reply to a snapshot request: the
+//                                        detector state (ArrayBuffer, JSON) to
+//                                        resume from; empty before "ready"
+//   { type: "report", bytes }            reply to a report request: the live
+//                                        profile's CalibrationReport (ArrayBuffer,
+//                                        JSON); empty before "ready"
 //
 // INBOUND (main -> worklet):
-//   { type: "wasm", bytes }              compile+instantiate the detector
+//   { type: "wasm", bytes, snapshot? }   compile+instantiate the detector;
+//                                        with `snapshot` (bytes from a previous
+//                                        "snapshot" reply) resume that stream,
+//                                        starting fresh if it can't be restored
 //   { type: "calibrate", classId, features }
 //                                        add a labeled few-shot sample (Task 5)
 //   { type: "setCalibration", enabled }  flip the FACTORY/YOURS A/B toggle
+//   { type: "setProvisional", enabled }  two-stage emission on/off (off while
+//                                        teaching, so calibration samples carry
+//                                        full-window features)
 //   { type: "resetCalibration" }         drop the live profile before a re-teach
 //                                        (so new samples don't append onto a
 //                                        re-seeded profile — the model reverts
 //                                        to factory until the profile refills)
+//   { type: "snapshot" }                 capture the detector state (pause, move
+//                                        to another worklet, AudioContext
+//                                        restart); replies { type: "snapshot" }
+//   { type: "report" }                   grade the live profile (which class to
+//                                        re-teach, which samples to drop);
+//                                        replies { type: "report" }
 //   { type: "flush" }                    end-of-stream: drain onsets still
 //                                        waiting on their 150ms window, then
 //                                        reply { type: "flushed" }
 //
-// WASM push() ABI: a flat Float32Array of EVENT_STRIDE (30) floats per event —
-//   [tMs, classId, conf, centroid, zcr, low, mid, high, peak, crest, mfcc1..20]
+// WASM push() ABI: a flat Float32Array of EVENT_STRIDE (31) floats per event —
+//   [tMs, classId, conf, centroid, zcr, low, mid, high, peak, crest, mfcc1..20,
+//    stage]
 // JSON-free, no serde on the render thread. Empty means "no event this quantum".
 // EVENT_STRIDE and the feature order MUST match crates/beatrice-dsp/src/lib.rs
 // (WASM_EVENT_STRIDE + WasmDetector::push); bump both in lockstep.
@@ -60,12 +94,15 @@ import "./textcodec-polyfill";
 import { initSync, WasmDetector } from "../../crates/beatrice-dsp/pkg/beatrice_dsp";
 
 /** Floats per event record in the push() ABI (see crate WASM_EVENT_STRIDE):
- * 3 header floats + 7 EventFeatures + 20 MFCCs. */
-const EVENT_STRIDE = 30;
+ * 3 header floats + 7 EventFeatures + 20 MFCCs + the stage. */
+const EVENT_STRIDE = 31;
+/** Offset of the stage float; the features end here. */
+const STAGE = EVENT_STRIDE - 1;
 
 interface WasmMessage {
   type: "wasm";
   bytes: ArrayBuffer;
+  snapshot?: ArrayBuffer;
 }
 interface CalibrateMessage {
   type: "calibrate";
@@ -76,9 +113,19 @@ interface SetCalibrationMessage {
   type: "setCalibration";
   enabled: boolean;
 }
+interface SetProvisionalMessage {
+  type: "setProvisional";
+  enabled: boolean;
+}
 interface ResetCalibrationMessage {
   type: "resetCalibration";
 }
+interface SnapshotMessage {
+  type: "snapshot";
+}
+interface ReportMessage {
+  type: "report";
+}
 interface FlushMessage {
   type: "flush";
 }
@@ -86,7 +133,10 @@ type InboundMessage =
   | WasmMessage
   | CalibrateMessage
   | SetCalibrationMessage
+  | SetProvisionalMessage
   | ResetCalibrationMessage
+  | SnapshotMessage
+  | ReportMessage
   | FlushMessage;
 
 class DetectorProcessor extends AudioWorkletProcessor {
@@ -100,7 +150,17 @@ class DetectorProcessor extends AudioWorkletProcessor {
         // Synchronous compile from the posted bytes. Passing a WebAssembly.Module
         // avoids any async instantiate on the audio thread.
         initSync({ module: new WebAssembly.Module(msg.bytes) });
-        this.det = new WasmDetector(sampleRate);
+        this.det = this.resume(msg.snapshot);
+        if (!this.det) {
+          // Resample to the factory model's 44.1 kHz training rate: the
+          // AudioContext commonly runs at 48 kHz, which skews the classifier's
+          // STFT/MFCC statistics. Event times are unaffected.
+          this.det = WasmDetector.at_model_rate(sampleRate);
+          // Two-stage emission: a provisional class ~100ms sooner than the
+          // full window, corrected by a "revised" event if the full window
+          // disagrees.
+          this.det.set_provisional(true);
+        }
         this.port.postMessage({ type: "ready" });
       } else if (msg.type === "calibrate") {
         // Few-shot: add a labeled sample to the live profile (Task 5).
@@ -113,10 +173,22 @@ class DetectorProcessor extends AudioWorkletProcessor {
       } else if (msg.type === "setCalibration") {
         // A/B toggle: MAP-adapted (personal) vs factory model.
         this.det?.set_calibration_enabled(!!msg.enabled);
+      } else if (msg.type === "setProvisional") {
+        this.det?.set_provisional(!!msg.enabled);
       } else if (msg.type === "resetCalibration") {
         // Re-teach begins: drop any re-seeded profile so fresh samples don't
         // append onto it (reverts to factory until the new profile refills).
         this.det?.clear_calibration();
+      } else if (msg.type === "snapshot") {
+        // Between quanta (message handlers never interleave with process()),
+        // so the state is consistent.
+        const bytes = this.det ? this.det.snapshot() : new Uint8Array(0);
+        this.port.postMessage({ type: "snapshot", bytes: bytes.buffer }, [bytes.buffer]);
+      } else if (msg.type === "report") {
+        // Leave-one-out over the whole profile: a few ms for a teach-sized
+        // one, so ask once a teach completes, not every quantum.
+        const bytes = this.det ? this.det.calibration_report_json() : new Uint8Array(0);
+        this.port.postMessage({ type: "report", bytes: bytes.buffer }, [bytes.buffer]);
       } else if (msg.type === "flush") {
         // End-of-stream: drain onsets still waiting on their 150ms window,
         // then signal completion so the main thread can tear down safely.
@@ -126,7 +198,19 @@ class DetectorProcessor extends AudioWorkletProcessor {
     };
   }
 
-  /** Decode EVENT_STRIDE-float records and post one "event" message each. */
+  /** The detector a snapshot resumes (its settings and stream clock carry
+   * over), or null when there is none or it was taken at another rate. */
+  private resume(snapshot?: ArrayBuffer): WasmDetector | null {
+    if (!snapshot || snapshot.byteLength === 0) return null;
+    try {
+      return WasmDetector.restore(new Uint8Array(snapshot), sampleRate);
+    } catch {
+      return null;
+    }
+  }
+
+  /** Decode EVENT_STRIDE-float records and post one "event" message each,
+   * then the beat tracker's updated tempo if there were any. */
   private postEvents(recs: Float32Array | number[]) {
     for (let i = 0; i + EVENT_STRIDE - 1 < recs.length; i += EVENT_STRIDE) {
       this.port.postMessage({
@@ -135,11 +219,21 @@ class DetectorProcessor extends AudioWorkletProcessor {
         tMs: recs[i],
         classId: recs[i + 1],
         conf: recs[i + 2],
-        // 7 EventFeatures + 20 MFCCs — everything after the 3 header floats,
-        // in ABI order, so a calibration echo-back round-trips losslessly.
-        features: Array.from(
-          (recs as Float32Array).slice(i + 3, i + EVENT_STRIDE)
-        ),
+        // 7 EventFeatures + 20 MFCCs — everything between the 3 header
+        // floats and the stage, in ABI order, so a calibration echo-back
+        // round-trips losslessly.
+        features: Array.from((recs as Float32Array).slice(i + 3, i + STAGE)),
+        stage: recs[i + STAGE],
+      });
+    }
+    if (recs.length > 0 && this.det) {
+      // The tracker only moves when an event lands, so only post then.
+      this.port.postMessage({
+        type: "tempo",
+        bpm: this.det.tempo_bpm(),
+        phase: this.det.beat_phase(),
+        conf: this.det.tempo_confidence(),
+        lastBeatMs: this.det.last_beat_ms(),
       });
     }
   }